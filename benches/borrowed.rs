@@ -0,0 +1,71 @@
+//! Compares the owned and the borrowing readers on a large urlset, run with
+//! `cargo bench --features borrowed`.
+extern crate sitemap;
+
+use sitemap::borrowed::{BorrowedEvent, BorrowedReader};
+use sitemap::reader::{SiteMapEvent, SiteMapReader};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const URLS: usize = 200_000;
+const RUNS: u32 = 5;
+
+fn document() -> String {
+    let mut document = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                                     <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+    for i in 0..URLS {
+        document.push_str(&format!("  <url>\n    <loc>http://www.example.com/page/{}?a=1&amp;b=2</loc>\n    \
+                                    <lastmod>2024-01-{:02}T10:20:30+02:00</lastmod>\n    \
+                                    <changefreq>daily</changefreq>\n    <priority>0.{}</priority>\n  </url>\n",
+                                   i,
+                                   i % 28 + 1,
+                                   i % 10));
+    }
+    document.push_str("</urlset>\n");
+    document
+}
+
+/// Best time of `RUNS` runs of `run`.
+fn measure<F: FnMut() -> usize>(name: &str, mut run: F) {
+    let mut best = Duration::MAX;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        let urls = black_box(run());
+        best = best.min(start.elapsed());
+        assert_eq!(urls, URLS);
+    }
+    println!("{:<22} {:>8.1} ms, {:>6.0} ns/url",
+             name,
+             best.as_secs_f64() * 1e3,
+             best.as_nanos() as f64 / URLS as f64);
+}
+
+fn main() {
+    let document = document();
+    measure("owned entries", || {
+        SiteMapReader::new(document.as_bytes())
+            .filter(|event| matches!(event, Ok(SiteMapEvent::Url(_))))
+            .count()
+    });
+    measure("borrowed text", || {
+        BorrowedReader::new(&document)
+            .filter(|event| matches!(event, Ok(BorrowedEvent::Url(url)) if url.loc.is_some()))
+            .count()
+    });
+    measure("borrowed, parsed url", || {
+        BorrowedReader::new(&document)
+            .filter_map(|event| match event {
+                Ok(BorrowedEvent::Url(url)) => url.loc_url().and_then(Result::ok),
+                _ => None,
+            })
+            .count()
+    });
+    measure("borrowed, to_entry", || {
+        BorrowedReader::new(&document)
+            .filter_map(|event| match event {
+                Ok(BorrowedEvent::Url(url)) => Some(url.to_entry()),
+                _ => None,
+            })
+            .count()
+    });
+}
@@ -0,0 +1,57 @@
+//! Compares the two parsers of `SiteMapReader` on a large urlset, run with
+//! `cargo bench --features fast-xml`.
+//!
+//! Documents declared in another encoding than UTF-8 are still parsed by
+//! xml-rs, the ISO-8859-1 copy of the urlset measures it in the same run.
+extern crate sitemap;
+
+use sitemap::reader::{SiteMapEvent, SiteMapReader};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const URLS: usize = 100_000;
+const RUNS: u32 = 5;
+
+fn document(encoding: &str) -> String {
+    let mut document = format!("<?xml version=\"1.0\" encoding=\"{}\"?>\n\
+                                <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+                               encoding);
+    for i in 0..URLS {
+        document.push_str(&format!("  <url>\n    <loc>http://www.example.com/page/{}?a=1&amp;b=2</loc>\n    \
+                                    <lastmod>2024-01-{:02}T10:20:30+02:00</lastmod>\n    \
+                                    <changefreq>daily</changefreq>\n    <priority>0.{}</priority>\n  </url>\n",
+                                   i,
+                                   i % 28 + 1,
+                                   i % 10));
+    }
+    document.push_str("</urlset>\n");
+    document
+}
+
+/// Best time of `RUNS` runs of `run`.
+fn measure<F: FnMut() -> usize>(name: &str, mut run: F) {
+    let mut best = Duration::MAX;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        let urls = black_box(run());
+        best = best.min(start.elapsed());
+        assert_eq!(urls, URLS);
+    }
+    println!("{:<22} {:>8.1} ms, {:>6.0} ns/url",
+             name,
+             best.as_secs_f64() * 1e3,
+             best.as_nanos() as f64 / URLS as f64);
+}
+
+fn urls(document: &str) -> usize {
+    SiteMapReader::new(document.as_bytes())
+        .filter(|event| matches!(event, Ok(SiteMapEvent::Url(_))))
+        .count()
+}
+
+fn main() {
+    let latin1 = document("ISO-8859-1");
+    let utf8 = document("UTF-8");
+    measure("xml-rs", || urls(&latin1));
+    measure("quick-xml", || urls(&utf8));
+}
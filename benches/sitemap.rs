@@ -0,0 +1,127 @@
+//! Performance budget of the reading and writing of urlsets of 1k, 100k and
+//! 1M urls, run with `cargo bench --bench sitemap --features gzip`; the
+//! numbers are published in the README.
+//!
+//! The documents are declared in UTF-8, parsed with quick-xml when the
+//! `fast-xml` feature is enabled too.
+extern crate sitemap;
+
+use sitemap::reader::{ReaderOptions, SiteMapEvent, SiteMapReader};
+use sitemap::structs::UrlEntry;
+use sitemap::writer::{Indent, SiteMapWriter, WriterOptions};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const SIZES: [usize; 3] = [1_000, 100_000, 1_000_000];
+
+fn document(urls: usize) -> String {
+    let mut document = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                                     <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+    for i in 0..urls {
+        document.push_str(&format!("  <url>\n    <loc>http://www.example.com/page/{}?a=1&amp;b=2</loc>\n    \
+                                    <lastmod>2024-01-{:02}T10:20:30+02:00</lastmod>\n    \
+                                    <changefreq>daily</changefreq>\n    <priority>0.{}</priority>\n  </url>\n",
+                                   i,
+                                   i % 28 + 1,
+                                   i % 10));
+    }
+    document.push_str("</urlset>\n");
+    document
+}
+
+/// Best time of a few runs of `run`, fewer for the larger documents, with
+/// the input returned by `setup` outside of the time.
+fn measure_with<I, S, F>(name: &str, urls: usize, mut setup: S, mut run: F)
+    where S: FnMut() -> I,
+          F: FnMut(I) -> usize
+{
+    let runs = if urls >= 1_000_000 { 2 } else { 5 };
+    let mut best = Duration::MAX;
+    for _ in 0..runs {
+        let input = setup();
+        let start = Instant::now();
+        let count = black_box(run(input));
+        best = best.min(start.elapsed());
+        assert_eq!(count, urls);
+    }
+    println!("{:<16} {:>9} urls {:>10.2} ms, {:>6.0} ns/url",
+             name,
+             urls,
+             best.as_secs_f64() * 1e3,
+             best.as_nanos() as f64 / urls as f64);
+}
+
+fn measure<F: FnMut() -> usize>(name: &str, urls: usize, mut run: F) {
+    measure_with(name, urls, || (), |()| run())
+}
+
+fn parse(document: &[u8], options: ReaderOptions) -> usize {
+    SiteMapReader::with_options(document, options)
+        .filter(|event| matches!(event, Ok(SiteMapEvent::Url(_))))
+        .count()
+}
+
+fn entries(document: &str) -> Vec<UrlEntry> {
+    SiteMapReader::new(document.as_bytes())
+        .filter_map(|event| match event {
+            Ok(SiteMapEvent::Url(url)) => Some(url),
+            _ => None,
+        })
+        .collect()
+}
+
+fn write(entries: Vec<UrlEntry>, indent: Indent) -> usize {
+    let options = WriterOptions {
+        indent,
+        ..WriterOptions::default()
+    };
+    let urls = entries.len();
+    let mut urlset = SiteMapWriter::with_options(Vec::new(), options).start_urlset().unwrap();
+    for url in entries {
+        urlset.url(url).unwrap();
+    }
+    black_box(urlset.end().unwrap());
+    urls
+}
+
+#[cfg(feature = "gzip")]
+fn gzip_round_trip(entries: Vec<UrlEntry>) -> usize {
+    let mut urlset = SiteMapWriter::new_gzip(Vec::new()).start_urlset().unwrap();
+    for url in entries {
+        urlset.url(url).unwrap();
+    }
+    let compressed = urlset.finish().unwrap();
+    SiteMapReader::from_gzip(&compressed[..])
+        .filter(|event| matches!(event, Ok(SiteMapEvent::Url(_))))
+        .count()
+}
+
+/// Count of the urls of `www.example.com` with `/page/` in their path,
+/// through copies of the urls or `Location::url`.
+fn filter(entries: &[UrlEntry], copy: bool) -> usize {
+    let keep = |url: &UrlEntry| if copy {
+        url.loc.get_url().is_some_and(|loc| loc.host_str() == Some("www.example.com") && loc.path().contains("/page/"))
+    } else {
+        url.loc.url().is_some_and(|loc| loc.host_str() == Some("www.example.com") && loc.path().contains("/page/"))
+    };
+    entries.iter().filter(|url| keep(url)).count()
+}
+
+fn main() {
+    let lazy = ReaderOptions {
+        parse_urls: false,
+        ..ReaderOptions::default()
+    };
+    for &urls in SIZES.iter() {
+        let document = document(urls);
+        let entries = entries(&document);
+        measure("parse", urls, || parse(document.as_bytes(), ReaderOptions::default()));
+        measure("parse, lazy urls", urls, || parse(document.as_bytes(), lazy));
+        measure("filter, copies", urls, || filter(&entries, true));
+        measure("filter, borrowed", urls, || filter(&entries, false));
+        measure_with("write compact", urls, || entries.clone(), |entries| write(entries, Indent::None));
+        measure_with("write pretty", urls, || entries.clone(), |entries| write(entries, Indent::default()));
+        #[cfg(feature = "gzip")]
+        measure_with("gzip round trip", urls, || entries.clone(), gzip_round_trip);
+    }
+}
@@ -0,0 +1,53 @@
+//! Counts the urls of a sitemap held in the memory of a WebAssembly module.
+//!
+//! Built for `wasm32-unknown-unknown` with the minimal feature set:
+//!
+//! ```text
+//! cargo build --release --target wasm32-unknown-unknown --no-default-features --example wasm_count_urls
+//! ```
+//!
+//! The host copies the document into a buffer returned by `alloc`, calls
+//! `count_urls` and frees the buffer with `dealloc`.
+extern crate sitemap;
+
+use sitemap::reader::{SiteMapEvent, SiteMapReader};
+use std::slice;
+
+/// Allocates a buffer of `len` bytes for the document.
+#[no_mangle]
+pub extern "C" fn alloc(len: usize) -> *mut u8 {
+    let mut buffer = Vec::with_capacity(len);
+    let ptr = buffer.as_mut_ptr();
+    std::mem::forget(buffer);
+    ptr
+}
+
+/// Frees a buffer returned by `alloc`.
+///
+/// # Safety
+///
+/// `ptr` must have been returned by `alloc` with the same `len`.
+#[no_mangle]
+pub unsafe extern "C" fn dealloc(ptr: *mut u8, len: usize) {
+    drop(Vec::from_raw_parts(ptr, 0, len));
+}
+
+/// Number of valid urls of the document of `len` bytes at `ptr`, reading
+/// stopped at the first error.
+///
+/// # Safety
+///
+/// `ptr` must point to `len` initialized bytes.
+#[no_mangle]
+pub unsafe extern "C" fn count_urls(ptr: *const u8, len: usize) -> u32 {
+    let document = slice::from_raw_parts(ptr, len);
+    let mut count = 0;
+    for event in SiteMapReader::new(document) {
+        match event {
+            Ok(SiteMapEvent::Url(url)) if url.loc.get_url().is_some() => count += 1,
+            Ok(_) => {}
+            Err(_) => break,
+        }
+    }
+    count
+}
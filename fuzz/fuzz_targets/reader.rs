@@ -0,0 +1,33 @@
+//! Reads arbitrary bytes as a sitemap, with the default options and with
+//! every option reading more of the document.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use sitemap::reader::{ReaderOptions, SiteMapEvent, SiteMapReader, TextSiteMapReader};
+
+fuzz_target!(|data: &[u8]| {
+    let lenient = ReaderOptions {
+        skip_leading_garbage: true,
+        case_insensitive_tags: true,
+        trim_whitespace: true,
+        root_events: true,
+        capture_unknown: true,
+        emit_unknown: true,
+        ..ReaderOptions::default()
+    };
+    let strict = ReaderOptions {
+        strict: true,
+        require_namespace: true,
+        ..ReaderOptions::default()
+    };
+    for options in [ReaderOptions::default(), lenient, strict].iter() {
+        for event in SiteMapReader::with_options(data, *options) {
+            if let Ok(SiteMapEvent::Url(url)) = event {
+                let _ = url.errors();
+            }
+        }
+    }
+    if let Ok(reader) = SiteMapReader::from_maybe_gzip(data) {
+        reader.for_each(drop);
+    }
+    TextSiteMapReader::new(data).for_each(drop);
+});
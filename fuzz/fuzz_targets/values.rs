@@ -0,0 +1,23 @@
+//! Parses arbitrary text as the value of each field, and writes the values
+//! read back.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use sitemap::structs::{ChangeFreq, LastMod, Location, Priority, UrlEntry};
+use sitemap::writer::{self, WriterOptions};
+
+fuzz_target!(|data: &[u8]| {
+    let text = String::from_utf8_lossy(data).into_owned();
+    let lastmod = LastMod::from(text.clone());
+    let _ = (lastmod.raw(), lastmod.get_date(), text.parse::<LastMod>());
+    let priority = Priority::from(text.clone());
+    let _ = (priority.raw(), text.parse::<Priority>());
+    let location = Location::from(text.clone());
+    let _ = (location.parsed(), text.parse::<Location>());
+    let _ = ChangeFreq::from(text.clone());
+    let _ = UrlEntry::from_xml_fragment(&text);
+    let mut url = UrlEntry::new();
+    url.loc = Location::from("http://www.example.com/".to_string());
+    url.lastmod = lastmod;
+    url.priority = priority;
+    let _ = writer::urlset_to_string(vec![url], WriterOptions::default());
+});
@@ -0,0 +1,286 @@
+//! Contains the asynchronous sitemap reader, enabled by the `async` feature.
+//!
+//! # Examples
+//!
+//! Reading sitemap from a `tokio::io::AsyncBufRead`.
+//!
+//! ```rust
+//! extern crate sitemap;
+//! extern crate tokio;
+//! use sitemap::async_reader::AsyncSiteMapReader;
+//! use sitemap::reader::SiteMapEvent;
+//! #[tokio::main(flavor = "current_thread")]
+//! async fn main() {
+//!     let document = "<urlset><url><loc>http://www.example.com/</loc></url></urlset>";
+//!     let mut reader = AsyncSiteMapReader::new(document.as_bytes());
+//!     while let Some(event) = reader.next_event().await {
+//!         match event {
+//!             Ok(SiteMapEvent::Url(url_entry)) => println!("url = {:?}", url_entry),
+//!             Ok(SiteMapEvent::SiteMap(sitemap_entry)) => println!("sitemap = {:?}", sitemap_entry),
+//!             Ok(_) => {}
+//!             Err(error) => println!("error = {:?}", error),
+//!         }
+//!     }
+//! }
+//! ```
+use crate::push::PushSiteMapParser;
+use crate::reader::{DocumentKind, ReaderOptions, SiteMapEvent, UrlFilter, Warning};
+use crate::structs;
+use crate::Error;
+use futures_core::Stream;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::AsyncBufRead;
+
+/// A wrapper around a `tokio::io::AsyncBufRead` instance which provides
+/// sitemap parsing, the asynchronous counterpart of `SiteMapReader`.
+///
+/// The document is parsed while it is read, never as a whole: the bytes of
+/// the source are fed to a `PushSiteMapParser` as they are available, so
+/// that the state of the reading is kept in the reader between polls.
+/// Entries are built exactly like in `SiteMapReader`.
+pub struct AsyncSiteMapReader<R> {
+    source: R,
+    /// Parser of the document, `None` once it has ended.
+    parser: Option<PushSiteMapParser>,
+    /// Entries and errors parsed but not returned yet.
+    events: VecDeque<Result<SiteMapEvent, Error>>,
+    /// Whether the source has ended.
+    ended: bool,
+    kind: Option<DocumentKind>,
+    namespaces: Vec<(String, String)>,
+    stylesheets: Vec<structs::Stylesheet>,
+    warnings: Vec<Warning>,
+}
+
+impl<R: AsyncBufRead + Unpin> AsyncSiteMapReader<R> {
+    /// Creates a new reader, consuming the given stream.
+    pub fn new(source: R) -> AsyncSiteMapReader<R> {
+        AsyncSiteMapReader::with_options(source, ReaderOptions::default())
+    }
+
+    /// Creates a new reader with the given options.
+    pub fn with_options(source: R, options: ReaderOptions) -> AsyncSiteMapReader<R> {
+        AsyncSiteMapReader {
+            source,
+            parser: Some(PushSiteMapParser::with_options(options)),
+            events: VecDeque::new(),
+            ended: false,
+            kind: None,
+            namespaces: Vec::new(),
+            stylesheets: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Kind of the document, known once its root element has been read.
+    pub fn document_kind(&self) -> Option<DocumentKind> {
+        match self.parser {
+            Some(ref parser) => parser.document_kind(),
+            None => self.kind,
+        }
+    }
+
+    /// See `SiteMapReader::root_namespaces`.
+    pub fn root_namespaces(&self) -> &[(String, String)] {
+        match self.parser {
+            Some(ref parser) => parser.root_namespaces(),
+            None => &self.namespaces,
+        }
+    }
+
+    /// See `SiteMapReader::stylesheets`.
+    pub fn stylesheets(&self) -> &[structs::Stylesheet] {
+        match self.parser {
+            Some(ref parser) => parser.stylesheets(),
+            None => &self.stylesheets,
+        }
+    }
+
+    /// See `SiteMapReader::warnings`.
+    pub fn warnings(&self) -> &[Warning] {
+        match self.parser {
+            Some(ref parser) => parser.warnings(),
+            None => &self.warnings,
+        }
+    }
+
+    /// See `SiteMapReader::strict`.
+    pub fn strict(mut self, strict: bool) -> AsyncSiteMapReader<R> {
+        self.parser = self.parser.map(|parser| parser.strict(strict));
+        self
+    }
+
+    /// Reads the next entry, `None` at the end of the document or after
+    /// the first malformed XML error.
+    ///
+    /// Dropping the returned future before completion loses no data, the
+    /// next call continues the reading, so that it can be used in `select!`
+    /// loops.
+    pub fn next_event(&mut self) -> NextEvent<'_, R> {
+        NextEvent { reader: self }
+    }
+
+    /// Polls the next entry, like `next_event`.
+    fn poll_next_event(&mut self, cx: &mut Context) -> Poll<Option<Result<SiteMapEvent, Error>>> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Poll::Ready(Some(event));
+            }
+            if self.parser.is_none() {
+                return Poll::Ready(None);
+            }
+            if self.ended {
+                self.finish();
+                continue;
+            }
+            let read = match Pin::new(&mut self.source).poll_fill_buf(cx) {
+                Poll::Ready(Ok(available)) => {
+                    let parser = self.parser.as_mut().unwrap();
+                    let events = parser.feed(available);
+                    self.ended = available.is_empty();
+                    let read = available.len();
+                    push_events(&mut self.events, events);
+                    read
+                }
+                Poll::Ready(Err(error)) => {
+                    self.events.push_back(Err(Error::Io(error)));
+                    self.close();
+                    continue;
+                }
+                Poll::Pending => return Poll::Pending,
+            };
+            Pin::new(&mut self.source).consume(read);
+        }
+    }
+
+    /// Parses the end of the document, once the source has ended.
+    fn finish(&mut self) {
+        // the empty chunks return the errors kept by the parser for the
+        // next call, with the entries after them
+        loop {
+            let events = self.parser.as_mut().unwrap().feed(&[]);
+            if matches!(events, Ok(ref events) if events.is_empty()) {
+                break;
+            }
+            push_events(&mut self.events, events);
+        }
+        let parser = self.close();
+        push_events(&mut self.events, parser.finish());
+    }
+
+    /// Ends the document, keeping what is known about it.
+    fn close(&mut self) -> PushSiteMapParser {
+        let parser = self.parser.take().unwrap();
+        self.kind = parser.document_kind();
+        self.namespaces = parser.root_namespaces().to_vec();
+        self.stylesheets = parser.stylesheets().to_vec();
+        self.warnings = parser.warnings().to_vec();
+        parser
+    }
+
+    /// Stream of the entries, dropping a pending `next()` of the stream
+    /// loses no data.
+    pub fn into_stream<'a>(self) -> SiteMapStream<'a>
+        where R: Send + 'a
+    {
+        SiteMapStream { inner: Box::new(self) }
+    }
+
+    /// Stream of the urls of a `<urlset>`, it ends with an error when the
+    /// document is a `<sitemapindex>`. Like `into_stream` it loses no data
+    /// when a pending `next()` is dropped.
+    pub fn into_url_stream<'a>(self) -> UrlStream<'a>
+        where R: Send + 'a
+    {
+        let reader = UrlReader {
+            reader: self,
+            filter: UrlFilter::default(),
+        };
+        UrlStream { inner: Box::new(reader) }
+    }
+}
+
+/// Queues the result of feeding a chunk to a `PushSiteMapParser`.
+fn push_events(queue: &mut VecDeque<Result<SiteMapEvent, Error>>,
+               events: Result<Vec<SiteMapEvent>, Error>) {
+    match events {
+        Ok(events) => queue.extend(events.into_iter().map(Ok)),
+        Err(error) => queue.push_back(Err(error)),
+    }
+}
+
+/// Future of the next entry of an `AsyncSiteMapReader`, returned by
+/// `AsyncSiteMapReader::next_event`.
+pub struct NextEvent<'a, R> {
+    reader: &'a mut AsyncSiteMapReader<R>,
+}
+
+impl<'a, R: AsyncBufRead + Unpin> Future for NextEvent<'a, R> {
+    type Output = Option<Result<SiteMapEvent, Error>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        self.reader.poll_next_event(cx)
+    }
+}
+
+/// Object safe access to the reader of a stream.
+trait PollItem<I>: Send {
+    fn poll_item(&mut self, cx: &mut Context) -> Poll<Option<I>>;
+}
+
+impl<R: AsyncBufRead + Unpin + Send> PollItem<Result<SiteMapEvent, Error>> for AsyncSiteMapReader<R> {
+    fn poll_item(&mut self, cx: &mut Context) -> Poll<Option<Result<SiteMapEvent, Error>>> {
+        self.poll_next_event(cx)
+    }
+}
+
+/// Reader of the urls of an `UrlStream`.
+struct UrlReader<R> {
+    reader: AsyncSiteMapReader<R>,
+    filter: UrlFilter,
+}
+
+impl<R: AsyncBufRead + Unpin + Send> PollItem<Result<structs::UrlEntry, Error>> for UrlReader<R> {
+    fn poll_item(&mut self, cx: &mut Context) -> Poll<Option<Result<structs::UrlEntry, Error>>> {
+        while !self.filter.finished() {
+            let event = match self.reader.poll_next_event(cx) {
+                Poll::Ready(event) => event,
+                Poll::Pending => return Poll::Pending,
+            };
+            if let Some(url) = self.filter.url(event, self.reader.document_kind()) {
+                return Poll::Ready(url);
+            }
+        }
+        Poll::Ready(None)
+    }
+}
+
+/// Stream of the entries of an `AsyncSiteMapReader`.
+pub struct SiteMapStream<'a> {
+    inner: Box<dyn PollItem<Result<SiteMapEvent, Error>> + 'a>,
+}
+
+impl<'a> Stream for SiteMapStream<'a> {
+    type Item = Result<SiteMapEvent, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        self.inner.poll_item(cx)
+    }
+}
+
+/// Stream of the urls of an `AsyncSiteMapReader`, returned by
+/// `AsyncSiteMapReader::into_url_stream`.
+pub struct UrlStream<'a> {
+    inner: Box<dyn PollItem<Result<structs::UrlEntry, Error>> + 'a>,
+}
+
+impl<'a> Stream for UrlStream<'a> {
+    type Item = Result<structs::UrlEntry, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        self.inner.poll_item(cx)
+    }
+}
@@ -0,0 +1,183 @@
+//! Contains the asynchronous sitemap writers, enabled by the `async` feature.
+//!
+//! The documents are exactly the ones written by the `writer` module, the
+//! output is buffered and written to the stream in blocks.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate sitemap;
+//! extern crate tokio;
+//! use sitemap::async_writer::AsyncUrlSetWriter;
+//! use sitemap::structs::UrlEntry;
+//! #[tokio::main(flavor = "current_thread")]
+//! async fn main() {
+//!     let mut writer = AsyncUrlSetWriter::new(Vec::new()).unwrap();
+//!     let url = UrlEntry::builder().loc("http://www.example.com/").build().unwrap();
+//!     writer.url(url).await.unwrap();
+//!     let output = writer.end().await.unwrap();
+//!     assert!(String::from_utf8(output).unwrap().contains("<loc>http://www.example.com/</loc>"));
+//! }
+//! ```
+use crate::structs::{SiteMapEntry, UrlEntry};
+use crate::writer::{LimitPolicy, SiteMapIndexWriter, SiteMapWriter, UrlSetWriter, WriterOptions};
+use crate::Error;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use url::Url;
+
+/// Size of the output written to the stream at once.
+const BUFFER_SIZE: usize = 8192;
+
+/// Writes the buffer to `out` once it is full, or always with `all`.
+async fn write_buffer<W: AsyncWrite + Unpin>(buffer: &mut Vec<u8>,
+                                             out: &mut W,
+                                             all: bool)
+                                             -> Result<(), Error> {
+    if all || buffer.len() >= BUFFER_SIZE {
+        out.write_all(buffer).await?;
+        buffer.clear();
+    }
+    Ok(())
+}
+
+/// Asynchronous writer of the `<urlset>` element.
+pub struct AsyncUrlSetWriter<W> {
+    writer: UrlSetWriter<Vec<u8>>,
+    out: W,
+    /// Whether writing to the stream failed, leaving a url incomplete.
+    failed: bool,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncUrlSetWriter<W> {
+    /// Creates a new writer, the output is buffered until the first block
+    /// is full.
+    pub fn new(out: W) -> Result<AsyncUrlSetWriter<W>, Error> {
+        AsyncUrlSetWriter::with_options(out, WriterOptions::default())
+    }
+
+    /// Creates a new writer with the given options.
+    pub fn with_options(out: W, options: WriterOptions) -> Result<AsyncUrlSetWriter<W>, Error> {
+        let buffer = Vec::with_capacity(BUFFER_SIZE);
+        let writer = SiteMapWriter::with_options(buffer, options).start_urlset()?;
+        Ok(AsyncUrlSetWriter { writer, out, failed: false })
+    }
+
+    /// See `UrlSetWriter::limit_policy`.
+    pub fn limit_policy(mut self, limit_policy: LimitPolicy) -> AsyncUrlSetWriter<W> {
+        self.writer = self.writer.limit_policy(limit_policy);
+        self
+    }
+
+    /// See `UrlSetWriter::base_url`.
+    pub fn base_url(mut self, base_url: Url) -> AsyncUrlSetWriter<W> {
+        self.writer = self.writer.base_url(base_url);
+        self
+    }
+
+    /// See `UrlSetWriter::max_urls`.
+    pub fn max_urls(mut self, max_urls: usize) -> AsyncUrlSetWriter<W> {
+        self.writer = self.writer.max_urls(max_urls);
+        self
+    }
+
+    /// See `UrlSetWriter::max_bytes`.
+    pub fn max_bytes(mut self, max_bytes: u64) -> AsyncUrlSetWriter<W> {
+        self.writer = self.writer.max_bytes(max_bytes);
+        self
+    }
+
+    /// Limits exceeded so far with `LimitPolicy::Warn`.
+    pub fn warnings(&self) -> &[Error] {
+        self.writer.warnings()
+    }
+
+    /// Writes a url, see `UrlSetWriter::url`. Once writing to the stream
+    /// failed, the next urls and `end` fail with `Error::State`.
+    pub async fn url(&mut self, url: UrlEntry) -> Result<(), Error> {
+        if self.failed {
+            return Err(Error::State("url() called after a failed write"));
+        }
+        self.writer.url(url)?;
+        let result = write_buffer(self.writer.get_mut(), &mut self.out, false).await;
+        self.failed = result.is_err();
+        result
+    }
+
+    /// Writes a comment, see `UrlSetWriter::comment`, buffered with the next
+    /// entries.
+    pub fn comment(&mut self, text: &str) -> Result<(), Error> {
+        if self.failed {
+            return Err(Error::State("comment() called after a failed write"));
+        }
+        self.writer.comment(text)
+    }
+
+    /// Closes the urlset, flushes the stream and returns it.
+    pub async fn end(self) -> Result<W, Error> {
+        if self.failed {
+            return Err(Error::State("end() called after a failed write"));
+        }
+        let mut buffer = self.writer.end()?;
+        let mut out = self.out;
+        write_buffer(&mut buffer, &mut out, true).await?;
+        out.flush().await?;
+        Ok(out)
+    }
+}
+
+/// Asynchronous writer of the `<sitemapindex>` element.
+pub struct AsyncSiteMapIndexWriter<W> {
+    writer: SiteMapIndexWriter<Vec<u8>>,
+    out: W,
+    /// Whether writing to the stream failed, leaving a sitemap incomplete.
+    failed: bool,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncSiteMapIndexWriter<W> {
+    /// Creates a new writer, the output is buffered until the first block
+    /// is full.
+    pub fn new(out: W) -> Result<AsyncSiteMapIndexWriter<W>, Error> {
+        AsyncSiteMapIndexWriter::with_options(out, WriterOptions::default())
+    }
+
+    /// Creates a new writer with the given options.
+    pub fn with_options(out: W, options: WriterOptions) -> Result<AsyncSiteMapIndexWriter<W>, Error> {
+        let buffer = Vec::with_capacity(BUFFER_SIZE);
+        let writer = SiteMapWriter::with_options(buffer, options).start_sitemapindex()?;
+        Ok(AsyncSiteMapIndexWriter { writer, out, failed: false })
+    }
+
+    /// Writes a sitemap, see `SiteMapIndexWriter::sitemap`. Once writing to
+    /// the stream failed, the next sitemaps and `end` fail with
+    /// `Error::State`.
+    pub async fn sitemap(&mut self, sitemap: SiteMapEntry) -> Result<(), Error> {
+        if self.failed {
+            return Err(Error::State("sitemap() called after a failed write"));
+        }
+        self.writer.sitemap(sitemap)?;
+        let result = write_buffer(self.writer.get_mut(), &mut self.out, false).await;
+        self.failed = result.is_err();
+        result
+    }
+
+    /// Writes a comment, see `SiteMapIndexWriter::comment`, buffered with the next
+    /// entries.
+    pub fn comment(&mut self, text: &str) -> Result<(), Error> {
+        if self.failed {
+            return Err(Error::State("comment() called after a failed write"));
+        }
+        self.writer.comment(text)
+    }
+
+    /// Closes the sitemapindex, flushes the stream and returns it.
+    pub async fn end(self) -> Result<W, Error> {
+        if self.failed {
+            return Err(Error::State("end() called after a failed write"));
+        }
+        let mut buffer = self.writer.end()?;
+        let mut out = self.out;
+        write_buffer(&mut buffer, &mut out, true).await?;
+        out.flush().await?;
+        Ok(out)
+    }
+}
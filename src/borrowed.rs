@@ -0,0 +1,353 @@
+//! Contains the borrowing sitemap reader, enabled by the `borrowed` feature.
+//!
+//! `BorrowedReader` reads a document held in memory and returns the text of
+//! the `<loc>`, `<lastmod>`, `<changefreq>` and `<priority>` elements as
+//! slices of the document, allocating only for text with references or
+//! split by comments. Values are parsed on demand, with `loc_url` and
+//! `lastmod_value`, or converted to the owned entries of `SiteMapReader`
+//! with `to_entry`. Extension elements are skipped.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate sitemap;
+//! use sitemap::borrowed::{BorrowedEvent, BorrowedReader};
+//! fn main() {
+//!     let document = "<urlset><url><loc>http://www.example.com/</loc></url></urlset>";
+//!     for event in BorrowedReader::new(document) {
+//!         match event {
+//!             Ok(BorrowedEvent::Url(url)) => println!("url = {:?}", url.loc),
+//!             Ok(BorrowedEvent::SiteMap(sitemap)) => println!("sitemap = {:?}", sitemap.loc),
+//!             Err(error) => println!("error = {:?}", error),
+//!         }
+//!     }
+//! }
+//! ```
+use crate::structs::{self, LastMod, Location, SiteMapEntry, UrlEntry, SITEMAP_NAMESPACE};
+use crate::Error;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::Event;
+use quick_xml::name::ResolveResult;
+use quick_xml::NsReader;
+use std::borrow::Cow;
+use std::iter::FusedIterator;
+use std::str;
+use url::Url;
+
+/// Url entry with the text of its elements borrowed from the document.
+#[derive(Debug,Clone,Default,PartialEq)]
+pub struct BorrowedUrl<'a> {
+    /// Text of `<loc>`, trimmed.
+    pub loc: Option<Cow<'a, str>>,
+    /// Text of `<lastmod>`, trimmed.
+    pub lastmod: Option<Cow<'a, str>>,
+    /// Text of `<changefreq>`, trimmed.
+    pub changefreq: Option<Cow<'a, str>>,
+    /// Text of `<priority>`, trimmed.
+    pub priority: Option<Cow<'a, str>>,
+}
+
+impl<'a> BorrowedUrl<'a> {
+    /// Parses the location.
+    pub fn loc_url(&self) -> Option<Result<Url, url::ParseError>> {
+        self.loc.as_deref().map(Url::parse)
+    }
+    /// Parses the modification time.
+    pub fn lastmod_value(&self) -> Option<Result<LastMod, Error>> {
+        self.lastmod.as_deref().map(str::parse)
+    }
+    /// Entry with the parsed values, like the one of `SiteMapReader`
+    /// without extensions.
+    pub fn to_entry(&self) -> UrlEntry {
+        let mut entry = UrlEntry::new();
+        entry.loc = location(&self.loc);
+        if let Some(ref lastmod) = self.lastmod {
+            entry.lastmod = LastMod::from(lastmod.to_string());
+        }
+        if let Some(ref changefreq) = self.changefreq {
+            entry.changefreq = changefreq.to_string().into();
+        }
+        if let Some(ref priority) = self.priority {
+            entry.priority = priority.to_string().into();
+        }
+        entry
+    }
+}
+
+/// Sitemap entry with the text of its elements borrowed from the document.
+#[derive(Debug,Clone,Default,PartialEq)]
+pub struct BorrowedSiteMap<'a> {
+    /// Text of `<loc>`, trimmed.
+    pub loc: Option<Cow<'a, str>>,
+    /// Text of `<lastmod>`, trimmed.
+    pub lastmod: Option<Cow<'a, str>>,
+}
+
+impl<'a> BorrowedSiteMap<'a> {
+    /// Parses the location.
+    pub fn loc_url(&self) -> Option<Result<Url, url::ParseError>> {
+        self.loc.as_deref().map(Url::parse)
+    }
+    /// Parses the modification time.
+    pub fn lastmod_value(&self) -> Option<Result<LastMod, Error>> {
+        self.lastmod.as_deref().map(str::parse)
+    }
+    /// Entry with the parsed values, like the one of `SiteMapReader`.
+    pub fn to_entry(&self) -> SiteMapEntry {
+        let mut entry = SiteMapEntry::new();
+        entry.loc = location(&self.loc);
+        if let Some(ref lastmod) = self.lastmod {
+            entry.lastmod = LastMod::from(lastmod.to_string());
+        }
+        entry
+    }
+}
+
+fn location(loc: &Option<Cow<str>>) -> Location {
+    match *loc {
+        Some(ref loc) => Location::from(loc.to_string()),
+        None => Location::None,
+    }
+}
+
+/// Entry read by `BorrowedReader`.
+#[derive(Debug,Clone,PartialEq)]
+pub enum BorrowedEvent<'a> {
+    /// Url entry.
+    Url(BorrowedUrl<'a>),
+    /// Sitemap entry.
+    SiteMap(BorrowedSiteMap<'a>),
+}
+
+/// Element of an entry whose text is kept.
+#[derive(Debug,Clone,Copy,PartialEq)]
+enum Field {
+    Loc,
+    LastMod,
+    ChangeFreq,
+    Priority,
+}
+
+/// Entry being read.
+enum Current<'a> {
+    Url(BorrowedUrl<'a>),
+    SiteMap(BorrowedSiteMap<'a>),
+}
+
+/// Reader of the url and sitemap entries of a document held in memory,
+/// borrowing their text from it.
+///
+/// The document must be well-formed, the reader ends after the first
+/// error. Entries are the `<url>` and `<sitemap>` children of the root
+/// element and fields their children, in the sitemap namespace or without
+/// namespace.
+pub struct BorrowedReader<'a> {
+    document: &'a str,
+    parser: NsReader<&'a [u8]>,
+    depth: usize,
+    current: Option<Current<'a>>,
+    field: Option<Field>,
+    text: Option<Cow<'a, str>>,
+    finished: bool,
+}
+
+impl<'a> BorrowedReader<'a> {
+    /// Creates a new reader of `document`.
+    pub fn new(document: &'a str) -> BorrowedReader<'a> {
+        BorrowedReader {
+            document,
+            parser: NsReader::from_str(document),
+            depth: 0,
+            current: None,
+            field: None,
+            text: None,
+            finished: false,
+        }
+    }
+
+    /// Position of the byte at `offset` of the document.
+    fn offset_position(&self, offset: u64) -> structs::Position {
+        let before = &self.document.as_bytes()[..(offset as usize).min(self.document.len())];
+        let line_start = before.iter().rposition(|&byte| byte == b'\n').map_or(0, |newline| newline + 1);
+        structs::Position {
+            line: before.iter().filter(|&&byte| byte == b'\n').count() as u64 + 1,
+            column: (before.len() - line_start) as u64 + 1,
+        }
+    }
+
+    fn parse_error<M: Into<String>>(&mut self, offset: u64, message: M) -> Error {
+        self.finished = true;
+        let position = self.offset_position(offset);
+        Error::Parse {
+            line: position.line,
+            column: position.column,
+            message: message.into(),
+        }
+    }
+
+    /// Appends `text` to the text of the current field, borrowing it while
+    /// it is the only part.
+    fn push_text(&mut self, text: Cow<'a, str>) {
+        if self.field.is_none() {
+            return;
+        }
+        self.text = match self.text.take() {
+            None => Some(text),
+            Some(previous) => Some(Cow::Owned(previous.into_owned() + &text)),
+        };
+    }
+
+    /// Field of the element `local_name`, child of the current entry.
+    fn field(&self, sitemap_namespace: bool, local_name: &[u8]) -> Option<Field> {
+        if self.depth != 2 || !sitemap_namespace {
+            return None;
+        }
+        match (&self.current, local_name) {
+            (Some(_), b"loc") => Some(Field::Loc),
+            (Some(_), b"lastmod") => Some(Field::LastMod),
+            (Some(Current::Url(_)), b"changefreq") => Some(Field::ChangeFreq),
+            (Some(Current::Url(_)), b"priority") => Some(Field::Priority),
+            _ => None,
+        }
+    }
+
+    /// Handles the start of an element, `sitemap_namespace` when it is in
+    /// the sitemap namespace or without namespace, `empty` for `<element/>`.
+    fn start(&mut self, sitemap_namespace: bool, local_name: &[u8], empty: bool) -> Option<BorrowedEvent<'a>> {
+        if self.depth == 1 && sitemap_namespace {
+            self.current = match local_name {
+                b"url" => Some(Current::Url(BorrowedUrl::default())),
+                b"sitemap" => Some(Current::SiteMap(BorrowedSiteMap::default())),
+                _ => None,
+            };
+        } else if let Some(field) = self.field(sitemap_namespace, local_name) {
+            self.field = Some(field);
+            self.text = None;
+        }
+        self.depth += 1;
+        if empty {
+            self.end()
+        } else {
+            None
+        }
+    }
+
+    /// Handles the end of an element, returning the entry it closes.
+    fn end(&mut self) -> Option<BorrowedEvent<'a>> {
+        self.depth = self.depth.saturating_sub(1);
+        match self.depth {
+            1 => match self.current.take() {
+                Some(Current::Url(url)) => Some(BorrowedEvent::Url(url)),
+                Some(Current::SiteMap(sitemap)) => Some(BorrowedEvent::SiteMap(sitemap)),
+                None => None,
+            },
+            2 => {
+                let field = self.field.take()?;
+                let text = trim(self.text.take().unwrap_or(Cow::Borrowed("")));
+                match (self.current.as_mut(), field) {
+                    (Some(Current::Url(url)), Field::Loc) => url.loc = Some(text),
+                    (Some(Current::Url(url)), Field::LastMod) => url.lastmod = Some(text),
+                    (Some(Current::Url(url)), Field::ChangeFreq) => url.changefreq = Some(text),
+                    (Some(Current::Url(url)), Field::Priority) => url.priority = Some(text),
+                    (Some(Current::SiteMap(sitemap)), Field::Loc) => sitemap.loc = Some(text),
+                    (Some(Current::SiteMap(sitemap)), Field::LastMod) => sitemap.lastmod = Some(text),
+                    _ => {}
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+fn is_sitemap_namespace(namespace: &ResolveResult) -> bool {
+    match *namespace {
+        ResolveResult::Unbound => true,
+        ResolveResult::Bound(ref namespace) => namespace.as_ref() == SITEMAP_NAMESPACE.as_bytes(),
+        ResolveResult::Unknown(_) => false,
+    }
+}
+
+/// `text` without leading and trailing whitespace, still borrowed if it was.
+fn trim(text: Cow<str>) -> Cow<str> {
+    match text {
+        Cow::Borrowed(text) => Cow::Borrowed(text.trim()),
+        Cow::Owned(text) if text.trim().len() == text.len() => Cow::Owned(text),
+        Cow::Owned(text) => Cow::Owned(text.trim().to_string()),
+    }
+}
+
+/// Text of `bytes`, still borrowed if they were.
+fn borrowed_str(bytes: Cow<[u8]>) -> Result<Cow<str>, str::Utf8Error> {
+    match bytes {
+        Cow::Borrowed(bytes) => str::from_utf8(bytes).map(Cow::Borrowed),
+        Cow::Owned(bytes) => String::from_utf8(bytes).map(Cow::Owned).map_err(|error| error.utf8_error()),
+    }
+}
+
+impl<'a> Iterator for BorrowedReader<'a> {
+    type Item = Result<BorrowedEvent<'a>, Error>;
+    fn next(&mut self) -> Option<Result<BorrowedEvent<'a>, Error>> {
+        while !self.finished {
+            let offset = self.parser.buffer_position();
+            let event = match self.parser.read_resolved_event() {
+                Ok((namespace, Event::Start(start))) => {
+                    let sitemap_namespace = is_sitemap_namespace(&namespace);
+                    self.start(sitemap_namespace, start.local_name().as_ref(), false)
+                }
+                Ok((namespace, Event::Empty(start))) => {
+                    let sitemap_namespace = is_sitemap_namespace(&namespace);
+                    self.start(sitemap_namespace, start.local_name().as_ref(), true)
+                }
+                Ok((_, Event::End(_))) => self.end(),
+                Ok((_, Event::Text(text))) => {
+                    match borrowed_str(text.into_inner()) {
+                        Ok(text) => self.push_text(text),
+                        Err(error) => return Some(Err(self.parse_error(offset, error.to_string()))),
+                    }
+                    None
+                }
+                Ok((_, Event::CData(data))) => {
+                    match borrowed_str(data.into_inner()) {
+                        Ok(data) => self.push_text(data),
+                        Err(error) => return Some(Err(self.parse_error(offset, error.to_string()))),
+                    }
+                    None
+                }
+                Ok((_, Event::GeneralRef(reference))) => {
+                    let text = match reference.resolve_char_ref() {
+                        Ok(Some(character)) => character.to_string(),
+                        Ok(None) => {
+                            let name = String::from_utf8_lossy(&reference).into_owned();
+                            match resolve_predefined_entity(&name) {
+                                Some(text) => text.to_string(),
+                                None => {
+                                    let message = format!("unknown entity '{}'", name);
+                                    return Some(Err(self.parse_error(offset, message)));
+                                }
+                            }
+                        }
+                        Err(error) => return Some(Err(self.parse_error(offset, error.to_string()))),
+                    };
+                    self.push_text(Cow::Owned(text));
+                    None
+                }
+                Ok((_, Event::Eof)) => {
+                    self.finished = true;
+                    None
+                }
+                Ok(_) => None,
+                Err(error) => {
+                    let offset = self.parser.error_position();
+                    return Some(Err(self.parse_error(offset, error.to_string())));
+                }
+            };
+            if let Some(event) = event {
+                return Some(Ok(event));
+            }
+        }
+        None
+    }
+}
+
+impl<'a> FusedIterator for BorrowedReader<'a> {}
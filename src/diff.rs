@@ -0,0 +1,197 @@
+//! Contains the comparison of two versions of a sitemap.
+//!
+//! Entries are matched by their normalized location, entries of the new
+//! version without a match are added, entries of the old version without a
+//! match are removed, matching entries with a different `lastmod`,
+//! `changefreq` or `priority` are changed.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate sitemap;
+//! use sitemap::diff::{self, DiffOptions, FieldChange};
+//! use sitemap::structs::UrlEntry;
+//! fn main() {
+//!     let old = vec![
+//!         UrlEntry::builder().loc("http://www.example.com/").priority(0.5).build().unwrap(),
+//!         UrlEntry::builder().loc("http://www.example.com/old").build().unwrap(),
+//!     ];
+//!     let new = vec![
+//!         UrlEntry::builder().loc("http://www.example.com").priority(0.8).build().unwrap(),
+//!         UrlEntry::builder().loc("http://www.example.com/new").build().unwrap(),
+//!     ];
+//!     let diff = diff::diff(&old, &new, &DiffOptions::default());
+//!     assert_eq!(diff.added[0].loc, new[1].loc);
+//!     assert_eq!(diff.removed[0].loc, old[1].loc);
+//!     match diff.changed[0].fields[0] {
+//!         FieldChange::Priority { ref new, .. } => assert_eq!(new.get_priority(), Some(0.8)),
+//!         _ => unreachable!(),
+//!     }
+//! }
+//! ```
+use crate::reader::SiteMapReader;
+use crate::structs::{CanonicalizeOptions, ChangeFreq, LastMod, Location, Priority, UrlEntry};
+use crate::util::{self, LocOptions};
+use crate::Error;
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::io::Read;
+
+/// Options of the comparison.
+#[derive(Debug,Clone,Copy,Default,PartialEq)]
+pub struct DiffOptions {
+    /// Canonicalization of the locations before comparing them.
+    pub canonicalize: CanonicalizeOptions,
+    /// Comparison of the canonical locations.
+    pub loc: LocOptions,
+}
+
+/// Change of a field of an entry.
+#[derive(Debug,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum FieldChange {
+    /// Different `lastmod`, values with a different precision but the same
+    /// time are the same.
+    LastMod {
+        /// Value of the old entry.
+        old: LastMod,
+        /// Value of the new entry.
+        new: LastMod,
+    },
+    /// Different `changefreq`.
+    ChangeFreq {
+        /// Value of the old entry.
+        old: ChangeFreq,
+        /// Value of the new entry.
+        new: ChangeFreq,
+    },
+    /// Different `priority`.
+    Priority {
+        /// Value of the old entry.
+        old: Priority,
+        /// Value of the new entry.
+        new: Priority,
+    },
+}
+
+/// Entry in both versions with different fields.
+#[derive(Debug,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct EntryChange {
+    /// Location of the new entry.
+    pub loc: Location,
+    /// Changed fields, in the order `lastmod`, `changefreq`, `priority`.
+    pub fields: Vec<FieldChange>,
+}
+
+/// Differences between two versions of a sitemap.
+#[derive(Debug,Clone,Default,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct SiteMapDiff {
+    /// Entries of the new version only, in their order.
+    pub added: Vec<UrlEntry>,
+    /// Entries of the old version only, in their order.
+    pub removed: Vec<UrlEntry>,
+    /// Entries of both versions with different fields, in the order of the
+    /// new version.
+    pub changed: Vec<EntryChange>,
+}
+
+impl SiteMapDiff {
+    /// Whether the versions have the same entries.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compares the entries of two versions of a sitemap.
+///
+/// Only the first entry of each location is compared, later ones are
+/// ignored.
+pub fn diff(old: &[UrlEntry], new: &[UrlEntry], options: &DiffOptions) -> SiteMapDiff {
+    let old = old.iter().cloned().map(Ok::<UrlEntry, Infallible>);
+    let new = new.iter().cloned().map(Ok);
+    match diff_entries(old, new, options) {
+        Ok(diff) => diff,
+        Err(error) => match error {},
+    }
+}
+
+/// Compares the urls of two sitemap documents, see `diff`.
+///
+/// The old entries are kept in memory, the new entries are compared while
+/// they are read. The first error of either reader ends the comparison.
+pub fn diff_readers<A, B>(old: SiteMapReader<A>,
+                          new: SiteMapReader<B>,
+                          options: &DiffOptions)
+                          -> Result<SiteMapDiff, Error>
+    where A: Read,
+          B: Read
+{
+    diff_entries(old.into_url_iter(), new.into_url_iter(), options)
+}
+
+fn diff_entries<E, I, J>(old: I, new: J, options: &DiffOptions) -> Result<SiteMapDiff, E>
+    where I: Iterator<Item = Result<UrlEntry, E>>,
+          J: Iterator<Item = Result<UrlEntry, E>>
+{
+    let key = |loc: &Location| util::loc_key(&loc.canonicalize(&options.canonicalize), &options.loc);
+    let mut old_entries = HashMap::new();
+    for (index, entry) in old.enumerate() {
+        let entry = entry?;
+        old_entries.entry(key(&entry.loc)).or_insert((index, entry));
+    }
+    let mut diff = SiteMapDiff::default();
+    let mut seen = HashSet::new();
+    for entry in new {
+        let entry = entry?;
+        let loc = key(&entry.loc);
+        if !seen.insert(loc.clone()) {
+            continue;
+        }
+        match old_entries.remove(&loc) {
+            Some((_, old)) => {
+                let fields = changes(old, &entry);
+                if !fields.is_empty() {
+                    diff.changed.push(EntryChange {
+                        loc: entry.loc,
+                        fields,
+                    });
+                }
+            }
+            None => diff.added.push(entry),
+        }
+    }
+    let mut removed: Vec<_> = old_entries.into_values().collect();
+    removed.sort_by_key(|&(index, _)| index);
+    diff.removed = removed.into_iter().map(|(_, entry)| entry).collect();
+    Ok(diff)
+}
+
+/// Changed fields from `old` to `new`.
+fn changes(old: UrlEntry, new: &UrlEntry) -> Vec<FieldChange> {
+    let mut fields = Vec::new();
+    let same_lastmod = match (old.lastmod.get_time(), new.lastmod.get_time()) {
+        (Some(old), Some(new)) => old == new,
+        _ => old.lastmod == new.lastmod,
+    };
+    if !same_lastmod {
+        fields.push(FieldChange::LastMod {
+            old: old.lastmod,
+            new: new.lastmod.clone(),
+        });
+    }
+    if old.changefreq != new.changefreq {
+        fields.push(FieldChange::ChangeFreq {
+            old: old.changefreq,
+            new: new.changefreq.clone(),
+        });
+    }
+    if old.priority != new.priority {
+        fields.push(FieldChange::Priority {
+            old: old.priority,
+            new: new.priority.clone(),
+        });
+    }
+    fields
+}
@@ -0,0 +1,545 @@
+//! Contains the documents held in memory, read and written as a whole.
+//!
+//! `SiteMap` and `SiteMapIndex` keep the entries of a document with the
+//! namespaces and stylesheets of its root, to edit small sitemaps in place.
+//! Very large sitemaps are better read and written as streams with
+//! `SiteMapReader` and `SiteMapWriter`.
+//!
+//! `from_path` reads a file, gzip compressed or not, XML or text, and
+//! `write_to_path` replaces a file atomically, both enabled by the `fs`
+//! feature.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate sitemap;
+//! extern crate url;
+//! use sitemap::document::SiteMap;
+//! use sitemap::structs::UrlEntry;
+//! use url::Url;
+//! fn main() {
+//!     let document = "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\
+//!                     <url><loc>http://www.example.com/</loc></url>\
+//!                     <url><loc>http://www.example.com/old</loc></url></urlset>";
+//!     let mut sitemap = SiteMap::read_from(document.as_bytes()).unwrap();
+//!     sitemap.remove_by_loc(&Url::parse("http://www.example.com/old").unwrap());
+//!     sitemap.upsert(UrlEntry::builder().loc("http://www.example.com/").priority(0.8).build().unwrap());
+//!     sitemap.push(UrlEntry::builder().loc("http://www.example.com/new").build().unwrap());
+//!     assert_eq!(sitemap.entries().len(), 2);
+//!     let written = sitemap.to_string().unwrap();
+//!     assert!(written.contains("<priority>0.8</priority>"));
+//! }
+//! ```
+#[cfg(feature = "fs")]
+use crate::reader::TextSiteMapReader;
+use crate::reader::{DocumentKind, ReaderOptions, SiteMapEvent, SiteMapReader};
+use crate::structs::{CanonicalizeOptions, Location, SiteMapEntry, Stylesheet, UrlEntry};
+use crate::util::{self, LocOptions};
+use crate::writer::{SiteMapWriter, WriterOptions};
+use crate::Error;
+#[cfg(all(feature = "fs", feature = "gzip"))]
+use flate2::read::GzDecoder;
+#[cfg(all(feature = "fs", feature = "gzip"))]
+use flate2::write::GzEncoder;
+#[cfg(all(feature = "fs", feature = "gzip"))]
+use flate2::Compression;
+#[cfg(feature = "fs")]
+use std::convert::TryFrom;
+#[cfg(feature = "fs")]
+use std::fs::{self, File};
+use std::io::{Read, Write};
+#[cfg(feature = "fs")]
+use std::io::{BufRead, BufReader, BufWriter};
+#[cfg(feature = "fs")]
+use std::path::Path;
+#[cfg(feature = "fs")]
+use std::process;
+use url::Url;
+
+/// Urls of a `<urlset>` document held in memory.
+#[derive(Debug,Clone,Default,PartialEq)]
+pub struct SiteMap {
+    entries: Vec<UrlEntry>,
+    namespaces: Vec<(String, String)>,
+    stylesheets: Vec<Stylesheet>,
+    options: ReaderOptions,
+}
+
+/// Sitemaps of a `<sitemapindex>` document held in memory.
+#[derive(Debug,Clone,Default,PartialEq)]
+pub struct SiteMapIndex {
+    entries: Vec<SiteMapEntry>,
+    namespaces: Vec<(String, String)>,
+    stylesheets: Vec<Stylesheet>,
+    options: ReaderOptions,
+}
+
+/// Entries, root namespaces and stylesheets of a document.
+type Document<E> = (Vec<E>, Vec<(String, String)>, Vec<Stylesheet>);
+
+/// Reads the entries of a document of `kind`, `entry` returning the entry
+/// of an event of this kind.
+fn read<R, E, F>(source: R, options: ReaderOptions, kind: DocumentKind, entry: F) -> Result<Document<E>, Error>
+    where R: Read,
+          F: Fn(SiteMapEvent) -> Option<E>
+{
+    let mut reader = SiteMapReader::with_options(source, options);
+    let mut entries = Vec::new();
+    for event in reader.by_ref() {
+        if let Some(entry) = entry(event?) {
+            entries.push(entry);
+        }
+    }
+    match (reader.document_kind(), kind) {
+        (Some(DocumentKind::UrlSet), DocumentKind::UrlSet) |
+        (Some(DocumentKind::SiteMapIndex), DocumentKind::SiteMapIndex) => {
+            Ok((entries, reader.root_namespaces().to_vec(), reader.stylesheets().to_vec()))
+        }
+        (Some(DocumentKind::SiteMapIndex), _) => {
+            Err(Error::Invalid("expected urlset, found sitemapindex".to_string()))
+        }
+        (Some(DocumentKind::UrlSet), _) => {
+            Err(Error::Invalid("expected sitemapindex, found urlset".to_string()))
+        }
+        _ => Err(Error::Invalid("document without urlset or sitemapindex".to_string())),
+    }
+}
+
+/// Normalized location compared by `upsert` and `remove_by_loc`, `None`
+/// without a location.
+fn key(loc: &Location) -> Option<String> {
+    match *loc {
+        Location::None => None,
+        _ => Some(util::loc_key(&loc.canonicalize(&CanonicalizeOptions::default()), &LocOptions::default())),
+    }
+}
+
+/// Replaces the entry of `entries` with the location of `entry`, returning
+/// it, or appends `entry`.
+fn upsert<E>(entries: &mut Vec<E>, entry: E, loc: fn(&E) -> &Location) -> Option<E> {
+    let found = key(loc(&entry)).and_then(|key| {
+        entries.iter().position(|existing| self::key(loc(existing)).as_ref() == Some(&key))
+    });
+    match found {
+        Some(index) => Some(std::mem::replace(&mut entries[index], entry)),
+        None => {
+            entries.push(entry);
+            None
+        }
+    }
+}
+
+/// Removes the entries of `entries` with the location `url`, returning how
+/// many were removed.
+fn remove_by_loc<E>(entries: &mut Vec<E>, url: &Url, loc: fn(&E) -> &Location) -> usize {
+    let key = key(&Location::Url(url.clone()));
+    let len = entries.len();
+    entries.retain(|entry| self::key(loc(entry)) != key);
+    len - entries.len()
+}
+
+/// Error of a gzip compressed file without the `gzip` feature.
+#[cfg(all(feature = "fs", not(feature = "gzip")))]
+fn gzip_error() -> Error {
+    Error::Invalid("gzip compressed sitemap, enable the gzip feature".to_string())
+}
+
+/// Whether `path` has the `.gz` extension.
+#[cfg(feature = "fs")]
+fn is_gzip_path(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("gz"))
+}
+
+/// Opens the file at `path`, decompressed if it has the `.gz` extension or
+/// starts with the gzip magic bytes.
+#[cfg(feature = "fs")]
+fn open(path: &Path) -> Result<Box<dyn BufRead>, Error> {
+    let mut file = BufReader::new(File::open(path)?);
+    if is_gzip_path(path) || file.fill_buf()?.starts_with(&[0x1f, 0x8b]) {
+        #[cfg(feature = "gzip")]
+        return Ok(Box::new(BufReader::new(GzDecoder::new(file))));
+        #[cfg(not(feature = "gzip"))]
+        return Err(gzip_error());
+    }
+    Ok(Box::new(file))
+}
+
+/// Whether the first byte of `source` other than whitespace and the byte
+/// order mark is `<`, consuming only whitespace. An empty source is XML.
+#[cfg(feature = "fs")]
+fn is_xml<R: BufRead>(source: &mut R) -> Result<bool, Error> {
+    loop {
+        let buffer = source.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok(true);
+        }
+        let content = buffer.strip_prefix(b"\xef\xbb\xbf").unwrap_or(buffer);
+        match content.iter().find(|byte| !byte.is_ascii_whitespace()) {
+            Some(byte) => return Ok(*byte == b'<'),
+            None => {
+                let len = buffer.len();
+                source.consume(len);
+            }
+        }
+    }
+}
+
+/// Writes the file at `path` with `write` through a temporary file of the
+/// same directory renamed over it, compressed if it has the `.gz`
+/// extension. The temporary file is removed on errors.
+#[cfg(feature = "fs")]
+fn write_path<F>(path: &Path, write: F) -> Result<(), Error>
+    where F: FnOnce(&mut dyn Write) -> Result<(), Error>
+{
+    let name = match path.file_name() {
+        Some(name) => name.to_string_lossy(),
+        None => return Err(Error::file(path, Error::Invalid("path without file name".to_string()))),
+    };
+    let temp = path.with_file_name(format!(".{}.{}.tmp", name, process::id()));
+    let result = write_temp(&temp, is_gzip_path(path), write).and_then(|()| Ok(fs::rename(&temp, path)?));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result.map_err(|error| Error::file(path, error))
+}
+
+/// Writes the temporary file `temp`, synced to the disk before the rename.
+#[cfg(feature = "fs")]
+fn write_temp<F>(temp: &Path, gzip: bool, write: F) -> Result<(), Error>
+    where F: FnOnce(&mut dyn Write) -> Result<(), Error>
+{
+    let mut out = BufWriter::new(File::create(temp)?);
+    if gzip {
+        #[cfg(feature = "gzip")]
+        {
+            let mut encoder = GzEncoder::new(&mut out, Compression::default());
+            write(&mut encoder)?;
+            encoder.finish()?;
+        }
+        #[cfg(not(feature = "gzip"))]
+        return Err(gzip_error());
+    } else {
+        write(&mut out)?;
+    }
+    let file = out.into_inner().map_err(|error| error.into_error())?;
+    file.sync_all()?;
+    Ok(())
+}
+
+impl SiteMap {
+    /// Creates an empty urlset.
+    pub fn new() -> SiteMap {
+        SiteMap::default()
+    }
+
+    /// Reads a `<urlset>` document with the default options, see
+    /// `read_from_with_options`.
+    pub fn read_from<R: Read>(source: R) -> Result<SiteMap, Error> {
+        SiteMap::read_from_with_options(source, ReaderOptions::default())
+    }
+
+    /// Reads a `<urlset>` document with `options`.
+    ///
+    /// Fails on the first error of the reader, and on a `<sitemapindex>`.
+    /// The entries with invalid fields are kept unless `options.strict`.
+    pub fn read_from_with_options<R: Read>(source: R, options: ReaderOptions) -> Result<SiteMap, Error> {
+        let entry = |event| match event {
+            SiteMapEvent::Url(url) => Some(url),
+            _ => None,
+        };
+        let (entries, namespaces, stylesheets) = read(source, options, DocumentKind::UrlSet, entry)?;
+        Ok(SiteMap {
+            entries,
+            namespaces,
+            stylesheets,
+            options,
+        })
+    }
+
+    /// Reads the sitemap file at `path` with the default options.
+    ///
+    /// The file is decompressed if it has the `.gz` extension or starts
+    /// with the gzip magic bytes, and read as a text sitemap, one url per
+    /// line, unless its first character is `<`. The errors are
+    /// `Error::File` errors with the path.
+    #[cfg(feature = "fs")]
+    pub fn from_path(path: &Path) -> Result<SiteMap, Error> {
+        let read = || {
+            let mut source = open(path)?;
+            if is_xml(&mut source)? {
+                return SiteMap::read_from(source);
+            }
+            let entries = TextSiteMapReader::new(source).collect::<Result<_, _>>()?;
+            Ok(SiteMap {
+                entries,
+                ..SiteMap::default()
+            })
+        };
+        read().map_err(|error| Error::file(path, error))
+    }
+
+    /// Urls of the document.
+    pub fn entries(&self) -> &[UrlEntry] {
+        &self.entries
+    }
+
+    /// Urls of the document, to edit them.
+    pub fn entries_mut(&mut self) -> &mut Vec<UrlEntry> {
+        &mut self.entries
+    }
+
+    /// Returns the urls of the document.
+    pub fn into_entries(self) -> Vec<UrlEntry> {
+        self.entries
+    }
+
+    /// Namespaces declared by the root element, as `(prefix, namespace)`
+    /// pairs sorted by prefix, see `SiteMapReader::root_namespaces`.
+    pub fn namespaces(&self) -> &[(String, String)] {
+        &self.namespaces
+    }
+
+    /// Stylesheets of the document, written back before the root element.
+    pub fn stylesheets(&self) -> &[Stylesheet] {
+        &self.stylesheets
+    }
+
+    /// Options the document was read with, the default ones for a new
+    /// urlset.
+    pub fn options(&self) -> ReaderOptions {
+        self.options
+    }
+
+    /// Appends an url, even with the location of another one.
+    pub fn push(&mut self, entry: UrlEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Replaces the first url with the same normalized location as
+    /// `entry`, returning it, or appends `entry`.
+    ///
+    /// Locations are compared after `Location::canonicalize` with the
+    /// default options, an url without location is always appended.
+    pub fn upsert(&mut self, entry: UrlEntry) -> Option<UrlEntry> {
+        upsert(&mut self.entries, entry, |url| &url.loc)
+    }
+
+    /// Removes the urls with the normalized location `loc`, see `upsert`,
+    /// returning how many were removed.
+    pub fn remove_by_loc(&mut self, loc: &Url) -> usize {
+        remove_by_loc(&mut self.entries, loc, |url| &url.loc)
+    }
+
+    /// Resolves the relative locations of the urls against `base_url`,
+    /// returning how many were resolved, see `Location::resolve`.
+    ///
+    /// Fails with `Error::Resolve` on the first location not resolved,
+    /// leaving the urls unchanged.
+    pub fn resolve_locs(&mut self, base_url: &Url) -> Result<usize, Error> {
+        let mut resolved = Vec::new();
+        for (index, entry) in self.entries.iter().enumerate() {
+            match entry.loc.resolve_relative(base_url) {
+                Some(Ok(url)) => resolved.push((index, url)),
+                Some(Err(error)) => {
+                    return Err(Error::Resolve {
+                        reference: entry.loc.raw().unwrap_or_default(),
+                        error,
+                    })
+                }
+                None => {}
+            }
+        }
+        let count = resolved.len();
+        for (index, url) in resolved {
+            self.entries[index].loc = Location::Url(url);
+        }
+        Ok(count)
+    }
+
+    /// Writes the document to `out` with the default options, see
+    /// `write_to_with_options`.
+    pub fn write_to<W: Write>(&self, out: W) -> Result<W, Error> {
+        self.write_to_with_options(out, WriterOptions::default())
+    }
+
+    /// Writes the document to `out` with `options` and returns it.
+    ///
+    /// The stylesheets and the prefixed namespaces of the root element are
+    /// written back, the sitemap namespace being the default one.
+    pub fn write_to_with_options<W: Write>(&self, out: W, options: WriterOptions) -> Result<W, Error> {
+        let mut writer = SiteMapWriter::with_options(out, options);
+        for stylesheet in &self.stylesheets {
+            writer = writer.stylesheet(stylesheet.clone());
+        }
+        let mut urlset = writer.start_urlset()?;
+        for (prefix, uri) in &self.namespaces {
+            if !prefix.is_empty() {
+                urlset = urlset.namespace(prefix, uri)?;
+            }
+        }
+        for entry in &self.entries {
+            urlset.url(entry.clone())?;
+        }
+        urlset.end()
+    }
+
+    /// Writes the document to a string with the default options.
+    pub fn to_string(&self) -> Result<String, Error> {
+        String::from_utf8(self.write_to(Vec::new())?).map_err(|error| Error::Invalid(error.to_string()))
+    }
+
+    /// Writes the document to the file at `path` with the default options,
+    /// gzip compressed if it has the `.gz` extension.
+    ///
+    /// The document is written to a temporary file of the same directory
+    /// renamed over `path`, so the file is never left truncated. The
+    /// errors are `Error::File` errors with the path.
+    #[cfg(feature = "fs")]
+    pub fn write_to_path(&self, path: &Path) -> Result<(), Error> {
+        write_path(path, |out| self.write_to(out).map(|_| ()))
+    }
+}
+
+impl SiteMapIndex {
+    /// Creates an empty sitemapindex.
+    pub fn new() -> SiteMapIndex {
+        SiteMapIndex::default()
+    }
+
+    /// Reads a `<sitemapindex>` document with the default options, see
+    /// `read_from_with_options`.
+    pub fn read_from<R: Read>(source: R) -> Result<SiteMapIndex, Error> {
+        SiteMapIndex::read_from_with_options(source, ReaderOptions::default())
+    }
+
+    /// Reads a `<sitemapindex>` document with `options`.
+    ///
+    /// Fails on the first error of the reader, and on a `<urlset>`. The
+    /// entries with invalid fields are kept unless `options.strict`.
+    pub fn read_from_with_options<R: Read>(source: R, options: ReaderOptions) -> Result<SiteMapIndex, Error> {
+        let entry = |event| match event {
+            SiteMapEvent::SiteMap(sitemap) => Some(sitemap),
+            _ => None,
+        };
+        let (entries, namespaces, stylesheets) = read(source, options, DocumentKind::SiteMapIndex, entry)?;
+        Ok(SiteMapIndex {
+            entries,
+            namespaces,
+            stylesheets,
+            options,
+        })
+    }
+
+    /// Reads the sitemapindex file at `path` with the default options,
+    /// decompressed like `SiteMap::from_path`. A text sitemap is an error.
+    #[cfg(feature = "fs")]
+    pub fn from_path(path: &Path) -> Result<SiteMapIndex, Error> {
+        let read = || {
+            let mut source = open(path)?;
+            if !is_xml(&mut source)? {
+                return Err(Error::Invalid("expected sitemapindex, found text sitemap".to_string()));
+            }
+            SiteMapIndex::read_from(source)
+        };
+        read().map_err(|error| Error::file(path, error))
+    }
+
+    /// Sitemaps of the document.
+    pub fn entries(&self) -> &[SiteMapEntry] {
+        &self.entries
+    }
+
+    /// Sitemaps of the document, to edit them.
+    pub fn entries_mut(&mut self) -> &mut Vec<SiteMapEntry> {
+        &mut self.entries
+    }
+
+    /// Returns the sitemaps of the document.
+    pub fn into_entries(self) -> Vec<SiteMapEntry> {
+        self.entries
+    }
+
+    /// Namespaces declared by the root element, as `(prefix, namespace)`
+    /// pairs sorted by prefix, see `SiteMapReader::root_namespaces`.
+    pub fn namespaces(&self) -> &[(String, String)] {
+        &self.namespaces
+    }
+
+    /// Stylesheets of the document, written back before the root element.
+    pub fn stylesheets(&self) -> &[Stylesheet] {
+        &self.stylesheets
+    }
+
+    /// Options the document was read with, the default ones for a new
+    /// sitemapindex.
+    pub fn options(&self) -> ReaderOptions {
+        self.options
+    }
+
+    /// Appends a sitemap, even with the location of another one.
+    pub fn push(&mut self, entry: SiteMapEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Replaces the first sitemap with the same normalized location as
+    /// `entry`, returning it, or appends `entry`, see `SiteMap::upsert`.
+    pub fn upsert(&mut self, entry: SiteMapEntry) -> Option<SiteMapEntry> {
+        upsert(&mut self.entries, entry, |sitemap| &sitemap.loc)
+    }
+
+    /// Removes the sitemaps with the normalized location `loc`, returning
+    /// how many were removed.
+    pub fn remove_by_loc(&mut self, loc: &Url) -> usize {
+        remove_by_loc(&mut self.entries, loc, |sitemap| &sitemap.loc)
+    }
+
+    /// Writes the document to `out` with the default options, see
+    /// `write_to_with_options`.
+    pub fn write_to<W: Write>(&self, out: W) -> Result<W, Error> {
+        self.write_to_with_options(out, WriterOptions::default())
+    }
+
+    /// Writes the document to `out` with `options` and returns it.
+    ///
+    /// The stylesheets are written back, not the namespaces, the index
+    /// writer declaring the sitemap namespace only.
+    pub fn write_to_with_options<W: Write>(&self, out: W, options: WriterOptions) -> Result<W, Error> {
+        let mut writer = SiteMapWriter::with_options(out, options);
+        for stylesheet in &self.stylesheets {
+            writer = writer.stylesheet(stylesheet.clone());
+        }
+        let mut index = writer.start_sitemapindex()?;
+        for entry in &self.entries {
+            index.sitemap(entry.clone())?;
+        }
+        index.end()
+    }
+
+    /// Writes the document to a string with the default options.
+    pub fn to_string(&self) -> Result<String, Error> {
+        String::from_utf8(self.write_to(Vec::new())?).map_err(|error| Error::Invalid(error.to_string()))
+    }
+
+    /// Writes the document to the file at `path`, see
+    /// `SiteMap::write_to_path`.
+    #[cfg(feature = "fs")]
+    pub fn write_to_path(&self, path: &Path) -> Result<(), Error> {
+        write_path(path, |out| self.write_to(out).map(|_| ()))
+    }
+}
+
+#[cfg(feature = "fs")]
+impl<'a> TryFrom<&'a Path> for SiteMap {
+    type Error = Error;
+    fn try_from(path: &'a Path) -> Result<SiteMap, Error> {
+        SiteMap::from_path(path)
+    }
+}
+
+#[cfg(feature = "fs")]
+impl<'a> TryFrom<&'a Path> for SiteMapIndex {
+    type Error = Error;
+    fn try_from(path: &'a Path) -> Result<SiteMapIndex, Error> {
+        SiteMapIndex::from_path(path)
+    }
+}
@@ -0,0 +1,271 @@
+//! Contains the transcoding of documents to UTF-8, enabled by the
+//! `encodings` feature.
+//!
+//! The encoding is detected from the byte order mark, then from the
+//! `encoding` of the XML declaration, UTF-8 otherwise. The declaration of the
+//! transcoded document is rewritten to UTF-8, positions of the errors refer
+//! to the transcoded document.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate sitemap;
+//! use sitemap::encoding::DecodingReader;
+//! use sitemap::reader::{SiteMapReader, SiteMapEvent};
+//! fn main() {
+//!     let document: &[u8] = b"<?xml version=\"1.0\" encoding=\"windows-1252\"?>\
+//!                             <urlset><url><loc>http://www.example.com/caf\xe9</loc></url></urlset>";
+//!     for event in SiteMapReader::new(DecodingReader::new(document)) {
+//!         if let Ok(SiteMapEvent::Url(url_entry)) = event {
+//!             println!("url = {:?}", url_entry.loc);
+//!         }
+//!     }
+//! }
+//! ```
+use crate::reader::declaration_encoding;
+use encoding_rs::{CoderResult, Decoder, Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use std::io::{self, BufRead, Read};
+#[cfg(feature = "async")]
+use std::pin::Pin;
+#[cfg(feature = "async")]
+use std::task::{Context, Poll};
+#[cfg(feature = "async")]
+use tokio::io::{AsyncBufRead, AsyncRead, ReadBuf};
+
+/// Bytes read at most to detect the encoding.
+const SNIFF_SIZE: usize = 1024;
+const BUFFER_SIZE: usize = 8192;
+
+/// Encoding of the document starting with `prefix` and the length of its
+/// byte order mark.
+fn detect(prefix: &[u8]) -> (&'static Encoding, usize) {
+    if let Some(bom) = Encoding::for_bom(prefix) {
+        return bom;
+    }
+    if prefix.starts_with(&[b'<', 0, b'?', 0]) {
+        return (UTF_16LE, 0);
+    }
+    if prefix.starts_with(&[0, b'<', 0, b'?']) {
+        return (UTF_16BE, 0);
+    }
+    match declared_encoding(prefix).and_then(Encoding::for_label) {
+        // a 16 bit encoding would not have an ASCII declaration
+        Some(encoding) if encoding != UTF_16LE && encoding != UTF_16BE => (encoding, 0),
+        _ => (UTF_8, 0),
+    }
+}
+
+fn declared_encoding(prefix: &[u8]) -> Option<&[u8]> {
+    declaration_encoding(prefix).map(|(start, end)| &prefix[start..end])
+}
+
+/// Whether `prefix` holds the end of the declaration, in an 8 or 16 bit
+/// encoding.
+fn has_declaration_end(prefix: &[u8]) -> bool {
+    let mut units = prefix.chunks_exact(2).zip(prefix.get(2..).unwrap_or(&[]).chunks_exact(2));
+    prefix.windows(2).any(|window| window == b"?>") ||
+    units.any(|units| units == (b"?\0", b">\0") || units == (b"\0?", b"\0>"))
+}
+
+/// Transcoder of the bytes fed so far, shared by the readers.
+struct Transcoder {
+    sniffed: Vec<u8>,
+    decoder: Option<Decoder>,
+    encoding: Option<&'static Encoding>,
+    output: String,
+    position: usize,
+    finished: bool,
+}
+
+impl Transcoder {
+    fn new() -> Transcoder {
+        Transcoder {
+            sniffed: Vec::new(),
+            decoder: None,
+            encoding: None,
+            output: String::new(),
+            position: 0,
+            finished: false,
+        }
+    }
+
+    /// Transcoded bytes not read yet.
+    fn available(&self) -> &[u8] {
+        &self.output.as_bytes()[self.position..]
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.position = (self.position + amount).min(self.output.len());
+    }
+
+    /// Whether more input is needed to read further.
+    fn needs_input(&self) -> bool {
+        !self.finished && self.position == self.output.len()
+    }
+
+    /// Transcodes `input`, empty at the end of the source.
+    fn feed(&mut self, input: &[u8]) {
+        let last = input.is_empty();
+        self.output.clear();
+        self.position = 0;
+        if self.decoder.is_some() {
+            self.decode(input, last);
+        } else {
+            self.sniffed.extend_from_slice(input);
+            if !last && self.sniffed.len() < SNIFF_SIZE && !has_declaration_end(&self.sniffed) {
+                return;
+            }
+            let (encoding, bom) = detect(&self.sniffed);
+            self.encoding = Some(encoding);
+            self.decoder = Some(encoding.new_decoder_without_bom_handling());
+            let sniffed = ::std::mem::take(&mut self.sniffed);
+            self.decode(&sniffed[bom..], last);
+            if let Some((start, end)) = declaration_encoding(&self.output) {
+                self.output.replace_range(start..end, "UTF-8");
+            }
+        }
+        self.finished = last;
+    }
+
+    fn decode(&mut self, mut input: &[u8], last: bool) {
+        let decoder = match self.decoder {
+            Some(ref mut decoder) => decoder,
+            None => return,
+        };
+        loop {
+            let needed = decoder.max_utf8_buffer_length(input.len()).unwrap_or(BUFFER_SIZE);
+            self.output.reserve(needed);
+            let (result, read, _) = decoder.decode_to_string(input, &mut self.output, last);
+            input = &input[read..];
+            if result == CoderResult::InputEmpty {
+                break;
+            }
+        }
+    }
+}
+
+/// Reader transcoding a document to UTF-8.
+pub struct DecodingReader<R: Read> {
+    inner: R,
+    transcoder: Transcoder,
+    buffer: Vec<u8>,
+}
+
+impl<R: Read> DecodingReader<R> {
+    /// Creates a new reader, consuming the given source.
+    pub fn new(source: R) -> DecodingReader<R> {
+        DecodingReader {
+            inner: source,
+            transcoder: Transcoder::new(),
+            buffer: vec![0; BUFFER_SIZE],
+        }
+    }
+
+    /// Name of the detected encoding, `None` before reading.
+    pub fn encoding(&self) -> Option<&'static str> {
+        self.transcoder.encoding.map(Encoding::name)
+    }
+
+    /// Unwraps the source.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> BufRead for DecodingReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while self.transcoder.needs_input() {
+            let read = self.inner.read(&mut self.buffer)?;
+            self.transcoder.feed(&self.buffer[..read]);
+        }
+        Ok(self.transcoder.available())
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.transcoder.consume(amount);
+    }
+}
+
+impl<R: Read> Read for DecodingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = {
+            let available = self.fill_buf()?;
+            let read = available.len().min(buf.len());
+            buf[..read].copy_from_slice(&available[..read]);
+            read
+        };
+        self.consume(read);
+        Ok(read)
+    }
+}
+
+/// Asynchronous reader transcoding a document to UTF-8, enabled by the
+/// `async` feature.
+#[cfg(feature = "async")]
+pub struct AsyncDecodingReader<R> {
+    inner: R,
+    transcoder: Transcoder,
+    buffer: Vec<u8>,
+}
+
+#[cfg(feature = "async")]
+impl<R: AsyncRead + Unpin> AsyncDecodingReader<R> {
+    /// Creates a new reader, consuming the given stream.
+    pub fn new(source: R) -> AsyncDecodingReader<R> {
+        AsyncDecodingReader {
+            inner: source,
+            transcoder: Transcoder::new(),
+            buffer: vec![0; BUFFER_SIZE],
+        }
+    }
+
+    /// Name of the detected encoding, `None` before reading.
+    pub fn encoding(&self) -> Option<&'static str> {
+        self.transcoder.encoding.map(Encoding::name)
+    }
+
+    /// Unwraps the stream.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+#[cfg(feature = "async")]
+impl<R: AsyncRead + Unpin> AsyncBufRead for AsyncDecodingReader<R> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<&[u8]>> {
+        let this = self.get_mut();
+        while this.transcoder.needs_input() {
+            let mut buf = ReadBuf::new(&mut this.buffer);
+            match Pin::new(&mut this.inner).poll_read(cx, &mut buf) {
+                Poll::Ready(Ok(())) => this.transcoder.feed(buf.filled()),
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        Poll::Ready(Ok(this.transcoder.available()))
+    }
+
+    fn consume(self: Pin<&mut Self>, amount: usize) {
+        self.get_mut().transcoder.consume(amount);
+    }
+}
+
+#[cfg(feature = "async")]
+impl<R: AsyncRead + Unpin> AsyncRead for AsyncDecodingReader<R> {
+    fn poll_read(mut self: Pin<&mut Self>,
+                 cx: &mut Context,
+                 buf: &mut ReadBuf)
+                 -> Poll<io::Result<()>> {
+        let read = match self.as_mut().poll_fill_buf(cx) {
+            Poll::Ready(Ok(available)) => {
+                let read = available.len().min(buf.remaining());
+                buf.put_slice(&available[..read]);
+                read
+            }
+            Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+            Poll::Pending => return Poll::Pending,
+        };
+        self.consume(read);
+        Poll::Ready(Ok(()))
+    }
+}
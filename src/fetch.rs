@@ -0,0 +1,150 @@
+//! Contains the fetching of sitemaps over HTTP, enabled by the `http` feature.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! extern crate sitemap;
+//! use sitemap::fetch;
+//! use sitemap::reader::SiteMapEvent;
+//! fn main() {
+//!     let reader = fetch::read_from_url("http://www.example.com/sitemap.xml").unwrap();
+//!     for event in reader {
+//!         if let Ok(SiteMapEvent::Url(url_entry)) = event {
+//!             println!("url = {:?}", url_entry);
+//!         }
+//!     }
+//! }
+//! ```
+use crate::reader::SiteMapReader;
+use crate::resolver::Fetch;
+use crate::structs::MAX_SITEMAP_SIZE;
+use crate::{Error, LimitKind};
+use flate2::read::GzDecoder;
+use reqwest::blocking::Client;
+use reqwest::header;
+use std::io::{self, Read};
+use std::time::Duration;
+use url::Url;
+
+/// User agent of the requests, by default.
+pub const USER_AGENT: &str = concat!("rust-sitemap/", env!("CARGO_PKG_VERSION"));
+
+/// Fetcher of documents over HTTP, with a blocking client.
+///
+/// Compressed responses, either with a gzip content encoding or with a gzip
+/// payload like `.xml.gz` files, are decompressed.
+#[derive(Debug,Clone)]
+pub struct HttpFetch {
+    client: Client,
+    timeout: Duration,
+    max_body_size: u64,
+    user_agent: String,
+}
+
+impl Default for HttpFetch {
+    fn default() -> HttpFetch {
+        HttpFetch::new()
+    }
+}
+
+impl HttpFetch {
+    /// Creates a new fetcher.
+    pub fn new() -> HttpFetch {
+        HttpFetch {
+            client: Client::new(),
+            timeout: Duration::from_secs(30),
+            max_body_size: MAX_SITEMAP_SIZE,
+            user_agent: USER_AGENT.to_string(),
+        }
+    }
+
+    /// Timeout of each request, 30 seconds by default.
+    pub fn timeout(mut self, timeout: Duration) -> HttpFetch {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Maximum size in bytes of a document, both compressed and
+    /// uncompressed, `MAX_SITEMAP_SIZE` by default.
+    pub fn max_body_size(mut self, max_body_size: u64) -> HttpFetch {
+        self.max_body_size = max_body_size;
+        self
+    }
+
+    /// User agent of the requests, `USER_AGENT` by default.
+    pub fn user_agent<S: Into<String>>(mut self, user_agent: S) -> HttpFetch {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Reads `source` fully, failing over the maximum body size.
+    fn read_body<R: Read>(&self, source: R) -> Result<Vec<u8>, Error> {
+        let mut body = Vec::new();
+        source.take(self.max_body_size.saturating_add(1)).read_to_end(&mut body)?;
+        if body.len() as u64 > self.max_body_size {
+            return Err(Error::LimitExceeded {
+                kind: LimitKind::Size,
+                limit: self.max_body_size,
+            });
+        }
+        Ok(body)
+    }
+}
+
+/// Content types of sitemaps, either XML or possibly compressed payloads.
+fn is_sitemap_content_type(content_type: &str) -> bool {
+    let mime = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    mime == "application/xml" || mime == "text/xml" || mime.ends_with("+xml") ||
+    mime == "application/gzip" || mime == "application/x-gzip" ||
+    mime == "application/octet-stream"
+}
+
+fn io_error(error: reqwest::Error) -> Error {
+    Error::Io(io::Error::other(error))
+}
+
+impl Fetch for HttpFetch {
+    fn fetch(&self, url: &Url) -> Result<Box<dyn Read>, Error> {
+        let response = self.client
+            .get(url.as_str())
+            .timeout(self.timeout)
+            .header(header::USER_AGENT, self.user_agent.as_str())
+            .send()
+            .map_err(io_error)?;
+        if !response.status().is_success() {
+            return Err(Error::HttpStatus {
+                url: url.to_string(),
+                status: response.status().as_u16(),
+            });
+        }
+        if let Some(content_type) = response.headers().get(header::CONTENT_TYPE) {
+            let content_type = String::from_utf8_lossy(content_type.as_bytes()).into_owned();
+            if !is_sitemap_content_type(&content_type) {
+                return Err(Error::ContentType {
+                    url: url.to_string(),
+                    content_type,
+                });
+            }
+        }
+        if response.content_length().is_some_and(|length| length > self.max_body_size) {
+            return Err(Error::LimitExceeded {
+                kind: LimitKind::Size,
+                limit: self.max_body_size,
+            });
+        }
+        let body = self.read_body(response)?;
+        let body = if body.starts_with(&[0x1f, 0x8b]) {
+            self.read_body(GzDecoder::new(&body[..]))?
+        } else {
+            body
+        };
+        Ok(Box::new(io::Cursor::new(body)))
+    }
+}
+
+/// Fetches the sitemap at `url` with the default `HttpFetch`.
+pub fn read_from_url(url: &str) -> Result<SiteMapReader<impl Read>, Error> {
+    let url = Url::parse(url)?;
+    let source = HttpFetch::new().fetch(&url)?;
+    Ok(SiteMapReader::new(source))
+}
@@ -0,0 +1,344 @@
+//! Contains the filtering of the urls read from a sitemap.
+//!
+//! `FilterEntries::filter_entries` keeps the entries accepted by an
+//! `EntryFilter` and every error, closures taking a `&UrlEntry` are filters
+//! too. `FilterEntryStream` does the same on streams, with the `async`
+//! feature, and `loc_matches` needs the `glob` feature.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate sitemap;
+//! extern crate chrono;
+//! use chrono::DateTime;
+//! use sitemap::filter::{self, FilterEntries, MissingLastMod};
+//! use sitemap::reader::SiteMapReader;
+//! fn main() {
+//!     let document = "<urlset>\
+//!                     <url><loc>http://www.example.com/</loc><lastmod>2020-01-01</lastmod></url>\
+//!                     <url><loc>http://www.example.com/new</loc><lastmod>2020-02-01</lastmod></url>\
+//!                     </urlset>";
+//!     let since = DateTime::parse_from_rfc3339("2020-01-15T00:00:00Z").unwrap();
+//!     let urls: Vec<_> = SiteMapReader::new(document.as_bytes())
+//!         .into_url_iter()
+//!         .filter_entries(filter::modified_since(since, MissingLastMod::Exclude))
+//!         .collect();
+//!     assert_eq!(urls.len(), 1);
+//! }
+//! ```
+use crate::structs::{ChangeFreq, Location, UrlEntry};
+use crate::Error;
+use chrono::{DateTime, FixedOffset};
+#[cfg(feature = "async")]
+use futures_core::Stream;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+#[cfg(feature = "async")]
+use std::task::{Context, Poll};
+
+/// Predicate on url entries.
+pub trait EntryFilter {
+    /// Whether `entry` is kept.
+    fn matches(&self, entry: &UrlEntry) -> bool;
+}
+
+impl<F: Fn(&UrlEntry) -> bool> EntryFilter for F {
+    fn matches(&self, entry: &UrlEntry) -> bool {
+        self(entry)
+    }
+}
+
+/// Policy for the entries without a valid `lastmod`.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum MissingLastMod {
+    /// The entries are kept.
+    Include,
+    /// The entries are removed.
+    Exclude,
+}
+
+/// Filter returned by `modified_since`.
+#[derive(Debug,Clone)]
+pub struct ModifiedSince {
+    time: DateTime<FixedOffset>,
+    missing: MissingLastMod,
+}
+
+impl EntryFilter for ModifiedSince {
+    fn matches(&self, entry: &UrlEntry) -> bool {
+        match entry.lastmod.get_time() {
+            Some(time) => time > self.time,
+            None => self.missing == MissingLastMod::Include,
+        }
+    }
+}
+
+/// Keeps the entries modified after `time`, dates count as midnight UTC.
+pub fn modified_since(time: DateTime<FixedOffset>, missing: MissingLastMod) -> ModifiedSince {
+    ModifiedSince { time, missing }
+}
+
+/// Filter returned by `changefreq_at_most`.
+#[derive(Debug,Clone)]
+pub struct ChangeFreqAtMost {
+    rank: Option<u8>,
+}
+
+impl EntryFilter for ChangeFreqAtMost {
+    fn matches(&self, entry: &UrlEntry) -> bool {
+        match (entry.changefreq.rank(), self.rank) {
+            (Some(rank), Some(max)) => rank <= max,
+            _ => false,
+        }
+    }
+}
+
+/// Keeps the entries changing at least as often as `changefreq`, in the
+/// order `always` < `hourly` < `daily` < `weekly` < `monthly` < `yearly` <
+/// `never`. Entries without a valid change frequency are removed.
+pub fn changefreq_at_most(changefreq: ChangeFreq) -> ChangeFreqAtMost {
+    ChangeFreqAtMost { rank: changefreq.rank() }
+}
+
+/// Filter returned by `loc_contains`.
+#[derive(Debug,Clone)]
+pub struct LocContains {
+    text: String,
+}
+
+impl EntryFilter for LocContains {
+    fn matches(&self, entry: &UrlEntry) -> bool {
+        match *entry.loc.parsed() {
+            Location::Url(ref url) => url.as_str().contains(&self.text),
+            _ => false,
+        }
+    }
+}
+
+/// Keeps the entries with `text` in their url.
+pub fn loc_contains<S: Into<String>>(text: S) -> LocContains {
+    LocContains { text: text.into() }
+}
+
+/// Filter returned by `host_is`.
+#[derive(Debug,Clone)]
+pub struct HostIs {
+    host: String,
+}
+
+impl EntryFilter for HostIs {
+    fn matches(&self, entry: &UrlEntry) -> bool {
+        entry.loc.parsed().host() == Some(self.host.as_str())
+    }
+}
+
+/// Keeps the entries on `host`, ignoring its case.
+pub fn host_is(host: &str) -> HostIs {
+    HostIs { host: host.to_lowercase() }
+}
+
+/// Component of a glob pattern.
+#[cfg(feature = "glob")]
+#[derive(Debug,Clone,PartialEq)]
+enum Token {
+    Char(char),
+    /// `?`, any character but `/`.
+    Any,
+    /// `*`, any characters but `/`.
+    Star,
+    /// `**`, any characters.
+    DoubleStar,
+    /// `[...]`, a character in or, when negated, out of the ranges.
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+/// Glob pattern matched against the whole url, enabled by the `glob`
+/// feature.
+///
+/// `?` matches a character and `*` any characters, except `/`, `**`
+/// matches any characters and `[a-z]` or `[!a-z]` a character in or out of
+/// the ranges.
+#[cfg(feature = "glob")]
+#[derive(Debug,Clone,PartialEq)]
+pub struct Glob {
+    tokens: Vec<Token>,
+}
+
+#[cfg(feature = "glob")]
+impl Glob {
+    /// Compiles `pattern`, failing on an unclosed `[`.
+    pub fn new(pattern: &str) -> Result<Glob, Error> {
+        let mut tokens = Vec::new();
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            let token = match c {
+                '?' => Token::Any,
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    Token::DoubleStar
+                }
+                '*' => Token::Star,
+                '[' => {
+                    let negated = chars.peek() == Some(&'!');
+                    if negated {
+                        chars.next();
+                    }
+                    let mut ranges = Vec::new();
+                    loop {
+                        let start = match chars.next() {
+                            Some(']') if !ranges.is_empty() => break,
+                            Some(start) => start,
+                            None => {
+                                return Err(Error::invalid_value("glob", pattern, "unclosed character class"))
+                            }
+                        };
+                        if chars.peek() == Some(&'-') {
+                            chars.next();
+                            match chars.next() {
+                                Some(']') => {
+                                    ranges.push((start, start));
+                                    ranges.push(('-', '-'));
+                                    break;
+                                }
+                                Some(end) => ranges.push((start, end)),
+                                None => {
+                                    return Err(Error::invalid_value("glob", pattern, "unclosed character class"))
+                                }
+                            }
+                        } else {
+                            ranges.push((start, start));
+                        }
+                    }
+                    Token::Class { negated, ranges }
+                }
+                c => Token::Char(c),
+            };
+            tokens.push(token);
+        }
+        Ok(Glob { tokens })
+    }
+
+    /// Whether the whole `text` matches the pattern.
+    pub fn matches(&self, text: &str) -> bool {
+        let text: Vec<char> = text.chars().collect();
+        // matched[i]: whether the tokens so far match the first i characters
+        let mut matched = vec![false; text.len() + 1];
+        matched[0] = true;
+        for token in &self.tokens {
+            let mut next = vec![false; text.len() + 1];
+            for i in 0..=text.len() {
+                next[i] = match *token {
+                    Token::Star => matched[i] || (i > 0 && next[i - 1] && text[i - 1] != '/'),
+                    Token::DoubleStar => matched[i] || (i > 0 && next[i - 1]),
+                    _ if i == 0 => false,
+                    Token::Char(c) => matched[i - 1] && text[i - 1] == c,
+                    Token::Any => matched[i - 1] && text[i - 1] != '/',
+                    Token::Class { negated, ref ranges } => {
+                        let c = text[i - 1];
+                        let found = ranges.iter().any(|&(start, end)| start <= c && c <= end);
+                        matched[i - 1] && found != negated
+                    }
+                };
+            }
+            matched = next;
+        }
+        matched[text.len()]
+    }
+}
+
+/// Filter returned by `loc_matches`, enabled by the `glob` feature.
+#[cfg(feature = "glob")]
+#[derive(Debug,Clone)]
+pub struct LocMatches {
+    pattern: Glob,
+}
+
+#[cfg(feature = "glob")]
+impl EntryFilter for LocMatches {
+    fn matches(&self, entry: &UrlEntry) -> bool {
+        match *entry.loc.parsed() {
+            Location::Url(ref url) => self.pattern.matches(url.as_str()),
+            _ => false,
+        }
+    }
+}
+
+/// Keeps the entries with a url matching `pattern`, enabled by the `glob`
+/// feature.
+#[cfg(feature = "glob")]
+pub fn loc_matches(pattern: Glob) -> LocMatches {
+    LocMatches { pattern }
+}
+
+/// Filtering of iterators of url entries.
+pub trait FilterEntries: Iterator<Item = Result<UrlEntry, Error>> + Sized {
+    /// Keeps the entries accepted by `filter`, and the errors.
+    fn filter_entries<F: EntryFilter>(self, filter: F) -> FilteredEntries<Self, F> {
+        FilteredEntries {
+            inner: self,
+            filter,
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<UrlEntry, Error>>> FilterEntries for I {}
+
+/// Iterator returned by `FilterEntries::filter_entries`.
+pub struct FilteredEntries<I, F> {
+    inner: I,
+    filter: F,
+}
+
+impl<I, F> Iterator for FilteredEntries<I, F>
+    where I: Iterator<Item = Result<UrlEntry, Error>>,
+          F: EntryFilter
+{
+    type Item = Result<UrlEntry, Error>;
+    fn next(&mut self) -> Option<Result<UrlEntry, Error>> {
+        let filter = &self.filter;
+        self.inner.find(|entry| entry.as_ref().map_or(true, |entry| filter.matches(entry)))
+    }
+}
+
+/// Filtering of streams of url entries, enabled by the `async` feature.
+#[cfg(feature = "async")]
+pub trait FilterEntryStream: Stream<Item = Result<UrlEntry, Error>> + Sized {
+    /// Keeps the entries accepted by `filter`, and the errors.
+    fn filter_entries<F: EntryFilter>(self, filter: F) -> FilteredStream<Self, F> {
+        FilteredStream {
+            inner: self,
+            filter,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: Stream<Item = Result<UrlEntry, Error>>> FilterEntryStream for S {}
+
+/// Stream returned by `FilterEntryStream::filter_entries`, enabled by the
+/// `async` feature.
+#[cfg(feature = "async")]
+pub struct FilteredStream<S, F> {
+    inner: S,
+    filter: F,
+}
+
+#[cfg(feature = "async")]
+impl<S, F> Stream for FilteredStream<S, F>
+    where S: Stream<Item = Result<UrlEntry, Error>> + Unpin,
+          F: EntryFilter + Unpin
+{
+    type Item = Result<UrlEntry, Error>;
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match Pin::new(&mut this.inner).poll_next(cx) {
+                Poll::Ready(Some(Ok(ref entry))) if !this.filter.matches(entry) => {}
+                poll => return poll,
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Contains the fingerprint of the logical content of a sitemap, to detect
+//! that a polled sitemap changed without comparing its entries.
+//!
+//! The fingerprint is computed over the normalized location and the
+//! modification time of each url, the other fields are ignored. The hashes
+//! of the entries are summed, so that the order of the entries and the
+//! formatting of the document do not change it.
+//!
+//! # Stability
+//!
+//! The fingerprint of the same entries is the same on every platform and
+//! across the versions of this crate: the hashes are FNV-1a hashes of the
+//! normalized texts, not the hashes of the standard library, and the
+//! normalization is only changed by a new major version. The parsing of the
+//! urls is the one of the `url` crate, its normalization of the hosts and
+//! paths is the one of its WHATWG parser.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate chrono;
+//! extern crate sitemap;
+//! use chrono::{DateTime, NaiveDate};
+//! use sitemap::fingerprint;
+//! use sitemap::structs::UrlEntry;
+//! fn main() {
+//!     let a = UrlEntry::builder()
+//!         .loc("http://www.example.com/a")
+//!         .lastmod_date(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap())
+//!         .build()
+//!         .unwrap();
+//!     let b = UrlEntry::builder().loc("http://WWW.example.com/b").build().unwrap();
+//!     let shuffled = vec![
+//!         UrlEntry::builder().loc("http://www.example.com/b").priority(0.5).build().unwrap(),
+//!         UrlEntry::builder()
+//!             .loc("http://www.example.com/a")
+//!             .lastmod(DateTime::parse_from_rfc3339("2024-01-01T02:00:00+02:00").unwrap())
+//!             .build()
+//!             .unwrap(),
+//!     ];
+//!     assert_eq!(fingerprint::fingerprint(vec![a, b]), fingerprint::fingerprint(shuffled));
+//! }
+//! ```
+use crate::reader::SiteMapReader;
+use crate::structs::{CanonicalizeOptions, LastMod, UrlEntry};
+use crate::util::{self, LocOptions};
+use crate::Error;
+use std::io::Read;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Fingerprint of the entries added so far, the streaming counterpart of
+/// `fingerprint`.
+#[derive(Debug,Clone,Copy,Default,PartialEq,Eq)]
+pub struct Fingerprint {
+    sum: u64,
+    entries: u64,
+}
+
+impl Fingerprint {
+    /// Creates the fingerprint of no entry.
+    pub fn new() -> Fingerprint {
+        Fingerprint::default()
+    }
+    /// Fingerprint of the urls of `reader`, failing with its first error.
+    pub fn from_reader<R: Read>(reader: SiteMapReader<R>) -> Result<Fingerprint, Error> {
+        let mut fingerprint = Fingerprint::new();
+        for url in reader.into_url_iter() {
+            fingerprint.add(&url?);
+        }
+        Ok(fingerprint)
+    }
+    /// Adds `entry`, an entry added twice changes the fingerprint.
+    pub fn add(&mut self, entry: &UrlEntry) {
+        self.sum = self.sum.wrapping_add(entry_hash(entry));
+        self.entries += 1;
+    }
+    /// Number of entries added.
+    pub fn entries(&self) -> u64 {
+        self.entries
+    }
+    /// Value of the fingerprint, also depending on the number of entries.
+    pub fn value(&self) -> u64 {
+        mix(self.sum ^ mix(self.entries))
+    }
+}
+
+/// Fingerprint of `entries`, see the module documentation.
+pub fn fingerprint<I: IntoIterator<Item = UrlEntry>>(entries: I) -> u64 {
+    let mut fingerprint = Fingerprint::new();
+    for entry in entries {
+        fingerprint.add(&entry);
+    }
+    fingerprint.value()
+}
+
+/// Fingerprint of the urls of `reader`, computed while they are read.
+pub fn fingerprint_reader<R: Read>(reader: SiteMapReader<R>) -> Result<u64, Error> {
+    Fingerprint::from_reader(reader).map(|fingerprint| fingerprint.value())
+}
+
+/// Hash of the normalized location and modification time of `entry`.
+///
+/// The times are hashed in UTC, so that the same time with another offset
+/// or precision has the same hash, the invalid ones by their text.
+fn entry_hash(entry: &UrlEntry) -> u64 {
+    let loc = util::loc_key(&entry.loc.canonicalize(&CanonicalizeOptions::default()), &LocOptions::default());
+    let mut hash = fnv(FNV_OFFSET, loc.as_bytes());
+    hash = match entry.lastmod.get_time() {
+        Some(time) => {
+            let hash = fnv(hash, &[1]);
+            let hash = fnv(hash, &time.timestamp().to_be_bytes());
+            fnv(hash, &time.timestamp_subsec_nanos().to_be_bytes())
+        }
+        None => {
+            match entry.lastmod {
+                LastMod::None => fnv(hash, &[0]),
+                ref lastmod => fnv(fnv(hash, &[2]), lastmod.raw().unwrap_or_default().as_bytes()),
+            }
+        }
+    };
+    mix(hash)
+}
+
+/// FNV-1a hash of `bytes` continued from `hash`.
+fn fnv(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME))
+}
+
+/// Finalizer of SplitMix64, spreading the bits of the hashes before they are
+/// summed.
+fn mix(value: u64) -> u64 {
+    let mut value = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
@@ -0,0 +1,134 @@
+//! Contains the helpers for the children of a sitemap index: their
+//! freshness, in `freshness`, and their parallel resolution, enabled by the
+//! `parallel` feature.
+//!
+//! The children are fetched and read by a rayon thread pool, each one
+//! through a `SiteMapIndexResolver`, so nested indexes get the same depth,
+//! cycle and url count checks as `SiteMapIndexResolver::resolve`.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate sitemap;
+//! use sitemap::index;
+//! use sitemap::resolver::MemoryFetch;
+//! use sitemap::structs::SiteMapEntry;
+//! # #[cfg(feature = "parallel")]
+//! fn main() {
+//!     let fetcher = MemoryFetch::new()
+//!         .document("http://www.example.com/a.xml",
+//!                   "<urlset><url><loc>http://www.example.com/</loc></url></urlset>")
+//!         .document("http://www.example.com/b.xml",
+//!                   "<urlset><url><loc>http://www.example.com/b</loc></url></urlset>");
+//!     let entries: Vec<SiteMapEntry> = ["http://www.example.com/a.xml", "http://www.example.com/b.xml"]
+//!         .iter()
+//!         .map(|loc| SiteMapEntry::builder().loc(loc).build().unwrap())
+//!         .collect();
+//!     let counts = index::resolve_parallel(&entries, fetcher, |_, urls| Ok(urls.count()));
+//!     assert_eq!(counts.into_iter().collect::<Result<Vec<_>, _>>().unwrap(), vec![1, 1]);
+//! }
+//! # #[cfg(not(feature = "parallel"))]
+//! # fn main() {}
+//! ```
+#[cfg(feature = "parallel")]
+use crate::resolver::{Fetch, Resolve, SiteMapIndexResolver};
+#[cfg(feature = "parallel")]
+use crate::structs::{CanonicalizeOptions, SiteMapEntry, MAX_URLS_PER_SITEMAP};
+#[cfg(feature = "parallel")]
+use crate::util::{self, LocOptions};
+#[cfg(feature = "parallel")]
+use crate::Error;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+#[cfg(feature = "parallel")]
+use std::collections::HashSet;
+
+pub mod freshness;
+
+/// Options of `resolve_parallel`.
+#[cfg(feature = "parallel")]
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct ParallelOptions {
+    /// Maximum number of sitemaps fetched and read at the same time, 8 by
+    /// default.
+    pub threads: usize,
+    /// Maximum number of nested indexes, counting the index of the entries,
+    /// 1 by default like `SiteMapIndexResolver::max_depth`.
+    pub max_depth: usize,
+    /// Maximum number of urls of each child, 50000 sitemaps of 50000 urls by
+    /// default like `SiteMapIndexResolver::max_urls`.
+    pub max_urls: usize,
+}
+
+#[cfg(feature = "parallel")]
+impl Default for ParallelOptions {
+    fn default() -> ParallelOptions {
+        ParallelOptions {
+            threads: 8,
+            max_depth: 1,
+            max_urls: MAX_URLS_PER_SITEMAP * MAX_URLS_PER_SITEMAP,
+        }
+    }
+}
+
+/// Resolves the sitemaps of `entries` in parallel with the default options,
+/// see `resolve_parallel_with_options`.
+#[cfg(feature = "parallel")]
+pub fn resolve_parallel<F, H, T>(entries: &[SiteMapEntry], fetch: F, handle: H) -> Vec<Result<T, Error>>
+    where F: Fetch + Sync,
+          H: Fn(&SiteMapEntry, Resolve<'_, F>) -> Result<T, Error> + Sync,
+          T: Send
+{
+    resolve_parallel_with_options(entries, fetch, handle, &ParallelOptions::default())
+}
+
+/// Resolves the sitemaps of `entries`, the children of an index, on a
+/// rayon pool of `options.threads` threads.
+///
+/// `handle` is called for each entry with the urls of its sitemap, its
+/// result is at the index of the entry in the returned results. An entry
+/// without a valid location, or with the normalized location of a previous
+/// entry, is an error; the errors of a child never stop the others.
+#[cfg(feature = "parallel")]
+pub fn resolve_parallel_with_options<F, H, T>(entries: &[SiteMapEntry],
+                                              fetch: F,
+                                              handle: H,
+                                              options: &ParallelOptions)
+                                              -> Vec<Result<T, Error>>
+    where F: Fetch + Sync,
+          H: Fn(&SiteMapEntry, Resolve<'_, F>) -> Result<T, Error> + Sync,
+          T: Send
+{
+    // the children are nested once in the index of the entries
+    let resolver = SiteMapIndexResolver::new(fetch)
+        .max_depth(options.max_depth.saturating_sub(1))
+        .max_urls(options.max_urls);
+    let mut visited = HashSet::new();
+    let sitemaps: Vec<Result<_, Error>> = entries.iter()
+        .map(|entry| match entry.loc.get_url() {
+            Some(url) => {
+                let key = util::loc_key(&entry.loc.canonicalize(&CanonicalizeOptions::default()), &LocOptions::default());
+                if visited.insert(key) {
+                    Ok(url)
+                } else {
+                    Err(Error::Invalid(format!("sitemap {} already visited", url)))
+                }
+            }
+            None => {
+                let error = entry.errors().into_iter().next();
+                Err(error.unwrap_or_else(|| Error::Invalid("sitemap without loc".to_string())))
+            }
+        })
+        .collect();
+    let resolve = || {
+        sitemaps.into_par_iter()
+            .zip(entries)
+            .map(|(sitemap, entry)| sitemap.and_then(|url| handle(entry, resolver.resolve(&url))))
+            .collect()
+    };
+    // the global pool of rayon if the pool can not be built
+    match rayon::ThreadPoolBuilder::new().num_threads(options.threads.max(1)).build() {
+        Ok(pool) => pool.install(resolve),
+        Err(_) => resolve(),
+    }
+}
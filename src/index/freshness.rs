@@ -0,0 +1,79 @@
+//! Contains the freshness of the children of a sitemap index, from their
+//! modification times.
+//!
+//! The precision of the `<lastmod>` read is kept: a time is compared as is,
+//! a date only, `LastMod::Date`, has no time zone and is a whole day of
+//! possible modifications, until the end of the day in the last time zone,
+//! `-12:00`. `2024-05-01` is so more recent than any time before
+//! `2024-05-02T12:00:00Z`, not than midnight UTC only.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate sitemap;
+//! extern crate chrono;
+//! use chrono::DateTime;
+//! use sitemap::index::freshness;
+//! use sitemap::reader::{SiteMapEvent, SiteMapReader};
+//! fn main() {
+//!     let document = "<sitemapindex>\
+//!                     <sitemap><loc>http://www.example.com/a.xml</loc><lastmod>2024-05-01</lastmod></sitemap>\
+//!                     <sitemap><loc>http://www.example.com/b.xml</loc>\
+//!                     <lastmod>2024-05-01T10:00:00+00:00</lastmod></sitemap>\
+//!                     </sitemapindex>";
+//!     let index: Vec<_> = SiteMapReader::new(document.as_bytes())
+//!         .filter_map(|event| match event {
+//!             Ok(SiteMapEvent::SiteMap(sitemap)) => Some(sitemap),
+//!             _ => None,
+//!         })
+//!         .collect();
+//!     let since = DateTime::parse_from_rfc3339("2024-05-01T18:00:00+02:00").unwrap();
+//!     let stale = freshness::stale_children(&index, since);
+//!     assert_eq!(stale.len(), 1);
+//!     assert_eq!(stale[0].loc.as_str(), Some("http://www.example.com/a.xml"));
+//! }
+//! ```
+use crate::structs::{LastMod, SiteMapEntry};
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone};
+
+/// Offset of the last time zone to end a day, `-12:00`.
+const LAST_OFFSET: i32 = 12 * 3600;
+
+/// End of the day `date` in the last time zone, the latest time at which a
+/// child modified on this date may have been modified. `None` for the last
+/// date of chrono.
+pub fn end_of_day(date: NaiveDate) -> Option<DateTime<FixedOffset>> {
+    let midnight = date.succ_opt()?.and_hms_opt(0, 0, 0)?;
+    FixedOffset::west_opt(LAST_OFFSET)?.from_local_datetime(&midnight).single()
+}
+
+/// Offset of the first time zone to start a day, `+14:00`.
+const FIRST_OFFSET: i32 = 14 * 3600;
+
+/// Start of the day `date` in the first time zone, the earliest time at
+/// which an entry modified on this date may have been modified.
+pub(crate) fn start_of_day(date: NaiveDate) -> Option<DateTime<FixedOffset>> {
+    let midnight = date.and_hms_opt(0, 0, 0)?;
+    FixedOffset::east_opt(FIRST_OFFSET)?.from_local_datetime(&midnight).single()
+}
+
+/// Whether the child `entry` may have been modified after `since`.
+///
+/// A time is compared to `since`, a date only is stale until its end of
+/// day, see `end_of_day`, and a child without a valid `<lastmod>` is
+/// always stale.
+pub fn is_stale(entry: &SiteMapEntry, since: &DateTime<FixedOffset>) -> bool {
+    match entry.lastmod {
+        LastMod::DateTime(ref time) => time > since,
+        LastMod::W3c(ref time) => time.time > *since,
+        LastMod::NonStandard(ref time) => time.time > *since,
+        LastMod::Date(date) => end_of_day(date).is_none_or(|end| end > *since),
+        LastMod::None | LastMod::Err(_) => true,
+    }
+}
+
+/// Children of `index` that may have been modified after `since`, to read
+/// again, in their order, see `is_stale`.
+pub fn stale_children(index: &[SiteMapEntry], since: DateTime<FixedOffset>) -> Vec<&SiteMapEntry> {
+    index.iter().filter(|entry| is_stale(entry, &since)).collect()
+}
@@ -6,18 +6,471 @@ extern crate xml;
 extern crate url;
 extern crate chrono_utils;
 extern crate chrono;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(feature = "gzip")]
+extern crate flate2;
+#[cfg(feature = "async")]
+extern crate tokio;
+#[cfg(any(feature = "async", feature = "borrowed", feature = "fast-xml"))]
+extern crate quick_xml;
+#[cfg(feature = "async")]
+extern crate futures_core;
+#[cfg(feature = "http")]
+extern crate reqwest;
+#[cfg(feature = "encodings")]
+extern crate encoding_rs;
+#[cfg(all(feature = "http", target_arch = "wasm32"))]
+compile_error!("the http feature uses the blocking reqwest client, not available on wasm32");
+#[cfg(all(feature = "parallel", target_arch = "wasm32"))]
+compile_error!("the parallel feature uses threads, not available on wasm32");
 pub mod structs;
 pub mod reader;
 pub mod writer;
+pub mod resolver;
+pub mod robots;
+pub mod diff;
+pub mod merge;
+pub mod stats;
+pub mod fingerprint;
+pub mod filter;
+pub mod split;
+pub mod util;
+pub mod validate;
+pub mod document;
+#[cfg(any(feature = "async", feature = "fast-xml"))]
+pub mod push;
+#[cfg(feature = "fs")]
+pub mod publish;
+#[cfg(feature = "http")]
+pub mod fetch;
+#[cfg(feature = "http")]
+pub mod ping;
+#[cfg(feature = "encodings")]
+pub mod encoding;
+#[cfg(feature = "borrowed")]
+pub mod borrowed;
+pub mod index;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "async")]
+pub mod async_reader;
+#[cfg(feature = "async")]
+pub mod async_writer;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(any(feature = "async", feature = "fast-xml"))]
+mod quick;
 
+use std::error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Sitemap error.
 #[derive(Debug)]
 pub enum Error {
+    /// XML write error.
     XmlWriteError(xml::writer::Error),
+    /// XML read error.
+    XmlReadError(xml::reader::Error),
+    /// I/O error.
+    Io(io::Error),
+    /// Url parse error.
+    Url(url::ParseError),
+    /// Invalid value of the element `tag`.
+    InvalidValue {
+        /// Name of the element being processed.
+        tag: String,
+        /// Raw value of the element.
+        value: String,
+        /// Description of the problem.
+        message: String,
+    },
+    /// Entry built with invalid fields.
+    Build(BuildError),
+    /// Generic validation error.
     Invalid(String),
+    /// Invalid content at a position of the document.
+    Parse {
+        /// Line number, starting from 1.
+        line: u64,
+        /// Column number, starting from 1.
+        column: u64,
+        /// Description of the problem.
+        message: String,
+    },
+    /// Element of an entry without one of its required children.
+    MissingElement {
+        /// Position of the element in the document, when known.
+        position: Option<structs::Position>,
+        /// Name of the element.
+        element: &'static str,
+        /// Name of the missing child.
+        child: &'static str,
+    },
+    /// Sitemap over one of the protocol limits.
+    LimitExceeded {
+        /// Limit exceeded.
+        kind: LimitKind,
+        /// Value of the limit.
+        limit: u64,
+    },
+    /// Unsuccessful HTTP response.
+    HttpStatus {
+        /// Requested url.
+        url: String,
+        /// Status code of the response.
+        status: u16,
+    },
+    /// Document over one of the resource limits of the reader.
+    ResourceLimit {
+        /// Limit exceeded.
+        which: ResourceKind,
+        /// Value of the limit.
+        limit: u64,
+    },
+    /// Document not starting with XML.
+    NotXml {
+        /// First bytes of the document.
+        bytes: Vec<u8>,
+    },
+    /// Response with a content type other than XML.
+    ContentType {
+        /// Requested url.
+        url: String,
+        /// Content type of the response.
+        content_type: String,
+    },
+    /// Entry written without a valid location.
+    MissingLoc {
+        /// Index of the entry among the ones passed to the writer, from 0.
+        index: usize,
+    },
+    /// Root element started after the end of the document.
+    DocumentEnded,
+    /// Entry written with the location of a previous one.
+    DuplicateLoc {
+        /// Index of the entry among the ones passed to the writer, from 0.
+        index: usize,
+        /// Location of the entry.
+        loc: String,
+    },
+    /// Entry refused by the writer for an invalid field.
+    InvalidEntry {
+        /// Index of the entry among the ones passed to the writer, from 0.
+        index: usize,
+        /// Name of the invalid field.
+        field: &'static str,
+        /// Description of the problem.
+        reason: String,
+    },
+    /// Writer method called when the state of the writer does not allow it.
+    State(&'static str),
+    /// Relative location not resolved against the base url.
+    Resolve {
+        /// Text of the location.
+        reference: String,
+        /// Cause of the failure.
+        error: ResolveError,
+    },
+    /// Error reading or writing the file at `path`.
+    File {
+        /// Path of the file.
+        path: PathBuf,
+        /// Error of the file.
+        error: Box<Error>,
+    },
+}
+
+/// Protocol limit of a sitemap.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum LimitKind {
+    /// Number of urls.
+    UrlCount,
+    /// Size in bytes of the uncompressed document.
+    Size,
+    /// Number of url and sitemap entries, see
+    /// `reader::ReaderOptions::max_entries`.
+    EntryCount,
+}
+
+/// Resource limit of the reader, see `reader::ReaderOptions`.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum ResourceKind {
+    /// Nesting of the elements.
+    Depth,
+    /// Length in bytes of the text of an element.
+    TextLength,
+    /// Number of bytes of the document.
+    Bytes,
+}
+
+/// Failure of the resolution of a relative location against a base url,
+/// see `structs::Location::resolve`.
+#[derive(Debug,Clone,PartialEq)]
+pub enum ResolveError {
+    /// Location not joined to the base url.
+    Parse(url::ParseError),
+    /// Location resolved to a url of another host than the base url.
+    OtherHost(url::Url),
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ResolveError::Parse(ref err) => write!(f, "{}", err),
+            ResolveError::OtherHost(ref url) => {
+                write!(f, "resolved to {} on another host than the base url", url)
+            }
+        }
+    }
+}
+
+impl error::Error for ResolveError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            ResolveError::Parse(ref err) => Some(err),
+            ResolveError::OtherHost(_) => None,
+        }
+    }
+}
+
+impl Error {
+    /// Creates a new `Error::InvalidValue`.
+    pub fn invalid_value<T, V, M>(tag: T, value: V, message: M) -> Error
+        where T: Into<String>,
+              V: Into<String>,
+              M: Into<String>
+    {
+        Error::InvalidValue {
+            tag: tag.into(),
+            value: value.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Creates a new `Error::File` of `error` at `path`.
+    #[cfg(feature = "fs")]
+    pub(crate) fn file<P: Into<PathBuf>>(path: P, error: Error) -> Error {
+        Error::File {
+            path: path.into(),
+            error: Box::new(error),
+        }
+    }
+
+    /// Position in the document of reader errors.
+    pub fn position(&self) -> Option<structs::Position> {
+        match *self {
+            Error::XmlReadError(ref err) => {
+                let position = xml::common::Position::position(err);
+                Some(structs::Position {
+                    line: position.row + 1,
+                    column: position.column + 1,
+                })
+            }
+            Error::Parse { line, column, .. } => Some(structs::Position { line, column }),
+            Error::MissingElement { position, .. } => position,
+            Error::File { ref error, .. } => error.position(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::XmlWriteError(ref err) => write!(f, "XML write error: {}", err),
+            Error::XmlReadError(ref err) => write!(f, "XML read error: {}", err),
+            Error::Io(ref err) => write!(f, "I/O error: {}", err),
+            Error::Url(ref err) => write!(f, "Url parse error: {}", err),
+            Error::InvalidValue { ref tag, ref value, ref message } => {
+                write!(f, "Invalid <{}> value '{}': {}", tag, value, message)
+            }
+            Error::Build(ref err) => write!(f, "{}", err),
+            Error::Invalid(ref message) => write!(f, "{}", message),
+            Error::Parse { line, column, ref message } => {
+                write!(f, "{}:{} {}", line, column, message)
+            }
+            Error::MissingElement { position: Some(position), element, child } => {
+                write!(f, "{}:{} {} element without {}", position.line, position.column, element, child)
+            }
+            Error::MissingElement { position: None, element, child } => {
+                write!(f, "{} element without {}", element, child)
+            }
+            Error::LimitExceeded { kind: LimitKind::UrlCount, limit } => {
+                write!(f, "Sitemap over the limit of {} urls", limit)
+            }
+            Error::LimitExceeded { kind: LimitKind::Size, limit } => {
+                write!(f, "Sitemap over the limit of {} bytes", limit)
+            }
+            Error::LimitExceeded { kind: LimitKind::EntryCount, limit } => {
+                write!(f, "Sitemap over the limit of {} entries", limit)
+            }
+            Error::ResourceLimit { which: ResourceKind::Depth, limit } => {
+                write!(f, "Elements nested over the limit of {} levels", limit)
+            }
+            Error::ResourceLimit { which: ResourceKind::TextLength, limit } => {
+                write!(f, "Element text over the limit of {} bytes", limit)
+            }
+            Error::ResourceLimit { which: ResourceKind::Bytes, limit } => {
+                write!(f, "Document over the limit of {} bytes", limit)
+            }
+            Error::NotXml { ref bytes } => {
+                write!(f, "Not an XML document, starting with {:?}", String::from_utf8_lossy(bytes))
+            }
+            Error::HttpStatus { ref url, status } => {
+                write!(f, "HTTP status {} fetching {}", status, url)
+            }
+            Error::ContentType { ref url, ref content_type } => {
+                write!(f, "Unexpected content type '{}' fetching {}", content_type, url)
+            }
+            Error::MissingLoc { index } => write!(f, "Entry {} without a valid loc", index),
+            Error::DocumentEnded => {
+                write!(f, "Root element after the end of the document, rotate the writer to a new stream")
+            }
+            Error::DuplicateLoc { index, ref loc } => {
+                write!(f, "Entry {} with the duplicate loc {}", index, loc)
+            }
+            Error::InvalidEntry { index, field, ref reason } => {
+                write!(f, "Entry {} has an invalid {}: {}", index, field, reason)
+            }
+            Error::State(message) => write!(f, "Invalid writer state: {}", message),
+            Error::Resolve { ref reference, ref error } => {
+                write!(f, "Cannot resolve the loc '{}': {}", reference, error)
+            }
+            Error::File { ref path, ref error } => write!(f, "{}: {}", path.display(), error),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::XmlWriteError(ref err) => Some(err),
+            Error::XmlReadError(ref err) => Some(err),
+            Error::Io(ref err) => Some(err),
+            Error::Url(ref err) => Some(err),
+            Error::Build(ref err) => Some(err),
+            Error::InvalidValue { .. } => None,
+            Error::Invalid(_) => None,
+            Error::Parse { .. } => None,
+            Error::MissingElement { .. } => None,
+            Error::LimitExceeded { .. } => None,
+            Error::ResourceLimit { .. } => None,
+            Error::NotXml { .. } => None,
+            Error::HttpStatus { .. } => None,
+            Error::ContentType { .. } => None,
+            Error::MissingLoc { .. } => None,
+            Error::DocumentEnded => None,
+            Error::DuplicateLoc { .. } => None,
+            Error::InvalidEntry { .. } => None,
+            Error::State(_) => None,
+            Error::Resolve { ref error, .. } => Some(error),
+            Error::File { ref error, .. } => Some(&**error),
+        }
+    }
+}
+
+impl From<BuildError> for Error {
+    fn from(err: BuildError) -> Error {
+        Error::Build(err)
+    }
 }
 
 impl From<xml::writer::Error> for Error {
     fn from(err: xml::writer::Error) -> Error {
-        Error::XmlWriteError(err)
+        match err {
+            xml::writer::Error::Io(err) => Error::Io(err),
+            err => Error::XmlWriteError(err),
+        }
     }
 }
+
+impl From<xml::reader::Error> for Error {
+    fn from(err: xml::reader::Error) -> Error {
+        Error::XmlReadError(err)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+impl From<url::ParseError> for Error {
+    fn from(err: url::ParseError) -> Error {
+        Error::Url(err)
+    }
+}
+
+/// Invalid field of an entry.
+#[derive(Debug,Clone,PartialEq)]
+pub struct FieldError {
+    field: &'static str,
+    value: Option<String>,
+    message: String,
+}
+
+impl FieldError {
+    /// Creates a new field error.
+    pub fn new<M: Into<String>>(field: &'static str, value: Option<&str>, message: M) -> FieldError {
+        FieldError {
+            field,
+            value: value.map(|value| value.to_string()),
+            message: message.into(),
+        }
+    }
+    /// Name of the invalid field.
+    pub fn field(&self) -> &'static str {
+        self.field
+    }
+    /// Rejected value, `None` when the field is missing.
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+    /// Description of the problem.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.value {
+            Some(ref value) => write!(f, "{} '{}': {}", self.field, value, self.message),
+            None => write!(f, "{}: {}", self.field, self.message),
+        }
+    }
+}
+
+/// Error returned by the entry builders, lists every invalid field.
+#[derive(Debug,Clone,PartialEq)]
+pub struct BuildError {
+    errors: Vec<FieldError>,
+}
+
+impl BuildError {
+    /// Creates a new build error.
+    pub fn new(errors: Vec<FieldError>) -> BuildError {
+        BuildError { errors }
+    }
+    /// Invalid fields, in the order they were set.
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid entry")?;
+        for (i, error) in self.errors.iter().enumerate() {
+            write!(f, "{} {}", if i == 0 { ":" } else { ";" }, error)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for BuildError {}
@@ -0,0 +1,284 @@
+//! Contains the merging of the urls of several sitemaps.
+//!
+//! Sources are interleaved, taking one entry from each source in turn, and
+//! entries with the same normalized location are merged following the
+//! `ConflictPolicy`. Over the protocol limits, the merged urls can be written
+//! with `writer::SplitSiteMapWriter`, or the urls over `MergeOptions::max_urls`
+//! taken with `Merge::into_remaining`.
+//!
+//! `merge` streams the entries with `ConflictPolicy::FirstWins`, the default,
+//! and keeps every merged entry in memory with the other policies.
+//! `merge_reopening` reads the sources twice instead, keeping only the
+//! locations, modification times and priorities in memory in the first
+//! pass, and streaming the entries kept in the second one.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate sitemap;
+//! use sitemap::merge::{self, MergeOptions};
+//! use sitemap::structs::UrlEntry;
+//! fn main() {
+//!     let first = vec![
+//!         UrlEntry::builder().loc("http://www.example.com/").build().unwrap(),
+//!         UrlEntry::builder().loc("http://www.example.com/a").build().unwrap(),
+//!     ];
+//!     let second = vec![UrlEntry::builder().loc("http://www.example.com/").build().unwrap()];
+//!     let sources = vec![first.into_iter(), second.into_iter()];
+//!     let merged: Vec<UrlEntry> = merge::merge(sources, &MergeOptions::default()).collect();
+//!     assert_eq!(merged.len(), 2);
+//! }
+//! ```
+use crate::structs::{CanonicalizeOptions, Priority, UrlEntry};
+use crate::util::{self, LocOptions};
+use chrono::{DateTime, FixedOffset};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::vec;
+
+/// Entry kept among the entries with the same location.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum ConflictPolicy {
+    /// The first entry read. Entries are merged while they are read.
+    FirstWins,
+    /// The entry with the most recent `lastmod`, then the one with the
+    /// highest priority. Every source is read before the first entry, see
+    /// `merge_reopening`.
+    FreshestWins,
+    /// The entry with the highest priority, then the one with the most
+    /// recent `lastmod`. Every source is read before the first entry, see
+    /// `merge_reopening`.
+    HighestPriorityWins,
+}
+
+/// Options of `merge`.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct MergeOptions {
+    /// Entry kept among the entries with the same location,
+    /// `ConflictPolicy::FirstWins` by default.
+    pub policy: ConflictPolicy,
+    /// Maximum number of merged entries, unlimited by default. The entries
+    /// over it are returned by `Merge::into_remaining`.
+    pub max_urls: Option<usize>,
+    /// Canonicalization of the locations before comparing them.
+    pub canonicalize: CanonicalizeOptions,
+    /// Comparison of the canonical locations.
+    pub loc: LocOptions,
+}
+
+impl Default for MergeOptions {
+    fn default() -> MergeOptions {
+        MergeOptions {
+            policy: ConflictPolicy::FirstWins,
+            max_urls: None,
+            canonicalize: CanonicalizeOptions::default(),
+            loc: LocOptions::default(),
+        }
+    }
+}
+
+/// Merges the entries of `sources`.
+///
+/// With `ConflictPolicy::FirstWins` only the normalized locations are kept
+/// in memory, with the other policies every merged entry is, read from all
+/// the sources before the first one is returned. Merged entries are in the
+/// order of the first entry of their location.
+pub fn merge<I>(sources: Vec<I>, options: &MergeOptions) -> Merge<I>
+    where I: Iterator<Item = UrlEntry>
+{
+    Merge {
+        sources,
+        next_source: 0,
+        options: *options,
+        seen: HashSet::new(),
+        resolved: None,
+        winners: None,
+        next_winner: 0,
+        read: 0,
+        count: 0,
+    }
+}
+
+/// Merges the entries of the sources returned by `open`, called twice and
+/// returning the same entries each time, for example by opening the same
+/// files again.
+///
+/// The first sources are read to find the entry kept for each location,
+/// keeping only its normalized location, its modification time and its
+/// priority, then the entries kept are streamed from the second sources,
+/// in their order. Merged entries are the ones of `merge`.
+pub fn merge_reopening<F, I>(mut open: F, options: &MergeOptions) -> Merge<I>
+    where F: FnMut() -> Vec<I>,
+          I: Iterator<Item = UrlEntry>
+{
+    let winners = merge(open(), options).winners();
+    let mut merged = merge(open(), options);
+    merged.winners = Some(winners);
+    merged
+}
+
+/// Fields of an entry compared by the policies, with its index among the
+/// entries read.
+struct Rank {
+    index: usize,
+    lastmod: Option<DateTime<FixedOffset>>,
+    priority: Priority,
+}
+
+/// Iterator over the merged entries, returned by `merge`.
+pub struct Merge<I: Iterator<Item = UrlEntry>> {
+    sources: Vec<I>,
+    next_source: usize,
+    options: MergeOptions,
+    seen: HashSet<String>,
+    resolved: Option<vec::IntoIter<UrlEntry>>,
+    /// Indexes among the entries read of the entries kept, in order, with
+    /// `merge_reopening`.
+    winners: Option<Vec<usize>>,
+    next_winner: usize,
+    /// Number of entries read from the sources.
+    read: usize,
+    count: usize,
+}
+
+impl<I: Iterator<Item = UrlEntry>> Merge<I> {
+    /// Next entry of the sources, in turn.
+    fn next_entry(&mut self) -> Option<UrlEntry> {
+        while !self.sources.is_empty() {
+            if self.next_source >= self.sources.len() {
+                self.next_source = 0;
+            }
+            match self.sources[self.next_source].next() {
+                Some(entry) => {
+                    self.next_source += 1;
+                    return Some(entry);
+                }
+                None => {
+                    self.sources.remove(self.next_source);
+                }
+            }
+        }
+        None
+    }
+
+    /// Merged entries from the sources, uncapped, starting after the last
+    /// one returned: the entries over `MergeOptions::max_urls`, to write
+    /// in another sitemap.
+    pub fn into_remaining(mut self) -> Merge<I> {
+        self.options.max_urls = None;
+        self
+    }
+
+    fn key(&self, entry: &UrlEntry) -> String {
+        util::loc_key(&entry.loc.canonicalize(&self.options.canonicalize), &self.options.loc)
+    }
+
+    /// Reads every source, keeping the entries following the policy.
+    fn resolve(&mut self) -> Vec<UrlEntry> {
+        let mut merged: Vec<UrlEntry> = Vec::new();
+        let mut positions = HashMap::new();
+        while let Some(entry) = self.next_entry() {
+            let key = self.key(&entry);
+            match positions.get(&key) {
+                Some(&position) => {
+                    if self.compare(&entry, &merged[position]) == Ordering::Greater {
+                        merged[position] = entry;
+                    }
+                }
+                None => {
+                    positions.insert(key, merged.len());
+                    merged.push(entry);
+                }
+            }
+        }
+        merged
+    }
+
+    /// Reads every source, returning the indexes of the entries kept
+    /// following the policy, in order.
+    fn winners(&mut self) -> Vec<usize> {
+        let mut kept: HashMap<String, Rank> = HashMap::new();
+        let mut index = 0;
+        while let Some(entry) = self.next_entry() {
+            let key = self.key(&entry);
+            let rank = Rank {
+                index,
+                lastmod: entry.lastmod.get_time(),
+                priority: entry.priority,
+            };
+            index += 1;
+            match kept.get_mut(&key) {
+                Some(winner) => {
+                    if self.compare_ranks(&rank, winner) == Ordering::Greater {
+                        *winner = rank;
+                    }
+                }
+                None => {
+                    kept.insert(key, rank);
+                }
+            }
+        }
+        let mut winners: Vec<usize> = kept.into_values().map(|rank| rank.index).collect();
+        winners.sort_unstable();
+        winners
+    }
+
+    /// Next entry kept of the sources read again by `merge_reopening`.
+    fn next_winner(&mut self) -> Option<UrlEntry> {
+        loop {
+            let winner = *self.winners.as_ref()?.get(self.next_winner)?;
+            let entry = self.next_entry()?;
+            self.read += 1;
+            if self.read - 1 == winner {
+                self.next_winner += 1;
+                return Some(entry);
+            }
+        }
+    }
+
+    fn compare(&self, a: &UrlEntry, b: &UrlEntry) -> Ordering {
+        let rank = |entry: &UrlEntry| Rank {
+            index: 0,
+            lastmod: entry.lastmod.get_time(),
+            priority: entry.priority.clone(),
+        };
+        self.compare_ranks(&rank(a), &rank(b))
+    }
+
+    fn compare_ranks(&self, a: &Rank, b: &Rank) -> Ordering {
+        let lastmod = || a.lastmod.cmp(&b.lastmod);
+        let priority = || a.priority.cmp(&b.priority);
+        match self.options.policy {
+            ConflictPolicy::FirstWins => Ordering::Equal,
+            ConflictPolicy::FreshestWins => lastmod().then_with(priority),
+            ConflictPolicy::HighestPriorityWins => priority().then_with(lastmod),
+        }
+    }
+}
+
+impl<I: Iterator<Item = UrlEntry>> Iterator for Merge<I> {
+    type Item = UrlEntry;
+    fn next(&mut self) -> Option<UrlEntry> {
+        if self.options.max_urls.is_some_and(|max_urls| self.count >= max_urls) {
+            return None;
+        }
+        let entry = if self.winners.is_some() {
+            self.next_winner()?
+        } else if self.options.policy == ConflictPolicy::FirstWins {
+            loop {
+                let entry = self.next_entry()?;
+                let key = self.key(&entry);
+                if self.seen.insert(key) {
+                    break entry;
+                }
+            }
+        } else {
+            if self.resolved.is_none() {
+                self.resolved = Some(self.resolve().into_iter());
+            }
+            self.resolved.as_mut()?.next()?
+        };
+        self.count += 1;
+        Some(entry)
+    }
+}
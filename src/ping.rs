@@ -0,0 +1,121 @@
+//! Contains the submission of sitemaps to search engines, enabled by the
+//! `http` feature.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! extern crate sitemap;
+//! extern crate url;
+//! use sitemap::ping::{self, Engine};
+//! use url::Url;
+//! fn main() {
+//!     let sitemap_url = Url::parse("http://www.example.com/sitemap.xml").unwrap();
+//!     for result in ping::ping(&[Engine::Google, Engine::Bing], &sitemap_url) {
+//!         println!("{:?} {:?} in {:?}", result.engine, result.status, result.latency);
+//!     }
+//! }
+//! ```
+use crate::fetch::USER_AGENT;
+use crate::Error;
+use reqwest::header;
+use std::io;
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// Timeout of each ping request.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Search engine accepting sitemap pings.
+#[derive(Debug,Clone,PartialEq)]
+pub enum Engine {
+    /// Google, `https://www.google.com/ping`.
+    Google,
+    /// Bing, `https://www.bing.com/ping`.
+    Bing,
+    /// Other engine, the sitemap url is added to the query as the `sitemap`
+    /// parameter.
+    Custom(Url),
+}
+
+impl Engine {
+    /// Url pinging the engine for `sitemap_url`.
+    pub fn ping_url(&self, sitemap_url: &Url) -> Url {
+        let mut url = match *self {
+            Engine::Google => Url::parse("https://www.google.com/ping").unwrap(),
+            Engine::Bing => Url::parse("https://www.bing.com/ping").unwrap(),
+            Engine::Custom(ref url) => url.clone(),
+        };
+        url.query_pairs_mut().append_pair("sitemap", sitemap_url.as_str());
+        url
+    }
+}
+
+/// Outcome of the ping of an engine.
+#[derive(Debug)]
+pub struct PingResult {
+    /// Engine pinged.
+    pub engine: Engine,
+    /// HTTP status of the response, the error when no response was received.
+    pub status: Result<u16, Error>,
+    /// Time until the response or the error.
+    pub latency: Duration,
+}
+
+impl PingResult {
+    /// Whether the engine answered with a success status.
+    pub fn is_success(&self) -> bool {
+        match self.status {
+            Ok(status) => (200..300).contains(&status),
+            Err(_) => false,
+        }
+    }
+}
+
+fn io_error(error: reqwest::Error) -> Error {
+    Error::Io(io::Error::other(error))
+}
+
+/// Pings each engine in turn with a blocking client.
+pub fn ping(engines: &[Engine], sitemap_url: &Url) -> Vec<PingResult> {
+    let client = reqwest::blocking::Client::new();
+    engines.iter()
+        .map(|engine| {
+            let start = Instant::now();
+            let status = client.get(engine.ping_url(sitemap_url).as_str())
+                .timeout(TIMEOUT)
+                .header(header::USER_AGENT, USER_AGENT)
+                .send()
+                .map(|response| response.status().as_u16())
+                .map_err(io_error);
+            PingResult {
+                engine: engine.clone(),
+                status,
+                latency: start.elapsed(),
+            }
+        })
+        .collect()
+}
+
+/// Pings each engine in turn, the asynchronous version of `ping` enabled
+/// also by the `async` feature.
+#[cfg(feature = "async")]
+pub async fn ping_async(engines: &[Engine], sitemap_url: &Url) -> Vec<PingResult> {
+    let client = reqwest::Client::new();
+    let mut results = Vec::new();
+    for engine in engines {
+        let start = Instant::now();
+        let status = client.get(engine.ping_url(sitemap_url).as_str())
+            .timeout(TIMEOUT)
+            .header(header::USER_AGENT, USER_AGENT)
+            .send()
+            .await
+            .map(|response| response.status().as_u16())
+            .map_err(io_error);
+        results.push(PingResult {
+            engine: engine.clone(),
+            status,
+            latency: start.elapsed(),
+        });
+    }
+    results
+}
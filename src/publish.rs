@@ -0,0 +1,159 @@
+//! Contains the atomic publication of the files of a split sitemap.
+//!
+//! `AtomicPublisher` creates the sitemaps and their index in a temporary
+//! directory of the target directory, then renames them over the published
+//! files, the index last, so the published index never references sitemaps
+//! of an unfinished run.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate sitemap;
+//! use sitemap::publish::AtomicPublisher;
+//! use sitemap::structs::UrlEntry;
+//! use sitemap::writer::SplitSiteMapWriter;
+//! use std::env;
+//! use std::fs;
+//! use std::io::Write;
+//! fn main() {
+//!     let target = env::temp_dir().join(format!("sitemap-publish-{}", std::process::id()));
+//!     fs::create_dir_all(&target).unwrap();
+//!     let publisher = AtomicPublisher::new(&target, "sitemap_index.xml").unwrap();
+//!     let mut writer = SplitSiteMapWriter::new(|number| publisher.create(&format!("sitemap-{}.xml", number)),
+//!                                              "http://www.example.com/sitemap-{}.xml")
+//!         .unwrap();
+//!     writer.url(UrlEntry::builder().loc("http://www.example.com/").build().unwrap()).unwrap();
+//!     writer.end(publisher.create("sitemap_index.xml").unwrap()).unwrap().flush().unwrap();
+//!     let removed = publisher.publish().unwrap();
+//!     assert!(removed.is_empty());
+//!     assert!(target.join("sitemap-1.xml").exists());
+//!     fs::remove_dir_all(&target).unwrap();
+//! }
+//! ```
+use crate::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Number of the temporary directories created by this process.
+static PUBLISHERS: AtomicUsize = AtomicUsize::new(0);
+
+/// Error of the I/O operation on the file at `path`.
+fn io<T>(path: &Path, result: io::Result<T>) -> Result<T, Error> {
+    result.map_err(|error| Error::file(path, Error::Io(error)))
+}
+
+/// Whether `name` is the name of a file, without directories.
+fn is_file_name(name: &str) -> bool {
+    Path::new(name).file_name().is_some_and(|file_name| file_name == name)
+}
+
+/// Publisher of the files of a run of a split writer.
+///
+/// The files are created by `create` in a temporary directory and moved to
+/// the target directory by `publish`. The temporary directory is removed
+/// when the publisher is dropped without publishing.
+pub struct AtomicPublisher {
+    target: PathBuf,
+    temp: PathBuf,
+    index: String,
+    stale_prefix: String,
+}
+
+impl AtomicPublisher {
+    /// Creates a new publisher of the files of `target`, `index` being the
+    /// name of the sitemap index.
+    ///
+    /// The temporary directory is created in `target`, so the files are
+    /// moved by renames of the same file system. Its name is unique to the
+    /// process and to the publisher, the creation fails when it already
+    /// exists, an existing directory is never removed.
+    pub fn new(target: &Path, index: &str) -> Result<AtomicPublisher, Error> {
+        if !is_file_name(index) {
+            return Err(Error::Invalid(format!("index '{}' is not a file name", index)));
+        }
+        let number = PUBLISHERS.fetch_add(1, Ordering::Relaxed);
+        let temp = target.join(format!(".publish-{}-{}", process::id(), number));
+        io(&temp, fs::create_dir(&temp))?;
+        Ok(AtomicPublisher {
+            target: target.to_path_buf(),
+            temp,
+            index: index.to_string(),
+            stale_prefix: "sitemap-".to_string(),
+        })
+    }
+
+    /// Prefix of the stale sitemaps removed by `publish`, `sitemap-` by
+    /// default.
+    pub fn stale_prefix(mut self, prefix: &str) -> AtomicPublisher {
+        self.stale_prefix = prefix.to_string();
+        self
+    }
+
+    /// Creates the file `name` in the temporary directory, to be used by
+    /// the factory of a `SplitSiteMapWriter` and for the index.
+    ///
+    /// The file is buffered, the index has to be flushed once written.
+    pub fn create(&self, name: &str) -> io::Result<BufWriter<File>> {
+        if !is_file_name(name) {
+            let message = format!("'{}' is not a file name", name);
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+        Ok(BufWriter::new(File::create(self.temp.join(name))?))
+    }
+
+    /// Moves the created files to the target directory and removes the
+    /// stale sitemaps, returning their paths.
+    ///
+    /// The files are synced to the disk, then renamed over the published
+    /// ones in the order of their names, the index last. The stale
+    /// sitemaps are the files of the target directory with the prefix of
+    /// `stale_prefix` and `.xml` in their name not created by this run.
+    ///
+    /// Fails without renaming any file when the index was not created. On
+    /// errors the temporary directory is removed, the files renamed before
+    /// the error being already published.
+    pub fn publish(self) -> Result<Vec<PathBuf>, Error> {
+        let mut names = Vec::new();
+        for entry in io(&self.temp, fs::read_dir(&self.temp))? {
+            let name = io(&self.temp, entry)?.file_name();
+            names.push(name.to_string_lossy().into_owned());
+        }
+        names.sort();
+        let index = match names.iter().position(|name| *name == self.index) {
+            Some(index) => names.remove(index),
+            None => return Err(Error::Invalid(format!("index {} not created", self.index))),
+        };
+        names.push(index);
+        for name in &names {
+            let path = self.temp.join(name);
+            io(&path, OpenOptions::new().write(true).open(&path).and_then(|file| file.sync_all()))?;
+        }
+        for name in &names {
+            io(&self.target.join(name), fs::rename(self.temp.join(name), self.target.join(name)))?;
+        }
+        #[cfg(unix)]
+        io(&self.target, File::open(&self.target).and_then(|directory| directory.sync_all()))?;
+        io(&self.temp, fs::remove_dir(&self.temp))?;
+        let mut removed = Vec::new();
+        for entry in io(&self.target, fs::read_dir(&self.target))? {
+            let entry = io(&self.target, entry)?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with(&self.stale_prefix) && name.contains(".xml") && !names.contains(&name) &&
+               io(&entry.path(), entry.file_type())?.is_file() {
+                io(&entry.path(), fs::remove_file(entry.path()))?;
+                removed.push(entry.path());
+            }
+        }
+        removed.sort();
+        Ok(removed)
+    }
+}
+
+impl Drop for AtomicPublisher {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.temp);
+    }
+}
@@ -0,0 +1,353 @@
+//! Contains the push parser of sitemaps, enabled by the `fast-xml` and
+//! `async` features.
+//!
+//! The document is given in chunks of any size, as they are received from
+//! the network, and each chunk returns the entries it completes. A chunk
+//! may end anywhere, inside an element, an attribute or a multi-byte UTF-8
+//! character: the bytes after the last complete markup are kept until the
+//! next chunk.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate sitemap;
+//! use sitemap::push::PushSiteMapParser;
+//! use sitemap::reader::SiteMapEvent;
+//! fn main() {
+//!     let document = "<urlset><url><loc>http://www.example.com/\u{e9}t\u{e9}</loc></url></urlset>";
+//!     let mut parser = PushSiteMapParser::new();
+//!     let mut urls = Vec::new();
+//!     for chunk in document.as_bytes().chunks(3) {
+//!         for event in parser.feed(chunk).expect("valid sitemap") {
+//!             if let SiteMapEvent::Url(url) = event {
+//!                 urls.push(url);
+//!             }
+//!         }
+//!     }
+//!     assert!(parser.finish().expect("valid sitemap").is_empty());
+//!     assert_eq!(urls[0].loc.as_str(), Some("http://www.example.com/%C3%A9t%C3%A9"));
+//! }
+//! ```
+use crate::quick::{self, parse_error, PositionTracker, Tokens};
+use crate::reader::{DocumentKind, ReaderOptions, ReaderState, SiteMapEvent, SourceLimits, Warning};
+use crate::structs;
+use crate::Error;
+use quick_xml::NsReader;
+use std::io::{self, BufRead, Read};
+
+/// UTF-8 byte order mark.
+const BOM: &[u8] = &[0xef, 0xbb, 0xbf];
+
+/// Markup scanned at the end of the bytes fed, to find where the last
+/// complete markup ends, as quick-xml reads it.
+#[derive(Debug,Clone,Copy)]
+enum Markup {
+    Text,
+    /// After `<`.
+    Open,
+    /// Inside a tag, in the quoted value of an attribute if any.
+    Tag(Option<u8>),
+    /// After `<!`.
+    Bang,
+    /// After `<!-`.
+    CommentStart,
+    /// Inside a comment, after up to two `-`.
+    Comment(u8),
+    /// Inside a CDATA section, after up to two `]`.
+    CData(u8),
+    /// Inside a processing instruction, after a `?` or not.
+    Instruction(bool),
+    /// Inside a `<!DOCTYPE>`, with the number of its `<` not closed by a
+    /// `>`.
+    DocType(usize),
+}
+
+impl Markup {
+    /// Markup after `byte`, `Text` after the byte ending a markup.
+    fn next(self, byte: u8) -> Markup {
+        match (self, byte) {
+            (Markup::Text, b'<') => Markup::Open,
+            (Markup::Text, _) => Markup::Text,
+            (Markup::Open, b'!') => Markup::Bang,
+            (Markup::Open, b'?') => Markup::Instruction(false),
+            (Markup::Open, _) | (Markup::Tag(None), _) => {
+                match byte {
+                    b'>' => Markup::Text,
+                    b'"' | b'\'' => Markup::Tag(Some(byte)),
+                    _ => Markup::Tag(None),
+                }
+            }
+            (Markup::Tag(Some(quote)), _) if byte == quote => Markup::Tag(None),
+            (Markup::Tag(_), _) => self,
+            (Markup::Bang, b'-') => Markup::CommentStart,
+            (Markup::Bang, b'[') => Markup::CData(0),
+            (Markup::Bang, _) => Markup::DocType(0).next(byte),
+            (Markup::CommentStart, _) => Markup::Comment(0),
+            (Markup::Comment(2), b'>') => Markup::Text,
+            (Markup::Comment(dashes), b'-') => Markup::Comment((dashes + 1).min(2)),
+            (Markup::Comment(_), _) => Markup::Comment(0),
+            (Markup::CData(2), b'>') => Markup::Text,
+            (Markup::CData(brackets), b']') => Markup::CData((brackets + 1).min(2)),
+            (Markup::CData(_), _) => Markup::CData(0),
+            (Markup::Instruction(true), b'>') => Markup::Text,
+            (Markup::Instruction(_), _) => Markup::Instruction(byte == b'?'),
+            (Markup::DocType(0), b'>') => Markup::Text,
+            (Markup::DocType(depth), b'>') => Markup::DocType(depth - 1),
+            (Markup::DocType(depth), b'<') => Markup::DocType(depth + 1),
+            (Markup::DocType(_), _) => self,
+        }
+    }
+}
+
+/// Bytes fed to a `PushSiteMapParser`, given to quick-xml up to the end of
+/// the last complete markup, so that it never reads a token in part.
+struct Fed {
+    data: Vec<u8>,
+    /// Bytes of `data` read by quick-xml.
+    consumed: usize,
+    /// End of the last complete markup in `data`, its end once finished.
+    released: usize,
+    markup: Markup,
+    /// Whether some bytes have been given to quick-xml.
+    started: bool,
+    /// Whether the last chunk has been fed.
+    ended: bool,
+}
+
+impl Fed {
+    fn push(&mut self, bytes: &[u8]) {
+        self.data.drain(..self.consumed);
+        self.released -= self.consumed;
+        self.consumed = 0;
+        let start = self.data.len();
+        self.data.extend_from_slice(bytes);
+        let mut released = None;
+        for (i, &byte) in bytes.iter().enumerate() {
+            let markup = self.markup.next(byte);
+            if matches!(markup, Markup::Text) && !matches!(self.markup, Markup::Text) {
+                released = Some(start + i + 1);
+            }
+            self.markup = markup;
+        }
+        if let Some(released) = released {
+            self.release(released);
+        }
+    }
+
+    /// Gives the bytes up to `released` to quick-xml, without the byte order
+    /// mark that it would not count in its positions.
+    fn release(&mut self, released: usize) {
+        self.released = released;
+        if !self.started {
+            self.started = true;
+            if self.data.starts_with(BOM) {
+                self.data.drain(..BOM.len());
+                self.released -= BOM.len();
+            }
+        }
+    }
+
+    /// Gives all the bytes to quick-xml, after the last chunk.
+    fn end(&mut self) {
+        self.release(self.data.len());
+        self.ended = true;
+    }
+
+    /// Whether quick-xml can read, without stopping in a token, some bytes
+    /// or the end of the document.
+    fn available(&self) -> bool {
+        self.consumed < self.released || self.ended
+    }
+}
+
+impl Read for Fed {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = {
+            let available = self.fill_buf()?;
+            let read = available.len().min(buf.len());
+            buf[..read].copy_from_slice(&available[..read]);
+            read
+        };
+        self.consume(read);
+        Ok(read)
+    }
+}
+
+impl BufRead for Fed {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(&self.data[self.consumed..self.released])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.consumed = (self.consumed + amt).min(self.released);
+    }
+}
+
+/// Push parser of sitemaps, fed the document in chunks, the counterpart of
+/// `SiteMapReader` for the sources that can not be read.
+///
+/// Entries are built exactly like in `SiteMapReader`, from UTF-8 documents
+/// parsed by quick-xml, and the malformed XML errors are `Error::Parse`,
+/// like the ones of the asynchronous reader.
+pub struct PushSiteMapParser {
+    parser: NsReader<PositionTracker<Fed>>,
+    state: ReaderState,
+    buffer: Vec<u8>,
+    tokens: Tokens,
+    limits: SourceLimits,
+    /// Limit exceeded by a chunk, returned once the chunks before it are
+    /// parsed.
+    exceeded: Option<Error>,
+    /// Error read after some entries, returned by the next call.
+    error: Option<Error>,
+}
+
+impl Default for PushSiteMapParser {
+    fn default() -> PushSiteMapParser {
+        PushSiteMapParser::new()
+    }
+}
+
+impl PushSiteMapParser {
+    /// Creates a new parser.
+    pub fn new() -> PushSiteMapParser {
+        PushSiteMapParser::with_options(ReaderOptions::default())
+    }
+
+    /// Creates a new parser with the given options, the byte limits are
+    /// checked on the chunks fed, `skip_leading_garbage` is not supported.
+    pub fn with_options(options: ReaderOptions) -> PushSiteMapParser {
+        let fed = Fed {
+            data: Vec::new(),
+            consumed: 0,
+            released: 0,
+            markup: Markup::Text,
+            started: false,
+            ended: false,
+        };
+        PushSiteMapParser {
+            parser: NsReader::from_reader(PositionTracker::new(fed)),
+            limits: SourceLimits::new(&options),
+            state: ReaderState::new(options),
+            buffer: Vec::new(),
+            tokens: Tokens::new(parse_error),
+            exceeded: None,
+            error: None,
+        }
+    }
+
+    /// Kind of the document, known once its root element has been read.
+    pub fn document_kind(&self) -> Option<DocumentKind> {
+        self.state.document_kind()
+    }
+
+    /// See `SiteMapReader::root_namespaces`.
+    pub fn root_namespaces(&self) -> &[(String, String)] {
+        self.state.root_namespaces()
+    }
+
+    /// See `SiteMapReader::stylesheets`.
+    pub fn stylesheets(&self) -> &[structs::Stylesheet] {
+        self.state.stylesheets()
+    }
+
+    /// See `SiteMapReader::warnings`.
+    pub fn warnings(&self) -> &[Warning] {
+        self.state.warnings()
+    }
+
+    /// See `SiteMapReader::strict`.
+    pub fn strict(mut self, strict: bool) -> PushSiteMapParser {
+        self.state.set_strict(strict);
+        self
+    }
+
+    /// Parses the next chunk of the document, returning the entries it
+    /// completes.
+    ///
+    /// An error is returned by the call reading it when no entry precedes
+    /// it, by the next call otherwise, so that the entries and errors keep
+    /// the order of the document. Once the document has ended, by its last
+    /// element or by an error ending it, the chunks are ignored.
+    pub fn feed(&mut self, bytes: &[u8]) -> Result<Vec<SiteMapEvent>, Error> {
+        if !self.tokens.finished && self.exceeded.is_none() {
+            match self.limits.count(bytes) {
+                Ok(()) => self.parser.get_mut().inner.push(bytes),
+                Err(error) => self.exceeded = Some(error),
+            }
+        }
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        let mut events = Vec::new();
+        while let Some(event) = self.next_event() {
+            match event {
+                Ok(event) => events.push(event),
+                Err(error) if events.is_empty() => return Err(error),
+                Err(error) => {
+                    self.error = Some(error);
+                    break;
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    /// Parses the end of the document, after the last chunk, returning the
+    /// remaining entries or the first remaining error. Feeding empty chunks
+    /// until one returns no entry reads all the entries and errors before.
+    pub fn finish(mut self) -> Result<Vec<SiteMapEvent>, Error> {
+        self.parser.get_mut().inner.end();
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        let mut events = Vec::new();
+        while let Some(event) = self.next_event() {
+            events.push(event?);
+        }
+        Ok(events)
+    }
+
+    fn xml_error(&mut self, error: quick_xml::Error) -> Error {
+        match error {
+            quick_xml::Error::Io(error) => Error::Io(io::Error::new(error.kind(), error.to_string())),
+            error => {
+                let offset = self.parser.error_position();
+                parse_error(self.parser.get_mut().position(offset), error.to_string())
+            }
+        }
+    }
+
+    /// Reads the next entry from the bytes given to quick-xml, `None` once
+    /// they are all read or the document has ended.
+    fn next_event(&mut self) -> Option<Result<SiteMapEvent, Error>> {
+        while !self.tokens.finished && self.parser.get_ref().inner.available() {
+            let offset = self.parser.buffer_position();
+            self.buffer.clear();
+            let root = !self.state.in_element();
+            let captures = self.state.captures();
+            let token = self.parser
+                .read_resolved_event_into(&mut self.buffer)
+                .and_then(|(namespace, event)| quick::token(namespace, event, root, captures));
+            match token {
+                Ok(token) => {
+                    let position = self.parser.get_mut().position(offset);
+                    if let Some(event) = self.tokens.handle(&mut self.state, token, position) {
+                        return self.state.truncate(Some(event));
+                    }
+                }
+                Err(error) => {
+                    self.tokens.finished = true;
+                    let error = self.xml_error(error);
+                    return self.state.truncate(Some(Err(error)));
+                }
+            }
+        }
+        if self.tokens.finished {
+            return None;
+        }
+        let error = self.exceeded.take()?;
+        self.tokens.finished = true;
+        self.state.truncate(Some(Err(error)))
+    }
+}
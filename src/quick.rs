@@ -0,0 +1,438 @@
+//! Contains the parts of the readers based on quick-xml, shared by the
+//! push parser, that the asynchronous reader feeds, and the `fast-xml`
+//! backend of `SiteMapReader`.
+use crate::reader::{RawStart, ReaderState, SiteMapEvent};
+use crate::structs;
+use crate::Error;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::name::{PrefixDeclaration, ResolveResult};
+use std::collections::VecDeque;
+#[cfg(feature = "fast-xml")]
+use quick_xml::NsReader;
+use std::io::{self, BufRead};
+#[cfg(feature = "fast-xml")]
+use std::io::{BufReader, Read};
+
+/// Line starts of the bytes read so far.
+pub(crate) struct Lines {
+    consumed: u64,
+    scanned: u64,
+    newlines: VecDeque<u64>,
+    line: u64,
+    line_start: u64,
+}
+
+impl Lines {
+    /// Scans the bytes available at the consumed offset, only the ones not
+    /// seen yet.
+    pub(crate) fn scan(&mut self, available: &[u8]) {
+        let start = (self.scanned - self.consumed) as usize;
+        for (i, byte) in available.iter().enumerate().skip(start) {
+            if *byte == b'\n' {
+                self.newlines.push_back(self.consumed + i as u64);
+            }
+        }
+        self.scanned = self.scanned.max(self.consumed + available.len() as u64);
+    }
+
+    pub(crate) fn consume(&mut self, amt: usize) {
+        self.consumed += amt as u64;
+    }
+}
+
+/// Stream keeping track of the line starts, to report positions.
+pub(crate) struct PositionTracker<R> {
+    pub(crate) inner: R,
+    pub(crate) lines: Lines,
+}
+
+impl<R> PositionTracker<R> {
+    /// Creates a tracker of `inner`.
+    pub(crate) fn new(inner: R) -> PositionTracker<R> {
+        PositionTracker {
+            inner,
+            lines: Lines {
+                consumed: 0,
+                scanned: 0,
+                newlines: VecDeque::new(),
+                line: 0,
+                line_start: 0,
+            },
+        }
+    }
+
+    /// Position of the byte at `offset`, offsets must not decrease between
+    /// calls.
+    pub(crate) fn position(&mut self, offset: u64) -> structs::Position {
+        let lines = &mut self.lines;
+        while let Some(&newline) = lines.newlines.front() {
+            if newline >= offset {
+                break;
+            }
+            lines.newlines.pop_front();
+            lines.line += 1;
+            lines.line_start = newline + 1;
+        }
+        structs::Position {
+            line: lines.line + 1,
+            column: offset.saturating_sub(lines.line_start) + 1,
+        }
+    }
+}
+
+impl<R: BufRead> io::Read for PositionTracker<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = {
+            let available = self.fill_buf()?;
+            let read = available.len().min(buf.len());
+            buf[..read].copy_from_slice(&available[..read]);
+            read
+        };
+        self.consume(read);
+        Ok(read)
+    }
+}
+
+impl<R: BufRead> BufRead for PositionTracker<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let available = self.inner.fill_buf()?;
+        self.lines.scan(available);
+        Ok(available)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.lines.consume(amt);
+        self.inner.consume(amt);
+    }
+}
+
+/// Error of the malformed documents, at `position`, for the readers
+/// without the errors of xml-rs.
+pub(crate) fn parse_error(position: structs::Position, message: String) -> Error {
+    Error::Parse {
+        line: position.line,
+        column: position.column,
+        message,
+    }
+}
+
+/// Start of an element.
+pub(crate) struct Element {
+    local_name: String,
+    namespace: Option<String>,
+    attributes: Vec<(String, String)>,
+    namespaces: Option<Vec<(String, String)>>,
+    raw: Option<RawStart>,
+}
+
+/// Content of an XML event.
+pub(crate) enum Token {
+    Start(Element, bool),
+    End,
+    Text(String),
+    UnknownEntity(String),
+    Instruction(String, String),
+    Comment(String),
+    Doctype,
+    Eof,
+    Other,
+}
+
+/// Namespaces declared by an element.
+fn namespaces(start: &BytesStart) -> Result<Vec<(String, String)>, quick_xml::Error> {
+    let mut namespaces = Vec::new();
+    for attribute in start.attributes() {
+        let attribute = attribute?;
+        let prefix = match attribute.key.as_namespace_binding() {
+            Some(PrefixDeclaration::Default) => String::new(),
+            Some(PrefixDeclaration::Named(prefix)) => String::from_utf8_lossy(prefix).into_owned(),
+            None => continue,
+        };
+        let namespace = attribute.unescape_value()?.into_owned();
+        if !namespace.is_empty() {
+            namespaces.push((prefix, namespace));
+        }
+    }
+    Ok(namespaces)
+}
+
+/// Name, namespace and unprefixed attributes of an element, with the
+/// namespaces it declares when it is the `root`, and its `RawStart` when
+/// `captures`.
+fn element(namespace: ResolveResult,
+           start: &BytesStart,
+           root: bool,
+           captures: bool)
+           -> Result<Element, quick_xml::Error> {
+    let local_name = String::from_utf8_lossy(start.local_name().as_ref()).into_owned();
+    let namespace = match namespace {
+        ResolveResult::Bound(namespace) => Some(String::from_utf8_lossy(namespace.as_ref()).into_owned()),
+        _ => None,
+    };
+    let mut attributes = Vec::new();
+    for attribute in start.attributes() {
+        let attribute = attribute?;
+        if attribute.key.prefix().is_none() && attribute.key.as_ref() != b"xmlns" {
+            let name = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+            attributes.push((name, attribute.unescape_value()?.into_owned()));
+        }
+    }
+    let raw = if captures {
+        let mut attributes = Vec::new();
+        for attribute in start.attributes() {
+            let attribute = attribute?;
+            if attribute.key.as_namespace_binding().is_none() {
+                let name = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+                attributes.push((name, attribute.unescape_value()?.into_owned()));
+            }
+        }
+        Some(RawStart {
+            prefix: start.name().prefix().map(|prefix| String::from_utf8_lossy(prefix.as_ref()).into_owned()),
+            namespaces: namespaces(start)?,
+            attributes,
+        })
+    } else {
+        None
+    };
+    let namespaces = if root { Some(namespaces(start)?) } else { None };
+    Ok(Element {
+        local_name,
+        namespace,
+        attributes,
+        namespaces,
+        raw,
+    })
+}
+
+/// Token of a resolved event, `root` when no element is open, with the
+/// `RawStart` of the elements when `captures`.
+pub(crate) fn token(namespace: ResolveResult,
+                    event: Event,
+                    root: bool,
+                    captures: bool)
+                    -> Result<Token, quick_xml::Error> {
+    match event {
+        Event::Start(start) => {
+            element(namespace, &start, root, captures).map(|element| Token::Start(element, false))
+        }
+        Event::Empty(start) => {
+            element(namespace, &start, root, captures).map(|element| Token::Start(element, true))
+        }
+        Event::End(_) => Ok(Token::End),
+        Event::Text(text) => {
+            text.decode()
+                .map(|text| Token::Text(text.into_owned()))
+                .map_err(quick_xml::Error::from)
+        }
+        Event::CData(data) => {
+            data.decode()
+                .map(|data| Token::Text(data.into_owned()))
+                .map_err(quick_xml::Error::from)
+        }
+        Event::GeneralRef(reference) => {
+            match reference.resolve_char_ref()? {
+                Some(character) => Ok(Token::Text(character.to_string())),
+                None => {
+                    let name = String::from_utf8_lossy(&reference).into_owned();
+                    match resolve_predefined_entity(&name) {
+                        Some(text) => Ok(Token::Text(text.to_string())),
+                        None => Ok(Token::UnknownEntity(name)),
+                    }
+                }
+            }
+        }
+        Event::Comment(text) => {
+            text.decode()
+                .map(|text| Token::Comment(text.into_owned()))
+                .map_err(quick_xml::Error::from)
+        }
+        Event::DocType(_) => Ok(Token::Doctype),
+        Event::PI(instruction) => {
+            let name = String::from_utf8_lossy(instruction.target()).into_owned();
+            let data = String::from_utf8_lossy(instruction.content()).into_owned();
+            Ok(Token::Instruction(name, data))
+        }
+        Event::Eof => Ok(Token::Eof),
+        _ => Ok(Token::Other),
+    }
+}
+
+/// Entries built from the tokens, with the text read so far.
+pub(crate) struct Tokens {
+    text: String,
+    /// Error of the malformed documents at a position.
+    syntax_error: fn(structs::Position, String) -> Error,
+    pub(crate) finished: bool,
+}
+
+impl Tokens {
+    pub(crate) fn new(syntax_error: fn(structs::Position, String) -> Error) -> Tokens {
+        Tokens {
+            text: String::new(),
+            syntax_error,
+            finished: false,
+        }
+    }
+
+    /// Passes the text read so far to the entries, whitespace only text
+    /// is ignored.
+    fn flush_text(&mut self, state: &mut ReaderState) -> Option<Result<SiteMapEvent, Error>> {
+        let event = if self.text.trim().is_empty() {
+            None
+        } else {
+            state.characters(&self.text)
+        };
+        self.text.clear();
+        event
+    }
+
+    /// Handles `token`, starting at `position`, returning the completed
+    /// entry or error if any.
+    pub(crate) fn handle(&mut self,
+                         state: &mut ReaderState,
+                         token: Token,
+                         position: structs::Position)
+                         -> Option<Result<SiteMapEvent, Error>> {
+        let event = match token {
+            Token::Start(element, empty) => {
+                if let Some(event) = self.flush_text(state) {
+                    self.finished = state.aborted();
+                    return Some(event);
+                }
+                if let Some(namespaces) = element.namespaces {
+                    state.set_root_namespaces(namespaces);
+                }
+                let event = state.start_element(&element.local_name,
+                                                element.namespace.as_deref(),
+                                                &element.attributes,
+                                                element.raw,
+                                                position);
+                if event.is_none() && empty {
+                    state.end_element(position)
+                } else {
+                    event
+                }
+            }
+            Token::End => {
+                match self.flush_text(state) {
+                    Some(event) => Some(event),
+                    None => state.end_element(position),
+                }
+            }
+            Token::Text(text) => {
+                self.text.push_str(&text);
+                if self.text.len() > state.text_limit() {
+                    self.flush_text(state)
+                } else {
+                    None
+                }
+            }
+            Token::Instruction(name, data) => {
+                state.processing_instruction(&name, &data);
+                None
+            }
+            Token::Comment(text) => state.comment(&text),
+            Token::Doctype => state.doctype(position),
+            Token::UnknownEntity(name) => {
+                self.finished = true;
+                return Some(Err((self.syntax_error)(position, format!("Unexpected entity: {}", name))));
+            }
+            Token::Eof => {
+                self.finished = true;
+                if state.in_element() {
+                    return Some(Err((self.syntax_error)(position, "Unexpected end of stream".to_string())));
+                }
+                if state.document_kind().is_none() {
+                    let message = "Unexpected end of stream: no root element found".to_string();
+                    return Some(Err((self.syntax_error)(position, message)));
+                }
+                None
+            }
+            Token::Other => None,
+        };
+        if event.is_some() {
+            self.finished = state.aborted();
+        }
+        event
+    }
+}
+
+/// quick-xml parser of a `SiteMapReader`, enabled by the `fast-xml`
+/// feature.
+#[cfg(feature = "fast-xml")]
+pub(crate) struct Parser<R: Read> {
+    reader: NsReader<PositionTracker<BufReader<R>>>,
+    buffer: Vec<u8>,
+    tokens: Tokens,
+    /// Line and column of the start of `source` in the document.
+    origin: (u64, u64),
+}
+
+#[cfg(feature = "fast-xml")]
+impl<R: Read> Parser<R> {
+    /// Creates a parser of `source`, starting at the zero based `line` and
+    /// `column` of the document.
+    pub(crate) fn new(source: R,
+                      (line, column): (u64, u64),
+                      syntax_error: fn(structs::Position, String) -> Error)
+                      -> Parser<R> {
+        let reader = NsReader::from_reader(PositionTracker::new(BufReader::new(source)));
+        Parser {
+            reader,
+            buffer: Vec::new(),
+            tokens: Tokens::new(syntax_error),
+            origin: (line, column),
+        }
+    }
+
+    pub(crate) fn source_mut(&mut self) -> &mut R {
+        self.reader.get_mut().inner.get_mut()
+    }
+
+    #[cfg(feature = "fast-xml")]
+    pub(crate) fn source(&self) -> &R {
+        self.reader.get_ref().inner.get_ref()
+    }
+
+    /// Position in the document of the byte at `offset` of the source.
+    fn position(&mut self, offset: u64) -> structs::Position {
+        let (line, column) = self.origin;
+        let position = self.reader.get_mut().position(offset);
+        structs::Position {
+            line: position.line + line,
+            column: position.column + if position.line == 1 { column } else { 0 },
+        }
+    }
+
+    /// Reads the next entry, `None` at the end of the document or after
+    /// the first error ending it. The errors of quick-xml are returned
+    /// with their position, for the caller to convert.
+    pub(crate) fn next_event(&mut self,
+                             state: &mut ReaderState)
+                             -> Option<Result<Result<SiteMapEvent, Error>, (structs::Position, quick_xml::Error)>> {
+        while !self.tokens.finished {
+            let offset = self.reader.buffer_position();
+            self.buffer.clear();
+            let root = !state.in_element();
+            let captures = state.captures();
+            let token = self.reader
+                .read_resolved_event_into(&mut self.buffer)
+                .and_then(|(namespace, event)| token(namespace, event, root, captures));
+            match token {
+                Ok(token) => {
+                    let position = self.position(offset);
+                    if let Some(event) = self.tokens.handle(state, token, position) {
+                        return Some(Ok(event));
+                    }
+                }
+                Err(error) => {
+                    self.tokens.finished = true;
+                    let offset = self.reader.error_position();
+                    return Some(Err((self.position(offset), error)));
+                }
+            }
+        }
+        None
+    }
+}
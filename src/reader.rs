@@ -6,49 +6,788 @@
 //!
 //! ```rust
 //! extern crate sitemap;
-//! use sitemap::reader::{SiteMapReader,SiteMapEntity};
+//! use sitemap::reader::{SiteMapReader,SiteMapEvent};
 //! use std::fs::File;
 //! fn main() {
-//!    let mut urls = Vec::new();
-//! 	let mut sitemaps = Vec::new();
-//! 	let mut errors = Vec::new();
-//! 	let file = File::open("tests/documents/sitemap1.xml").expect("Unable to open file.");
-//! 	let parser = SiteMapReader::new(file);
-//! 	for entity in parser {
-//! 		match entity {
-//! 			SiteMapEntity::Url(url_entry) => {
-//! 				urls.push(url_entry);
-//! 			},
-//! 			SiteMapEntity::SiteMap(sitemap_entry) => {
-//! 				sitemaps.push(sitemap_entry);
-//! 			},
-//! 			SiteMapEntity::Err(error) => {
-//! 				errors.push(error);
-//! 			},
-//! 		}
-//! 	}
-//! 	println!("urls = {:?}",urls);
-//! 	println!("sitemaps = {:?}",sitemaps);
-//! 	println!("errors = {:?}",errors);
+//!     let mut urls = Vec::new();
+//!     let mut sitemaps = Vec::new();
+//!     let mut errors = Vec::new();
+//!     let file = File::open("tests/documents/sitemap1.xml").expect("Unable to open file.");
+//!     let parser = SiteMapReader::new(file);
+//!     for event in parser {
+//!         match event {
+//!             Ok(SiteMapEvent::Url(url_entry)) => {
+//!                 urls.push(url_entry);
+//!             },
+//!             Ok(SiteMapEvent::SiteMap(sitemap_entry)) => {
+//!                 sitemaps.push(sitemap_entry);
+//!             },
+//!             Ok(_) => {},
+//!             Err(error) => {
+//!                 errors.push(error);
+//!             },
+//!         }
+//!     }
+//!     println!("urls = {:?}",urls);
+//!     println!("sitemaps = {:?}",sitemaps);
+//!     println!("errors = {:?}",errors);
 //! }
 //! ```
-use structs;
-use xml;
-use std::io::Read;
-use xml::reader::{EventReader, XmlEvent, Events};
+//!
+//! Collecting all the urls, failing on the first error.
+//!
+//! ```rust
+//! extern crate sitemap;
+//! use sitemap::reader::{SiteMapReader,SiteMapEvent};
+//! fn main() {
+//!     let document = "<urlset><url><loc>http://www.example.com/</loc></url></urlset>";
+//!     let urls = SiteMapReader::new(document.as_bytes())
+//!         .filter_map(|event| match event {
+//!             Ok(SiteMapEvent::Url(url_entry)) => Some(Ok(url_entry)),
+//!             Ok(_) => None,
+//!             Err(error) => Some(Err(error)),
+//!         })
+//!         .collect::<Result<Vec<_>, _>>()
+//!         .expect("valid sitemap");
+//!     assert_eq!(urls.len(), 1);
+//! }
+//! ```
+use crate::structs;
+use std::io::{BufRead, Read};
+use std::io;
+#[cfg(feature = "gzip")]
+use flate2::read::GzDecoder;
+use crate::Error;
+use xml::common::Position;
+use xml::escape::{escape_str_attribute, escape_str_pcdata};
+use xml::attribute::OwnedAttribute;
+use xml::name::OwnedName;
+use xml::reader::{EventReader, Events, ParserConfig, XmlEvent};
 use std::convert::From;
+use std::iter::FusedIterator;
+use std::fmt;
+use std::mem;
+#[cfg(feature = "gzip")]
+use std::sync::Arc;
+#[cfg(feature = "gzip")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "async")]
+use std::pin::Pin;
+#[cfg(feature = "async")]
+use std::task::{Context, Poll};
+#[cfg(feature = "async")]
+use futures_core::Stream;
+#[cfg(feature = "feeds")]
+use chrono::DateTime;
 
 /// A wrapper around an `std::io::Read` instance which provides sitemap parsing.
 ///
-/// It's provides iterator over `SiteMapEntity`.
+/// It's provides iterator over `Result<SiteMapEvent, Error>`, the iteration
+/// ends at the end of the document or after the first malformed XML error.
+///
+/// With the `fast-xml` feature UTF-8 documents are parsed by quick-xml,
+/// the other ones by xml-rs. The entries and errors are the same, except
+/// that quick-xml does not expand the entities declared by a `<!DOCTYPE>`,
+/// they are unexpected entity errors.
 pub struct SiteMapReader<T: Read + Sized> {
+    state: ReaderState,
+    parser: Parser<T>,
+    finished: bool,
+    /// Bytes read from the source, kept once the parser has ended.
+    bytes: u64,
+    entries: u64,
+    /// Compressed bytes read, for the readers of gzip streams.
+    #[cfg(feature = "gzip")]
+    compressed: Option<Arc<AtomicU64>>,
+    on_progress: Option<OnProgress>,
+}
+/// Counters of the bytes read and entries emitted by a `SiteMapReader`,
+/// given to the callback of `SiteMapReader::on_progress`.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub struct Progress {
+    /// Bytes read from the source, decompressed for the gzip streams.
+    pub bytes: u64,
+    /// Compressed bytes read, for the readers created by
+    /// `SiteMapReader::from_gzip` or `SiteMapReader::from_maybe_gzip` of
+    /// a compressed stream.
+    pub compressed_bytes: Option<u64>,
+    /// Url and sitemap entries emitted.
+    pub entries: u64,
+}
+/// Progress callback of a `SiteMapReader`, with the counters of its last
+/// call.
+struct OnProgress {
+    callback: Box<dyn FnMut(Progress) + Send>,
+    last: Progress,
+    ended: bool,
+}
+/// XML parser of a `SiteMapReader`, chosen once the start of the document
+/// has been read.
+#[allow(clippy::large_enum_variant)]
+enum Parser<T: Read> {
+    Pending(Prelude<T>),
+    Xml(Events<Prelude<T>>),
+    #[cfg(feature = "fast-xml")]
+    Quick(crate::quick::Parser<Prelude<T>>),
+    Ended,
+}
+/// Number of bytes of the document kept in `Error::NotXml`.
+const NOT_XML_PREFIX: usize = 32;
+/// Number of bytes read at most to find the encoding of the declaration.
+#[cfg(feature = "fast-xml")]
+const DECLARATION_SIZE: usize = 1024;
+/// Source skipping what precedes the XML before the parser reads it, and
+/// enforcing the byte limits.
+struct Prelude<T: Read> {
+    inner: T,
+    buffer: io::Cursor<Vec<u8>>,
+    line: u64,
+    column: u64,
+    limits: SourceLimits,
+}
+impl<T: Read> Prelude<T> {
+    /// Reads from the source, appending to the buffer, `false` at the end.
+    fn fill(&mut self) -> Result<bool, Error> {
+        let mut chunk = [0u8; 512];
+        loop {
+            match self.inner.read(&mut chunk) {
+                Ok(0) => return Ok(false),
+                Ok(read) => {
+                    self.limits.count(&chunk[..read])?;
+                    self.buffer.get_mut().extend_from_slice(&chunk[..read]);
+                    return Ok(true);
+                }
+                Err(ref error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(Error::from(error)),
+            }
+        }
+    }
+    /// Drops the bytes before `start`, counting the lines and columns.
+    fn skip(&mut self, bom: usize, start: usize) {
+        let skipped = &self.buffer.get_ref()[bom..start];
+        match skipped.iter().rposition(|&byte| byte == b'\n') {
+            Some(newline) => {
+                self.line += skipped.iter().filter(|&&byte| byte == b'\n').count() as u64;
+                self.column = (skipped.len() - newline - 1) as u64;
+            }
+            None => self.column += skipped.len() as u64,
+        }
+        self.buffer.get_mut().drain(..start);
+    }
+    /// Consumes the UTF-8 byte order mark and the whitespace before an XML
+    /// declaration, and with `skip_garbage` anything before the first `<`.
+    fn start(&mut self, skip_garbage: bool) -> Result<(), Error> {
+        while self.buffer.get_ref().len() < 8 && self.fill()? {}
+        let head = self.buffer.get_ref().clone();
+        // UTF-16 documents are left to the parser
+        if head.starts_with(&[0xfe, 0xff]) || head.starts_with(&[0xff, 0xfe]) ||
+           head.starts_with(&[0, b'<']) || head.starts_with(&[b'<', 0]) {
+            return Ok(());
+        }
+        let mut bom = if head.starts_with(&[0xef, 0xbb, 0xbf]) { 3 } else { 0 };
+        let mut start = bom;
+        loop {
+            let buffer = self.buffer.get_ref();
+            match buffer[start..].iter().position(|byte| !byte.is_ascii_whitespace()) {
+                Some(offset) => {
+                    start += offset;
+                    break;
+                }
+                None => {
+                    start = buffer.len();
+                    if !self.fill()? {
+                        return Err(self.not_xml(&head));
+                    }
+                }
+            }
+        }
+        while self.buffer.get_ref().len() < start + 5 && self.fill()? {}
+        let buffer = self.buffer.get_ref();
+        if buffer[start] == b'<' {
+            if buffer[start..].starts_with(b"<?xml") {
+                self.skip(bom, start);
+            } else {
+                self.skip(bom, bom);
+            }
+            return Ok(());
+        }
+        if !skip_garbage {
+            return Err(self.not_xml(&head));
+        }
+        loop {
+            let buffer = self.buffer.get_ref();
+            match buffer[start..].iter().position(|&byte| byte == b'<') {
+                Some(offset) => {
+                    self.skip(bom, start + offset);
+                    return Ok(());
+                }
+                None => {
+                    let end = buffer.len();
+                    self.skip(bom, end);
+                    bom = 0;
+                    start = 0;
+                    if !self.fill()? {
+                        return Err(self.not_xml(&head));
+                    }
+                }
+            }
+        }
+    }
+    /// Whether the document is in UTF-8, it has no UTF-16 byte order mark
+    /// and its declaration, if any, has no other encoding.
+    #[cfg(feature = "fast-xml")]
+    fn is_utf8(&mut self) -> Result<bool, Error> {
+        while self.buffer.get_ref().len() < DECLARATION_SIZE &&
+              !self.buffer.get_ref().windows(2).any(|window| window == b"?>") && self.fill()? {}
+        let head = self.buffer.get_ref();
+        if head.starts_with(&[0xfe, 0xff]) || head.starts_with(&[0xff, 0xfe]) ||
+           head.starts_with(&[0, b'<']) || head.starts_with(&[b'<', 0]) {
+            return Ok(false);
+        }
+        Ok(match declaration_encoding(head) {
+            Some((start, end)) => {
+                let encoding = &head[start..end];
+                encoding.eq_ignore_ascii_case(b"UTF-8") || encoding.eq_ignore_ascii_case(b"UTF8")
+            }
+            None => true,
+        })
+    }
+    fn not_xml(&self, head: &[u8]) -> Error {
+        Error::NotXml { bytes: head[..head.len().min(NOT_XML_PREFIX)].to_vec() }
+    }
+}
+impl<T: Read> Read for Prelude<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if (self.buffer.position() as usize) < self.buffer.get_ref().len() {
+            // counted when read from the source
+            return self.buffer.read(buf);
+        }
+        let read = self.inner.read(buf)?;
+        self.limits.count(&buf[..read]).map_err(|error| io::Error::other(error.to_string()))?;
+        Ok(read)
+    }
+}
+/// Value of the `encoding` of the XML declaration at the start of `text`,
+/// with its range.
+#[cfg(any(feature = "encodings", feature = "fast-xml"))]
+pub(crate) fn declaration_encoding<T: AsRef<[u8]> + ?Sized>(text: &T) -> Option<(usize, usize)> {
+    let text = text.as_ref();
+    if !text.starts_with(b"<?xml") {
+        return None;
+    }
+    let end = text.windows(2).position(|window| window == b"?>")?;
+    let declaration = &text[..end];
+    let name = declaration.windows(8).position(|window| window == b"encoding")?;
+    let mut start = name + 8;
+    while start < end && declaration[start].is_ascii_whitespace() {
+        start += 1;
+    }
+    if declaration.get(start) != Some(&b'=') {
+        return None;
+    }
+    start += 1;
+    while start < end && declaration[start].is_ascii_whitespace() {
+        start += 1;
+    }
+    let quote = *declaration.get(start).filter(|&&quote| quote == b'"' || quote == b'\'')?;
+    let length = declaration[start + 1..].iter().position(|&byte| byte == quote)?;
+    Some((start + 1, start + 1 + length))
+}
+/// Entries being built from the XML events, shared by the readers.
+pub(crate) struct ReaderState {
     path: Vec<String>,
     url_item: structs::UrlEntry,
+    image_item: structs::ImageEntry,
+    video_item: structs::VideoEntry,
+    news_item: NewsItem,
     sitemap_item: structs::SiteMapEntry,
-    parser: Events<T>,
+    kind: Option<DocumentKind>,
+    root_namespaces: Option<Vec<(String, String)>>,
+    stylesheets: Vec<structs::Stylesheet>,
+    text: String,
+    options: ReaderOptions,
+    urls: usize,
+    entries: u64,
+    warnings: Vec<Warning>,
+    aborted: bool,
+    /// Namespaces declared by the open elements, with
+    /// `ReaderOptions::capture_unknown` or `ReaderOptions::emit_unknown`.
+    scope: Vec<Vec<(String, String)>>,
+    /// Unknown element being read.
+    capture: Option<Capture>,
+    /// Number of implied elements around the document, 1 for the fragments
+    /// of a single entry.
+    outer: usize,
+    /// Position and text of the child of `<sitemap>` other than `<loc>`
+    /// and `<lastmod>` being read.
+    sitemap_extra: Option<(structs::Position, String)>,
+    /// Positions of the first and the current element of the repeated
+    /// field being read.
+    duplicate: Option<(structs::Position, structs::Position)>,
+}
+/// Unknown element being read, kept by `ReaderOptions::capture_unknown`
+/// or emitted by `ReaderOptions::emit_unknown`.
+struct Capture {
+    element: structs::RawElement,
+    /// Qualified names of the open descendants of the element.
+    open: Vec<String>,
+    name: OwnedName,
+    position: structs::Position,
+    keep: bool,
+    emit: bool,
+}
+/// Appends the start tag of the element `name` to `xml`.
+fn push_start_tag(xml: &mut String, name: &str, attributes: &[(String, String)]) {
+    xml.push('<');
+    xml.push_str(name);
+    for (attribute, value) in attributes {
+        xml.push(' ');
+        xml.push_str(attribute);
+        xml.push_str("=\"");
+        xml.push_str(&escape_str_attribute(value));
+        xml.push('"');
+    }
+    xml.push('>');
+}
+/// Prefix, namespace declarations and attributes of an element, passed to
+/// `ReaderState::start_element` with `ReaderOptions::capture_unknown`.
+pub(crate) struct RawStart {
+    /// Prefix of the name of the element.
+    pub(crate) prefix: Option<String>,
+    /// Namespaces declared by the element, or all the namespaces in scope.
+    pub(crate) namespaces: Vec<(String, String)>,
+    /// Attributes with their qualified names, without the namespace
+    /// declarations.
+    pub(crate) attributes: Vec<(String, String)>,
+}
+/// Children of `<url>` read into the fields of `UrlEntry`.
+const URL_CHILDREN: [&str; 8] =
+    ["loc", "lastmod", "changefreq", "priority", "image:image", "video:video", "news:news", "xhtml:link"];
+/// Children of `<sitemap>` read into the fields of `SiteMapEntry`.
+const SITEMAP_CHILDREN: [&str; 2] = ["loc", "lastmod"];
+/// Options of `SiteMapReader`.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct ReaderOptions {
+    /// An entry with invalid fields ends the iteration with its first
+    /// error, otherwise the errors are kept in the `Err` variants of the
+    /// fields and in the warnings. `false` by default.
+    pub strict: bool,
+    /// Maximum number of urls, the iteration ends with
+    /// `Error::LimitExceeded` after them. Unlimited by default.
+    pub max_urls: Option<usize>,
+    /// Maximum number of url and sitemap entries, the iteration ends with
+    /// `Error::LimitExceeded` after them. Unlimited by default.
+    pub max_entries: Option<u64>,
+    /// Ends the iteration over `max_urls`, `max_entries` or `max_bytes`
+    /// with a `SiteMapEvent::Truncated` instead of the error. `false` by
+    /// default.
+    pub truncate: bool,
+    /// Requires the root element in the sitemap namespace, either 0.9 or
+    /// 0.84, otherwise the elements are matched by their local name.
+    /// `false` by default, always required in strict mode.
+    pub require_namespace: bool,
+    /// Trims the whitespace around free text like titles, `false` by
+    /// default. Urls, dates and numbers are always trimmed.
+    pub trim_whitespace: bool,
+    /// Skips the bytes before the first `<` of the document, otherwise
+    /// they are an `Error::NotXml`. `false` by default.
+    pub skip_leading_garbage: bool,
+    /// Matches the element names ignoring their case, like `<URL>` or
+    /// `<LastMod>`. `false` by default, ignored in strict mode.
+    pub case_insensitive_tags: bool,
+    /// Emits a `SiteMapEvent::UrlSetStart` or `SiteMapEvent::IndexStart`
+    /// before the entries. `false` by default.
+    pub root_events: bool,
+    /// Maximum nesting of the elements, 32 by default.
+    pub max_depth: usize,
+    /// Maximum length in bytes of the text of a sitemap element, like
+    /// `<loc>`, 4096 by default.
+    pub max_text_length: usize,
+    /// Maximum length in bytes of the text of an extension element, like
+    /// `<video:description>`, 65536 by default.
+    ///
+    /// Raw text is limited to 8 times this length, before the entities
+    /// are expanded.
+    pub max_extension_text_length: usize,
+    /// Accepts a `<!DOCTYPE>`, and the entities it declares. `false` by
+    /// default.
+    pub allow_doctype: bool,
+    /// Maximum number of bytes read from the source, unlimited by default.
+    pub max_bytes: Option<u64>,
+    /// Parses the `<loc>` of the urls and sitemaps and the `href` of the
+    /// alternates, otherwise they are kept as `Location::Raw` and their
+    /// errors are not warnings. `true` by default, always parsed in strict
+    /// mode.
+    pub parse_urls: bool,
+    /// Keeps the unknown child elements of the urls in
+    /// `UrlEntry::extensions`, otherwise they are ignored. `false` by
+    /// default.
+    ///
+    /// The content of each element is limited to
+    /// `max_extension_text_length` bytes.
+    pub capture_unknown: bool,
+    /// Emits a `SiteMapEvent::Unknown` for each unknown child element of
+    /// the root element and of the entries, after its end. `false` by
+    /// default.
+    ///
+    /// The content of each element is limited like with `capture_unknown`.
+    pub emit_unknown: bool,
+    /// Number of entries after which the callback of
+    /// `SiteMapReader::on_progress` is called, 1000 by default.
+    pub progress_entries: u64,
+    /// Number of bytes read after which the callback of
+    /// `SiteMapReader::on_progress` is called, 1 MiB by default.
+    pub progress_bytes: u64,
+    /// Value kept for the repeated `<loc>`, `<lastmod>`, `<changefreq>` and
+    /// `<priority>` of a url, and `<loc>` and `<lastmod>` of a sitemap.
+    /// `DuplicateChildren::LastWins` by default.
+    pub duplicate_children: DuplicateChildren,
+    /// Emits a `SiteMapEvent::Comment` for each comment of the document,
+    /// when it is read, so before the entry containing it. `false` by
+    /// default, the comments are skipped.
+    pub emit_comments: bool,
+}
+/// Policy for the fields repeated in an entry, see
+/// `ReaderOptions::duplicate_children`.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub enum DuplicateChildren {
+    /// Keeps the value of the last element.
+    #[default]
+    LastWins,
+    /// Keeps the value of the first element.
+    FirstWins,
+    /// Keeps the value of the first element and gives an `Error::Parse` at
+    /// the repeated element, which ends the iteration in strict mode.
+    Error,
+    /// Keeps the value of the last element and adds an `Error::Parse` at
+    /// the repeated element to the warnings.
+    Warn,
+}
+impl Default for ReaderOptions {
+    fn default() -> ReaderOptions {
+        ReaderOptions {
+            strict: false,
+            max_urls: None,
+            max_entries: None,
+            truncate: false,
+            require_namespace: false,
+            trim_whitespace: false,
+            skip_leading_garbage: false,
+            case_insensitive_tags: false,
+            root_events: false,
+            max_depth: 32,
+            max_text_length: 4096,
+            max_extension_text_length: 65536,
+            allow_doctype: false,
+            max_bytes: None,
+            parse_urls: true,
+            capture_unknown: false,
+            emit_unknown: false,
+            progress_entries: 1000,
+            progress_bytes: 1 << 20,
+            duplicate_children: DuplicateChildren::LastWins,
+            emit_comments: false,
+        }
+    }
+}
+/// Byte limits of a source, counted while the parser reads it, so that its
+/// buffers can not grow without bounds.
+pub(crate) struct SourceLimits {
+    max_bytes: Option<u64>,
+    max_run: u64,
+    bytes: u64,
+    run: u64,
+    exceeded: Option<(crate::ResourceKind, u64)>,
+}
+impl SourceLimits {
+    pub(crate) fn new(options: &ReaderOptions) -> SourceLimits {
+        SourceLimits {
+            max_bytes: options.max_bytes,
+            max_run: (options.max_text_length.max(options.max_extension_text_length) as u64)
+                .saturating_mul(8),
+            bytes: 0,
+            run: 0,
+            exceeded: None,
+        }
+    }
+    /// Counts the bytes read, failing once over a limit.
+    pub(crate) fn count(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.bytes += data.len() as u64;
+        // bytes between two `<`, the run continues from the previous data
+        let mut runs = data.split(|&byte| byte == b'<');
+        self.run += runs.next().map_or(0, <[u8]>::len) as u64;
+        let mut longest = self.run;
+        for run in runs {
+            self.run = run.len() as u64;
+            longest = longest.max(self.run);
+        }
+        let (which, limit) = match self.max_bytes {
+            Some(max_bytes) if self.bytes > max_bytes => (crate::ResourceKind::Bytes, max_bytes),
+            _ if longest > self.max_run => (crate::ResourceKind::TextLength, self.max_run),
+            _ => return Ok(()),
+        };
+        self.exceeded = Some((which, limit));
+        Err(Error::ResourceLimit { which, limit })
+    }
+    /// The limit exceeded, in place of the I/O error seen by the parser.
+    pub(crate) fn take_error(&mut self) -> Option<Error> {
+        self.exceeded.take().map(|(which, limit)| Error::ResourceLimit { which, limit })
+    }
+}
+/// Stream counting the bytes read, the compressed bytes of the gzip
+/// readers.
+#[cfg(feature = "gzip")]
+pub struct CountingReader<R> {
+    inner: R,
+    count: Arc<AtomicU64>,
+}
+#[cfg(feature = "gzip")]
+impl<R: Read> CountingReader<R> {
+    fn new(inner: R) -> CountingReader<R> {
+        CountingReader { inner, count: Arc::new(AtomicU64::new(0)) }
+    }
+    /// Number of bytes read so far.
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+    /// Underlying stream.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+    /// Returns the underlying stream.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+#[cfg(feature = "gzip")]
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.count.fetch_add(read as u64, Ordering::Relaxed);
+        Ok(read)
+    }
+}
+/// Stream decompressed only when it starts with the gzip magic bytes.
+#[cfg(feature = "gzip")]
+pub enum MaybeGzip<R: Read> {
+    /// Uncompressed stream.
+    Plain(io::Chain<io::Cursor<Vec<u8>>, R>),
+    /// Gzip compressed stream.
+    Gzip(GzDecoder<CountingReader<io::Chain<io::Cursor<Vec<u8>>, R>>>),
+}
+#[cfg(feature = "gzip")]
+impl<R: Read> Read for MaybeGzip<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match *self {
+            MaybeGzip::Plain(ref mut reader) => reader.read(buf),
+            MaybeGzip::Gzip(ref mut reader) => reader.read(buf),
+        }
+    }
+}
+#[cfg(feature = "gzip")]
+impl<R: Read> SiteMapReader<GzDecoder<CountingReader<R>>> {
+    /// Creates a new reader over a gzip compressed stream.
+    ///
+    /// The stream is decompressed while parsing, never as a whole.
+    pub fn from_gzip(source: R) -> SiteMapReader<GzDecoder<CountingReader<R>>> {
+        let source = CountingReader::new(source);
+        let compressed = source.count.clone();
+        let mut reader = SiteMapReader::new(GzDecoder::new(source));
+        reader.compressed = Some(compressed);
+        reader
+    }
+}
+#[cfg(feature = "gzip")]
+impl<R: Read> SiteMapReader<MaybeGzip<R>> {
+    /// Creates a new reader, decompressing the stream if it starts with the
+    /// gzip magic bytes `0x1f 0x8b`.
+    pub fn from_maybe_gzip(mut source: R) -> Result<SiteMapReader<MaybeGzip<R>>, Error> {
+        let mut head = [0u8; 2];
+        let mut len = 0;
+        while len < head.len() {
+            match source.read(&mut head[len..]) {
+                Ok(0) => break,
+                Ok(read) => len += read,
+                Err(ref error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(Error::from(error)),
+            }
+        }
+        let is_gzip = head[..len] == [0x1f, 0x8b];
+        let source = io::Cursor::new(head[..len].to_vec()).chain(source);
+        if !is_gzip {
+            return Ok(SiteMapReader::new(MaybeGzip::Plain(source)));
+        }
+        let source = CountingReader::new(source);
+        let compressed = source.count.clone();
+        let mut reader = SiteMapReader::new(MaybeGzip::Gzip(GzDecoder::new(source)));
+        reader.compressed = Some(compressed);
+        Ok(reader)
+    }
+}
+/// Content of a `news:news` element being parsed.
+#[derive(Default)]
+struct NewsItem {
+    name: Option<String>,
+    language: Option<String>,
+    publication_date: Option<structs::LastMod>,
+    title: Option<String>,
+}
+impl NewsItem {
+    fn into_news(self) -> structs::News {
+        let publication = match (self.name, self.language) {
+            (Some(name), Some(language)) => structs::NewsPublication { name, language },
+            _ => return structs::News::Err(structs::NewsError::MissingPublication),
+        };
+        let publication_date = match self.publication_date {
+            Some(publication_date) => publication_date,
+            None => return structs::News::Err(structs::NewsError::MissingPublicationDate),
+        };
+        match self.title {
+            Some(title) => {
+                structs::News::Entry(Box::new(structs::NewsEntry {
+                    publication,
+                    publication_date,
+                    title,
+                }))
+            }
+            None => structs::News::Err(structs::NewsError::MissingTitle),
+        }
+    }
+}
+/// Kind of sitemap document, from its root element.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum DocumentKind {
+    /// `<urlset>` document.
+    UrlSet,
+    /// `<sitemapindex>` document.
+    SiteMapIndex,
+    /// Document with another root element.
+    Unknown,
+}
+/// Sitemap event.
+#[derive(Debug,Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum SiteMapEvent {
+    /// Start of a `<urlset>`, before its entries, with
+    /// `ReaderOptions::root_events`.
+    UrlSetStart {
+        /// Namespaces declared by the root element, see
+        /// `SiteMapReader::root_namespaces`.
+        namespaces: Vec<(String, String)>,
+    },
+    /// Start of a `<sitemapindex>`, before its entries, with
+    /// `ReaderOptions::root_events`.
+    IndexStart {
+        /// Namespaces declared by the root element, see
+        /// `SiteMapReader::root_namespaces`.
+        namespaces: Vec<(String, String)>,
+    },
+    /// Url entry.
+    Url(structs::UrlEntry),
+    /// Sitemap entry.
+    SiteMap(structs::SiteMapEntry),
+    /// Last event of a reader over one of its limits, with
+    /// `ReaderOptions::truncate`, the rest of the document is not read.
+    Truncated {
+        /// Limit reached.
+        reason: TruncateReason,
+    },
+    /// Unknown child element of the root element or of an entry, after its
+    /// end, with `ReaderOptions::emit_unknown`.
+    Unknown {
+        /// Name of the element, with its prefix.
+        name: OwnedName,
+        /// Nesting of the element, 1 for the root element.
+        depth: usize,
+        /// Element as XML, without the namespace declarations.
+        raw: String,
+        /// Position of the start of the element.
+        position: structs::Position,
+    },
+    /// Comment of the document, without the whitespace around, with
+    /// `ReaderOptions::emit_comments`.
+    Comment(String),
+}
+/// Limit of a reader ending a `SiteMapEvent::Truncated` iteration.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum TruncateReason {
+    /// `ReaderOptions::max_urls` urls read.
+    Urls(u64),
+    /// `ReaderOptions::max_entries` entries read.
+    Entries(u64),
+    /// `ReaderOptions::max_bytes` bytes read.
+    Bytes(u64),
+}
+/// Problem of an entry not ending the reading in lenient mode, see
+/// `SiteMapReader::warnings`.
+#[derive(Debug,Clone,PartialEq)]
+pub struct Warning {
+    /// Position of the field, or of the entry, in the document.
+    pub position: Option<structs::Position>,
+    /// Index of the entry among the urls and sitemaps of the document,
+    /// starting from 0.
+    pub entry_index: usize,
+    /// Kind of the problem.
+    pub kind: WarningKind,
+    /// Description of the problem.
+    pub message: String,
+}
+/// Kind of a `Warning`, for each value kept or recovered by the reader.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum WarningKind {
+    /// `<loc>` that is not a valid url, kept in `Location::Err`.
+    InvalidLoc,
+    /// `<lastmod>` that is not a date, kept in `LastMod::Err`.
+    InvalidLastMod,
+    /// `<lastmod>` in RFC 2822 or RFC 3339 instead of a W3C datetime.
+    NonStandardLastMod,
+    /// Unknown `<changefreq>`, kept in `ChangeFreq::Err`.
+    InvalidChangeFreq,
+    /// `<changefreq>` not in lowercase, read ignoring the case, reported
+    /// in strict mode only.
+    ChangeFreqCase,
+    /// `<priority>` that is not a number.
+    InvalidPriority,
+    /// `<priority>` outside of 0.0 and 1.0.
+    PriorityOutOfRange,
+    /// `<news:news>` without its required children.
+    InvalidNews,
+    /// Field repeated in an entry, with `DuplicateChildren::Warn`.
+    DuplicateChild,
+    /// Child of `<sitemap>` that is a field of urls, like `<changefreq>`.
+    SiteMapUrlField,
+    /// `<image:image>` without `<image:loc>`, kept with `Location::None`.
+    MissingImageLoc,
+}
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.position {
+            Some(position) => write!(f, "{}:{} {}", position.line, position.column, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+impl From<Warning> for Error {
+    /// `Error::Parse` of the warning, line and column are 0 without
+    /// position, `Error::MissingElement` of the missing children.
+    fn from(warning: Warning) -> Error {
+        if let WarningKind::MissingImageLoc = warning.kind {
+            return Error::MissingElement {
+                position: warning.position,
+                element: "image:image",
+                child: "image:loc",
+            };
+        }
+        Error::Parse {
+            line: warning.position.map_or(0, |position| position.line),
+            column: warning.position.map_or(0, |position| position.column),
+            message: warning.message,
+        }
+    }
 }
-/// Sitemap entry.
+/// Sitemap entry, item of the iterator returned by `SiteMapReader::entities`.
 #[derive(Debug,Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum SiteMapEntity {
     /// Url entry.
     Url(structs::UrlEntry),
@@ -60,81 +799,1455 @@ pub enum SiteMapEntity {
 impl<T: Read + Sized> SiteMapReader<T> {
     /// Creates a new reader, consuming the given stream.
     pub fn new(source: T) -> SiteMapReader<T> {
-        let parser = EventReader::new(source).into_iter();
+        SiteMapReader::with_options(source, ReaderOptions::default())
+    }
+    /// Creates a new reader with the given options.
+    ///
+    /// A UTF-8 byte order mark and the whitespace before the XML declaration
+    /// are skipped, a document starting with anything else than XML is an
+    /// `Error::NotXml`.
+    pub fn with_options(source: T, options: ReaderOptions) -> SiteMapReader<T> {
+        let prelude = Prelude {
+            inner: source,
+            buffer: io::Cursor::new(Vec::new()),
+            line: 0,
+            column: 0,
+            limits: SourceLimits::new(&options),
+        };
         SiteMapReader {
+            state: ReaderState::new(options),
+            parser: Parser::Pending(prelude),
+            finished: false,
+            bytes: 0,
+            entries: 0,
+            #[cfg(feature = "gzip")]
+            compressed: None,
+            on_progress: None,
+        }
+    }
+    /// Problems of the entries read so far not ending the reading, the
+    /// invalid fields in lenient mode, with their kind and the index of
+    /// their entry.
+    pub fn warnings(&self) -> &[Warning] {
+        self.state.warnings()
+    }
+    /// Kind of the document, known once its root element has been read.
+    pub fn document_kind(&self) -> Option<DocumentKind> {
+        self.state.document_kind()
+    }
+    /// Namespaces declared by the root element, as `(prefix, namespace)`
+    /// pairs sorted by prefix, the default namespace has an empty prefix.
+    /// Empty until the root element has been read.
+    pub fn root_namespaces(&self) -> &[(String, String)] {
+        self.state.root_namespaces()
+    }
+    /// Stylesheets of the `<?xml-stylesheet?>` processing instructions
+    /// before the root element, read so far.
+    pub fn stylesheets(&self) -> &[structs::Stylesheet] {
+        self.state.stylesheets()
+    }
+    /// Sets `ReaderOptions::strict`.
+    pub fn strict(mut self, strict: bool) -> SiteMapReader<T> {
+        self.state.set_strict(strict);
+        self
+    }
+    /// Calls `callback` with the counters each time
+    /// `ReaderOptions::progress_entries` entries or
+    /// `ReaderOptions::progress_bytes` bytes more have been read, whichever
+    /// comes first, and once at the end of the iteration.
+    pub fn on_progress<F: FnMut(Progress) + Send + 'static>(mut self, callback: F) -> SiteMapReader<T> {
+        self.on_progress = Some(OnProgress {
+            callback: Box::new(callback),
+            last: Progress::default(),
+            ended: false,
+        });
+        self
+    }
+    /// Number of bytes read from the source so far, decompressed for the
+    /// gzip streams.
+    ///
+    /// The parser reads ahead, the bytes may not all be parsed yet.
+    pub fn bytes_read(&self) -> u64 {
+        let prelude = match self.parser {
+            Parser::Pending(ref prelude) => prelude,
+            Parser::Xml(ref parser) => parser.source(),
+            #[cfg(feature = "fast-xml")]
+            Parser::Quick(ref parser) => parser.source(),
+            Parser::Ended => return self.bytes,
+        };
+        prelude.limits.bytes
+    }
+    /// Number of compressed bytes read from the source so far, for the
+    /// readers created by `SiteMapReader::from_gzip` or
+    /// `SiteMapReader::from_maybe_gzip` of a compressed stream.
+    pub fn compressed_bytes_read(&self) -> Option<u64> {
+        #[cfg(feature = "gzip")]
+        {
+            self.compressed.as_ref().map(|count| count.load(Ordering::Relaxed))
+        }
+        #[cfg(not(feature = "gzip"))]
+        {
+            None
+        }
+    }
+    /// Number of url and sitemap entries emitted so far.
+    pub fn entries_emitted(&self) -> u64 {
+        self.entries
+    }
+    /// Counters of the bytes read and entries emitted so far.
+    pub fn progress(&self) -> Progress {
+        Progress {
+            bytes: self.bytes_read(),
+            compressed_bytes: self.compressed_bytes_read(),
+            entries: self.entries,
+        }
+    }
+    /// Updates the counters after `event`, calling the progress callback
+    /// when due.
+    fn count(&mut self, event: &Option<Result<SiteMapEvent, Error>>) {
+        if let Some(Ok(SiteMapEvent::Url(_))) | Some(Ok(SiteMapEvent::SiteMap(_))) = *event {
+            self.entries += 1;
+        }
+        self.bytes = self.bytes_read();
+        let progress = self.progress();
+        let options = &self.state.options;
+        if let Some(ref mut on_progress) = self.on_progress {
+            let due = match *event {
+                None => !on_progress.ended,
+                Some(_) => {
+                    progress.entries - on_progress.last.entries >= options.progress_entries ||
+                    progress.bytes - on_progress.last.bytes >= options.progress_bytes
+                }
+            };
+            if due {
+                on_progress.ended = event.is_none();
+                on_progress.last = progress;
+                (on_progress.callback)(progress);
+            }
+        }
+    }
+    /// Position of the last event in the document, including the skipped
+    /// bytes.
+    fn text_position(parser: &Events<Prelude<T>>) -> structs::Position {
+        let position = Position::position(&**parser);
+        let prelude = parser.source();
+        structs::Position {
+            line: position.row + prelude.line + 1,
+            column: position.column + if position.row == 0 { prelude.column } else { 0 } + 1,
+        }
+    }
+    /// Attributes without namespace, as `(name, value)` pairs.
+    fn attributes(attributes: Vec<OwnedAttribute>) -> Vec<(String, String)> {
+        attributes.into_iter()
+            .filter(|attribute| attribute.name.namespace.is_none())
+            .map(|attribute| (attribute.name.local_name, attribute.value))
+            .collect()
+    }
+}
+impl ReaderState {
+    pub(crate) fn new(options: ReaderOptions) -> ReaderState {
+        ReaderState {
             path: Vec::new(),
             url_item: structs::UrlEntry::new(),
+            image_item: structs::ImageEntry::new(),
+            video_item: structs::VideoEntry::new(),
+            news_item: NewsItem::default(),
             sitemap_item: structs::SiteMapEntry::new(),
-            parser: parser,
+            kind: None,
+            root_namespaces: None,
+            stylesheets: Vec::new(),
+            text: String::new(),
+            options,
+            urls: 0,
+            entries: 0,
+            warnings: Vec::new(),
+            aborted: false,
+            scope: Vec::new(),
+            capture: None,
+            outer: 0,
+            sitemap_extra: None,
+            duplicate: None,
         }
     }
-    fn open_tag(&mut self) {
-        if self.path == vec!["urlset", "url"] {
+    /// Reads the elements as children of the root element of `kind`, for
+    /// the fragments of a single entry.
+    fn fragment(&mut self, kind: DocumentKind) {
+        let root = match kind {
+            DocumentKind::SiteMapIndex => "sitemapindex",
+            _ => "urlset",
+        };
+        self.path.push(root.to_string());
+        self.scope.push(Vec::new());
+        self.kind = Some(kind);
+        self.outer = 1;
+    }
+    pub(crate) fn set_strict(&mut self, strict: bool) {
+        self.options.strict = strict;
+    }
+    pub(crate) fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+    /// Whether the last event ended the document, following the options.
+    pub(crate) fn aborted(&self) -> bool {
+        self.aborted
+    }
+    /// Kind of the document, known once its root element has been read.
+    pub(crate) fn document_kind(&self) -> Option<DocumentKind> {
+        self.kind
+    }
+    /// Whether the elements are passed with their `RawStart`, following
+    /// `ReaderOptions::capture_unknown` and `ReaderOptions::emit_unknown`.
+    pub(crate) fn captures(&self) -> bool {
+        self.options.capture_unknown || self.options.emit_unknown
+    }
+    /// Whether an element is open.
+    pub(crate) fn in_element(&self) -> bool {
+        self.path.len() > self.outer
+    }
+    /// Namespaces declared by the root element, as `(prefix, namespace)`
+    /// pairs, the prefix of the default namespace is empty.
+    pub(crate) fn root_namespaces(&self) -> &[(String, String)] {
+        self.root_namespaces.as_deref().unwrap_or(&[])
+    }
+    /// Keeps the namespaces of the first root element, sorted by prefix.
+    pub(crate) fn set_root_namespaces(&mut self, mut namespaces: Vec<(String, String)>) {
+        if self.root_namespaces.is_none() {
+            namespaces.sort();
+            self.root_namespaces = Some(namespaces);
+        }
+    }
+    pub(crate) fn stylesheets(&self) -> &[structs::Stylesheet] {
+        &self.stylesheets
+    }
+    /// Handles a processing instruction, keeping the stylesheets before
+    /// the root element.
+    pub(crate) fn processing_instruction(&mut self, name: &str, data: &str) {
+        if name == "xml-stylesheet" && self.kind.is_none() && !self.in_element() {
+            if let Some(stylesheet) = structs::Stylesheet::from_data(data) {
+                self.stylesheets.push(stylesheet);
+            }
+        }
+    }
+    /// Handles the start of an element.
+    pub(crate) fn start_element(&mut self,
+                                local_name: &str,
+                                namespace: Option<&str>,
+                                attributes: &[(String, String)],
+                                raw: Option<RawStart>,
+                                position: structs::Position)
+                                -> Option<Result<SiteMapEvent, Error>> {
+        self.text.clear();
+        if self.path.len() >= self.options.max_depth {
+            self.aborted = true;
+            return Some(Err(Error::ResourceLimit {
+                which: crate::ResourceKind::Depth,
+                limit: self.options.max_depth as u64,
+            }));
+        }
+        let tag = self.tag_name(local_name, namespace);
+        self.path.push(tag);
+        if let Some(raw) = raw {
+            self.scope.push(raw.namespaces);
+            let unknown = self.unknown();
+            let keep = unknown && self.options.capture_unknown && self.path[0] == "urlset" && self.path.len() == 3;
+            let emit = unknown && self.options.emit_unknown;
+            if self.capture.is_some() || keep || emit {
+                let name = OwnedName {
+                    local_name: local_name.to_string(),
+                    namespace: namespace.map(str::to_string),
+                    prefix: raw.prefix,
+                };
+                return self.capture_start(name, raw.attributes, position, keep, emit);
+            }
+        }
+        let mut root = None;
+        if self.path.len() == 1 {
+            if self.kind.is_none() {
+                root = Some(match self.path[0].as_str() {
+                    "urlset" => DocumentKind::UrlSet,
+                    "sitemapindex" => DocumentKind::SiteMapIndex,
+                    _ => DocumentKind::Unknown,
+                });
+                self.kind = root;
+            }
+            if self.options.require_namespace || self.options.strict {
+                let message = match namespace {
+                    Some(structs::SITEMAP_NAMESPACE) | Some(structs::SITEMAP_NAMESPACE_084) => None,
+                    Some(namespace) => {
+                        Some(format!("{} element in the unknown namespace {}", local_name, namespace))
+                    }
+                    None => Some(format!("{} element without the sitemap namespace", local_name)),
+                };
+                if let Some(message) = message {
+                    self.aborted = true;
+                    return Some(Err(Error::Parse {
+                        line: position.line,
+                        column: position.column,
+                        message,
+                    }));
+                }
+            }
+        }
+        self.open_tag(attributes, position);
+        if !self.options.root_events {
+            return None;
+        }
+        let namespaces = self.root_namespaces().to_vec();
+        match root {
+            Some(DocumentKind::UrlSet) => Some(Ok(SiteMapEvent::UrlSetStart { namespaces })),
+            Some(DocumentKind::SiteMapIndex) => Some(Ok(SiteMapEvent::IndexStart { namespaces })),
+            _ => None,
+        }
+    }
+    /// Handles the end of an element, returning the completed entry if any.
+    pub(crate) fn end_element(&mut self,
+                              position: structs::Position)
+                              -> Option<Result<SiteMapEvent, Error>> {
+        if self.captures() {
+            self.scope.pop();
+            if let Some(ref mut capture) = self.capture {
+                let depth = self.path.len();
+                self.path.pop();
+                if let Some(name) = capture.open.pop() {
+                    capture.element.inner.push_str("</");
+                    capture.element.inner.push_str(&name);
+                    capture.element.inner.push('>');
+                    return None;
+                }
+                let capture = self.capture.take()?;
+                let event = if capture.emit {
+                    let element = &capture.element;
+                    let mut raw = String::with_capacity(2 * element.name.len() + element.inner.len() + 5);
+                    push_start_tag(&mut raw, &element.name, &element.attributes);
+                    raw.push_str(&element.inner);
+                    raw.push_str("</");
+                    raw.push_str(&element.name);
+                    raw.push('>');
+                    Some(Ok(SiteMapEvent::Unknown {
+                        name: capture.name,
+                        depth,
+                        raw,
+                        position: capture.position,
+                    }))
+                } else {
+                    None
+                };
+                if capture.keep {
+                    self.url_item.extensions.push(capture.element);
+                }
+                return event;
+            }
+        }
+        let text = ::std::mem::take(&mut self.text);
+        if !text.is_empty() {
+            self.text_content(text);
+        }
+        let event = self.close_tag(position);
+        self.path.pop();
+        if let Some(Ok(SiteMapEvent::Url(_))) | Some(Ok(SiteMapEvent::SiteMap(_))) = event {
+            if let Some(max_entries) = self.options.max_entries {
+                if self.entries >= max_entries {
+                    self.aborted = true;
+                    return Some(Err(Error::LimitExceeded {
+                        kind: crate::LimitKind::EntryCount,
+                        limit: max_entries,
+                    }));
+                }
+            }
+            self.entries += 1;
+        }
+        let warnings = match event {
+            Some(Ok(SiteMapEvent::Url(ref url_entry))) => {
+                if let Some(max_urls) = self.options.max_urls {
+                    if self.urls >= max_urls {
+                        self.aborted = true;
+                        return Some(Err(Error::LimitExceeded {
+                            kind: crate::LimitKind::UrlCount,
+                            limit: max_urls as u64,
+                        }));
+                    }
+                }
+                self.urls += 1;
+                url_entry.read_warnings(self.entries as usize - 1)
+            }
+            Some(Ok(SiteMapEvent::SiteMap(ref sitemap_entry))) => {
+                sitemap_entry.read_warnings(self.entries as usize - 1)
+            }
+            _ => return event,
+        };
+        if !self.options.strict {
+            self.warnings.extend(warnings);
+            return event;
+        }
+        match warnings.into_iter().next() {
+            Some(warning) => {
+                self.aborted = true;
+                Some(Err(Error::from(warning)))
+            }
+            None => event,
+        }
+    }
+    /// Handles text of the current element, the text is collected until
+    /// the end of the element.
+    pub(crate) fn characters(&mut self, data: &str) -> Option<Result<SiteMapEvent, Error>> {
+        if let Some(ref mut capture) = self.capture {
+            capture.element.inner.push_str(&escape_str_pcdata(data));
+            return self.capture_limit();
+        }
+        let limit = self.text_limit();
+        if self.text.len() + data.len() > limit {
+            self.aborted = true;
+            return Some(Err(Error::ResourceLimit {
+                which: crate::ResourceKind::TextLength,
+                limit: limit as u64,
+            }));
+        }
+        self.text.push_str(data);
+        None
+    }
+    /// Keeps the start of the element `name`, the first of an unknown
+    /// element or one of its descendants, with the namespaces of its
+    /// prefixes.
+    fn capture_start(&mut self,
+                     owned_name: OwnedName,
+                     attributes: Vec<(String, String)>,
+                     position: structs::Position,
+                     keep: bool,
+                     emit: bool)
+                     -> Option<Result<SiteMapEvent, Error>> {
+        let name = owned_name.borrow().to_repr();
+        let mut prefixes = vec![owned_name.prefix.as_deref().unwrap_or("")];
+        for (attribute, _) in &attributes {
+            if let Some((prefix, _)) = attribute.split_once(':') {
+                prefixes.push(prefix);
+            }
+        }
+        let mut namespaces = Vec::new();
+        for prefix in prefixes {
+            let namespace = self.scope.iter().rev().flatten().find(|(declared, _)| declared == prefix);
+            if let Some((prefix, namespace)) = namespace {
+                if !namespaces.iter().any(|(declared, _)| declared == prefix) {
+                    namespaces.push((prefix.clone(), namespace.clone()));
+                }
+            }
+        }
+        let capture = match self.capture {
+            Some(ref mut capture) => capture,
+            None => {
+                let element = structs::RawElement {
+                    name,
+                    attributes,
+                    inner: String::new(),
+                    namespaces,
+                };
+                self.capture = Some(Capture {
+                    element,
+                    open: Vec::new(),
+                    name: owned_name,
+                    position,
+                    keep,
+                    emit,
+                });
+                return None;
+            }
+        };
+        let element = &mut capture.element;
+        for namespace in namespaces {
+            if !element.namespaces.iter().any(|(declared, _)| *declared == namespace.0) {
+                element.namespaces.push(namespace);
+            }
+        }
+        push_start_tag(&mut element.inner, &name, &attributes);
+        capture.open.push(name);
+        self.capture_limit()
+    }
+    /// Whether the current element is an unknown child of the root element
+    /// or of an entry.
+    fn unknown(&self) -> bool {
+        match (self.path.len(), self.path[0].as_str()) {
+            (2, "urlset") => self.path[1] != "url",
+            (2, "sitemapindex") => self.path[1] != "sitemap",
+            (3, "urlset") => self.path[1] == "url" && !URL_CHILDREN.contains(&self.path[2].as_str()),
+            (3, "sitemapindex") => {
+                self.path[1] == "sitemap" && !SITEMAP_CHILDREN.contains(&self.path[2].as_str())
+            }
+            _ => false,
+        }
+    }
+    /// Error of a kept element with a content over the limit, if any.
+    fn capture_limit(&mut self) -> Option<Result<SiteMapEvent, Error>> {
+        let limit = self.options.max_extension_text_length;
+        match self.capture {
+            Some(ref capture) if capture.element.inner.len() > limit => {
+                self.aborted = true;
+                Some(Err(Error::ResourceLimit {
+                    which: crate::ResourceKind::TextLength,
+                    limit: limit as u64,
+                }))
+            }
+            _ => None,
+        }
+    }
+    /// Maximum length of the text of the current element.
+    pub(crate) fn text_limit(&self) -> usize {
+        match self.path.last() {
+            Some(tag) if tag.contains(':') => self.options.max_extension_text_length,
+            _ => self.options.max_text_length,
+        }
+    }
+    /// Handles a comment, emitted following the options.
+    pub(crate) fn comment(&self, text: &str) -> Option<Result<SiteMapEvent, Error>> {
+        if self.options.emit_comments {
+            Some(Ok(SiteMapEvent::Comment(text.trim().to_string())))
+        } else {
+            None
+        }
+    }
+    /// Handles a `<!DOCTYPE>`, rejected unless allowed by the options.
+    pub(crate) fn doctype(&mut self, position: structs::Position) -> Option<Result<SiteMapEvent, Error>> {
+        if self.options.allow_doctype {
+            return None;
+        }
+        self.aborted = true;
+        Some(Err(Error::Parse {
+            line: position.line,
+            column: position.column,
+            message: "DOCTYPE declarations are not allowed".to_string(),
+        }))
+    }
+    /// Path component of the element, with its conventional extension prefix.
+    fn tag_name(&self, local_name: &str, namespace: Option<&str>) -> String {
+        let local_name = if self.options.case_insensitive_tags && !self.options.strict {
+            local_name.to_lowercase()
+        } else {
+            local_name.to_string()
+        };
+        match namespace {
+            Some(structs::IMAGE_NAMESPACE) => format!("image:{}", local_name),
+            Some(structs::VIDEO_NAMESPACE) => format!("video:{}", local_name),
+            Some(structs::NEWS_NAMESPACE) => format!("news:{}", local_name),
+            Some(structs::XHTML_NAMESPACE) => format!("xhtml:{}", local_name),
+            _ => local_name,
+        }
+    }
+    fn attribute<'a>(attributes: &'a [(String, String)], name: &str) -> Option<&'a str> {
+        attributes.iter()
+            .find(|(attribute, _)| attribute == name)
+            .map(|(_, value)| value.as_str())
+    }
+    /// Name of the entry field the path points to, if any.
+    fn field_name(&self) -> Option<&'static str> {
+        if self.path.len() != 3 {
+            return None;
+        }
+        let fields: &[&'static str] = match (self.path[0].as_str(), self.path[1].as_str()) {
+            ("urlset", "url") => &["loc", "lastmod", "changefreq", "priority", "news:news"],
+            ("sitemapindex", "sitemap") => &["loc", "lastmod"],
+            _ => return None,
+        };
+        fields.iter().find(|&&field| field == self.path[2]).cloned()
+    }
+    fn open_tag(&mut self, attributes: &[(String, String)], position: structs::Position) {
+        if let Some(field) = self.field_name() {
+            let positions = if self.path[0] == "urlset" {
+                &mut self.url_item.positions
+            } else {
+                &mut self.sitemap_item.positions
+            };
+            match positions.field(field) {
+                // news are not replaced, their elements are merged
+                Some(first) if field != "news:news" => {
+                    self.duplicate = Some((first, position));
+                    if let DuplicateChildren::LastWins | DuplicateChildren::Warn = self.options.duplicate_children {
+                        positions.fields.retain(|&(name, _)| name != field);
+                        positions.fields.push((field, position));
+                    }
+                }
+                _ => positions.fields.push((field, position)),
+            }
+        }
+        if self.path == ["urlset", "url"] {
             self.url_item = structs::UrlEntry::new();
-        } else if self.path == vec!["sitemapindex", "sitemap"] {
+            // the url and its loc, lastmod, changefreq, priority and news
+            self.url_item.positions.fields.reserve_exact(6);
+            self.url_item.positions.set_start(position);
+        } else if self.path == ["urlset", "url", "image:image"] {
+            self.image_item = structs::ImageEntry::new();
+        } else if self.path == ["urlset", "url", "video:video"] {
+            self.video_item = structs::VideoEntry::new();
+        } else if self.path == ["urlset", "url", "news:news"] {
+            self.news_item = NewsItem::default();
+        } else if self.path == ["urlset", "url", "xhtml:link"] {
+            let rel = ReaderState::attribute(attributes, "rel");
+            let hreflang = ReaderState::attribute(attributes, "hreflang");
+            let href = ReaderState::attribute(attributes, "href");
+            if let (Some("alternate"), Some(hreflang), Some(href)) = (rel, hreflang, href) {
+                let href = self.location(href.to_string());
+                self.url_item.alternates.push(structs::Alternate {
+                    hreflang: hreflang.to_string(),
+                    href,
+                });
+            }
+        } else if self.path == ["sitemapindex", "sitemap"] {
             self.sitemap_item = structs::SiteMapEntry::new();
+            self.sitemap_item.positions.fields.reserve_exact(3);
+            self.sitemap_item.positions.set_start(position);
+        } else if self.is_sitemap_extra() {
+            self.sitemap_extra = Some((position, String::new()));
         }
     }
+    /// Error of the repeated field ending, started at `start` and first
+    /// read at `first`, reported as `ReaderOptions::duplicate_children`.
+    fn duplicate_error(&mut self,
+                       first: structs::Position,
+                       start: structs::Position)
+                       -> Option<Result<SiteMapEvent, Error>> {
+        let warning = Warning {
+            position: Some(start),
+            entry_index: self.entries as usize,
+            kind: WarningKind::DuplicateChild,
+            message: format!("duplicate {} in {}, first at {}:{}", self.path[2], self.path[1], first.line, first.column),
+        };
+        match self.options.duplicate_children {
+            DuplicateChildren::LastWins | DuplicateChildren::FirstWins => None,
+            DuplicateChildren::Warn => {
+                self.warnings.push(warning);
+                None
+            }
+            DuplicateChildren::Error => {
+                if self.options.strict {
+                    self.aborted = true;
+                }
+                Some(Err(Error::from(warning)))
+            }
+        }
+    }
+    /// `SiteMapEvent::Truncated` in place of the error of a limit, with
+    /// `ReaderOptions::truncate`.
+    pub(crate) fn truncate(&self, event: Option<Result<SiteMapEvent, Error>>) -> Option<Result<SiteMapEvent, Error>> {
+        if !self.options.truncate {
+            return event;
+        }
+        let reason = match event {
+            Some(Err(Error::LimitExceeded { kind: crate::LimitKind::UrlCount, limit })) => TruncateReason::Urls(limit),
+            Some(Err(Error::LimitExceeded { kind: crate::LimitKind::EntryCount, limit })) => {
+                TruncateReason::Entries(limit)
+            }
+            Some(Err(Error::ResourceLimit { which: crate::ResourceKind::Bytes, limit })) => TruncateReason::Bytes(limit),
+            event => return event,
+        };
+        Some(Ok(SiteMapEvent::Truncated { reason }))
+    }
+    /// Whether the path points to a child of `<sitemap>` other than
+    /// `<loc>` and `<lastmod>`, like the `<changefreq>` and `<priority>`
+    /// of urls.
+    fn is_sitemap_extra(&self) -> bool {
+        self.path.len() == 3 && self.path[..2] == ["sitemapindex", "sitemap"] &&
+        !SITEMAP_CHILDREN.contains(&self.path[2].as_str())
+    }
     fn text_content(&mut self, data: String) {
-        if self.path == vec!["urlset", "url", "loc"] {
-            self.url_item.loc = structs::Location::from(data);
-        } else if self.path == vec!["urlset", "url", "lastmod"] {
-            self.url_item.lastmod = structs::LastMod::from(data);
-        } else if self.path == vec!["urlset", "url", "changefreq"] {
-            self.url_item.changefreq = structs::ChangeFreq::from(data);
-        } else if self.path == vec!["urlset", "url", "priority"] {
-            self.url_item.priority = structs::Priority::from(data);
-        } else if self.path == vec!["sitemapindex", "sitemap", "loc"] {
-            self.sitemap_item.loc = structs::Location::from(data);
-        } else if self.path == vec!["sitemapindex", "sitemap", "lastmod"] {
-            self.sitemap_item.lastmod = structs::LastMod::from(data);
-        }
-    }
-    fn close_tag(&mut self) -> Option<SiteMapEntity> {
-        if self.path == vec!["urlset", "url"] {
-            return Some(SiteMapEntity::Url(self.url_item.clone()));
-        } else if self.path == vec!["sitemapindex", "sitemap"] {
-            return Some(SiteMapEntity::SiteMap(self.sitemap_item.clone()));
+        let data = if self.options.trim_whitespace && data.trim().len() != data.len() {
+            data.trim().to_string()
+        } else {
+            data
+        };
+        let value = if data.trim().len() == data.len() {
+            data.clone()
+        } else {
+            data.trim().to_string()
+        };
+        if self.duplicate.is_some() {
+            if let DuplicateChildren::FirstWins | DuplicateChildren::Error = self.options.duplicate_children {
+                return;
+            }
         }
-        return None;
+        if self.path == ["urlset", "url", "loc"] {
+            self.url_item.loc = self.location(value);
+        } else if self.path == ["urlset", "url", "lastmod"] {
+            self.url_item.lastmod = structs::LastMod::from(value);
+        } else if self.path == ["urlset", "url", "changefreq"] {
+            self.url_item.changefreq = structs::ChangeFreq::from(value);
+            let changefreq = &self.url_item.changefreq;
+            if self.options.strict && !changefreq.as_str().is_empty() && changefreq.as_str() != data.trim() {
+                let message = format!("'{}' is not in lowercase", data.trim());
+                let kind = WarningKind::ChangeFreqCase;
+                let warning = self.url_item.positions.warning(self.entries as usize, "changefreq", kind, message);
+                self.warnings.push(warning);
+            }
+        } else if self.path == ["urlset", "url", "priority"] {
+            self.url_item.priority = structs::Priority::from(value);
+        } else if self.path == ["urlset", "url", "image:image", "image:loc"] {
+            self.image_item.loc = structs::Location::from(value);
+        } else if self.path == ["urlset", "url", "image:image", "image:title"] {
+            self.image_item.title = Some(data);
+        } else if self.path == ["urlset", "url", "image:image", "image:caption"] {
+            self.image_item.caption = Some(data);
+        } else if self.path == ["urlset", "url", "image:image", "image:geo_location"] {
+            self.image_item.geo_location = Some(data);
+        } else if self.path == ["urlset", "url", "image:image", "image:license"] {
+            self.image_item.license = structs::Location::from(value);
+        } else if self.path.len() == 4 && self.path[..3] == ["urlset", "url", "video:video"] {
+            self.video_content(data, value);
+        } else if self.path == ["urlset", "url", "news:news", "news:publication", "news:name"] {
+            self.news_item.name = Some(data);
+        } else if self.path == ["urlset", "url", "news:news", "news:publication", "news:language"] {
+            self.news_item.language = Some(data);
+        } else if self.path == ["urlset", "url", "news:news", "news:publication_date"] {
+            self.news_item.publication_date = Some(structs::LastMod::from(value));
+        } else if self.path == ["urlset", "url", "news:news", "news:title"] {
+            self.news_item.title = Some(data);
+        } else if self.path == ["sitemapindex", "sitemap", "loc"] {
+            self.sitemap_item.loc = self.location(value);
+        } else if self.path == ["sitemapindex", "sitemap", "lastmod"] {
+            self.sitemap_item.lastmod = structs::LastMod::from(value);
+        } else if let Some((_, ref mut text)) = self.sitemap_extra {
+            *text = value;
+        }
+    }
+    /// Location of a `<loc>` or of the `href` of an alternate, not parsed
+    /// without `ReaderOptions::parse_urls`.
+    fn location(&self, value: String) -> structs::Location {
+        if self.options.parse_urls || self.options.strict {
+            structs::Location::from(value)
+        } else {
+            structs::Location::Raw(value)
+        }
+    }
+    fn video_content(&mut self, data: String, value: String) {
+        let video = &mut self.video_item;
+        match self.path[3].as_str() {
+            "video:thumbnail_loc" => video.thumbnail_loc = structs::Location::from(value),
+            "video:title" => video.title = Some(data),
+            "video:description" => video.description = Some(data),
+            "video:content_loc" => video.content_loc = structs::Location::from(value),
+            "video:player_loc" => video.player_loc = structs::Location::from(value),
+            "video:duration" => video.duration = structs::VideoDuration::from(value),
+            "video:publication_date" => video.publication_date = structs::LastMod::from(value),
+            "video:expiration_date" => video.expiration_date = structs::LastMod::from(value),
+            "video:tag" => video.tags.push(data),
+            "video:live" => {
+                video.live = match value.to_lowercase().as_str() {
+                    "yes" => Some(true),
+                    "no" => Some(false),
+                    _ => None,
+                }
+            }
+            _ => {}
+        }
+    }
+    fn close_tag(&mut self, position: structs::Position) -> Option<Result<SiteMapEvent, Error>> {
+        if let Some((first, start)) = self.duplicate.take() {
+            return self.duplicate_error(first, start);
+        }
+        if self.path == ["urlset", "url"] {
+            return Some(Ok(SiteMapEvent::Url(::std::mem::take(&mut self.url_item))));
+        } else if self.path == ["urlset", "url", "image:image"] {
+            let image = ::std::mem::take(&mut self.image_item);
+            if image.loc == structs::Location::None {
+                self.url_item.positions.fields.push(("image:image", position));
+            }
+            self.url_item.images.push(image);
+        } else if self.path == ["urlset", "url", "video:video"] {
+            let video = ::std::mem::take(&mut self.video_item);
+            self.url_item.videos.push(video);
+        } else if self.path == ["urlset", "url", "news:news"] {
+            let news = ::std::mem::take(&mut self.news_item);
+            self.url_item.news = news.into_news();
+        } else if self.path == ["sitemapindex", "sitemap"] {
+            return Some(Ok(SiteMapEvent::SiteMap(::std::mem::take(&mut self.sitemap_item))));
+        } else if self.is_sitemap_extra() {
+            let (start, text) = self.sitemap_extra.take()?;
+            let warning = Warning {
+                position: Some(start),
+                entry_index: self.entries as usize,
+                kind: WarningKind::SiteMapUrlField,
+                message: format!("{} '{}' is not a field of sitemap", self.path[2], text),
+            };
+            if self.options.strict {
+                self.aborted = true;
+                return Some(Err(Error::from(warning)));
+            }
+            self.warnings.push(warning);
+        }
+        None
     }
 }
+impl<T: Read + Sized> SiteMapReader<T> {
+    /// Starts the document, choosing its parser.
+    fn start(&mut self, mut prelude: Prelude<T>) -> Result<(), Error> {
+        if let Err(error) = prelude.start(self.state.options.skip_leading_garbage) {
+            self.bytes = prelude.limits.bytes;
+            return Err(error);
+        }
+        #[cfg(feature = "fast-xml")]
+        {
+            if prelude.is_utf8()? {
+                let origin = (prelude.line, prelude.column);
+                self.parser = Parser::Quick(crate::quick::Parser::new(prelude, origin, xml_error));
+                return Ok(());
+            }
+        }
+        let config = ParserConfig::new()
+            .max_entity_expansion_length(self.state.options.max_extension_text_length)
+            .ignore_comments(!self.state.options.emit_comments);
+        self.parser = Parser::Xml(EventReader::new_with_config(prelude, config).into_iter());
+        Ok(())
+    }
+    /// Next entry of a document parsed by xml-rs.
+    fn next_xml(&mut self) -> Option<Result<SiteMapEvent, Error>> {
+        let parser = match self.parser {
+            Parser::Xml(ref mut parser) => parser,
+            _ => return None,
+        };
+        while !self.finished {
+            match parser.next() {
+                Some(Ok(XmlEvent::StartElement { name, attributes, namespace })) => {
+                    let captures = self.state.captures();
+                    let namespaces = if !self.state.in_element() || captures {
+                        namespace.into_iter()
+                            .filter(|&(prefix, uri)| {
+                                prefix != "xml" && prefix != "xmlns" && !uri.is_empty()
+                            })
+                            .map(|(prefix, uri)| (prefix.to_string(), uri.to_string()))
+                            .collect()
+                    } else {
+                        Vec::new()
+                    };
+                    if !self.state.in_element() {
+                        self.state.set_root_namespaces(namespaces.clone());
+                    }
+                    let raw = if captures {
+                        Some(RawStart {
+                            prefix: name.prefix.clone(),
+                            namespaces,
+                            attributes: attributes.iter().map(|attribute| {
+                                (attribute.name.borrow().to_repr(), attribute.value.clone())
+                            }).collect(),
+                        })
+                    } else {
+                        None
+                    };
+                    let attributes = SiteMapReader::<T>::attributes(attributes);
+                    let position = SiteMapReader::text_position(parser);
+                    let event = self.state.start_element(&name.local_name,
+                                                         name.namespace.as_deref(),
+                                                         &attributes,
+                                                         raw,
+                                                         position);
+                    if event.is_some() {
+                        self.finished = self.state.aborted();
+                        return event;
+                    }
+                }
+                Some(Ok(XmlEvent::EndElement { .. })) => {
+                    let position = SiteMapReader::text_position(parser);
+                    let event = self.state.end_element(position);
+                    if event.is_some() {
+                        self.finished = self.state.aborted();
+                        return event;
+                    }
+                }
+                Some(Ok(XmlEvent::Characters(data))) | Some(Ok(XmlEvent::CData(data))) => {
+                    let event = self.state.characters(&data);
+                    if event.is_some() {
+                        self.finished = self.state.aborted();
+                        return event;
+                    }
+                }
+                Some(Ok(XmlEvent::Doctype { .. })) => {
+                    let position = SiteMapReader::text_position(parser);
+                    let event = self.state.doctype(position);
+                    if event.is_some() {
+                        self.finished = self.state.aborted();
+                        return event;
+                    }
+                }
+                Some(Ok(XmlEvent::Comment(text))) => {
+                    if let Some(event) = self.state.comment(&text) {
+                        return Some(event);
+                    }
+                }
+                Some(Ok(XmlEvent::ProcessingInstruction { name, data })) => {
+                    self.state.processing_instruction(&name, data.as_deref().unwrap_or(""));
+                }
+                Some(Ok(XmlEvent::EndDocument)) | None => {
+                    self.finished = true;
+                }
+                Some(Err(error)) => {
+                    self.finished = true;
+                    match parser.source_mut().limits.take_error() {
+                        Some(error) => return Some(Err(error)),
+                        None => return Some(Err(Error::XmlReadError(error))),
+                    }
+                }
+                Some(Ok(_)) => {}
+            }
+        }
+        None
+    }
+    /// Next entry of a document parsed by quick-xml.
+    #[cfg(feature = "fast-xml")]
+    fn next_quick(&mut self) -> Option<Result<SiteMapEvent, Error>> {
+        let parser = match self.parser {
+            Parser::Quick(ref mut parser) => parser,
+            _ => return None,
+        };
+        let event = match parser.next_event(&mut self.state) {
+            Some(Ok(event)) => event,
+            Some(Err((position, error))) => {
+                self.finished = true;
+                match (parser.source_mut().limits.take_error(), error) {
+                    (Some(error), _) => Err(error),
+                    (None, quick_xml::Error::Io(error)) => Err(Error::Io(io::Error::new(error.kind(), error.to_string()))),
+                    (None, error) => Err(xml_error(position, error.to_string())),
+                }
+            }
+            None => {
+                self.finished = true;
+                return None;
+            }
+        };
+        Some(event)
+    }
+}
+/// Error of the malformed documents parsed by quick-xml, at `position`.
+#[cfg(feature = "fast-xml")]
+fn xml_error(position: structs::Position, message: String) -> Error {
+    let position = xml::common::TextPosition {
+        row: position.line.saturating_sub(1),
+        column: position.column.saturating_sub(1),
+    };
+    Error::XmlReadError(xml::reader::Error::from((&position, message)))
+}
 impl<T: Read + Sized> Iterator for SiteMapReader<T> {
+    type Item = Result<SiteMapEvent, Error>;
+    fn next(&mut self) -> Option<Result<SiteMapEvent, Error>> {
+        let event = self.next_event();
+        let event = self.state.truncate(event);
+        self.count(&event);
+        event
+    }
+}
+impl<T: Read + Sized> SiteMapReader<T> {
+    fn next_event(&mut self) -> Option<Result<SiteMapEvent, Error>> {
+        if let Parser::Pending(_) = self.parser {
+            if let Parser::Pending(prelude) = mem::replace(&mut self.parser, Parser::Ended) {
+                if let Err(error) = self.start(prelude) {
+                    self.finished = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+        match self.parser {
+            #[cfg(feature = "fast-xml")]
+            Parser::Quick(_) => self.next_quick(),
+            _ => self.next_xml(),
+        }
+    }
+}
+impl<T: Read + Sized> FusedIterator for SiteMapReader<T> {}
+impl<T: Read + Sized> SiteMapReader<T> {
+    /// Iterator over `SiteMapEntity`, kept for compatibility, iterating the
+    /// reader itself is preferred.
+    pub fn entities(self) -> Entities<T> {
+        Entities { reader: self }
+    }
+}
+impl<T: Read + Sized> SiteMapReader<T> {
+    /// Iterator over the urls of a `<urlset>`, it ends with an error when
+    /// the document is a `<sitemapindex>`.
+    pub fn into_url_iter(self) -> UrlIter<T> {
+        UrlIter {
+            reader: self,
+            filter: UrlFilter::default(),
+        }
+    }
+    /// Stream over the urls of a `<urlset>`, see `into_url_iter`.
+    ///
+    /// The stream reads the source with blocking calls, use
+    /// `AsyncSiteMapReader::into_url_stream` for asynchronous sources.
+    #[cfg(feature = "async")]
+    pub fn into_url_stream(self) -> UrlStream<T> {
+        UrlStream { urls: self.into_url_iter() }
+    }
+}
+/// Urls out of the reader events, a `<sitemapindex>` ends them with an error.
+#[derive(Default)]
+pub(crate) struct UrlFilter {
+    finished: bool,
+}
+impl UrlFilter {
+    pub(crate) fn finished(&self) -> bool {
+        self.finished
+    }
+    /// Url or error out of `event`, `Some(None)` at the end of the urls and
+    /// `None` for the start of the `<urlset>`.
+    pub(crate) fn url(&mut self,
+                      event: Option<Result<SiteMapEvent, Error>>,
+                      kind: Option<DocumentKind>)
+                      -> Option<Option<Result<structs::UrlEntry, Error>>> {
+        let index = match event {
+            _ if self.finished => return Some(None),
+            Some(Ok(SiteMapEvent::UrlSetStart { .. })) |
+            Some(Ok(SiteMapEvent::Unknown { .. })) |
+            Some(Ok(SiteMapEvent::Truncated { .. })) |
+            Some(Ok(SiteMapEvent::Comment(_))) => return None,
+            Some(Ok(SiteMapEvent::Url(url_entry))) => return Some(Some(Ok(url_entry))),
+            Some(Err(error)) => return Some(Some(Err(error))),
+            Some(Ok(SiteMapEvent::IndexStart { .. })) | Some(Ok(SiteMapEvent::SiteMap(_))) => true,
+            None => kind == Some(DocumentKind::SiteMapIndex),
+        };
+        self.finished = true;
+        if index {
+            Some(Some(Err(Error::Invalid("expected urlset, found sitemapindex".to_string()))))
+        } else {
+            Some(None)
+        }
+    }
+}
+/// Iterator over the urls of a reader, returned by `SiteMapReader::into_url_iter`.
+pub struct UrlIter<T: Read + Sized> {
+    reader: SiteMapReader<T>,
+    filter: UrlFilter,
+}
+impl<T: Read + Sized> Iterator for UrlIter<T> {
+    type Item = Result<structs::UrlEntry, Error>;
+    fn next(&mut self) -> Option<Result<structs::UrlEntry, Error>> {
+        while !self.filter.finished() {
+            let event = self.reader.next();
+            if let Some(url) = self.filter.url(event, self.reader.document_kind()) {
+                return url;
+            }
+        }
+        None
+    }
+}
+impl<T: Read + Sized> FusedIterator for UrlIter<T> {}
+/// Stream over the urls of a reader, returned by `SiteMapReader::into_url_stream`.
+#[cfg(feature = "async")]
+pub struct UrlStream<T: Read + Sized> {
+    urls: UrlIter<T>,
+}
+#[cfg(feature = "async")]
+impl<T: Read + Sized + Unpin> Stream for UrlStream<T> {
+    type Item = Result<structs::UrlEntry, Error>;
+    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.urls.next())
+    }
+}
+/// Iterator over the `SiteMapEntity` of a reader.
+pub struct Entities<T: Read + Sized> {
+    reader: SiteMapReader<T>,
+}
+impl<T: Read + Sized> Iterator for Entities<T> {
     type Item = SiteMapEntity;
     fn next(&mut self) -> Option<SiteMapEntity> {
         loop {
-            let e = self.parser.next();
-            match e {
-                Some(e) => {
-                    match e {
-                        Ok(XmlEvent::StartElement { name, .. }) => {
-                            let tag_name = name.local_name.to_lowercase();
-                            self.path.push(tag_name.to_string());
-                            self.open_tag();
-                        }
-                        Ok(XmlEvent::EndElement { .. }) => {
-                            let entity = self.close_tag();
-                            self.path.pop();
-                            match entity {
-                                Some(entity) => {
-                                    return Some(entity);
-                                }
-                                None => {}
-                            }
-                        }
-                        Ok(XmlEvent::Characters(data)) => {
-                            self.text_content(data);
-                        }
-                        Err(error) => {
-                            return Some(SiteMapEntity::Err(error));
-                        }
-                        _ => {}
+            let entity = match self.reader.next()? {
+                Ok(SiteMapEvent::UrlSetStart { .. }) |
+                Ok(SiteMapEvent::IndexStart { .. }) |
+                Ok(SiteMapEvent::Unknown { .. }) |
+                Ok(SiteMapEvent::Truncated { .. }) |
+                Ok(SiteMapEvent::Comment(_)) => continue,
+                Ok(SiteMapEvent::Url(url_entry)) => SiteMapEntity::Url(url_entry),
+                Ok(SiteMapEvent::SiteMap(sitemap_entry)) => SiteMapEntity::SiteMap(sitemap_entry),
+                Err(Error::XmlReadError(error)) => SiteMapEntity::Err(error),
+                Err(Error::Parse { line, column, message }) => {
+                    let position = xml::common::TextPosition {
+                        row: line.saturating_sub(1),
+                        column: column.saturating_sub(1),
+                    };
+                    SiteMapEntity::Err(xml::reader::Error::from((&position, message)))
+                }
+                Err(error) => {
+                    let position = xml::common::TextPosition::new();
+                    SiteMapEntity::Err(xml::reader::Error::from((&position, error.to_string())))
+                }
+            };
+            return Some(entity);
+        }
+    }
+}
+/// Reader of text sitemaps, one absolute url per line.
+///
+/// It's provides iterator over `Result<UrlEntry, Error>`, the entries have
+/// only the `loc` field. Blank lines are skipped, invalid urls and lines
+/// over `MAX_URL_LENGTH` characters are `Error::Parse` errors.
+pub struct TextSiteMapReader<R: BufRead> {
+    source: R,
+    line: u64,
+    buffer: Vec<u8>,
+    finished: bool,
+}
+impl<R: BufRead> TextSiteMapReader<R> {
+    /// Creates a new reader, consuming the given stream.
+    pub fn new(source: R) -> TextSiteMapReader<R> {
+        TextSiteMapReader {
+            source,
+            line: 0,
+            buffer: Vec::new(),
+            finished: false,
+        }
+    }
+    fn error<M: Into<String>>(&self, column: u64, message: M) -> Error {
+        Error::Parse {
+            line: self.line,
+            column,
+            message: message.into(),
+        }
+    }
+    /// Entry of the current line, `None` for blank lines.
+    fn entry(&self) -> Option<Result<structs::UrlEntry, Error>> {
+        let line = match ::std::str::from_utf8(&self.buffer) {
+            Ok(line) => line,
+            Err(error) => {
+                let column = error.valid_up_to() as u64 + 1;
+                return Some(Err(self.error(column, "invalid UTF-8")));
+            }
+        };
+        let line = if self.line == 1 {
+            line.strip_prefix('\u{feff}').unwrap_or(line)
+        } else {
+            line
+        };
+        let trimmed = line.trim_start();
+        let column = (line.chars().count() - trimmed.chars().count()) as u64 + 1;
+        let loc = trimmed.trim_end();
+        if loc.is_empty() {
+            return None;
+        }
+        if loc.chars().count() > structs::MAX_URL_LENGTH {
+            let message = format!("line longer than {} characters", structs::MAX_URL_LENGTH);
+            return Some(Err(self.error(column + structs::MAX_URL_LENGTH as u64, message)));
+        }
+        match structs::Location::from(loc.to_string()) {
+            structs::Location::Url(url) => {
+                let position = structs::Position {
+                    line: self.line,
+                    column,
+                };
+                let mut url_entry = structs::UrlEntry::new();
+                url_entry.loc = structs::Location::Url(url);
+                url_entry.positions.set_start(position);
+                url_entry.positions.fields.push(("loc", position));
+                Some(Ok(url_entry))
+            }
+            _ => Some(Err(self.error(column, format!("invalid url '{}'", loc)))),
+        }
+    }
+}
+impl<R: BufRead> Iterator for TextSiteMapReader<R> {
+    type Item = Result<structs::UrlEntry, Error>;
+    fn next(&mut self) -> Option<Result<structs::UrlEntry, Error>> {
+        while !self.finished {
+            self.buffer.clear();
+            match self.source.read_until(b'\n', &mut self.buffer) {
+                Ok(0) => self.finished = true,
+                Ok(_) => {
+                    self.line += 1;
+                    let entry = self.entry();
+                    if entry.is_some() {
+                        return entry;
                     }
                 }
-                None => {
-                    return None;
+                Err(error) => {
+                    self.finished = true;
+                    return Some(Err(Error::Io(error)));
+                }
+            }
+        }
+        None
+    }
+}
+impl<R: BufRead> FusedIterator for TextSiteMapReader<R> {}
+/// Namespace of Atom feeds.
+#[cfg(feature = "feeds")]
+const ATOM_NAMESPACE: &str = "http://www.w3.org/2005/Atom";
+/// Reader of RSS 2.0 and Atom 1.0 feeds, enabled by the `feeds` feature.
+///
+/// It's provides iterator over `Result<UrlEntry, Error>`, with the link of
+/// each item as `loc` and its publication or update date as `lastmod`, the
+/// rest of the feed is ignored. Items without link are `Error::Parse`
+/// errors, the iteration ends after the first malformed XML error.
+#[cfg(feature = "feeds")]
+pub struct FeedReader<T: Read + Sized> {
+    parser: Events<T>,
+    path: Vec<String>,
+    entry: Option<structs::UrlEntry>,
+    text: String,
+    finished: bool,
+}
+#[cfg(feature = "feeds")]
+impl<T: Read + Sized> FeedReader<T> {
+    /// Creates a new reader, consuming the given stream.
+    pub fn new(source: T) -> FeedReader<T> {
+        FeedReader {
+            parser: EventReader::new(source).into_iter(),
+            path: Vec::new(),
+            entry: None,
+            text: String::new(),
+            finished: false,
+        }
+    }
+    fn text_position(&self) -> structs::Position {
+        let position = Position::position(&*self.parser);
+        structs::Position {
+            line: position.row + 1,
+            column: position.column + 1,
+        }
+    }
+    /// Whether the current element is an RSS item (`Some(false)`) or an
+    /// Atom entry (`Some(true)`) or one of their children.
+    fn in_item(&self, namespace: Option<&str>) -> Option<bool> {
+        if self.path.len() >= 3 && self.path[..3] == ["rss", "channel", "item"] &&
+           namespace.is_none() {
+            Some(false)
+        } else if self.path.len() >= 2 && self.path[..2] == ["feed", "entry"] &&
+                  namespace == Some(ATOM_NAMESPACE) {
+            Some(true)
+        } else {
+            None
+        }
+    }
+    fn start_element(&mut self,
+                     local_name: &str,
+                     namespace: Option<&str>,
+                     attributes: &[OwnedAttribute]) {
+        self.path.push(local_name.to_string());
+        self.text.clear();
+        let atom = match self.in_item(namespace) {
+            Some(atom) => atom,
+            None => return,
+        };
+        let position = self.text_position();
+        let depth = if atom { 2 } else { 3 };
+        if self.path.len() == depth {
+            let mut entry = structs::UrlEntry::new();
+            entry.positions.set_start(position);
+            self.entry = Some(entry);
+        } else if atom && self.path.len() == 3 && local_name == "link" {
+            let attribute = |name: &str| {
+                attributes.iter()
+                    .find(|attribute| attribute.name.namespace.is_none() &&
+                                      attribute.name.local_name == name)
+                    .map(|attribute| attribute.value.trim().to_string())
+            };
+            let rel = attribute("rel").unwrap_or_else(|| "alternate".to_string());
+            if let (Some(entry), Some(href)) = (self.entry.as_mut(), attribute("href")) {
+                if rel == "alternate" && entry.loc == structs::Location::None {
+                    entry.loc = structs::Location::from(href);
+                    entry.positions.fields.push(("loc", position));
+                }
+            }
+        } else if self.path.len() == depth + 1 {
+            let field = match local_name {
+                "link" => "loc",
+                "pubDate" | "updated" | "published" => "lastmod",
+                _ => return,
+            };
+            if let Some(entry) = self.entry.as_mut() {
+                entry.positions.fields.push((field, position));
+            }
+        }
+    }
+    fn end_element(&mut self, namespace: Option<&str>) -> Option<Result<structs::UrlEntry, Error>> {
+        let item = self.in_item(namespace);
+        let name = self.path.pop();
+        let atom = item?;
+        let depth = if atom { 1 } else { 2 };
+        if self.path.len() == depth {
+            let entry = self.entry.take()?;
+            if entry.loc == structs::Location::None {
+                let position = entry.positions.start().unwrap_or(self.text_position());
+                return Some(Err(Error::Parse {
+                    line: position.line,
+                    column: position.column,
+                    message: "feed item without link".to_string(),
+                }));
+            }
+            return Some(Ok(entry));
+        }
+        let entry = self.entry.as_mut()?;
+        if self.path.len() != depth + 1 {
+            return None;
+        }
+        let text = self.text.trim().to_string();
+        match (atom, name.as_deref()) {
+            (false, Some("link")) if entry.loc == structs::Location::None => {
+                entry.loc = structs::Location::from(text);
+            }
+            (false, Some("pubDate")) => {
+                entry.lastmod = match DateTime::parse_from_rfc2822(&text) {
+                    Ok(date) => structs::LastMod::DateTime(date),
+                    Err(_) => structs::LastMod::from(text),
+                };
+            }
+            (true, Some("updated")) => entry.lastmod = structs::LastMod::from(text),
+            (true, Some("published")) if entry.lastmod == structs::LastMod::None => {
+                entry.lastmod = structs::LastMod::from(text);
+            }
+            _ => {}
+        }
+        None
+    }
+}
+#[cfg(feature = "feeds")]
+impl<T: Read + Sized> Iterator for FeedReader<T> {
+    type Item = Result<structs::UrlEntry, Error>;
+    fn next(&mut self) -> Option<Result<structs::UrlEntry, Error>> {
+        while !self.finished {
+            match self.parser.next() {
+                Some(Ok(XmlEvent::StartElement { name, attributes, .. })) => {
+                    self.start_element(&name.local_name, name.namespace.as_deref(), &attributes);
                 }
+                Some(Ok(XmlEvent::EndElement { name })) => {
+                    let event = self.end_element(name.namespace.as_deref());
+                    if event.is_some() {
+                        return event;
+                    }
+                }
+                Some(Ok(XmlEvent::Characters(data))) | Some(Ok(XmlEvent::CData(data))) => {
+                    self.text.push_str(&data);
+                }
+                Some(Ok(XmlEvent::EndDocument)) | None => {
+                    self.finished = true;
+                }
+                Some(Err(error)) => {
+                    self.finished = true;
+                    return Some(Err(Error::XmlReadError(error)));
+                }
+                Some(Ok(_)) => {}
             }
         }
+        None
+    }
+}
+#[cfg(feature = "feeds")]
+impl<T: Read + Sized> FusedIterator for FeedReader<T> {}
+/// Content of a sitemap document.
+#[derive(Debug,Clone,PartialEq)]
+pub enum SiteMapDocument {
+    /// Urls of a `<urlset>`.
+    UrlSet(Vec<structs::UrlEntry>),
+    /// Sitemaps of a `<sitemapindex>`.
+    Index(Vec<structs::SiteMapEntry>),
+}
+/// Result of the parse functions.
+#[derive(Debug)]
+pub struct Parsed<T> {
+    /// Parsed content.
+    pub value: T,
+    /// Errors of the invalid entries, always empty with strict parsing.
+    pub warnings: Vec<Error>,
+}
+/// Reads the single entry element of `fragment`, a `<url>` with
+/// `DocumentKind::UrlSet` or a `<sitemap>` with `DocumentKind::SiteMapIndex`,
+/// outside its root element.
+///
+/// The entry is read like in a document, invalid fields failing only with
+/// `ReaderOptions::strict`. Fails with `Error::Invalid` without an entry or
+/// with more than one.
+pub(crate) fn read_fragment(fragment: &str, kind: DocumentKind, options: ReaderOptions) -> Result<SiteMapEvent, Error> {
+    let mut reader = SiteMapReader::with_options(fragment.as_bytes(), options);
+    reader.state.fragment(kind);
+    let name = match kind {
+        DocumentKind::SiteMapIndex => "sitemap",
+        _ => "url",
+    };
+    let mut entry = None;
+    for event in reader {
+        match event? {
+            SiteMapEvent::Url(_) | SiteMapEvent::SiteMap(_) if entry.is_some() => {
+                return Err(Error::Invalid(format!("fragment with more than one {} element", name)));
+            }
+            event @ SiteMapEvent::Url(_) | event @ SiteMapEvent::SiteMap(_) => entry = Some(event),
+            _ => {}
+        }
+    }
+    entry.ok_or_else(|| Error::Invalid(format!("fragment without a {} element", name)))
+}
+/// Parses a whole document.
+///
+/// With `strict` the first entry with an invalid field is an error,
+/// otherwise such entries are kept and their errors collected in the
+/// warnings. The errors of the reader, XML errors, limits, I/O errors and
+/// documents that are not XML, are always fatal, like a root element other
+/// than `<urlset>` and `<sitemapindex>`.
+pub fn parse<R: Read>(source: R, strict: bool) -> Result<Parsed<SiteMapDocument>, Error> {
+    let mut warnings = Vec::new();
+    let mut urls = Vec::new();
+    let mut sitemaps = Vec::new();
+    let mut reader = SiteMapReader::new(source);
+    for event in reader.by_ref() {
+        let errors = match event {
+            Ok(SiteMapEvent::UrlSetStart { .. }) |
+            Ok(SiteMapEvent::IndexStart { .. }) |
+            Ok(SiteMapEvent::Unknown { .. }) |
+            Ok(SiteMapEvent::Truncated { .. }) |
+            Ok(SiteMapEvent::Comment(_)) => continue,
+            Ok(SiteMapEvent::Url(url_entry)) => {
+                let errors = url_entry.errors();
+                urls.push(url_entry);
+                errors
+            }
+            Ok(SiteMapEvent::SiteMap(sitemap_entry)) => {
+                let errors = sitemap_entry.errors();
+                sitemaps.push(sitemap_entry);
+                errors
+            }
+            Err(error) => return Err(error),
+        };
+        if strict && !errors.is_empty() {
+            return Err(errors.into_iter().next().unwrap());
+        }
+        warnings.extend(errors);
+    }
+    let value = match reader.document_kind() {
+        Some(DocumentKind::UrlSet) if sitemaps.is_empty() => SiteMapDocument::UrlSet(urls),
+        Some(DocumentKind::SiteMapIndex) if urls.is_empty() => SiteMapDocument::Index(sitemaps),
+        Some(DocumentKind::Unknown) => {
+            return Err(Error::Invalid("document with a root element other than urlset and sitemapindex".to_string()));
+        }
+        Some(_) => {
+            return Err(Error::Invalid("document with both urls and sitemaps".to_string()));
+        }
+        None => {
+            return Err(Error::Invalid("document without urlset or sitemapindex".to_string()));
+        }
+    };
+    Ok(Parsed { value, warnings })
+}
+/// Parses a `<urlset>` document, see `parse`.
+pub fn parse_urls<R: Read>(source: R,
+                           strict: bool)
+                           -> Result<Parsed<Vec<structs::UrlEntry>>, Error> {
+    let parsed = parse(source, strict)?;
+    match parsed.value {
+        SiteMapDocument::UrlSet(urls) => {
+            Ok(Parsed {
+                value: urls,
+                warnings: parsed.warnings,
+            })
+        }
+        SiteMapDocument::Index(_) => {
+            Err(Error::Invalid("expected urlset, found sitemapindex".to_string()))
+        }
+    }
+}
+/// Parses a `<sitemapindex>` document, see `parse`.
+pub fn parse_index<R: Read>(source: R,
+                            strict: bool)
+                            -> Result<Parsed<Vec<structs::SiteMapEntry>>, Error> {
+    let parsed = parse(source, strict)?;
+    match parsed.value {
+        SiteMapDocument::Index(sitemaps) => {
+            Ok(Parsed {
+                value: sitemaps,
+                warnings: parsed.warnings,
+            })
+        }
+        SiteMapDocument::UrlSet(_) => {
+            Err(Error::Invalid("expected sitemapindex, found urlset".to_string()))
+        }
     }
 }
@@ -0,0 +1,213 @@
+//! Contains the resolution of sitemap indexes into the urls of their sitemaps.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate sitemap;
+//! extern crate url;
+//! use sitemap::resolver::{MemoryFetch, SiteMapIndexResolver};
+//! use url::Url;
+//! fn main() {
+//!     let fetcher = MemoryFetch::new()
+//!         .document("http://www.example.com/sitemap_index.xml",
+//!                   "<sitemapindex><sitemap><loc>http://www.example.com/sitemap.xml</loc></sitemap></sitemapindex>")
+//!         .document("http://www.example.com/sitemap.xml",
+//!                   "<urlset><url><loc>http://www.example.com/</loc></url></urlset>");
+//!     let resolver = SiteMapIndexResolver::new(fetcher);
+//!     let index = Url::parse("http://www.example.com/sitemap_index.xml").unwrap();
+//!     let urls = resolver.resolve(&index).collect::<Result<Vec<_>, _>>().unwrap();
+//!     assert_eq!(urls.len(), 1);
+//! }
+//! ```
+use crate::reader::{SiteMapEvent, SiteMapReader};
+use crate::structs::{SiteMapEntry, UrlEntry, MAX_URLS_PER_SITEMAP};
+use crate::{Error, LimitKind};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Read};
+use url::Url;
+
+/// Source of the sitemap documents.
+pub trait Fetch {
+    /// Opens the document at `url`.
+    fn fetch(&self, url: &Url) -> Result<Box<dyn Read>, Error>;
+}
+
+impl<F: Fetch + ?Sized> Fetch for &F {
+    fn fetch(&self, url: &Url) -> Result<Box<dyn Read>, Error> {
+        (**self).fetch(url)
+    }
+}
+
+/// Fetcher of documents kept in memory, mostly useful for tests.
+#[derive(Debug,Clone,Default)]
+pub struct MemoryFetch {
+    documents: HashMap<String, Vec<u8>>,
+}
+
+impl MemoryFetch {
+    /// Creates a fetcher without documents.
+    pub fn new() -> MemoryFetch {
+        MemoryFetch::default()
+    }
+
+    /// Adds the document returned for `url`.
+    pub fn document<U: AsRef<str>, D: Into<Vec<u8>>>(mut self, url: U, document: D) -> MemoryFetch {
+        self.documents.insert(url.as_ref().to_string(), document.into());
+        self
+    }
+}
+
+impl Fetch for MemoryFetch {
+    fn fetch(&self, url: &Url) -> Result<Box<dyn Read>, Error> {
+        match self.documents.get(url.as_str()) {
+            Some(document) => Ok(Box::new(io::Cursor::new(document.clone()))),
+            None => {
+                let message = format!("no document for {}", url);
+                Err(Error::Io(io::Error::new(io::ErrorKind::NotFound, message)))
+            }
+        }
+    }
+}
+
+/// Walks a `<sitemapindex>` and the indexes it contains, yielding the urls
+/// of all the sitemaps.
+pub struct SiteMapIndexResolver<F: Fetch> {
+    fetcher: F,
+    max_depth: usize,
+    max_urls: usize,
+}
+
+impl<F: Fetch> SiteMapIndexResolver<F> {
+    /// Creates a new resolver, fetching the documents with `fetcher`.
+    pub fn new(fetcher: F) -> SiteMapIndexResolver<F> {
+        SiteMapIndexResolver {
+            fetcher,
+            max_depth: 1,
+            max_urls: MAX_URLS_PER_SITEMAP * MAX_URLS_PER_SITEMAP,
+        }
+    }
+
+    /// Maximum number of nested indexes, by default 1: an index of
+    /// sitemaps, as in the protocol.
+    pub fn max_depth(mut self, max_depth: usize) -> SiteMapIndexResolver<F> {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Maximum number of urls yielded, 50000 sitemaps of 50000 urls by
+    /// default.
+    pub fn max_urls(mut self, max_urls: usize) -> SiteMapIndexResolver<F> {
+        self.max_urls = max_urls;
+        self
+    }
+
+    /// Iterator over the urls of the document at `url`, depth first in
+    /// document order.
+    ///
+    /// Errors of a sitemap, including cycles and indexes over the maximum
+    /// depth, are yielded and the iteration moves to the next sitemap; it
+    /// ends after the maximum number of urls.
+    pub fn resolve(&self, url: &Url) -> Resolve<'_, F> {
+        Resolve {
+            resolver: self,
+            root: Some(url.clone()),
+            documents: Vec::new(),
+            visited: HashSet::new(),
+            urls: 0,
+            finished: false,
+        }
+    }
+}
+
+/// Iterator over the urls of an index, returned by
+/// `SiteMapIndexResolver::resolve`.
+pub struct Resolve<'a, F: Fetch> {
+    resolver: &'a SiteMapIndexResolver<F>,
+    root: Option<Url>,
+    documents: Vec<SiteMapReader<Box<dyn Read>>>,
+    visited: HashSet<Url>,
+    urls: usize,
+    finished: bool,
+}
+
+impl<'a, F: Fetch> Resolve<'a, F> {
+    /// Starts reading the document at `url`, nested at `depth`.
+    fn open(&mut self, url: Url, depth: usize) -> Result<(), Error> {
+        if depth > self.resolver.max_depth {
+            return Err(Error::Invalid(format!("sitemap {} over the maximum depth of {}",
+                                              url,
+                                              self.resolver.max_depth)));
+        }
+        if self.visited.contains(&url) {
+            return Err(Error::Invalid(format!("sitemap {} already visited", url)));
+        }
+        let source = self.resolver.fetcher.fetch(&url)?;
+        self.visited.insert(url);
+        self.documents.push(SiteMapReader::new(source));
+        Ok(())
+    }
+
+    fn sitemap(&mut self, sitemap_entry: SiteMapEntry) -> Result<(), Error> {
+        match sitemap_entry.loc.get_url() {
+            Some(url) => {
+                let depth = self.documents.len();
+                self.open(url, depth)
+            }
+            None => {
+                let error = sitemap_entry.errors().into_iter().next();
+                Err(error.unwrap_or_else(|| Error::Invalid("sitemap without loc".to_string())))
+            }
+        }
+    }
+}
+
+impl<'a, F: Fetch> Iterator for Resolve<'a, F> {
+    type Item = Result<UrlEntry, Error>;
+    fn next(&mut self) -> Option<Result<UrlEntry, Error>> {
+        if self.finished {
+            return None;
+        }
+        if let Some(root) = self.root.take() {
+            if let Err(error) = self.open(root, 0) {
+                self.finished = true;
+                return Some(Err(error));
+            }
+        }
+        loop {
+            let event = match self.documents.last_mut() {
+                Some(document) => document.next(),
+                None => {
+                    self.finished = true;
+                    return None;
+                }
+            };
+            match event {
+                Some(Ok(SiteMapEvent::Url(url_entry))) => {
+                    if self.urls >= self.resolver.max_urls {
+                        self.finished = true;
+                        return Some(Err(Error::LimitExceeded {
+                            kind: LimitKind::UrlCount,
+                            limit: self.resolver.max_urls as u64,
+                        }));
+                    }
+                    self.urls += 1;
+                    return Some(Ok(url_entry));
+                }
+                Some(Ok(SiteMapEvent::SiteMap(sitemap_entry))) => {
+                    if let Err(error) = self.sitemap(sitemap_entry) {
+                        return Some(Err(error));
+                    }
+                }
+                Some(Ok(SiteMapEvent::UrlSetStart { .. })) |
+                Some(Ok(SiteMapEvent::IndexStart { .. })) |
+                Some(Ok(SiteMapEvent::Unknown { .. })) |
+                Some(Ok(SiteMapEvent::Truncated { .. })) |
+                Some(Ok(SiteMapEvent::Comment(_))) => {}
+                Some(Err(error)) => return Some(Err(error)),
+                None => {
+                    self.documents.pop();
+                }
+            }
+        }
+    }
+}
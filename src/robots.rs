@@ -0,0 +1,46 @@
+//! Contains the discovery of sitemaps from the `Sitemap:` directives of a
+//! robots.txt file.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate sitemap;
+//! use sitemap::robots;
+//! fn main() {
+//!     let robots_txt = "User-agent: *\nDisallow: /private\nSitemap: http://www.example.com/sitemap.xml\n";
+//!     let sitemaps = robots::extract_sitemaps(robots_txt);
+//!     assert_eq!(sitemaps[0].as_ref().unwrap().as_str(), "http://www.example.com/sitemap.xml");
+//! }
+//! ```
+use crate::structs::Location;
+use crate::Error;
+use url::Url;
+
+/// Urls of the `Sitemap:` directives, in order of appearance.
+///
+/// Urls that can't be parsed, including relative ones, are returned as
+/// `Error::InvalidValue` with the raw value, to be resolved by the caller.
+pub fn extract_sitemaps(robots_txt: &str) -> Vec<Result<Url, Error>> {
+    let robots_txt = robots_txt.strip_prefix('\u{feff}').unwrap_or(robots_txt);
+    robots_txt.lines()
+        .filter_map(sitemap_directive)
+        .map(|value| match Location::from(value.to_string()) {
+            Location::Url(url) => Ok(url),
+            Location::Err { error, raw } => Err(Error::invalid_value("Sitemap", raw, error.to_string())),
+            Location::None | Location::Raw(_) => Err(Error::invalid_value("Sitemap", value, "missing url")),
+        })
+        .collect()
+}
+
+/// Value of a `Sitemap:` directive, without comments.
+fn sitemap_directive(line: &str) -> Option<&str> {
+    let line = line.split('#').next().unwrap_or("");
+    let mut parts = line.splitn(2, ':');
+    let name = parts.next()?.trim();
+    let value = parts.next()?.trim();
+    if name.eq_ignore_ascii_case("sitemap") && !value.is_empty() {
+        Some(value)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,188 @@
+//! Serde support for sitemap structures, enabled by the `serde` feature.
+//!
+//! Fields are serialized in their sitemap textual form: `Location` as the url
+//! string, `LastMod` as a W3C datetime, `ChangeFreq` as its lowercase keyword,
+//! `Priority` as a number and `VideoDuration` as a number of seconds.
+//! News entries are serialized as objects. Missing values are serialized as
+//! `null`.
+//! Deserialization reuses the `From<String>` parsing and reports invalid values
+//! as serde errors.
+//! `Stats` are only serialized, as an object of the values of their accessors.
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::Error as DeError;
+use serde::ser::Error as SerError;
+use serde::ser::SerializeStruct;
+use crate::stats::Stats;
+use crate::structs::{Location, LastMod, ChangeFreq, Priority, VideoDuration, News, NewsEntry};
+
+impl Serialize for Location {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            Location::None => serializer.serialize_none(),
+            Location::Url(ref url) => serializer.serialize_some(url.as_str()),
+            Location::Raw(ref raw) => serializer.serialize_some(raw),
+            Location::Err { ref error, .. } => {
+                Err(S::Error::custom(format!("invalid loc: {}", error)))
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Location {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Location, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            None => Ok(Location::None),
+            Some(raw) => {
+                match Location::from(raw.clone()) {
+                    Location::Err { error, .. } => {
+                        Err(D::Error::custom(format!("invalid loc '{}': {}", raw, error)))
+                    }
+                    location => Ok(location),
+                }
+            }
+        }
+    }
+}
+
+impl Serialize for LastMod {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match (self, self.to_w3c()) {
+            (LastMod::Err(failure), _) => {
+                Err(S::Error::custom(format!("invalid lastmod: {:?}", failure.error)))
+            }
+            (_, Some(ref time)) => serializer.serialize_some(time),
+            (_, None) => serializer.serialize_none(),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for LastMod {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<LastMod, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            None => Ok(LastMod::None),
+            Some(raw) => {
+                match LastMod::from(raw.clone()) {
+                    LastMod::Err(failure) => {
+                        Err(D::Error::custom(format!("invalid lastmod '{}': {:?}", raw, failure.error)))
+                    }
+                    lastmod => Ok(lastmod),
+                }
+            }
+        }
+    }
+}
+
+impl Serialize for ChangeFreq {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            ChangeFreq::None => serializer.serialize_none(),
+            ChangeFreq::Err(ref error) => {
+                Err(S::Error::custom(format!("invalid changefreq: {}", error)))
+            }
+            _ => serializer.serialize_some(self.as_str()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ChangeFreq {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ChangeFreq, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            None => Ok(ChangeFreq::None),
+            Some(raw) => {
+                match ChangeFreq::from(raw) {
+                    ChangeFreq::Err(error) => {
+                        Err(D::Error::custom(format!("invalid changefreq: {}", error)))
+                    }
+                    changefreq => Ok(changefreq),
+                }
+            }
+        }
+    }
+}
+
+impl Serialize for Priority {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            Priority::None => serializer.serialize_none(),
+            Priority::Value(value) => serializer.serialize_some(&value),
+            _ => Err(S::Error::custom(format!("invalid priority: {:?}", self))),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Priority {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Priority, D::Error> {
+        match Option::<f32>::deserialize(deserializer)? {
+            None => Ok(Priority::None),
+            Some(value) => {
+                match Priority::from(value.to_string()) {
+                    Priority::Value(value) => Ok(Priority::Value(value)),
+                    priority => Err(D::Error::custom(format!("invalid priority: {:?}", priority))),
+                }
+            }
+        }
+    }
+}
+
+impl Serialize for VideoDuration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            VideoDuration::None => serializer.serialize_none(),
+            VideoDuration::Value(value) => serializer.serialize_some(&value.as_secs()),
+            _ => Err(S::Error::custom(format!("invalid video duration: {:?}", self))),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for VideoDuration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<VideoDuration, D::Error> {
+        match Option::<u64>::deserialize(deserializer)? {
+            None => Ok(VideoDuration::None),
+            Some(seconds) => {
+                match VideoDuration::from(seconds) {
+                    VideoDuration::Value(value) => Ok(VideoDuration::Value(value)),
+                    duration => {
+                        Err(D::Error::custom(format!("invalid video duration: {:?}", duration)))
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Serialize for News {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            News::None => serializer.serialize_none(),
+            News::Entry(ref entry) => serializer.serialize_some(entry),
+            News::Err(ref error) => Err(S::Error::custom(format!("invalid news: {}", error))),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for News {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<News, D::Error> {
+        match Option::<NewsEntry>::deserialize(deserializer)? {
+            None => Ok(News::None),
+            Some(entry) => Ok(News::Entry(Box::new(entry))),
+        }
+    }
+}
+
+impl Serialize for Stats {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut stats = serializer.serialize_struct("Stats", 11)?;
+        stats.serialize_field("urls", &self.urls())?;
+        stats.serialize_field("changefreq", self.changefreq())?;
+        stats.serialize_field("min_lastmod", self.min_lastmod())?;
+        stats.serialize_field("median_lastmod", &self.median_lastmod())?;
+        stats.serialize_field("max_lastmod", self.max_lastmod())?;
+        stats.serialize_field("priorities", self.priorities())?;
+        stats.serialize_field("missing", self.missing())?;
+        stats.serialize_field("invalid", self.invalid())?;
+        stats.serialize_field("hosts", self.hosts())?;
+        stats.serialize_field("hosts_truncated", &self.hosts_truncated())?;
+        stats.serialize_field("bytes", &self.bytes())?;
+        stats.end()
+    }
+}
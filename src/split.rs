@@ -0,0 +1,85 @@
+//! Contains the splitting of url entries into a sitemap for each host.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate sitemap;
+//! use sitemap::split;
+//! use sitemap::structs::UrlEntry;
+//! use std::env;
+//! use std::fs::File;
+//! fn main() {
+//!     let entries = vec![
+//!         UrlEntry::builder().loc("http://www.example.com/").build().unwrap(),
+//!         UrlEntry::builder().loc("http://blog.example.com/").build().unwrap(),
+//!     ];
+//!     let split = split::by_host(entries, |host: &str, number: usize| {
+//!         File::create(env::temp_dir().join(format!("sitemap-{}-{}.xml", host, number)))
+//!     }).unwrap();
+//!     assert_eq!(split.hosts["www.example.com"].urls, 1);
+//!     assert!(split.errors.is_empty());
+//! }
+//! ```
+use crate::structs::UrlEntry;
+use crate::writer::SplitSiteMapWriter;
+use crate::Error;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+use std::rc::Rc;
+
+/// Sitemaps written for a host.
+#[derive(Debug,Clone,Copy,Default,PartialEq)]
+pub struct HostSummary {
+    /// Number of sitemaps.
+    pub files: usize,
+    /// Number of urls.
+    pub urls: usize,
+}
+
+/// Result of `by_host`.
+#[derive(Debug,Clone,Default,PartialEq)]
+pub struct HostSplit {
+    /// Sitemaps written for each host.
+    pub hosts: BTreeMap<String, HostSummary>,
+    /// Entries without a valid location or without a host, not written.
+    pub errors: Vec<UrlEntry>,
+}
+
+/// Writes the entries into sitemaps for each host of their urls.
+///
+/// `sink` is called with the host and the number of the sitemap, starting
+/// from 1, for each sitemap. Sitemaps are split at the url count and the
+/// size limits of the protocol, like `SplitSiteMapWriter`, the sitemaps of
+/// all the hosts stay open until the end of the entries.
+pub fn by_host<I, F, W>(entries: I, sink: F) -> Result<HostSplit, Error>
+    where I: IntoIterator<Item = UrlEntry>,
+          F: FnMut(&str, usize) -> io::Result<W>,
+          W: Write
+{
+    let sink = Rc::new(RefCell::new(sink));
+    let mut writers: HashMap<String, SplitSiteMapWriter<W>> = HashMap::new();
+    let mut split = HostSplit::default();
+    for entry in entries {
+        let host = entry.loc.parsed().host().map(|host| host.to_string());
+        let host = match host {
+            Some(host) => host,
+            None => {
+                split.errors.push(entry);
+                continue;
+            }
+        };
+        let writer = writers.entry(host.clone()).or_insert_with(|| {
+            let sink = Rc::clone(&sink);
+            let host = host.clone();
+            SplitSiteMapWriter::without_index(move |number| (sink.borrow_mut())(&host, number))
+        });
+        writer.url(entry)?;
+        split.hosts.entry(host).or_default().urls += 1;
+    }
+    for (host, writer) in writers {
+        let files = writer.end_sitemaps()?;
+        split.hosts.entry(host).or_default().files = files;
+    }
+    Ok(split)
+}
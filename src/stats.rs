@@ -0,0 +1,309 @@
+//! Contains the statistics of the urls of a sitemap, computed in a single
+//! pass over the entries.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate sitemap;
+//! use sitemap::reader::SiteMapReader;
+//! use sitemap::stats::Stats;
+//! fn main() {
+//!     let document = "<urlset><url><loc>http://www.example.com/</loc><changefreq>daily</changefreq></url>\
+//!                     <url><loc>http://www.example.com/about</loc><priority>0.8</priority></url></urlset>";
+//!     let stats = Stats::from_reader(SiteMapReader::new(document.as_bytes())).unwrap();
+//!     assert_eq!(stats.urls(), 2);
+//!     assert_eq!(stats.changefreq().daily, 1);
+//!     assert_eq!(stats.missing().priority, 1);
+//!     println!("{}", stats);
+//! }
+//! ```
+use crate::reader::SiteMapReader;
+use crate::structs::{ChangeFreq, LastMod, Location, Priority, UrlEntry};
+use crate::Error;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::io::Read;
+
+/// Number of `lastmod` values kept to estimate the median.
+const LASTMOD_SAMPLES: usize = 1024;
+
+/// Options of `Stats`.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct StatsOptions {
+    /// Maximum number of distinct hosts kept, 10000 by default. Hosts over
+    /// the limit are not counted.
+    pub max_hosts: usize,
+}
+
+impl Default for StatsOptions {
+    fn default() -> StatsOptions {
+        StatsOptions { max_hosts: 10000 }
+    }
+}
+
+/// Number of entries for each change frequency.
+#[derive(Debug,Clone,Copy,Default,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ChangeFreqCounts {
+    /// `always` entries.
+    pub always: u64,
+    /// `hourly` entries.
+    pub hourly: u64,
+    /// `daily` entries.
+    pub daily: u64,
+    /// `weekly` entries.
+    pub weekly: u64,
+    /// `monthly` entries.
+    pub monthly: u64,
+    /// `yearly` entries.
+    pub yearly: u64,
+    /// `never` entries.
+    pub never: u64,
+}
+
+/// Number of entries for each optional field.
+#[derive(Debug,Clone,Copy,Default,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct FieldCounts {
+    /// Entries counted for `lastmod`.
+    pub lastmod: u64,
+    /// Entries counted for `changefreq`.
+    pub changefreq: u64,
+    /// Entries counted for `priority`.
+    pub priority: u64,
+}
+
+/// Statistics of url entries.
+///
+/// Serialized as a map of the values of the accessors.
+#[derive(Debug,Clone,PartialEq)]
+pub struct Stats {
+    urls: u64,
+    changefreq: ChangeFreqCounts,
+    min_lastmod: LastMod,
+    max_lastmod: LastMod,
+    priorities: [u64; 11],
+    missing: FieldCounts,
+    invalid: FieldCounts,
+    hosts: BTreeSet<String>,
+    hosts_truncated: bool,
+    bytes: u64,
+    options: StatsOptions,
+    lastmods: Vec<LastMod>,
+    lastmod_stride: u64,
+    dated: u64,
+}
+
+impl Default for Stats {
+    fn default() -> Stats {
+        Stats::new()
+    }
+}
+
+impl Stats {
+    /// Creates empty statistics.
+    pub fn new() -> Stats {
+        Stats::with_options(StatsOptions::default())
+    }
+    /// Creates empty statistics with the given options.
+    pub fn with_options(options: StatsOptions) -> Stats {
+        Stats {
+            urls: 0,
+            changefreq: ChangeFreqCounts::default(),
+            min_lastmod: LastMod::None,
+            max_lastmod: LastMod::None,
+            priorities: [0; 11],
+            missing: FieldCounts::default(),
+            invalid: FieldCounts::default(),
+            hosts: BTreeSet::new(),
+            hosts_truncated: false,
+            bytes: 0,
+            options,
+            lastmods: Vec::new(),
+            lastmod_stride: 1,
+            dated: 0,
+        }
+    }
+    /// Statistics of the urls of `reader`, failing with its first error.
+    pub fn from_reader<R: Read>(reader: SiteMapReader<R>) -> Result<Stats, Error> {
+        let mut stats = Stats::new();
+        for url in reader.into_url_iter() {
+            stats.add(&url?);
+        }
+        Ok(stats)
+    }
+    /// Counts `entry`.
+    pub fn add(&mut self, entry: &UrlEntry) {
+        self.urls += 1;
+        self.bytes += estimated_size(entry);
+        match entry.changefreq {
+            ChangeFreq::None => self.missing.changefreq += 1,
+            ChangeFreq::Always => self.changefreq.always += 1,
+            ChangeFreq::Hourly => self.changefreq.hourly += 1,
+            ChangeFreq::Daily => self.changefreq.daily += 1,
+            ChangeFreq::Weekly => self.changefreq.weekly += 1,
+            ChangeFreq::Monthly => self.changefreq.monthly += 1,
+            ChangeFreq::Yearly => self.changefreq.yearly += 1,
+            ChangeFreq::Never => self.changefreq.never += 1,
+            ChangeFreq::Err(_) => self.invalid.changefreq += 1,
+        }
+        match entry.priority {
+            Priority::None => self.missing.priority += 1,
+            Priority::Value(value) if (0.0..=1.0).contains(&value) => {
+                self.priorities[(value * 10.0).round() as usize] += 1;
+            }
+            _ => self.invalid.priority += 1,
+        }
+        match entry.lastmod.get_time() {
+            Some(time) => {
+                if self.min_lastmod.get_time().is_none_or(|min| time < min) {
+                    self.min_lastmod = entry.lastmod.clone();
+                }
+                if self.max_lastmod.get_time().is_none_or(|max| time > max) {
+                    self.max_lastmod = entry.lastmod.clone();
+                }
+                self.sample_lastmod(&entry.lastmod);
+            }
+            None if entry.lastmod == LastMod::None => self.missing.lastmod += 1,
+            None => self.invalid.lastmod += 1,
+        }
+        if let Some(host) = entry.loc.parsed().host() {
+            if self.hosts.len() < self.options.max_hosts {
+                self.hosts.insert(host.to_string());
+            } else if !self.hosts.contains(host) {
+                self.hosts_truncated = true;
+            }
+        }
+    }
+    /// Keeps every `lastmod_stride` value, halving the sample and doubling
+    /// the stride once full.
+    fn sample_lastmod(&mut self, lastmod: &LastMod) {
+        if self.dated.is_multiple_of(self.lastmod_stride) {
+            if self.lastmods.len() == LASTMOD_SAMPLES {
+                let mut index = 0;
+                self.lastmods.retain(|_| {
+                    index += 1;
+                    index % 2 == 1
+                });
+                self.lastmod_stride *= 2;
+            }
+            if self.dated.is_multiple_of(self.lastmod_stride) {
+                self.lastmods.push(lastmod.clone());
+            }
+        }
+        self.dated += 1;
+    }
+    /// Number of urls.
+    pub fn urls(&self) -> u64 {
+        self.urls
+    }
+    /// Number of urls for each change frequency.
+    pub fn changefreq(&self) -> &ChangeFreqCounts {
+        &self.changefreq
+    }
+    /// Oldest `lastmod`, `LastMod::None` without valid ones.
+    pub fn min_lastmod(&self) -> &LastMod {
+        &self.min_lastmod
+    }
+    /// Most recent `lastmod`, `LastMod::None` without valid ones.
+    pub fn max_lastmod(&self) -> &LastMod {
+        &self.max_lastmod
+    }
+    /// Median `lastmod`, the lower one for an even count, `LastMod::None`
+    /// without valid ones. Over 1024 valid values it is estimated from an
+    /// evenly spaced sample of them.
+    pub fn median_lastmod(&self) -> LastMod {
+        if self.lastmods.is_empty() {
+            return LastMod::None;
+        }
+        let mut sorted: Vec<&LastMod> = self.lastmods.iter().collect();
+        let middle = (sorted.len() - 1) / 2;
+        sorted.select_nth_unstable_by_key(middle, |lastmod| lastmod.get_time());
+        sorted[middle].clone()
+    }
+    /// Number of urls for each priority rounded to one decimal, from 0.0 to
+    /// 1.0.
+    pub fn priorities(&self) -> &[u64; 11] {
+        &self.priorities
+    }
+    /// Number of urls without each field.
+    pub fn missing(&self) -> &FieldCounts {
+        &self.missing
+    }
+    /// Number of urls with an invalid value of each field.
+    pub fn invalid(&self) -> &FieldCounts {
+        &self.invalid
+    }
+    /// Distinct hosts of the urls, up to `StatsOptions::max_hosts`.
+    pub fn hosts(&self) -> &BTreeSet<String> {
+        &self.hosts
+    }
+    /// Whether some hosts were not kept, over `StatsOptions::max_hosts`.
+    pub fn hosts_truncated(&self) -> bool {
+        self.hosts_truncated
+    }
+    /// Estimated size in bytes of the `<url>` elements written without
+    /// indentation, ignoring the extensions.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+}
+
+/// Size of the `<url>` element of `entry`, without extensions.
+fn estimated_size(entry: &UrlEntry) -> u64 {
+    let mut size = "<url></url>".len();
+    if let Location::Url(ref url) = *entry.loc.parsed() {
+        let ampersands = url.as_str().matches('&').count();
+        size += "<loc></loc>".len() + url.as_str().len() + ampersands * "amp;".len();
+    }
+    if let Some(lastmod) = entry.lastmod.to_w3c() {
+        size += "<lastmod></lastmod>".len() + lastmod.len();
+    }
+    match entry.changefreq {
+        ChangeFreq::None | ChangeFreq::Err(_) => {}
+        ref changefreq => size += "<changefreq></changefreq>".len() + changefreq.as_str().len(),
+    }
+    if let Priority::Value(_) = entry.priority {
+        size += "<priority>0.0</priority>".len();
+    }
+    size as u64
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn lastmod(lastmod: &LastMod) -> String {
+            lastmod.raw().unwrap_or_else(|| "-".to_string())
+        }
+        let counts = &self.changefreq;
+        writeln!(f, "urls        {}", self.urls)?;
+        writeln!(f,
+                 "changefreq  always {}, hourly {}, daily {}, weekly {}, monthly {}, yearly {}, never {}",
+                 counts.always,
+                 counts.hourly,
+                 counts.daily,
+                 counts.weekly,
+                 counts.monthly,
+                 counts.yearly,
+                 counts.never)?;
+        writeln!(f,
+                 "lastmod     min {}, median {}, max {}",
+                 lastmod(&self.min_lastmod),
+                 lastmod(&self.median_lastmod()),
+                 lastmod(&self.max_lastmod))?;
+        write!(f, "priority   ")?;
+        for (tenth, count) in self.priorities.iter().enumerate() {
+            write!(f, " {}.{} {}", tenth / 10, tenth % 10, count)?;
+        }
+        writeln!(f)?;
+        for (name, counts) in [("missing", &self.missing), ("invalid", &self.invalid)] {
+            writeln!(f,
+                     "{:<11} lastmod {}, changefreq {}, priority {}",
+                     name,
+                     counts.lastmod,
+                     counts.changefreq,
+                     counts.priority)?;
+        }
+        writeln!(f, "hosts       {}{}", self.hosts.len(), if self.hosts_truncated { "+" } else { "" })?;
+        write!(f, "bytes       {}", self.bytes)
+    }
+}
@@ -1,18 +1,57 @@
 //! Contains structures for working with sitemap.
 use url::Url;
-use url;
+use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::convert::From;
-use chrono_utils;
+use std::str::FromStr;
 use chrono::DateTime;
 use chrono::FixedOffset;
+use chrono::NaiveDate;
+use chrono::NaiveTime;
+use chrono::Datelike;
+use chrono::Timelike;
+use chrono::Utc;
 use chrono_utils::parser::parse_w3c_datetime;
+use std::error;
 use std::fmt;
+#[cfg(feature = "fs")]
+use std::fs;
 use std::num;
-use Error;
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use crate::{BuildError, Error, FieldError, ResolveError};
+use crate::reader::{self, DocumentKind, ReaderOptions, SiteMapEvent, Warning, WarningKind};
+use crate::writer::{self, WriterOptions};
+
+/// Namespace of the sitemap protocol.
+pub const SITEMAP_NAMESPACE: &str = "http://www.sitemaps.org/schemas/sitemap/0.9";
+/// Namespace of the sitemap protocol before 0.9, still in use.
+pub const SITEMAP_NAMESPACE_084: &str = "http://www.google.com/schemas/sitemap/0.84";
+/// Maximum number of urls the protocol allows in a single sitemap.
+pub const MAX_URLS_PER_SITEMAP: usize = 50000;
+/// Maximum size in bytes of an uncompressed sitemap.
+pub const MAX_SITEMAP_SIZE: u64 = 52_428_800;
+/// Maximum length in characters of a url.
+pub const MAX_URL_LENGTH: usize = 2048;
+/// Namespace of the image sitemap extension.
+pub const IMAGE_NAMESPACE: &str = "http://www.google.com/schemas/sitemap-image/1.1";
+/// Maximum number of images the protocol allows for a single url.
+pub const MAX_IMAGES_PER_URL: usize = 1000;
+/// Namespace of the video sitemap extension.
+pub const VIDEO_NAMESPACE: &str = "http://www.google.com/schemas/sitemap-video/1.1";
+/// Maximum duration of a video in seconds.
+pub const MAX_VIDEO_DURATION: u64 = 28800;
+/// Namespace of the news sitemap extension.
+pub const NEWS_NAMESPACE: &str = "http://www.google.com/schemas/sitemap-news/0.9";
+/// Namespace of the `xhtml:link` alternate extension.
+pub const XHTML_NAMESPACE: &str = "http://www.w3.org/1999/xhtml";
 
 /// Url entry. Contains url location, modification time,
 /// priority, update frequency.
-#[derive(Clone,Debug)]
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct UrlEntry {
     /// URL of the page.
     pub loc: Location,
@@ -22,44 +61,507 @@ pub struct UrlEntry {
     pub changefreq: ChangeFreq,
     /// The priority of this URL relative to other URLs on the site.
     pub priority: Priority,
+    /// Images of the page, `image:image` extension.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub images: Vec<ImageEntry>,
+    /// Videos of the page, `video:video` extension.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub videos: Vec<VideoEntry>,
+    /// News article of the page, `news:news` extension.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "News::is_none"))]
+    pub news: News,
+    /// Alternate language versions of the page, `xhtml:link` extension.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub alternates: Vec<Alternate>,
+    /// Unknown child elements, kept with `ReaderOptions::capture_unknown`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub extensions: Vec<RawElement>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) positions: Positions,
+}
+
+/// Position in the source document, line and column start from 1.
+#[derive(Debug,Clone,Copy,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Position {
+    /// Line number.
+    pub line: u64,
+    /// Column number.
+    pub column: u64,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Positions of an entry and of its fields in the source document,
+/// they are not part of the entry value and are ignored by `eq`.
+#[derive(Debug,Clone,Default)]
+pub(crate) struct Positions {
+    /// Positions of the entry element, named `""`, and of its fields.
+    pub(crate) fields: Vec<(&'static str, Position)>,
+}
+
+impl PartialEq for Positions {
+    fn eq(&self, _: &Positions) -> bool {
+        true
+    }
+}
+
+impl Positions {
+    pub(crate) fn field(&self, field: &str) -> Option<Position> {
+        self.fields.iter().find(|&&(name, _)| name == field).map(|&(_, position)| position)
+    }
+
+    /// Position of the entry element.
+    pub(crate) fn start(&self) -> Option<Position> {
+        self.field("")
+    }
+
+    /// Sets the position of the entry element.
+    pub(crate) fn set_start(&mut self, position: Position) {
+        self.fields.retain(|&(name, _)| !name.is_empty());
+        self.fields.push(("", position));
+    }
+
+    /// Warning of the invalid `field` of the entry `entry_index`, at the
+    /// field position if known, at the entry position otherwise.
+    pub(crate) fn warning<E: fmt::Display>(&self, entry_index: usize, field: &str, kind: WarningKind, error: E) -> Warning {
+        Warning {
+            position: self.field(field).or_else(|| self.start()),
+            entry_index,
+            kind,
+            message: format!("Invalid <{}> value: {}", field, error),
+        }
+    }
+
+    /// Parse error of `field`, at the field position if known, at the
+    /// entry position otherwise.
+    fn error<E: fmt::Display>(&self, field: &str, error: E) -> Error {
+        let position = self.field(field).or_else(|| self.start());
+        Error::Parse {
+            line: position.map_or(0, |position| position.line),
+            column: position.map_or(0, |position| position.column),
+            message: format!("Invalid <{}> value: {}", field, error),
+        }
+    }
+}
+
+/// Image entry of the image sitemap extension.
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ImageEntry {
+    /// URL of the image.
+    pub loc: Location,
+    /// The title of the image.
+    pub title: Option<String>,
+    /// The caption of the image.
+    pub caption: Option<String>,
+    /// The geographic location of the image.
+    pub geo_location: Option<String>,
+    /// URL of the license of the image.
+    pub license: Location,
+}
+
+impl Default for ImageEntry {
+    fn default() -> ImageEntry {
+        ImageEntry::new()
+    }
+}
+
+impl ImageEntry {
+    /// Creates a new empty `ImageEntry`.
+    pub fn new() -> ImageEntry {
+        ImageEntry {
+            loc: Location::None,
+            title: None,
+            caption: None,
+            geo_location: None,
+            license: Location::None,
+        }
+    }
+    /// Creates a new `ImageEntry` with the given location.
+    pub fn with_loc(loc: Url) -> ImageEntry {
+        let mut image = ImageEntry::new();
+        image.loc = Location::Url(loc);
+        image
+    }
+}
+
+/// Video entry of the video sitemap extension.
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct VideoEntry {
+    /// URL of the video thumbnail.
+    pub thumbnail_loc: Location,
+    /// The title of the video.
+    pub title: Option<String>,
+    /// The description of the video.
+    pub description: Option<String>,
+    /// URL of the video media file.
+    pub content_loc: Location,
+    /// URL of the video player.
+    pub player_loc: Location,
+    /// The duration of the video.
+    pub duration: VideoDuration,
+    /// The date the video was first published.
+    pub publication_date: LastMod,
+    /// The date after which the video is no longer available.
+    pub expiration_date: LastMod,
+    /// Tags describing the video.
+    pub tags: Vec<String>,
+    /// Whether the video is a live stream.
+    pub live: Option<bool>,
+}
+
+impl Default for VideoEntry {
+    fn default() -> VideoEntry {
+        VideoEntry::new()
+    }
+}
+
+impl VideoEntry {
+    /// Creates a new empty `VideoEntry`.
+    pub fn new() -> VideoEntry {
+        VideoEntry {
+            thumbnail_loc: Location::None,
+            title: None,
+            description: None,
+            content_loc: Location::None,
+            player_loc: Location::None,
+            duration: VideoDuration::None,
+            publication_date: LastMod::None,
+            expiration_date: LastMod::None,
+            tags: Vec::new(),
+            live: None,
+        }
+    }
+}
+
+/// Alternate version of the page, `<xhtml:link rel="alternate">`.
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Alternate {
+    /// Language of the alternate page, may be `x-default`.
+    pub hreflang: String,
+    /// URL of the alternate page.
+    pub href: Location,
 }
 
+impl Alternate {
+    /// Creates a new `Alternate`.
+    pub fn new<S: Into<String>>(hreflang: S, href: Url) -> Alternate {
+        Alternate {
+            hreflang: hreflang.into(),
+            href: Location::Url(href),
+        }
+    }
+}
+
+/// Element of an extension not supported by the crate, kept as XML.
+#[derive(Clone,Debug,Default,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RawElement {
+    /// Qualified name of the element, like `geo:geo`.
+    pub name: String,
+    /// Attributes with their qualified names, without the namespace
+    /// declarations.
+    pub attributes: Vec<(String, String)>,
+    /// Content of the element as XML, without the namespace declarations.
+    pub inner: String,
+    /// Namespaces of the prefixes of the element and of its content, as
+    /// `(prefix, namespace)` pairs, the prefix of the default namespace is
+    /// empty.
+    pub namespaces: Vec<(String, String)>,
+}
+
+/// News publication of the news sitemap extension.
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NewsPublication {
+    /// Name of the news publication.
+    pub name: String,
+    /// Language of the publication, ISO 639 code.
+    pub language: String,
+}
+
+/// News entry of the news sitemap extension.
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NewsEntry {
+    /// The publication of the article.
+    pub publication: NewsPublication,
+    /// The date the article was published.
+    pub publication_date: LastMod,
+    /// The title of the article.
+    pub title: String,
+}
+
+impl NewsEntry {
+    /// Creates a new `NewsEntry`.
+    pub fn new(publication: NewsPublication,
+               publication_date: DateTime<FixedOffset>,
+               title: String)
+               -> NewsEntry {
+        NewsEntry {
+            publication,
+            publication_date: LastMod::DateTime(publication_date),
+            title,
+        }
+    }
+}
+
+/// Error of an invalid `news:news` element.
+#[derive(Clone,Debug,PartialEq)]
+pub enum NewsError {
+    /// `news:publication` element, or one of its children, is missing.
+    MissingPublication,
+    /// `news:publication_date` element is missing.
+    MissingPublicationDate,
+    /// `news:title` element is missing.
+    MissingTitle,
+}
+impl fmt::Display for NewsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            NewsError::MissingPublication => write!(f, "news:publication is missing"),
+            NewsError::MissingPublicationDate => write!(f, "news:publication_date is missing"),
+            NewsError::MissingTitle => write!(f, "news:title is missing"),
+        }
+    }
+}
+impl error::Error for NewsError {}
+
+/// News article of the url.
+#[derive(Clone,Debug,PartialEq)]
+pub enum News {
+    /// No value.
+    None,
+    /// News entry, boxed to keep the entries small.
+    Entry(Box<NewsEntry>),
+    /// Invalid news element.
+    Err(NewsError),
+}
+impl News {
+    /// Returns news entry if present.
+    pub fn get_entry(&self) -> Option<&NewsEntry> {
+        match *self {
+            News::Entry(ref entry) => Some(entry),
+            _ => None,
+        }
+    }
+    /// Returns `true` when there is no news element.
+    pub fn is_none(&self) -> bool {
+        *self == News::None
+    }
+}
+
+/// Builder of `UrlEntry`.
+///
+/// Setters never fail, invalid values are collected and reported all
+/// together by `build()`.
 pub struct UrlEntryBuilder {
     url_entry: UrlEntry,
+    errors: Vec<FieldError>,
+    base_url: Option<Url>,
+}
+
+fn parse_loc(url: &str) -> Result<Url, FieldError> {
+    Url::parse(url).map_err(|error| FieldError::new("loc", Some(url), error.to_string()))
+}
+
+/// Value of `field` parsed from `raw` like the reader does, the error being
+/// kept in `errors`.
+fn parse_field<T>(field: &'static str, raw: &str, errors: &mut Vec<FieldError>) -> Option<T>
+    where T: FromStr<Err = Error>
+{
+    match raw.trim().parse() {
+        Ok(value) => Some(value),
+        Err(Error::InvalidValue { message, .. }) => {
+            errors.push(FieldError::new(field, Some(raw), message));
+            None
+        }
+        Err(error) => {
+            errors.push(FieldError::new(field, Some(raw), error.to_string()));
+            None
+        }
+    }
+}
+
+/// W3C modification time of `raw`, the errors and the other formats being
+/// kept in `errors`.
+fn parse_w3c_lastmod(raw: &str, errors: &mut Vec<FieldError>) -> Option<LastMod> {
+    match parse_field("lastmod", raw, errors)? {
+        LastMod::NonStandard(time) => {
+            let message = format!("{} time instead of W3C datetime", time.format);
+            errors.push(FieldError::new("lastmod", Some(raw), message));
+            None
+        }
+        lastmod => Some(lastmod),
+    }
+}
+
+fn check_loc(loc: &Location, errors: &mut Vec<FieldError>) {
+    let loc_error = errors.iter().any(|error| error.field() == "loc");
+    if !loc_error && !matches!(*loc, Location::Url(_)) {
+        errors.push(FieldError::new("loc", None, "location is required"));
+    }
 }
 
 impl UrlEntryBuilder {
-    pub fn loc(mut self, url: String) -> Result<UrlEntryBuilder, Error> {
-        self.url_entry.loc = Location::from(url);
-        Ok(self)
+    /// Sets the location, `url` must be a valid absolute url.
+    pub fn loc<S: AsRef<str>>(mut self, url: S) -> UrlEntryBuilder {
+        match parse_loc(url.as_ref()) {
+            Ok(url) => self.loc_url(url),
+            Err(error) => {
+                self.errors.push(error);
+                self
+            }
+        }
+    }
+    /// Sets an already parsed location.
+    pub fn loc_url(mut self, url: Url) -> UrlEntryBuilder {
+        self.url_entry.loc = Location::Url(url);
+        self
+    }
+    /// Sets the location of `reference` resolved against the base url of
+    /// `UrlEntry::builder_with_base`, see `Location::resolve`.
+    pub fn loc_relative(mut self, reference: &str) -> UrlEntryBuilder {
+        let resolved = match self.base_url {
+            Some(ref base_url) => Location::resolve(base_url, reference).map_err(|error| error.to_string()),
+            None => Err("relative location without a base url".to_string()),
+        };
+        match resolved {
+            Ok(url) => self.loc_url(url),
+            Err(message) => {
+                self.errors.push(FieldError::new("loc", Some(reference), message));
+                self
+            }
+        }
     }
-    pub fn lastmod(mut self, date: DateTime<FixedOffset>) -> Result<UrlEntryBuilder, Error> {
+    pub fn lastmod(mut self, date: DateTime<FixedOffset>) -> UrlEntryBuilder {
         self.url_entry.lastmod = LastMod::DateTime(date);
+        self
+    }
+    /// Sets a modification date without time.
+    pub fn lastmod_date(mut self, date: NaiveDate) -> UrlEntryBuilder {
+        self.url_entry.lastmod = LastMod::Date(date);
+        self
+    }
+    /// Sets the modification time of a W3C date or datetime, parsed like
+    /// the `<lastmod>` of a document. The RFC 3339 and RFC 2822 times read
+    /// as `LastMod::NonStandard` are errors.
+    pub fn lastmod_str(mut self, lastmod: &str) -> UrlEntryBuilder {
+        if let Some(lastmod) = parse_w3c_lastmod(lastmod, &mut self.errors) {
+            self.url_entry.lastmod = lastmod;
+        }
+        self
+    }
+    /// Sets the modification time of the file at `path`, in whole seconds.
+    ///
+    /// Fails with `Error::Io` when the file can not be read or the
+    /// platform does not record modification times.
+    #[cfg(feature = "fs")]
+    pub fn lastmod_from_file<P: AsRef<Path>>(mut self, path: P) -> Result<UrlEntryBuilder, Error> {
+        let modified = fs::metadata(path)?.modified()?;
+        self.url_entry.lastmod = LastMod::from(modified);
         Ok(self)
     }
-    pub fn changefreq(mut self, changefreq: ChangeFreq) -> Result<UrlEntryBuilder, Error> {
+    pub fn changefreq(mut self, changefreq: ChangeFreq) -> UrlEntryBuilder {
         self.url_entry.changefreq = changefreq;
-        Ok(self)
+        self
+    }
+    /// Sets the change frequency of a keyword, in any case, e.g. `"daily"`.
+    pub fn changefreq_str(mut self, changefreq: &str) -> UrlEntryBuilder {
+        if let Some(changefreq) = parse_field("changefreq", changefreq, &mut self.errors) {
+            self.url_entry.changefreq = changefreq;
+        }
+        self
     }
-    pub fn priority(mut self, val: f32) -> Result<UrlEntryBuilder, Error> {
-        if val > 1.0 || val < 0.0 {
-            Err(Error::Invalid("priority should be betwheen 0 and 1".to_string()))
+    /// Adds an image, at most `MAX_IMAGES_PER_URL` images are allowed.
+    pub fn image(mut self, image: ImageEntry) -> UrlEntryBuilder {
+        if self.url_entry.images.len() >= MAX_IMAGES_PER_URL {
+            if !self.errors.iter().any(|error| error.field() == "image") {
+                self.errors.push(FieldError::new("image",
+                                                 None,
+                                                 "a url can contain at most 1000 images"));
+            }
+        } else {
+            self.url_entry.images.push(image);
+        }
+        self
+    }
+    /// Adds a video, its duration must be valid.
+    pub fn video(mut self, video: VideoEntry) -> UrlEntryBuilder {
+        match video.duration {
+            VideoDuration::None | VideoDuration::Value(_) => {}
+            ref duration => {
+                self.errors.push(FieldError::new("video:duration",
+                                                 None,
+                                                 format!("invalid duration {:?}", duration)));
+            }
+        }
+        self.url_entry.videos.push(video);
+        self
+    }
+    /// Adds an alternate version of the page, order is preserved.
+    pub fn alternate(mut self, alternate: Alternate) -> UrlEntryBuilder {
+        self.url_entry.alternates.push(alternate);
+        self
+    }
+    /// Adds an element of an unsupported extension, written as it is.
+    pub fn extension(mut self, extension: RawElement) -> UrlEntryBuilder {
+        self.url_entry.extensions.push(extension);
+        self
+    }
+    /// Sets the news article.
+    pub fn news(mut self, news: NewsEntry) -> UrlEntryBuilder {
+        self.url_entry.news = News::Entry(Box::new(news));
+        self
+    }
+    /// Sets the priority, `val` must be between 0 and 1.
+    pub fn priority(mut self, val: f32) -> UrlEntryBuilder {
+        if !(0.0..=1.0).contains(&val) {
+            self.errors.push(FieldError::new("priority",
+                                             Some(&val.to_string()),
+                                             "priority should be between 0 and 1"));
         } else {
             self.url_entry.priority = Priority::Value(val);
-            Ok(self)
         }
+        self
+    }
+    /// Sets the priority of a decimal number between 0 and 1, with a `.`
+    /// decimal separator whatever the locale.
+    pub fn priority_str(mut self, priority: &str) -> UrlEntryBuilder {
+        if priority.contains(',') {
+            self.errors.push(FieldError::new("priority", Some(priority), "decimal separator should be '.'"));
+        } else if let Some(priority) = parse_field("priority", priority, &mut self.errors) {
+            self.url_entry.priority = priority;
+        }
+        self
     }
 
-    pub fn build(self) -> Result<UrlEntry, Error> {
-        // TODO: add check for at least the name.
-        if let Location::Url(_) = self.url_entry.loc {
+    /// Builds the entry, reporting every invalid or missing field.
+    pub fn build(mut self) -> Result<UrlEntry, BuildError> {
+        check_loc(&self.url_entry.loc, &mut self.errors);
+        if self.errors.is_empty() {
             Ok(self.url_entry)
         } else {
-            Err(Error::Invalid("Required a location in the Url".to_string()))
+            Err(BuildError::new(self.errors))
         }
     }
 }
 
+impl Default for UrlEntry {
+    fn default() -> UrlEntry {
+        UrlEntry::new()
+    }
+}
+
 impl UrlEntry {
     /// Creates a new empty `UrlEntry`.
     pub fn new() -> UrlEntry {
@@ -68,133 +570,896 @@ impl UrlEntry {
             lastmod: LastMod::None,
             changefreq: ChangeFreq::None,
             priority: Priority::None,
+            images: Vec::new(),
+            videos: Vec::new(),
+            news: News::None,
+            alternates: Vec::new(),
+            extensions: Vec::new(),
+            positions: Positions::default(),
         }
     }
     pub fn builder() -> UrlEntryBuilder {
-        UrlEntryBuilder { url_entry: UrlEntry::new() }
+        UrlEntryBuilder {
+            url_entry: UrlEntry::new(),
+            errors: Vec::new(),
+            base_url: None,
+        }
+    }
+    /// Builder resolving the locations of `loc_relative` against
+    /// `base_url`, e.g. the origin of the site.
+    pub fn builder_with_base(base_url: Url) -> UrlEntryBuilder {
+        UrlEntryBuilder {
+            base_url: Some(base_url),
+            ..UrlEntry::builder()
+        }
+    }
+
+    /// Reads a single `<url>` element outside any `<urlset>`, see
+    /// `from_xml_fragment_with_options`.
+    pub fn from_xml_fragment(fragment: &str) -> Result<UrlEntry, Error> {
+        UrlEntry::from_xml_fragment_with_options(fragment, ReaderOptions::default())
+    }
+
+    /// Reads a single `<url>` element outside any `<urlset>`, its extension
+    /// children being read like in a document with the same options.
+    ///
+    /// The prefixes of the extensions have to be declared in the fragment.
+    /// Fails with `Error::Invalid` when the fragment has no `<url>` element
+    /// or more than one.
+    pub fn from_xml_fragment_with_options(fragment: &str, options: ReaderOptions) -> Result<UrlEntry, Error> {
+        match reader::read_fragment(fragment, DocumentKind::UrlSet, options)? {
+            SiteMapEvent::Url(url) => Ok(url),
+            _ => Err(Error::Invalid("fragment without a url element".to_string())),
+        }
+    }
+
+    /// Writes the entry alone as a `<url>` element, without XML declaration,
+    /// declaring its extension namespaces on the element.
+    ///
+    /// A url without a valid location fails with `Error::MissingLoc`, or
+    /// gives an empty string with `WriterOptions::skip_invalid`.
+    pub fn to_xml_string(&self, options: &WriterOptions) -> Result<String, Error> {
+        writer::url_to_string(self, options)
+    }
+
+    /// Position of the `<url>` element, when read from a document.
+    pub fn position(&self) -> Option<Position> {
+        self.positions.start()
+    }
+
+    /// Position of the element of `field`, e.g. `"lastmod"`, when read
+    /// from a document.
+    pub fn field_position(&self, field: &str) -> Option<Position> {
+        self.positions.field(field)
+    }
+
+    /// `Error::Parse` of every invalid field, line and column are 0 when
+    /// the entry was not read from a document. A `Raw` location is parsed.
+    pub fn errors(&self) -> Vec<Error> {
+        let mut errors = self.read_errors();
+        if let Location::Raw(_) = self.loc {
+            if let Location::Err { ref error, .. } = *self.loc.parsed() {
+                errors.insert(0, self.positions.error("loc", error));
+            }
+        }
+        errors
+    }
+
+    /// Errors of the invalid fields, a `Raw` location is not checked.
+    pub(crate) fn read_errors(&self) -> Vec<Error> {
+        self.read_warnings(0).into_iter().map(Error::from).collect()
+    }
+
+    /// Warnings of the invalid fields of the entry `entry_index`, a `Raw`
+    /// location is not checked.
+    pub(crate) fn read_warnings(&self, entry_index: usize) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        let positions = &self.positions;
+        if let Location::Err { ref error, .. } = self.loc {
+            warnings.push(positions.warning(entry_index, "loc", WarningKind::InvalidLoc, error));
+        }
+        match self.lastmod {
+            LastMod::Err(ref failure) => {
+                warnings.push(positions.warning(entry_index, "lastmod", WarningKind::InvalidLastMod, failure.error))
+            }
+            LastMod::NonStandard(ref time) => {
+                let message = format!("{} time instead of W3C datetime", time.format);
+                warnings.push(positions.warning(entry_index, "lastmod", WarningKind::NonStandardLastMod, message))
+            }
+            _ => {}
+        }
+        if let ChangeFreq::Err(ref error) = self.changefreq {
+            warnings.push(positions.warning(entry_index, "changefreq", WarningKind::InvalidChangeFreq, error));
+        }
+        match self.priority {
+            Priority::Err(ref failure) => {
+                warnings.push(positions.warning(entry_index, "priority", WarningKind::InvalidPriority, &failure.error))
+            }
+            Priority::ErrValueLesserZero(_) |
+            Priority::ErrValueGreaterOne(_) => {
+                let message = "priority should be between 0 and 1";
+                warnings.push(positions.warning(entry_index, "priority", WarningKind::PriorityOutOfRange, message))
+            }
+            Priority::ErrNotANumber(_) => {
+                let message = "priority is not a number";
+                warnings.push(positions.warning(entry_index, "priority", WarningKind::InvalidPriority, message))
+            }
+            _ => {}
+        }
+        if let News::Err(ref error) = self.news {
+            warnings.push(positions.warning(entry_index, "news:news", WarningKind::InvalidNews, error));
+        }
+        // positions of the images without location, in their order
+        let mut images = positions.fields.iter().filter(|&&(name, _)| name == "image:image");
+        for _ in self.images.iter().filter(|image| image.loc == Location::None) {
+            warnings.push(Warning {
+                position: images.next().map(|&(_, position)| position).or_else(|| positions.start()),
+                entry_index,
+                kind: WarningKind::MissingImageLoc,
+                message: "image:image element without image:loc".to_string(),
+            });
+        }
+        warnings
     }
 }
 
+/// Builder of `SiteMapEntry`.
+///
+/// Setters never fail, invalid values are collected and reported all
+/// together by `build()`.
 pub struct SiteMapEntryBuilder {
     sitemap_entry: SiteMapEntry,
+    errors: Vec<FieldError>,
 }
 
 impl SiteMapEntryBuilder {
-    pub fn loc(mut self, url: String) -> Result<SiteMapEntryBuilder, Error> {
-        self.sitemap_entry.loc = Location::from(url);
-        Ok(self)
+    /// Sets the location, `url` must be a valid absolute url.
+    pub fn loc<S: AsRef<str>>(mut self, url: S) -> SiteMapEntryBuilder {
+        match parse_loc(url.as_ref()) {
+            Ok(url) => self.loc_url(url),
+            Err(error) => {
+                self.errors.push(error);
+                self
+            }
+        }
+    }
+
+    /// Sets an already parsed location.
+    pub fn loc_url(mut self, url: Url) -> SiteMapEntryBuilder {
+        self.sitemap_entry.loc = Location::Url(url);
+        self
     }
 
-    pub fn lastmod(mut self, date: DateTime<FixedOffset>) -> Result<SiteMapEntryBuilder, Error> {
+    pub fn lastmod(mut self, date: DateTime<FixedOffset>) -> SiteMapEntryBuilder {
         self.sitemap_entry.lastmod = LastMod::DateTime(date);
-        Ok(self)
+        self
+    }
+
+    /// Sets a modification date without time.
+    pub fn lastmod_date(mut self, date: NaiveDate) -> SiteMapEntryBuilder {
+        self.sitemap_entry.lastmod = LastMod::Date(date);
+        self
     }
 
-    pub fn build(self) -> Result<SiteMapEntry, Error> {
-        // TODO: add check for at least the name.
-        if let Location::Url(_) = self.sitemap_entry.loc {
+    /// Sets the modification time of a W3C date or datetime, see
+    /// `UrlEntryBuilder::lastmod_str`.
+    pub fn lastmod_str(mut self, lastmod: &str) -> SiteMapEntryBuilder {
+        if let Some(lastmod) = parse_w3c_lastmod(lastmod, &mut self.errors) {
+            self.sitemap_entry.lastmod = lastmod;
+        }
+        self
+    }
+
+    /// Builds the entry, reporting every invalid or missing field.
+    pub fn build(mut self) -> Result<SiteMapEntry, BuildError> {
+        check_loc(&self.sitemap_entry.loc, &mut self.errors);
+        if self.errors.is_empty() {
             Ok(self.sitemap_entry)
         } else {
-            Err(Error::Invalid("Required a location in the sitemap".to_string()))
+            Err(BuildError::new(self.errors))
         }
     }
 }
 
 /// Sitemap entry. Contains url location and modification time.
-#[derive(Clone,Debug)]
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct SiteMapEntry {
     /// URL of the sitemap.
     pub loc: Location,
     /// The date of last modification of the file.
     pub lastmod: LastMod,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) positions: Positions,
+}
+impl Default for SiteMapEntry {
+    fn default() -> SiteMapEntry {
+        SiteMapEntry::new()
+    }
 }
+
 impl SiteMapEntry {
     /// Creates a new empty `SiteMapEntry`.
     pub fn new() -> SiteMapEntry {
         SiteMapEntry {
             loc: Location::None,
             lastmod: LastMod::None,
+            positions: Positions::default(),
         }
     }
 
     pub fn builder() -> SiteMapEntryBuilder {
-        SiteMapEntryBuilder { sitemap_entry: SiteMapEntry::new() }
+        SiteMapEntryBuilder {
+            sitemap_entry: SiteMapEntry::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Reads a single `<sitemap>` element outside any `<sitemapindex>`, see
+    /// `from_xml_fragment_with_options`.
+    pub fn from_xml_fragment(fragment: &str) -> Result<SiteMapEntry, Error> {
+        SiteMapEntry::from_xml_fragment_with_options(fragment, ReaderOptions::default())
+    }
+
+    /// Reads a single `<sitemap>` element outside any `<sitemapindex>`,
+    /// like in a document with the same options.
+    ///
+    /// Fails with `Error::Invalid` when the fragment has no `<sitemap>`
+    /// element or more than one.
+    pub fn from_xml_fragment_with_options(fragment: &str, options: ReaderOptions) -> Result<SiteMapEntry, Error> {
+        match reader::read_fragment(fragment, DocumentKind::SiteMapIndex, options)? {
+            SiteMapEvent::SiteMap(sitemap) => Ok(sitemap),
+            _ => Err(Error::Invalid("fragment without a sitemap element".to_string())),
+        }
+    }
+
+    /// Writes the entry alone as a `<sitemap>` element, without XML
+    /// declaration.
+    ///
+    /// A sitemap without a valid location fails with `Error::MissingLoc`,
+    /// or gives an empty string with `WriterOptions::skip_invalid`.
+    pub fn to_xml_string(&self, options: &WriterOptions) -> Result<String, Error> {
+        writer::sitemap_to_string(self, options)
+    }
+
+    /// Position of the `<sitemap>` element, when read from a document.
+    pub fn position(&self) -> Option<Position> {
+        self.positions.start()
+    }
+
+    /// Position of the element of `field`, when read from a document.
+    pub fn field_position(&self, field: &str) -> Option<Position> {
+        self.positions.field(field)
+    }
+
+    /// `Error::Parse` of every invalid field, line and column are 0 when
+    /// the entry was not read from a document. A `Raw` location is parsed.
+    pub fn errors(&self) -> Vec<Error> {
+        let mut errors = self.read_errors();
+        if let Location::Raw(_) = self.loc {
+            if let Location::Err { ref error, .. } = *self.loc.parsed() {
+                errors.insert(0, self.positions.error("loc", error));
+            }
+        }
+        errors
+    }
+
+    /// Errors of the invalid fields, a `Raw` location is not checked.
+    pub(crate) fn read_errors(&self) -> Vec<Error> {
+        self.read_warnings(0).into_iter().map(Error::from).collect()
+    }
+
+    /// Warnings of the invalid fields of the entry `entry_index`, a `Raw`
+    /// location is not checked.
+    pub(crate) fn read_warnings(&self, entry_index: usize) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        let positions = &self.positions;
+        if let Location::Err { ref error, .. } = self.loc {
+            warnings.push(positions.warning(entry_index, "loc", WarningKind::InvalidLoc, error));
+        }
+        match self.lastmod {
+            LastMod::Err(ref failure) => {
+                warnings.push(positions.warning(entry_index, "lastmod", WarningKind::InvalidLastMod, failure.error))
+            }
+            LastMod::NonStandard(ref time) => {
+                let message = format!("{} time instead of W3C datetime", time.format);
+                warnings.push(positions.warning(entry_index, "lastmod", WarningKind::NonStandardLastMod, message))
+            }
+            _ => {}
+        }
+        warnings
+    }
+}
+
+impl From<SiteMapEntry> for UrlEntry {
+    /// Url of the location and modification time of the sitemap, with
+    /// their positions, for the sitemaps listed in a `<urlset>`.
+    fn from(sitemap: SiteMapEntry) -> UrlEntry {
+        let mut url = UrlEntry::new();
+        url.loc = sitemap.loc;
+        url.lastmod = sitemap.lastmod;
+        url.positions = sitemap.positions;
+        url
+    }
+}
+
+impl From<UrlEntry> for SiteMapEntry {
+    /// Sitemap of the location and modification time of the url, with
+    /// their positions, the other fields are dropped.
+    fn from(url: UrlEntry) -> SiteMapEntry {
+        let mut positions = url.positions;
+        positions.fields.retain(|&(name, _)| ["", "loc", "lastmod"].contains(&name));
+        SiteMapEntry {
+            loc: url.loc,
+            lastmod: url.lastmod,
+            positions,
+        }
+    }
+}
+
+/// `<?xml-stylesheet?>` processing instruction of a document.
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Stylesheet {
+    /// Location of the stylesheet.
+    pub href: String,
+    /// Media type of the stylesheet, like `text/xsl`.
+    pub media_type: String,
+}
+
+impl Stylesheet {
+    /// Creates a new XSLT stylesheet, of type `text/xsl`.
+    pub fn new(href: &str) -> Stylesheet {
+        Stylesheet {
+            href: href.to_string(),
+            media_type: "text/xsl".to_string(),
+        }
+    }
+
+    /// Stylesheet of the data of a processing instruction, `None` without
+    /// `href` or `type` pseudo attributes.
+    pub(crate) fn from_data(data: &str) -> Option<Stylesheet> {
+        let mut href = None;
+        let mut media_type = None;
+        let mut rest = data.trim_start();
+        while !rest.is_empty() {
+            let (name, value) = rest.split_once('=')?;
+            let value = value.trim_start();
+            let quote = value.chars().next().filter(|&quote| quote == '"' || quote == '\'')?;
+            let (value, after) = value[1..].split_once(quote)?;
+            let value = unescape(value);
+            match name.trim() {
+                "href" => href = Some(value),
+                "type" => media_type = Some(value),
+                _ => {}
+            }
+            rest = after.trim_start();
+        }
+        Some(Stylesheet {
+            href: href?,
+            media_type: media_type?,
+        })
     }
 }
 
+/// Replaces the predefined entities and the character references of a
+/// pseudo attribute value.
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let reference = rest.find(';').map(|end| (&rest[1..end], end));
+        let character = reference.and_then(|(name, _)| match name {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => {
+                let code = match name.strip_prefix("#x") {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => name.strip_prefix('#').and_then(|decimal| decimal.parse().ok()),
+                };
+                code.and_then(char::from_u32)
+            }
+        });
+        match (character, reference) {
+            (Some(character), Some((_, end))) => {
+                unescaped.push(character);
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
 /// Url location.
-#[derive(Debug,Clone)]
+#[derive(Debug,Clone,PartialEq)]
 pub enum Location {
     /// No value.
     None,
     /// Url
     Url(Url),
     /// Url parse error.
-    Err(url::ParseError),
+    Err {
+        /// Parse error.
+        error: url::ParseError,
+        /// Text that failed to parse.
+        raw: String,
+    },
+    /// Url text not parsed yet, read with `ReaderOptions::parse_urls` off.
+    Raw(String),
 }
 impl Location {
-    /// Returns url if present.
+    /// Returns a copy of the url if present, parsing a `Raw` location. See
+    /// `url` to borrow it.
     pub fn get_url(&self) -> Option<Url> {
         match *self {
             Location::Url(ref url) => {
-                return Some(url.clone());
+                Some(url.clone())
             }
+            Location::Raw(ref raw) => Url::parse(raw).ok(),
             _ => {
-                return None;
+                None
+            }
+        }
+    }
+    /// Resolves `reference`, absolute or relative, against `base` with
+    /// `Url::join`.
+    ///
+    /// Fails with `ResolveError::OtherHost` when the url has another host
+    /// or scheme than `base`, e.g. for `//cdn.example.com/a`, and with
+    /// `ResolveError::Parse` when it does not parse.
+    pub fn resolve(base: &Url, reference: &str) -> Result<Url, ResolveError> {
+        let url = base.join(reference.trim()).map_err(ResolveError::Parse)?;
+        if url.scheme() != base.scheme() || url.host() != base.host() || url.port() != base.port() {
+            return Err(ResolveError::OtherHost(url));
+        }
+        Ok(url)
+    }
+    /// Returns the location resolved against `base` when it is a relative
+    /// url, `None` for the other locations, see `resolve`.
+    pub(crate) fn resolve_relative(&self, base: &Url) -> Option<Result<Url, ResolveError>> {
+        match *self {
+            Location::Err { error: url::ParseError::RelativeUrlWithoutBase, ref raw } => {
+                Some(Location::resolve(base, raw))
+            }
+            Location::Raw(ref raw) if Url::parse(raw) == Err(url::ParseError::RelativeUrlWithoutBase) => {
+                Some(Location::resolve(base, raw))
+            }
+            _ => None,
+        }
+    }
+    /// Returns the url of a `Url` location, `None` for the others, a `Raw`
+    /// location not being parsed.
+    pub fn url(&self) -> Option<&Url> {
+        match *self {
+            Location::Url(ref url) => Some(url),
+            _ => None,
+        }
+    }
+    /// Returns the scheme of a `Url` location, `None` for the others, see
+    /// `url`.
+    pub fn scheme(&self) -> Option<&str> {
+        self.url().map(Url::scheme)
+    }
+    /// Returns the host of a `Url` location, an IPv6 address in brackets,
+    /// `None` for the others and the urls without host, see `url`.
+    pub fn host(&self) -> Option<&str> {
+        self.url()?.host_str()
+    }
+    /// Returns the path of a `Url` location, `None` for the others, see
+    /// `url`.
+    pub fn path(&self) -> Option<&str> {
+        self.url().map(Url::path)
+    }
+    /// Whether the url has the scheme, host and port of `other`, the default
+    /// port of the scheme being the same as no port. A `Raw` location is
+    /// parsed, `false` for no value and parse errors.
+    pub fn is_same_origin(&self, other: &Url) -> bool {
+        match *self.parsed() {
+            Location::Url(ref url) => {
+                url.scheme() == other.scheme() && url.host() == other.host() &&
+                url.port_or_known_default() == other.port_or_known_default()
+            }
+            _ => false,
+        }
+    }
+    /// Returns the text of a valid or not parsed url, `None` for no value
+    /// and parse errors.
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Location::Url(ref url) => Some(url.as_str()),
+            Location::Raw(ref raw) => Some(raw),
+            _ => None,
+        }
+    }
+    /// Returns the text of the location, the original one for parse errors.
+    pub fn raw(&self) -> Option<String> {
+        match *self {
+            Location::None => None,
+            Location::Url(ref url) => Some(url.to_string()),
+            Location::Err { ref raw, .. } | Location::Raw(ref raw) => Some(raw.clone()),
+        }
+    }
+    /// Returns the location with a `Raw` url parsed, into `Url` or `Err`,
+    /// the other locations are borrowed.
+    pub fn parsed(&self) -> Cow<'_, Location> {
+        match *self {
+            Location::Raw(ref raw) => Cow::Owned(Location::from(raw.clone())),
+            _ => Cow::Borrowed(self),
+        }
+    }
+    /// Returns the canonical form of the url, invalid locations are returned
+    /// unchanged.
+    ///
+    /// Urls are already parsed with a lowercase scheme and host, without the
+    /// default port, with resolved dot-segments and international hosts in
+    /// punycode. Percent-encoding is normalized to uppercase hex, decoding
+    /// the unreserved characters. A `Raw` location is parsed first.
+    pub fn canonicalize(&self, options: &CanonicalizeOptions) -> Location {
+        let mut url = match *self.parsed() {
+            Location::Url(ref url) => url.clone(),
+            ref location => return location.clone(),
+        };
+        let path = normalize_percent_encoding(url.path());
+        url.set_path(&path);
+        if let Some(query) = url.query() {
+            let mut pairs: Vec<String> = query.split('&').map(normalize_percent_encoding).collect();
+            if options.sort_query {
+                pairs.sort_by(|a, b| a.split('=').next().cmp(&b.split('=').next()));
+            }
+            url.set_query(Some(&pairs.join("&")));
+        }
+        if options.strip_fragment {
+            url.set_fragment(None);
+        } else if let Some(fragment) = url.fragment() {
+            let fragment = normalize_percent_encoding(fragment);
+            url.set_fragment(Some(&fragment));
+        }
+        Location::Url(url)
+    }
+}
+/// Options of `Location::canonicalize`.
+#[derive(Debug,Clone,Copy,Default,PartialEq)]
+pub struct CanonicalizeOptions {
+    /// Whether the fragment is removed, false by default.
+    pub strip_fragment: bool,
+    /// Whether the query parameters are sorted by name, false by default.
+    /// Parameters with the same name keep their order.
+    pub sort_query: bool,
+}
+/// Percent-encoded text with uppercase hex digits and the unreserved
+/// characters decoded.
+fn normalize_percent_encoding(text: &str) -> String {
+    fn hex(digit: u8) -> Option<u8> {
+        (digit as char).to_digit(16).map(|digit| digit as u8)
+    }
+    let bytes = text.as_bytes();
+    let mut normalized = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let digit = |offset: usize| bytes.get(i + offset).and_then(|&digit| hex(digit));
+        let escaped = match (bytes[i], digit(1), digit(2)) {
+            (b'%', Some(high), Some(low)) => Some(high * 16 + low),
+            _ => None,
+        };
+        match escaped {
+            Some(byte) if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) => {
+                normalized.push(byte);
+                i += 3;
+            }
+            Some(_) => {
+                normalized.push(b'%');
+                normalized.push(bytes[i + 1].to_ascii_uppercase());
+                normalized.push(bytes[i + 2].to_ascii_uppercase());
+                i += 3;
+            }
+            None => {
+                normalized.push(bytes[i]);
+                i += 1;
             }
         }
     }
+    String::from_utf8_lossy(&normalized).into_owned()
 }
 impl From<String> for Location {
-    /// Parses Url from string.
-    fn from(url: String) -> Self {
-        match Url::parse(&url) {
+    /// Parses Url from string, keeping the parse error in `Location::Err`.
+    ///
+    /// Prefer `str::parse`, which reports the error instead.
+    fn from(raw: String) -> Self {
+        match Url::parse(&raw) {
             Ok(url) => {
-                return Location::Url(url);
+                Location::Url(url)
             }
             Err(error) => {
-                return Location::Err(error);
+                Location::Err { error, raw }
             }
         }
     }
 }
 /// The date of last modification of the resource.
-#[derive(Debug,Clone)]
+#[derive(Debug,Clone,PartialEq)]
 pub enum LastMod {
     /// No value.
     None,
+    /// Modification date, without time.
+    Date(NaiveDate),
     /// Modification time
     DateTime(DateTime<FixedOffset>),
+    /// Modification time read in W3C datetime with a precision or an offset
+    /// that `DateTime` does not write back, like `2024-05-01T10:00Z`.
+    W3c(Box<W3cTime>),
+    /// Modification time in a format other than W3C datetime.
+    NonStandard(Box<NonStandardTime>),
     /// Parse error
-    Err(chrono_utils::parser::error::ParseError),
+    Err(Box<ParseFailure<chrono_utils::parser::error::ParseError>>),
+}
+/// Modification time of `LastMod::W3c`.
+#[derive(Debug,Clone,PartialEq)]
+pub struct W3cTime {
+    /// Modification time.
+    pub time: DateTime<FixedOffset>,
+    /// Text of the time, written back as is.
+    pub raw: String,
+}
+/// Modification time of `LastMod::NonStandard`.
+#[derive(Debug,Clone,PartialEq)]
+pub struct NonStandardTime {
+    /// Modification time.
+    pub time: DateTime<FixedOffset>,
+    /// Format of the text.
+    pub format: DateFormat,
+    /// Text of the time.
+    pub raw: String,
+}
+/// Text of a field that failed to parse, in the error variants of the
+/// fields. They are boxed, the valid values only are kept inline.
+#[derive(Debug,Clone,PartialEq)]
+pub struct ParseFailure<E> {
+    /// Parse error.
+    pub error: E,
+    /// Text that failed to parse.
+    pub raw: String,
+}
+/// Format of the text of a `LastMod`.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum DateFormat {
+    /// W3C datetime, the format of the protocol, either the date or the
+    /// complete time with an optional fraction of second.
+    W3c,
+    /// RFC 3339 time outside of the W3C profile, like with a space between
+    /// the date and the time.
+    Rfc3339,
+    /// RFC 2822 time, like in HTTP headers.
+    Rfc2822,
+}
+impl fmt::Display for DateFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DateFormat::W3c => write!(f, "W3C datetime"),
+            DateFormat::Rfc3339 => write!(f, "RFC 3339"),
+            DateFormat::Rfc2822 => write!(f, "RFC 2822"),
+        }
+    }
+}
+/// Parses a modification time as W3C datetime, then RFC 3339 and RFC 2822,
+/// failing with the error of the W3C datetime.
+fn parse_lastmod(raw: &str) -> Result<LastMod, chrono_utils::parser::error::ParseError> {
+    let error = match parse_w3c_datetime(raw) {
+        Ok(time) if raw.len() == 10 => return Ok(LastMod::Date(time.date_naive())),
+        Ok(time) => {
+            // the text written for a `DateTime` has seconds, the digits of
+            // the fraction by three and a numeric offset
+            let mut text = Matches { rest: raw, equal: true };
+            write_rfc3339(&mut text, &time, true, false);
+            if text.equal && text.rest.is_empty() {
+                return Ok(LastMod::DateTime(time));
+            }
+            return Ok(LastMod::W3c(Box::new(W3cTime {
+                time,
+                raw: raw.to_string(),
+            })));
+        }
+        Err(error) => error,
+    };
+    let (time, format) = if let Ok(time) = DateTime::parse_from_rfc3339(raw) {
+        (time, DateFormat::Rfc3339)
+    } else if let Ok(time) = DateTime::parse_from_rfc2822(raw) {
+        (time, DateFormat::Rfc2822)
+    } else {
+        return Err(error);
+    };
+    Ok(LastMod::NonStandard(Box::new(NonStandardTime {
+        time,
+        format,
+        raw: raw.to_string(),
+    })))
+}
+/// Writer comparing the text written to `rest`, without allocating it.
+struct Matches<'a> {
+    rest: &'a str,
+    equal: bool,
+}
+impl<'a> fmt::Write for Matches<'a> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        match self.rest.strip_prefix(text) {
+            Some(rest) if self.equal => self.rest = rest,
+            _ => self.equal = false,
+        }
+        Ok(())
+    }
 }
 impl LastMod {
-    /// Returns modification time if present.
+    /// Returns modification time if present, midnight UTC for a date. See
+    /// `time` to borrow it.
     pub fn get_time(&self) -> Option<DateTime<FixedOffset>> {
         match *self {
-            LastMod::DateTime(ref time) => {
-                return Some(time.clone());
+            LastMod::Date(date) => {
+                Some(date.and_time(NaiveTime::MIN).and_utc().fixed_offset())
             }
+            LastMod::DateTime(time) => {
+                Some(time)
+            }
+            LastMod::W3c(ref time) => Some(time.time),
+            LastMod::NonStandard(ref time) => Some(time.time),
             _ => {
-                return None;
+                None
             }
         }
     }
+    /// Returns the modification time of a `DateTime`, `W3c` or
+    /// `NonStandard` time, `None` for a date without time.
+    pub fn time(&self) -> Option<&DateTime<FixedOffset>> {
+        match *self {
+            LastMod::DateTime(ref time) => Some(time),
+            LastMod::W3c(ref time) => Some(&time.time),
+            LastMod::NonStandard(ref time) => Some(&time.time),
+            _ => None,
+        }
+    }
+    /// Returns the text of the time, the original one for parse errors.
+    pub fn raw(&self) -> Option<String> {
+        match *self {
+            LastMod::None => None,
+            LastMod::Date(_) | LastMod::DateTime(_) => self.to_w3c(),
+            LastMod::W3c(ref time) => Some(time.raw.clone()),
+            LastMod::NonStandard(ref time) => Some(time.raw.clone()),
+            LastMod::Err(ref failure) => Some(failure.raw.clone()),
+        }
+    }
+    /// Returns the format of the text the time was parsed from.
+    pub fn format(&self) -> Option<DateFormat> {
+        match *self {
+            LastMod::Date(_) | LastMod::DateTime(_) | LastMod::W3c(_) => Some(DateFormat::W3c),
+            LastMod::NonStandard(ref time) => Some(time.format),
+            _ => None,
+        }
+    }
+    /// Returns the modification date, of the time in its own offset.
+    pub fn get_date(&self) -> Option<NaiveDate> {
+        match *self {
+            LastMod::Date(date) => Some(date),
+            _ => self.get_time().map(|time| time.date_naive()),
+        }
+    }
+    /// Text of the value in W3C datetime, with the precision it was
+    /// parsed with.
+    pub(crate) fn to_w3c(&self) -> Option<String> {
+        let mut text = String::new();
+        if self.write_w3c(&mut text) {
+            Some(text)
+        } else {
+            None
+        }
+    }
+    /// Appends the text of `to_w3c` to `text`, returning whether there is
+    /// a value.
+    pub(crate) fn write_w3c(&self, text: &mut String) -> bool {
+        match *self {
+            LastMod::Date(date) => write_date(text, date),
+            LastMod::W3c(ref time) => text.push_str(&time.raw),
+            _ => match self.get_time() {
+                Some(time) => write_rfc3339(text, &time, true, false),
+                None => return false,
+            },
+        }
+        true
+    }
+}
+/// Appends `date` to `text` like its `%Y-%m-%d` format.
+pub(crate) fn write_date<W: fmt::Write>(text: &mut W, date: NaiveDate) {
+    let _ = if (0..=9999).contains(&date.year()) {
+        write!(text, "{:04}", date.year())
+    } else {
+        write!(text, "{:+05}", date.year())
+    };
+    let _ = write!(text, "-{:02}-{:02}", date.month(), date.day());
+}
+/// Appends `time` to `text` like `DateTime::to_rfc3339_opts`, with the
+/// digits of the fraction of its seconds or in whole seconds, with `Z` for
+/// UTC or `+00:00`. The chrono formats allocate the text of the offset.
+pub(crate) fn write_rfc3339<W: fmt::Write>(text: &mut W, time: &DateTime<FixedOffset>, fraction: bool, use_z: bool) {
+    let local = time.naive_local();
+    write_date(text, local.date());
+    let (mut second, mut nanosecond) = (local.second(), local.nanosecond());
+    if nanosecond >= 1_000_000_000 {
+        second += 1;
+        nanosecond -= 1_000_000_000;
+    }
+    let _ = write!(text, "T{:02}:{:02}:{:02}", local.hour(), local.minute(), second);
+    let _ = match nanosecond {
+        _ if !fraction => Ok(()),
+        0 => Ok(()),
+        _ if nanosecond % 1_000_000 == 0 => write!(text, ".{:03}", nanosecond / 1_000_000),
+        _ if nanosecond % 1_000 == 0 => write!(text, ".{:06}", nanosecond / 1_000),
+        _ => write!(text, ".{:09}", nanosecond),
+    };
+    let offset = time.offset().local_minus_utc();
+    if use_z && offset == 0 {
+        let _ = text.write_char('Z');
+        return;
+    }
+    // rounded to the minute
+    let minutes = (offset.abs() + 30) / 60;
+    let sign = if offset < 0 { '-' } else { '+' };
+    let _ = write!(text, "{}{:02}:{:02}", sign, minutes / 60, minutes % 60);
+}
+impl FromStr for Location {
+    type Err = Error;
+    fn from_str(raw: &str) -> Result<Location, Error> {
+        Url::parse(raw)
+            .map(Location::Url)
+            .map_err(|error| Error::invalid_value("loc", raw, error.to_string()))
+    }
+}
+impl LastMod {
+    /// Modification time of `time` in UTC, keeping the milliseconds.
+    pub fn from_system_time_millis(time: SystemTime) -> LastMod {
+        LastMod::from_system_time(time, 1_000_000)
+    }
+    /// Modification time of `time` in UTC, truncated to multiples of
+    /// `nanoseconds`.
+    fn from_system_time(time: SystemTime, nanoseconds: u32) -> LastMod {
+        let time = DateTime::<Utc>::from(time);
+        let subsec = time.nanosecond() - time.nanosecond() % nanoseconds;
+        let time = time.with_nanosecond(subsec).unwrap_or(time);
+        LastMod::DateTime(time.fixed_offset())
+    }
+}
+impl From<SystemTime> for LastMod {
+    /// Modification time of `time` in UTC, truncated to whole seconds.
+    fn from(time: SystemTime) -> Self {
+        LastMod::from_system_time(time, 1_000_000_000)
+    }
 }
 impl From<String> for LastMod {
-    fn from(time: String) -> Self {
-        match parse_w3c_datetime(&time) {
-            Ok(time) => {
-                return LastMod::DateTime(time);
+    /// Parses W3C datetime from string, falling back to RFC 3339 and RFC
+    /// 2822 in `LastMod::NonStandard`, keeping the parse error in
+    /// `LastMod::Err`.
+    ///
+    /// Prefer `str::parse`, which reports the error instead.
+    fn from(raw: String) -> Self {
+        match parse_lastmod(&raw) {
+            Ok(lastmod) => {
+                lastmod
             }
             Err(error) => {
-                return LastMod::Err(error);
-            }			
+                LastMod::Err(Box::new(ParseFailure { error, raw }))
+            }
         }
     }
 }
 /// Error parsing URL Priority.
-#[derive(PartialEq,Debug,Clone)]
+#[derive(PartialEq,Eq,Debug,Clone)]
 pub struct ChangeFreqParseError {
     /// Error description
     pub description: String,
@@ -202,17 +1467,45 @@ pub struct ChangeFreqParseError {
 impl ChangeFreqParseError {
     /// Creates new error.
     pub fn new(description: String) -> ChangeFreqParseError {
-        ChangeFreqParseError { description: description }
+        ChangeFreqParseError { description }
+    }
+    /// Returns the keyword closest to the text, at most two letters added,
+    /// removed or changed, ignoring the case, like `daily` for `Dayly`.
+    pub fn suggestion(&self) -> Option<&str> {
+        let text = self.description.trim().to_lowercase();
+        ChangeFreq::ALL.iter()
+            .map(|changefreq| (edit_distance(&text, changefreq.as_keyword()), changefreq.as_keyword()))
+            .filter(|&(distance, _)| distance <= 2)
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, keyword)| keyword)
+    }
+}
+/// Number of characters to add, remove or change to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let change = previous[j] + usize::from(ca != cb);
+            current.push(change.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
     }
+    previous[b.len()]
 }
 impl fmt::Display for ChangeFreqParseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let _ = try!(write!(f, "Not recognezed string '{}'", self.description));
-        return Ok(());
+        write!(f, "Not recognezed string '{}'", self.description)?;
+        if let Some(suggestion) = self.suggestion() {
+            write!(f, ", did you mean '{}'?", suggestion)?;
+        }
+        Ok(())
     }
 }
+impl error::Error for ChangeFreqParseError {}
 /// How frequently the page is likely to change.
-#[derive(PartialEq,Debug,Clone)]
+#[derive(PartialEq,Eq,Debug,Clone)]
 pub enum ChangeFreq {
     /// No value.
     None,
@@ -231,10 +1524,53 @@ pub enum ChangeFreq {
     /// Archived URL.
     Never,
     /// Parse error.
-    Err(ChangeFreqParseError),
+    Err(Box<ChangeFreqParseError>),
 }
 impl ChangeFreq {
+    /// The valid change frequencies, from the most to the least frequent.
+    pub const ALL: [ChangeFreq; 7] = [ChangeFreq::Always,
+                                      ChangeFreq::Hourly,
+                                      ChangeFreq::Daily,
+                                      ChangeFreq::Weekly,
+                                      ChangeFreq::Monthly,
+                                      ChangeFreq::Yearly,
+                                      ChangeFreq::Never];
+
+    /// Returns the nominal interval between the changes, zero for `always`,
+    /// 30 days for `monthly` and 365 days for `yearly`. `never`, no value
+    /// and errors have none.
+    pub fn as_duration(&self) -> Option<Duration> {
+        const HOUR: u64 = 3600;
+        const DAY: u64 = 24 * HOUR;
+        match *self {
+            ChangeFreq::Always => Some(Duration::from_secs(0)),
+            ChangeFreq::Hourly => Some(Duration::from_secs(HOUR)),
+            ChangeFreq::Daily => Some(Duration::from_secs(DAY)),
+            ChangeFreq::Weekly => Some(Duration::from_secs(7 * DAY)),
+            ChangeFreq::Monthly => Some(Duration::from_secs(30 * DAY)),
+            ChangeFreq::Yearly => Some(Duration::from_secs(365 * DAY)),
+            ChangeFreq::Never | ChangeFreq::None | ChangeFreq::Err(_) => None,
+        }
+    }
+    /// Order of the valid change frequencies, from `always` to `never`.
+    pub(crate) fn rank(&self) -> Option<u8> {
+        match *self {
+            ChangeFreq::Always => Some(0),
+            ChangeFreq::Hourly => Some(1),
+            ChangeFreq::Daily => Some(2),
+            ChangeFreq::Weekly => Some(3),
+            ChangeFreq::Monthly => Some(4),
+            ChangeFreq::Yearly => Some(5),
+            ChangeFreq::Never => Some(6),
+            ChangeFreq::None | ChangeFreq::Err(_) => None,
+        }
+    }
+    /// Returns the keyword of the change frequency, empty for no value and
+    /// errors.
     pub fn as_str(&self) -> &str {
+        self.as_keyword()
+    }
+    fn as_keyword(&self) -> &'static str {
         match *self {
             ChangeFreq::None => "",
             ChangeFreq::Always => "always",
@@ -249,34 +1585,89 @@ impl ChangeFreq {
 
     }
 }
+impl fmt::Display for ChangeFreq {
+    /// Writes the keyword of `as_str`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+impl PartialOrd for ChangeFreq {
+    fn partial_cmp(&self, other: &ChangeFreq) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for ChangeFreq {
+    /// Change frequencies are ordered from `always` to `never`, no value
+    /// is greater than `never` and errors are greater than no value,
+    /// ordered by their text.
+    fn cmp(&self, other: &ChangeFreq) -> Ordering {
+        fn key(changefreq: &ChangeFreq) -> u8 {
+            match *changefreq {
+                ChangeFreq::None => 7,
+                ChangeFreq::Err(_) => 8,
+                ref valid => valid.rank().unwrap_or(0),
+            }
+        }
+        key(self).cmp(&key(other)).then_with(|| match (self, other) {
+            (ChangeFreq::Err(a), ChangeFreq::Err(b)) => a.description.cmp(&b.description),
+            _ => Ordering::Equal,
+        })
+    }
+}
+impl FromStr for LastMod {
+    type Err = Error;
+    fn from_str(raw: &str) -> Result<LastMod, Error> {
+        parse_lastmod(raw)
+            .map_err(|error| Error::invalid_value("lastmod", raw, error.to_string()))
+    }
+}
 impl From<String> for ChangeFreq {
+    /// Parses the change frequency keyword, ignoring the case and the
+    /// whitespace around, keeping unknown keywords in `ChangeFreq::Err`.
+    ///
+    /// Prefer `str::parse`, which reports the error instead.
     fn from(time: String) -> Self {
-        let lowercase_time = time.to_lowercase();
+        let lowercase_time = time.trim().to_lowercase();
         match lowercase_time.as_ref() {
             "always" => {
-                return ChangeFreq::Always;
+                ChangeFreq::Always
             }
             "hourly" => {
-                return ChangeFreq::Hourly;
+                ChangeFreq::Hourly
             }
             "daily" => {
-                return ChangeFreq::Daily;
+                ChangeFreq::Daily
             }
             "weekly" => {
-                return ChangeFreq::Weekly;
+                ChangeFreq::Weekly
             }
             "monthly" => {
-                return ChangeFreq::Monthly;
+                ChangeFreq::Monthly
             }
             "yearly" => {
-                return ChangeFreq::Yearly;
+                ChangeFreq::Yearly
             }
             "never" => {
-                return ChangeFreq::Never;
+                ChangeFreq::Never
             }
             _ => {
-                return ChangeFreq::Err(ChangeFreqParseError::new(time));
+                ChangeFreq::Err(Box::new(ChangeFreqParseError::new(time)))
+            }
+        }
+    }
+}
+impl FromStr for ChangeFreq {
+    type Err = Error;
+    fn from_str(raw: &str) -> Result<ChangeFreq, Error> {
+        match ChangeFreq::from(raw.to_string()) {
+            ChangeFreq::Err(error) => {
+                let message = match error.suggestion() {
+                    Some(suggestion) => format!("unknown change frequency, did you mean '{}'?", suggestion),
+                    None => "unknown change frequency".to_string(),
+                };
+                Err(Error::invalid_value("changefreq", raw, message))
             }
+            changefreq => Ok(changefreq),
         }
     }
 }
@@ -289,41 +1680,190 @@ pub enum Priority {
     /// Priority
     Value(f32),
     /// Parse error.
-    Err(num::ParseFloatError),
+    Err(Box<ParseFailure<num::ParseFloatError>>),
     /// Error: priority lesser than zero.
     ErrValueLesserZero(f32),
     /// Error: priority greater than one.
     ErrValueGreaterOne(f32),
+    /// Error: priority not a number, with its text.
+    ErrNotANumber(Box<String>),
 }
 impl Priority {
+    /// Highest priority.
+    pub const MAX: Priority = Priority::Value(1.0);
+    /// Lowest priority.
+    pub const MIN: Priority = Priority::Value(0.0);
+    /// Priority of the urls without one, as defined by the protocol.
+    pub const DEFAULT: Priority = Priority::Value(0.5);
+
+    /// Creates a priority, `value` must be a number between 0 and 1.
+    pub fn new(value: f32) -> Result<Priority, Error> {
+        if value.is_nan() {
+            Err(Error::invalid_value("priority", value.to_string(), "priority is not a number"))
+        } else if !(0.0..=1.0).contains(&value) {
+            Err(Error::invalid_value("priority", value.to_string(), "priority should be between 0 and 1"))
+        } else {
+            Ok(Priority::Value(value))
+        }
+    }
     /// Returns priority if present.
     pub fn get_priority(&self) -> Option<f32> {
         match *self {
             Priority::Value(value) => {
-                return Some(value);
+                Some(value)
             }
             _ => {
-                return None;
+                None
             }
         }
     }
+    /// Returns the text of the priority, the original one for parse errors.
+    pub fn raw(&self) -> Option<String> {
+        match *self {
+            Priority::None => None,
+            Priority::Value(value) |
+            Priority::ErrValueLesserZero(value) |
+            Priority::ErrValueGreaterOne(value) => Some(value.to_string()),
+            Priority::Err(ref failure) => Some(failure.raw.clone()),
+            Priority::ErrNotANumber(ref raw) => Some(raw.to_string()),
+        }
+    }
+}
+impl PartialEq for Priority {
+    /// Priorities are compared by exact value, the writer emits the shortest
+    /// representation that parses back to the same `f32`. `NaN` values are
+    /// considered equal to each other, parse errors are compared by kind.
+    fn eq(&self, other: &Priority) -> bool {
+        fn same(a: f32, b: f32) -> bool {
+            a == b || (a.is_nan() && b.is_nan())
+        }
+        match (self, other) {
+            (Priority::None, Priority::None) => true,
+            (Priority::Value(a), Priority::Value(b)) => same(*a, *b),
+            (Priority::Err(a), Priority::Err(b)) => a.error == b.error,
+            (Priority::ErrValueLesserZero(a), Priority::ErrValueLesserZero(b)) => same(*a, *b),
+            (Priority::ErrValueGreaterOne(a), Priority::ErrValueGreaterOne(b)) => same(*a, *b),
+            (Priority::ErrNotANumber(_), Priority::ErrNotANumber(_)) => true,
+            _ => false,
+        }
+    }
+}
+impl Eq for Priority {}
+impl PartialOrd for Priority {
+    fn partial_cmp(&self, other: &Priority) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Priority {
+    /// Priorities are ordered by value, `None` counting as the default 0.5
+    /// and ordered just before it. Errors are lower than any value, `NaN`
+    /// values lower than any other value.
+    fn cmp(&self, other: &Priority) -> Ordering {
+        fn value(a: f32, b: f32) -> Ordering {
+            match (a.is_nan(), b.is_nan()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Less,
+                (false, true) => Ordering::Greater,
+                (false, false) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+            }
+        }
+        // order of the variants, then the value used for the comparison
+        fn key(priority: &Priority) -> (u8, f32) {
+            match *priority {
+                Priority::Err { .. } | Priority::ErrNotANumber(_) => (0, 0.0),
+                Priority::ErrValueLesserZero(value) => (1, value),
+                Priority::ErrValueGreaterOne(value) => (2, value),
+                Priority::None => (3, 0.5),
+                Priority::Value(value) => (3, value),
+            }
+        }
+        let (a, b) = (key(self), key(other));
+        a.0.cmp(&b.0)
+            .then_with(|| value(a.1, b.1))
+            .then_with(|| match (self, other) {
+                (Priority::None, Priority::Value(_)) => Ordering::Less,
+                (Priority::Value(_), Priority::None) => Ordering::Greater,
+                (Priority::Err(a), Priority::Err(b)) => a.error.to_string().cmp(&b.error.to_string()),
+                (Priority::Err(_), Priority::ErrNotANumber(_)) => Ordering::Less,
+                (Priority::ErrNotANumber(_), Priority::Err(_)) => Ordering::Greater,
+                _ => Ordering::Equal,
+            })
+    }
 }
 impl From<String> for Priority {
+    /// Parses the priority, keeping errors in the `Err` variants.
+    ///
+    /// Prefer `str::parse`, which reports the error instead.
     fn from(priority: String) -> Self {
         let value = priority.parse::<f32>();
         match value {
             Ok(value) => {
-                if value > 1.0 {
-                    return Priority::ErrValueGreaterOne(value);
+                if value.is_nan() {
+                    Priority::ErrNotANumber(Box::new(priority))
+                } else if value > 1.0 {
+                    Priority::ErrValueGreaterOne(value)
                 } else if value < 0.0 {
-                    return Priority::ErrValueLesserZero(value);
+                    Priority::ErrValueLesserZero(value)
                 } else {
-                    return Priority::Value(value);
+                    Priority::Value(value)
                 }
             }
             Err(error) => {
-                return Priority::Err(error);
+                Priority::Err(Box::new(ParseFailure { error, raw: priority }))
             }
         }
     }
 }
+impl FromStr for Priority {
+    type Err = Error;
+    fn from_str(raw: &str) -> Result<Priority, Error> {
+        match raw.parse::<f32>() {
+            Ok(value) if (0.0..=1.0).contains(&value) => Ok(Priority::Value(value)),
+            Ok(_) => Err(Error::invalid_value("priority", raw, "priority should be between 0 and 1")),
+            Err(error) => Err(Error::invalid_value("priority", raw, error.to_string())),
+        }
+    }
+}
+
+/// The duration of a video.
+#[derive(Debug,Clone,PartialEq)]
+pub enum VideoDuration {
+    /// No value.
+    None,
+    /// Duration, between 1 and `MAX_VIDEO_DURATION` seconds.
+    Value(Duration),
+    /// Parse error.
+    Err(num::ParseIntError),
+    /// Error: duration lesser than one second.
+    ErrValueLesserOne(u64),
+    /// Error: duration greater than `MAX_VIDEO_DURATION` seconds.
+    ErrValueGreaterMax(u64),
+}
+impl VideoDuration {
+    /// Returns duration if present.
+    pub fn get_duration(&self) -> Option<Duration> {
+        match *self {
+            VideoDuration::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+impl From<u64> for VideoDuration {
+    fn from(seconds: u64) -> Self {
+        if seconds < 1 {
+            VideoDuration::ErrValueLesserOne(seconds)
+        } else if seconds > MAX_VIDEO_DURATION {
+            VideoDuration::ErrValueGreaterMax(seconds)
+        } else {
+            VideoDuration::Value(Duration::from_secs(seconds))
+        }
+    }
+}
+impl From<String> for VideoDuration {
+    fn from(duration: String) -> Self {
+        match duration.parse::<u64>() {
+            Ok(seconds) => VideoDuration::from(seconds),
+            Err(error) => VideoDuration::Err(error),
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! Contains the generation of arbitrary entries for property tests, enabled
+//! by the `testing` feature.
+//!
+//! `Gen` is a small deterministic generator, the same seed always giving the
+//! same values, and `Arbitrary` builds valid values from it: the generated
+//! entries are written by `SiteMapWriter` and read back by `SiteMapReader`
+//! with the same value. `check` runs a property on many generated values and
+//! reports the seed of the first failing one.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate sitemap;
+//! use sitemap::structs::UrlEntry;
+//! use sitemap::testing::{self, Arbitrary, Gen};
+//! fn main() {
+//!     let url = UrlEntry::arbitrary(&mut Gen::new(7));
+//!     assert!(url.errors().is_empty());
+//!     testing::check(100, |url: UrlEntry| url.loc.get_url().is_some());
+//! }
+//! ```
+use crate::structs::{Alternate, ChangeFreq, ImageEntry, LastMod, Location, Priority, SiteMapEntry, UrlEntry};
+use chrono::{FixedOffset, NaiveDate, TimeZone};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use url::Url;
+
+/// Pieces of the generated paths, queries and titles.
+const WORDS: [&str; 12] = ["page", "caf\u{e9}", "\u{65e5}\u{672c}\u{8a9e}", "a b", "\u{fc}ber", "x", "2024",
+                           "\u{1f600}", "a&b", "<tag>", "it's", "\u{441}\u{430}\u{439}\u{442}"];
+
+/// Deterministic generator of pseudo-random values, xorshift64*.
+#[derive(Debug,Clone)]
+pub struct Gen {
+    state: u64,
+}
+
+impl Gen {
+    /// Creates a new generator, the same `seed` giving the same values.
+    pub fn new(seed: u64) -> Gen {
+        // the state must not be zero
+        Gen { state: seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1 }
+    }
+
+    /// Next value of the generator.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Value lower than `bound`, 0 for a 0 `bound`.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        match bound {
+            0 => 0,
+            _ => self.next_u64() % bound,
+        }
+    }
+
+    /// `true` once every `n` times on average.
+    pub fn one_in(&mut self, n: u64) -> bool {
+        self.below(n) == 0
+    }
+
+    /// Item of `items`, which must not be empty.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+
+    /// Text of one to three words, with unicode and XML special characters.
+    pub fn text(&mut self) -> String {
+        let words = 1 + self.below(3);
+        (0..words).map(|_| *self.choose(&WORDS)).collect::<Vec<_>>().join("-")
+    }
+
+    /// Valid url of `www.example.com`, with a unicode path and an optional
+    /// query.
+    pub fn url(&mut self) -> Url {
+        let mut text = String::from("http://www.example.com/");
+        for _ in 0..self.below(4) {
+            text.push_str(&self.text());
+            text.push('/');
+        }
+        if self.one_in(2) {
+            text.push_str("?q=");
+            text.push_str(&self.text());
+        }
+        // every piece is valid, the url crate percent-encodes what it has to
+        Url::parse(&text).unwrap_or_else(|_| Url::parse("http://www.example.com/").unwrap())
+    }
+}
+
+/// Type of the values built by `Gen`.
+pub trait Arbitrary: Sized {
+    /// Builds an arbitrary value from `gen`.
+    fn arbitrary(gen: &mut Gen) -> Self;
+}
+
+impl Arbitrary for ChangeFreq {
+    /// One of the valid change frequencies or no value.
+    fn arbitrary(gen: &mut Gen) -> ChangeFreq {
+        match gen.below(ChangeFreq::ALL.len() as u64 + 1) as usize {
+            0 => ChangeFreq::None,
+            index => ChangeFreq::ALL[index - 1].clone(),
+        }
+    }
+}
+
+impl Arbitrary for Priority {
+    /// No value, exactly 0.0 or 1.0, or a multiple of 0.1, so that it is
+    /// written without rounding.
+    fn arbitrary(gen: &mut Gen) -> Priority {
+        match gen.below(4) {
+            0 => Priority::None,
+            1 => Priority::MIN,
+            2 => Priority::MAX,
+            _ => Priority::Value(gen.below(11) as f32 / 10.0),
+        }
+    }
+}
+
+impl Arbitrary for LastMod {
+    /// No value, a date or a time in whole seconds with an offset, from
+    /// year 1 to year 9999, the dates of `0001-01-01` and `9999-12-31` and
+    /// the 29th of February included.
+    fn arbitrary(gen: &mut Gen) -> LastMod {
+        let date = match gen.below(5) {
+            0 => NaiveDate::from_ymd_opt(1, 1, 1),
+            1 => NaiveDate::from_ymd_opt(9999, 12, 31),
+            2 => NaiveDate::from_ymd_opt(2000 + 4 * gen.below(25) as i32, 2, 29),
+            _ => {
+                NaiveDate::from_ymd_opt(1 + gen.below(9999) as i32,
+                                        1 + gen.below(12) as u32,
+                                        1 + gen.below(28) as u32)
+            }
+        };
+        let date = match date {
+            Some(date) => date,
+            None => return LastMod::None,
+        };
+        match gen.below(3) {
+            0 => LastMod::None,
+            1 => LastMod::Date(date),
+            _ => {
+                // leap seconds are not generated
+                let time = date.and_hms_opt(gen.below(24) as u32, gen.below(60) as u32, gen.below(60) as u32);
+                // whole quarters of an hour from -12:00 to +12:00, the range
+                // of the W3C datetimes read
+                let offset = FixedOffset::east_opt((gen.below(97) as i32 - 48) * 900);
+                match (time, offset) {
+                    (Some(time), Some(offset)) => {
+                        offset.from_local_datetime(&time).single().map_or(LastMod::None, LastMod::DateTime)
+                    }
+                    _ => LastMod::None,
+                }
+            }
+        }
+    }
+}
+
+impl Arbitrary for ImageEntry {
+    /// Image with a location and an optional title.
+    fn arbitrary(gen: &mut Gen) -> ImageEntry {
+        ImageEntry {
+            title: if gen.one_in(2) { Some(gen.text()) } else { None },
+            ..ImageEntry::with_loc(gen.url())
+        }
+    }
+}
+
+impl Arbitrary for UrlEntry {
+    /// Url with a valid location and any of the other fields, up to two
+    /// images and two alternates.
+    fn arbitrary(gen: &mut Gen) -> UrlEntry {
+        let mut url = UrlEntry::new();
+        url.loc = Location::Url(gen.url());
+        url.lastmod = LastMod::arbitrary(gen);
+        url.changefreq = ChangeFreq::arbitrary(gen);
+        url.priority = Priority::arbitrary(gen);
+        for _ in 0..gen.below(3) {
+            url.images.push(ImageEntry::arbitrary(gen));
+        }
+        for _ in 0..gen.below(3) {
+            let hreflang = *gen.choose(&["fr", "de-CH", "x-default"]);
+            url.alternates.push(Alternate::new(hreflang, gen.url()));
+        }
+        url
+    }
+}
+
+impl Arbitrary for SiteMapEntry {
+    /// Sitemap with a valid location and an optional modification time.
+    fn arbitrary(gen: &mut Gen) -> SiteMapEntry {
+        let mut sitemap = SiteMapEntry::new();
+        sitemap.loc = Location::Url(gen.url());
+        sitemap.lastmod = LastMod::arbitrary(gen);
+        sitemap
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for Vec<T> {
+    /// Zero to nine values.
+    fn arbitrary(gen: &mut Gen) -> Vec<T> {
+        (0..gen.below(10)).map(|_| T::arbitrary(gen)).collect()
+    }
+}
+
+/// Runs `property` on the values of `cases` seeds, see `check_with_seed`.
+pub fn check<T, F>(cases: u64, property: F)
+    where T: Arbitrary + fmt::Debug + Clone,
+          F: FnMut(T) -> bool
+{
+    check_with_seed(0, cases, property)
+}
+
+/// Runs `property` on the values generated from the seeds `seed` to
+/// `seed + cases - 1`.
+///
+/// Panics on the first value for which the property is `false` or panics,
+/// with the seed and the value, so that the case can be run again from
+/// `Gen::new(seed)`.
+pub fn check_with_seed<T, F>(seed: u64, cases: u64, mut property: F)
+    where T: Arbitrary + fmt::Debug + Clone,
+          F: FnMut(T) -> bool
+{
+    for seed in seed..seed.saturating_add(cases) {
+        let value = T::arbitrary(&mut Gen::new(seed));
+        let result = panic::catch_unwind(AssertUnwindSafe(|| property(value.clone())));
+        if !matches!(result, Ok(true)) {
+            panic!("property failed for seed {}: {:?}", seed, value);
+        }
+    }
+}
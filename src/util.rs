@@ -0,0 +1,117 @@
+//! Contains the sorting, deduplication and canonicalization of url entries,
+//! for example after merging several sitemaps.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate sitemap;
+//! use sitemap::structs::UrlEntry;
+//! use sitemap::util;
+//! fn main() {
+//!     let mut entries = vec![
+//!         UrlEntry::builder().loc("http://www.example.com").priority(0.3).build().unwrap(),
+//!         UrlEntry::builder().loc("http://www.example.com/about").build().unwrap(),
+//!         UrlEntry::builder().loc("http://www.example.com/").priority(0.8).build().unwrap(),
+//!     ];
+//!     util::dedup_by_loc(&mut entries);
+//!     assert_eq!(entries.len(), 2);
+//!     assert_eq!(entries[0].priority.get_priority(), Some(0.8));
+//! }
+//! ```
+use crate::structs::{CanonicalizeOptions, Location, UrlEntry};
+use std::cmp::{Ordering, Reverse};
+use std::collections::HashMap;
+
+/// Comparison of the locations of the entries.
+#[derive(Debug,Clone,Copy,Default,PartialEq)]
+pub struct LocOptions {
+    /// Whether urls differing only by the fragment are the same, false by
+    /// default.
+    pub ignore_fragment: bool,
+    /// Whether urls differing only by a trailing slash of the path are the
+    /// same, false by default.
+    pub ignore_trailing_slash: bool,
+}
+
+/// Text of `loc` compared by `options`, the raw text for invalid urls.
+pub(crate) fn loc_key(loc: &Location, options: &LocOptions) -> String {
+    match *loc.parsed() {
+        Location::Url(ref url) => {
+            let mut url = url.clone();
+            if options.ignore_fragment {
+                url.set_fragment(None);
+            }
+            if options.ignore_trailing_slash && url.path().len() > 1 && url.path().ends_with('/') {
+                let path = url.path().trim_end_matches('/').to_string();
+                url.set_path(if path.is_empty() { "/" } else { &path });
+            }
+            url.to_string()
+        }
+        _ => loc.raw().unwrap_or_default(),
+    }
+}
+
+/// Order of the entries to keep, the most recently modified then the one
+/// with the highest priority.
+fn freshness(a: &UrlEntry, b: &UrlEntry) -> Ordering {
+    a.lastmod.get_time().cmp(&b.lastmod.get_time()).then_with(|| a.priority.cmp(&b.priority))
+}
+
+/// Removes the entries with the same location, see `dedup_by_loc_with_options`.
+pub fn dedup_by_loc(entries: &mut Vec<UrlEntry>) {
+    dedup_by_loc_with_options(entries, &LocOptions::default());
+}
+
+/// Removes the entries with the same location as compared by `options`.
+///
+/// The entry with the most recent `lastmod` is kept, the one with the
+/// highest priority for the same `lastmod`, the first one otherwise. The
+/// kept entry takes the place of the first one with its location.
+pub fn dedup_by_loc_with_options(entries: &mut Vec<UrlEntry>, options: &LocOptions) {
+    let mut kept: Vec<UrlEntry> = Vec::with_capacity(entries.len());
+    let mut positions = HashMap::new();
+    for entry in entries.drain(..) {
+        let key = loc_key(&entry.loc, options);
+        match positions.get(&key) {
+            Some(&position) => {
+                if freshness(&entry, &kept[position]) == Ordering::Greater {
+                    kept[position] = entry;
+                }
+            }
+            None => {
+                positions.insert(key, kept.len());
+                kept.push(entry);
+            }
+        }
+    }
+    *entries = kept;
+}
+
+/// Sorts the entries by location, urls with the same location keep their
+/// order.
+pub fn sort_by_loc(entries: &mut [UrlEntry]) {
+    let options = LocOptions::default();
+    entries.sort_by_cached_key(|entry| loc_key(&entry.loc, &options));
+}
+
+/// Sorts the entries by decreasing priority, entries without a priority
+/// count as the default 0.5 and invalid priorities come last.
+pub fn sort_by_priority_desc(entries: &mut [UrlEntry]) {
+    entries.sort_by(|a, b| b.priority.cmp(&a.priority));
+}
+
+/// Sorts the entries by decreasing `lastmod`, entries without a valid
+/// `lastmod` come last.
+pub fn sort_by_lastmod_desc(entries: &mut [UrlEntry]) {
+    entries.sort_by_key(|entry| Reverse(entry.lastmod.get_time()));
+}
+
+/// Replaces the location of every entry with its canonical form, see
+/// `Location::canonicalize`.
+pub fn canonicalize<'a, I>(entries: I, options: &CanonicalizeOptions)
+    where I: IntoIterator<Item = &'a mut UrlEntry>
+{
+    for entry in entries {
+        entry.loc = entry.loc.canonicalize(options);
+    }
+}
@@ -0,0 +1,711 @@
+//! Contains the checks of sitemaps against the protocol.
+//!
+//! `validate` reads a document and reports every issue found, by category.
+//! `check_scope` checks that the urls of a sitemap have the scheme and the
+//! host of the sitemap, and are in the directory of the sitemap.
+//! `audit_freshness` reports the entries not modified as often as their
+//! change frequency tells, and the ones dated in the future.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate sitemap;
+//! use sitemap::validate::{self, IssueKind, ValidationOptions};
+//! fn main() {
+//!     let document = "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\
+//!                     <url><loc>http://www.example.com/</loc><priority>2</priority></url>\
+//!                     </urlset>";
+//!     let report = validate::validate(document.as_bytes(), &ValidationOptions::default());
+//!     assert_eq!(report.entries, 1);
+//!     assert_eq!(report.count(IssueKind::InvalidPriority), 1);
+//!     println!("{}", report);
+//! }
+//! ```
+//!
+//! ```rust
+//! extern crate sitemap;
+//! extern crate url;
+//! use sitemap::structs::UrlEntry;
+//! use sitemap::validate::{self, ScopeViolationKind};
+//! use url::Url;
+//! fn main() {
+//!     let sitemap_url = Url::parse("https://www.example.com/sitemap.xml").unwrap();
+//!     let entries = vec![
+//!         UrlEntry::builder().loc("https://www.example.com/about").build().unwrap(),
+//!         UrlEntry::builder().loc("https://cdn.example.com/about").build().unwrap(),
+//!     ];
+//!     let violations = validate::check_scope(&sitemap_url, entries.iter());
+//!     assert_eq!(violations.len(), 1);
+//!     assert_eq!(violations[0].index, 1);
+//!     assert_eq!(violations[0].kind, ScopeViolationKind::Host);
+//! }
+//! ```
+//!
+//! ```rust
+//! extern crate chrono;
+//! extern crate sitemap;
+//! use chrono::DateTime;
+//! use sitemap::structs::{ChangeFreq, UrlEntry};
+//! use sitemap::validate::{self, Freshness, FreshnessOptions};
+//! fn main() {
+//!     let lastmod = DateTime::parse_from_rfc3339("2024-05-01T10:00:00+00:00").unwrap();
+//!     let entries = vec![
+//!         UrlEntry::builder()
+//!             .loc("http://www.example.com/")
+//!             .lastmod(lastmod)
+//!             .changefreq(ChangeFreq::Daily)
+//!             .build()
+//!             .unwrap(),
+//!         UrlEntry::builder().loc("http://www.example.com/about").changefreq(ChangeFreq::Yearly).build().unwrap(),
+//!     ];
+//!     let now = DateTime::parse_from_rfc3339("2024-05-04T10:00:00+00:00").unwrap();
+//!     let report = validate::audit_freshness(&entries, now, &FreshnessOptions::default());
+//!     assert_eq!((report.stale, report.missing_lastmod), (1, 1));
+//!     match report.offenders[0].freshness {
+//!         Freshness::StaleForChangefreq { overdue } => assert_eq!(overdue.as_secs(), 2 * 24 * 3600),
+//!         _ => unreachable!(),
+//!     }
+//!     println!("{}", report);
+//! }
+//! ```
+use crate::index::freshness;
+use crate::reader::{DocumentKind, ReaderOptions, SiteMapEvent, SiteMapReader, WarningKind};
+use crate::structs::{self, ChangeFreq, LastMod, Location, Position, Priority, UrlEntry};
+use chrono::{DateTime, FixedOffset, NaiveTime};
+use std::collections::HashSet;
+use std::fmt;
+use std::io::Read;
+use std::time::Duration;
+use url::Url;
+
+/// Options of `validate`.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct ValidationOptions {
+    /// Maximum number of samples kept for each category, 10 by default.
+    pub max_samples: usize,
+    /// Options of the reader, `strict` is ignored.
+    pub reader: ReaderOptions,
+}
+
+impl Default for ValidationOptions {
+    fn default() -> ValidationOptions {
+        ValidationOptions {
+            max_samples: 10,
+            reader: ReaderOptions::default(),
+        }
+    }
+}
+
+/// Category of the issues of a validation.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum IssueKind {
+    /// Missing or invalid `<loc>`.
+    InvalidLoc,
+    /// `<loc>` over `MAX_URL_LENGTH` characters.
+    LocTooLong,
+    /// Invalid `<lastmod>`, or not a W3C datetime.
+    InvalidLastMod,
+    /// `<priority>` not a number between 0 and 1.
+    InvalidPriority,
+    /// Unknown `<changefreq>`.
+    InvalidChangeFreq,
+    /// Entry over `MAX_URLS_PER_SITEMAP` entries.
+    TooManyEntries,
+    /// `<loc>` of a previous entry.
+    DuplicateLoc,
+    /// `&` not starting an entity or character reference.
+    UnescapedAmpersand,
+    /// Root element without the sitemap namespace.
+    MissingNamespace,
+    /// `<image:image>` without `<image:loc>`.
+    MissingImageLoc,
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match *self {
+            IssueKind::InvalidLoc => "invalid loc",
+            IssueKind::LocTooLong => "loc over 2048 characters",
+            IssueKind::InvalidLastMod => "invalid lastmod",
+            IssueKind::InvalidPriority => "priority out of range",
+            IssueKind::InvalidChangeFreq => "unknown changefreq",
+            IssueKind::TooManyEntries => "entries over 50000",
+            IssueKind::DuplicateLoc => "duplicate loc",
+            IssueKind::UnescapedAmpersand => "unescaped ampersand",
+            IssueKind::MissingNamespace => "missing sitemap namespace",
+            IssueKind::MissingImageLoc => "image without loc",
+        };
+        write!(f, "{}", description)
+    }
+}
+
+/// Sample of an issue.
+#[derive(Debug,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Issue {
+    /// Position in the document, when known.
+    pub position: Option<Position>,
+    /// Offending text.
+    pub value: String,
+    /// Valid value close to the offending text, for the unknown change
+    /// frequencies.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub suggestion: Option<String>,
+}
+
+/// Issues of a category.
+#[derive(Debug,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Issues {
+    /// Category of the issues.
+    pub kind: IssueKind,
+    /// Number of issues.
+    pub count: usize,
+    /// First issues, up to `ValidationOptions::max_samples`.
+    pub samples: Vec<Issue>,
+}
+
+/// Result of `validate`.
+#[derive(Debug,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ValidationReport {
+    /// Number of url or sitemap entries.
+    pub entries: usize,
+    /// Issues found, by category in order of first occurrence.
+    pub issues: Vec<Issues>,
+    /// Error that ended the reading before the end of the document.
+    pub fatal: Option<String>,
+}
+
+impl ValidationReport {
+    /// Whether the document was read without issues.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty() && self.fatal.is_none()
+    }
+    /// Issues of the category `kind`, if any.
+    pub fn issues(&self, kind: IssueKind) -> Option<&Issues> {
+        self.issues.iter().find(|issues| issues.kind == kind)
+    }
+    /// Number of issues of the category `kind`.
+    pub fn count(&self, kind: IssueKind) -> usize {
+        self.issues(kind).map_or(0, |issues| issues.count)
+    }
+    fn add<V: Into<String>>(&mut self,
+                            kind: IssueKind,
+                            position: Option<Position>,
+                            value: V,
+                            max_samples: usize) {
+        let issue = Issue {
+            position,
+            value: value.into(),
+            suggestion: None,
+        };
+        self.add_issue(kind, issue, max_samples)
+    }
+    fn add_issue(&mut self, kind: IssueKind, issue: Issue, max_samples: usize) {
+        let index = match self.issues.iter().position(|issues| issues.kind == kind) {
+            Some(index) => index,
+            None => {
+                self.issues.push(Issues {
+                    kind,
+                    count: 0,
+                    samples: Vec::new(),
+                });
+                self.issues.len() - 1
+            }
+        };
+        let issues = &mut self.issues[index];
+        issues.count += 1;
+        if issues.samples.len() < max_samples {
+            issues.samples.push(issue);
+        }
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let count: usize = self.issues.iter().map(|issues| issues.count).sum();
+        write!(f, "{} entries, {} issues", self.entries, count)?;
+        if let Some(ref fatal) = self.fatal {
+            write!(f, ", reading stopped: {}", fatal)?;
+        }
+        for issues in &self.issues {
+            write!(f, "\n{}: {}", issues.kind, issues.count)?;
+            for sample in &issues.samples {
+                if let Some(position) = sample.position {
+                    write!(f, "\n  {}:{}", position.line, position.column)?;
+                } else {
+                    write!(f, "\n ")?;
+                }
+                write!(f, " {}", sample.value)?;
+                if let Some(ref suggestion) = sample.suggestion {
+                    write!(f, " (did you mean '{}'?)", suggestion)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads the document from `source` and reports its issues.
+///
+/// XML errors end the reading, the report has the issues found until then
+/// and the error in `fatal`.
+pub fn validate<R: Read>(mut source: R, options: &ValidationOptions) -> ValidationReport {
+    let mut report = ValidationReport {
+        entries: 0,
+        issues: Vec::new(),
+        fatal: None,
+    };
+    let max_samples = options.max_samples;
+    let mut document = Vec::new();
+    if let Err(error) = source.read_to_end(&mut document) {
+        report.fatal = Some(crate::Error::Io(error).to_string());
+        return report;
+    }
+    for (position, value) in unescaped_ampersands(&document) {
+        report.add(IssueKind::UnescapedAmpersand, Some(position), value, max_samples);
+    }
+    let reader_options = ReaderOptions {
+        strict: false,
+        ..options.reader
+    };
+    let mut reader = SiteMapReader::with_options(&document[..], reader_options);
+    let mut locs = HashSet::new();
+    for event in &mut reader {
+        let (loc, lastmod, positions) = match event {
+            Ok(SiteMapEvent::Url(ref entry)) => {
+                check_url(&mut report, entry, max_samples);
+                let positions = [entry.position(), entry.field_position("loc"), entry.field_position("lastmod")];
+                (&entry.loc, &entry.lastmod, positions)
+            }
+            Ok(SiteMapEvent::SiteMap(ref entry)) => {
+                let positions = [entry.position(), entry.field_position("loc"), entry.field_position("lastmod")];
+                (&entry.loc, &entry.lastmod, positions)
+            }
+            Ok(_) => continue,
+            Err(error) => {
+                report.fatal = Some(error.to_string());
+                break;
+            }
+        };
+        let [position, loc_position, lastmod_position] = positions;
+        report.entries += 1;
+        if report.entries > structs::MAX_URLS_PER_SITEMAP {
+            report.add(IssueKind::TooManyEntries, position, loc.raw().unwrap_or_default(), max_samples);
+        }
+        let loc_position = loc_position.or(position);
+        match *loc.parsed() {
+            Location::Url(ref url) => {
+                if url.as_str().chars().count() > structs::MAX_URL_LENGTH {
+                    report.add(IssueKind::LocTooLong, loc_position, url.as_str(), max_samples);
+                }
+                if !locs.insert(url.to_string()) {
+                    report.add(IssueKind::DuplicateLoc, loc_position, url.as_str(), max_samples);
+                }
+            }
+            _ => report.add(IssueKind::InvalidLoc, loc_position, loc.raw().unwrap_or_default(), max_samples),
+        }
+        if let LastMod::Err(_) | LastMod::NonStandard(_) = *lastmod {
+            let raw = lastmod.raw().unwrap_or_default();
+            report.add(IssueKind::InvalidLastMod, lastmod_position.or(position), raw, max_samples);
+        }
+    }
+    let sitemap_namespace = reader.root_namespaces().iter().any(|(_, namespace)| {
+        namespace == structs::SITEMAP_NAMESPACE || namespace == structs::SITEMAP_NAMESPACE_084
+    });
+    match reader.document_kind() {
+        Some(DocumentKind::UrlSet) | Some(DocumentKind::SiteMapIndex) if !sitemap_namespace => {
+            report.add(IssueKind::MissingNamespace, None, "", max_samples);
+        }
+        _ => {}
+    }
+    report
+}
+
+/// Checks the fields of url entries only.
+fn check_url(report: &mut ValidationReport, entry: &UrlEntry, max_samples: usize) {
+    let position = |field| entry.field_position(field).or_else(|| entry.position());
+    match entry.priority {
+        Priority::None => {}
+        Priority::Value(value) if !value.is_nan() => {}
+        ref priority => {
+            report.add(IssueKind::InvalidPriority,
+                       position("priority"),
+                       priority.raw().unwrap_or_default(),
+                       max_samples)
+        }
+    }
+    if let ChangeFreq::Err(ref error) = entry.changefreq {
+        let issue = Issue {
+            position: position("changefreq"),
+            value: error.description.clone(),
+            suggestion: error.suggestion().map(str::to_string),
+        };
+        report.add_issue(IssueKind::InvalidChangeFreq, issue, max_samples);
+    }
+    for warning in entry.read_warnings(0) {
+        if let WarningKind::MissingImageLoc = warning.kind {
+            report.add(IssueKind::MissingImageLoc, warning.position, "image:image", max_samples);
+        }
+    }
+}
+
+/// Positions of the `&` not starting a reference, outside of comments and
+/// CDATA sections, with the text following them.
+fn unescaped_ampersands(document: &[u8]) -> Vec<(Position, String)> {
+    let mut ampersands = Vec::new();
+    let mut position = Position { line: 1, column: 1 };
+    let mut i = 0;
+    let mut skip_until: Option<&[u8]> = None;
+    while i < document.len() {
+        let rest = &document[i..];
+        match skip_until {
+            Some(end) if rest.starts_with(end) => skip_until = None,
+            Some(_) => {}
+            None if rest.starts_with(b"<!--") => skip_until = Some(b"-->"),
+            None if rest.starts_with(b"<![CDATA[") => skip_until = Some(b"]]>"),
+            None if rest[0] == b'&' && !is_reference(&rest[1..]) => {
+                let length = rest.iter()
+                    .position(|&byte| byte == b'<' || byte.is_ascii_whitespace())
+                    .unwrap_or(rest.len())
+                    .min(32);
+                ampersands.push((position, String::from_utf8_lossy(&rest[..length]).into_owned()));
+            }
+            None => {}
+        }
+        match document[i] {
+            b'\n' => {
+                position.line += 1;
+                position.column = 1;
+            }
+            // continuation bytes of UTF-8 characters
+            0x80..=0xbf => {}
+            _ => position.column += 1,
+        }
+        i += 1;
+    }
+    ampersands
+}
+
+/// Whether `text`, following a `&`, is the rest of a reference.
+fn is_reference(text: &[u8]) -> bool {
+    let end = match text.iter().position(|&byte| byte == b';') {
+        Some(end) => end,
+        None => return false,
+    };
+    let name = &text[..end];
+    match name {
+        [b'#', b'x', digits @ ..] if !digits.is_empty() => digits.iter().all(u8::is_ascii_hexdigit),
+        [b'#', digits @ ..] if !digits.is_empty() => digits.iter().all(u8::is_ascii_digit),
+        [first, rest @ ..] => {
+            (first.is_ascii_alphabetic() || *first == b'_' || *first == b':') &&
+            rest.iter().all(|&byte| byte.is_ascii_alphanumeric() || b"._:-".contains(&byte) || byte >= 0x80)
+        }
+        [] => false,
+    }
+}
+
+/// Options of the checks of `check_scope_with_options`.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct ScopeOptions {
+    /// Whether the urls must be in the directory of the sitemap, true by
+    /// default.
+    pub check_path: bool,
+}
+
+impl Default for ScopeOptions {
+    fn default() -> ScopeOptions {
+        ScopeOptions { check_path: true }
+    }
+}
+
+/// Rule broken by an entry.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum ScopeViolationKind {
+    /// Scheme other than the one of the sitemap.
+    Scheme,
+    /// Host or port other than the ones of the sitemap.
+    Host,
+    /// Path outside of the directory of the sitemap.
+    Path,
+}
+
+/// Entry outside of the scope of its sitemap.
+#[derive(Debug,Clone,PartialEq)]
+pub struct ScopeViolation {
+    /// Index of the entry, starting from 0.
+    pub index: usize,
+    /// Url of the entry.
+    pub url: Url,
+    /// Rule broken by the entry, the scheme then the host then the path.
+    pub kind: ScopeViolationKind,
+}
+
+impl fmt::Display for ScopeViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self.kind {
+            ScopeViolationKind::Scheme => "scheme other than the one of the sitemap",
+            ScopeViolationKind::Host => "host other than the one of the sitemap",
+            ScopeViolationKind::Path => "path outside of the directory of the sitemap",
+        };
+        write!(f, "Url {} {}: {}", self.index, self.url, message)
+    }
+}
+
+/// Checks the entries of the sitemap at `sitemap_url` with the default
+/// options, see `check_scope_with_options`.
+pub fn check_scope<'a, I>(sitemap_url: &Url, entries: I) -> Vec<ScopeViolation>
+    where I: Iterator<Item = &'a UrlEntry>
+{
+    check_scope_with_options(sitemap_url, entries, &ScopeOptions::default())
+}
+
+/// Checks the entries of the sitemap at `sitemap_url`, reporting at most
+/// one violation for each entry. Entries without a valid location are not
+/// reported.
+pub fn check_scope_with_options<'a, I>(sitemap_url: &Url,
+                                       entries: I,
+                                       options: &ScopeOptions)
+                                       -> Vec<ScopeViolation>
+    where I: Iterator<Item = &'a UrlEntry>
+{
+    let path = sitemap_url.path();
+    let directory = &path[..path.rfind('/').map_or(0, |slash| slash + 1)];
+    entries.enumerate()
+        .filter_map(|(index, entry)| {
+            let loc = entry.loc.parsed();
+            let url = match *loc {
+                Location::Url(ref url) => url,
+                _ => return None,
+            };
+            let kind = if loc.scheme() != Some(sitemap_url.scheme()) {
+                ScopeViolationKind::Scheme
+            } else if !loc.is_same_origin(sitemap_url) {
+                ScopeViolationKind::Host
+            } else if options.check_path && !url.path().starts_with(directory) {
+                ScopeViolationKind::Path
+            } else {
+                return None;
+            };
+            Some(ScopeViolation {
+                index,
+                url: url.clone(),
+                kind,
+            })
+        })
+        .collect()
+}
+
+/// Options of `audit_freshness`.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct FreshnessOptions {
+    /// Delay allowed after the interval of the change frequency before an
+    /// entry is stale, zero by default.
+    pub grace: Duration,
+    /// Delay allowed after the time of the audit before an entry is dated
+    /// in the future, for the skew of the clocks, zero by default.
+    pub clock_skew: Duration,
+    /// Maximum number of offenders kept, 10 by default.
+    pub max_offenders: usize,
+}
+
+impl Default for FreshnessOptions {
+    fn default() -> FreshnessOptions {
+        FreshnessOptions {
+            grace: Duration::from_secs(0),
+            clock_skew: Duration::from_secs(0),
+            max_offenders: 10,
+        }
+    }
+}
+
+/// Freshness of an entry, from its `lastmod` and its `changefreq`.
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum Freshness {
+    /// Modified within the interval of its change frequency, or without a
+    /// change frequency with an interval.
+    Consistent,
+    /// Not modified within the interval of its change frequency.
+    StaleForChangefreq {
+        /// Time since the end of the interval and of the grace delay.
+        overdue: Duration,
+    },
+    /// Modified after the time of the audit.
+    FutureDated {
+        /// Time from the audit to the modification.
+        ahead: Duration,
+    },
+    /// Without a valid `lastmod`.
+    MissingLastmod,
+}
+
+impl Freshness {
+    /// Delay of a stale or future dated entry.
+    fn delay(&self) -> Option<Duration> {
+        match *self {
+            Freshness::StaleForChangefreq { overdue } => Some(overdue),
+            Freshness::FutureDated { ahead } => Some(ahead),
+            Freshness::Consistent | Freshness::MissingLastmod => None,
+        }
+    }
+}
+
+impl fmt::Display for Freshness {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Freshness::Consistent => write!(f, "consistent"),
+            Freshness::StaleForChangefreq { overdue } => write!(f, "stale, overdue by {}", Delay(overdue)),
+            Freshness::FutureDated { ahead } => write!(f, "dated {} in the future", Delay(ahead)),
+            Freshness::MissingLastmod => write!(f, "missing lastmod"),
+        }
+    }
+}
+
+/// Delay written in days and hours, hours and minutes or seconds.
+struct Delay(Duration);
+
+impl fmt::Display for Delay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let seconds = self.0.as_secs();
+        if seconds >= 86400 {
+            write!(f, "{}d {}h", seconds / 86400, seconds % 86400 / 3600)
+        } else if seconds >= 3600 {
+            write!(f, "{}h {}m", seconds / 3600, seconds % 3600 / 60)
+        } else {
+            write!(f, "{}s", seconds)
+        }
+    }
+}
+
+/// Stale or future dated entry.
+#[derive(Debug,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct FreshnessOffender {
+    /// Index of the entry, starting from 0.
+    pub index: usize,
+    /// Location of the entry.
+    pub loc: Location,
+    /// Freshness of the entry, `StaleForChangefreq` or `FutureDated`.
+    pub freshness: Freshness,
+}
+
+/// Result of `audit_freshness`.
+#[derive(Debug,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct FreshnessReport {
+    /// Number of entries.
+    pub entries: usize,
+    /// Number of `Freshness::Consistent` entries.
+    pub consistent: usize,
+    /// Number of `Freshness::StaleForChangefreq` entries.
+    pub stale: usize,
+    /// Number of `Freshness::FutureDated` entries.
+    pub future_dated: usize,
+    /// Number of `Freshness::MissingLastmod` entries.
+    pub missing_lastmod: usize,
+    /// Stale and future dated entries with the longest delays, up to
+    /// `FreshnessOptions::max_offenders`, the longest first and in the
+    /// order of the entries for the same delay.
+    pub offenders: Vec<FreshnessOffender>,
+}
+
+impl FreshnessReport {
+    /// Whether no entry is stale or future dated.
+    pub fn is_consistent(&self) -> bool {
+        self.stale == 0 && self.future_dated == 0
+    }
+}
+
+impl fmt::Display for FreshnessReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,
+               "{} entries, {} consistent, {} stale, {} future dated, {} missing lastmod",
+               self.entries,
+               self.consistent,
+               self.stale,
+               self.future_dated,
+               self.missing_lastmod)?;
+        for offender in &self.offenders {
+            write!(f, "\n  {} {}: {}", offender.index, offender.loc.raw().unwrap_or_default(), offender.freshness)?;
+        }
+        Ok(())
+    }
+}
+
+/// Freshness of `entry` at the time `now`.
+///
+/// A date only, without a time zone, may have been modified from its
+/// start in the first time zone to its end in the last one: it is dated in
+/// the future when it starts after `now`, and is stale when it ends before
+/// the interval of the change frequency, see `ChangeFreq::as_duration`.
+/// An entry with `never` or no change frequency is never stale, an invalid
+/// `lastmod` is missing.
+pub fn entry_freshness(entry: &UrlEntry, now: &DateTime<FixedOffset>, options: &FreshnessOptions) -> Freshness {
+    let (earliest, latest) = match entry.lastmod {
+        LastMod::DateTime(time) => (time, time),
+        LastMod::W3c(ref time) => (time.time, time.time),
+        LastMod::NonStandard(ref time) => (time.time, time.time),
+        LastMod::Date(date) => {
+            let midnight = date.and_time(NaiveTime::MIN).and_utc().fixed_offset();
+            (freshness::start_of_day(date).unwrap_or(midnight), freshness::end_of_day(date).unwrap_or(midnight))
+        }
+        LastMod::None | LastMod::Err(_) => return Freshness::MissingLastmod,
+    };
+    if let Ok(ahead) = earliest.signed_duration_since(*now).to_std() {
+        if ahead > options.clock_skew {
+            return Freshness::FutureDated { ahead };
+        }
+    }
+    let interval = match entry.changefreq.as_duration() {
+        Some(interval) => interval.saturating_add(options.grace),
+        None => return Freshness::Consistent,
+    };
+    match now.signed_duration_since(latest).to_std() {
+        Ok(age) if age > interval => Freshness::StaleForChangefreq { overdue: age - interval },
+        _ => Freshness::Consistent,
+    }
+}
+
+/// Audits the freshness of `entries` at the time `now`, see
+/// `entry_freshness`.
+pub fn audit_freshness<'a, I>(entries: I, now: DateTime<FixedOffset>, options: &FreshnessOptions) -> FreshnessReport
+    where I: IntoIterator<Item = &'a UrlEntry>
+{
+    let mut report = FreshnessReport {
+        entries: 0,
+        consistent: 0,
+        stale: 0,
+        future_dated: 0,
+        missing_lastmod: 0,
+        offenders: Vec::new(),
+    };
+    for (index, entry) in entries.into_iter().enumerate() {
+        report.entries += 1;
+        let freshness = entry_freshness(entry, &now, options);
+        match freshness {
+            Freshness::Consistent => report.consistent += 1,
+            Freshness::StaleForChangefreq { .. } => report.stale += 1,
+            Freshness::FutureDated { .. } => report.future_dated += 1,
+            Freshness::MissingLastmod => report.missing_lastmod += 1,
+        }
+        let delay = match freshness.delay() {
+            Some(delay) => delay,
+            None => continue,
+        };
+        let offenders = &mut report.offenders;
+        let rank = offenders.partition_point(|offender| offender.freshness.delay() >= Some(delay));
+        if rank < options.max_offenders {
+            offenders.insert(rank,
+                             FreshnessOffender {
+                                 index,
+                                 loc: entry.loc.clone(),
+                                 freshness,
+                             });
+            offenders.truncate(options.max_offenders);
+        }
+    }
+    report
+}
@@ -1,88 +1,2052 @@
+//! Contains the writers of sitemaps.
+//!
+//! `write_urlset` and `write_index` write a complete document of the
+//! entries of an iterator, the streaming writers below are for the
+//! documents written as their entries are known, split or rotated.
+//!
+//! ```rust
+//! extern crate sitemap;
+//! use sitemap::structs::UrlEntry;
+//! use sitemap::writer::{self, WriterOptions};
+//! fn main() {
+//!     let urls = vec![UrlEntry::builder().loc("http://www.example.com/").build().unwrap()];
+//!     let summary = writer::write_urlset(Vec::new(), urls, &WriterOptions::default()).unwrap();
+//!     assert_eq!((summary.written, summary.skipped), (1, 0));
+//! }
+//! ```
+//!
+//! Each writer is a state of the document: `SiteMapWriter::start_urlset`
+//! and `SiteMapWriter::start_sitemapindex` consume the `SiteMapWriter` and
+//! return the writer of the entries, `end` consumes it and returns the
+//! stream, so that no entry can be written after the end and no document
+//! ended twice. The transitions checked while writing fail with an error:
+//! a second root element with `Error::DocumentEnded`, and the entries and
+//! the end after a failed write with `Error::State`.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate sitemap;
+//! use sitemap::structs::UrlEntry;
+//! use sitemap::writer::SiteMapWriter;
+//! fn main() {
+//!     let mut urlset = SiteMapWriter::new(Vec::new()).start_urlset().unwrap();
+//!     urlset.url(UrlEntry::builder().loc("http://www.example.com/").build().unwrap()).unwrap();
+//!     // ends the document and returns the writer, `rotate` writes the next one
+//!     let writer = urlset.end_urlset().unwrap();
+//!     assert!(writer.start_sitemapindex().is_err());
+//! }
+//! ```
+//!
+//! A url written after the end does not compile:
+//!
+//! ```compile_fail
+//! extern crate sitemap;
+//! use sitemap::structs::UrlEntry;
+//! use sitemap::writer::SiteMapWriter;
+//! fn main() {
+//!     let mut urlset = SiteMapWriter::new(Vec::new()).start_urlset().unwrap();
+//!     let out = urlset.end().unwrap();
+//!     urlset.url(UrlEntry::new()).unwrap();
+//! }
+//! ```
 
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io;
 use std::io::Write;
-use Error;
+use std::mem;
+use std::str;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+#[cfg(feature = "gzip")]
+use flate2::Compression;
+#[cfg(feature = "gzip")]
+use flate2::write::GzEncoder;
+use crate::{Error, LimitKind};
+use xml::common::XmlVersion;
 use xml::writer::{EventWriter, EmitterConfig, XmlEvent};
-use structs::{UrlEntry, Location, LastMod, ChangeFreq, Priority, SiteMapEntry};
+use xml::writer::events::StartElementBuilder;
+use crate::structs::{UrlEntry, Location, LastMod, ChangeFreq, Priority, SiteMapEntry, ImageEntry};
+use crate::structs::{VideoEntry, VideoDuration, News, NewsEntry, RawElement, Stylesheet};
+use crate::structs::{SITEMAP_NAMESPACE, IMAGE_NAMESPACE, VIDEO_NAMESPACE, NEWS_NAMESPACE, XHTML_NAMESPACE};
+use crate::structs::{MAX_IMAGES_PER_URL, MAX_URLS_PER_SITEMAP, MAX_SITEMAP_SIZE};
+use crate::structs::{self, CanonicalizeOptions, UrlEntryBuilder};
+use crate::util::{self, LocOptions};
+use chrono::{DateTime, FixedOffset, Utc};
+use url::{Host, Position, Url};
 
 pub struct SiteMapWriter<T: Write + Sized> {
-    writer: EventWriter<T>,
+    writer: EventWriter<CountingWriter<T>>,
+    options: WriterOptions,
+    /// Text of the element written, reused for all the elements.
+    buffer: String,
+    stylesheets: Vec<Stylesheet>,
+    warnings: Vec<Error>,
+    duplicates_skipped: usize,
+    ended: bool,
+    /// Whether a write failed, leaving an element incomplete.
+    failed: bool,
 }
 
+/// Options of `SiteMapWriter`.
+#[derive(Debug,Clone,Copy)]
+pub struct WriterOptions {
+    /// Format of the `<lastmod>` elements, `LastModFormat::AsIs` by default.
+    pub lastmod_format: LastModFormat,
+    /// Decimals of the `<priority>` elements, rounded half up, 1 by
+    /// default.
+    pub priority_decimals: u8,
+    /// Skips the `<priority>` elements of the default priority 0.5,
+    /// `false` by default.
+    pub omit_default_priority: bool,
+    /// Indentation of the elements, `Indent::Spaces(2)` by default.
+    pub indent: Indent,
+    /// Line separator of the indented elements, `Newline::Lf` by default.
+    pub newline: Newline,
+    /// XML declaration, `Declaration::Default` by default.
+    pub xml_declaration: Declaration,
+    /// Skips the entries without a valid location, otherwise writing them
+    /// fails with `Error::MissingLoc`. `false` by default.
+    pub skip_invalid: bool,
+    /// Policy of `UrlSetWriter` for the urls with the location of a
+    /// previous one, `DedupPolicy::Off` by default. The normalized
+    /// locations are kept in memory for the whole urlset.
+    pub dedup: DedupPolicy,
+    /// Canonicalization of the locations compared by `dedup`.
+    pub dedup_canonicalize: CanonicalizeOptions,
+    /// Comparison of the canonical locations by `dedup`.
+    pub dedup_loc: LocOptions,
+    /// Pins the formatting left to the platform or to the configuration
+    /// order, so that the same entries and options always give the same
+    /// bytes, `false` by default.
+    ///
+    /// The line separator is always `\n`, the `<urlset>` attributes are
+    /// sorted by name and, with `LastModFormat::AsIs`, times are written in
+    /// UTC, like `2024-01-01T08:30:00Z`, dates staying dates. In any mode
+    /// namespace declarations are sorted by prefix and priorities are
+    /// rounded from their shortest decimal representation, stylesheets stay
+    /// in the order they are added.
+    pub deterministic: bool,
+    /// Text written for the locations, `LocEncoding::PreserveRaw` by
+    /// default.
+    pub loc_encoding: LocEncoding,
+}
+
+impl Default for WriterOptions {
+    fn default() -> WriterOptions {
+        WriterOptions {
+            lastmod_format: LastModFormat::AsIs,
+            priority_decimals: 1,
+            omit_default_priority: false,
+            indent: Indent::default(),
+            newline: Newline::default(),
+            xml_declaration: Declaration::default(),
+            skip_invalid: false,
+            dedup: DedupPolicy::default(),
+            dedup_canonicalize: CanonicalizeOptions::default(),
+            dedup_loc: LocOptions::default(),
+            deterministic: false,
+            loc_encoding: LocEncoding::default(),
+        }
+    }
+}
+
+impl WriterOptions {
+    /// Line separator written, `\n` in deterministic mode.
+    fn line_separator(&self) -> &'static str {
+        if self.deterministic {
+            Newline::Lf.as_str()
+        } else {
+            self.newline.as_str()
+        }
+    }
+
+    /// Size of the closing tag of the root element `name`.
+    fn end_len(&self, name: &str) -> u64 {
+        let newline = match self.indent {
+            Indent::None => "",
+            _ => self.line_separator(),
+        };
+        (newline.len() + "</>".len() + name.len()) as u64
+    }
+}
+
+/// What `UrlSetWriter` does with a url with the location of a previous one.
+#[derive(Debug,Clone,Copy,PartialEq,Default)]
+pub enum DedupPolicy {
+    /// Locations are not checked.
+    #[default]
+    Off,
+    /// `url()` returns `Error::DuplicateLoc`.
+    Error,
+    /// The url is skipped.
+    SkipSilently,
+    /// The url is skipped and counted, see `UrlSetWriter::duplicates_skipped`.
+    SkipAndCount,
+}
+
+/// Indentation of the elements written.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum Indent {
+    /// Elements on a single line, after the XML declaration.
+    None,
+    /// Elements on their own line, indented by the number of spaces for
+    /// each level.
+    Spaces(u8),
+    /// Elements on their own line, indented by a tab for each level.
+    Tabs,
+}
+
+impl Default for Indent {
+    fn default() -> Indent {
+        Indent::Spaces(2)
+    }
+}
+
+/// XML declaration of the documents written.
+///
+/// The documents are always encoded in UTF-8, with another encoding the
+/// characters outside ASCII in the element content are written as
+/// character references, so that the document is valid in any encoding
+/// compatible with ASCII.
+#[derive(Debug,Clone,Copy,PartialEq,Default)]
+pub enum Declaration {
+    /// `<?xml version="1.0" encoding="UTF-8"?>`.
+    #[default]
+    Default,
+    /// No declaration, for documents concatenated to others.
+    None,
+    /// Declaration with the encoding and, if any, the standalone value.
+    Custom {
+        /// Name of the encoding, like `ISO-8859-1`.
+        encoding: &'static str,
+        /// Value of `standalone`, omitted when `None`.
+        standalone: Option<bool>,
+    },
+}
+
+impl Declaration {
+    /// Checks the encoding name, which must be a valid XML encoding name
+    /// compatible with ASCII.
+    fn check(&self) -> Result<(), Error> {
+        if let Declaration::Custom { encoding, .. } = *self {
+            let mut chars = encoding.chars();
+            let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic()) &&
+                        chars.all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-');
+            if !valid {
+                return Err(Error::invalid_value("encoding", encoding, "invalid encoding name"));
+            }
+            let name = encoding.to_ascii_uppercase();
+            if ["UTF-16", "UTF16", "UTF-32", "UTF32", "UCS-2", "UCS-4"].iter().any(|prefix| name.starts_with(prefix)) {
+                return Err(Error::invalid_value("encoding", encoding, "encoding not compatible with ASCII"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the characters outside ASCII are written as references.
+    fn ascii_only(&self) -> bool {
+        match *self {
+            Declaration::Custom { encoding, .. } => {
+                !encoding.eq_ignore_ascii_case("UTF-8") && !encoding.eq_ignore_ascii_case("UTF8")
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Line separator of the elements written.
+#[derive(Debug,Clone,Copy,PartialEq,Default)]
+pub enum Newline {
+    /// `\n`.
+    #[default]
+    Lf,
+    /// `\r\n`.
+    CrLf,
+}
+
+impl Newline {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            Newline::Lf => "\n",
+            Newline::CrLf => "\r\n",
+        }
+    }
+}
+
+/// Writes the text of `priority` clamped between 0 and 1, rounded half up
+/// to `decimals` digits of its shortest representation, in place of the
+/// content of `text`.
+fn priority_text(text: &mut String, priority: f32, decimals: u8) {
+    let decimals = usize::from(decimals);
+    text.clear();
+    let _ = write!(text, "{}", priority.clamp(0.0, 1.0));
+    let mut digits = mem::take(text).into_bytes();
+    let point = digits.iter().position(|&digit| digit == b'.');
+    let round_up = point.and_then(|point| digits.get(point + 1 + decimals)).is_some_and(|&digit| digit >= b'5');
+    if let Some(point) = point {
+        digits.remove(point);
+    }
+    digits.resize(point.unwrap_or(digits.len()) + decimals, b'0');
+    if round_up {
+        let mut carry = true;
+        for digit in digits.iter_mut().rev() {
+            if *digit == b'9' {
+                *digit = b'0';
+            } else {
+                *digit += 1;
+                carry = false;
+                break;
+            }
+        }
+        if carry {
+            digits.insert(0, b'1');
+        }
+    }
+    if decimals > 0 {
+        digits.insert(digits.len() - decimals, b'.');
+    }
+    *text = String::from_utf8(digits).expect("ASCII digits");
+}
+
+/// Format of the `<lastmod>` elements written, times are converted to UTC
+/// before dropping their time.
+#[derive(Debug,Clone,Copy,Default)]
+pub enum LastModFormat {
+    /// Precision and offset of the value.
+    #[default]
+    AsIs,
+    /// Date in UTC, like `2023-12-31` for `2024-01-01T01:00:00+02:00`.
+    DateOnly,
+    /// Time in UTC in whole seconds, midnight for a date.
+    SecondsUtc,
+    /// Text returned by the function, called for valid values only.
+    Custom(fn(&LastMod) -> String),
+}
+
+impl LastModFormat {
+    /// Appends the text of `lastmod` in this format to `text`, returning
+    /// whether there is a valid value.
+    fn write(&self, lastmod: &LastMod, text: &mut String) -> bool {
+        let time = match lastmod.get_time() {
+            Some(time) => time,
+            None => return false,
+        };
+        match *self {
+            LastModFormat::AsIs => return lastmod.write_w3c(text),
+            LastModFormat::DateOnly => {
+                let date = match *lastmod {
+                    LastMod::Date(date) => date,
+                    _ => time.with_timezone(&Utc).date_naive(),
+                };
+                structs::write_date(text, date);
+            }
+            LastModFormat::SecondsUtc => utc_rfc3339(text, time, false, false),
+            LastModFormat::Custom(format) => text.push_str(&format(lastmod)),
+        }
+        true
+    }
+}
+
+/// Text of the locations written, of `<loc>`, `image:loc`, the video
+/// locations and the `href` of the alternates.
+///
+/// A `Url` is normalized when parsed: the host in punycode, like
+/// `xn--mnchen-3ya.example`, and the path and query percent-encoded, like
+/// `/blog/%C3%BCrlaub`. The original text is only kept by a `Raw`
+/// location, read with `ReaderOptions::parse_urls` off.
+#[derive(Debug,Clone,Copy,PartialEq,Default)]
+pub enum LocEncoding {
+    /// Normalized text of the url, a `Raw` location being parsed first; a
+    /// `Raw` location that does not parse is not written.
+    AsParsed,
+    /// Normalized text of a `Url`, original text of a `Raw` location.
+    #[default]
+    PreserveRaw,
+    /// Normalized text of the url decoded for people, like
+    /// `http://münchen.example/blog/ürlaub`: the host in unicode and the
+    /// percent-encoded UTF-8 characters other than ASCII decoded, the
+    /// ASCII ones like `%2F` and `%26` staying encoded. A `Raw` location is
+    /// parsed first.
+    DisplayUnicode,
+}
+
+impl LocEncoding {
+    /// Text written for `loc`, `None` when it has no valid value.
+    pub(crate) fn text<'a>(&self, loc: &'a Location) -> Option<Cow<'a, str>> {
+        match *self {
+            LocEncoding::AsParsed => match *loc {
+                Location::Raw(ref raw) => Url::parse(raw).ok().map(|url| Cow::Owned(url.into())),
+                _ => loc.url().map(|url| Cow::Borrowed(url.as_str())),
+            },
+            LocEncoding::PreserveRaw => loc.as_str().map(Cow::Borrowed),
+            LocEncoding::DisplayUnicode => loc.parsed().url().map(|url| Cow::Owned(display_unicode(url))),
+        }
+    }
+}
+
+/// Text of `url` with the host in unicode and the percent-encoded UTF-8
+/// characters other than ASCII and control characters decoded.
+fn display_unicode(url: &Url) -> String {
+    let mut text = String::with_capacity(url.as_str().len());
+    text.push_str(&url[..Position::BeforeHost]);
+    match url.host() {
+        Some(Host::Domain(domain)) => text.push_str(&url::quirks::domain_to_unicode(domain)),
+        _ => text.push_str(&url[Position::BeforeHost..Position::AfterHost]),
+    }
+    // the serialization of the url is ASCII
+    let rest = &url[Position::AfterHost..];
+    let bytes = rest.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let mut run = Vec::new();
+        while let Some(byte) = bytes.get(i + 3 * run.len()..i + 3 * run.len() + 3).and_then(escaped_byte) {
+            if byte < 0x80 {
+                break;
+            }
+            run.push(byte);
+        }
+        // the valid UTF-8 prefix of the run is decoded, the rest kept
+        let valid = match str::from_utf8(&run) {
+            Ok(valid) => valid,
+            Err(error) => str::from_utf8(&run[..error.valid_up_to()]).unwrap_or_default(),
+        };
+        if valid.is_empty() {
+            let len = if run.is_empty() { 1 } else { 3 };
+            text.push_str(&rest[i..i + len]);
+            i += len;
+            continue;
+        }
+        for c in valid.chars() {
+            if c.is_control() {
+                let mut buffer = [0; 4];
+                for byte in c.encode_utf8(&mut buffer).bytes() {
+                    let _ = write!(text, "%{:02X}", byte);
+                }
+            } else {
+                text.push(c);
+            }
+        }
+        i += 3 * valid.len();
+    }
+    text
+}
+
+/// Byte of the percent-encoded `escape`, like `%C3`.
+fn escaped_byte(escape: &[u8]) -> Option<u8> {
+    match *escape {
+        [b'%', high, low] => {
+            let digit = |digit: u8| (digit as char).to_digit(16);
+            Some((digit(high)? * 16 + digit(low)?) as u8)
+        }
+        _ => None,
+    }
+}
+
+/// Appends `time` in UTC to `text` like `DateTime::to_rfc3339_opts`.
+fn utc_rfc3339(text: &mut String, time: DateTime<FixedOffset>, fraction: bool, use_z: bool) {
+    structs::write_rfc3339(text, &time.with_timezone(&Utc).fixed_offset(), fraction, use_z);
+}
+
+/// Stream counting the bytes written through it.
+struct CountingWriter<T: Write> {
+    inner: T,
+    count: u64,
+}
+
+impl<T: Write> Write for CountingWriter<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.count += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// What `UrlSetWriter::urls` does with the urls failing.
+#[derive(Debug,Clone,Copy,PartialEq,Default)]
+pub enum ErrorPolicy {
+    /// The first url failing ends the writing with its error.
+    #[default]
+    FailFast,
+    /// The urls failing are skipped and their errors collected.
+    Collect,
+}
+
+/// What `UrlSetWriter` does when a sitemap goes over the protocol limits.
+#[derive(Debug,Clone,Copy,PartialEq,Default)]
+pub enum LimitPolicy {
+    /// Limits are not checked.
+    #[default]
+    Ignore,
+    /// The limit is recorded in the warnings, see `SiteMapWriter::warnings`.
+    Warn,
+    /// `url()` returns `Error::LimitExceeded`.
+    Error,
+}
+
+/// Applies `policy` to a limit exceeded, warnings are recorded once per kind.
+fn limit_exceeded(policy: LimitPolicy,
+                  warnings: &mut Vec<Error>,
+                  kind: LimitKind,
+                  limit: u64)
+                  -> Result<(), Error> {
+    match policy {
+        LimitPolicy::Ignore => Ok(()),
+        LimitPolicy::Error => Err(Error::LimitExceeded { kind, limit }),
+        LimitPolicy::Warn => {
+            let warned = warnings.iter().any(|warning| match *warning {
+                Error::LimitExceeded { kind: ref warned, .. } => *warned == kind,
+                _ => false,
+            });
+            if !warned {
+                warnings.push(Error::LimitExceeded { kind, limit });
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Writer of the `<urlset>` element.
+///
+/// The opening tag is written together with the first url, so extension
+/// namespaces are declared on `<urlset>` only when the first url uses them,
+/// otherwise they are declared on the extension element itself.
+///
+/// The sitemap namespace is always the default namespace of `<urlset>`, the
+/// namespaces and attributes added with `namespace` and `attribute` are
+/// written after it.
 pub struct UrlSetWriter<T: Write + Sized> {
     sitemap: SiteMapWriter<T>,
+    started: bool,
+    namespaces: Vec<(String, String)>,
+    attributes: Vec<(String, String)>,
+    root_namespaces: Vec<(String, String)>,
+    limit_policy: LimitPolicy,
+    max_urls: usize,
+    max_bytes: u64,
+    urls: usize,
+    skipped: usize,
+    seen: HashSet<String>,
+    duplicates: usize,
+    /// Urls passed to the writer that failed without being written.
+    rejected: usize,
+    duplicates_skipped: usize,
+    warnings: Vec<Error>,
+    base_url: Option<Url>,
+}
+
+/// Extension namespaces used by the url, as `(prefix, uri)` pairs.
+fn extension_namespaces(url: &UrlEntry) -> Vec<(&str, &str)> {
+    let mut namespaces = Vec::new();
+    if !url.images.is_empty() {
+        namespaces.push(("image", IMAGE_NAMESPACE));
+    }
+    if !url.videos.is_empty() {
+        namespaces.push(("video", VIDEO_NAMESPACE));
+    }
+    if let News::Entry(_) = url.news {
+        namespaces.push(("news", NEWS_NAMESPACE));
+    }
+    if !url.alternates.is_empty() {
+        namespaces.push(("xhtml", XHTML_NAMESPACE));
+    }
+    for extension in &url.extensions {
+        for (prefix, uri) in &extension.namespaces {
+            if !prefix.is_empty() && !namespaces.iter().any(|(declared, _)| declared == prefix) {
+                namespaces.push((prefix.as_str(), uri.as_str()));
+            }
+        }
+    }
+    namespaces
+}
+
+/// Whether an entry with `loc` is written, counting it in `skipped` when
+/// it is skipped following the options. `written` is the number of entries
+/// written so far.
+fn check_loc(loc: &Location,
+             options: &WriterOptions,
+             written: usize,
+             skipped: &mut usize)
+             -> Result<bool, Error> {
+    if options.loc_encoding.text(loc).is_some() {
+        return Ok(true);
+    }
+    if options.skip_invalid {
+        *skipped += 1;
+        return Ok(false);
+    }
+    Err(Error::MissingLoc { index: written + *skipped })
+}
+
+/// Fails with `Error::InvalidEntry` on a url with more than
+/// `MAX_IMAGES_PER_URL` images, `index` being its index among the urls
+/// written so far.
+fn check_url(url: &UrlEntry, index: usize) -> Result<(), Error> {
+    if url.images.len() > MAX_IMAGES_PER_URL {
+        return Err(Error::InvalidEntry {
+            index,
+            field: "image",
+            reason: format!("{} images, a url can contain at most {}", url.images.len(), MAX_IMAGES_PER_URL),
+        });
+    }
+    Ok(())
+}
+
+/// Whether `name` is a valid XML name without colon.
+fn is_ncname(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_') &&
+    chars.all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
 }
 
 impl<T: Write + Sized> UrlSetWriter<T> {
+    /// Writes the opening tag, composed of the sitemap namespace, the custom
+    /// namespaces, the extension namespaces of the first url and the custom
+    /// attributes.
+    fn start(&mut self, url: Option<&UrlEntry>) -> Result<(), Error> {
+        if self.started {
+            return Ok(());
+        }
+        self.sitemap.write_prolog()?;
+        let mut namespaces = self.namespaces.clone();
+        if let Some(url) = url {
+            for (prefix, uri) in extension_namespaces(url) {
+                if !namespaces.iter().any(|(declared, _)| declared == prefix) {
+                    namespaces.push((prefix.to_string(), uri.to_string()));
+                }
+            }
+        }
+        let mut start = XmlEvent::start_element("urlset").default_ns(SITEMAP_NAMESPACE);
+        for (prefix, uri) in &namespaces {
+            start = start.ns(prefix.as_str(), uri.as_str());
+        }
+        let mut attributes: Vec<&(String, String)> = self.attributes.iter().collect();
+        if self.sitemap.options.deterministic {
+            attributes.sort();
+        }
+        for (name, value) in attributes {
+            start = start.attr(name.as_str(), value);
+        }
+        self.sitemap.writer.write(start)?;
+        self.root_namespaces = namespaces;
+        self.started = true;
+        Ok(())
+    }
+
+    /// Declares a namespace on `<urlset>`, before the first url.
+    ///
+    /// Fails with `Error::State` after the first url, and on an invalid or
+    /// reserved prefix, the sitemap namespace is always the default one. The
+    /// extension namespaces of the first url are declared too, unless their
+    /// prefix is already declared.
+    pub fn namespace(mut self, prefix: &str, uri: &str) -> Result<UrlSetWriter<T>, Error> {
+        if self.started {
+            return Err(Error::State("namespace declared after the first url"));
+        }
+        if !is_ncname(prefix) || prefix.to_ascii_lowercase().starts_with("xml") {
+            return Err(Error::invalid_value("namespace", prefix, "invalid namespace prefix"));
+        }
+        match self.namespaces.iter_mut().find(|(declared, _)| declared == prefix) {
+            Some(namespace) => namespace.1 = uri.to_string(),
+            None => self.namespaces.push((prefix.to_string(), uri.to_string())),
+        }
+        Ok(self)
+    }
+
+    /// Adds an attribute to `<urlset>`, before the first url, like
+    /// `xsi:schemaLocation`. Attributes are written in the order they are
+    /// added.
+    ///
+    /// Fails with `Error::State` after the first url, and on an invalid
+    /// name, the prefix of the name must be declared with `namespace`.
+    pub fn attribute(mut self, name: &str, value: &str) -> Result<UrlSetWriter<T>, Error> {
+        if self.started {
+            return Err(Error::State("attribute added after the first url"));
+        }
+        let valid = match name.split_once(':') {
+            Some((prefix, local)) => {
+                is_ncname(local) && self.namespaces.iter().any(|(declared, _)| declared == prefix)
+            }
+            None => is_ncname(name) && !name.eq_ignore_ascii_case("xmlns"),
+        };
+        if !valid {
+            return Err(Error::invalid_value("attribute", name, "invalid attribute name"));
+        }
+        self.attributes.retain(|(added, _)| added != name);
+        self.attributes.push((name.to_string(), value.to_string()));
+        Ok(self)
+    }
+
+    /// Start of an extension element, declaring its namespace when the
+    /// root element does not.
+    fn extension_start<'a>(&self,
+                           name: &'a str,
+                           prefix: &'static str,
+                           uri: &'static str)
+                           -> StartElementBuilder<'a> {
+        let start = XmlEvent::start_element(name);
+        let declared = self.root_namespaces.iter().any(|(declared, declared_uri)| {
+            declared == prefix && declared_uri == uri
+        });
+        if declared {
+            start
+        } else {
+            start.ns(prefix, uri)
+        }
+    }
+
+    /// Writes an element of an unsupported extension as it is, declaring
+    /// the namespaces the root element does not.
+    fn extension(&mut self, extension: &RawElement) -> Result<(), Error> {
+        let mut start = XmlEvent::start_element(extension.name.as_str());
+        for (prefix, uri) in &extension.namespaces {
+            let declared = self.root_namespaces.iter().any(|(declared, declared_uri)| {
+                declared == prefix && declared_uri == uri
+            });
+            if prefix.is_empty() && uri != SITEMAP_NAMESPACE {
+                start = start.default_ns(uri.as_str());
+            } else if !prefix.is_empty() && !declared {
+                start = start.ns(prefix.as_str(), uri.as_str());
+            }
+        }
+        for (name, value) in &extension.attributes {
+            start = start.attr(name.as_str(), value);
+        }
+        self.sitemap.writer.write(start)?;
+        if !extension.inner.is_empty() {
+            self.sitemap.writer.write(XmlEvent::RawCharacters(&extension.inner))?;
+        }
+        self.sitemap.writer.write(XmlEvent::end_element().name(extension.name.as_str()))?;
+        Ok(())
+    }
+
+    fn image(&mut self, image: &ImageEntry) -> Result<(), Error> {
+        let encoding = self.sitemap.options.loc_encoding;
+        let loc = match encoding.text(&image.loc) {
+            Some(loc) => loc,
+            None => return Ok(()),
+        };
+        let start = self.extension_start("image:image", "image", IMAGE_NAMESPACE);
+        self.sitemap.writer.write(start)?;
+        self.sitemap.content_element("image:loc", &loc)?;
+        if let Some(ref caption) = image.caption {
+            self.sitemap.content_element("image:caption", caption)?;
+        }
+        if let Some(ref geo_location) = image.geo_location {
+            self.sitemap.content_element("image:geo_location", geo_location)?;
+        }
+        if let Some(ref title) = image.title {
+            self.sitemap.content_element("image:title", title)?;
+        }
+        if let Some(license) = encoding.text(&image.license) {
+            self.sitemap.content_element("image:license", &license)?;
+        }
+        self.sitemap.writer.write(XmlEvent::end_element().name("image:image"))?;
+        Ok(())
+    }
+
+    fn video(&mut self, video: &VideoEntry) -> Result<(), Error> {
+        let encoding = self.sitemap.options.loc_encoding;
+        let start = self.extension_start("video:video", "video", VIDEO_NAMESPACE);
+        self.sitemap.writer.write(start)?;
+        if let Some(loc) = encoding.text(&video.thumbnail_loc) {
+            self.sitemap.content_element("video:thumbnail_loc", &loc)?;
+        }
+        if let Some(ref title) = video.title {
+            self.sitemap.content_element("video:title", title)?;
+        }
+        if let Some(ref description) = video.description {
+            self.sitemap.content_element("video:description", description)?;
+        }
+        if let Some(loc) = encoding.text(&video.content_loc) {
+            self.sitemap.content_element("video:content_loc", &loc)?;
+        }
+        if let Some(loc) = encoding.text(&video.player_loc) {
+            self.sitemap.content_element("video:player_loc", &loc)?;
+        }
+        if let VideoDuration::Value(duration) = video.duration {
+            self.sitemap
+                .write_text_element("video:duration", |text| write!(text, "{}", duration.as_secs()).is_ok())?;
+        }
+        self.sitemap
+            .write_text_element("video:expiration_date", |text| video.expiration_date.write_w3c(text))?;
+        self.sitemap
+            .write_text_element("video:publication_date", |text| video.publication_date.write_w3c(text))?;
+        for tag in &video.tags {
+            self.sitemap.content_element("video:tag", tag)?;
+        }
+        if let Some(live) = video.live {
+            self.sitemap.content_element("video:live", if live { "yes" } else { "no" })?;
+        }
+        self.sitemap.writer.write(XmlEvent::end_element().name("video:video"))?;
+        Ok(())
+    }
+
+    fn news(&mut self, news: &NewsEntry) -> Result<(), Error> {
+        let start = self.extension_start("news:news", "news", NEWS_NAMESPACE);
+        self.sitemap.writer.write(start)?;
+        self.sitemap.writer.write(XmlEvent::start_element("news:publication"))?;
+        self.sitemap.content_element("news:name", &news.publication.name)?;
+        self.sitemap.content_element("news:language", &news.publication.language)?;
+        self.sitemap.writer.write(XmlEvent::end_element().name("news:publication"))?;
+        self.sitemap
+            .write_text_element("news:publication_date", |text| news.publication_date.write_w3c(text))?;
+        self.sitemap.content_element("news:title", &news.title)?;
+        self.sitemap.writer.write(XmlEvent::end_element().name("news:news"))?;
+        Ok(())
+    }
+
+    /// Policy applied when the sitemap goes over the limits, by default
+    /// limits are ignored.
+    pub fn limit_policy(mut self, limit_policy: LimitPolicy) -> UrlSetWriter<T> {
+        self.limit_policy = limit_policy;
+        self
+    }
+
+    /// Maximum number of urls, `MAX_URLS_PER_SITEMAP` by default.
+    pub fn max_urls(mut self, max_urls: usize) -> UrlSetWriter<T> {
+        self.max_urls = max_urls;
+        self
+    }
+
+    /// Maximum size in bytes including the closing tag, `MAX_SITEMAP_SIZE`
+    /// by default.
+    pub fn max_bytes(mut self, max_bytes: u64) -> UrlSetWriter<T> {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Base url of the relative locations, resolved by `url` with
+    /// `Location::resolve`. Without a base url they are invalid locations.
+    pub fn base_url(mut self, base_url: Url) -> UrlSetWriter<T> {
+        self.base_url = Some(base_url);
+        self
+    }
+
+    /// Limits exceeded so far with `LimitPolicy::Warn`.
+    pub fn warnings(&self) -> &[Error] {
+        &self.warnings
+    }
+
+    fn limit_exceeded(&mut self, kind: LimitKind, limit: u64) -> Result<(), Error> {
+        limit_exceeded(self.limit_policy, &mut self.warnings, kind, limit)
+    }
+
+    /// Number of entries without a valid location skipped with
+    /// `WriterOptions::skip_invalid`.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Number of duplicate urls skipped with `DedupPolicy::SkipAndCount`,
+    /// also available from the writer returned by `end_urlset`.
+    pub fn duplicates_skipped(&self) -> usize {
+        self.duplicates_skipped
+    }
+
+    /// Writes a url, images and alternates without a valid location and
+    /// invalid news elements are skipped.
+    ///
+    /// A url without a valid location fails with `Error::MissingLoc`, or is
+    /// skipped with `WriterOptions::skip_invalid`. A url with the location
+    /// of a previous one is handled following `WriterOptions::dedup`. A url
+    /// with too many images fails with `Error::InvalidEntry`. A
+    /// `Location::Raw` is written as it is, escaped.
+    ///
+    /// With a `base_url`, a relative location is resolved first; one not
+    /// resolved fails with `Error::Resolve`, or is skipped with
+    /// `WriterOptions::skip_invalid`.
+    ///
+    /// The url count is checked before writing the url, the size after,
+    /// so with `LimitPolicy::Error` the url going over the size is written.
+    ///
+    /// Once writing to the stream failed, leaving a url incomplete, the
+    /// next urls and `end` fail with `Error::State`.
     pub fn url(&mut self, url: UrlEntry) -> Result<(), Error> {
-        self.sitemap.writer.write(XmlEvent::start_element("url"))?;
-        if let Location::Url(loc) = url.loc {
-            self.sitemap.write_content_element("loc", loc.as_str())?;
+        if self.sitemap.failed {
+            return Err(Error::State("url() called after a failed write"));
+        }
+        let urls = self.urls;
+        let result = self.write_entry(url);
+        if result.is_err() && self.urls == urls && !self.sitemap.failed {
+            self.rejected += 1;
+        }
+        result
+    }
+
+    /// Builds a url with `build`, from an empty builder, and writes it, see
+    /// `url`.
+    ///
+    /// A url with invalid fields fails with `Error::InvalidEntry`, naming
+    /// the first invalid field, with the index of the url among the ones
+    /// passed to the writer.
+    ///
+    /// ```rust
+    /// extern crate sitemap;
+    /// use sitemap::writer::SiteMapWriter;
+    /// fn main() {
+    ///     let mut urlset = SiteMapWriter::new(Vec::new()).start_urlset().unwrap();
+    ///     urlset.url_with(|url| url.loc("http://www.example.com/").priority(0.5)).unwrap();
+    ///     assert!(urlset.url_with(|url| url.loc("http://www.example.com/a").priority(2.0)).is_err());
+    ///     let written = String::from_utf8(urlset.end().unwrap()).unwrap();
+    ///     assert!(written.contains("<priority>0.5</priority>"));
+    /// }
+    /// ```
+    pub fn url_with<F>(&mut self, build: F) -> Result<(), Error>
+        where F: FnOnce(UrlEntryBuilder) -> UrlEntryBuilder
+    {
+        if self.sitemap.failed {
+            return Err(Error::State("url() called after a failed write"));
+        }
+        match build(UrlEntry::builder()).build() {
+            Ok(url) => self.url(url),
+            Err(error) => {
+                let index = self.index();
+                self.rejected += 1;
+                let field = error.errors().first().map_or("entry", |error| error.field());
+                let reason = error.errors().iter().map(ToString::to_string).collect::<Vec<_>>().join("; ");
+                Err(Error::InvalidEntry { index, field, reason })
+            }
+        }
+    }
+
+    /// Writes the urls in their order, see `url`.
+    ///
+    /// With `ErrorPolicy::FailFast` the first url failing ends the writing
+    /// with its error, with `ErrorPolicy::Collect` the urls failing are not
+    /// written and their errors are returned. Once writing to the stream
+    /// failed the writing ends with the error in both cases.
+    pub fn urls<I>(&mut self, urls: I, policy: ErrorPolicy) -> Result<Vec<Error>, Error>
+        where I: IntoIterator<Item = UrlEntry>
+    {
+        let mut errors = Vec::new();
+        for url in urls {
+            match self.url(url) {
+                Ok(()) => {}
+                Err(error) if policy == ErrorPolicy::Collect && !self.sitemap.failed => errors.push(error),
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(errors)
+    }
+
+    /// Index of the next url among the ones passed to the writer.
+    fn index(&self) -> usize {
+        self.urls + self.duplicates + self.skipped + self.rejected
+    }
+
+    fn write_entry(&mut self, mut url: UrlEntry) -> Result<(), Error> {
+        let written = self.urls + self.duplicates + self.rejected;
+        let resolved = self.base_url.as_ref().and_then(|base_url| url.loc.resolve_relative(base_url));
+        match resolved {
+            Some(Ok(loc)) => url.loc = Location::Url(loc),
+            Some(Err(_)) if self.sitemap.options.skip_invalid => {
+                self.skipped += 1;
+                return Ok(());
+            }
+            Some(Err(error)) => {
+                return Err(Error::Resolve {
+                    reference: url.loc.raw().unwrap_or_default(),
+                    error,
+                })
+            }
+            None => {}
+        }
+        if !check_loc(&url.loc, &self.sitemap.options, written, &mut self.skipped)? {
+            return Ok(());
+        }
+        check_url(&url, written + self.skipped)?;
+        let options = self.sitemap.options;
+        let key = match options.dedup {
+            DedupPolicy::Off => None,
+            _ => Some(util::loc_key(&url.loc.canonicalize(&options.dedup_canonicalize), &options.dedup_loc)),
+        };
+        if let Some(ref key) = key {
+            if self.seen.contains(key) {
+                let index = written + self.skipped;
+                match options.dedup {
+                    DedupPolicy::Error => {
+                        return Err(Error::DuplicateLoc {
+                            index,
+                            loc: url.loc.raw().unwrap_or_default(),
+                        })
+                    }
+                    DedupPolicy::SkipAndCount => self.duplicates_skipped += 1,
+                    _ => {}
+                }
+                self.duplicates += 1;
+                return Ok(());
+            }
+        }
+        if self.urls >= self.max_urls {
+            let limit = self.max_urls as u64;
+            self.limit_exceeded(LimitKind::UrlCount, limit)?;
+        }
+        if let Err(error) = self.write_url(&url) {
+            self.sitemap.failed = true;
+            return Err(error);
         }
-        if let LastMod::DateTime(lastmod) = url.lastmod {
-            self.sitemap.write_content_element("lastmod", lastmod.to_rfc3339().as_str())?;
+        self.urls += 1;
+        if let Some(key) = key {
+            self.seen.insert(key);
+        }
+        if self.bytes_written() + self.sitemap.options.end_len("urlset") > self.max_bytes {
+            let limit = self.max_bytes;
+            self.limit_exceeded(LimitKind::Size, limit)?;
+        }
+        Ok(())
+    }
+
+    fn write_url(&mut self, url: &UrlEntry) -> Result<(), Error> {
+        self.start(Some(url))?;
+        self.write_url_element(url, XmlEvent::start_element("url"))
+    }
+
+    /// Writes the `<url>` element of `url`, opened by `start`.
+    fn write_url_element(&mut self, url: &UrlEntry, start: StartElementBuilder<'_>) -> Result<(), Error> {
+        let encoding = self.sitemap.options.loc_encoding;
+        self.sitemap.writer.write(start)?;
+        if let Some(loc) = encoding.text(&url.loc) {
+            self.sitemap.content_element("loc", &loc)?;
         }
+        self.sitemap.write_lastmod(&url.lastmod)?;
         match url.changefreq {
             ChangeFreq::Err(_) => {}
             ChangeFreq::None => {}
             _ => {
-                self.sitemap.write_content_element("changefreq", url.changefreq.as_str())?;
+                self.sitemap.content_element("changefreq", url.changefreq.as_str())?;
             }
         }
-        if let Priority::Value(priority) = url.priority {
-            self.sitemap.write_content_element("lastmod", priority.to_string().as_str())?;
+        match url.priority {
+            Priority::Value(priority) if !priority.is_nan() => {
+                let options = self.sitemap.options;
+                self.sitemap.write_text_element("priority", |text| {
+                    priority_text(text, priority, options.priority_decimals);
+                    !(options.omit_default_priority && text.parse() == Ok(0.5))
+                })?;
+            }
+            _ => {}
+        }
+        for alternate in &url.alternates {
+            if let Some(href) = encoding.text(&alternate.href) {
+                let link = self.extension_start("xhtml:link", "xhtml", XHTML_NAMESPACE)
+                    .attr("rel", "alternate")
+                    .attr("hreflang", &alternate.hreflang)
+                    .attr("href", &href);
+                self.sitemap.writer.write(link)?;
+                self.sitemap.writer.write(XmlEvent::end_element().name("xhtml:link"))?;
+            }
+        }
+        for image in &url.images {
+            self.image(image)?;
+        }
+        for video in &url.videos {
+            self.video(video)?;
+        }
+        if let News::Entry(ref news) = url.news {
+            self.news(news)?;
+        }
+        for extension in &url.extensions {
+            self.extension(extension)?;
         }
         self.sitemap.writer.write(XmlEvent::end_element().name("url"))?;
         Ok(())
     }
 
-    pub fn end(mut self) -> Result<SiteMapWriter<T>, Error> {
+    /// Writes a comment, before `<urlset>` until the first url, after the
+    /// last url written otherwise. `--`, not allowed in a comment, is
+    /// written `- -`.
+    pub fn comment(&mut self, text: &str) -> Result<(), Error> {
+        if self.sitemap.failed {
+            return Err(Error::State("comment() called after a failed write"));
+        }
+        if !self.started {
+            self.sitemap.write_prolog()?;
+        }
+        self.sitemap.write_comment(text)
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn get_mut(&mut self) -> &mut T {
+        self.sitemap.get_mut()
+    }
+
+    /// Bytes written so far, before compression for gzip streams.
+    pub fn bytes_written(&self) -> u64 {
+        self.sitemap.bytes_written()
+    }
+
+    /// Closes the urlset and returns the underlying stream.
+    pub fn end(self) -> Result<T, Error> {
+        Ok(self.end_urlset()?.into_inner())
+    }
+
+    /// Closes the urlset and returns the writer, to write the next document
+    /// with `SiteMapWriter::rotate`. The limit warnings and the number of
+    /// duplicates skipped are moved to the returned writer.
+    pub fn end_urlset(mut self) -> Result<SiteMapWriter<T>, Error> {
+        if self.sitemap.failed {
+            return Err(Error::State("end() called after a failed write"));
+        }
+        self.start(None)?;
         self.sitemap.writer.write(XmlEvent::end_element().name("urlset"))?;
+        self.sitemap.ended = true;
+        self.sitemap.warnings.append(&mut self.warnings);
+        self.sitemap.duplicates_skipped = self.duplicates_skipped;
         Ok(self.sitemap)
     }
 }
 
 pub struct SiteMapIndexWriter<T: Write + Sized> {
     sitemap: SiteMapWriter<T>,
+    sitemaps: usize,
+    skipped: usize,
 }
 
 impl<T: Write + Sized> SiteMapIndexWriter<T> {
+    /// Starts a sitemapindex and writes a sitemap for each of the `files`,
+    /// with their modification time as lastmod.
+    ///
+    /// The location of a file is its name, percent-encoded, appended to the
+    /// path of `base_url` with or without a trailing slash, discarding the
+    /// query and the fragment of `base_url`.
+    pub fn from_files<I>(writer: SiteMapWriter<T>,
+                         base_url: &Url,
+                         files: I)
+                         -> Result<SiteMapIndexWriter<T>, Error>
+        where I: IntoIterator<Item = (PathBuf, Option<SystemTime>)>
+    {
+        if base_url.cannot_be_a_base() {
+            return Err(Error::invalid_value("loc", base_url.as_str(), "base url without a path"));
+        }
+        let mut index_writer = writer.start_sitemapindex()?;
+        for (path, modified) in files {
+            let name = match path.file_name().and_then(|name| name.to_str()) {
+                Some(name) => name,
+                None => {
+                    let path = path.to_string_lossy();
+                    return Err(Error::invalid_value("loc", path, "file name missing or not valid unicode"));
+                }
+            };
+            let mut loc = base_url.clone();
+            loc.set_query(None);
+            loc.set_fragment(None);
+            if let Ok(mut segments) = loc.path_segments_mut() {
+                segments.pop_if_empty().push(name);
+            }
+            index_writer.sitemap(SiteMapEntry {
+                    loc: Location::Url(loc),
+                    lastmod: modified.map_or(LastMod::None, LastMod::from),
+                    ..SiteMapEntry::new()
+                })?;
+        }
+        Ok(index_writer)
+    }
+
+    /// Writes a sitemap, a sitemap without a valid location fails with
+    /// `Error::MissingLoc`, or is skipped with `WriterOptions::skip_invalid`.
+    ///
+    /// Once writing to the stream failed, leaving a sitemap incomplete, the
+    /// next sitemaps and `end` fail with `Error::State`.
     pub fn sitemap(&mut self, sitemapentry: SiteMapEntry) -> Result<(), Error> {
-        self.sitemap.writer.write(XmlEvent::start_element("sitemap"))?;
-        if let Location::Url(loc) = sitemapentry.loc {
-            self.sitemap.write_content_element("loc", loc.as_str())?;
+        if self.sitemap.failed {
+            return Err(Error::State("sitemap() called after a failed write"));
         }
-        if let LastMod::DateTime(lastmod) = sitemapentry.lastmod {
-            self.sitemap.write_content_element("lastmod", lastmod.to_rfc3339().as_str())?;
+        if !check_loc(&sitemapentry.loc, &self.sitemap.options, self.sitemaps, &mut self.skipped)? {
+            return Ok(());
         }
+        self.sitemaps += 1;
+        if let Err(error) = self.write_sitemap(&sitemapentry) {
+            self.sitemap.failed = true;
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    fn write_sitemap(&mut self, sitemapentry: &SiteMapEntry) -> Result<(), Error> {
+        self.sitemap.writer.write(XmlEvent::start_element("sitemap"))?;
+        if let Some(loc) = self.sitemap.options.loc_encoding.text(&sitemapentry.loc) {
+            self.sitemap.content_element("loc", &loc)?;
+        }
+        self.sitemap.write_lastmod(&sitemapentry.lastmod)?;
         self.sitemap.writer.write(XmlEvent::end_element().name("sitemap"))?;
         Ok(())
     }
 
-    pub fn end(mut self) -> Result<SiteMapWriter<T>, Error> {
+    #[cfg(feature = "async")]
+    pub(crate) fn get_mut(&mut self) -> &mut T {
+        self.sitemap.get_mut()
+    }
+
+    /// Bytes written so far, before compression for gzip streams.
+    pub fn bytes_written(&self) -> u64 {
+        self.sitemap.bytes_written()
+    }
+
+    /// Writes a comment after the last sitemap written, `--` is written
+    /// `- -`.
+    pub fn comment(&mut self, text: &str) -> Result<(), Error> {
+        if self.sitemap.failed {
+            return Err(Error::State("comment() called after a failed write"));
+        }
+        self.sitemap.write_comment(text)
+    }
+
+    /// Number of sitemaps without a valid location skipped with
+    /// `WriterOptions::skip_invalid`.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Closes the sitemapindex and returns the underlying stream.
+    pub fn end(self) -> Result<T, Error> {
+        Ok(self.end_sitemapindex()?.into_inner())
+    }
+
+    /// Closes the sitemapindex and returns the writer, to write the next
+    /// document with `SiteMapWriter::rotate`.
+    pub fn end_sitemapindex(mut self) -> Result<SiteMapWriter<T>, Error> {
+        if self.sitemap.failed {
+            return Err(Error::State("end() called after a failed write"));
+        }
         self.sitemap.writer.write(XmlEvent::end_element().name("sitemapindex"))?;
+        self.sitemap.ended = true;
         Ok(self.sitemap)
     }
 }
 
+/// Escapes the five XML special characters, as required by the protocol
+/// for the element content, and with `ascii` the characters outside ASCII.
+fn escape(content: &str, ascii: bool) -> Cow<'_, str> {
+    if (!ascii || content.is_ascii()) && !content.contains(['&', '<', '>', '\'', '"']) {
+        return Cow::Borrowed(content);
+    }
+    let mut escaped = String::with_capacity(content.len() + 16);
+    let _ = escape_pieces::<(), _>(content, ascii, |piece| {
+        escaped.push_str(piece);
+        Ok(())
+    });
+    Cow::Owned(escaped)
+}
+
+/// Text of a comment, with a space after each `-` followed by another one,
+/// `--` not being allowed in a comment.
+fn comment_text(text: &str) -> Cow<'_, str> {
+    if !text.contains("--") {
+        return Cow::Borrowed(text);
+    }
+    let mut escaped = String::with_capacity(text.len() + 8);
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        escaped.push(c);
+        if c == '-' && chars.peek() == Some(&'-') {
+            escaped.push(' ');
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+/// Calls `write` with the pieces of `content` escaped like `escape`, the
+/// unescaped text between the entities written as it is.
+fn escape_pieces<E, F>(content: &str, ascii: bool, mut write: F) -> Result<(), E>
+    where F: FnMut(&str) -> Result<(), E>
+{
+    let mut start = 0;
+    // long enough for `&#x10FFFF;`
+    let mut reference = [0u8; 10];
+    let size = reference.len();
+    for (index, c) in content.char_indices() {
+        let entity = match c {
+            '&' => "&amp;",
+            '<' => "&lt;",
+            '>' => "&gt;",
+            '\'' => "&apos;",
+            '"' => "&quot;",
+            c if ascii && !c.is_ascii() => {
+                let mut cursor = &mut reference[..];
+                let _ = write!(cursor, "&#x{:X};", u32::from(c));
+                let len = size - cursor.len();
+                std::str::from_utf8(&reference[..len]).unwrap_or_default()
+            }
+            _ => continue,
+        };
+        if start < index {
+            write(&content[start..index])?;
+        }
+        write(entity)?;
+        start = index + c.len_utf8();
+    }
+    if start < content.len() {
+        write(&content[start..])?;
+    }
+    Ok(())
+}
+
 impl<T: Write + Sized> SiteMapWriter<T> {
     pub fn new(out: T) -> SiteMapWriter<T> {
-        let writer = EmitterConfig::new().perform_indent(true).create_writer(out);
-        SiteMapWriter { writer: writer }
+        SiteMapWriter::with_options(out, WriterOptions::default())
+    }
+
+    /// Creates a new writer with the given options.
+    pub fn with_options(out: T, options: WriterOptions) -> SiteMapWriter<T> {
+        let indent = match options.indent {
+            Indent::None => String::new(),
+            Indent::Spaces(spaces) => " ".repeat(usize::from(spaces)),
+            Indent::Tabs => "\t".to_string(),
+        };
+        let writer = EmitterConfig::new()
+            .write_document_declaration(options.xml_declaration != Declaration::None)
+            .perform_indent(options.indent != Indent::None)
+            .indent_string(indent)
+            .line_separator(options.line_separator())
+            .keep_element_names_stack(false)
+            .create_writer(CountingWriter { inner: out, count: 0 });
+        SiteMapWriter {
+            writer,
+            options,
+            buffer: String::new(),
+            stylesheets: Vec::new(),
+            warnings: Vec::new(),
+            duplicates_skipped: 0,
+            ended: false,
+            failed: false,
+        }
+    }
+
+    /// Adds an XSLT stylesheet, written after the XML declaration.
+    pub fn with_stylesheet(self, href: &str) -> SiteMapWriter<T> {
+        self.stylesheet(Stylesheet::new(href))
+    }
+
+    /// Adds a stylesheet, written after the XML declaration.
+    pub fn stylesheet(mut self, stylesheet: Stylesheet) -> SiteMapWriter<T> {
+        self.stylesheets.push(stylesheet);
+        self
+    }
+
+    /// Options of the writer.
+    pub fn options(&self) -> WriterOptions {
+        self.options
+    }
+
+    /// Returns the underlying stream, with the document written so far, and
+    /// a writer of the next document to `out`, with the same options and
+    /// stylesheets.
+    pub fn rotate<U: Write>(self, out: U) -> (T, SiteMapWriter<U>) {
+        let next = SiteMapWriter {
+            stylesheets: self.stylesheets.clone(),
+            ..SiteMapWriter::with_options(out, self.options)
+        };
+        (self.into_inner(), next)
+    }
+
+    /// Starts the `<urlset>` root element, fails with `Error::DocumentEnded`
+    /// once a root element has been closed, see `rotate`.
+    pub fn start_urlset(self) -> Result<UrlSetWriter<T>, Error> {
+        if self.ended {
+            return Err(Error::DocumentEnded);
+        }
+        self.options.xml_declaration.check()?;
+        Ok(UrlSetWriter {
+            sitemap: self,
+            started: false,
+            namespaces: Vec::new(),
+            attributes: Vec::new(),
+            root_namespaces: Vec::new(),
+            limit_policy: LimitPolicy::default(),
+            max_urls: MAX_URLS_PER_SITEMAP,
+            max_bytes: MAX_SITEMAP_SIZE,
+            urls: 0,
+            skipped: 0,
+            seen: HashSet::new(),
+            duplicates: 0,
+            rejected: 0,
+            duplicates_skipped: 0,
+            warnings: Vec::new(),
+            base_url: None,
+        })
     }
 
-    pub fn start_urlset(mut self) -> Result<UrlSetWriter<T>, Error> {
-        self.writer.write(XmlEvent::start_element("urlset"))?;
-        Ok(UrlSetWriter { sitemap: self })
+    /// Fails with `Error::State`, the elements are written by the
+    /// `UrlSetWriter` and the `SiteMapIndexWriter`: one written outside
+    /// their root element would be a second root element.
+    pub fn write_content_element(&mut self, _ele: &str, _content: &str) -> Result<(), Error> {
+        Err(Error::State("write_content_element() called outside a root element"))
     }
 
-    pub fn write_content_element(&mut self, ele: &str, content: &str) -> Result<(), Error> {
+    /// Writes an element with the text `content`, escaped.
+    fn content_element(&mut self, ele: &str, content: &str) -> Result<(), Error> {
         self.writer.write(XmlEvent::start_element(ele))?;
-        self.writer.write(XmlEvent::characters(content))?;
+        let ascii = self.options.xml_declaration.ascii_only();
+        let writer = &mut self.writer;
+        escape_pieces(content, ascii, |piece| writer.write(XmlEvent::RawCharacters(piece)))?;
         self.writer.write(XmlEvent::end_element().name(ele))?;
         Ok(())
     }
+
+    /// Writes the declaration and the stylesheets at the start of the
+    /// stream.
+    fn write_prolog(&mut self) -> Result<(), Error> {
+        if self.writer.inner_ref().count > 0 {
+            return Ok(());
+        }
+        let (encoding, standalone) = match self.options.xml_declaration {
+            Declaration::Default => ("UTF-8", None),
+            Declaration::None => ("", None),
+            Declaration::Custom { encoding, standalone } => (encoding, standalone),
+        };
+        if !encoding.is_empty() {
+            self.writer.write(XmlEvent::StartDocument {
+                version: XmlVersion::Version10,
+                encoding: Some(encoding),
+                standalone,
+            })?;
+        }
+        let ascii = self.options.xml_declaration.ascii_only();
+        for stylesheet in &self.stylesheets {
+            let data = format!("type=\"{}\" href=\"{}\"",
+                               escape(&stylesheet.media_type, ascii),
+                               escape(&stylesheet.href, ascii));
+            self.writer.write(XmlEvent::processing_instruction("xml-stylesheet", Some(&data)))?;
+        }
+        Ok(())
+    }
+
+    /// Writes a comment, `--` written `- -`. Fails with `Error::InvalidValue`
+    /// on characters outside ASCII with an ASCII only declaration, a comment
+    /// can not contain character references.
+    fn write_comment(&mut self, text: &str) -> Result<(), Error> {
+        if self.options.xml_declaration.ascii_only() && !text.is_ascii() {
+            return Err(Error::invalid_value("comment", text, "characters outside ASCII"));
+        }
+        self.writer.write(XmlEvent::Comment(&comment_text(text)))?;
+        Ok(())
+    }
+
+    /// Writes an element with the text appended by `text` to the buffer
+    /// reused for all the elements, nothing when it returns `false`.
+    fn write_text_element<F>(&mut self, ele: &str, text: F) -> Result<(), Error>
+        where F: FnOnce(&mut String) -> bool
+    {
+        let mut buffer = mem::take(&mut self.buffer);
+        buffer.clear();
+        let result = if text(&mut buffer) {
+            self.content_element(ele, &buffer)
+        } else {
+            Ok(())
+        };
+        self.buffer = buffer;
+        result
+    }
+
+    /// Writes the `<lastmod>` element of a valid value, following the
+    /// options.
+    fn write_lastmod(&mut self, lastmod: &LastMod) -> Result<(), Error> {
+        let options = self.options;
+        self.write_text_element("lastmod", |text| match (options.lastmod_format, lastmod) {
+            (LastModFormat::AsIs, &LastMod::DateTime(time)) if options.deterministic => {
+                utc_rfc3339(text, time, true, true);
+                true
+            }
+            (LastModFormat::AsIs, LastMod::W3c(time)) if options.deterministic => {
+                utc_rfc3339(text, time.time, true, true);
+                true
+            }
+            (LastModFormat::AsIs, LastMod::NonStandard(time)) if options.deterministic => {
+                utc_rfc3339(text, time.time, true, true);
+                true
+            }
+            (format, lastmod) => format.write(lastmod, text),
+        })
+    }
+    /// Starts the `<sitemapindex>` root element, fails with
+    /// `Error::DocumentEnded` once a root element has been closed, see
+    /// `rotate`.
     pub fn start_sitemapindex(mut self) -> Result<SiteMapIndexWriter<T>, Error> {
-        self.writer.write(XmlEvent::start_element("sitemapindex"))?;
-        Ok(SiteMapIndexWriter { sitemap: self })
+        if self.ended {
+            return Err(Error::DocumentEnded);
+        }
+        self.options.xml_declaration.check()?;
+        self.write_prolog()?;
+        self.writer.write(XmlEvent::start_element("sitemapindex").default_ns(SITEMAP_NAMESPACE))?;
+        Ok(SiteMapIndexWriter {
+            sitemap: self,
+            sitemaps: 0,
+            skipped: 0,
+        })
+    }
+
+    /// Limits exceeded by the last urlset written with `LimitPolicy::Warn`.
+    pub fn warnings(&self) -> &[Error] {
+        &self.warnings
+    }
+
+    /// Duplicate urls skipped by the last urlset written with
+    /// `DedupPolicy::SkipAndCount`.
+    pub fn duplicates_skipped(&self) -> usize {
+        self.duplicates_skipped
+    }
+
+    /// Bytes written so far, before compression for gzip streams.
+    pub fn bytes_written(&self) -> u64 {
+        self.writer.inner_ref().count
+    }
+
+    /// Returns the underlying stream.
+    pub fn into_inner(self) -> T {
+        self.writer.into_inner().inner
+    }
+
+    /// Underlying stream, the writer may still hold part of an element.
+    #[cfg(feature = "async")]
+    pub(crate) fn get_mut(&mut self) -> &mut T {
+        &mut self.writer.inner_mut().inner
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl<W: Write> SiteMapWriter<GzEncoder<W>> {
+    /// Creates a new writer producing a gzip compressed document.
+    pub fn new_gzip(out: W) -> SiteMapWriter<GzEncoder<W>> {
+        SiteMapWriter::new(GzEncoder::new(out, Compression::default()))
+    }
+
+    /// Finishes the gzip stream and returns the underlying stream.
+    pub fn finish(self) -> Result<W, Error> {
+        Ok(self.into_inner().finish()?)
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl<W: Write> UrlSetWriter<GzEncoder<W>> {
+    /// Closes the urlset, finishes the gzip stream and returns the
+    /// underlying stream.
+    pub fn finish(self) -> Result<W, Error> {
+        Ok(self.end()?.finish()?)
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl<W: Write> SiteMapIndexWriter<GzEncoder<W>> {
+    /// Closes the sitemapindex, finishes the gzip stream and returns the
+    /// underlying stream.
+    pub fn finish(self) -> Result<W, Error> {
+        Ok(self.end()?.finish()?)
+    }
+}
+
+/// Writes `url` alone as a `<url>` element, declaring its extension
+/// namespaces, see `UrlEntry::to_xml_string`.
+pub(crate) fn url_to_string(url: &UrlEntry, options: &WriterOptions) -> Result<String, Error> {
+    let options = WriterOptions {
+        xml_declaration: Declaration::None,
+        ..*options
+    };
+    let mut writer = SiteMapWriter::with_options(Vec::new(), options).start_urlset()?;
+    if check_loc(&url.loc, &options, 0, &mut writer.skipped)? {
+        check_url(url, 0)?;
+        let mut start = XmlEvent::start_element("url");
+        for (prefix, uri) in extension_namespaces(url) {
+            start = start.ns(prefix, uri);
+            writer.root_namespaces.push((prefix.to_string(), uri.to_string()));
+        }
+        writer.write_url_element(url, start)?;
+    }
+    String::from_utf8(writer.sitemap.into_inner()).map_err(|error| Error::Invalid(error.to_string()))
+}
+
+/// Writes `sitemap` alone as a `<sitemap>` element, see
+/// `SiteMapEntry::to_xml_string`.
+pub(crate) fn sitemap_to_string(sitemap: &SiteMapEntry, options: &WriterOptions) -> Result<String, Error> {
+    let options = WriterOptions {
+        xml_declaration: Declaration::None,
+        ..*options
+    };
+    let mut writer = SiteMapIndexWriter {
+        sitemap: SiteMapWriter::with_options(Vec::new(), options),
+        sitemaps: 0,
+        skipped: 0,
+    };
+    writer.sitemap(sitemap.clone())?;
+    String::from_utf8(writer.sitemap.into_inner()).map_err(|error| Error::Invalid(error.to_string()))
+}
+
+/// Counts of a document written by `write_urlset` or `write_index`.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub struct WriteSummary {
+    /// Entries written.
+    pub written: usize,
+    /// Entries skipped, without a valid location with
+    /// `WriterOptions::skip_invalid`, or duplicates with
+    /// `WriterOptions::dedup`.
+    pub skipped: usize,
+    /// Bytes written, before compression for gzip streams.
+    pub bytes: u64,
+    /// Time taken to write the document, zero on `wasm32` where the time
+    /// is not measured.
+    pub duration: Duration,
+}
+
+/// Start of a write, `None` on `wasm32` where `Instant::now` may panic.
+fn write_start() -> Option<Instant> {
+    if cfg!(target_arch = "wasm32") {
+        None
+    } else {
+        Some(Instant::now())
+    }
+}
+
+/// Writes the complete urlset of `entries` to `out` and flushes it, the
+/// simplest way to write a sitemap. `UrlSetWriter` writes the urls one
+/// at a time, with more control on the document, and
+/// `SplitSiteMapWriter` the sitemaps over the protocol limits.
+///
+/// Fails with the first error of `UrlSetWriter::url`.
+///
+/// ```rust
+/// extern crate sitemap;
+/// use sitemap::structs::UrlEntry;
+/// use sitemap::writer::{self, WriterOptions};
+/// fn main() {
+///     let urls = (0..3).map(|i| UrlEntry::builder().loc(format!("http://www.example.com/{}", i)).build().unwrap());
+///     let mut out = Vec::new();
+///     let summary = writer::write_urlset(&mut out, urls, &WriterOptions::default()).unwrap();
+///     assert_eq!(summary.written, 3);
+///     assert_eq!(summary.bytes, out.len() as u64);
+/// }
+/// ```
+pub fn write_urlset<W, I>(out: W, entries: I, options: &WriterOptions) -> Result<WriteSummary, Error>
+    where W: Write,
+          I: IntoIterator<Item = UrlEntry>
+{
+    let start = write_start();
+    let mut urlset = SiteMapWriter::with_options(out, *options).start_urlset()?;
+    for entry in entries {
+        urlset.url(entry)?;
+    }
+    let (written, skipped) = (urlset.urls, urlset.skipped + urlset.duplicates);
+    let writer = urlset.end_urlset()?;
+    let bytes = writer.bytes_written();
+    writer.into_inner().flush()?;
+    Ok(WriteSummary {
+        written,
+        skipped,
+        bytes,
+        duration: start.map(|start| start.elapsed()).unwrap_or_default(),
+    })
+}
+
+/// Writes the complete sitemapindex of `entries` to `out` and flushes it,
+/// see `write_urlset`.
+///
+/// Fails with the first error of `SiteMapIndexWriter::sitemap`.
+pub fn write_index<W, I>(out: W, entries: I, options: &WriterOptions) -> Result<WriteSummary, Error>
+    where W: Write,
+          I: IntoIterator<Item = SiteMapEntry>
+{
+    let start = write_start();
+    let mut index = SiteMapWriter::with_options(out, *options).start_sitemapindex()?;
+    for entry in entries {
+        index.sitemap(entry)?;
+    }
+    let (written, skipped) = (index.sitemaps, index.skipped);
+    let writer = index.end_sitemapindex()?;
+    let bytes = writer.bytes_written();
+    writer.into_inner().flush()?;
+    Ok(WriteSummary {
+        written,
+        skipped,
+        bytes,
+        duration: start.map(|start| start.elapsed()).unwrap_or_default(),
+    })
+}
+
+/// Writes the urlset of `entries` to a buffer and returns it.
+///
+/// The whole document is kept in memory, very large sitemaps are better
+/// written to their stream with `UrlSetWriter`.
+pub fn urlset_to_vec<I>(entries: I, options: WriterOptions) -> Result<Vec<u8>, Error>
+    where I: IntoIterator<Item = UrlEntry>
+{
+    let mut writer = SiteMapWriter::with_options(Vec::new(), options).start_urlset()?;
+    for entry in entries {
+        writer.url(entry)?;
+    }
+    writer.end()
+}
+
+/// Writes the urlset of `entries` to a string, see `urlset_to_vec`.
+pub fn urlset_to_string<I>(entries: I, options: WriterOptions) -> Result<String, Error>
+    where I: IntoIterator<Item = UrlEntry>
+{
+    String::from_utf8(urlset_to_vec(entries, options)?).map_err(|error| Error::Invalid(error.to_string()))
+}
+
+/// Writes the sitemapindex of `entries` to a buffer and returns it.
+///
+/// The whole document is kept in memory, very large indexes are better
+/// written to their stream with `SiteMapIndexWriter`.
+pub fn sitemapindex_to_vec<I>(entries: I, options: WriterOptions) -> Result<Vec<u8>, Error>
+    where I: IntoIterator<Item = SiteMapEntry>
+{
+    let mut writer = SiteMapWriter::with_options(Vec::new(), options).start_sitemapindex()?;
+    for entry in entries {
+        writer.sitemap(entry)?;
+    }
+    writer.end()
+}
+
+/// Writes the sitemapindex of `entries` to a string, see
+/// `sitemapindex_to_vec`.
+pub fn sitemapindex_to_string<I>(entries: I, options: WriterOptions) -> Result<String, Error>
+    where I: IntoIterator<Item = SiteMapEntry>
+{
+    String::from_utf8(sitemapindex_to_vec(entries, options)?).map_err(|error| Error::Invalid(error.to_string()))
+}
+
+/// Output of a single split sitemap, holds the last url until it is known
+/// to fit in the sitemap.
+struct SplitBuffer<T: Write> {
+    out: Option<T>,
+    pending: Vec<u8>,
+    written: u64,
+}
+
+impl<T: Write> SplitBuffer<T> {
+    fn size(&self) -> u64 {
+        self.written + self.pending.len() as u64
+    }
+
+    fn commit(&mut self) -> io::Result<()> {
+        if let Some(ref mut out) = self.out {
+            out.write_all(&self.pending)?;
+        }
+        self.written += self.pending.len() as u64;
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+impl<T: Write> Write for SplitBuffer<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writer of urls that rotates to a new sitemap once the current one
+/// reaches the url count or the size limit, and writes the sitemap index
+/// of all the generated files at the end.
+///
+/// The factory is called with the number of the sitemap, starting from 1,
+/// and the `{}` of the location template is replaced by the same number.
+pub struct SplitSiteMapWriter<'a, T: Write> {
+    factory: Box<dyn FnMut(usize) -> io::Result<T> + 'a>,
+    finish: fn(T) -> io::Result<()>,
+    template: String,
+    max_urls: usize,
+    max_bytes: u64,
+    options: WriterOptions,
+    current: Option<UrlSetWriter<SplitBuffer<T>>>,
+    urls: usize,
+    written: usize,
+    skipped: usize,
+    files: usize,
+    sitemaps: Vec<Url>,
+}
+
+fn flush_output<T: Write>(mut out: T) -> io::Result<()> {
+    out.flush()
+}
+
+impl<'a, T: Write> SplitSiteMapWriter<'a, T> {
+    /// Creates a new split writer, `template` is the location of the
+    /// sitemaps with `{}` in place of the sitemap number.
+    pub fn new<F>(factory: F, template: &str) -> Result<SplitSiteMapWriter<'a, T>, Error>
+        where F: FnMut(usize) -> io::Result<T> + 'a
+    {
+        SplitSiteMapWriter::with_finish(Box::new(factory), flush_output, template)
+    }
+
+    fn with_finish(factory: Box<dyn FnMut(usize) -> io::Result<T> + 'a>,
+                   finish: fn(T) -> io::Result<()>,
+                   template: &str)
+                   -> Result<SplitSiteMapWriter<'a, T>, Error> {
+        if !template.contains("{}") {
+            return Err(Error::Invalid(format!("location template '{}' without {{}}", template)));
+        }
+        Url::parse(&template.replace("{}", "1"))?;
+        Ok(SplitSiteMapWriter {
+            factory,
+            finish,
+            template: template.to_string(),
+            max_urls: MAX_URLS_PER_SITEMAP,
+            max_bytes: MAX_SITEMAP_SIZE,
+            options: WriterOptions::default(),
+            current: None,
+            urls: 0,
+            written: 0,
+            skipped: 0,
+            files: 0,
+            sitemaps: Vec::new(),
+        })
+    }
+
+    /// Creates a new split writer of the sitemaps only, without location
+    /// template, to be ended by `end_sitemaps`.
+    pub(crate) fn without_index<F>(factory: F) -> SplitSiteMapWriter<'a, T>
+        where F: FnMut(usize) -> io::Result<T> + 'a
+    {
+        SplitSiteMapWriter {
+            factory: Box::new(factory),
+            finish: flush_output,
+            template: String::new(),
+            max_urls: MAX_URLS_PER_SITEMAP,
+            max_bytes: MAX_SITEMAP_SIZE,
+            options: WriterOptions::default(),
+            current: None,
+            urls: 0,
+            written: 0,
+            skipped: 0,
+            files: 0,
+            sitemaps: Vec::new(),
+        }
+    }
+
+    /// Maximum number of urls of each sitemap, `MAX_URLS_PER_SITEMAP` by default.
+    pub fn max_urls(mut self, max_urls: usize) -> SplitSiteMapWriter<'a, T> {
+        self.max_urls = max_urls;
+        self
+    }
+
+    /// Maximum size in bytes of each sitemap, `MAX_SITEMAP_SIZE` by default.
+    pub fn max_bytes(mut self, max_bytes: u64) -> SplitSiteMapWriter<'a, T> {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Options of the sitemaps and of the index.
+    pub fn options(mut self, options: WriterOptions) -> SplitSiteMapWriter<'a, T> {
+        self.options = options;
+        self
+    }
+
+    /// Number of urls without a valid location skipped with
+    /// `WriterOptions::skip_invalid`.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Writes a url, starting a new sitemap when the current one is full.
+    ///
+    /// A url without a valid location fails with `Error::MissingLoc`, or is
+    /// skipped with `WriterOptions::skip_invalid`, a url with too many
+    /// images fails with `Error::InvalidEntry`.
+    pub fn url(&mut self, url: UrlEntry) -> Result<(), Error> {
+        if !check_loc(&url.loc, &self.options, self.written, &mut self.skipped)? {
+            return Ok(());
+        }
+        check_url(&url, self.written + self.skipped)?;
+        if self.urls >= self.max_urls {
+            self.close()?;
+        }
+        let mut writer = match self.current.take() {
+            Some(writer) => writer,
+            None => {
+                let buffer = SplitBuffer {
+                    out: None,
+                    pending: Vec::new(),
+                    written: 0,
+                };
+                SiteMapWriter::with_options(buffer, self.options).start_urlset()?
+            }
+        };
+        writer.write_url(&url)?;
+        if self.fits(&writer) {
+            return self.commit(writer);
+        }
+        if self.urls == 0 {
+            return Err(Error::Invalid(format!("url larger than the {} bytes sitemap limit",
+                                              self.max_bytes)));
+        }
+        writer.sitemap.writer.inner_mut().inner.pending.clear();
+        self.current = Some(writer);
+        self.close()?;
+        self.url(url)
+    }
+
+    fn fits(&self, writer: &UrlSetWriter<SplitBuffer<T>>) -> bool {
+        let size = writer.sitemap.writer.inner_ref().inner.size() + self.options.end_len("urlset");
+        size <= self.max_bytes
+    }
+
+    fn commit(&mut self, mut writer: UrlSetWriter<SplitBuffer<T>>) -> Result<(), Error> {
+        {
+            let buffer = &mut writer.sitemap.writer.inner_mut().inner;
+            if buffer.out.is_none() {
+                buffer.out = Some((self.factory)(self.files + 1)?);
+            }
+            buffer.commit()?;
+        }
+        self.urls += 1;
+        self.written += 1;
+        self.current = Some(writer);
+        Ok(())
+    }
+
+    /// Closes the current sitemap, if any.
+    fn close(&mut self) -> Result<(), Error> {
+        if let Some(writer) = self.current.take() {
+            let mut buffer = writer.end()?;
+            buffer.commit()?;
+            if let Some(out) = buffer.out {
+                (self.finish)(out)?;
+            }
+            self.files += 1;
+            if !self.template.is_empty() {
+                self.sitemaps.push(Url::parse(&self.template.replace("{}", &self.files.to_string()))?);
+            }
+        }
+        self.urls = 0;
+        Ok(())
+    }
+
+    /// Closes the last sitemap, returning the number of sitemaps.
+    pub(crate) fn end_sitemaps(mut self) -> Result<usize, Error> {
+        self.close()?;
+        Ok(self.files)
+    }
+
+    /// Closes the last sitemap and writes to `index` the sitemap index
+    /// of all the generated sitemaps, using the current time as lastmod.
+    pub fn end<W: Write>(mut self, index: W) -> Result<W, Error> {
+        self.close()?;
+        let now: DateTime<FixedOffset> = Utc::now().into();
+        let mut index_writer = SiteMapWriter::with_options(index, self.options).start_sitemapindex()?;
+        for loc in self.sitemaps {
+            index_writer.sitemap(SiteMapEntry {
+                    loc: Location::Url(loc),
+                    lastmod: LastMod::DateTime(now),
+                    ..SiteMapEntry::new()
+                })?;
+        }
+        index_writer.end()
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn finish_gzip<W: Write>(out: GzEncoder<W>) -> io::Result<()> {
+    out.finish().map(|_| ())
+}
+
+#[cfg(feature = "gzip")]
+impl<'a, W: Write + 'a> SplitSiteMapWriter<'a, GzEncoder<W>> {
+    /// Creates a new split writer producing gzip compressed sitemaps, the
+    /// size limit applies to the uncompressed documents.
+    pub fn new_gzip<F>(mut factory: F,
+                       template: &str)
+                       -> Result<SplitSiteMapWriter<'a, GzEncoder<W>>, Error>
+        where F: FnMut(usize) -> io::Result<W> + 'a
+    {
+        let factory = move |number| {
+            factory(number).map(|out| GzEncoder::new(out, Compression::default()))
+        };
+        SplitSiteMapWriter::with_finish(Box::new(factory), finish_gzip, template)
+    }
+}
+
+/// Writer of text sitemaps, one url per line.
+///
+/// The protocol limits are checked like in `UrlSetWriter`.
+pub struct TextSiteMapWriter<T: Write + Sized> {
+    out: CountingWriter<T>,
+    limit_policy: LimitPolicy,
+    max_urls: usize,
+    max_bytes: u64,
+    urls: usize,
+    warnings: Vec<Error>,
+}
+
+impl<T: Write + Sized> TextSiteMapWriter<T> {
+    /// Creates a new writer.
+    pub fn new(out: T) -> TextSiteMapWriter<T> {
+        TextSiteMapWriter {
+            out: CountingWriter {
+                inner: out,
+                count: 0,
+            },
+            limit_policy: LimitPolicy::default(),
+            max_urls: MAX_URLS_PER_SITEMAP,
+            max_bytes: MAX_SITEMAP_SIZE,
+            urls: 0,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Policy applied when the sitemap goes over the limits, by default
+    /// limits are ignored.
+    pub fn limit_policy(mut self, limit_policy: LimitPolicy) -> TextSiteMapWriter<T> {
+        self.limit_policy = limit_policy;
+        self
+    }
+
+    /// Maximum number of urls, `MAX_URLS_PER_SITEMAP` by default.
+    pub fn max_urls(mut self, max_urls: usize) -> TextSiteMapWriter<T> {
+        self.max_urls = max_urls;
+        self
+    }
+
+    /// Maximum size in bytes, `MAX_SITEMAP_SIZE` by default.
+    pub fn max_bytes(mut self, max_bytes: u64) -> TextSiteMapWriter<T> {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Limits exceeded so far with `LimitPolicy::Warn`.
+    pub fn warnings(&self) -> &[Error] {
+        &self.warnings
+    }
+
+    /// Writes the location of a url, the other fields are not part of text
+    /// sitemaps. Urls without a valid location are refused, a `Raw`
+    /// location is parsed and written as the parsed url.
+    pub fn url(&mut self, url: UrlEntry) -> Result<(), Error> {
+        let loc = url.loc.parsed();
+        let loc = match *loc {
+            Location::Url(ref url) => url.as_str(),
+            _ => return Err(Error::Invalid("url without a valid loc".to_string())),
+        };
+        if self.urls >= self.max_urls {
+            let limit = self.max_urls as u64;
+            limit_exceeded(self.limit_policy, &mut self.warnings, LimitKind::UrlCount, limit)?;
+        }
+        self.out.write_all(loc.as_bytes())?;
+        self.out.write_all(b"\n")?;
+        self.urls += 1;
+        if self.out.count > self.max_bytes {
+            let limit = self.max_bytes;
+            limit_exceeded(self.limit_policy, &mut self.warnings, LimitKind::Size, limit)?;
+        }
+        Ok(())
+    }
+
+    /// Bytes written so far.
+    pub fn bytes_written(&self) -> u64 {
+        self.out.count
+    }
+
+    /// Returns the underlying stream.
+    pub fn into_inner(self) -> T {
+        self.out.inner
     }
 }
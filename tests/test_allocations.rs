@@ -0,0 +1,138 @@
+extern crate sitemap;
+
+use sitemap::reader::{ReaderOptions, SiteMapEvent, SiteMapReader};
+use sitemap::structs::UrlEntry;
+use sitemap::writer::SiteMapWriter;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// System allocator counting the allocations of each thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn document(urls: usize) -> String {
+    let mut document = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                                     <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+    for i in 0..urls {
+        document.push_str(&format!("  <url>\n    <loc>http://www.example.com/page/{}?a=1&amp;b=2</loc>\n    \
+                                    <lastmod>2024-01-{:02}T10:20:30+02:00</lastmod>\n    \
+                                    <changefreq>daily</changefreq>\n    <priority>0.{}</priority>\n  </url>\n",
+                                   i,
+                                   i % 28 + 1,
+                                   i % 10));
+    }
+    document.push_str("</urlset>\n");
+    document
+}
+
+/// Allocations of the thread while reading the urls of `document`.
+fn allocations(document: &str, options: ReaderOptions) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    let urls = SiteMapReader::with_options(document.as_bytes(), options)
+        .filter(|event| matches!(event, Ok(SiteMapEvent::Url(_))))
+        .count();
+    assert!(urls > 0);
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+/// Allocations of each url, without those of the start of the document.
+fn allocations_per_url(options: ReaderOptions) -> f64 {
+    let small = allocations(&document(100), options);
+    let large = allocations(&document(1100), options);
+    (large - small) as f64 / 1000.0
+}
+
+/// Allowed allocations of each url of `document`, xml-rs allocating
+/// the names and namespaces of every element.
+#[cfg(not(feature = "fast-xml"))]
+const BUDGET: f64 = 130.0;
+#[cfg(feature = "fast-xml")]
+const BUDGET: f64 = 60.0;
+
+#[test]
+fn test_allocations_per_url() {
+    let per_url = allocations_per_url(ReaderOptions::default());
+    assert!(per_url <= BUDGET, "{} allocations per url, {} expected", per_url, BUDGET);
+    let lazy = allocations_per_url(ReaderOptions {
+        parse_urls: false,
+        ..ReaderOptions::default()
+    });
+    assert!(lazy < per_url, "{} allocations per url with lazy urls", lazy);
+}
+
+/// Allocations of each url written, without those of the start of the
+/// document.
+fn write_allocations_per_url() -> f64 {
+    let urls: Vec<UrlEntry> = SiteMapReader::new(document(1100).as_bytes())
+        .filter_map(|event| match event {
+            Ok(SiteMapEvent::Url(url)) => Some(url),
+            _ => None,
+        })
+        .collect();
+    let write = |urls: Vec<UrlEntry>| {
+        let mut output = Vec::with_capacity(1 << 20);
+        let before = ALLOCATIONS.with(Cell::get);
+        {
+            let mut urlset = SiteMapWriter::new(&mut output).start_urlset().unwrap();
+            for url in urls {
+                urlset.url(url).unwrap();
+            }
+            urlset.end().unwrap();
+        }
+        ALLOCATIONS.with(Cell::get) - before
+    };
+    (write(urls.clone()) - write(urls[..100].to_vec())) as f64 / 1000.0
+}
+
+#[test]
+fn test_write_allocations_per_url() {
+    assert_eq!(write_allocations_per_url(), 0.0);
+}
+
+/// Allocations of the thread while counting the urls of `urls` for which
+/// `keep` is true.
+fn filter_allocations<F: Fn(&UrlEntry) -> bool>(urls: &[UrlEntry], keep: F) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    assert_eq!(urls.iter().filter(|url| keep(url)).count(), urls.len());
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn test_borrowing_accessors_allocations() {
+    let urls: Vec<UrlEntry> = SiteMapReader::new(document(1000).as_bytes())
+        .filter_map(|event| match event {
+            Ok(SiteMapEvent::Url(url)) => Some(url),
+            _ => None,
+        })
+        .collect();
+    let borrowed = filter_allocations(&urls, |url| {
+        url.loc.url().and_then(|loc| loc.host_str()) == Some("www.example.com") &&
+        url.loc.as_str().is_some_and(|loc| loc.contains("/page/")) && url.lastmod.time().is_some()
+    });
+    assert_eq!(borrowed, 0);
+    // each copy of a url allocates its text
+    let copied = filter_allocations(&urls, |url| {
+        url.loc.get_url().is_some_and(|loc| loc.host_str() == Some("www.example.com"))
+    });
+    assert!(copied >= urls.len(), "{} allocations", copied);
+}
@@ -0,0 +1,76 @@
+extern crate sitemap;
+extern crate url;
+
+use sitemap::reader::{SiteMapReader, SiteMapEvent};
+use sitemap::writer::SiteMapWriter;
+use sitemap::structs::{Alternate, UrlEntry};
+use std::fs::File;
+use std::io::Read;
+use url::Url;
+
+fn read_urls<T: Read>(source: T) -> Vec<UrlEntry> {
+    let mut urls = Vec::new();
+    for entity in SiteMapReader::new(source) {
+        match entity {
+            Ok(SiteMapEvent::Url(url_entry)) => urls.push(url_entry),
+            Ok(_) => {}
+            Err(error) => panic!("unexpected error {:?}", error),
+        }
+    }
+    urls
+}
+
+fn write_urls(urls: &[UrlEntry]) -> String {
+    let mut output = Vec::<u8>::new();
+    {
+        let mut url_writer = SiteMapWriter::new(&mut output).start_urlset().expect("start urlset");
+        for url in urls {
+            url_writer.url(url.clone()).expect("write url");
+        }
+        url_writer.end().expect("end urlset");
+    }
+    String::from_utf8(output).unwrap()
+}
+
+fn alternate(hreflang: &str, href: &str) -> Alternate {
+    Alternate::new(hreflang, Url::parse(href).unwrap())
+}
+
+#[test]
+fn test_read_alternates() {
+    let urls = read_urls(File::open("tests/documents/sitemap_alternates.xml").unwrap());
+    assert_eq!(urls.len(), 2);
+    assert_eq!(urls[0].alternates,
+               vec![alternate("de", "http://www.example.com/deutsch/page.html"),
+                    alternate("de-ch", "http://www.example.com/schweiz-deutsch/page.html"),
+                    alternate("en", "http://www.example.com/english/page.html"),
+                    alternate("x-default", "http://www.example.com/page.html")]);
+    assert!(urls[1].alternates.is_empty());
+}
+
+#[test]
+fn test_write_alternates() {
+    let urls = read_urls(File::open("tests/documents/sitemap_alternates.xml").unwrap());
+    let output = write_urls(&urls);
+    assert!(output.contains("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" xmlns:xhtml=\"http://www.w3.org/1999/xhtml\">"));
+    assert!(output.contains("<xhtml:link rel=\"alternate\" hreflang=\"x-default\" \
+                             href=\"http://www.example.com/page.html\" />"));
+    assert_eq!(read_urls(output.as_bytes()), urls);
+
+    let output = write_urls(&urls[1..]);
+    assert!(!output.contains("xhtml"));
+}
+
+#[test]
+fn test_build_alternates() {
+    let entry = UrlEntry::builder()
+        .loc("http://www.example.com/page.html")
+        .alternate(alternate("fr", "http://www.example.com/fr/page.html"))
+        .alternate(alternate("x-default", "http://www.example.com/page.html"))
+        .build()
+        .unwrap();
+    assert_eq!(entry.alternates[0].hreflang, "fr");
+    assert_eq!(entry.alternates[1].hreflang, "x-default");
+    let output = write_urls(std::slice::from_ref(&entry));
+    assert_eq!(read_urls(output.as_bytes()), vec![entry]);
+}
@@ -0,0 +1,84 @@
+#![cfg(feature = "testing")]
+extern crate sitemap;
+
+use sitemap::reader::{SiteMapEvent, SiteMapReader};
+use sitemap::structs::{LastMod, Priority, SiteMapEntry, UrlEntry};
+use sitemap::testing::{self, Arbitrary, Gen};
+use sitemap::writer::{self, WriterOptions};
+
+fn read_urls(document: &str) -> Vec<UrlEntry> {
+    SiteMapReader::new(document.as_bytes())
+        .filter_map(|event| match event {
+            Ok(SiteMapEvent::Url(url)) => Some(url),
+            Ok(_) => None,
+            Err(error) => panic!("unexpected error {:?}", error),
+        })
+        .collect()
+}
+
+fn read_sitemaps(document: &str) -> Vec<SiteMapEntry> {
+    SiteMapReader::new(document.as_bytes())
+        .filter_map(|event| match event {
+            Ok(SiteMapEvent::SiteMap(sitemap)) => Some(sitemap),
+            Ok(_) => None,
+            Err(error) => panic!("unexpected error {:?}", error),
+        })
+        .collect()
+}
+
+#[test]
+fn test_generator() {
+    let values: Vec<u64> = (0..4).map(|_| Gen::new(3).next_u64()).collect();
+    assert!(values.windows(2).all(|pair| pair[0] == pair[1]));
+    assert_ne!(Gen::new(3).next_u64(), Gen::new(4).next_u64());
+    assert_eq!(UrlEntry::arbitrary(&mut Gen::new(11)), UrlEntry::arbitrary(&mut Gen::new(11)));
+    testing::check(500, |url: UrlEntry| url.errors().is_empty());
+    testing::check(500, |sitemap: SiteMapEntry| sitemap.errors().is_empty());
+}
+
+#[test]
+fn test_generator_edge_cases() {
+    let mut gen = Gen::new(1);
+    let priorities: Vec<Priority> = (0..200).map(|_| Priority::arbitrary(&mut gen)).collect();
+    assert!(priorities.contains(&Priority::MIN));
+    assert!(priorities.contains(&Priority::MAX));
+    assert!(priorities.contains(&Priority::None));
+    let dates: Vec<LastMod> = (0..500).map(|_| LastMod::arbitrary(&mut gen)).collect();
+    assert!(dates.iter().any(|lastmod| lastmod.get_time().is_some_and(|time| time.format("%Y").to_string() == "0001")));
+    assert!(dates.iter().any(|lastmod| lastmod.get_time().is_some_and(|time| time.format("%Y").to_string() == "9999")));
+    assert!(dates.contains(&LastMod::None));
+}
+
+#[test]
+#[should_panic(expected = "property failed for seed 0")]
+fn test_check_failure() {
+    testing::check(10, |_: UrlEntry| false);
+}
+
+#[test]
+fn test_url_round_trip() {
+    testing::check(300, |urls: Vec<UrlEntry>| {
+        let written = writer::urlset_to_string(urls.clone(), WriterOptions::default()).unwrap();
+        let read = read_urls(&written);
+        let rewritten = writer::urlset_to_string(read.clone(), WriterOptions::default()).unwrap();
+        read == urls && rewritten == written
+    });
+}
+
+#[test]
+fn test_sitemap_round_trip() {
+    testing::check(300, |sitemaps: Vec<SiteMapEntry>| {
+        let written = writer::sitemapindex_to_string(sitemaps.clone(), WriterOptions::default()).unwrap();
+        let read = read_sitemaps(&written);
+        let rewritten = writer::sitemapindex_to_string(read.clone(), WriterOptions::default()).unwrap();
+        read == sitemaps && rewritten == written
+    });
+}
+
+#[test]
+fn test_fragment_round_trip() {
+    testing::check(300, |url: UrlEntry| {
+        let fragment = url.to_xml_string(&WriterOptions::default()).unwrap();
+        UrlEntry::from_xml_fragment(&fragment).unwrap() == url
+    });
+}
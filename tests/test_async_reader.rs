@@ -0,0 +1,255 @@
+#![cfg(feature = "async")]
+extern crate futures;
+extern crate sitemap;
+extern crate tokio;
+
+use futures::StreamExt;
+use sitemap::Error;
+use sitemap::async_reader::AsyncSiteMapReader;
+use sitemap::reader::{DocumentKind, ReaderOptions, SiteMapEvent, SiteMapReader};
+use std::fs;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, BufReader, ReadBuf};
+
+/// Stream returning a few bytes at a time, pending every other read.
+struct ChunkedReader {
+    data: Vec<u8>,
+    position: usize,
+    pending: bool,
+}
+
+impl ChunkedReader {
+    fn new(data: Vec<u8>) -> BufReader<ChunkedReader> {
+        BufReader::with_capacity(16,
+                                 ChunkedReader {
+                                     data,
+                                     position: 0,
+                                     pending: false,
+                                 })
+    }
+}
+
+impl AsyncRead for ChunkedReader {
+    fn poll_read(mut self: Pin<&mut Self>,
+                 cx: &mut Context,
+                 buf: &mut ReadBuf)
+                 -> Poll<io::Result<()>> {
+        self.pending = !self.pending;
+        if self.pending {
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        let end = (self.position + 7).min(self.data.len()).min(self.position + buf.remaining());
+        buf.put_slice(&self.data[self.position..end]);
+        self.position = end;
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[derive(Debug,PartialEq)]
+#[allow(clippy::large_enum_variant)]
+enum Summary {
+    UrlSetStart(Vec<(String, String)>),
+    IndexStart(Vec<(String, String)>),
+    Url(sitemap::structs::UrlEntry, Option<sitemap::structs::Position>),
+    SiteMap(sitemap::structs::SiteMapEntry, Option<sitemap::structs::Position>),
+    Unknown(String, usize, String, sitemap::structs::Position),
+    Truncated(sitemap::reader::TruncateReason),
+    Comment(String),
+    Err(Option<sitemap::structs::Position>),
+}
+
+fn summary(event: Result<SiteMapEvent, Error>) -> Summary {
+    match event {
+        Ok(SiteMapEvent::UrlSetStart { namespaces }) => Summary::UrlSetStart(namespaces),
+        Ok(SiteMapEvent::IndexStart { namespaces }) => Summary::IndexStart(namespaces),
+        Ok(SiteMapEvent::Url(url_entry)) => {
+            let position = url_entry.position();
+            Summary::Url(url_entry, position)
+        }
+        Ok(SiteMapEvent::SiteMap(sitemap_entry)) => {
+            let position = sitemap_entry.position();
+            Summary::SiteMap(sitemap_entry, position)
+        }
+        Ok(SiteMapEvent::Unknown { name, depth, raw, position }) => {
+            Summary::Unknown(name.to_string(), depth, raw, position)
+        }
+        Ok(SiteMapEvent::Truncated { reason }) => Summary::Truncated(reason),
+        Ok(SiteMapEvent::Comment(text)) => Summary::Comment(text),
+        Err(error) => Summary::Err(error.position()),
+    }
+}
+
+static DOCUMENTS: &[&str] = &["tests/documents/sitemap1.xml",
+                               "tests/documents/sitemap_images.xml",
+                               "tests/documents/sitemap_videos.xml",
+                               "tests/documents/sitemap_news.xml",
+                               "tests/documents/sitemap_alternates.xml",
+                               "tests/documents/sitemap_pretty.xml",
+                               "tests/documents/sitemap_no_namespace.xml",
+                               "tests/documents/sitemap_namespace_084.xml",
+                               "tests/documents/sitemap_prefixed.xml"];
+
+#[tokio::test]
+async fn test_async_reader_matches_sync_reader() {
+    for document in DOCUMENTS {
+        let data = fs::read(document).unwrap();
+        let mut sync_reader = SiteMapReader::new(&data[..]);
+        let expected: Vec<Summary> = sync_reader.by_ref().map(summary).collect();
+        let mut reader = AsyncSiteMapReader::new(ChunkedReader::new(data.clone()));
+        let mut events = Vec::new();
+        while let Some(event) = reader.next_event().await {
+            events.push(summary(event));
+        }
+        assert_eq!(events, expected, "{}", document);
+        assert_eq!(reader.document_kind(), Some(DocumentKind::UrlSet));
+        assert_eq!(reader.root_namespaces(), sync_reader.root_namespaces(), "{}", document);
+    }
+}
+
+#[tokio::test]
+async fn test_async_reader_root_events() {
+    let options = ReaderOptions { root_events: true, ..ReaderOptions::default() };
+    for document in DOCUMENTS.iter().chain(&["tests/documents/sitemap_index_pretty.xml"]) {
+        let data = fs::read(document).unwrap();
+        let expected: Vec<Summary> = SiteMapReader::with_options(&data[..], options).map(summary).collect();
+        assert!(matches!(expected[0], Summary::UrlSetStart(_) | Summary::IndexStart(_)), "{}", document);
+        let mut reader = AsyncSiteMapReader::with_options(ChunkedReader::new(data), options);
+        let mut events = Vec::new();
+        while let Some(event) = reader.next_event().await {
+            events.push(summary(event));
+        }
+        assert_eq!(events, expected, "{}", document);
+    }
+}
+
+#[tokio::test]
+async fn test_async_stream_is_cancel_safe() {
+    let data = fs::read("tests/documents/sitemap_images.xml").unwrap();
+    let expected: Vec<Summary> = SiteMapReader::new(&data[..]).map(summary).collect();
+    let mut stream = AsyncSiteMapReader::new(ChunkedReader::new(data)).into_stream();
+    let mut events = Vec::new();
+    let mut polls = 0;
+    loop {
+        // every poll drops the `next()` future
+        polls += 1;
+        match futures::poll!(stream.next()) {
+            Poll::Ready(Some(event)) => events.push(summary(event)),
+            Poll::Ready(None) => break,
+            Poll::Pending => tokio::task::yield_now().await,
+        }
+    }
+    assert_eq!(events, expected);
+    assert!(polls > events.len());
+}
+
+#[tokio::test]
+async fn test_async_next_event_is_cancel_safe() {
+    let data = fs::read("tests/documents/sitemap_videos.xml").unwrap();
+    let expected: Vec<Summary> = SiteMapReader::new(&data[..]).map(summary).collect();
+    let mut reader = AsyncSiteMapReader::new(ChunkedReader::new(data));
+    let mut events = Vec::new();
+    let mut polls = 0;
+    loop {
+        // every poll drops the `next_event()` future
+        polls += 1;
+        match futures::poll!(reader.next_event()) {
+            Poll::Ready(Some(event)) => events.push(summary(event)),
+            Poll::Ready(None) => break,
+            Poll::Pending => tokio::task::yield_now().await,
+        }
+    }
+    assert_eq!(events, expected);
+    assert!(polls > events.len());
+    assert_eq!(reader.document_kind(), Some(DocumentKind::UrlSet));
+}
+
+#[tokio::test]
+async fn test_async_reader_errors() {
+    let data: &[u8] = b"<urlset>\n<url><loc>http://www.example.com/</loc></url>\n<url></urlset>";
+    let events: Vec<Result<SiteMapEvent, Error>> = AsyncSiteMapReader::new(data)
+        .into_stream()
+        .collect()
+        .await;
+    assert_eq!(events.len(), 2);
+    assert!(matches!(events[0], Ok(SiteMapEvent::Url(_))));
+    match events[1] {
+        Err(ref error @ Error::Parse { .. }) => {
+            assert_eq!(error.position().map(|position| position.line), Some(3))
+        }
+        ref other => panic!("unexpected event {:?}", other),
+    }
+
+    let data: &[u8] = b"<urlset><url><loc>http://www.example.com/?a=1&amp;b=&#50;</loc></url>";
+    let mut reader = AsyncSiteMapReader::new(data);
+    match reader.next_event().await {
+        Some(Ok(SiteMapEvent::Url(url_entry))) => {
+            assert_eq!(url_entry.loc.get_url().unwrap().as_str(), "http://www.example.com/?a=1&b=2")
+        }
+        other => panic!("unexpected event {:?}", other),
+    }
+    assert!(matches!(reader.next_event().await, Some(Err(Error::Parse { .. }))));
+    assert!(reader.next_event().await.is_none());
+}
+
+#[tokio::test]
+async fn test_async_url_stream() {
+    let data = fs::read("tests/documents/sitemap_videos.xml").unwrap();
+    let expected = SiteMapReader::new(&data[..])
+        .into_url_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let urls = AsyncSiteMapReader::new(ChunkedReader::new(data.clone()))
+        .into_url_stream()
+        .map(|url| url.unwrap())
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(urls, expected);
+    let urls = SiteMapReader::new(&data[..])
+        .into_url_stream()
+        .map(|url| url.unwrap())
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(urls, expected);
+
+    let count = std::sync::atomic::AtomicUsize::new(0);
+    AsyncSiteMapReader::new(&data[..])
+        .into_url_stream()
+        .for_each_concurrent(16, |url| {
+            assert!(url.is_ok());
+            count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            futures::future::ready(())
+        })
+        .await;
+    assert_eq!(count.into_inner(), expected.len());
+
+    let data: &[u8] = b"<sitemapindex><sitemap><loc>http://www.example.com/sitemap.xml</loc></sitemap></sitemapindex>";
+    let results: Vec<_> = AsyncSiteMapReader::new(data).into_url_stream().collect().await;
+    assert_eq!(results.len(), 1);
+    assert!(matches!(results[0], Err(Error::Invalid(_))));
+}
+
+#[tokio::test]
+async fn test_async_reader_cdata() {
+    let data: &[u8] = b"<urlset>\
+        <url><loc><![CDATA[http://www.example.com/?a=1&b=2]]></loc>\
+        <priority><![CDATA[0.5]]></priority></url>\
+        <url><loc>http://www.example.com/<![CDATA[search?q=a&b]]>&amp;page=2</loc></url>\
+        </urlset>";
+    let expected: Vec<Summary> = SiteMapReader::new(data).map(summary).collect();
+    let mut reader = AsyncSiteMapReader::new(ChunkedReader::new(data.to_vec()));
+    let mut events = Vec::new();
+    while let Some(event) = reader.next_event().await {
+        events.push(summary(event));
+    }
+    assert_eq!(events, expected);
+    match events[1] {
+        Summary::Url(ref url_entry, _) => {
+            assert_eq!(url_entry.loc.get_url().unwrap().as_str(),
+                       "http://www.example.com/search?q=a&b&page=2")
+        }
+        ref other => panic!("unexpected event {:?}", other),
+    }
+}
@@ -0,0 +1,199 @@
+#![cfg(feature = "async")]
+extern crate chrono;
+extern crate sitemap;
+extern crate tokio;
+
+use chrono::DateTime;
+use sitemap::Error;
+use sitemap::async_writer::{AsyncSiteMapIndexWriter, AsyncUrlSetWriter};
+use sitemap::structs::{ChangeFreq, SiteMapEntry, UrlEntry};
+use sitemap::writer::{LimitPolicy, SiteMapWriter};
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::AsyncWrite;
+
+fn urls(count: usize) -> Vec<UrlEntry> {
+    let date = DateTime::parse_from_rfc3339("2016-07-08T09:10:11+00:00").unwrap();
+    (0..count)
+        .map(|i| {
+            UrlEntry::builder()
+                .loc(format!("http://www.example.com/page{}.html", i))
+                .changefreq(ChangeFreq::Daily)
+                .priority(0.2)
+                .lastmod(date)
+                .build()
+                .expect("valid")
+        })
+        .collect()
+}
+
+fn sitemaps(count: usize) -> Vec<SiteMapEntry> {
+    let date = DateTime::parse_from_rfc3339("2016-07-18T09:10:11+00:00").unwrap();
+    (0..count)
+        .map(|i| {
+            SiteMapEntry::builder()
+                .loc(format!("http://www.example.com/sitemap{}.xml", i))
+                .lastmod(date)
+                .build()
+                .expect("valid")
+        })
+        .collect()
+}
+
+fn sync_urlset(urls: Vec<UrlEntry>) -> Vec<u8> {
+    let mut writer = SiteMapWriter::new(Vec::new()).start_urlset().expect("start urlset");
+    for url in urls {
+        writer.url(url).expect("can write the url");
+    }
+    writer.end().expect("close urlset")
+}
+
+/// Stream accepting a few bytes at a time and counting the writes.
+struct ChunkedWriter {
+    data: Vec<u8>,
+    writes: usize,
+    flushed: bool,
+}
+
+impl AsyncWrite for ChunkedWriter {
+    fn poll_write(mut self: Pin<&mut Self>,
+                  _cx: &mut Context,
+                  buf: &[u8])
+                  -> Poll<io::Result<usize>> {
+        let written = buf.len().min(1000);
+        self.data.extend_from_slice(&buf[..written]);
+        self.writes += 1;
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, _cx: &mut Context) -> Poll<io::Result<()>> {
+        self.flushed = true;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[tokio::test]
+async fn test_async_urlset_matches_sync() {
+    for count in &[0, 1, 2, 500] {
+        let mut writer = AsyncUrlSetWriter::new(Vec::new()).expect("start urlset");
+        for url in urls(*count) {
+            writer.url(url).await.expect("can write the url");
+        }
+        let output = writer.end().await.expect("close urlset");
+        assert_eq!(output, sync_urlset(urls(*count)));
+    }
+}
+
+#[tokio::test]
+async fn test_async_sitemapindex_matches_sync() {
+    let mut expected = SiteMapWriter::new(Vec::new())
+        .start_sitemapindex()
+        .expect("start sitemapindex");
+    for sitemap in sitemaps(300) {
+        expected.sitemap(sitemap).expect("can write the sitemap");
+    }
+    let expected = expected.end().expect("close sitemapindex");
+
+    let mut writer = AsyncSiteMapIndexWriter::new(Vec::new()).expect("start sitemapindex");
+    for sitemap in sitemaps(300) {
+        writer.sitemap(sitemap).await.expect("can write the sitemap");
+    }
+    let output = writer.end().await.expect("close sitemapindex");
+    assert_eq!(output, expected);
+}
+
+#[tokio::test]
+async fn test_async_writer_buffers_output() {
+    let out = ChunkedWriter {
+        data: Vec::new(),
+        writes: 0,
+        flushed: false,
+    };
+    let mut writer = AsyncUrlSetWriter::new(out).expect("start urlset");
+    for url in urls(500) {
+        writer.url(url).await.expect("can write the url");
+    }
+    let out = writer.end().await.expect("close urlset");
+    let expected = sync_urlset(urls(500));
+    assert_eq!(out.data, expected);
+    assert!(out.flushed);
+    // partial writes of 1000 bytes, never one write per url
+    assert!(out.writes <= expected.len() / 1000 + 10);
+}
+
+#[tokio::test]
+async fn test_async_writer_limits() {
+    let mut writer = AsyncUrlSetWriter::new(Vec::new())
+        .expect("start urlset")
+        .limit_policy(LimitPolicy::Error)
+        .max_urls(2);
+    let mut urls = urls(3).into_iter();
+    writer.url(urls.next().unwrap()).await.expect("can write the url");
+    writer.url(urls.next().unwrap()).await.expect("can write the url");
+    match writer.url(urls.next().unwrap()).await {
+        Err(Error::LimitExceeded { limit: 2, .. }) => {}
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+/// Stream failing once `capacity` bytes have been written.
+struct FullWriter {
+    capacity: usize,
+}
+
+impl AsyncWrite for FullWriter {
+    fn poll_write(mut self: Pin<&mut Self>,
+                  _cx: &mut Context,
+                  buf: &[u8])
+                  -> Poll<io::Result<usize>> {
+        if self.capacity == 0 {
+            return Poll::Ready(Err(io::Error::other("stream full")));
+        }
+        let written = buf.len().min(self.capacity);
+        self.capacity -= written;
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[tokio::test]
+async fn test_async_writer_after_failed_write() {
+    let mut writer = AsyncUrlSetWriter::new(FullWriter { capacity: 10000 }).expect("start urlset");
+    let mut urls = urls(500).into_iter();
+    for url in urls.by_ref() {
+        if let Err(error) = writer.url(url).await {
+            assert!(matches!(error, Error::Io(_)), "{:?}", error);
+            break;
+        }
+    }
+    match writer.url(urls.next().unwrap()).await {
+        Err(error @ Error::State(_)) => {
+            assert_eq!(error.to_string(), "Invalid writer state: url() called after a failed write")
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert!(matches!(writer.end().await, Err(Error::State("end() called after a failed write"))));
+
+    let mut writer = AsyncSiteMapIndexWriter::new(FullWriter { capacity: 10000 }).expect("start sitemapindex");
+    let mut sitemaps = sitemaps(300).into_iter();
+    for sitemap in sitemaps.by_ref() {
+        if writer.sitemap(sitemap).await.is_err() {
+            break;
+        }
+    }
+    assert!(matches!(writer.sitemap(sitemaps.next().unwrap()).await,
+                     Err(Error::State("sitemap() called after a failed write"))));
+    assert!(matches!(writer.end().await, Err(Error::State("end() called after a failed write"))));
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "borrowed")]
+extern crate sitemap;
+
+use sitemap::Error;
+use sitemap::borrowed::{BorrowedEvent, BorrowedReader};
+use sitemap::reader::{SiteMapEvent, SiteMapReader};
+use sitemap::structs::{LastMod, Location};
+use std::borrow::Cow;
+use std::fs;
+
+#[test]
+fn test_borrowed_matches_owned() {
+    let document = fs::read_to_string("tests/documents/sitemap1.xml").unwrap();
+    let mut urls = Vec::new();
+    let mut sitemaps = Vec::new();
+    for event in BorrowedReader::new(&document) {
+        match event.expect("valid event") {
+            BorrowedEvent::Url(url) => urls.push(url.to_entry()),
+            BorrowedEvent::SiteMap(sitemap) => sitemaps.push(sitemap.to_entry()),
+        }
+    }
+    let mut expected_urls = Vec::new();
+    let mut expected_sitemaps = Vec::new();
+    for event in SiteMapReader::new(document.as_bytes()) {
+        match event {
+            Ok(SiteMapEvent::Url(url)) => expected_urls.push(url),
+            Ok(SiteMapEvent::SiteMap(sitemap)) => expected_sitemaps.push(sitemap),
+            Ok(_) => {}
+            Err(error) => panic!("unexpected error {:?}", error),
+        }
+    }
+    assert_eq!(urls, expected_urls);
+    assert_eq!(sitemaps, expected_sitemaps);
+}
+
+#[test]
+fn test_borrowed_text() {
+    let document = "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" \
+                    xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\">\
+                    <url>\n  <loc> http://www.example.com/ </loc><lastmod>2024-01-05</lastmod>\
+                    <image:image><image:loc>http://www.example.com/image.jpg</image:loc></image:image>\
+                    </url>\
+                    <url><loc>http://www.example.com/?a=1&amp;b=<![CDATA[2]]></loc><priority/></url>\
+                    </urlset>";
+    let events: Vec<BorrowedEvent> = BorrowedReader::new(document).map(|event| event.expect("valid event")).collect();
+    let url = match events[0] {
+        BorrowedEvent::Url(ref url) => url,
+        _ => panic!("expected a url"),
+    };
+    match url.loc {
+        Some(Cow::Borrowed(loc)) => assert_eq!(loc, "http://www.example.com/"),
+        ref loc => panic!("unexpected loc {:?}", loc),
+    }
+    assert_eq!(url.loc_url().unwrap().unwrap().as_str(), "http://www.example.com/");
+    assert!(matches!(url.lastmod_value(), Some(Ok(LastMod::Date(_)))));
+    assert_eq!(url.changefreq, None);
+    let url = match events[1] {
+        BorrowedEvent::Url(ref url) => url,
+        _ => panic!("expected a url"),
+    };
+    match url.loc {
+        Some(Cow::Owned(ref loc)) => assert_eq!(loc, "http://www.example.com/?a=1&b=2"),
+        ref loc => panic!("unexpected loc {:?}", loc),
+    }
+    assert_eq!(url.priority.as_deref(), Some(""));
+    assert_eq!(url.to_entry().loc, Location::Url("http://www.example.com/?a=1&b=2".parse().unwrap()));
+    assert_eq!(events.len(), 2);
+}
+
+#[test]
+fn test_borrowed_error() {
+    let document = "<urlset>\n<url><loc>http://www.example.com/&unknown;</loc></url>\n</urlset>";
+    let mut reader = BorrowedReader::new(document);
+    match reader.next() {
+        Some(Err(Error::Parse { line: 2, .. })) => {}
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert!(reader.next().is_none());
+
+    let results: Vec<_> = BorrowedReader::new("<urlset><url><loc>a</url></urlset>").collect();
+    assert!(matches!(results[..], [Err(Error::Parse { line: 1, .. })]));
+}
@@ -0,0 +1,126 @@
+extern crate sitemap;
+
+use sitemap::reader::{ReaderOptions, SiteMapEvent, SiteMapReader};
+use sitemap::structs::{ImageEntry, UrlEntry};
+use sitemap::writer::{Declaration, Indent, SiteMapWriter, WriterOptions};
+
+static COMMENTED: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                          <!-- prolog -->\n\
+                          <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" \
+                          xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\"><!-- root -->\n\
+                          <url><!-- url --><loc>http://www.example.com/<!-- loc -->a</loc>\
+                          <priority><!-- priority -->0.5</priority>\
+                          <image:image><!-- image --><image:loc>http://www.example.com/a.png</image:loc></image:image>\
+                          </url>\n<!-- between -->\n\
+                          <url><loc>http://www.example.com/b</loc><!-- end of url --></url>\n\
+                          </urlset>\n<!-- after -->\n";
+
+static PLAIN: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                      <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" \
+                      xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\">\n\
+                      <url><loc>http://www.example.com/a</loc><priority>0.5</priority>\
+                      <image:image><image:loc>http://www.example.com/a.png</image:loc></image:image></url>\n\
+                      <url><loc>http://www.example.com/b</loc></url>\n\
+                      </urlset>\n";
+
+fn events(document: &str, options: ReaderOptions) -> Vec<String> {
+    SiteMapReader::with_options(document.as_bytes(), options)
+        .map(|event| match event {
+            Ok(SiteMapEvent::Url(url)) => format!("Url {:?} {:?} {:?}", url.loc, url.priority, url.images),
+            Ok(event) => format!("{:?}", event),
+            Err(error) => format!("{:?}", error),
+        })
+        .collect()
+}
+
+/// The document as read by quick-xml with the `fast-xml` feature, and
+/// declared in ASCII as read by xml-rs.
+fn both_parsers(document: &str) -> [String; 2] {
+    [document.to_string(), document.replacen("encoding=\"UTF-8\"", "encoding=\"ascii\"", 1)]
+}
+
+#[test]
+fn test_comments_skipped() {
+    for (commented, plain) in both_parsers(COMMENTED).iter().zip(both_parsers(PLAIN).iter()) {
+        let expected = events(plain, ReaderOptions::default());
+        assert_eq!(expected.len(), 2);
+        assert_eq!(events(commented, ReaderOptions::default()), expected);
+    }
+}
+
+#[test]
+fn test_comments_emitted() {
+    let options = ReaderOptions {
+        emit_comments: true,
+        ..ReaderOptions::default()
+    };
+    for (commented, plain) in both_parsers(COMMENTED).iter().zip(both_parsers(PLAIN).iter()) {
+        let urls = events(plain, ReaderOptions::default());
+        let comment = |text: &str| format!("{:?}", SiteMapEvent::Comment(text.to_string()));
+        let expected = vec![comment("prolog"),
+                            comment("root"),
+                            comment("url"),
+                            comment("loc"),
+                            comment("priority"),
+                            comment("image"),
+                            urls[0].clone(),
+                            comment("between"),
+                            comment("end of url"),
+                            urls[1].clone(),
+                            comment("after")];
+        assert_eq!(events(commented, options), expected, "{}", commented);
+    }
+
+    let index = "<sitemapindex><!-- a --><sitemap><loc>http://www.example.com/a.xml</loc></sitemap>\
+                 <!--b--></sitemapindex>";
+    let read = events(index, options);
+    assert_eq!(read.len(), 3);
+    assert_eq!(read[0], "Comment(\"a\")");
+    assert!(read[1].contains("a.xml"), "{}", read[1]);
+    assert_eq!(read[2], "Comment(\"b\")");
+}
+
+#[test]
+fn test_comments_written() {
+    let options = WriterOptions {
+        indent: Indent::None,
+        ..WriterOptions::default()
+    };
+    let mut urlset = SiteMapWriter::with_options(Vec::new(), options).start_urlset().unwrap();
+    urlset.comment("generated -- do not edit").unwrap();
+    let mut url = UrlEntry::builder().loc("http://www.example.com/a").build().unwrap();
+    url.images.push(ImageEntry::with_loc("http://www.example.com/a.png".parse().unwrap()));
+    urlset.url(url).unwrap();
+    urlset.comment("a---b-").unwrap();
+    urlset.url(UrlEntry::builder().loc("http://www.example.com/b").build().unwrap()).unwrap();
+    let written = String::from_utf8(urlset.end().unwrap()).unwrap();
+    assert!(written.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?><!-- generated - - do not edit --><urlset"),
+            "{}",
+            written);
+    assert!(written.contains("</url><!-- a- - -b- --><url>"), "{}", written);
+
+    let read_options = ReaderOptions {
+        emit_comments: true,
+        ..ReaderOptions::default()
+    };
+    let read = events(&written, read_options);
+    assert_eq!(read.len(), 4);
+    assert_eq!(read[0], "Comment(\"generated - - do not edit\")");
+    assert!(read[1].contains("a.png"), "{}", read[1]);
+    assert_eq!(read[2], "Comment(\"a- - -b-\")");
+
+    let mut index = SiteMapWriter::with_options(Vec::new(), options).start_sitemapindex().unwrap();
+    index.comment("index").unwrap();
+    let written = String::from_utf8(index.end().unwrap()).unwrap();
+    assert!(written.ends_with("<sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\"><!-- index --></sitemapindex>"), "{}", written);
+
+    let options = WriterOptions {
+        xml_declaration: Declaration::Custom { encoding: "US-ASCII", standalone: None },
+        ..options
+    };
+    let mut urlset = SiteMapWriter::with_options(Vec::new(), options).start_urlset().unwrap();
+    assert!(urlset.comment("\u{e9}t\u{e9}").is_err());
+    urlset.comment("ascii").unwrap();
+    let written = String::from_utf8(urlset.end().unwrap()).unwrap();
+    assert!(written.starts_with("<?xml version=\"1.0\" encoding=\"US-ASCII\"?><!-- ascii --><urlset"), "{}", written);
+}
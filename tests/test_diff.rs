@@ -0,0 +1,86 @@
+extern crate sitemap;
+extern crate chrono;
+
+use chrono::{DateTime, NaiveDate};
+use sitemap::diff::{self, DiffOptions, FieldChange};
+use sitemap::reader::SiteMapReader;
+use sitemap::structs::{CanonicalizeOptions, ChangeFreq, LastMod, UrlEntry};
+use sitemap::util::LocOptions;
+
+fn locs(entries: &[UrlEntry]) -> Vec<String> {
+    entries.iter().map(|entry| entry.loc.raw().unwrap()).collect()
+}
+
+#[test]
+fn test_diff() {
+    let date = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+    let midnight = DateTime::parse_from_rfc3339("2020-01-01T00:00:00Z").unwrap();
+    let old = vec![UrlEntry::builder().loc("http://www.example.com/a").lastmod_date(date).build().unwrap(),
+                   UrlEntry::builder().loc("http://www.example.com/b").build().unwrap(),
+                   UrlEntry::builder().loc("http://www.example.com/c").priority(0.5).build().unwrap(),
+                   UrlEntry::builder().loc("http://www.example.com/d").build().unwrap(),
+                   UrlEntry::builder().loc("http://www.example.com/e#top").build().unwrap()];
+    let new = vec![UrlEntry::builder().loc("http://www.example.com/f").build().unwrap(),
+                   UrlEntry::builder().loc("http://www.example.com/c")
+                       .changefreq(ChangeFreq::Daily)
+                       .priority(0.7)
+                       .build()
+                       .unwrap(),
+                   UrlEntry::builder().loc("http://www.example.com/a").lastmod(midnight).build().unwrap(),
+                   UrlEntry::builder().loc("HTTP://WWW.EXAMPLE.COM:80/b").build().unwrap(),
+                   UrlEntry::builder().loc("http://www.example.com/f").priority(0.1).build().unwrap(),
+                   UrlEntry::builder().loc("http://www.example.com/e").build().unwrap()];
+    let diff = diff::diff(&old, &new, &DiffOptions::default());
+    assert_eq!(locs(&diff.added), ["http://www.example.com/f", "http://www.example.com/e"]);
+    assert_eq!(diff.added[0].priority, new[0].priority);
+    assert_eq!(locs(&diff.removed), ["http://www.example.com/d", "http://www.example.com/e#top"]);
+    assert_eq!(diff.changed.len(), 1);
+    assert_eq!(diff.changed[0].loc, new[1].loc);
+    assert_eq!(diff.changed[0].fields,
+               [FieldChange::ChangeFreq {
+                    old: ChangeFreq::None,
+                    new: ChangeFreq::Daily,
+                },
+                FieldChange::Priority {
+                    old: old[2].priority.clone(),
+                    new: new[1].priority.clone(),
+                }]);
+
+    let options = DiffOptions {
+        canonicalize: CanonicalizeOptions { strip_fragment: true, ..CanonicalizeOptions::default() },
+        loc: LocOptions::default(),
+    };
+    let diff = diff::diff(&old, &new, &options);
+    assert_eq!(locs(&diff.added), ["http://www.example.com/f"]);
+    assert_eq!(locs(&diff.removed), ["http://www.example.com/d"]);
+    assert!(diff::diff(&new, &new, &options).is_empty());
+}
+
+#[test]
+fn test_diff_readers() {
+    let old = "<urlset>\
+               <url><loc>http://www.example.com/a</loc><lastmod>2020-01-01</lastmod></url>\
+               <url><loc>http://www.example.com/b</loc></url>\
+               </urlset>";
+    let new = "<urlset>\
+               <url><loc>http://www.example.com/a</loc><lastmod>2020-01-02</lastmod></url>\
+               <url><loc>http://www.example.com/c</loc></url>\
+               </urlset>";
+    let diff = diff::diff_readers(SiteMapReader::new(old.as_bytes()),
+                                  SiteMapReader::new(new.as_bytes()),
+                                  &DiffOptions::default())
+        .unwrap();
+    assert_eq!(locs(&diff.added), ["http://www.example.com/c"]);
+    assert_eq!(locs(&diff.removed), ["http://www.example.com/b"]);
+    assert_eq!(diff.changed[0].fields,
+               [FieldChange::LastMod {
+                    old: LastMod::Date(NaiveDate::from_ymd_opt(2020, 1, 1).unwrap()),
+                    new: LastMod::Date(NaiveDate::from_ymd_opt(2020, 1, 2).unwrap()),
+                }]);
+
+    let broken = "<urlset><url><loc>http://www.example.com/a</loc></urlset>";
+    assert!(diff::diff_readers(SiteMapReader::new(old.as_bytes()),
+                               SiteMapReader::new(broken.as_bytes()),
+                               &DiffOptions::default())
+        .is_err());
+}
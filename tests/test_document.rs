@@ -0,0 +1,207 @@
+extern crate sitemap;
+extern crate url;
+
+use sitemap::document::{SiteMap, SiteMapIndex};
+use sitemap::reader::ReaderOptions;
+use sitemap::structs::{Priority, SiteMapEntry, Stylesheet, UrlEntry};
+#[cfg(feature = "fs")]
+use sitemap::Error;
+#[cfg(feature = "fs")]
+use std::convert::TryFrom;
+#[cfg(feature = "fs")]
+use std::fs;
+#[cfg(feature = "fs")]
+use std::path::{Path, PathBuf};
+use url::Url;
+
+static URLSET: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                       <?xml-stylesheet type=\"text/xsl\" href=\"/sitemap.xsl\"?>\n\
+                       <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" \
+                       xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\">\
+                       <url><loc>http://www.example.com/</loc><priority>0.5</priority></url>\
+                       <url><loc>http://www.example.com/a</loc></url>\
+                       <url><loc>http://www.example.com/b</loc></url></urlset>";
+
+static INDEX: &str = "<sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\
+                      <sitemap><loc>http://www.example.com/a.xml</loc></sitemap>\
+                      <sitemap><loc>http://www.example.com/b.xml</loc></sitemap></sitemapindex>";
+
+fn locs(sitemap: &SiteMap) -> Vec<String> {
+    sitemap.entries().iter().map(|url| url.loc.get_url().unwrap().to_string()).collect()
+}
+
+/// Empty directory of the files of `test`.
+#[cfg(feature = "fs")]
+fn directory(test: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("sitemap-{}-{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+fn url(loc: &str) -> UrlEntry {
+    UrlEntry::builder().loc(loc).build().unwrap()
+}
+
+#[test]
+fn test_document_read() {
+    let sitemap = SiteMap::read_from(URLSET.as_bytes()).unwrap();
+    assert_eq!(locs(&sitemap),
+               vec!["http://www.example.com/", "http://www.example.com/a", "http://www.example.com/b"]);
+    assert_eq!(sitemap.namespaces(),
+               [("".to_string(), "http://www.sitemaps.org/schemas/sitemap/0.9".to_string()),
+                ("image".to_string(), "http://www.google.com/schemas/sitemap-image/1.1".to_string())]);
+    assert_eq!(sitemap.stylesheets(), [Stylesheet::new("/sitemap.xsl")]);
+    assert_eq!(sitemap.options(), ReaderOptions::default());
+    assert_eq!(SiteMap::read_from(INDEX.as_bytes()).unwrap_err().to_string(),
+               "expected urlset, found sitemapindex");
+    assert_eq!(SiteMapIndex::read_from(URLSET.as_bytes()).unwrap_err().to_string(),
+               "expected sitemapindex, found urlset");
+    assert!(SiteMap::read_from("<urlset><url>".as_bytes()).is_err());
+}
+
+#[test]
+fn test_document_edit() {
+    let mut sitemap = SiteMap::read_from(URLSET.as_bytes()).unwrap();
+    sitemap.entries_mut().retain(|url| url.loc.get_url().unwrap().path() != "/a");
+    let mut first = url("HTTP://WWW.EXAMPLE.COM:80/");
+    first.priority = Priority::Value(0.9);
+    let replaced = sitemap.upsert(first).unwrap();
+    assert_eq!(replaced.priority, Priority::Value(0.5));
+    assert_eq!(sitemap.upsert(url("http://www.example.com/c")), None);
+    sitemap.push(url("http://www.example.com/b"));
+    assert_eq!(locs(&sitemap),
+               vec!["http://www.example.com/",
+                    "http://www.example.com/b",
+                    "http://www.example.com/c",
+                    "http://www.example.com/b"]);
+    assert_eq!(sitemap.entries()[0].priority, Priority::Value(0.9));
+    assert_eq!(sitemap.remove_by_loc(&Url::parse("http://www.example.com/b").unwrap()), 2);
+    assert_eq!(sitemap.remove_by_loc(&Url::parse("http://www.example.com/b").unwrap()), 0);
+    assert_eq!(locs(&sitemap), vec!["http://www.example.com/", "http://www.example.com/c"]);
+}
+
+#[test]
+fn test_document_write() {
+    let sitemap = SiteMap::read_from(URLSET.as_bytes()).unwrap();
+    let written = sitemap.to_string().unwrap();
+    assert!(written.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                                 <?xml-stylesheet type=\"text/xsl\" href=\"/sitemap.xsl\"?>"),
+            "{}", written);
+    assert!(written.contains("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" \
+                              xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\">"),
+            "{}", written);
+    let read = SiteMap::read_from(written.as_bytes()).unwrap();
+    assert_eq!(read.entries(), sitemap.entries());
+    assert_eq!(read.namespaces(), sitemap.namespaces());
+    assert_eq!(read.stylesheets(), sitemap.stylesheets());
+    assert_eq!(sitemap.write_to(Vec::new()).unwrap(), written.into_bytes());
+    let mut empty = SiteMap::new();
+    empty.push(url("http://www.example.com/"));
+    assert_eq!(empty.to_string().unwrap(),
+               "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n  <url>\n    \
+                <loc>http://www.example.com/</loc>\n  </url>\n</urlset>");
+}
+
+#[test]
+fn test_document_index() {
+    let mut index = SiteMapIndex::read_from(INDEX.as_bytes()).unwrap();
+    assert_eq!(index.entries().len(), 2);
+    let sitemap = SiteMapEntry::builder().loc("http://www.example.com/c.xml").build().unwrap();
+    assert_eq!(index.upsert(sitemap.clone()), None);
+    assert!(index.upsert(sitemap).is_some());
+    assert_eq!(index.remove_by_loc(&Url::parse("http://www.example.com/a.xml").unwrap()), 1);
+    let read = SiteMapIndex::read_from(index.to_string().unwrap().as_bytes()).unwrap();
+    assert_eq!(read.entries(), index.entries());
+    let locs: Vec<String> = read.into_entries()
+        .into_iter()
+        .map(|sitemap| sitemap.loc.get_url().unwrap().to_string())
+        .collect();
+    assert_eq!(locs, vec!["http://www.example.com/b.xml", "http://www.example.com/c.xml"]);
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_document_from_path() {
+    let sitemap = SiteMap::from_path(Path::new("tests/documents/sitemap1.xml")).unwrap();
+    assert_eq!(SiteMap::read_from(fs::File::open("tests/documents/sitemap1.xml").unwrap()).unwrap(),
+               sitemap);
+    for file in ["sitemap_bom.xml", "sitemap_leading_whitespace.xml"].iter() {
+        let path = Path::new("tests/documents").join(file);
+        assert!(!SiteMap::try_from(path.as_path()).unwrap().entries().is_empty(), "{}", file);
+    }
+    let directory = directory("from-path");
+    let path = directory.join("sitemap.txt");
+    fs::write(&path, "\u{feff}http://www.example.com/\n\n  http://www.example.com/a\n").unwrap();
+    assert_eq!(locs(&SiteMap::from_path(&path).unwrap()),
+               vec!["http://www.example.com/", "http://www.example.com/a"]);
+    assert_eq!(SiteMapIndex::from_path(&path).unwrap_err().to_string(),
+               format!("{}: expected sitemapindex, found text sitemap", path.display()));
+    let error = SiteMap::from_path(Path::new("tests/documents/sitemap.txt")).unwrap_err();
+    assert_eq!(error.to_string(),
+               "tests/documents/sitemap.txt: 6:1 invalid url 'catalog.html'");
+    assert_eq!(error.position().map(|position| position.line), Some(6));
+    match SiteMap::from_path(&directory.join("missing.xml")) {
+        Err(Error::File { ref path, ref error }) if path == &directory.join("missing.xml") => {
+            match **error {
+                Error::Io(_) => {}
+                ref error => panic!("unexpected error {:?}", error),
+            }
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_document_write_to_path() {
+    let directory = directory("write-to-path");
+    let path = directory.join("sitemap.xml");
+    fs::write(&path, "old").unwrap();
+    let sitemap = SiteMap::read_from(URLSET.as_bytes()).unwrap();
+    sitemap.write_to_path(&path).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), sitemap.to_string().unwrap());
+    assert_eq!(SiteMap::from_path(&path).unwrap(), sitemap);
+    let index = SiteMapIndex::read_from(INDEX.as_bytes()).unwrap();
+    index.write_to_path(&directory.join("index.xml")).unwrap();
+    assert_eq!(SiteMapIndex::from_path(&directory.join("index.xml")).unwrap().entries(),
+               index.entries());
+    let mut files: Vec<_> = fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    assert_eq!(files, vec!["index.xml", "sitemap.xml"]);
+
+    let error = sitemap.write_to_path(&directory.join("missing").join("sitemap.xml")).unwrap_err();
+    assert!(error.to_string().starts_with(&format!("{}: I/O error",
+                                                   directory.join("missing").join("sitemap.xml").display())),
+            "{}",
+            error);
+    assert_eq!(fs::read_dir(&directory).unwrap().count(), 2);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[cfg(all(feature = "fs", feature = "gzip"))]
+#[test]
+fn test_document_gzip_path() {
+    let sitemap = SiteMap::from_path(Path::new("tests/documents/sitemap_large.xml.gz")).unwrap();
+    assert!(!sitemap.entries().is_empty());
+    let directory = directory("gzip-path");
+    let path = directory.join("sitemap.xml.gz");
+    sitemap.write_to_path(&path).unwrap();
+    assert_eq!(fs::read(&path).unwrap()[..2], [0x1f, 0x8b]);
+    assert_eq!(SiteMap::from_path(&path).unwrap().entries(), sitemap.entries());
+    // compressed content is detected without the extension
+    fs::rename(&path, directory.join("sitemap")).unwrap();
+    assert_eq!(SiteMap::from_path(&directory.join("sitemap")).unwrap().entries(), sitemap.entries());
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[cfg(all(feature = "fs", not(feature = "gzip")))]
+#[test]
+fn test_document_gzip_path_without_feature() {
+    assert_eq!(SiteMap::from_path(Path::new("tests/documents/sitemap_large.xml.gz")).unwrap_err().to_string(),
+               "tests/documents/sitemap_large.xml.gz: gzip compressed sitemap, enable the gzip feature");
+}
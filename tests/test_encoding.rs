@@ -0,0 +1,86 @@
+#![cfg(feature = "encodings")]
+extern crate sitemap;
+
+use sitemap::encoding::DecodingReader;
+use sitemap::reader::SiteMapReader;
+use sitemap::structs::UrlEntry;
+use std::fs::File;
+use std::io::{self, Read};
+
+/// Reader returning a byte per call, to split every character.
+struct ByteReader<R: Read>(R);
+
+impl<R: Read> Read for ByteReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(1);
+        self.0.read(&mut buf[..len])
+    }
+}
+
+fn locs(urls: &[UrlEntry]) -> Vec<String> {
+    urls.iter().map(|url| url.loc.get_url().unwrap().to_string()).collect()
+}
+
+fn read_urls<R: Read>(source: R) -> Vec<UrlEntry> {
+    SiteMapReader::new(source).into_url_iter().map(Result::unwrap).collect()
+}
+
+#[test]
+fn test_decoding_reader_detects_encoding() {
+    let expected = vec!["https://www.example.com/caf%C3%A9/cr%C3%A8me-br%C3%BBl%C3%A9e",
+                        "https://xn--e1afmkfd.xn--p1ai/%D1%81%D1%82%D1%80%D0%B0%D0%BD%D0%B8%D1%86%D0%B0?q=%C3%BCber",
+                        "https://www.example.com/%E6%97%A5%E6%9C%AC%E8%AA%9E/%E3%83%9A%E3%83%BC%E3%82%B8"];
+    for &(file_name, encoding) in &[("tests/documents/sitemap_utf16le.xml", "UTF-16LE"),
+                                    ("tests/documents/sitemap_utf16be.xml", "UTF-16BE")] {
+        let mut reader = DecodingReader::new(File::open(file_name).unwrap());
+        let mut text = String::new();
+        reader.read_to_string(&mut text).unwrap();
+        assert_eq!(reader.encoding(), Some(encoding));
+        assert!(text.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"), "{}", text);
+        assert_eq!(locs(&read_urls(text.as_bytes())), expected, "{}", file_name);
+        let reader = DecodingReader::new(ByteReader(File::open(file_name).unwrap()));
+        assert_eq!(locs(&read_urls(reader)), expected, "{}", file_name);
+    }
+}
+
+#[test]
+fn test_decoding_reader_declared_encoding() {
+    let reader = DecodingReader::new(File::open("tests/documents/sitemap_windows1252.xml").unwrap());
+    let urls = read_urls(reader);
+    assert_eq!(urls[1].loc.get_url().unwrap().as_str(),
+               "https://www.example.com/l%E2%80%99%C3%A9t%C3%A9%E2%82%AC");
+    let reader = DecodingReader::new(File::open("tests/documents/sitemap_latin1.xml").unwrap());
+    let urls = read_urls(reader);
+    assert_eq!(urls[1].loc.get_url().unwrap().as_str(), "https://www.example.com/gr%C3%BC%C3%9Fe");
+
+    // without a declaration the document is UTF-8, the BOM is dropped
+    let data: &[u8] = b"\xef\xbb\xbf<urlset><url><loc>http://www.example.com/\xc3\xa9</loc></url></urlset>";
+    let mut reader = DecodingReader::new(data);
+    let mut text = String::new();
+    reader.read_to_string(&mut text).unwrap();
+    assert_eq!(reader.encoding(), Some("UTF-8"));
+    assert!(text.starts_with("<urlset>"));
+    let data: &[u8] = b"<?xml version='1.0' encoding = 'UTF-16'?><urlset/>";
+    let mut text = String::new();
+    DecodingReader::new(data).read_to_string(&mut text).unwrap();
+    assert_eq!(text, "<?xml version='1.0' encoding = 'UTF-8'?><urlset/>");
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_async_decoding_reader() {
+    use sitemap::async_reader::AsyncSiteMapReader;
+    use sitemap::encoding::AsyncDecodingReader;
+    use sitemap::reader::SiteMapEvent;
+    use tokio::io::BufReader;
+
+    let data = std::fs::read("tests/documents/sitemap_utf16le.xml").unwrap();
+    let mut reader = AsyncSiteMapReader::new(AsyncDecodingReader::new(BufReader::new(&data[..])));
+    let mut urls = Vec::new();
+    while let Some(event) = reader.next_event().await {
+        if let Ok(SiteMapEvent::Url(url_entry)) = event {
+            urls.push(url_entry);
+        }
+    }
+    assert_eq!(locs(&urls), locs(&read_urls(&data[..])));
+}
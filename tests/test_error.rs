@@ -0,0 +1,89 @@
+extern crate sitemap;
+extern crate url;
+
+use sitemap::Error;
+use sitemap::reader::{SiteMapReader, SiteMapEvent};
+use sitemap::structs::UrlEntry;
+use std::error;
+use std::io;
+
+fn count_urls(data: &[u8]) -> Result<usize, Box<dyn error::Error>> {
+    let mut count = 0;
+    for entity in SiteMapReader::new(data) {
+        match entity {
+            Ok(SiteMapEvent::Url(_)) => count += 1,
+            Ok(_) => {}
+            Err(error) => return Err(error.into()),
+        }
+    }
+    UrlEntry::builder().loc("http://www.example.com/").priority(0.5).build()?;
+    Ok(count)
+}
+
+#[test]
+fn test_error_propagation() {
+    let data = b"<urlset><url><loc>http://www.example.com/</loc></url></urlset>";
+    assert_eq!(count_urls(data).unwrap(), 1);
+    let error = count_urls(b"<urlset><url></urlset>").unwrap_err();
+    let error = error.downcast_ref::<Error>().expect("crate error");
+    match *error {
+        Error::XmlReadError(_) => {}
+        ref other => panic!("unexpected error {:?}", other),
+    }
+}
+
+#[test]
+fn test_error_source() {
+    let error = Error::from(url::Url::parse("not a url").unwrap_err());
+    assert!(error::Error::source(&error).is_some());
+    assert!(error.to_string().starts_with("Url parse error"));
+
+    let error = Error::from(io::Error::other("broken pipe"));
+    assert_eq!(error::Error::source(&error).unwrap().to_string(), "broken pipe");
+
+    let error = Error::invalid_value("priority", "2", "priority should be between 0 and 1");
+    assert!(error::Error::source(&error).is_none());
+    assert_eq!(error.to_string(),
+               "Invalid <priority> value '2': priority should be between 0 and 1");
+
+    let build_error = UrlEntry::builder().priority(2.0).build().unwrap_err();
+    let error = Error::from(build_error.clone());
+    assert_eq!(error::Error::source(&error).unwrap().to_string(), build_error.to_string());
+
+    let error = Error::File {
+        path: "sitemap.xml".into(),
+        error: Box::new(Error::Invalid("empty".to_string())),
+    };
+    assert_eq!(error::Error::source(&error).unwrap().to_string(), "empty");
+    assert_eq!(error.to_string(), "sitemap.xml: empty");
+}
+
+#[test]
+fn test_error_raw_values() {
+    use sitemap::structs::{LastMod, Location, Priority};
+    let data = b"<urlset><url><loc>/relative.html</loc><lastmod>2016-13-01</lastmod>\
+                 <priority>high</priority></url></urlset>";
+    let url_entry = match SiteMapReader::new(&data[..]).next() {
+        Some(Ok(SiteMapEvent::Url(url_entry))) => url_entry,
+        other => panic!("unexpected event {:?}", other),
+    };
+    match url_entry.loc {
+        Location::Err { error, ref raw } => {
+            assert_eq!(error, url::ParseError::RelativeUrlWithoutBase);
+            assert_eq!(raw, "/relative.html");
+        }
+        ref other => panic!("unexpected loc {:?}", other),
+    }
+    assert!(matches!(url_entry.lastmod, LastMod::Err { .. }));
+    assert_eq!(url_entry.lastmod.raw(), Some("2016-13-01".to_string()));
+    assert!(matches!(url_entry.priority, Priority::Err { .. }));
+    assert_eq!(url_entry.priority.raw(), Some("high".to_string()));
+    assert_eq!(url_entry.errors().len(), 3);
+
+    assert_eq!(Location::from("http://www.example.com".to_string()).raw(),
+               Some("http://www.example.com/".to_string()));
+    assert_eq!(Location::None.raw(), None);
+    assert_eq!(Priority::from("2".to_string()).raw(), Some("2".to_string()));
+    assert_eq!(LastMod::from("2016-07-08T09:10:11+00:00".to_string()).raw(),
+               Some("2016-07-08T09:10:11+00:00".to_string()));
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "fast-xml")]
+extern crate sitemap;
+
+use sitemap::Error;
+use sitemap::reader::{ReaderOptions, SiteMapReader};
+use std::fs;
+
+const DECLARATION: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>";
+
+/// Events of `document`, errors of the malformed XML only by their kind as
+/// the messages of the parsers differ.
+fn events(document: &str, options: ReaderOptions) -> Vec<String> {
+    SiteMapReader::with_options(document.as_bytes(), options)
+        .map(|event| match event {
+            Ok(event) => format!("{:?}", event),
+            Err(Error::XmlReadError(_)) => "malformed XML".to_string(),
+            Err(error) => format!("{:?}", error),
+        })
+        .collect()
+}
+
+/// Asserts that quick-xml reads `document` like xml-rs, used for the same
+/// document declared in ASCII.
+fn assert_same_events(document: &str, options: ReaderOptions) {
+    assert!(document.starts_with(DECLARATION));
+    let ascii = document.replacen("encoding=\"UTF-8\"", "encoding=\"ascii\"", 1);
+    assert_eq!(events(document, options), events(&ascii, options), "{}", document);
+}
+
+#[test]
+fn test_fast_xml_documents() {
+    let files = ["sitemap1.xml",
+                 "sitemap_alternates.xml",
+                 "sitemap_images.xml",
+                 "sitemap_index_pretty.xml",
+                 "sitemap_namespace_084.xml",
+                 "sitemap_news.xml",
+                 "sitemap_no_namespace.xml",
+                 "sitemap_prefixed.xml",
+                 "sitemap_pretty.xml",
+                 "sitemap_videos.xml"];
+    for file in files.iter() {
+        let document = fs::read_to_string(format!("tests/documents/{}", file)).unwrap();
+        assert!(document.is_ascii(), "{}", file);
+        assert_same_events(&document, ReaderOptions::default());
+    }
+}
+
+#[test]
+fn test_fast_xml_text() {
+    let options = ReaderOptions {
+        root_events: true,
+        ..ReaderOptions::default()
+    };
+    let documents = ["<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" \
+                      xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\">\n\
+                      <url><loc>http://www.example.com/?a=1&amp;b=&#50;&#x33;</loc>\
+                      <image:image><image:loc><![CDATA[http://www.example.com/a.png?x=<1>]]></image:loc>\
+                      <image:title>A &lt;b&gt; &quot;title&quot; &apos;</image:title></image:image>\
+                      <lastmod>2024-01-01</lastmod><priority> 0.5 </priority></url>\n\
+                      <url><loc>http://www.example.com/<![CDATA[cdata]]>/page</loc><changefreq>daily</changefreq></url>\n\
+                      </urlset>",
+                     "<sm:sitemapindex xmlns:sm=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\
+                      <sm:sitemap><sm:loc>http://www.example.com/sitemap.xml</sm:loc></sm:sitemap>\
+                      <sitemap><loc>http://www.example.com/other.xml</loc></sitemap>\
+                      </sm:sitemapindex>",
+                     "<urlset><url><loc>http://www.example.com/</loc><lastmod>yesterday</lastmod>\
+                      <image:image xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\"/>\
+                      </url><url/></urlset>"];
+    for document in documents.iter() {
+        assert_same_events(&format!("{}\n{}", DECLARATION, document), options);
+    }
+    let capture = ReaderOptions {
+        capture_unknown: true,
+        ..ReaderOptions::default()
+    };
+    let document = "<urlset xmlns:geo=\"http://www.google.com/geo/schemas/sitemap/1.0\">\
+                    <url><loc>http://www.example.com/</loc>\
+                    <geo:geo a=\"&quot;1&quot;\"><geo:format>k&amp;l</geo:format><!-- comment --></geo:geo>\
+                    <o:block xmlns:o=\"urn:o\" o:id=\"1\"><![CDATA[<text>]]><plain/></o:block></url></urlset>";
+    assert_same_events(&format!("{}\n{}", DECLARATION, document), capture);
+    let emit = ReaderOptions {
+        emit_unknown: true,
+        ..ReaderOptions::default()
+    };
+    let document = "<urlset xmlns:geo=\"http://www.google.com/geo/schemas/sitemap/1.0\">\
+                    <url><loc>http://www.example.com/</loc><lastmode>2024</lastmode>\
+                    <geo:geo a=\"&quot;1&quot;\"><geo:format>k&amp;l</geo:format></geo:geo></url><urls/></urlset>";
+    assert_same_events(&format!("{}\n{}", DECLARATION, document), emit);
+}
+
+#[test]
+fn test_fast_xml_errors() {
+    let documents = ["<urlset><url><loc>http://www.example.com/</loc></url>",
+                     "<urlset><url><loc>http://www.example.com/</lo></url></urlset>",
+                     "<urlset><url><loc>http://www.example.com/&unknown;</loc></url></urlset>",
+                     "<urlset><url><loc>http://www.example.com/</loc></url></urlset><urlset/>"];
+    for document in documents.iter() {
+        assert_same_events(&format!("{}\n{}", DECLARATION, document), ReaderOptions::default());
+    }
+    let strict = ReaderOptions {
+        strict: true,
+        ..ReaderOptions::default()
+    };
+    let document = "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\
+                    <url><loc>http://www.example.com/</loc><priority>high</priority></url>\
+                    <url><loc>http://www.example.com/next</loc></url></urlset>";
+    assert_same_events(&format!("{}\n{}", DECLARATION, document), strict);
+}
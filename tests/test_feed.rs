@@ -0,0 +1,59 @@
+#![cfg(feature = "feeds")]
+extern crate chrono;
+extern crate sitemap;
+
+use chrono::DateTime;
+use sitemap::Error;
+use sitemap::reader::FeedReader;
+use sitemap::structs::{LastMod, Position};
+use std::fs::File;
+
+#[test]
+fn test_read_rss_feed() {
+    let file = File::open("tests/documents/feed_rss.xml").unwrap();
+    let results: Vec<_> = FeedReader::new(file).collect();
+    assert_eq!(results.len(), 4);
+    let first = results[0].as_ref().unwrap();
+    assert_eq!(first.loc.get_url().unwrap().as_str(), "http://www.example.com/first.html");
+    assert_eq!(first.lastmod.get_time(),
+               Some(DateTime::parse_from_rfc3339("2003-06-10T04:00:00+00:00").unwrap()));
+    assert_eq!(first.position(), Some(Position { line: 7, column: 5 }));
+    assert_eq!(first.field_position("loc"), Some(Position { line: 9, column: 7 }));
+    let second = results[1].as_ref().unwrap();
+    assert_eq!(second.loc.get_url().unwrap().as_str(),
+               "http://www.example.com/second.html?a=1&b=2");
+    assert_eq!(second.lastmod.get_time(),
+               Some(DateTime::parse_from_rfc3339("2003-06-03T09:39:21+00:00").unwrap()));
+    match results[2] {
+        Err(Error::Parse { line: 17, ref message, .. }) => assert_eq!(message, "feed item without link"),
+        ref other => panic!("unexpected result {:?}", other),
+    }
+    let third = results[3].as_ref().unwrap();
+    assert!(matches!(third.lastmod, LastMod::Err { .. }));
+    assert_eq!(third.errors().len(), 1);
+}
+
+#[test]
+fn test_read_atom_feed() {
+    let file = File::open("tests/documents/feed_atom.xml").unwrap();
+    let urls = FeedReader::new(file).collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(urls.len(), 2);
+    assert_eq!(urls[0].loc.get_url().unwrap().as_str(), "http://www.example.com/first.html");
+    assert_eq!(urls[0].lastmod.get_time(),
+               Some(DateTime::parse_from_rfc3339("2003-12-13T18:30:02+00:00").unwrap()));
+    assert_eq!(urls[1].loc.get_url().unwrap().as_str(), "http://www.example.com/second.html");
+    assert_eq!(urls[1].lastmod.get_time(),
+               Some(DateTime::parse_from_rfc3339("2003-12-14T10:00:00+02:00").unwrap()));
+}
+
+#[test]
+fn test_read_feed_errors() {
+    let data: &[u8] = b"<rss><channel><item><link>http://www.example.com/</link></item><item></rss>";
+    let mut reader = FeedReader::new(data);
+    assert!(matches!(reader.next(), Some(Ok(_))));
+    assert!(matches!(reader.next(), Some(Err(Error::XmlReadError(_)))));
+    assert!(reader.next().is_none());
+    // a sitemap is not a feed
+    let file = File::open("tests/documents/sitemap1.xml").unwrap();
+    assert_eq!(FeedReader::new(file).count(), 0);
+}
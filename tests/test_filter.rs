@@ -0,0 +1,102 @@
+extern crate sitemap;
+extern crate chrono;
+#[cfg(feature = "async")]
+extern crate futures;
+#[cfg(feature = "async")]
+extern crate tokio;
+
+use chrono::DateTime;
+use sitemap::filter::{self, FilterEntries, MissingLastMod};
+use sitemap::reader::SiteMapReader;
+use sitemap::structs::{ChangeFreq, UrlEntry};
+use sitemap::Error;
+
+const DOCUMENT: &str = "<urlset>\
+    <url><loc>http://www.example.com/</loc><lastmod>2020-01-01</lastmod><changefreq>always</changefreq></url>\
+    <url><loc>http://www.example.com/blog/a</loc><lastmod>2020-03-01T10:00:00+02:00</lastmod>\
+    <changefreq>weekly</changefreq></url>\
+    <url><loc>http://cdn.example.com/blog/b.png</loc><changefreq>never</changefreq></url>\
+    <url><loc>http://www.example.com/blog/2020/c</loc><lastmod>yesterday</lastmod></url>\
+    </urlset>";
+
+fn filtered<F: filter::EntryFilter>(filter: F) -> Vec<String> {
+    SiteMapReader::new(DOCUMENT.as_bytes())
+        .into_url_iter()
+        .filter_entries(filter)
+        .map(|entry| entry.unwrap().loc.raw().unwrap())
+        .collect()
+}
+
+#[test]
+fn test_modified_since() {
+    let since = DateTime::parse_from_rfc3339("2020-01-01T00:00:00Z").unwrap();
+    assert_eq!(filtered(filter::modified_since(since, MissingLastMod::Exclude)),
+               ["http://www.example.com/blog/a"]);
+    assert_eq!(filtered(filter::modified_since(since, MissingLastMod::Include)),
+               ["http://www.example.com/blog/a",
+                "http://cdn.example.com/blog/b.png",
+                "http://www.example.com/blog/2020/c"]);
+}
+
+#[test]
+fn test_changefreq_at_most() {
+    assert_eq!(filtered(filter::changefreq_at_most(ChangeFreq::Always)), ["http://www.example.com/"]);
+    assert_eq!(filtered(filter::changefreq_at_most(ChangeFreq::Monthly)),
+               ["http://www.example.com/", "http://www.example.com/blog/a"]);
+    assert_eq!(filtered(filter::changefreq_at_most(ChangeFreq::Never)).len(), 3);
+    assert!(filtered(filter::changefreq_at_most(ChangeFreq::None)).is_empty());
+}
+
+#[test]
+fn test_loc_and_host() {
+    assert_eq!(filtered(filter::loc_contains("/blog/")).len(), 3);
+    assert_eq!(filtered(filter::host_is("CDN.example.com")), ["http://cdn.example.com/blog/b.png"]);
+    assert_eq!(filtered(|entry: &UrlEntry| entry.lastmod.get_time().is_none()).len(), 2);
+}
+
+#[test]
+fn test_filter_errors() {
+    let document = "<urlset><url><loc>http://www.example.com/</loc></url><url></urlset>";
+    let entries: Vec<Result<UrlEntry, Error>> = SiteMapReader::new(document.as_bytes())
+        .into_url_iter()
+        .filter_entries(filter::loc_contains("nothing"))
+        .collect();
+    assert_eq!(entries.len(), 1);
+    assert!(entries[0].is_err());
+}
+
+#[cfg(feature = "glob")]
+#[test]
+fn test_loc_matches() {
+    use sitemap::filter::Glob;
+
+    let glob = |pattern: &str| Glob::new(pattern).unwrap();
+    assert_eq!(filtered(filter::loc_matches(glob("http://www.example.com/blog/*"))),
+               ["http://www.example.com/blog/a"]);
+    assert_eq!(filtered(filter::loc_matches(glob("http://www.example.com/blog/**"))),
+               ["http://www.example.com/blog/a", "http://www.example.com/blog/2020/c"]);
+    assert_eq!(filtered(filter::loc_matches(glob("http://*.example.com/**.png"))),
+               ["http://cdn.example.com/blog/b.png"]);
+    assert!(glob("http://www.example.com/blog/[!b]").matches("http://www.example.com/blog/a"));
+    assert!(!glob("http://www.example.com/blog/[b-z]").matches("http://www.example.com/blog/a"));
+    assert!(glob("http://www.example.com/?").matches("http://www.example.com/a"));
+    assert!(!glob("http://www.example.com?a").matches("http://www.example.com/a"));
+    assert!(glob("[]-]").matches("]") && glob("[a-]").matches("-"));
+    assert!(Glob::new("http://www.example.com/[a-").is_err());
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_filter_stream() {
+    use futures::StreamExt;
+    use sitemap::async_reader::AsyncSiteMapReader;
+    use sitemap::filter::FilterEntryStream;
+    use tokio::io::BufReader;
+
+    let urls: Vec<_> = AsyncSiteMapReader::new(BufReader::new(DOCUMENT.as_bytes()))
+        .into_url_stream()
+        .filter_entries(filter::host_is("www.example.com"))
+        .collect()
+        .await;
+    assert_eq!(urls.len(), 3);
+}
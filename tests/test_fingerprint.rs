@@ -0,0 +1,52 @@
+extern crate sitemap;
+
+use sitemap::fingerprint::{self, Fingerprint};
+use sitemap::reader::SiteMapReader;
+use sitemap::structs::UrlEntry;
+
+static DOCUMENT: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                         <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n\
+                         <url><loc>http://www.example.com/a</loc><lastmod>2024-01-01</lastmod></url>\n\
+                         <url><loc>http://www.example.com/b</loc><lastmod>2024-02-01T10:00:00+02:00</lastmod></url>\n\
+                         <url><loc>http://www.example.com/c</loc><lastmod>yesterday</lastmod></url>\n\
+                         </urlset>\n";
+
+static SHUFFLED: &str = "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\
+                         <url>\n  <loc> http://WWW.example.com:80/c </loc>\n  <lastmod>yesterday</lastmod>\n</url>\
+                         <url><priority>0.5</priority><loc>http://www.example.com/b</loc>\
+                         <lastmod>2024-02-01T08:00:00.000Z</lastmod></url>\
+                         <url><loc>http://www.example.com/%61</loc><lastmod>2024-01-01T00:00:00Z</lastmod>\
+                         <changefreq>daily</changefreq></url>\
+                         </urlset>";
+
+fn urls(document: &str) -> Vec<UrlEntry> {
+    SiteMapReader::new(document.as_bytes()).into_url_iter().map(|url| url.unwrap()).collect()
+}
+
+#[test]
+fn test_fingerprint() {
+    let value = fingerprint::fingerprint(urls(DOCUMENT));
+    assert_eq!(fingerprint::fingerprint(urls(SHUFFLED)), value);
+    assert_eq!(fingerprint::fingerprint_reader(SiteMapReader::new(DOCUMENT.as_bytes())).unwrap(), value);
+    let mut streamed = Fingerprint::new();
+    for url in urls(SHUFFLED).iter().rev() {
+        streamed.add(url);
+    }
+    assert_eq!((streamed.entries(), streamed.value()), (3, value));
+    // the value is persisted by the users, it is the same across versions
+    assert_eq!(value, 0xbdaf_13b2_c004_27c1);
+    assert_eq!(fingerprint::fingerprint(Vec::new()), 0xa706_dd2f_4d19_7e6f);
+
+    let changed = [DOCUMENT.replace("2024-01-01", "2024-01-02"),
+                   DOCUMENT.replace("/c</loc>", "/d</loc>"),
+                   DOCUMENT.replace("yesterday", "today"),
+                   DOCUMENT.replace("<lastmod>yesterday</lastmod>", ""),
+                   DOCUMENT.replace("</urlset>", "<url><loc>http://www.example.com/a</loc>\
+                                                  <lastmod>2024-01-01</lastmod></url></urlset>")];
+    for document in changed.iter() {
+        assert_ne!(fingerprint::fingerprint(urls(document)), value, "{}", document);
+    }
+
+    let error = fingerprint::fingerprint_reader(SiteMapReader::new(&DOCUMENT.as_bytes()[..200]));
+    assert!(error.is_err());
+}
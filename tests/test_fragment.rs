@@ -0,0 +1,196 @@
+extern crate sitemap;
+extern crate chrono;
+
+use chrono::{DateTime, NaiveDate};
+use sitemap::Error;
+use sitemap::reader::ReaderOptions;
+use sitemap::structs::{Alternate, ChangeFreq, ImageEntry, LastMod, Location, Position, RawElement, SiteMapEntry,
+                       UrlEntry};
+use sitemap::writer::{Indent, WriterOptions};
+
+/// Deterministic generator of the entries of the round trip tests.
+struct Generator(u64);
+
+impl Generator {
+    fn next(&mut self, bound: usize) -> usize {
+        // xorshift64
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+
+    fn text(&mut self) -> String {
+        const PIECES: [&str; 8] = ["page", "a&b", "<tag>", "\"quoted\"", "caf\u{e9}", "x y", "it's", "1"];
+        (0..1 + self.next(3)).map(|_| PIECES[self.next(PIECES.len())]).collect::<Vec<_>>().join("-")
+    }
+
+    fn loc(&mut self) -> String {
+        format!("http://www.example.com/{}?q={}", self.next(1000), self.next(10))
+    }
+
+    fn lastmod(&mut self) -> LastMod {
+        match self.next(3) {
+            0 => LastMod::None,
+            1 => LastMod::Date(NaiveDate::from_ymd_opt(2000 + self.next(30) as i32, 1 + self.next(12) as u32, 1).unwrap()),
+            _ => LastMod::DateTime(DateTime::parse_from_rfc3339("2024-03-01T08:30:00+02:00").unwrap()),
+        }
+    }
+
+    fn url(&mut self) -> UrlEntry {
+        let mut url = UrlEntry::builder().loc(self.loc()).build().unwrap();
+        url.lastmod = self.lastmod();
+        url.changefreq = match self.next(ChangeFreq::ALL.len() + 1) {
+            0 => ChangeFreq::None,
+            index => ChangeFreq::ALL[index - 1].clone(),
+        };
+        if self.next(2) == 0 {
+            url.priority = sitemap::structs::Priority::Value(self.next(11) as f32 / 10.0);
+        }
+        for _ in 0..self.next(3) {
+            url.images.push(ImageEntry {
+                loc: Location::from(self.loc()),
+                title: Some(self.text()),
+                ..ImageEntry::new()
+            });
+        }
+        for _ in 0..self.next(2) {
+            let href = self.loc().parse().unwrap();
+            url.alternates.push(Alternate::new("fr", href));
+        }
+        url
+    }
+}
+
+fn read(fragment: &str) -> UrlEntry {
+    match UrlEntry::from_xml_fragment(fragment) {
+        Ok(url) => url,
+        Err(error) => panic!("{}: {:?}", fragment, error),
+    }
+}
+
+#[test]
+fn test_url_fragment() {
+    let url = UrlEntry::builder()
+        .loc("http://www.example.com/?a=1&b=2")
+        .changefreq(ChangeFreq::Daily)
+        .image(ImageEntry::with_loc("http://www.example.com/a.png".parse().unwrap()))
+        .image(ImageEntry::with_loc("http://www.example.com/b.png".parse().unwrap()))
+        .build()
+        .unwrap();
+    let options = WriterOptions {
+        indent: Indent::None,
+        ..WriterOptions::default()
+    };
+    assert_eq!(url.to_xml_string(&options).unwrap(),
+               "<url xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\">\
+                <loc>http://www.example.com/?a=1&amp;b=2</loc><changefreq>daily</changefreq>\
+                <image:image><image:loc>http://www.example.com/a.png</image:loc></image:image>\
+                <image:image><image:loc>http://www.example.com/b.png</image:loc></image:image></url>");
+    let fragment = url.to_xml_string(&WriterOptions::default()).unwrap();
+    assert!(fragment.starts_with("<url"), "{}", fragment);
+    assert_eq!(read(&fragment), url);
+}
+
+#[test]
+fn test_url_fragment_read() {
+    let url = read("<url><loc>http://www.example.com/</loc><lastmod>2024-01-01</lastmod></url>");
+    assert_eq!(url.loc.get_url().unwrap().as_str(), "http://www.example.com/");
+    assert_eq!(url.field_position("lastmod"), Some(Position { line: 1, column: 40 }));
+    let url = read("<url xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\
+                    <loc>http://www.example.com/</loc><priority>2</priority></url>");
+    assert_eq!(url.errors().len(), 1);
+    let strict = ReaderOptions {
+        strict: true,
+        ..ReaderOptions::default()
+    };
+    assert!(UrlEntry::from_xml_fragment_with_options("<url><loc>http://www.example.com/</loc><priority>2</priority></url>",
+                                                     strict)
+        .is_err());
+}
+
+#[test]
+fn test_url_fragment_extensions() {
+    let fragment = "<url xmlns:geo=\"http://www.google.com/geo/schemas/sitemap/1.0\">\
+                    <loc>http://www.example.com/</loc><geo:geo><geo:format>kml</geo:format></geo:geo></url>";
+    assert!(read(fragment).extensions.is_empty());
+    let capture = ReaderOptions {
+        capture_unknown: true,
+        ..ReaderOptions::default()
+    };
+    let url = UrlEntry::from_xml_fragment_with_options(fragment, capture).unwrap();
+    assert_eq!(url.extensions,
+               vec![RawElement {
+                        name: "geo:geo".to_string(),
+                        attributes: Vec::new(),
+                        inner: "<geo:format>kml</geo:format>".to_string(),
+                        namespaces: vec![("geo".to_string(), "http://www.google.com/geo/schemas/sitemap/1.0".to_string())],
+                    }]);
+    let written = url.to_xml_string(&WriterOptions::default()).unwrap();
+    assert_eq!(UrlEntry::from_xml_fragment_with_options(&written, capture).unwrap(), url);
+}
+
+#[test]
+fn test_url_fragment_errors() {
+    let errors = ["<sitemap><loc>http://www.example.com/</loc></sitemap>",
+                  "<url><loc>http://www.example.com/</loc></url><url/>",
+                  "text"];
+    for fragment in errors.iter() {
+        assert!(UrlEntry::from_xml_fragment(fragment).is_err(), "{}", fragment);
+    }
+    match UrlEntry::from_xml_fragment("<sitemap/>") {
+        Err(Error::Invalid(message)) => assert_eq!(message, "fragment without a url element"),
+        result => panic!("unexpected result {:?}", result),
+    }
+    match UrlEntry::new().to_xml_string(&WriterOptions::default()) {
+        Err(Error::MissingLoc { index: 0 }) => {}
+        result => panic!("unexpected result {:?}", result),
+    }
+    let skip = WriterOptions {
+        skip_invalid: true,
+        ..WriterOptions::default()
+    };
+    assert_eq!(UrlEntry::new().to_xml_string(&skip).unwrap(), "");
+}
+
+#[test]
+fn test_sitemap_fragment() {
+    let sitemap = SiteMapEntry::builder()
+        .loc("http://www.example.com/sitemap.xml")
+        .lastmod_date(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap())
+        .build()
+        .unwrap();
+    let options = WriterOptions {
+        indent: Indent::None,
+        ..WriterOptions::default()
+    };
+    let fragment = sitemap.to_xml_string(&options).unwrap();
+    assert_eq!(fragment,
+               "<sitemap><loc>http://www.example.com/sitemap.xml</loc><lastmod>2024-01-01</lastmod></sitemap>");
+    assert_eq!(SiteMapEntry::from_xml_fragment(&fragment).unwrap(), sitemap);
+    match SiteMapEntry::from_xml_fragment("<url><loc>http://www.example.com/</loc></url>") {
+        Err(Error::Invalid(message)) => assert_eq!(message, "fragment without a sitemap element"),
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+fn test_fragment_round_trip() {
+    let mut generator = Generator(0x2545_f491_4f6c_dd1d);
+    let indents = [Indent::None, Indent::Spaces(2), Indent::Tabs];
+    for _ in 0..200 {
+        let url = generator.url();
+        let options = WriterOptions {
+            indent: indents[generator.next(indents.len())],
+            ..WriterOptions::default()
+        };
+        let fragment = url.to_xml_string(&options).unwrap();
+        assert_eq!(read(&fragment), url, "{}", fragment);
+        assert_eq!(read(&fragment).to_xml_string(&options).unwrap(), fragment);
+
+        let mut sitemap = SiteMapEntry::builder().loc(generator.loc()).build().unwrap();
+        sitemap.lastmod = generator.lastmod();
+        let fragment = sitemap.to_xml_string(&options).unwrap();
+        assert_eq!(SiteMapEntry::from_xml_fragment(&fragment).unwrap(), sitemap, "{}", fragment);
+    }
+}
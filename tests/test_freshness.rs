@@ -0,0 +1,131 @@
+extern crate chrono;
+extern crate sitemap;
+
+use chrono::{DateTime, NaiveDate};
+use sitemap::index::freshness;
+use sitemap::reader::{SiteMapEvent, SiteMapReader};
+use sitemap::structs::{LastMod, SiteMapEntry, UrlEntry};
+use sitemap::validate::{self, Freshness, FreshnessOptions};
+use sitemap::writer::SiteMapWriter;
+use std::time::Duration;
+
+static INDEX: &str = "<sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\
+                      <sitemap><loc>http://www.example.com/date.xml</loc><lastmod>2024-05-01</lastmod></sitemap>\
+                      <sitemap><loc>http://www.example.com/time.xml</loc>\
+                      <lastmod>2024-05-01T23:30:00-05:00</lastmod></sitemap>\
+                      <sitemap><loc>http://www.example.com/none.xml</loc></sitemap>\
+                      </sitemapindex>";
+
+fn index() -> Vec<SiteMapEntry> {
+    SiteMapReader::new(INDEX.as_bytes())
+        .filter_map(|event| match event {
+            Ok(SiteMapEvent::SiteMap(sitemap)) => Some(sitemap),
+            _ => None,
+        })
+        .collect()
+}
+
+fn stale(since: &str) -> Vec<&'static str> {
+    let index = index();
+    let since = DateTime::parse_from_rfc3339(since).unwrap();
+    let locs = freshness::stale_children(&index, since)
+        .into_iter()
+        .map(|entry| match entry.loc.as_str() {
+            Some("http://www.example.com/date.xml") => "date",
+            Some("http://www.example.com/time.xml") => "time",
+            _ => "none",
+        })
+        .collect();
+    locs
+}
+
+#[test]
+fn test_index_lastmod_precision() {
+    let index = index();
+    assert_eq!(index[0].lastmod, LastMod::Date(NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()));
+    assert!(matches!(index[1].lastmod, LastMod::DateTime(_)));
+    assert_eq!(index[2].lastmod, LastMod::None);
+    let mut writer = SiteMapWriter::new(Vec::new()).start_sitemapindex().unwrap();
+    for entry in index {
+        writer.sitemap(entry).unwrap();
+    }
+    let written = String::from_utf8(writer.end().unwrap()).unwrap();
+    assert!(written.contains("<lastmod>2024-05-01</lastmod>"), "{}", written);
+    assert!(written.contains("<lastmod>2024-05-01T23:30:00-05:00</lastmod>"), "{}", written);
+}
+
+#[test]
+fn test_stale_children_midnight() {
+    // midnight UTC and later in the day, the date may have been updated
+    assert_eq!(stale("2024-05-01T00:00:00Z"), vec!["date", "time", "none"]);
+    assert_eq!(stale("2024-05-02T00:30:00+02:00"), vec!["date", "time", "none"]);
+    assert_eq!(stale("2024-05-01T23:59:59-05:00"), vec!["date", "none"]);
+    assert_eq!(stale("2024-05-02T04:29:59Z"), vec!["date", "time", "none"]);
+    assert_eq!(stale("2024-05-02T04:30:00Z"), vec!["date", "none"]);
+    // the end of the day at -12:00
+    assert_eq!(stale("2024-05-02T11:59:59Z"), vec!["date", "none"]);
+    assert_eq!(stale("2024-05-02T23:59:59+12:00"), vec!["date", "none"]);
+    assert_eq!(stale("2024-05-02T00:00:00-12:00"), vec!["none"]);
+    assert_eq!(stale("2024-05-02T12:00:00Z"), vec!["none"]);
+    assert_eq!(stale("2024-05-03T01:59:59+14:00"), vec!["date", "none"]);
+    assert_eq!(stale("2024-05-03T02:00:00+14:00"), vec!["none"]);
+}
+
+#[test]
+fn test_end_of_day() {
+    let end = freshness::end_of_day(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()).unwrap();
+    assert_eq!(end.to_rfc3339(), "2024-03-01T00:00:00-12:00");
+    assert_eq!(freshness::end_of_day(NaiveDate::MAX), None);
+    let mut entry = SiteMapEntry::new();
+    entry.lastmod = LastMod::Date(NaiveDate::MAX);
+    assert!(freshness::is_stale(&entry, &DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap()));
+}
+
+#[test]
+fn test_audit_freshness() {
+    let document = "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\
+                    <url><loc>http://www.example.com/fresh</loc><lastmod>2024-05-09T12:00:00Z</lastmod>\
+                    <changefreq>daily</changefreq></url>\
+                    <url><loc>http://www.example.com/stale</loc><lastmod>2024-05-01T10:00:00Z</lastmod>\
+                    <changefreq>daily</changefreq></url>\
+                    <url><loc>http://www.example.com/future</loc><lastmod>2024-05-12</lastmod></url>\
+                    <url><loc>http://www.example.com/none</loc><changefreq>hourly</changefreq></url>\
+                    <url><loc>http://www.example.com/invalid</loc><lastmod>yesterday</lastmod></url>\
+                    <url><loc>http://www.example.com/weekly</loc><lastmod>2024-04-01</lastmod>\
+                    <changefreq>weekly</changefreq></url>\
+                    <url><loc>http://www.example.com/never</loc><lastmod>2000-01-01</lastmod>\
+                    <changefreq>never</changefreq></url>\
+                    <url><loc>http://www.example.com/today</loc><lastmod>2024-05-11</lastmod>\
+                    <changefreq>daily</changefreq></url>\
+                    </urlset>";
+    let entries: Vec<UrlEntry> = SiteMapReader::new(document.as_bytes()).into_url_iter().map(|url| url.unwrap()).collect();
+    let now = DateTime::parse_from_rfc3339("2024-05-10T12:00:00+02:00").unwrap();
+    let report = validate::audit_freshness(&entries, now, &FreshnessOptions::default());
+    assert_eq!((report.entries, report.consistent, report.stale, report.future_dated, report.missing_lastmod),
+               (8, 3, 2, 1, 2));
+    assert!(!report.is_consistent());
+    let offenders: Vec<_> = report.offenders.iter().map(|offender| (offender.index, offender.freshness)).collect();
+    let days = |days: u64, hours: u64| Duration::from_secs((days * 24 + hours) * 3600);
+    // 2024-04-01 ends at 2024-04-02T12:00:00Z, 7 days before it is overdue
+    assert_eq!(offenders,
+               [(5, Freshness::StaleForChangefreq { overdue: days(30, 22) }),
+                (1, Freshness::StaleForChangefreq { overdue: days(8, 0) }),
+                (2, Freshness::FutureDated { ahead: days(1, 0) })]);
+    assert_eq!(report.to_string(),
+               "8 entries, 3 consistent, 2 stale, 1 future dated, 2 missing lastmod\n  \
+                5 http://www.example.com/weekly: stale, overdue by 30d 22h\n  \
+                1 http://www.example.com/stale: stale, overdue by 8d 0h\n  \
+                2 http://www.example.com/future: dated 1d 0h in the future");
+
+    let options = FreshnessOptions {
+        grace: days(10, 0),
+        clock_skew: days(1, 0),
+        max_offenders: 1,
+    };
+    let report = validate::audit_freshness(&entries, now, &options);
+    assert_eq!((report.consistent, report.stale, report.future_dated), (5, 1, 0));
+    assert_eq!(report.offenders.len(), 1);
+    assert_eq!(report.offenders[0].loc.as_str(), Some("http://www.example.com/weekly"));
+    assert_eq!(validate::entry_freshness(&entries[7], &now, &options), Freshness::Consistent);
+    assert!(validate::audit_freshness(&entries[..1], now, &options).is_consistent());
+}
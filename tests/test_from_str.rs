@@ -0,0 +1,211 @@
+extern crate sitemap;
+extern crate url;
+extern crate chrono;
+
+use sitemap::Error;
+use chrono::DateTime;
+use sitemap::structs::{ChangeFreq, DateFormat, LastMod, Location, Priority};
+use std::time::Duration;
+
+fn invalid(error: Error) -> (String, String) {
+    match error {
+        Error::InvalidValue { tag, value, .. } => (tag, value),
+        error => panic!("unexpected error {:?}", error),
+    }
+}
+
+#[test]
+fn test_location_from_str() {
+    let loc: Location = "http://www.example.com/".parse().unwrap();
+    assert_eq!(loc.get_url().unwrap().as_str(), "http://www.example.com/");
+    let error = "/relative.html".parse::<Location>().unwrap_err();
+    assert_eq!(error.to_string(),
+               "Invalid <loc> value '/relative.html': relative URL without a base");
+}
+
+#[test]
+fn test_location_borrowing() {
+    let loc: Location = "http://www.example.com/a".parse().unwrap();
+    assert_eq!(loc.url(), loc.get_url().as_ref());
+    assert_eq!(loc.as_str(), Some("http://www.example.com/a"));
+    let raw = Location::Raw("http://www.example.com/b".to_string());
+    assert_eq!(raw.url(), None);
+    assert!(raw.get_url().is_some());
+    assert_eq!(raw.as_str(), Some("http://www.example.com/b"));
+    let error = Location::from("/relative.html".to_string());
+    assert_eq!(error.as_str(), None);
+    assert_eq!(error.raw().as_deref(), Some("/relative.html"));
+    assert_eq!(Location::None.url(), None);
+}
+
+#[test]
+fn test_lastmod_time() {
+    let lastmod: LastMod = "2016-07-08T09:10:11+02:00".parse().unwrap();
+    assert_eq!(lastmod.time(), lastmod.get_time().as_ref());
+    let lastmod = LastMod::from("Fri, 08 Jul 2016 09:10:11 GMT".to_string());
+    assert_eq!(lastmod.time(), Some(&DateTime::parse_from_rfc3339("2016-07-08T09:10:11+00:00").unwrap()));
+    // the midnight of a date is computed, not borrowed
+    let lastmod: LastMod = "2016-07-08".parse().unwrap();
+    assert_eq!(lastmod.time(), None);
+    assert!(lastmod.get_time().is_some());
+    assert_eq!(LastMod::None.time(), None);
+}
+
+#[test]
+fn test_lastmod_from_str() {
+    let lastmod: LastMod = "2016-07-08T09:10:11+00:00".parse().unwrap();
+    assert!(lastmod.get_time().is_some());
+    let lastmod: LastMod = "2016-07-08".parse().unwrap();
+    assert!(lastmod.get_time().is_some());
+    let error = "2023/05/01".parse::<LastMod>().unwrap_err();
+    assert_eq!(invalid(error), ("lastmod".to_string(), "2023/05/01".to_string()));
+}
+
+#[test]
+fn test_lastmod_formats() {
+    let lastmod = LastMod::from("2023-06-15T10:00:00.123Z".to_string());
+    assert_eq!(lastmod.format(), Some(DateFormat::W3c));
+    assert_eq!(lastmod.get_time().unwrap().timestamp_subsec_millis(), 123);
+
+    let lastmod = LastMod::from("2023-06-15 10:00:00+00:00".to_string());
+    assert_eq!(lastmod.format(), Some(DateFormat::Rfc3339));
+    assert_eq!(lastmod.get_time(),
+               Some(DateTime::parse_from_rfc3339("2023-06-15T10:00:00+00:00").unwrap()));
+    assert_eq!(lastmod.raw(), Some("2023-06-15 10:00:00+00:00".to_string()));
+
+    let lastmod: LastMod = "Thu, 15 Jun 2023 10:00:00 +0200".parse().unwrap();
+    assert_eq!(lastmod.format(), Some(DateFormat::Rfc2822));
+    assert_eq!(lastmod.get_time(),
+               Some(DateTime::parse_from_rfc3339("2023-06-15T10:00:00+02:00").unwrap()));
+
+    assert_eq!("2023-06-15".parse::<LastMod>().unwrap().format(), Some(DateFormat::W3c));
+    assert_eq!(LastMod::from("2023-06-15T10:00:00".to_string()).format(), None);
+    assert_eq!(LastMod::None.format(), None);
+}
+
+#[test]
+fn test_changefreq_from_str() {
+    assert_eq!("daily".parse::<ChangeFreq>().unwrap(), ChangeFreq::Daily);
+    assert_eq!("Never".parse::<ChangeFreq>().unwrap(), ChangeFreq::Never);
+    let error = "2 days".parse::<ChangeFreq>().unwrap_err();
+    assert_eq!(error.to_string(),
+               "Invalid <changefreq> value '2 days': unknown change frequency");
+}
+
+#[test]
+fn test_changefreq_suggestion() {
+    assert_eq!(ChangeFreq::from("  Weekly\n".to_string()), ChangeFreq::Weekly);
+    let suggestion = |text: &str| match ChangeFreq::from(text.to_string()) {
+        ChangeFreq::Err(error) => error.suggestion().map(str::to_string),
+        changefreq => panic!("{} parsed as {:?}", text, changefreq),
+    };
+    assert_eq!(suggestion("Dayly").as_deref(), Some("daily"));
+    assert_eq!(suggestion(" hourlly ").as_deref(), Some("hourly"));
+    assert_eq!(suggestion("month").as_deref(), Some("monthly"));
+    assert_eq!(suggestion("nevr").as_deref(), Some("never"));
+    assert_eq!(suggestion("sometimes"), None);
+    assert_eq!(suggestion(""), None);
+    if let ChangeFreq::Err(error) = ChangeFreq::from("Dayly".to_string()) {
+        assert_eq!(error.description, "Dayly");
+        assert_eq!(error.to_string(), "Not recognezed string 'Dayly', did you mean 'daily'?");
+    }
+    let error = "yeary".parse::<ChangeFreq>().unwrap_err();
+    assert_eq!(error.to_string(),
+               "Invalid <changefreq> value 'yeary': unknown change frequency, did you mean 'yearly'?");
+}
+
+#[test]
+fn test_changefreq_ordering() {
+    let mut changefreqs = vec![ChangeFreq::from("sometimes".to_string()),
+                               ChangeFreq::Never,
+                               ChangeFreq::None,
+                               ChangeFreq::Daily,
+                               ChangeFreq::from("often".to_string()),
+                               ChangeFreq::Always];
+    changefreqs.sort();
+    assert_eq!(changefreqs,
+               vec![ChangeFreq::Always,
+                    ChangeFreq::Daily,
+                    ChangeFreq::Never,
+                    ChangeFreq::None,
+                    ChangeFreq::from("often".to_string()),
+                    ChangeFreq::from("sometimes".to_string())]);
+    assert!(ChangeFreq::ALL.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn test_changefreq_display() {
+    for changefreq in ChangeFreq::ALL.iter() {
+        assert_eq!(changefreq.to_string(), changefreq.as_str());
+        assert_eq!(changefreq.to_string().parse::<ChangeFreq>().unwrap(), *changefreq);
+    }
+    assert_eq!(ChangeFreq::None.to_string(), "");
+}
+
+#[test]
+fn test_changefreq_duration() {
+    assert_eq!(ChangeFreq::Always.as_duration(), Some(Duration::from_secs(0)));
+    assert_eq!(ChangeFreq::Hourly.as_duration(), Some(Duration::from_secs(3600)));
+    assert_eq!(ChangeFreq::Weekly.as_duration(), Some(Duration::from_secs(7 * 86400)));
+    assert_eq!(ChangeFreq::Yearly.as_duration(), Some(Duration::from_secs(365 * 86400)));
+    assert_eq!(ChangeFreq::Never.as_duration(), None);
+    assert_eq!(ChangeFreq::None.as_duration(), None);
+    let durations: Vec<_> = ChangeFreq::ALL[..6].iter().map(|changefreq| changefreq.as_duration().unwrap()).collect();
+    assert!(durations.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn test_priority_from_str() {
+    assert_eq!("0.5".parse::<Priority>().unwrap(), Priority::Value(0.5));
+    assert_eq!("1".parse::<Priority>().unwrap(), Priority::Value(1.0));
+    assert_eq!("0".parse::<Priority>().unwrap(), Priority::Value(0.0));
+    let error = "1.5".parse::<Priority>().unwrap_err();
+    assert_eq!(error.to_string(),
+               "Invalid <priority> value '1.5': priority should be between 0 and 1");
+    assert_eq!(invalid("-0.1".parse::<Priority>().unwrap_err()),
+               ("priority".to_string(), "-0.1".to_string()));
+    assert_eq!(invalid("NaN".parse::<Priority>().unwrap_err()),
+               ("priority".to_string(), "NaN".to_string()));
+    assert_eq!(invalid("high".parse::<Priority>().unwrap_err()),
+               ("priority".to_string(), "high".to_string()));
+}
+
+#[test]
+fn test_priority_new() {
+    assert_eq!(Priority::new(0.8).unwrap(), Priority::Value(0.8));
+    assert_eq!(Priority::new(1.0).unwrap(), Priority::MAX);
+    assert_eq!(Priority::new(0.0).unwrap(), Priority::MIN);
+    assert_eq!(Priority::DEFAULT.get_priority(), Some(0.5));
+    assert_eq!(invalid(Priority::new(1.5).unwrap_err()),
+               ("priority".to_string(), "1.5".to_string()));
+    assert_eq!(invalid(Priority::new(f32::INFINITY).unwrap_err()),
+               ("priority".to_string(), "inf".to_string()));
+    assert_eq!(Priority::new(f32::NAN).unwrap_err().to_string(),
+               "Invalid <priority> value 'NaN': priority is not a number");
+}
+
+#[test]
+fn test_priority_ordering() {
+    let mut priorities = vec![Priority::MAX,
+                              Priority::Value(0.5),
+                              Priority::None,
+                              Priority::from("high".to_string()),
+                              Priority::Value(0.6),
+                              Priority::ErrValueGreaterOne(2.0),
+                              Priority::MIN,
+                              Priority::Value(0.4)];
+    priorities.sort();
+    assert_eq!(priorities,
+               [Priority::from("high".to_string()),
+                Priority::ErrValueGreaterOne(2.0),
+                Priority::MIN,
+                Priority::Value(0.4),
+                Priority::None,
+                Priority::Value(0.5),
+                Priority::Value(0.6),
+                Priority::MAX]);
+    assert!(Priority::None > Priority::Value(0.45));
+    assert!(Priority::None < Priority::Value(0.55));
+    assert!(Priority::Value(f32::NAN) < Priority::MIN);
+    assert_eq!(Priority::Value(-0.0).cmp(&Priority::MIN), std::cmp::Ordering::Equal);
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "gzip")]
+extern crate sitemap;
+
+use sitemap::reader::{SiteMapReader, SiteMapEvent};
+use std::fs::File;
+use std::io::{self, Read};
+use std::rc::Rc;
+use std::cell::Cell;
+
+/// Reader counting the bytes consumed from the underlying stream,
+/// returning at most 512 bytes per call like a slow network would.
+struct CountingReader<R: Read> {
+    inner: R,
+    count: Rc<Cell<usize>>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(512);
+        let read = self.inner.read(&mut buf[..len])?;
+        self.count.set(self.count.get() + read);
+        Ok(read)
+    }
+}
+
+fn count_urls<T: Read>(reader: SiteMapReader<T>) -> usize {
+    let mut count = 0;
+    for entity in reader {
+        match entity {
+            Ok(SiteMapEvent::Url(_)) => count += 1,
+            Ok(_) => {}
+            Err(error) => panic!("unexpected error {:?}", error),
+        }
+    }
+    count
+}
+
+#[test]
+fn test_read_gzip() {
+    let file = File::open("tests/documents/sitemap_large.xml.gz").unwrap();
+    assert_eq!(count_urls(SiteMapReader::from_gzip(file)), 5000);
+}
+
+#[test]
+fn test_read_maybe_gzip() {
+    let file = File::open("tests/documents/sitemap_large.xml.gz").unwrap();
+    assert_eq!(count_urls(SiteMapReader::from_maybe_gzip(file).unwrap()), 5000);
+    let file = File::open("tests/documents/sitemap1.xml").unwrap();
+    assert_eq!(count_urls(SiteMapReader::from_maybe_gzip(file).unwrap()), 5);
+    let short: &[u8] = b"<";
+    match SiteMapReader::from_maybe_gzip(short).unwrap().next() {
+        Some(Err(_)) => {}
+        other => panic!("unexpected entity {:?}", other),
+    }
+}
+
+#[test]
+fn test_read_gzip_streaming() {
+    let total = File::open("tests/documents/sitemap_large.xml.gz").unwrap().metadata().unwrap().len();
+    let count = Rc::new(Cell::new(0));
+    let file = CountingReader {
+        inner: File::open("tests/documents/sitemap_large.xml.gz").unwrap(),
+        count: count.clone(),
+    };
+    let mut reader = SiteMapReader::from_gzip(file);
+    match reader.next() {
+        Some(Ok(SiteMapEvent::Url(url))) => {
+            assert_eq!(url.loc.get_url().unwrap().as_str(), "http://www.example.com/page/0.html")
+        }
+        other => panic!("unexpected entity {:?}", other),
+    }
+    // only the beginning of the compressed stream has been consumed
+    assert!((count.get() as u64) < total / 2);
+    assert_eq!(count_urls(reader), 4999);
+    assert_eq!(count.get() as u64, total);
+}
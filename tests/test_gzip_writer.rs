@@ -0,0 +1,110 @@
+#![cfg(feature = "gzip")]
+extern crate sitemap;
+
+use sitemap::reader::{SiteMapReader, SiteMapEvent};
+use sitemap::writer::{SiteMapWriter, SplitSiteMapWriter};
+use sitemap::structs::{SiteMapEntry, UrlEntry};
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+fn read_sitemap(file_name: &str) -> (Vec<UrlEntry>, Vec<SiteMapEntry>) {
+    let mut urls = Vec::new();
+    let mut sitemaps = Vec::new();
+    for entity in SiteMapReader::new(File::open(file_name).unwrap()) {
+        match entity {
+            Ok(SiteMapEvent::Url(url_entry)) => urls.push(url_entry),
+            Ok(SiteMapEvent::SiteMap(sitemap_entry)) => sitemaps.push(sitemap_entry),
+            Ok(_) => {}
+            Err(error) => panic!("unexpected error {:?}", error),
+        }
+    }
+    (urls, sitemaps)
+}
+
+#[test]
+fn test_write_gzip_urlset() {
+    let (urls, _) = read_sitemap("tests/documents/sitemap1.xml");
+    let mut url_writer = SiteMapWriter::new_gzip(Vec::new()).start_urlset().unwrap();
+    for url in &urls {
+        url_writer.url(url.clone()).unwrap();
+    }
+    let output = url_writer.finish().unwrap();
+    assert_eq!(&output[..2], &[0x1f, 0x8b]);
+
+    let mut read = Vec::new();
+    for entity in SiteMapReader::from_gzip(&output[..]) {
+        match entity {
+            Ok(SiteMapEvent::Url(url_entry)) => read.push(url_entry),
+            other => panic!("unexpected entity {:?}", other),
+        }
+    }
+    assert_eq!(read, urls);
+}
+
+#[test]
+fn test_write_gzip_index() {
+    let (_, sitemaps) = read_sitemap("tests/documents/sitemap1.xml");
+    let mut index_writer = SiteMapWriter::new_gzip(Vec::new()).start_sitemapindex().unwrap();
+    for sitemap in &sitemaps {
+        index_writer.sitemap(sitemap.clone()).unwrap();
+    }
+    let output = index_writer.finish().unwrap();
+
+    let mut read = Vec::new();
+    for entity in SiteMapReader::from_maybe_gzip(&output[..]).unwrap() {
+        match entity {
+            Ok(SiteMapEvent::SiteMap(sitemap_entry)) => read.push(sitemap_entry),
+            Ok(SiteMapEvent::IndexStart { .. }) => {}
+            other => panic!("unexpected entity {:?}", other),
+        }
+    }
+    assert_eq!(read, sitemaps);
+}
+
+#[derive(Clone,Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_write_gzip_split() {
+    let (urls, _) = read_sitemap("tests/documents/sitemap1.xml");
+    let files: Rc<RefCell<Vec<SharedBuffer>>> = Rc::default();
+    {
+        let files = files.clone();
+        let mut writer = SplitSiteMapWriter::new_gzip(move |_| {
+                                                          let file = SharedBuffer::default();
+                                                          files.borrow_mut().push(file.clone());
+                                                          Ok(file)
+                                                      },
+                                                      "http://www.example.com/sitemap-{}.xml.gz")
+            .unwrap()
+            .max_urls(1);
+        for url in &urls {
+            writer.url(url.clone()).unwrap();
+        }
+        writer.end(io::sink()).unwrap();
+    }
+    let files = files.borrow();
+    assert_eq!(files.len(), urls.len());
+    let mut read = Vec::new();
+    for file in files.iter() {
+        let output = file.0.borrow();
+        for entity in SiteMapReader::from_gzip(&output[..]) {
+            match entity {
+                Ok(SiteMapEvent::Url(url_entry)) => read.push(url_entry),
+                other => panic!("unexpected entity {:?}", other),
+            }
+        }
+    }
+    assert_eq!(read, urls);
+}
@@ -0,0 +1,143 @@
+#![cfg(feature = "http")]
+extern crate flate2;
+extern crate sitemap;
+extern crate url;
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use sitemap::{Error, LimitKind};
+use sitemap::fetch::{self, HttpFetch, USER_AGENT};
+use sitemap::reader::SiteMapEvent;
+use sitemap::resolver::{Fetch, SiteMapIndexResolver};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use url::Url;
+
+static URLSET: &str = "<urlset><url><loc>http://www.example.com/</loc></url></urlset>";
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// Response of the test server for a path.
+fn response(path: &str, base: &str) -> Vec<u8> {
+    let (status, headers, body) = match path {
+        "/sitemap.xml" => ("200 OK", "Content-Type: application/xml\r\n", URLSET.as_bytes().to_vec()),
+        "/sitemap.xml.gz" => ("200 OK", "Content-Type: application/x-gzip\r\n", gzip(URLSET.as_bytes())),
+        "/encoded.xml" => {
+            ("200 OK",
+             "Content-Type: text/xml; charset=utf-8\r\nContent-Encoding: gzip\r\n",
+             gzip(URLSET.as_bytes()))
+        }
+        "/index.xml" => {
+            let index = format!("<sitemapindex><sitemap><loc>{}/sitemap.xml</loc></sitemap>\
+                                 <sitemap><loc>{}/sitemap.xml.gz</loc></sitemap></sitemapindex>",
+                                base,
+                                base);
+            ("200 OK", "Content-Type: application/xml\r\n", index.into_bytes())
+        }
+        "/page.html" => ("200 OK", "Content-Type: text/html\r\n", b"<html></html>".to_vec()),
+        "/large.xml" => ("200 OK", "Content-Type: application/xml\r\n", vec![b' '; 2048]),
+        "/bomb.xml.gz" => ("200 OK", "", gzip(&[b' '; 4096])),
+        _ => ("404 Not Found", "", Vec::new()),
+    };
+    let mut response = format!("HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n",
+                               status,
+                               headers,
+                               body.len())
+        .into_bytes();
+    response.extend_from_slice(&body);
+    response
+}
+
+/// Starts a server answering `requests` requests, returns its base url and
+/// the user agents received.
+fn server(requests: usize) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let user_agents = Arc::new(Mutex::new(Vec::new()));
+    let received = user_agents.clone();
+    let server_base = base.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming().take(requests) {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let path = line.split(' ').nth(1).unwrap_or("/").to_string();
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some(value) = header.to_ascii_lowercase().strip_prefix("user-agent:") {
+                    received.lock().unwrap().push(value.trim().to_string());
+                }
+            }
+            stream.write_all(&response(&path, &server_base)).unwrap();
+        }
+    });
+    (base, user_agents)
+}
+
+fn read(fetcher: &HttpFetch, url: &str) -> Result<String, Error> {
+    let mut document = String::new();
+    fetcher.fetch(&Url::parse(url).unwrap())?.read_to_string(&mut document)?;
+    Ok(document)
+}
+
+#[test]
+fn test_http_fetch() {
+    let (base, user_agents) = server(4);
+    let fetcher = HttpFetch::new();
+    assert_eq!(read(&fetcher, &format!("{}/sitemap.xml", base)).unwrap(), URLSET);
+    assert_eq!(read(&fetcher, &format!("{}/sitemap.xml.gz", base)).unwrap(), URLSET);
+    assert_eq!(read(&fetcher, &format!("{}/encoded.xml", base)).unwrap(), URLSET);
+    let fetcher = fetcher.user_agent("test-agent");
+    assert_eq!(read(&fetcher, &format!("{}/sitemap.xml", base)).unwrap(), URLSET);
+    assert_eq!(*user_agents.lock().unwrap(),
+               vec![USER_AGENT.to_string(),
+                    USER_AGENT.to_string(),
+                    USER_AGENT.to_string(),
+                    "test-agent".to_string()]);
+}
+
+#[test]
+fn test_http_fetch_errors() {
+    let (base, _) = server(4);
+    let fetcher = HttpFetch::new().max_body_size(1024);
+    match read(&fetcher, &format!("{}/missing.xml", base)) {
+        Err(Error::HttpStatus { status: 404, .. }) => {}
+        other => panic!("unexpected result {:?}", other),
+    }
+    match read(&fetcher, &format!("{}/page.html", base)) {
+        Err(Error::ContentType { ref content_type, .. }) => assert_eq!(content_type, "text/html"),
+        other => panic!("unexpected result {:?}", other),
+    }
+    match read(&fetcher, &format!("{}/large.xml", base)) {
+        Err(Error::LimitExceeded { kind: LimitKind::Size, limit: 1024 }) => {}
+        other => panic!("unexpected result {:?}", other),
+    }
+    match read(&fetcher, &format!("{}/bomb.xml.gz", base)) {
+        Err(Error::LimitExceeded { kind: LimitKind::Size, limit: 1024 }) => {}
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_read_from_url() {
+    let (base, _) = server(4);
+    let events: Vec<_> = fetch::read_from_url(&format!("{}/sitemap.xml", base)).unwrap().collect();
+    assert!(matches!(events[..], [Ok(SiteMapEvent::Url(_))]));
+
+    let resolver = SiteMapIndexResolver::new(HttpFetch::new());
+    let urls = resolver.resolve(&Url::parse(&format!("{}/index.xml", base)).unwrap())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(urls.len(), 2);
+}
@@ -0,0 +1,164 @@
+extern crate sitemap;
+extern crate url;
+
+use sitemap::Error;
+use sitemap::reader::{ReaderOptions, SiteMapReader, SiteMapEvent};
+use sitemap::validate::{self, IssueKind, ValidationOptions};
+use sitemap::writer::SiteMapWriter;
+use sitemap::structs::{ImageEntry, Location, UrlEntry};
+use std::fs::File;
+use std::io::Read;
+use url::Url;
+
+fn read_urls<T: Read>(source: T) -> (Vec<UrlEntry>, Vec<String>) {
+    let mut urls = Vec::new();
+    let mut errors = Vec::new();
+    for entity in SiteMapReader::new(source) {
+        match entity {
+            Ok(SiteMapEvent::Url(url_entry)) => urls.push(url_entry),
+            Ok(_) => {}
+            Err(error) => errors.push(error.to_string()),
+        }
+    }
+    (urls, errors)
+}
+
+fn write_urls(urls: &[UrlEntry]) -> String {
+    let mut output = Vec::<u8>::new();
+    {
+        let mut url_writer = SiteMapWriter::new(&mut output).start_urlset().expect("start urlset");
+        for url in urls {
+            url_writer.url(url.clone()).expect("write url");
+        }
+        url_writer.end().expect("end urlset");
+    }
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn test_read_images() {
+    let file = File::open("tests/documents/sitemap_images.xml").unwrap();
+    let (urls, errors) = read_urls(file);
+    assert_eq!(urls.len(), 3);
+    assert!(errors.is_empty());
+
+    let images = &urls[0].images;
+    assert_eq!(images.len(), 2);
+    assert_eq!(images[0].loc.get_url().unwrap(),
+               Url::parse("http://example.com/image.jpg").unwrap());
+    assert_eq!(images[0].caption, Some("The caption".to_string()));
+    assert_eq!(images[0].geo_location, Some("Limerick, Ireland".to_string()));
+    assert_eq!(images[0].title, Some("The title".to_string()));
+    assert_eq!(images[0].license.get_url().unwrap(),
+               Url::parse("http://example.com/license.html").unwrap());
+    assert_eq!(images[1],
+               ImageEntry::with_loc(Url::parse("http://example.com/photo.jpg").unwrap()));
+
+    // the image without location is kept, an error of its url
+    assert_eq!(urls[1].images.len(), 2);
+    assert_eq!(urls[1].images[0].loc, Location::None);
+    assert_eq!(urls[1].images[0].title, Some("Image without location".to_string()));
+    assert_eq!(urls[1].images[1],
+               ImageEntry::with_loc(Url::parse("http://example.com/valid.jpg").unwrap()));
+    match urls[1].errors()[..] {
+        [ref error @ Error::MissingElement { element: "image:image", child: "image:loc", .. }] => {
+            assert_eq!(error.to_string(), "22:7 image:image element without image:loc")
+        }
+        ref other => panic!("unexpected errors {:?}", other),
+    }
+    assert!(urls[0].errors().is_empty());
+    assert!(urls[2].images.is_empty());
+
+    let file = File::open("tests/documents/sitemap_images.xml").unwrap();
+    let strict = SiteMapReader::with_options(file, ReaderOptions { strict: true, ..ReaderOptions::default() });
+    let events: Vec<_> = strict.collect();
+    assert_eq!(events.len(), 2);
+    assert!(matches!(events[1], Err(Error::MissingElement { .. })), "{:?}", events[1]);
+
+    let file = File::open("tests/documents/sitemap_images.xml").unwrap();
+    let report = validate::validate(file, &ValidationOptions::default());
+    assert_eq!(report.count(IssueKind::MissingImageLoc), 1);
+    assert!(report.fatal.is_none());
+}
+
+#[test]
+fn test_read_images_any_prefix() {
+    let data = "<urlset xmlns:img=\"http://www.google.com/schemas/sitemap-image/1.1\">\
+                <url><loc>http://example.com/</loc>\
+                <img:image><img:loc>http://example.com/a.jpg</img:loc></img:image>\
+                <image><loc>http://example.com/not_an_image.jpg</loc></image>\
+                </url></urlset>";
+    let (urls, errors) = read_urls(data.as_bytes());
+    assert!(errors.is_empty());
+    assert_eq!(urls[0].loc.get_url().unwrap(), Url::parse("http://example.com/").unwrap());
+    assert_eq!(urls[0].images,
+               vec![ImageEntry::with_loc(Url::parse("http://example.com/a.jpg").unwrap())]);
+}
+
+#[test]
+fn test_write_images() {
+    let file = File::open("tests/documents/sitemap_images.xml").unwrap();
+    let (urls, _) = read_urls(file);
+    let output = write_urls(&urls);
+    assert!(output.contains("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\">"));
+    assert_eq!(output.matches("xmlns:image").count(), 1);
+    assert_eq!(output.matches("<image:image>").count(), 3);
+    let (read, errors) = read_urls(output.as_bytes());
+    assert!(errors.is_empty());
+    // the image without location is not written
+    let mut expected = urls.clone();
+    expected[1].images.remove(0);
+    assert_eq!(read, expected);
+}
+
+#[test]
+fn test_write_namespace_only_with_images() {
+    let plain = UrlEntry::builder().loc("http://example.com/").build().unwrap();
+    let output = write_urls(std::slice::from_ref(&plain));
+    assert!(!output.contains("xmlns:image"));
+
+    let image = ImageEntry::with_loc(Url::parse("http://example.com/a.jpg").unwrap());
+    let with_image = UrlEntry::builder()
+        .loc("http://example.com/images.html")
+        .image(image)
+        .build()
+        .unwrap();
+    let output = write_urls(&[plain, with_image.clone()]);
+    assert!(output.contains("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">"));
+    assert!(output.contains("<image:image xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\">"));
+    let (read, _) = read_urls(output.as_bytes());
+    assert_eq!(read[1], with_image);
+
+    let mut without_loc = with_image.clone();
+    without_loc.images.push(ImageEntry::new());
+    let output = write_urls(&[without_loc]);
+    assert_eq!(output.matches("<image:image>").count(), 1);
+}
+
+#[test]
+fn test_image_limit() {
+    let mut builder = UrlEntry::builder().loc("http://example.com/");
+    for i in 0..1000 {
+        let loc = Url::parse(&format!("http://example.com/{}.jpg", i)).unwrap();
+        builder = builder.image(ImageEntry::with_loc(loc));
+    }
+    let entry = builder.build().unwrap();
+    let output = write_urls(std::slice::from_ref(&entry));
+    let (read, _) = read_urls(output.as_bytes());
+    assert_eq!(read[0].images.len(), 1000);
+
+    let mut too_many = entry.clone();
+    too_many.images.push(ImageEntry::with_loc(Url::parse("http://example.com/x.jpg").unwrap()));
+    let mut output = Vec::<u8>::new();
+    let mut url_writer = SiteMapWriter::new(&mut output).start_urlset().unwrap();
+    assert!(url_writer.url(too_many).is_err());
+
+    let mut builder = UrlEntry::builder().loc("http://example.com/");
+    for image in entry.images {
+        builder = builder.image(image);
+    }
+    let error = builder.image(ImageEntry::new()).image(ImageEntry::new()).build().unwrap_err();
+    assert_eq!(error.errors().len(), 1);
+    assert_eq!(error.errors()[0].field(), "image");
+    assert_eq!(Location::None, ImageEntry::new().loc);
+}
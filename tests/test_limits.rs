@@ -0,0 +1,87 @@
+extern crate sitemap;
+
+use sitemap::{Error, LimitKind};
+use sitemap::writer::{LimitPolicy, SiteMapWriter};
+use sitemap::structs::{UrlEntry, MAX_URLS_PER_SITEMAP};
+use std::io;
+
+fn url(i: usize) -> UrlEntry {
+    UrlEntry::builder()
+        .loc(format!("http://www.example.com/page{}.html", i))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_url_count_limit() {
+    let mut writer = SiteMapWriter::new(io::sink())
+        .start_urlset()
+        .unwrap()
+        .limit_policy(LimitPolicy::Error);
+    for i in 0..MAX_URLS_PER_SITEMAP {
+        writer.url(url(i)).unwrap();
+    }
+    match writer.url(url(MAX_URLS_PER_SITEMAP)) {
+        Err(Error::LimitExceeded { kind: LimitKind::UrlCount, limit }) => {
+            assert_eq!(limit, MAX_URLS_PER_SITEMAP as u64)
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_limits_ignored_by_default() {
+    let mut writer = SiteMapWriter::new(io::sink()).start_urlset().unwrap().max_urls(1).max_bytes(1);
+    writer.url(url(0)).unwrap();
+    writer.url(url(1)).unwrap();
+    assert!(writer.end_urlset().unwrap().warnings().is_empty());
+}
+
+#[test]
+fn test_limit_warnings() {
+    let mut writer = SiteMapWriter::new(io::sink())
+        .start_urlset()
+        .unwrap()
+        .limit_policy(LimitPolicy::Warn)
+        .max_urls(1);
+    for i in 0..3 {
+        writer.url(url(i)).unwrap();
+    }
+    assert_eq!(writer.warnings().len(), 1);
+    let sitemap_writer = writer.end_urlset().unwrap();
+    let warnings = sitemap_writer.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(matches!(warnings[0], Error::LimitExceeded { kind: LimitKind::UrlCount, limit: 1 }));
+    assert_eq!(warnings[0].to_string(), "Sitemap over the limit of 1 urls");
+}
+
+#[test]
+fn test_size_limit_includes_closing_tag() {
+    let mut document = Vec::new();
+    {
+        let mut writer = SiteMapWriter::new(&mut document).start_urlset().unwrap();
+        writer.url(url(0)).unwrap();
+        writer.url(url(1)).unwrap();
+        writer.end().unwrap();
+    }
+    let size = document.len() as u64;
+
+    let mut writer = SiteMapWriter::new(io::sink())
+        .start_urlset()
+        .unwrap()
+        .limit_policy(LimitPolicy::Error)
+        .max_bytes(size);
+    writer.url(url(0)).unwrap();
+    writer.url(url(1)).unwrap();
+
+    let mut writer = SiteMapWriter::new(io::sink())
+        .start_urlset()
+        .unwrap()
+        .limit_policy(LimitPolicy::Error)
+        .max_bytes(size - 1);
+    writer.url(url(0)).unwrap();
+    match writer.url(url(1)) {
+        Err(Error::LimitExceeded { kind: LimitKind::Size, limit }) => assert_eq!(limit, size - 1),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
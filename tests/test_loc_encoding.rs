@@ -0,0 +1,91 @@
+extern crate sitemap;
+
+use sitemap::reader::{ReaderOptions, SiteMapEvent, SiteMapReader};
+use sitemap::structs::{Alternate, Location, UrlEntry};
+use sitemap::writer::{LocEncoding, SiteMapWriter, WriterOptions};
+
+static LOC: &str = "http://m\u{fc}nchen.example/blog/%C3%BCrlaub/\u{fc}mlaut\
+                    ?q=a%26b&amp;x=%2F&amp;city=m\u{fc}nchen#fr%C3%A4ge";
+
+fn read(parse_urls: bool) -> UrlEntry {
+    let document = format!("<urlset><url><loc>{}</loc></url></urlset>", LOC);
+    let options = ReaderOptions {
+        parse_urls,
+        ..ReaderOptions::default()
+    };
+    SiteMapReader::with_options(document.as_bytes(), options)
+        .filter_map(|event| match event {
+            Ok(SiteMapEvent::Url(url)) => Some(url),
+            _ => None,
+        })
+        .next()
+        .unwrap()
+}
+
+/// Text of the `<loc>` of `url` written with `loc_encoding`.
+fn written(url: &UrlEntry, loc_encoding: LocEncoding) -> Option<String> {
+    let options = WriterOptions {
+        loc_encoding,
+        skip_invalid: true,
+        ..WriterOptions::default()
+    };
+    let mut urlset = SiteMapWriter::with_options(Vec::new(), options).start_urlset().unwrap();
+    urlset.url(url.clone()).unwrap();
+    let written = String::from_utf8(urlset.end().unwrap()).unwrap();
+    let start = written.find("<loc>")? + "<loc>".len();
+    Some(written[start..start + written[start..].find("</loc>").unwrap()].to_string())
+}
+
+const NORMALIZED: &str = "http://xn--mnchen-3ya.example/blog/%C3%BCrlaub/%C3%BCmlaut\
+                          ?q=a%26b&amp;x=%2F&amp;city=m%C3%BCnchen#fr%C3%A4ge";
+const UNICODE: &str = "http://m\u{fc}nchen.example/blog/\u{fc}rlaub/\u{fc}mlaut\
+                       ?q=a%26b&amp;x=%2F&amp;city=m\u{fc}nchen#fr\u{e4}ge";
+
+#[test]
+fn test_loc_encoding_parsed_urls() {
+    let url = read(true);
+    assert_eq!(written(&url, LocEncoding::AsParsed).as_deref(), Some(NORMALIZED));
+    assert_eq!(written(&url, LocEncoding::PreserveRaw).as_deref(), Some(NORMALIZED));
+    assert_eq!(written(&url, LocEncoding::DisplayUnicode).as_deref(), Some(UNICODE));
+    assert_eq!(WriterOptions::default().loc_encoding, LocEncoding::PreserveRaw);
+}
+
+#[test]
+fn test_loc_encoding_raw_urls() {
+    let url = read(false);
+    assert_eq!(written(&url, LocEncoding::PreserveRaw).as_deref(), Some(LOC));
+    assert_eq!(written(&url, LocEncoding::AsParsed).as_deref(), Some(NORMALIZED));
+    assert_eq!(written(&url, LocEncoding::DisplayUnicode).as_deref(), Some(UNICODE));
+    // the decoded text parses back to the same url
+    let decoded = UNICODE.replace("&amp;", "&");
+    assert_eq!(Location::from(decoded).get_url(), read(true).loc.get_url());
+
+    let mut invalid = UrlEntry::new();
+    invalid.loc = Location::Raw("http://[::1/".to_string());
+    assert_eq!(written(&invalid, LocEncoding::PreserveRaw).as_deref(), Some("http://[::1/"));
+    assert_eq!(written(&invalid, LocEncoding::AsParsed), None);
+    assert_eq!(written(&invalid, LocEncoding::DisplayUnicode), None);
+}
+
+#[test]
+fn test_loc_encoding_display_unicode() {
+    let display = |loc: &str| {
+        let url = UrlEntry::builder().loc(loc).build().unwrap();
+        written(&url, LocEncoding::DisplayUnicode).unwrap()
+    };
+    // invalid UTF-8 and control characters stay encoded
+    assert_eq!(display("http://example.com/a%FFb/%C3%BC%C3/%C2%85"), "http://example.com/a%FFb/\u{fc}%C3/%C2%85");
+    assert_eq!(display("http://[::1]:8080/%E6%97%A5%20x"), "http://[::1]:8080/\u{65e5}%20x");
+    assert_eq!(display("http://xn--bcher-kva.example/"), "http://b\u{fc}cher.example/");
+
+    let mut url = UrlEntry::builder().loc("http://m\u{fc}nchen.example/").build().unwrap();
+    url.alternates.push(Alternate::new("de", "http://m\u{fc}nchen.example/d\u{e9}".parse().unwrap()));
+    let options = WriterOptions {
+        loc_encoding: LocEncoding::DisplayUnicode,
+        ..WriterOptions::default()
+    };
+    let mut urlset = SiteMapWriter::with_options(Vec::new(), options).start_urlset().unwrap();
+    urlset.url(url).unwrap();
+    let written = String::from_utf8(urlset.end().unwrap()).unwrap();
+    assert!(written.contains("href=\"http://m\u{fc}nchen.example/d\u{e9}\""), "{}", written);
+}
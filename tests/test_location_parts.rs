@@ -0,0 +1,56 @@
+extern crate sitemap;
+extern crate url;
+
+use sitemap::structs::{Location, UrlEntry};
+use sitemap::validate::{self, ScopeViolationKind};
+use url::Url;
+
+fn location(text: &str) -> Location {
+    Location::from(text.to_string())
+}
+
+#[test]
+fn test_location_parts() {
+    let loc = location("https://www.example.com:8443/a/b.html?q=1#top");
+    assert_eq!(loc.scheme(), Some("https"));
+    assert_eq!(loc.host(), Some("www.example.com"));
+    assert_eq!(loc.path(), Some("/a/b.html"));
+
+    let loc = location("http://[::1]:8080");
+    assert_eq!(loc.host(), Some("[::1]"));
+    assert_eq!(loc.path(), Some("/"));
+    assert_eq!(location("mailto:someone@example.com").host(), None);
+
+    for loc in [Location::None, location("not a url"), Location::Raw("http://www.example.com/".to_string())].iter() {
+        assert_eq!(loc.scheme(), None, "{:?}", loc);
+        assert_eq!(loc.host(), None, "{:?}", loc);
+        assert_eq!(loc.path(), None, "{:?}", loc);
+    }
+    let raw = Location::Raw("http://www.example.com/".to_string());
+    assert_eq!(raw.parsed().host(), Some("www.example.com"));
+}
+
+#[test]
+fn test_location_same_origin() {
+    let origin = Url::parse("https://www.example.com/sitemap.xml").unwrap();
+    assert!(location("https://www.example.com/a").is_same_origin(&origin));
+    assert!(location("https://WWW.Example.com:443/a").is_same_origin(&origin));
+    assert!(Location::Raw("https://www.example.com/a".to_string()).is_same_origin(&origin));
+    assert!(!location("http://www.example.com/a").is_same_origin(&origin));
+    assert!(!location("https://www.example.com:8443/a").is_same_origin(&origin));
+    assert!(!location("https://example.com/a").is_same_origin(&origin));
+    assert!(!location("/a").is_same_origin(&origin));
+    assert!(!Location::None.is_same_origin(&origin));
+
+    let ipv6 = Url::parse("http://[0:0::1]/").unwrap();
+    assert!(location("http://[::1]:80/a").is_same_origin(&ipv6));
+    assert!(!location("http://[::2]/a").is_same_origin(&ipv6));
+    assert!(!location("http://127.0.0.1/a").is_same_origin(&ipv6));
+
+    let entries: Vec<UrlEntry> = ["https://www.example.com:443/a", "http://www.example.com/b", "https://[::1]/c"]
+        .iter()
+        .map(|loc| UrlEntry::builder().loc(*loc).build().unwrap())
+        .collect();
+    let kinds: Vec<_> = validate::check_scope(&origin, entries.iter()).iter().map(|violation| violation.kind).collect();
+    assert_eq!(kinds, vec![ScopeViolationKind::Scheme, ScopeViolationKind::Host]);
+}
@@ -0,0 +1,103 @@
+extern crate sitemap;
+
+use sitemap::reader::{ReaderOptions, SiteMapEvent, SiteMapReader};
+use sitemap::structs::{LastMod, Location, Priority};
+use std::fs;
+
+static DOCUMENTS: &[&str] = &["tests/documents/sitemap1.xml",
+                               "tests/documents/sitemap_images.xml",
+                               "tests/documents/sitemap_videos.xml",
+                               "tests/documents/sitemap_news.xml",
+                               "tests/documents/sitemap_alternates.xml",
+                               "tests/documents/sitemap_index_pretty.xml",
+                               "tests/documents/sitemap_prefixed.xml",
+                               "tests/documents/sitemap_bom.xml"];
+
+static TIMES: &[&str] = &["2015-01-20T17:35:20.000000004-08:00",
+                          "2015-01-20T17:35:20+05:45",
+                          "2004-10-01T18:23:17Z",
+                          "Wed, 02 Oct 2002 13:00:00 GMT",
+                          "2024-02-29T00:00:00.5+12:00"];
+
+/// Prefixes of `text` ending on a character boundary.
+fn prefixes(text: &str) -> impl Iterator<Item = &str> {
+    (0..text.len()).filter(move |&end| text.is_char_boundary(end)).map(move |end| &text[..end])
+}
+
+fn read(document: &[u8], options: ReaderOptions) -> Vec<Result<SiteMapEvent, sitemap::Error>> {
+    SiteMapReader::with_options(document, options).collect()
+}
+
+#[test]
+fn test_truncated_times() {
+    for time in TIMES.iter() {
+        for prefix in prefixes(time) {
+            let lastmod = LastMod::from(prefix.to_string());
+            assert_eq!(lastmod.raw(), Some(prefix.to_string()).filter(|_| lastmod != LastMod::None));
+            let _ = prefix.parse::<LastMod>();
+            let document = format!("<urlset><url><loc>http://www.example.com/</loc><lastmod>{}</lastmod></url></urlset>",
+                                   prefix);
+            let events = read(document.as_bytes(), ReaderOptions::default());
+            assert_eq!(events.len(), 1, "{}", prefix);
+        }
+    }
+}
+
+#[test]
+fn test_malformed_values() {
+    let values = ["", "-", ".", "1e40", "-0", "NaN", "inf", "0x1", "\u{1f600}", "http://", "http://[::1", "//a", "%"];
+    for value in values.iter() {
+        let _ = (LastMod::from(value.to_string()), value.parse::<LastMod>());
+        let _ = (Priority::from(value.to_string()), value.parse::<Priority>());
+        let _ = (Location::from(value.to_string()).parsed().into_owned(), value.parse::<Location>());
+    }
+}
+
+#[test]
+fn test_truncated_documents() {
+    let strict = ReaderOptions {
+        strict: true,
+        ..ReaderOptions::default()
+    };
+    for path in DOCUMENTS.iter() {
+        let document = fs::read_to_string(path).unwrap();
+        let end = document.trim_end().len();
+        for prefix in prefixes(&document[..end]) {
+            // the documents may hold a urlset then an index
+            if prefix.trim_end().ends_with("</urlset>") {
+                continue;
+            }
+            for options in [ReaderOptions::default(), strict].iter() {
+                let events = read(prefix.as_bytes(), *options);
+                assert!(events.iter().any(|event| event.is_err()), "{} cut at {}", path, prefix.len());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_without_root() {
+    let documents = ["<?xml version=\"1.0\" encoding=\"UTF-8\"?>",
+                     "<?xml version=\"1.0\" encoding=\"ascii\"?>\n<!-- comment -->\n",
+                     "<?xml version=\"1.0\"?><?xml-stylesheet href=\"a.xsl\"?>"];
+    for document in documents.iter() {
+        let events = read(document.as_bytes(), ReaderOptions::default());
+        match events[..] {
+            [Err(ref error)] => assert!(error.to_string().contains("no root element found"), "{}", error),
+            ref events => panic!("unexpected events {:?}", events),
+        }
+    }
+}
+
+#[test]
+fn test_corrupted_documents() {
+    let document = fs::read("tests/documents/sitemap_news.xml").unwrap();
+    let replacements: [&[u8]; 6] = [b"<", b">", b"&", b"&#0;", b"\xff", b"]]>"];
+    for index in (0..document.len()).step_by(7) {
+        for replacement in replacements.iter() {
+            let mut corrupted = document.clone();
+            corrupted.splice(index..index + 1, replacement.iter().cloned());
+            let _ = read(&corrupted, ReaderOptions::default());
+        }
+    }
+}
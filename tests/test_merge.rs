@@ -0,0 +1,117 @@
+extern crate sitemap;
+extern crate chrono;
+
+use chrono::NaiveDate;
+use sitemap::merge::{self, ConflictPolicy, MergeOptions};
+use sitemap::structs::{Priority, UrlEntry};
+
+fn entry(loc: &str, day: Option<u32>, priority: Option<f32>) -> UrlEntry {
+    let mut builder = UrlEntry::builder().loc(loc);
+    if let Some(day) = day {
+        builder = builder.lastmod_date(NaiveDate::from_ymd_opt(2020, 1, day).unwrap());
+    }
+    if let Some(priority) = priority {
+        builder = builder.priority(priority);
+    }
+    builder.build().expect("valid entry")
+}
+
+fn sources() -> Vec<std::vec::IntoIter<UrlEntry>> {
+    vec![vec![entry("http://www.example.com/a", Some(1), Some(0.9)),
+              entry("http://www.example.com/b", None, None),
+              entry("http://www.example.com/c", None, None)]
+             .into_iter(),
+         vec![entry("http://www.example.com/d", None, None)].into_iter(),
+         vec![entry("HTTP://www.example.com/a", Some(2), Some(0.1)),
+              entry("http://www.example.com/b", Some(1), None)]
+             .into_iter()]
+}
+
+fn merged(options: &MergeOptions) -> Vec<(String, Option<String>, Priority)> {
+    merge::merge(sources(), options)
+        .map(|entry| (entry.loc.raw().unwrap(), entry.lastmod.raw(), entry.priority))
+        .collect()
+}
+
+#[test]
+fn test_merge_policies() {
+    assert_eq!(MergeOptions::default().policy, ConflictPolicy::FirstWins);
+    let options = MergeOptions { policy: ConflictPolicy::FreshestWins, ..MergeOptions::default() };
+    let locs: Vec<_> = merged(&options).into_iter().map(|(loc, _, _)| loc).collect();
+    assert_eq!(locs,
+               ["http://www.example.com/a",
+                "http://www.example.com/d",
+                "http://www.example.com/b",
+                "http://www.example.com/c"]);
+    let merged_entries = merged(&options);
+    assert_eq!(merged_entries[0].1, Some("2020-01-02".to_string()));
+    assert_eq!(merged_entries[2].1, Some("2020-01-01".to_string()));
+
+    let options = MergeOptions { policy: ConflictPolicy::HighestPriorityWins, ..MergeOptions::default() };
+    let merged_entries = merged(&options);
+    assert_eq!(merged_entries[0].2, Priority::Value(0.9));
+    assert_eq!(merged_entries[2].1, Some("2020-01-01".to_string()));
+
+    let options = MergeOptions { policy: ConflictPolicy::FirstWins, ..MergeOptions::default() };
+    let merged_entries = merged(&options);
+    assert_eq!(merged_entries.len(), 4);
+    assert_eq!(merged_entries[0].1, Some("2020-01-01".to_string()));
+    assert_eq!(merged_entries[2].1, None);
+}
+
+#[test]
+fn test_merge_max_urls() {
+    for &policy in &[ConflictPolicy::FirstWins, ConflictPolicy::FreshestWins] {
+        let options = MergeOptions { policy, max_urls: Some(2), ..MergeOptions::default() };
+        let locs: Vec<_> = merged(&options).into_iter().map(|(loc, _, _)| loc).collect();
+        assert_eq!(locs, ["http://www.example.com/a", "http://www.example.com/d"]);
+    }
+}
+
+#[test]
+fn test_merge_lazy() {
+    let source = (0..).map(|i| entry(&format!("http://www.example.com/{}", i % 10), None, None));
+    let options = MergeOptions { policy: ConflictPolicy::FirstWins, ..MergeOptions::default() };
+    let merged: Vec<_> = merge::merge(vec![Box::new(source) as Box<dyn Iterator<Item = UrlEntry>>], &options)
+        .take(5)
+        .collect();
+    assert_eq!(merged.len(), 5);
+}
+
+#[test]
+fn test_merge_remaining() {
+    let options = MergeOptions { max_urls: Some(3), ..MergeOptions::default() };
+    let mut merged = merge::merge(sources(), &options);
+    assert_eq!(merged.by_ref().count(), 3);
+    let remaining: Vec<_> = merged.into_remaining().map(|entry| entry.loc.raw().unwrap()).collect();
+    assert_eq!(remaining, ["http://www.example.com/c"]);
+}
+
+#[test]
+fn test_merge_reopening() {
+    for &policy in &[ConflictPolicy::FirstWins, ConflictPolicy::FreshestWins, ConflictPolicy::HighestPriorityWins] {
+        let options = MergeOptions { policy, ..MergeOptions::default() };
+        let mut opened = 0;
+        let open = || {
+            opened += 1;
+            sources()
+        };
+        let mut reopened: Vec<_> = merge::merge_reopening(open, &options)
+            .map(|entry| (entry.loc.raw().unwrap(), entry.lastmod.raw(), entry.priority))
+            .collect();
+        assert_eq!(opened, 2);
+        let mut expected = merged(&options);
+        reopened.sort_by(|a, b| a.0.cmp(&b.0));
+        expected.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(reopened, expected, "{:?}", policy);
+    }
+
+    // the entries kept are in the order they are read, a and b of the
+    // third source after d
+    let options = MergeOptions { policy: ConflictPolicy::FreshestWins, max_urls: Some(2), ..MergeOptions::default() };
+    let mut merged = merge::merge_reopening(sources, &options);
+    let locs: Vec<_> = merged.by_ref().map(|entry| entry.loc.raw().unwrap()).collect();
+    assert_eq!(locs, ["http://www.example.com/d", "http://www.example.com/a"]);
+    let remaining: Vec<_> = merged.into_remaining().map(|entry| entry.lastmod.raw()).collect();
+    assert_eq!(remaining, [Some("2020-01-01".to_string()), None]);
+}
@@ -0,0 +1,85 @@
+extern crate sitemap;
+
+use sitemap::reader::{ReaderOptions, SiteMapEvent, SiteMapReader};
+use sitemap::structs::{ChangeFreq, LastMod, Position, Priority, SiteMapEntry, UrlEntry};
+use sitemap::Error;
+use std::fs::File;
+
+fn sitemaps(events: &[Result<SiteMapEvent, Error>]) -> Vec<&SiteMapEntry> {
+    events.iter()
+        .filter_map(|event| match *event {
+            Ok(SiteMapEvent::SiteMap(ref sitemap)) => Some(sitemap),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_sitemap_url_fields_lenient() {
+    let mut reader = SiteMapReader::new(File::open("tests/documents/sitemap_index_url_fields.xml").unwrap());
+    let events: Vec<_> = reader.by_ref().collect();
+    let sitemaps = sitemaps(&events);
+    assert_eq!(sitemaps.len(), 2);
+    assert_eq!(sitemaps[0].loc.as_str(), Some("https://www.example.com/post-sitemap.xml"));
+    assert!(matches!(sitemaps[0].lastmod, LastMod::DateTime(_)));
+    assert_eq!(sitemaps[1].loc.as_str(), Some("https://www.example.com/page-sitemap.xml"));
+    assert!(sitemaps.iter().all(|sitemap| sitemap.errors().is_empty()));
+    let warnings: Vec<String> = reader.warnings().iter().map(|warning| warning.to_string()).collect();
+    assert_eq!(warnings,
+               vec!["6:5 changefreq 'daily' is not a field of sitemap",
+                    "7:5 priority '0.8' is not a field of sitemap",
+                    "11:5 changefreq 'weekly' is not a field of sitemap"]);
+}
+
+#[test]
+fn test_sitemap_url_fields_strict() {
+    let options = ReaderOptions { strict: true, ..ReaderOptions::default() };
+    let file = File::open("tests/documents/sitemap_index_url_fields.xml").unwrap();
+    let mut reader = SiteMapReader::with_options(file, options);
+    match reader.next() {
+        Some(Err(Error::Parse { line: 6, column: 5, ref message })) => {
+            assert_eq!(message, "changefreq 'daily' is not a field of sitemap")
+        }
+        other => panic!("unexpected event {:?}", other),
+    }
+    assert!(reader.next().is_none());
+    assert!(reader.warnings().is_empty());
+}
+
+#[test]
+fn test_urlset_of_sitemaps() {
+    let events: Vec<_> = SiteMapReader::new(File::open("tests/documents/sitemap_urlset_of_sitemaps.xml").unwrap())
+        .collect();
+    let sitemaps: Vec<SiteMapEntry> = events.into_iter()
+        .filter_map(|event| match event {
+            Ok(SiteMapEvent::Url(url)) => Some(SiteMapEntry::from(url)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(sitemaps.len(), 2);
+    assert_eq!(sitemaps[1].loc.as_str(), Some("https://www.example.com/page-sitemap.xml"));
+    assert!(matches!(sitemaps[1].lastmod, LastMod::Date(_)));
+    assert_eq!(sitemaps[1].field_position("lastmod"), Some(Position { line: 9, column: 5 }));
+    assert_eq!(sitemaps[1].field_position("changefreq"), None);
+    assert_eq!(sitemaps[1].position(), Some(Position { line: 7, column: 3 }));
+}
+
+#[test]
+fn test_entry_conversions() {
+    let url = UrlEntry::builder()
+        .loc("https://www.example.com/sitemap.xml")
+        .lastmod_str("2024-03-01")
+        .changefreq(ChangeFreq::Daily)
+        .priority(0.5)
+        .build()
+        .unwrap();
+    let sitemap = SiteMapEntry::from(url.clone());
+    assert_eq!(sitemap.loc, url.loc);
+    assert_eq!(sitemap.lastmod, url.lastmod);
+
+    let back = UrlEntry::from(sitemap);
+    assert_eq!(back.loc, url.loc);
+    assert_eq!(back.lastmod, url.lastmod);
+    assert_eq!(back.changefreq, ChangeFreq::None);
+    assert_eq!(back.priority, Priority::None);
+}
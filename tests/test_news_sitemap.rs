@@ -0,0 +1,84 @@
+extern crate sitemap;
+extern crate chrono;
+
+use sitemap::reader::{SiteMapReader, SiteMapEvent};
+use sitemap::writer::SiteMapWriter;
+use sitemap::structs::{LastMod, News, NewsEntry, NewsError, NewsPublication, UrlEntry};
+use chrono::DateTime;
+use std::fs::File;
+use std::io::Read;
+
+fn read_urls<T: Read>(source: T) -> Vec<UrlEntry> {
+    let mut urls = Vec::new();
+    for entity in SiteMapReader::new(source) {
+        match entity {
+            Ok(SiteMapEvent::Url(url_entry)) => urls.push(url_entry),
+            Ok(_) => {}
+            Err(error) => panic!("unexpected error {:?}", error),
+        }
+    }
+    urls
+}
+
+fn write_urls(urls: &[UrlEntry]) -> String {
+    let mut output = Vec::<u8>::new();
+    {
+        let mut url_writer = SiteMapWriter::new(&mut output).start_urlset().expect("start urlset");
+        for url in urls {
+            url_writer.url(url.clone()).expect("write url");
+        }
+        url_writer.end().expect("end urlset");
+    }
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn test_read_news() {
+    let urls = read_urls(File::open("tests/documents/sitemap_news.xml").unwrap());
+    assert_eq!(urls.len(), 5);
+    let news = urls[0].news.get_entry().expect("valid news");
+    assert_eq!(news.publication.name, "The Example Times");
+    assert_eq!(news.publication.language, "en");
+    assert_eq!(news.publication_date.get_time().unwrap(),
+               DateTime::parse_from_rfc3339("2008-12-23T00:00:00+00:00").unwrap());
+    assert_eq!(news.title, "Companies A, B in Merger Talks");
+
+    assert_eq!(urls[1].news, News::Err(NewsError::MissingTitle));
+    assert_eq!(urls[2].news, News::Err(NewsError::MissingPublication));
+    match urls[3].news.get_entry().unwrap().publication_date {
+        LastMod::Err { .. } => {}
+        ref other => panic!("unexpected lastmod {:?}", other),
+    }
+    assert_eq!(urls[4].news, News::None);
+}
+
+#[test]
+fn test_write_news() {
+    let urls = read_urls(File::open("tests/documents/sitemap_news.xml").unwrap());
+    let output = write_urls(&urls[..1]);
+    assert!(output.contains("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" xmlns:news=\"http://www.google.com/schemas/sitemap-news/0.9\">"));
+    assert!(output.contains("<news:title>Companies A, B in Merger Talks</news:title>"));
+    assert_eq!(read_urls(output.as_bytes()), &urls[..1]);
+
+    // invalid news are skipped
+    let output = write_urls(&urls[1..3]);
+    assert!(!output.contains("news:news"));
+}
+
+#[test]
+fn test_build_news() {
+    let publication = NewsPublication {
+        name: "The Example Times".to_string(),
+        language: "en".to_string(),
+    };
+    let date = DateTime::parse_from_rfc3339("2008-12-23T10:00:00+01:00").unwrap();
+    let news = NewsEntry::new(publication, date, "Title".to_string());
+    let entry = UrlEntry::builder()
+        .loc("http://www.example.org/business/article55.html")
+        .news(news)
+        .build()
+        .unwrap();
+    let output = write_urls(std::slice::from_ref(&entry));
+    assert!(output.contains("<news:publication_date>2008-12-23T10:00:00+01:00</news:publication_date>"));
+    assert_eq!(read_urls(output.as_bytes()), vec![entry]);
+}
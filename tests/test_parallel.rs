@@ -0,0 +1,114 @@
+#![cfg(feature = "parallel")]
+extern crate sitemap;
+
+use sitemap::index::{self, ParallelOptions};
+use sitemap::resolver::MemoryFetch;
+use sitemap::structs::{Location, SiteMapEntry};
+
+fn index(locs: &[&str]) -> String {
+    let mut document = String::from("<sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">");
+    for loc in locs {
+        document.push_str(&format!("<sitemap><loc>{}</loc></sitemap>", loc));
+    }
+    document.push_str("</sitemapindex>");
+    document
+}
+
+fn urlset(locs: &[&str]) -> String {
+    let mut document = String::from("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">");
+    for loc in locs {
+        document.push_str(&format!("<url><loc>{}</loc></url>", loc));
+    }
+    document.push_str("</urlset>");
+    document
+}
+
+fn entries(locs: &[&str]) -> Vec<SiteMapEntry> {
+    locs.iter().map(|loc| SiteMapEntry::builder().loc(*loc).build().unwrap()).collect()
+}
+
+/// Locations of the urls of each entry, or the message of its error.
+fn resolve(entries: &[SiteMapEntry], fetcher: MemoryFetch, options: &ParallelOptions) -> Vec<Result<Vec<String>, String>> {
+    index::resolve_parallel_with_options(entries, fetcher, |_, urls| {
+            urls.map(|url| url.map(|url| url.loc.get_url().unwrap().to_string())).collect()
+        }, options)
+        .into_iter()
+        .map(|result| result.map_err(|error| error.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_parallel_order() {
+    let mut fetcher = MemoryFetch::new();
+    let mut locs = Vec::new();
+    for i in 0..20 {
+        let loc = format!("http://www.example.com/{}.xml", i);
+        fetcher = fetcher.document(&loc, urlset(&[&format!("http://www.example.com/{}", i)]));
+        locs.push(loc);
+    }
+    let locs: Vec<&str> = locs.iter().map(String::as_str).collect();
+    let expected: Vec<Result<Vec<String>, String>> =
+        (0..20).map(|i| Ok(vec![format!("http://www.example.com/{}", i)])).collect();
+    for threads in [1, 3, 8, 50].iter() {
+        let options = ParallelOptions {
+            threads: *threads,
+            ..ParallelOptions::default()
+        };
+        assert_eq!(resolve(&entries(&locs), fetcher.clone(), &options), expected);
+    }
+}
+
+#[test]
+fn test_parallel_errors() {
+    let fetcher = MemoryFetch::new()
+        .document("http://www.example.com/a.xml", urlset(&["http://www.example.com/1"]))
+        .document("http://www.example.com/broken.xml", "<urlset><url>")
+        .document("http://www.example.com/c.xml", urlset(&["http://www.example.com/3"]));
+    let mut entries = entries(&["http://www.example.com/a.xml",
+                                "http://www.example.com/missing.xml",
+                                "http://www.example.com/broken.xml",
+                                "http://www.example.com/a.xml",
+                                "http://www.example.com/c.xml"]);
+    let mut entry = entries[0].clone();
+    entry.loc = Location::None;
+    entries.push(entry);
+    let results = resolve(&entries, fetcher, &ParallelOptions::default());
+    assert_eq!(results.len(), 6);
+    assert_eq!(results[0], Ok(vec!["http://www.example.com/1".to_string()]));
+    assert!(results[1].is_err());
+    assert!(results[2].is_err());
+    assert_eq!(results[3], Err("sitemap http://www.example.com/a.xml already visited".to_string()));
+    assert_eq!(results[4], Ok(vec!["http://www.example.com/3".to_string()]));
+    assert_eq!(results[5], Err("sitemap without loc".to_string()));
+}
+
+#[test]
+fn test_parallel_depth() {
+    let fetcher = MemoryFetch::new()
+        .document("http://www.example.com/nested.xml", index(&["http://www.example.com/a.xml"]))
+        .document("http://www.example.com/a.xml", urlset(&["http://www.example.com/1"]));
+    let entries = entries(&["http://www.example.com/nested.xml"]);
+    assert!(resolve(&entries, fetcher.clone(), &ParallelOptions::default())[0].is_err());
+    let options = ParallelOptions {
+        max_depth: 2,
+        ..ParallelOptions::default()
+    };
+    assert_eq!(resolve(&entries, fetcher, &options),
+               vec![Ok(vec!["http://www.example.com/1".to_string()])]);
+}
+
+#[test]
+fn test_parallel_empty() {
+    assert!(resolve(&[], MemoryFetch::new(), &ParallelOptions::default()).is_empty());
+}
+
+#[test]
+fn test_parallel_normalized_duplicates() {
+    let fetcher = MemoryFetch::new().document("http://www.example.com/a.xml", urlset(&["http://www.example.com/1"]));
+    let entries = entries(&["http://www.example.com/a.xml", "http://www.example.com/%61.xml", "http://WWW.Example.com/a.xml"]);
+    let results = resolve(&entries, fetcher, &ParallelOptions::default());
+    assert_eq!(results,
+               vec![Ok(vec!["http://www.example.com/1".to_string()]),
+                    Err("sitemap http://www.example.com/%61.xml already visited".to_string()),
+                    Err("sitemap http://www.example.com/a.xml already visited".to_string())]);
+}
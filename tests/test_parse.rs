@@ -0,0 +1,82 @@
+extern crate sitemap;
+
+use sitemap::Error;
+use sitemap::reader::{parse, parse_index, parse_urls, SiteMapDocument};
+use std::fs::File;
+
+static INVALID: &[u8] = b"<urlset>
+<url><loc>http://www.example.com/</loc><priority>1.5</priority></url>
+<url><loc>http://www.example.com/about</loc></url>
+</urlset>";
+
+#[test]
+fn test_parse_urls() {
+    let parsed = parse_urls(File::open("tests/documents/sitemap_images.xml").unwrap(), false)
+        .unwrap();
+    assert_eq!(parsed.value.len(), 3);
+    assert_eq!(parsed.warnings.len(), 1);
+
+    let parsed = parse_urls(INVALID, false).unwrap();
+    assert_eq!(parsed.value.len(), 2);
+    assert_eq!(parsed.warnings.len(), 1);
+    assert_eq!(parsed.warnings[0].to_string(),
+               "2:40 Invalid <priority> value: priority should be between 0 and 1");
+    match parse_urls(INVALID, true) {
+        Err(Error::Parse { line: 2, .. }) => {}
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_index() {
+    let index: &[u8] = b"<sitemapindex><sitemap><loc>http://www.example.com/1.xml</loc>\
+                         </sitemap></sitemapindex>";
+    let parsed = parse_index(index, true).unwrap();
+    assert_eq!(parsed.value.len(), 1);
+    assert!(parsed.warnings.is_empty());
+    assert!(matches!(parse_urls(index, true), Err(Error::Invalid(_))));
+    assert!(matches!(parse_index(INVALID, false), Err(Error::Invalid(_))));
+}
+
+#[test]
+fn test_parse_document() {
+    match parse(&b"<urlset></urlset>"[..], true).unwrap().value {
+        SiteMapDocument::UrlSet(urls) => assert!(urls.is_empty()),
+        other => panic!("unexpected document {:?}", other),
+    }
+    match parse(&b"<sitemapindex></sitemapindex>"[..], true).unwrap().value {
+        SiteMapDocument::Index(sitemaps) => assert!(sitemaps.is_empty()),
+        other => panic!("unexpected document {:?}", other),
+    }
+    assert!(matches!(parse(&b"<urlset><url>"[..], false), Err(Error::XmlReadError(_))));
+    assert!(matches!(parse(&b"<rss></rss>"[..], false), Err(Error::Invalid(_))));
+    // the first document has both a urlset and a sitemapindex
+    let file = File::open("tests/documents/sitemap1.xml").unwrap();
+    assert!(matches!(parse(file, false), Err(Error::Invalid(_))));
+}
+
+#[test]
+fn test_parse_fatal_errors() {
+    let long_loc = format!("<urlset><url><loc>http://www.example.com/{}</loc></url></urlset>", "a".repeat(5000));
+    let rss: &[u8] = b"<rss><channel><item><link>http://www.example.com/</link></item></channel></rss>";
+    for &strict in &[false, true] {
+        assert!(matches!(parse(long_loc.as_bytes(), strict), Err(Error::ResourceLimit { .. })));
+        assert!(matches!(parse_urls(long_loc.as_bytes(), strict), Err(Error::ResourceLimit { .. })));
+        assert!(matches!(parse_index(long_loc.as_bytes(), strict), Err(Error::ResourceLimit { .. })));
+
+        assert!(matches!(parse(&b"garbage"[..], strict), Err(Error::NotXml { .. })));
+        assert!(matches!(parse_urls(&b"garbage"[..], strict), Err(Error::NotXml { .. })));
+        assert!(matches!(parse_index(&b"garbage"[..], strict), Err(Error::NotXml { .. })));
+
+        for result in [parse(rss, strict).map(|_| ()),
+                       parse_urls(rss, strict).map(|_| ()),
+                       parse_index(rss, strict).map(|_| ())] {
+            match result {
+                Err(Error::Invalid(message)) => {
+                    assert_eq!(message, "document with a root element other than urlset and sitemapindex")
+                }
+                other => panic!("unexpected result {:?}", other),
+            }
+        }
+    }
+}
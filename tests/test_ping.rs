@@ -0,0 +1,88 @@
+#![cfg(feature = "http")]
+extern crate sitemap;
+extern crate url;
+
+use sitemap::ping::{self, Engine};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use url::Url;
+
+/// Starts a server answering `requests` requests, `/missing` with a 404,
+/// returns its base url and the paths requested.
+fn server(requests: usize) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let paths = Arc::new(Mutex::new(Vec::new()));
+    let received = paths.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming().take(requests) {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let path = line.split(' ').nth(1).unwrap_or("/").to_string();
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+            }
+            let status = if path.starts_with("/missing") { "404 Not Found" } else { "200 OK" };
+            received.lock().unwrap().push(path);
+            let response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                                   status);
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+    (base, paths)
+}
+
+#[test]
+fn test_ping_url() {
+    let sitemap_url = Url::parse("http://www.example.com/sitemap.xml?page=1&lang=en").unwrap();
+    assert_eq!(Engine::Google.ping_url(&sitemap_url).as_str(),
+               "https://www.google.com/ping?sitemap=http%3A%2F%2Fwww.example.com%2Fsitemap.\
+                xml%3Fpage%3D1%26lang%3Den");
+    assert_eq!(Engine::Bing.ping_url(&sitemap_url).as_str(),
+               "https://www.bing.com/ping?sitemap=http%3A%2F%2Fwww.example.com%2Fsitemap.\
+                xml%3Fpage%3D1%26lang%3Den");
+    let custom = Engine::Custom(Url::parse("http://search.example.org/submit?key=1").unwrap());
+    assert_eq!(custom.ping_url(&sitemap_url).as_str(),
+               "http://search.example.org/submit?key=1&sitemap=http%3A%2F%2Fwww.example.com%2F\
+                sitemap.xml%3Fpage%3D1%26lang%3Den");
+}
+
+#[test]
+fn test_ping() {
+    let (base, paths) = server(2);
+    let engines = [Engine::Custom(Url::parse(&format!("{}/ping", base)).unwrap()),
+                   Engine::Custom(Url::parse(&format!("{}/missing", base)).unwrap()),
+                   Engine::Custom(Url::parse("http://127.0.0.1:1/ping").unwrap())];
+    let sitemap_url = Url::parse("http://www.example.com/sitemap.xml").unwrap();
+    let results = ping::ping(&engines, &sitemap_url);
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].engine, engines[0]);
+    assert_eq!(results[0].status.as_ref().ok(), Some(&200));
+    assert!(results[0].is_success());
+    assert_eq!(results[1].status.as_ref().ok(), Some(&404));
+    assert!(!results[1].is_success());
+    assert!(results[2].status.is_err());
+    assert_eq!(*paths.lock().unwrap(),
+               vec!["/ping?sitemap=http%3A%2F%2Fwww.example.com%2Fsitemap.xml",
+                    "/missing?sitemap=http%3A%2F%2Fwww.example.com%2Fsitemap.xml"]);
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_ping_async() {
+    let (base, _) = server(2);
+    let engines = [Engine::Custom(Url::parse(&format!("{}/ping", base)).unwrap()),
+                   Engine::Custom(Url::parse(&format!("{}/missing", base)).unwrap())];
+    let sitemap_url = Url::parse("http://www.example.com/sitemap.xml").unwrap();
+    let results = ping::ping_async(&engines, &sitemap_url).await;
+    assert_eq!(results.iter().map(|result| result.status.as_ref().ok().cloned()).collect::<Vec<_>>(),
+               vec![Some(200), Some(404)]);
+}
@@ -0,0 +1,63 @@
+extern crate sitemap;
+
+use sitemap::Error;
+use sitemap::reader::{SiteMapReader, SiteMapEvent};
+use sitemap::structs::{Position, UrlEntry};
+
+static DOCUMENT: &str = "<urlset>
+  <url>
+    <loc>http://www.example.com/</loc>
+    <lastmod>2005-13-01</lastmod>
+    <priority>2</priority>
+  </url>
+</urlset>";
+
+fn read_urls(document: &str) -> Vec<UrlEntry> {
+    SiteMapReader::new(document.as_bytes())
+        .filter_map(|event| match event.unwrap() {
+            SiteMapEvent::Url(url_entry) => Some(url_entry),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_entry_positions() {
+    let urls = read_urls(DOCUMENT);
+    assert_eq!(urls[0].position(), Some(Position { line: 2, column: 3 }));
+    assert_eq!(urls[0].field_position("lastmod"), Some(Position { line: 4, column: 5 }));
+    assert_eq!(urls[0].field_position("changefreq"), None);
+
+    let errors = urls[0].errors();
+    assert_eq!(errors.len(), 2);
+    match errors[0] {
+        Error::Parse { line, column, ref message } => {
+            assert_eq!((line, column), (4, 5));
+            assert!(message.starts_with("Invalid <lastmod> value"));
+        }
+        ref other => panic!("unexpected error {:?}", other),
+    }
+    assert_eq!(errors[1].to_string(),
+               "5:5 Invalid <priority> value: priority should be between 0 and 1");
+    assert_eq!(errors[1].position(), Some(Position { line: 5, column: 5 }));
+
+    // positions are not part of the entry value
+    let mut entry = UrlEntry::new();
+    entry.loc = urls[0].loc.clone();
+    entry.lastmod = urls[0].lastmod.clone();
+    entry.priority = urls[0].priority.clone();
+    assert_eq!(entry, urls[0]);
+    assert_eq!(entry.position(), None);
+    match entry.errors()[0] {
+        Error::Parse { line: 0, column: 0, .. } => {}
+        ref other => panic!("unexpected error {:?}", other),
+    }
+}
+
+#[test]
+fn test_error_positions() {
+    let data: &[u8] = b"<urlset>\n<url>\n<loc>http://www.example.com/</loc></urlset>";
+    let error = SiteMapReader::new(data).find(|event| event.is_err()).unwrap().unwrap_err();
+    assert!(matches!(error, Error::XmlReadError(_)));
+    assert_eq!(error.position().map(|position| position.line), Some(3));
+}
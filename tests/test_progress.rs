@@ -0,0 +1,72 @@
+extern crate sitemap;
+
+use sitemap::reader::{Progress, ReaderOptions, SiteMapReader};
+use std::fs::{self, File};
+use std::sync::{Arc, Mutex};
+
+/// Reads `reader` to the end, with the progress reported.
+fn progress<T: std::io::Read>(reader: SiteMapReader<T>) -> (Vec<Progress>, Progress) {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let recorded = calls.clone();
+    let mut reader = reader.on_progress(move |progress| recorded.lock().unwrap().push(progress));
+    for entry in reader.by_ref() {
+        entry.unwrap();
+    }
+    let calls = calls.lock().unwrap().clone();
+    (calls, reader.progress())
+}
+
+#[test]
+fn test_progress_entries() {
+    let length = fs::metadata("tests/documents/sitemap1.xml").unwrap().len();
+    let options = ReaderOptions { progress_entries: 2, ..ReaderOptions::default() };
+    let reader = SiteMapReader::with_options(File::open("tests/documents/sitemap1.xml").unwrap(), options);
+    let (calls, last) = progress(reader);
+    let entries: Vec<u64> = calls.iter().map(|progress| progress.entries).collect();
+    assert_eq!(entries, vec![2, 4, 6, 7]);
+    assert_eq!(last, Progress { bytes: length, compressed_bytes: None, entries: 7 });
+    assert_eq!(calls.last(), Some(&last));
+}
+
+#[test]
+fn test_progress_bytes() {
+    let document: String = (0..100)
+        .map(|i| format!("<url><loc>http://www.example.com/{}</loc></url>\n", i))
+        .collect();
+    let document = format!("<urlset>\n{}</urlset>", document);
+    let options = ReaderOptions { progress_bytes: 1024, ..ReaderOptions::default() };
+    let reader = SiteMapReader::with_options(document.as_bytes(), options);
+    let (calls, last) = progress(reader);
+    assert!(calls.len() > 2, "{:?}", calls);
+    for pair in calls.windows(2) {
+        assert!(pair[1].bytes > pair[0].bytes || pair[1] == last, "{:?}", calls);
+    }
+    assert_eq!(last.bytes, document.len() as u64);
+    assert_eq!(last.entries, 100);
+
+    // no callback, the counters are kept
+    let mut reader = SiteMapReader::new(document.as_bytes());
+    assert_eq!(reader.by_ref().filter(Result::is_ok).count(), 100);
+    assert_eq!(reader.entries_emitted(), 100);
+    assert_eq!(reader.bytes_read(), document.len() as u64);
+    assert!(reader.next().is_none());
+    assert_eq!(reader.entries_emitted(), 100);
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn test_progress_gzip() {
+    let compressed = fs::metadata("tests/documents/sitemap_large.xml.gz").unwrap().len();
+    let (calls, last) = progress(SiteMapReader::from_gzip(File::open("tests/documents/sitemap_large.xml.gz").unwrap()));
+    assert_eq!(last.compressed_bytes, Some(compressed));
+    assert!(last.bytes > compressed, "{:?}", last);
+    assert_eq!(last.entries, 5000);
+    let mut entries: Vec<u64> = calls.iter().map(|progress| progress.entries).filter(|entries| entries % 1000 == 0).collect();
+    entries.dedup();
+    assert_eq!(entries, vec![1000, 2000, 3000, 4000, 5000]);
+
+    let reader = SiteMapReader::from_maybe_gzip(File::open("tests/documents/sitemap_large.xml.gz").unwrap()).unwrap();
+    assert_eq!(progress(reader).1, last);
+    let reader = SiteMapReader::from_maybe_gzip(File::open("tests/documents/sitemap1.xml").unwrap()).unwrap();
+    assert_eq!(progress(reader).1.compressed_bytes, None);
+}
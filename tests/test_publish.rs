@@ -0,0 +1,116 @@
+#![cfg(feature = "fs")]
+extern crate sitemap;
+
+use sitemap::Error;
+use sitemap::publish::AtomicPublisher;
+use sitemap::structs::UrlEntry;
+use sitemap::writer::SplitSiteMapWriter;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Empty target directory of `test`.
+fn target(test: &str) -> PathBuf {
+    let target = std::env::temp_dir().join(format!("sitemap-publish-{}-{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&target);
+    fs::create_dir_all(&target).unwrap();
+    target
+}
+
+fn files(target: &Path) -> Vec<String> {
+    let mut files: Vec<String> = fs::read_dir(target)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    files
+}
+
+/// Publishes a split sitemap of `urls` urls, one url for each sitemap.
+fn publish(target: &Path, urls: usize) -> Result<Vec<PathBuf>, Error> {
+    let publisher = AtomicPublisher::new(target, "sitemap_index.xml")?;
+    let mut writer = SplitSiteMapWriter::new(|number| publisher.create(&format!("sitemap-{}.xml", number)),
+                                             "http://www.example.com/sitemap-{}.xml")?
+        .max_urls(1);
+    for number in 0..urls {
+        writer.url(UrlEntry::builder().loc(format!("http://www.example.com/{}", number)).build()?)?;
+    }
+    writer.end(publisher.create("sitemap_index.xml")?)?.flush()?;
+    publisher.publish()
+}
+
+#[test]
+fn test_publish() {
+    let target = target("run");
+    for name in ["sitemap-9.xml.gz", "sitemap_index.xml", "sitemap-old.txt", "other.xml"].iter() {
+        fs::write(target.join(name), "old").unwrap();
+    }
+    fs::create_dir(target.join("sitemap-dir.xml")).unwrap();
+    assert_eq!(publish(&target, 3).unwrap(), vec![target.join("sitemap-9.xml.gz")]);
+    assert_eq!(files(&target),
+               vec!["other.xml",
+                    "sitemap-1.xml",
+                    "sitemap-2.xml",
+                    "sitemap-3.xml",
+                    "sitemap-dir.xml",
+                    "sitemap-old.txt",
+                    "sitemap_index.xml"]);
+    let index = fs::read_to_string(target.join("sitemap_index.xml")).unwrap();
+    assert!(index.contains("<loc>http://www.example.com/sitemap-3.xml</loc>"), "{}", index);
+    assert!(fs::read_to_string(target.join("sitemap-2.xml")).unwrap().contains("http://www.example.com/1"));
+
+    assert_eq!(publish(&target, 1).unwrap(),
+               vec![target.join("sitemap-2.xml"), target.join("sitemap-3.xml")]);
+    assert_eq!(files(&target),
+               vec!["other.xml", "sitemap-1.xml", "sitemap-dir.xml", "sitemap-old.txt", "sitemap_index.xml"]);
+    fs::remove_dir_all(&target).unwrap();
+}
+
+#[test]
+fn test_publish_rollback() {
+    let target = target("rollback");
+    fs::write(target.join("sitemap-1.xml"), "old").unwrap();
+    let publisher = AtomicPublisher::new(&target, "sitemap_index.xml").unwrap();
+    publisher.create("sitemap-1.xml").unwrap().write_all(b"new").unwrap();
+    publisher.create("sitemap-2.xml").unwrap();
+    assert_eq!(files(&target).len(), 2);
+    assert_eq!(publisher.publish().unwrap_err().to_string(), "index sitemap_index.xml not created");
+    assert_eq!(files(&target), vec!["sitemap-1.xml"]);
+    assert_eq!(fs::read_to_string(target.join("sitemap-1.xml")).unwrap(), "old");
+
+    let publisher = AtomicPublisher::new(&target, "sitemap_index.xml").unwrap();
+    publisher.create("sitemap_index.xml").unwrap();
+    drop(publisher);
+    assert_eq!(files(&target), vec!["sitemap-1.xml"]);
+
+    let publisher = AtomicPublisher::new(&target, "sitemap_index.xml").unwrap().stale_prefix("none-");
+    publisher.create("sitemap_index.xml").unwrap();
+    assert!(publisher.create("../sitemap-2.xml").is_err());
+    assert!(publisher.publish().unwrap().is_empty());
+    assert_eq!(files(&target), vec!["sitemap-1.xml", "sitemap_index.xml"]);
+
+    assert!(AtomicPublisher::new(&target, "index/sitemap.xml").is_err());
+    match AtomicPublisher::new(&target.join("missing"), "sitemap_index.xml") {
+        Err(Error::File { path, .. }) => assert!(path.starts_with(target.join("missing"))),
+        Err(error) => panic!("unexpected error {:?}", error),
+        Ok(_) => panic!("missing target directory"),
+    }
+    fs::remove_dir_all(&target).unwrap();
+}
+
+#[test]
+fn test_publish_concurrent_publishers() {
+    let target = target("concurrent");
+    let first = AtomicPublisher::new(&target, "sitemap_index.xml").unwrap();
+    first.create("sitemap-1.xml").unwrap().write_all(b"first").unwrap();
+    // a second publisher of the same process keeps the files of the first
+    let second = AtomicPublisher::new(&target, "index.xml").unwrap().stale_prefix("none-");
+    second.create("index.xml").unwrap().write_all(b"second").unwrap();
+    assert_eq!(files(&target).len(), 2);
+    first.create("sitemap_index.xml").unwrap();
+    assert!(first.publish().unwrap().is_empty());
+    assert!(second.publish().unwrap().is_empty());
+    assert_eq!(files(&target), vec!["index.xml", "sitemap-1.xml", "sitemap_index.xml"]);
+    assert_eq!(fs::read_to_string(target.join("sitemap-1.xml")).unwrap(), "first");
+    fs::remove_dir_all(&target).unwrap();
+}
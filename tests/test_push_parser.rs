@@ -0,0 +1,173 @@
+#![cfg(feature = "fast-xml")]
+extern crate sitemap;
+
+use sitemap::Error;
+use sitemap::push::PushSiteMapParser;
+use sitemap::reader::{DocumentKind, ReaderOptions, SiteMapEvent, SiteMapReader, TruncateReason};
+use std::fs;
+
+static DOCUMENTS: &[&str] = &["tests/documents/sitemap1.xml",
+                               "tests/documents/sitemap_images.xml",
+                               "tests/documents/sitemap_videos.xml",
+                               "tests/documents/sitemap_news.xml",
+                               "tests/documents/sitemap_alternates.xml",
+                               "tests/documents/sitemap_pretty.xml",
+                               "tests/documents/sitemap_bom.xml",
+                               "tests/documents/sitemap_prefixed.xml",
+                               "tests/documents/sitemap_index_pretty.xml"];
+
+/// Entries and errors of `data` fed in chunks of `size` bytes.
+fn pushed(data: &[u8], size: usize, options: ReaderOptions) -> Vec<String> {
+    let mut parser = PushSiteMapParser::with_options(options);
+    let mut events = Vec::new();
+    for chunk in data.chunks(size) {
+        let mut result = parser.feed(chunk);
+        while let Err(error) = result {
+            events.push(format!("{:?}", error));
+            result = parser.feed(b"");
+        }
+        events.extend(result.unwrap().iter().map(summary));
+    }
+    loop {
+        match parser.feed(b"") {
+            Ok(ref rest) if rest.is_empty() => break,
+            Ok(rest) => events.extend(rest.iter().map(summary)),
+            Err(error) => events.push(format!("{:?}", error)),
+        }
+    }
+    match parser.finish() {
+        Ok(rest) => events.extend(rest.iter().map(summary)),
+        Err(error) => events.push(format!("{:?}", error)),
+    }
+    events
+}
+
+/// Entry with its position.
+fn summary(event: &SiteMapEvent) -> String {
+    match event {
+        SiteMapEvent::Url(url) => format!("{:?} at {:?}", url, url.position()),
+        SiteMapEvent::SiteMap(sitemap) => format!("{:?} at {:?}", sitemap, sitemap.position()),
+        event => format!("{:?}", event),
+    }
+}
+
+fn read(data: &[u8], options: ReaderOptions) -> Vec<String> {
+    SiteMapReader::with_options(data, options)
+        .map(|event| match event {
+            Ok(event) => summary(&event),
+            Err(error) => format!("{:?}", error),
+        })
+        .collect()
+}
+
+#[test]
+fn test_push_parser_matches_reader() {
+    let options = ReaderOptions {
+        root_events: true,
+        ..ReaderOptions::default()
+    };
+    for document in DOCUMENTS {
+        let data = fs::read(document).unwrap();
+        let expected = read(&data, options);
+        assert!(expected.len() > 1, "{}", document);
+        for &size in [1, 2, 3, 5, 7, 64, data.len()].iter() {
+            assert_eq!(pushed(&data, size, options), expected, "{} in chunks of {}", document, size);
+        }
+    }
+}
+
+#[test]
+fn test_push_parser_split_markup() {
+    let document = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                    <!DOCTYPE urlset [ <!ELEMENT urlset ANY> <!ATTLIST urlset a CDATA \"x\"> ]>\n\
+                    <?xml-stylesheet type=\"text/xsl\" href=\"a.xsl?x>y\"?>\n\
+                    <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" \
+                    xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\">\n\
+                    <!-- <url> -- > --><url><loc>http://www.example.com/\u{e9}t\u{e9}/\u{65e5}\u{672c}?a=1&amp;b=&#x33;</loc>\
+                    <image:image><image:loc><![CDATA[http://www.example.com/a.png?x=<1>&y=]]]></image:loc>\
+                    <image:caption a='>' b=\"'\">\u{1f600} caption</image:caption></image:image>\
+                    <lastmod>2024-01-01</lastmod><priority>0.5</priority></url>\n\
+                    <url><loc>http://www.example.com/b</loc></url>\n</urlset>\n";
+    let data = document.as_bytes();
+    let options = ReaderOptions {
+        allow_doctype: true,
+        ..ReaderOptions::default()
+    };
+    let expected = read(data, options);
+    assert_eq!(expected.len(), 2);
+    assert!(expected[0].contains("/%C3%A9t%C3%A9/%E6%97%A5%E6%9C%AC\", query: Some(\"a=1&b=3\")"), "{}", expected[0]);
+    assert!(expected[0].contains("/a.png\", query: Some(\"x=%3C1%3E&y=]\")"), "{}", expected[0]);
+    assert!(expected[0].contains("\u{1f600} caption"), "{}", expected[0]);
+    for size in 1..=16 {
+        assert_eq!(pushed(data, size, options), expected, "chunks of {}", size);
+    }
+    // every split, inside the 4 bytes characters too
+    for split in 0..data.len() {
+        let mut parser = PushSiteMapParser::with_options(options);
+        let mut events = parser.feed(&data[..split]).unwrap();
+        events.extend(parser.feed(&data[split..]).unwrap());
+        assert_eq!(parser.document_kind(), Some(DocumentKind::UrlSet));
+        assert_eq!(parser.stylesheets().len(), 1);
+        events.extend(parser.finish().unwrap());
+        assert_eq!(events.iter().map(summary).collect::<Vec<_>>(), expected, "split at {}", split);
+    }
+}
+
+#[test]
+fn test_push_parser_entries_by_chunk() {
+    let mut parser = PushSiteMapParser::new();
+    assert!(parser.feed(b"<urlset><url><loc>http://www.example.com/a</loc></u").unwrap().is_empty());
+    let events = parser.feed(b"rl><url><loc>http://www.example.com/b</loc></url><url>").unwrap();
+    assert_eq!(events.len(), 2);
+    assert!(parser.feed(b"").unwrap().is_empty());
+    let events = parser.feed(b"<loc>http://www.example.com/c</loc></url></urlset>").unwrap();
+    match events[..] {
+        [SiteMapEvent::Url(ref url)] => assert_eq!(url.loc.as_str(), Some("http://www.example.com/c")),
+        ref other => panic!("unexpected events {:?}", other),
+    }
+    assert!(parser.finish().unwrap().is_empty());
+}
+
+#[test]
+fn test_push_parser_errors() {
+    // an error read after some entries is returned by the next call
+    let mut parser = PushSiteMapParser::new();
+    let chunk = b"<urlset>\n<url><loc>http://www.example.com/</loc></url>\n<url></urlset>";
+    assert_eq!(parser.feed(chunk).unwrap().len(), 1);
+    match parser.feed(b"<url>") {
+        Err(ref error @ Error::Parse { .. }) => assert_eq!(error.position().map(|position| position.line), Some(3)),
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert!(parser.feed(b"<url>").unwrap().is_empty());
+    assert!(parser.finish().unwrap().is_empty());
+
+    let mut parser = PushSiteMapParser::new();
+    assert!(parser.feed(b"<urlset><url><loc>http://www.example.com/</loc></url><url><loc>http").unwrap().len() == 1);
+    match parser.finish() {
+        Err(Error::Parse { line: 1, message, .. }) => assert!(message.contains("end of stream"), "{}", message),
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert!(matches!(PushSiteMapParser::new().finish(), Err(Error::Parse { .. })));
+
+    let options = ReaderOptions {
+        max_bytes: Some(80),
+        ..ReaderOptions::default()
+    };
+    let mut parser = PushSiteMapParser::with_options(options);
+    let url = b"<url><loc>http://www.example.com/</loc></url>";
+    assert!(parser.feed(b"<urlset>").unwrap().is_empty());
+    assert_eq!(parser.feed(url).unwrap().len(), 1);
+    assert!(matches!(parser.feed(url), Err(Error::ResourceLimit { limit: 80, .. })));
+    assert!(parser.feed(url).unwrap().is_empty());
+
+    let options = ReaderOptions {
+        truncate: true,
+        ..options
+    };
+    let mut parser = PushSiteMapParser::with_options(options);
+    assert!(parser.feed(b"<urlset>").unwrap().is_empty());
+    let events = [parser.feed(url).unwrap(), parser.feed(url).unwrap()].concat();
+    assert!(matches!(events[..],
+                     [SiteMapEvent::Url(_), SiteMapEvent::Truncated { reason: TruncateReason::Bytes(80) }]));
+    assert!(parser.finish().unwrap().is_empty());
+}
@@ -0,0 +1,135 @@
+extern crate sitemap;
+
+use sitemap::Error;
+use sitemap::reader::{ReaderOptions, SiteMapEvent, SiteMapReader};
+use sitemap::structs::{Priority, RawElement, UrlEntry};
+use sitemap::writer::SiteMapWriter;
+
+const GEO_NAMESPACE: &str = "http://www.google.com/geo/schemas/sitemap/1.0";
+
+static DOCUMENT: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                         <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" \
+                         xmlns:geo=\"http://www.google.com/geo/schemas/sitemap/1.0\" \
+                         xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\">\n\
+                         <url><loc>http://www.example.com/</loc>\
+                         <geo:geo mode=\"a&amp;b\">\n  <geo:format>kml</geo:format>\n</geo:geo>\
+                         <image:image><image:loc>http://www.example.com/a.png</image:loc></image:image>\
+                         </url>\n\
+                         <url><loc>http://www.example.com/b</loc>\
+                         <ext:block xmlns:ext=\"urn:ext\" xmlns:o=\"urn:o\" o:id=\"1\">\
+                         <o:item>a &lt; b</o:item><extra/></ext:block><priority>0.5</priority>\
+                         <empty/></url>\n\
+                         </urlset>";
+
+fn urls(document: &str, options: ReaderOptions) -> Vec<UrlEntry> {
+    SiteMapReader::with_options(document.as_bytes(), options)
+        .filter_map(|event| match event {
+            Ok(SiteMapEvent::Url(url)) => Some(url),
+            Ok(_) => None,
+            Err(error) => panic!("unexpected error {:?}", error),
+        })
+        .collect()
+}
+
+fn capture() -> ReaderOptions {
+    ReaderOptions {
+        capture_unknown: true,
+        ..ReaderOptions::default()
+    }
+}
+
+fn geo() -> RawElement {
+    RawElement {
+        name: "geo:geo".to_string(),
+        attributes: vec![("mode".to_string(), "a&b".to_string())],
+        inner: "<geo:format>kml</geo:format>".to_string(),
+        namespaces: vec![("geo".to_string(), GEO_NAMESPACE.to_string())],
+    }
+}
+
+#[test]
+fn test_read_extensions() {
+    let urls = urls(DOCUMENT, capture());
+    assert_eq!(urls.len(), 2);
+    assert_eq!(urls[0].extensions, vec![geo()]);
+    assert_eq!(urls[0].images.len(), 1);
+    assert_eq!(urls[1].extensions,
+               vec![RawElement {
+                        name: "ext:block".to_string(),
+                        attributes: vec![("o:id".to_string(), "1".to_string())],
+                        inner: "<o:item>a &lt; b</o:item><extra></extra>".to_string(),
+                        namespaces: vec![("ext".to_string(), "urn:ext".to_string()),
+                                         ("o".to_string(), "urn:o".to_string()),
+                                         ("".to_string(), "http://www.sitemaps.org/schemas/sitemap/0.9".to_string())],
+                    },
+                    RawElement {
+                        name: "empty".to_string(),
+                        namespaces: vec![("".to_string(), "http://www.sitemaps.org/schemas/sitemap/0.9".to_string())],
+                        ..RawElement::default()
+                    }]);
+    assert_eq!(urls[1].priority, Priority::Value(0.5));
+
+    let urls = self::urls(DOCUMENT, ReaderOptions::default());
+    assert!(urls.iter().all(|url| url.extensions.is_empty()));
+    assert_eq!(urls[0].images.len(), 1);
+}
+
+#[test]
+fn test_write_extensions() {
+    let urls = urls(DOCUMENT, capture());
+    let mut writer = SiteMapWriter::new(Vec::new()).start_urlset().unwrap();
+    for url in urls.clone() {
+        writer.url(url).unwrap();
+    }
+    let written = String::from_utf8(writer.end().unwrap()).unwrap();
+    assert!(written.contains("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" xmlns:geo=\"http://www.google.com/geo/schemas/sitemap/1.0\" \
+                              xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\">"),
+            "{}",
+            written);
+    assert!(written.contains("<geo:geo mode=\"a&amp;b\"><geo:format>kml</geo:format></geo:geo>"),
+            "{}",
+            written);
+    assert!(written.contains("<ext:block xmlns:ext=\"urn:ext\" xmlns:o=\"urn:o\" o:id=\"1\">\
+                              <o:item>a &lt; b</o:item><extra></extra></ext:block>"),
+            "{}",
+            written);
+    assert!(written.contains("<empty />"), "{}", written);
+    let read = self::urls(&written, capture());
+    assert_eq!(read.len(), 2);
+    for (read, url) in read.iter().zip(urls.iter()) {
+        assert_eq!(read.extensions.len(), url.extensions.len());
+        for (read, url) in read.extensions.iter().zip(url.extensions.iter()) {
+            assert_eq!((&read.name, &read.attributes, &read.inner), (&url.name, &url.attributes, &url.inner));
+        }
+    }
+
+    let url = UrlEntry::builder().loc("http://www.example.com/").extension(geo()).build().unwrap();
+    let mut writer = SiteMapWriter::new(Vec::new()).start_urlset().unwrap();
+    writer.url(UrlEntry::builder().loc("http://www.example.com/first").build().unwrap()).unwrap();
+    writer.url(url).unwrap();
+    let written = String::from_utf8(writer.end().unwrap()).unwrap();
+    assert!(written.contains("<geo:geo xmlns:geo=\"http://www.google.com/geo/schemas/sitemap/1.0\" mode="),
+            "{}",
+            written);
+}
+
+#[test]
+fn test_extensions_limit() {
+    let document = format!("<urlset><url><loc>http://www.example.com/</loc>\
+                            <geo:geo xmlns:geo=\"urn:geo\"><geo:a>{}</geo:a></geo:geo></url></urlset>",
+                           "a".repeat(100));
+    let options = ReaderOptions {
+        max_extension_text_length: 100,
+        ..capture()
+    };
+    let events: Vec<_> = SiteMapReader::with_options(document.as_bytes(), options).collect();
+    match events.last() {
+        Some(Err(Error::ResourceLimit { limit: 100, .. })) => {}
+        event => panic!("unexpected event {:?}", event),
+    }
+    let options = ReaderOptions {
+        max_extension_text_length: 120,
+        ..capture()
+    };
+    assert_eq!(urls(&document, options)[0].extensions[0].inner, format!("<geo:a>{}</geo:a>", "a".repeat(100)));
+}
@@ -1,14 +1,13 @@
 extern crate sitemap;
 extern crate url;
 extern crate chrono;
-extern crate xml;
-use sitemap::reader::{SiteMapReader, SiteMapEntity};
+use sitemap::Error;
+use sitemap::reader::{SiteMapReader, SiteMapEvent, SiteMapEntity};
 use url::Url;
 use std::fs::File;
 use chrono::DateTime;
 use sitemap::structs::{ChangeFreq, SiteMapEntry, UrlEntry};
-use xml::reader;
-fn read_sitemap(file_name: &str) -> (Vec<UrlEntry>, Vec<SiteMapEntry>, Vec<reader::Error>) {
+fn read_sitemap(file_name: &str) -> (Vec<UrlEntry>, Vec<SiteMapEntry>, Vec<Error>) {
     let mut urls = Vec::new();
     let mut sitemaps = Vec::new();
     let mut errors = Vec::new();
@@ -16,18 +15,19 @@ fn read_sitemap(file_name: &str) -> (Vec<UrlEntry>, Vec<SiteMapEntry>, Vec<reade
     let parser = SiteMapReader::new(file);
     for entity in parser {
         match entity {
-            SiteMapEntity::Url(url_entry) => {
+            Ok(SiteMapEvent::Url(url_entry)) => {
                 urls.push(url_entry);
             }
-            SiteMapEntity::SiteMap(sitemap_entry) => {
+            Ok(SiteMapEvent::SiteMap(sitemap_entry)) => {
                 sitemaps.push(sitemap_entry);
             }
-            SiteMapEntity::Err(error) => {
+            Ok(_) => {}
+            Err(error) => {
                 errors.push(error);
             }
         }
     }
-    return (urls, sitemaps, errors);
+    (urls, sitemaps, errors)
 }
 #[test]
 fn test_read_sitemap_check_urls() {
@@ -88,3 +88,165 @@ fn test_read_sitemap_check_sitemaps() {
     assert_eq!(sitemaps[1].lastmod.get_time().unwrap(),
                DateTime::parse_from_rfc3339("2005-01-01T00:00:00+00:00").unwrap());
 }
+
+#[test]
+fn test_read_sitemap_iterator_adapters() {
+    let file = File::open("tests/documents/sitemap1.xml").unwrap();
+    let events = SiteMapReader::new(file).collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(events.len(), 7);
+    let file = File::open("tests/documents/sitemap1.xml").unwrap();
+    let locs: Vec<String> = SiteMapReader::new(file)
+        .filter_map(|event| match event {
+            Ok(SiteMapEvent::Url(url_entry)) => url_entry.loc.get_url(),
+            _ => None,
+        })
+        .take(2)
+        .map(|loc| loc.to_string())
+        .collect();
+    assert_eq!(locs,
+               vec!["http://www.example.com/",
+                    "http://www.example.com/catalog?item=12&desc=vacation_hawaii"]);
+}
+
+#[test]
+fn test_read_sitemap_xml_error_ends_iteration() {
+    let data: &[u8] = b"<urlset><url><loc>http://www.example.com/</loc></url><url></urlset>";
+    let mut reader = SiteMapReader::new(data);
+    assert!(matches!(reader.next(), Some(Ok(SiteMapEvent::Url(_)))));
+    assert!(matches!(reader.next(), Some(Err(Error::XmlReadError(_)))));
+    assert!(reader.next().is_none());
+    assert!(reader.next().is_none());
+    let data: &[u8] = b"<urlset><url><loc>http://www.example.com/</loc></url></urlset>";
+    let result = SiteMapReader::new(data).collect::<Result<Vec<_>, _>>();
+    assert_eq!(result.unwrap().len(), 1);
+}
+
+#[test]
+fn test_read_sitemap_entities() {
+    let file = File::open("tests/documents/sitemap1.xml").unwrap();
+    let mut urls = 0;
+    let mut sitemaps = 0;
+    for entity in SiteMapReader::new(file).entities() {
+        match entity {
+            SiteMapEntity::Url(_) => urls += 1,
+            SiteMapEntity::SiteMap(_) => sitemaps += 1,
+            SiteMapEntity::Err(error) => panic!("unexpected error {:?}", error),
+        }
+    }
+    assert_eq!((urls, sitemaps), (5, 2));
+}
+
+#[test]
+fn test_read_sitemap_url_iter() {
+    let file = File::open("tests/documents/sitemap_videos.xml").unwrap();
+    let expected = read_sitemap("tests/documents/sitemap_videos.xml").0;
+    let urls = SiteMapReader::new(file).into_url_iter().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(urls, expected);
+
+    // the sitemapindex after the urlset ends the urls
+    let file = File::open("tests/documents/sitemap1.xml").unwrap();
+    let results: Vec<_> = SiteMapReader::new(file).into_url_iter().collect();
+    assert_eq!(results.len(), 6);
+    assert!(results[..5].iter().all(|result| result.is_ok()));
+    assert!(matches!(results[5], Err(Error::Invalid(_))));
+
+    let data: &[u8] = b"<sitemapindex></sitemapindex>";
+    let mut urls = SiteMapReader::new(data).into_url_iter();
+    match urls.next() {
+        Some(Err(Error::Invalid(message))) => {
+            assert_eq!(message, "expected urlset, found sitemapindex")
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert!(urls.next().is_none());
+    let data: &[u8] = b"<urlset></urlset>";
+    assert!(SiteMapReader::new(data).into_url_iter().next().is_none());
+}
+
+#[test]
+fn test_read_sitemap_strict() {
+    let data: &[u8] = b"<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n\
+                        <url><loc>http://www.example.com/</loc></url>\n\
+                        <url><loc>http://www.example.com/other.html</loc><priority>1.5</priority></url>\n\
+                        <url><loc>http://www.example.com/last.html</loc></url>\n</urlset>";
+    let results: Vec<_> = SiteMapReader::new(data).strict(true).collect();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    match results[1] {
+        Err(Error::Parse { line: 3, ref message, .. }) => {
+            assert_eq!(message, "Invalid <priority> value: priority should be between 0 and 1")
+        }
+        ref other => panic!("unexpected result {:?}", other),
+    }
+    // lenient by default
+    assert!(SiteMapReader::new(data).all(|result| result.is_ok()));
+}
+
+#[test]
+fn test_read_sitemap_pretty_printed() {
+    let (urls, sitemaps, errors) = read_sitemap("tests/documents/sitemap_pretty.xml");
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert!(sitemaps.is_empty());
+    assert_eq!(urls.len(), 3);
+    assert_eq!(urls[0].loc.get_url(), Some(Url::parse("https://www.example.com/").unwrap()));
+    assert_eq!(urls[0].lastmod.get_time(),
+               Some(DateTime::parse_from_rfc3339("2024-03-01T10:20:30+00:00").unwrap()));
+    assert_eq!(urls[0].changefreq, ChangeFreq::Weekly);
+    assert_eq!(urls[0].priority.get_priority(), Some(1.0));
+    assert_eq!(urls[1].loc.get_url().unwrap().as_str(),
+               "https://www.example.com/search?q=rust&page=2");
+    assert!(urls[1].lastmod.get_time().is_some());
+    assert_eq!(urls[1].images[0].loc.get_url().unwrap().as_str(),
+               "https://www.example.com/images/logo.png");
+    assert_eq!(urls[2].changefreq, ChangeFreq::Monthly);
+    assert_eq!(urls[2].priority.get_priority(), Some(0.8));
+    assert!(urls.iter().all(|url_entry| url_entry.errors().is_empty()));
+
+    let (urls, sitemaps, errors) = read_sitemap("tests/documents/sitemap_index_pretty.xml");
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert!(urls.is_empty());
+    assert_eq!(sitemaps.len(), 2);
+    assert_eq!(sitemaps[1].loc.get_url().unwrap().as_str(),
+               "https://www.example.com/page-sitemap.xml");
+    assert_eq!(sitemaps[1].lastmod.get_time(),
+               Some(DateTime::parse_from_rfc3339("2024-02-15T08:00:00+00:00").unwrap()));
+}
+
+#[test]
+fn test_read_sitemap_cdata() {
+    let data: &[u8] = b"<urlset>\
+        <url><loc><![CDATA[http://www.example.com/?a=1&b=2]]></loc>\
+        <lastmod><![CDATA[2024-03-01]]></lastmod>\
+        <changefreq><![CDATA[ daily ]]></changefreq>\
+        <priority><![CDATA[0.5]]></priority></url>\
+        <url><loc>http://www.example.com/<![CDATA[search?q=a&b]]>&amp;page=2</loc></url>\
+        <url><loc>\n  <![CDATA[http://www.example.com/<about>]]>\n</loc></url>\
+        </urlset>";
+    let urls: Vec<UrlEntry> = SiteMapReader::new(data).into_url_iter().map(Result::unwrap).collect();
+    assert_eq!(urls.len(), 3);
+    assert_eq!(urls[0].loc.get_url().unwrap().as_str(), "http://www.example.com/?a=1&b=2");
+    assert!(urls[0].lastmod.get_time().is_some());
+    assert_eq!(urls[0].changefreq, ChangeFreq::Daily);
+    assert_eq!(urls[0].priority.get_priority(), Some(0.5));
+    assert_eq!(urls[1].loc.get_url().unwrap().as_str(),
+               "http://www.example.com/search?q=a&b&page=2");
+    assert_eq!(urls[2].loc.get_url().unwrap().as_str(), "http://www.example.com/%3Cabout%3E");
+}
+
+#[test]
+fn test_read_sitemap_utf16_and_latin1() {
+    for file_name in &["tests/documents/sitemap_utf16le.xml", "tests/documents/sitemap_utf16be.xml"] {
+        let (urls, _, errors) = read_sitemap(file_name);
+        assert!(errors.is_empty(), "{}: {:?}", file_name, errors);
+        let locs: Vec<String> = urls.iter().map(|url| url.loc.get_url().unwrap().to_string()).collect();
+        assert_eq!(locs,
+                   vec!["https://www.example.com/caf%C3%A9/cr%C3%A8me-br%C3%BBl%C3%A9e",
+                        "https://xn--e1afmkfd.xn--p1ai/%D1%81%D1%82%D1%80%D0%B0%D0%BD%D0%B8%D1%86%D0%B0?q=%C3%BCber",
+                        "https://www.example.com/%E6%97%A5%E6%9C%AC%E8%AA%9E/%E3%83%9A%E3%83%BC%E3%82%B8"],
+                   "{}",
+                   file_name);
+    }
+    let (urls, _, errors) = read_sitemap("tests/documents/sitemap_latin1.xml");
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(urls[1].loc.get_url().unwrap().as_str(), "https://www.example.com/gr%C3%BC%C3%9Fe");
+}
@@ -0,0 +1,473 @@
+extern crate sitemap;
+
+use sitemap::{Error, LimitKind};
+use sitemap::reader::{DocumentKind, DuplicateChildren, ReaderOptions, SiteMapEvent, SiteMapReader};
+use sitemap::structs::{CanonicalizeOptions, ChangeFreq, DateFormat, LastMod, Location, Position, Priority};
+use sitemap::writer::SiteMapWriter;
+use std::fs::File;
+
+static JUNK: &[u8] = b"<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">
+<url><loc>http://www.example.com/</loc><priority>1.5</priority></url>
+<url><loc>http://www.example.com/a.html</loc><changefreq>2 days</changefreq>\
+<lastmod>2023/05/01</lastmod></url>
+<url><loc>http://www.example.com/b.html</loc></url>
+</urlset>";
+
+fn urls(events: &[Result<SiteMapEvent, Error>]) -> usize {
+    events.iter().filter(|event| matches!(event, Ok(SiteMapEvent::Url(_)))).count()
+}
+
+#[test]
+fn test_options_default() {
+    assert_eq!(ReaderOptions::default(),
+               ReaderOptions {
+                   strict: false,
+                   max_urls: None,
+                   max_entries: None,
+                   truncate: false,
+                   require_namespace: false,
+                   trim_whitespace: false,
+                   skip_leading_garbage: false,
+                   case_insensitive_tags: false,
+                   root_events: false,
+                   max_depth: 32,
+                   max_text_length: 4096,
+                   max_extension_text_length: 65536,
+                   allow_doctype: false,
+                   max_bytes: None,
+                   parse_urls: true,
+                   capture_unknown: false,
+                   emit_unknown: false,
+                   progress_entries: 1000,
+                   progress_bytes: 1 << 20,
+                   duplicate_children: DuplicateChildren::LastWins,
+                   emit_comments: false,
+               });
+}
+
+#[test]
+fn test_options_lenient_warnings() {
+    let mut reader = SiteMapReader::with_options(JUNK, ReaderOptions::default());
+    let events: Vec<_> = reader.by_ref().collect();
+    assert_eq!(urls(&events), 3);
+    match events[0] {
+        Ok(SiteMapEvent::Url(ref url_entry)) => {
+            assert_eq!(url_entry.priority, Priority::ErrValueGreaterOne(1.5))
+        }
+        ref other => panic!("unexpected event {:?}", other),
+    }
+    match events[1] {
+        Ok(SiteMapEvent::Url(ref url_entry)) => {
+            assert!(matches!(url_entry.changefreq, ChangeFreq::Err(_)));
+            assert!(matches!(url_entry.lastmod, LastMod::Err { .. }));
+        }
+        ref other => panic!("unexpected event {:?}", other),
+    }
+    let warnings: Vec<String> = reader.warnings().iter().map(|warning| warning.to_string()).collect();
+    assert_eq!(warnings.len(), 3);
+    assert!(warnings[0].starts_with("2:"));
+    assert!(warnings[0].contains("Invalid <priority> value"));
+    assert!(warnings[1].contains("Invalid <lastmod> value"));
+    assert!(warnings[2].contains("Invalid <changefreq> value"));
+}
+
+#[test]
+fn test_options_strict() {
+    let options = ReaderOptions { strict: true, ..ReaderOptions::default() };
+    let mut reader = SiteMapReader::with_options(JUNK, options);
+    match reader.next() {
+        Some(Err(Error::Parse { line: 2, ref message, .. })) => {
+            assert!(message.starts_with("Invalid <priority> value"))
+        }
+        other => panic!("unexpected event {:?}", other),
+    }
+    assert!(reader.next().is_none());
+    assert!(reader.warnings().is_empty());
+}
+
+#[test]
+fn test_options_strict_changefreq_case() {
+    let data: &[u8] = b"<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n\
+                        <url><loc>http://www.example.com/</loc><changefreq> Daily </changefreq></url>\n\
+                        <url><loc>http://www.example.com/a</loc><changefreq>daily</changefreq></url>\n</urlset>";
+    let mut reader = SiteMapReader::new(data).strict(true);
+    let changefreqs: Vec<_> = reader.by_ref()
+        .map(|event| match event {
+            Ok(SiteMapEvent::Url(url)) => url.changefreq,
+            other => panic!("unexpected event {:?}", other),
+        })
+        .collect();
+    assert_eq!(changefreqs, [ChangeFreq::Daily, ChangeFreq::Daily]);
+    let warnings: Vec<String> = reader.warnings().iter().map(|warning| warning.to_string()).collect();
+    assert_eq!(warnings, ["2:40 Invalid <changefreq> value: 'Daily' is not in lowercase"]);
+    let mut reader = SiteMapReader::new(data);
+    assert_eq!(reader.by_ref().count(), 2);
+    assert!(reader.warnings().is_empty());
+}
+
+#[test]
+fn test_options_non_standard_lastmod() {
+    let data: &[u8] = b"<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n\
+                        <url><loc>http://www.example.com/</loc>\
+                        <lastmod>Thu, 15 Jun 2023 10:00:00 GMT</lastmod></url>\n</urlset>";
+    let mut reader = SiteMapReader::new(data);
+    match reader.next() {
+        Some(Ok(SiteMapEvent::Url(ref url_entry))) => {
+            assert!(url_entry.lastmod.get_time().is_some());
+            assert_eq!(url_entry.lastmod.format(), Some(DateFormat::Rfc2822));
+        }
+        other => panic!("unexpected event {:?}", other),
+    }
+    assert_eq!(reader.warnings().len(), 1);
+    assert_eq!(reader.warnings()[0].to_string(),
+               "2:40 Invalid <lastmod> value: RFC 2822 time instead of W3C datetime");
+    match SiteMapReader::new(data).strict(true).next() {
+        Some(Err(Error::Parse { line: 2, .. })) => {}
+        other => panic!("unexpected event {:?}", other),
+    }
+}
+
+#[test]
+fn test_options_max_urls() {
+    let options = ReaderOptions { max_urls: Some(2), ..ReaderOptions::default() };
+    let events: Vec<_> = SiteMapReader::with_options(JUNK, options).collect();
+    assert_eq!(events.len(), 3);
+    assert_eq!(urls(&events), 2);
+    assert!(matches!(events[2], Err(Error::LimitExceeded { kind: LimitKind::UrlCount, limit: 2 })));
+    let options = ReaderOptions { max_urls: Some(3), ..ReaderOptions::default() };
+    assert_eq!(urls(&SiteMapReader::with_options(JUNK, options).collect::<Vec<_>>()), 3);
+}
+
+#[test]
+fn test_options_namespace() {
+    let options = ReaderOptions { require_namespace: true, ..ReaderOptions::default() };
+    assert_eq!(urls(&SiteMapReader::with_options(JUNK, options).collect::<Vec<_>>()), 3);
+    let data: &[u8] = b"<urlset><url><loc>http://www.example.com/</loc></url></urlset>";
+    let events: Vec<_> = SiteMapReader::with_options(data, options).collect();
+    assert_eq!(events.len(), 1);
+    match events[0] {
+        Err(Error::Parse { line: 1, ref message, .. }) => {
+            assert_eq!(message, "urlset element without the sitemap namespace")
+        }
+        ref other => panic!("unexpected event {:?}", other),
+    }
+    assert_eq!(urls(&SiteMapReader::new(data).collect::<Vec<_>>()), 1);
+    // strict mode requires the namespace
+    let events: Vec<_> = SiteMapReader::new(data).strict(true).collect();
+    assert!(matches!(events[..], [Err(Error::Parse { .. })]));
+
+    let data: &[u8] = b"<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9/\"></urlset>";
+    match SiteMapReader::with_options(data, options).next() {
+        Some(Err(Error::Parse { ref message, .. })) => {
+            assert_eq!(message,
+                       "urlset element in the unknown namespace http://www.sitemaps.org/schemas/sitemap/0.9/")
+        }
+        other => panic!("unexpected event {:?}", other),
+    }
+}
+
+#[test]
+fn test_options_namespace_variants() {
+    let required = ReaderOptions { require_namespace: true, ..ReaderOptions::default() };
+    let variants = [("tests/documents/sitemap_no_namespace.xml", false, vec![]),
+                    ("tests/documents/sitemap_namespace_084.xml",
+                     true,
+                     vec![("", "http://www.google.com/schemas/sitemap/0.84")]),
+                    ("tests/documents/sitemap_prefixed.xml",
+                     true,
+                     vec![("image", "http://www.google.com/schemas/sitemap-image/1.1"),
+                          ("sm", "http://www.sitemaps.org/schemas/sitemap/0.9")])];
+    for (file_name, valid, namespaces) in variants.iter() {
+        let mut reader = SiteMapReader::new(File::open(file_name).unwrap());
+        let entries: Vec<_> = reader.by_ref().map(Result::unwrap).collect();
+        assert_eq!(entries.len(), 2, "{}", file_name);
+        match entries[0] {
+            SiteMapEvent::Url(ref url_entry) => {
+                assert_eq!(url_entry.loc.get_url().unwrap().as_str(), "http://www.example.com/");
+                assert!(url_entry.lastmod.get_time().is_some());
+            }
+            ref other => panic!("unexpected event {:?}", other),
+        }
+        let expected: Vec<(String, String)> = namespaces.iter()
+            .map(|&(prefix, namespace)| (prefix.to_string(), namespace.to_string()))
+            .collect();
+        assert_eq!(reader.root_namespaces(), &expected[..], "{}", file_name);
+
+        let events: Vec<_> = SiteMapReader::with_options(File::open(file_name).unwrap(), required).collect();
+        assert_eq!(urls(&events), if *valid { 2 } else { 0 }, "{}", file_name);
+    }
+    let reader = SiteMapReader::new(File::open("tests/documents/sitemap_prefixed.xml").unwrap());
+    match reader.into_url_iter().next() {
+        Some(Ok(url_entry)) => assert_eq!(url_entry.images.len(), 1),
+        other => panic!("unexpected entry {:?}", other),
+    }
+}
+
+#[test]
+fn test_options_trim_whitespace() {
+    let data: &[u8] = b"<urlset><url><loc>\n  http://www.example.com/\n  </loc>\
+                        <priority> 0.5 </priority><changefreq>\tdaily\n</changefreq></url></urlset>";
+    let options = ReaderOptions { trim_whitespace: true, ..ReaderOptions::default() };
+    let mut reader = SiteMapReader::with_options(data, options);
+    match reader.next() {
+        Some(Ok(SiteMapEvent::Url(url_entry))) => {
+            assert_eq!(url_entry.loc.get_url().unwrap().as_str(), "http://www.example.com/");
+            assert_eq!(url_entry.priority, Priority::Value(0.5));
+            assert_eq!(url_entry.changefreq, ChangeFreq::Daily);
+        }
+        other => panic!("unexpected event {:?}", other),
+    }
+    assert!(reader.warnings().is_empty());
+
+    let data: &[u8] = b"<urlset xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\">\
+                        <url><loc>http://www.example.com/</loc><image:image>\
+                        <image:loc>http://www.example.com/image.png</image:loc>\
+                        <image:title>\n  Example\n</image:title></image:image></url></urlset>";
+    let options = ReaderOptions { trim_whitespace: true, ..ReaderOptions::default() };
+    match SiteMapReader::with_options(data, options).next() {
+        Some(Ok(SiteMapEvent::Url(url_entry))) => {
+            assert_eq!(url_entry.images[0].title, Some("Example".to_string()))
+        }
+        other => panic!("unexpected event {:?}", other),
+    }
+    match SiteMapReader::new(data).next() {
+        Some(Ok(SiteMapEvent::Url(url_entry))) => {
+            assert_eq!(url_entry.images[0].title, Some("\n  Example\n".to_string()))
+        }
+        other => panic!("unexpected event {:?}", other),
+    }
+}
+
+#[test]
+fn test_options_case_insensitive_tags() {
+    let data: &[u8] = b"<UrlSet xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\
+                        <URL><Loc>http://www.example.com/CamelCase.html?Q=A</Loc>\
+                        <LastMod>2024-03-01</LastMod><ChangeFreq>daily</ChangeFreq>\
+                        <PRIORITY>0.5</PRIORITY></URL></UrlSet>";
+    // exact matching by default
+    assert_eq!(urls(&SiteMapReader::new(data).collect::<Vec<_>>()), 0);
+    let options = ReaderOptions { case_insensitive_tags: true, ..ReaderOptions::default() };
+    let mut reader = SiteMapReader::with_options(data, options);
+    let events: Vec<_> = reader.by_ref().collect();
+    assert_eq!(events.len(), 1);
+    match events[0] {
+        Ok(SiteMapEvent::Url(ref url_entry)) => {
+            assert_eq!(url_entry.loc.get_url().unwrap().as_str(),
+                       "http://www.example.com/CamelCase.html?Q=A");
+            assert!(url_entry.lastmod.get_time().is_some());
+            assert_eq!(url_entry.changefreq, ChangeFreq::Daily);
+            assert_eq!(url_entry.priority, Priority::Value(0.5));
+        }
+        ref other => panic!("unexpected event {:?}", other),
+    }
+    assert_eq!(reader.document_kind(), Some(DocumentKind::UrlSet));
+
+    let data: &[u8] = b"<SITEMAPINDEX><Sitemap><LOC>http://www.example.com/Sitemap1.xml</LOC>\
+                        </Sitemap></SITEMAPINDEX>";
+    match SiteMapReader::with_options(data, options).next() {
+        Some(Ok(SiteMapEvent::SiteMap(ref sitemap_entry))) => {
+            assert_eq!(sitemap_entry.loc.get_url().unwrap().as_str(),
+                       "http://www.example.com/Sitemap1.xml")
+        }
+        other => panic!("unexpected event {:?}", other),
+    }
+
+    // exact matching in strict mode
+    let options = ReaderOptions { strict: true, ..options };
+    let data: &[u8] = b"<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\
+                        <URL><loc>http://www.example.com/</loc></URL>\
+                        <url><loc>http://www.example.com/other.html</loc></url></urlset>";
+    let events: Vec<_> = SiteMapReader::with_options(data, options).map(Result::unwrap).collect();
+    assert_eq!(events.len(), 1);
+    match events[0] {
+        SiteMapEvent::Url(ref url_entry) => {
+            assert_eq!(url_entry.loc.get_url().unwrap().as_str(), "http://www.example.com/other.html")
+        }
+        ref other => panic!("unexpected event {:?}", other),
+    }
+}
+
+#[test]
+fn test_options_root_events() {
+    let options = ReaderOptions { root_events: true, ..ReaderOptions::default() };
+    let file = File::open("tests/documents/sitemap_prefixed.xml").unwrap();
+    let events: Vec<_> = SiteMapReader::with_options(file, options).map(Result::unwrap).collect();
+    assert_eq!(events.len(), 3);
+    match events[0] {
+        SiteMapEvent::UrlSetStart { ref namespaces } => {
+            assert_eq!(namespaces,
+                       &[("image".to_string(), "http://www.google.com/schemas/sitemap-image/1.1".to_string()),
+                         ("sm".to_string(), "http://www.sitemaps.org/schemas/sitemap/0.9".to_string())])
+        }
+        ref other => panic!("unexpected event {:?}", other),
+    }
+    assert!(matches!(events[1], SiteMapEvent::Url(_)));
+
+    let file = File::open("tests/documents/sitemap_index_pretty.xml").unwrap();
+    let mut reader = SiteMapReader::with_options(file, options);
+    match reader.next() {
+        Some(Ok(SiteMapEvent::IndexStart { ref namespaces })) => {
+            assert_eq!(namespaces,
+                       &[(String::new(), "http://www.sitemaps.org/schemas/sitemap/0.9".to_string())])
+        }
+        other => panic!("unexpected event {:?}", other),
+    }
+    assert_eq!(reader.document_kind(), Some(DocumentKind::SiteMapIndex));
+    assert!(matches!(reader.next(), Some(Ok(SiteMapEvent::SiteMap(_)))));
+
+    // skipped by the url iterator, an index fails at its start
+    let file = File::open("tests/documents/sitemap_prefixed.xml").unwrap();
+    assert_eq!(SiteMapReader::with_options(file, options).into_url_iter().count(), 2);
+    let file = File::open("tests/documents/sitemap_index_pretty.xml").unwrap();
+    let urls: Vec<_> = SiteMapReader::with_options(file, options).into_url_iter().collect();
+    assert!(matches!(urls[..], [Err(Error::Invalid(_))]));
+
+    // no events by default or for other documents
+    let file = File::open("tests/documents/sitemap_prefixed.xml").unwrap();
+    assert_eq!(SiteMapReader::new(file).count(), 2);
+    let data: &[u8] = b"<rss><channel></channel></rss>";
+    let mut reader = SiteMapReader::with_options(data, options);
+    assert!(reader.next().is_none());
+    assert_eq!(reader.document_kind(), Some(DocumentKind::Unknown));
+}
+
+#[test]
+fn test_options_leading_bytes() {
+    for file_name in &["tests/documents/sitemap_bom.xml", "tests/documents/sitemap_leading_whitespace.xml"] {
+        let file = File::open(file_name).unwrap();
+        let entries: Vec<_> = SiteMapReader::new(file).into_url_iter().map(Result::unwrap).collect();
+        assert_eq!(entries.len(), 2, "{}", file_name);
+        assert_eq!(entries[1].loc.get_url().unwrap().as_str(), "http://www.example.com/about");
+    }
+    // positions count the skipped lines
+    let file = File::open("tests/documents/sitemap_leading_whitespace.xml").unwrap();
+    let entries: Vec<_> = SiteMapReader::new(file).into_url_iter().map(Result::unwrap).collect();
+    assert_eq!(entries[0].position(), Some(Position { line: 5, column: 3 }));
+
+    let data: &[u8] = b"\xef\xbb\xbf \r\n<?xml version=\"1.0\"?><urlset><url><loc>http://www.example.com/</loc></url></urlset>";
+    assert_eq!(urls(&SiteMapReader::new(data).collect::<Vec<_>>()), 1);
+
+    let data: &[u8] = b"Warning: cache miss\n<?xml version=\"1.0\"?>\
+                        <urlset><url><loc>http://www.example.com/</loc></url></urlset>";
+    match SiteMapReader::new(data).next() {
+        Some(Err(Error::NotXml { bytes })) => assert_eq!(bytes, b"Warning: cache miss\n<?xml versio"),
+        other => panic!("unexpected event {:?}", other),
+    }
+    let options = ReaderOptions { skip_leading_garbage: true, ..ReaderOptions::default() };
+    let events: Vec<_> = SiteMapReader::with_options(data, options).collect();
+    assert_eq!(urls(&events), 1);
+    assert_eq!(events.len(), 1);
+}
+
+#[test]
+fn test_options_not_xml() {
+    let options = ReaderOptions { skip_leading_garbage: true, ..ReaderOptions::default() };
+    for data in &[&b"{\"error\": \"not found\"}"[..], &b""[..], &b"  \n "[..]] {
+        let events: Vec<_> = SiteMapReader::new(*data).collect();
+        assert_eq!(events.len(), 1);
+        match events[0] {
+            Err(ref error @ Error::NotXml { .. }) => {
+                assert!(error.to_string().starts_with("Not an XML document, starting with"))
+            }
+            ref other => panic!("unexpected event {:?}", other),
+        }
+        let events: Vec<_> = SiteMapReader::with_options(*data, options).collect();
+        assert!(matches!(events[..], [Err(Error::NotXml { .. })]));
+    }
+    let error = SiteMapReader::new(&b"<html>no sitemap"[..]).last();
+    assert!(matches!(error, Some(Err(Error::XmlReadError(_)))));
+}
+
+#[test]
+fn test_options_raw_urls() {
+    let document = b"<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" \
+                     xmlns:xhtml=\"http://www.w3.org/1999/xhtml\">\
+                     <url><loc>HTTP://www.Example.com/?a=1&amp;b=2</loc>\
+                     <xhtml:link rel=\"alternate\" hreflang=\"de\" href=\"HTTP://www.Example.com/de\"/></url>\
+                     <url><loc>not a url</loc></url></urlset>";
+    let options = ReaderOptions { parse_urls: false, ..ReaderOptions::default() };
+    let mut reader = SiteMapReader::with_options(&document[..], options);
+    let urls: Vec<_> = reader.by_ref().filter_map(|event| match event {
+        Ok(SiteMapEvent::Url(url_entry)) => Some(url_entry),
+        _ => None,
+    }).collect();
+    assert!(reader.warnings().is_empty());
+    assert_eq!(urls[0].loc, Location::Raw("HTTP://www.Example.com/?a=1&b=2".to_string()));
+    assert_eq!(urls[0].loc.get_url().map(String::from),
+               Some("http://www.example.com/?a=1&b=2".to_string()));
+    assert!(urls[0].errors().is_empty());
+    assert_eq!(urls[0].alternates[0].href, Location::Raw("HTTP://www.Example.com/de".to_string()));
+    assert_eq!(urls[1].loc.raw(), Some("not a url".to_string()));
+    assert_eq!(urls[1].loc.get_url(), None);
+    assert!(matches!(*urls[1].loc.parsed(), Location::Err { .. }));
+    assert!(matches!(urls[1].loc.canonicalize(&CanonicalizeOptions::default()), Location::Err { .. }));
+    match urls[1].errors()[..] {
+        [Error::Parse { ref message, .. }] => assert!(message.starts_with("Invalid <loc> value")),
+        ref other => panic!("unexpected errors {:?}", other),
+    }
+    let mut writer = SiteMapWriter::new(Vec::new()).start_urlset().expect("start urlset");
+    writer.url(urls[0].clone()).expect("raw url");
+    let written = String::from_utf8(writer.end().expect("end")).unwrap();
+    assert!(written.contains("<loc>HTTP://www.Example.com/?a=1&amp;b=2</loc>"), "{}", written);
+    assert!(written.contains("href=\"HTTP://www.Example.com/de\""), "{}", written);
+    let strict = ReaderOptions { strict: true, ..options };
+    let events: Vec<_> = SiteMapReader::with_options(&document[..], strict).collect();
+    assert!(matches!(events[..], [Ok(SiteMapEvent::Url(_)), Err(Error::Parse { .. })]));
+}
+
+static DUPLICATES: &[u8] = b"<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">
+<url><loc>http://www.example.com/a</loc><lastmod>2024-01-01</lastmod>
+<lastmod>2024-02-01</lastmod><priority>0.5</priority></url>
+<url><loc>http://www.example.com/b</loc><loc>http://www.example.com/c</loc></url>
+</urlset>";
+
+/// Locations and modification times of the urls read with `policy`, and
+/// the errors and warnings.
+fn read_duplicates(policy: DuplicateChildren, strict: bool) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let options = ReaderOptions { duplicate_children: policy, strict, ..ReaderOptions::default() };
+    let mut reader = SiteMapReader::with_options(DUPLICATES, options);
+    let mut fields = Vec::new();
+    let mut errors = Vec::new();
+    for event in reader.by_ref() {
+        match event {
+            Ok(SiteMapEvent::Url(url)) => {
+                fields.push(format!("{} {:?}", url.loc.as_str().unwrap(), url.lastmod.get_time().map(|time| time.to_rfc3339())));
+                if let Some(position) = url.field_position("lastmod").or_else(|| url.field_position("loc")) {
+                    fields.push(format!("{}:{}", position.line, position.column));
+                }
+            }
+            Ok(_) => {}
+            Err(error) => errors.push(error.to_string()),
+        }
+    }
+    let warnings = reader.warnings().iter().map(|warning| warning.to_string()).collect();
+    (fields, errors, warnings)
+}
+
+#[test]
+fn test_options_duplicate_children() {
+    let last = vec!["http://www.example.com/a Some(\"2024-02-01T00:00:00+00:00\")".to_string(),
+                    "3:1".to_string(),
+                    "http://www.example.com/c None".to_string(),
+                    "4:41".to_string()];
+    let first = vec!["http://www.example.com/a Some(\"2024-01-01T00:00:00+00:00\")".to_string(),
+                     "2:41".to_string(),
+                     "http://www.example.com/b None".to_string(),
+                     "4:6".to_string()];
+    let duplicates = vec!["3:1 duplicate lastmod in url, first at 2:41".to_string(),
+                          "4:41 duplicate loc in url, first at 4:6".to_string()];
+    assert_eq!(read_duplicates(DuplicateChildren::LastWins, false), (last.clone(), vec![], vec![]));
+    assert_eq!(read_duplicates(DuplicateChildren::LastWins, true), (last.clone(), vec![], vec![]));
+    assert_eq!(read_duplicates(DuplicateChildren::FirstWins, false), (first.clone(), vec![], vec![]));
+    assert_eq!(read_duplicates(DuplicateChildren::Warn, false), (last, vec![], duplicates.clone()));
+    assert_eq!(read_duplicates(DuplicateChildren::Error, false), (first.clone(), duplicates.clone(), vec![]));
+    assert_eq!(read_duplicates(DuplicateChildren::Error, true), (vec![], duplicates[..1].to_vec(), vec![]));
+
+    let index = b"<sitemapindex><sitemap><loc>http://www.example.com/a.xml</loc>\
+                  <loc>http://www.example.com/b.xml</loc></sitemap></sitemapindex>";
+    let options = ReaderOptions { duplicate_children: DuplicateChildren::FirstWins, ..ReaderOptions::default() };
+    match SiteMapReader::with_options(&index[..], options).next() {
+        Some(Ok(SiteMapEvent::SiteMap(sitemap))) => assert_eq!(sitemap.loc.as_str(), Some("http://www.example.com/a.xml")),
+        other => panic!("unexpected event {:?}", other),
+    }
+}
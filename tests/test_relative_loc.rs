@@ -0,0 +1,102 @@
+extern crate sitemap;
+extern crate url;
+
+use sitemap::document::SiteMap;
+use sitemap::structs::{Location, UrlEntry};
+use sitemap::writer::{SiteMapWriter, WriterOptions};
+use sitemap::{Error, ResolveError};
+use url::Url;
+
+fn base() -> Url {
+    Url::parse("https://www.example.com/site/").unwrap()
+}
+
+#[test]
+fn test_resolve() {
+    let resolve = |reference| Location::resolve(&base(), reference).map(|url| url.to_string());
+    assert_eq!(resolve("/blog/post-1?page=2#comments"),
+               Ok("https://www.example.com/blog/post-1?page=2#comments".to_string()));
+    assert_eq!(resolve("blog/post-1"), Ok("https://www.example.com/site/blog/post-1".to_string()));
+    assert_eq!(resolve("../about"), Ok("https://www.example.com/about".to_string()));
+    assert_eq!(resolve(" ?q=a b "), Ok("https://www.example.com/site/?q=a%20b".to_string()));
+    assert_eq!(resolve("https://www.example.com/a"), Ok("https://www.example.com/a".to_string()));
+    for &(reference, url) in [("//cdn.example.com/a", "https://cdn.example.com/a"),
+                              ("http://www.example.com/a", "http://www.example.com/a"),
+                              ("https://www.example.com:8443/a", "https://www.example.com:8443/a")]
+        .iter() {
+        match Location::resolve(&base(), reference) {
+            Err(ResolveError::OtherHost(resolved)) => assert_eq!(resolved.as_str(), url),
+            result => panic!("unexpected result {:?} for {}", result, reference),
+        }
+    }
+    assert_eq!(Location::resolve(&base(), "http://[::1"),
+               Err(ResolveError::Parse(url::ParseError::InvalidIpv6Address)));
+    let mailto = Url::parse("mailto:someone@example.com").unwrap();
+    assert!(matches!(Location::resolve(&mailto, "/a"), Err(ResolveError::Parse(_))));
+}
+
+#[test]
+fn test_builder_loc_relative() {
+    let url = UrlEntry::builder_with_base(base()).loc_relative("/blog/post-1?a=1&b=2").build().unwrap();
+    assert_eq!(url.loc.as_str(), Some("https://www.example.com/blog/post-1?a=1&b=2"));
+    let error = UrlEntry::builder_with_base(base()).loc_relative("//cdn.example.com/a").build().unwrap_err();
+    assert_eq!(error.to_string(),
+               "Invalid entry: loc '//cdn.example.com/a': resolved to https://cdn.example.com/a \
+                on another host than the base url");
+    let error = UrlEntry::builder().loc_relative("/blog").build().unwrap_err();
+    assert_eq!(error.to_string(), "Invalid entry: loc '/blog': relative location without a base url");
+}
+
+#[test]
+fn test_writer_base_url() {
+    let mut urlset = SiteMapWriter::new(Vec::new()).start_urlset().unwrap().base_url(base());
+    let mut url = UrlEntry::new();
+    url.loc = Location::Raw("/blog/post-1".to_string());
+    urlset.url(url.clone()).unwrap();
+    url.loc = Location::from("post-2".to_string());
+    urlset.url(url.clone()).unwrap();
+    url.loc = Location::Raw("//cdn.example.com/a".to_string());
+    match urlset.url(url.clone()) {
+        Err(Error::Resolve { reference, error: ResolveError::OtherHost(_) }) => {
+            assert_eq!(reference, "//cdn.example.com/a")
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+    let written = String::from_utf8(urlset.end().unwrap()).unwrap();
+    assert!(written.contains("<loc>https://www.example.com/blog/post-1</loc>"), "{}", written);
+    assert!(written.contains("<loc>https://www.example.com/site/post-2</loc>"), "{}", written);
+
+    let options = WriterOptions {
+        skip_invalid: true,
+        ..WriterOptions::default()
+    };
+    let mut urlset = SiteMapWriter::with_options(Vec::new(), options).start_urlset().unwrap().base_url(base());
+    urlset.url(url).unwrap();
+    assert_eq!(urlset.skipped(), 1);
+
+    // without a base url a relative location is a missing loc
+    let mut urlset = SiteMapWriter::new(Vec::new()).start_urlset().unwrap();
+    let mut url = UrlEntry::new();
+    url.loc = Location::from("/blog".to_string());
+    assert!(matches!(urlset.url(url), Err(Error::MissingLoc { index: 0 })));
+}
+
+#[test]
+fn test_document_resolve_locs() {
+    let document = "<urlset><url><loc>/blog/post-1</loc></url>\
+                    <url><loc>https://www.example.com/</loc></url>\
+                    <url><loc>about</loc></url></urlset>";
+    let mut sitemap = SiteMap::read_from(document.as_bytes()).unwrap();
+    assert_eq!(sitemap.resolve_locs(&base()).unwrap(), 2);
+    let locs: Vec<&str> = sitemap.entries().iter().filter_map(|url| url.loc.as_str()).collect();
+    assert_eq!(locs,
+               vec!["https://www.example.com/blog/post-1", "https://www.example.com/", "https://www.example.com/site/about"]);
+
+    let document = "<urlset><url><loc>/blog</loc></url><url><loc>//cdn.example.com/a</loc></url></urlset>";
+    let mut sitemap = SiteMap::read_from(document.as_bytes()).unwrap();
+    let error = sitemap.resolve_locs(&base()).unwrap_err();
+    assert_eq!(error.to_string(),
+               "Cannot resolve the loc '//cdn.example.com/a': resolved to https://cdn.example.com/a \
+                on another host than the base url");
+    assert_eq!(sitemap.entries()[0].loc.as_str(), None);
+}
@@ -0,0 +1,148 @@
+extern crate sitemap;
+extern crate url;
+
+use sitemap::{Error, LimitKind};
+use sitemap::resolver::{Fetch, MemoryFetch, SiteMapIndexResolver};
+use std::cell::RefCell;
+use std::io::Read;
+use url::Url;
+
+fn index(locs: &[&str]) -> String {
+    let mut document = String::from("<sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">");
+    for loc in locs {
+        document.push_str(&format!("<sitemap><loc>{}</loc></sitemap>", loc));
+    }
+    document.push_str("</sitemapindex>");
+    document
+}
+
+fn urlset(locs: &[&str]) -> String {
+    let mut document = String::from("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">");
+    for loc in locs {
+        document.push_str(&format!("<url><loc>{}</loc></url>", loc));
+    }
+    document.push_str("</urlset>");
+    document
+}
+
+fn resolve<F: Fetch>(resolver: &SiteMapIndexResolver<F>, url: &str) -> Vec<Result<String, String>> {
+    resolver.resolve(&Url::parse(url).unwrap())
+        .map(|result| {
+            result.map(|url_entry| url_entry.loc.get_url().unwrap().to_string())
+                .map_err(|error| error.to_string())
+        })
+        .collect()
+}
+
+/// Fetcher recording the requested urls.
+struct RecordingFetch {
+    inner: MemoryFetch,
+    fetched: RefCell<Vec<String>>,
+}
+
+impl Fetch for RecordingFetch {
+    fn fetch(&self, url: &Url) -> Result<Box<dyn Read>, Error> {
+        self.fetched.borrow_mut().push(url.to_string());
+        self.inner.fetch(url)
+    }
+}
+
+#[test]
+fn test_resolve_index() {
+    let fetcher = MemoryFetch::new()
+        .document("http://www.example.com/index.xml",
+                  index(&["http://www.example.com/a.xml", "http://www.example.com/b.xml"]))
+        .document("http://www.example.com/a.xml",
+                  urlset(&["http://www.example.com/1", "http://www.example.com/2"]))
+        .document("http://www.example.com/b.xml", urlset(&["http://www.example.com/3"]));
+    let resolver = SiteMapIndexResolver::new(fetcher);
+    assert_eq!(resolve(&resolver, "http://www.example.com/index.xml"),
+               vec![Ok("http://www.example.com/1".to_string()),
+                    Ok("http://www.example.com/2".to_string()),
+                    Ok("http://www.example.com/3".to_string())]);
+    // a urlset resolves to its own urls
+    assert_eq!(resolve(&resolver, "http://www.example.com/b.xml"),
+               vec![Ok("http://www.example.com/3".to_string())]);
+}
+
+#[test]
+fn test_resolve_errors_continue() {
+    let fetcher = MemoryFetch::new()
+        .document("http://www.example.com/index.xml",
+                  index(&["http://www.example.com/missing.xml",
+                          "not a url",
+                          "http://www.example.com/a.xml"]))
+        .document("http://www.example.com/a.xml", urlset(&["http://www.example.com/1"]));
+    let resolver = SiteMapIndexResolver::new(fetcher);
+    let results = resolve(&resolver, "http://www.example.com/index.xml");
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0],
+               Err("I/O error: no document for http://www.example.com/missing.xml".to_string()));
+    assert!(results[1].as_ref().unwrap_err().contains("Invalid <loc> value"));
+    assert_eq!(results[2], Ok("http://www.example.com/1".to_string()));
+
+    let results = resolve(&resolver, "http://www.example.com/other.xml");
+    assert_eq!(results,
+               vec![Err("I/O error: no document for http://www.example.com/other.xml".to_string())]);
+}
+
+#[test]
+fn test_resolve_cycles() {
+    let fetcher = RecordingFetch {
+        inner: MemoryFetch::new()
+            .document("http://www.example.com/index.xml",
+                      index(&["http://www.example.com/index.xml",
+                              "http://www.example.com/nested.xml"]))
+            .document("http://www.example.com/nested.xml",
+                      index(&["http://www.example.com/index.xml",
+                              "http://www.example.com/a.xml"]))
+            .document("http://www.example.com/a.xml", urlset(&["http://www.example.com/1"])),
+        fetched: RefCell::new(Vec::new()),
+    };
+    let resolver = SiteMapIndexResolver::new(&fetcher).max_depth(2);
+    let results = resolve(&resolver, "http://www.example.com/index.xml");
+    assert_eq!(results,
+               vec![Err("sitemap http://www.example.com/index.xml already visited".to_string()),
+                    Err("sitemap http://www.example.com/index.xml already visited".to_string()),
+                    Ok("http://www.example.com/1".to_string())]);
+    assert_eq!(*fetcher.fetched.borrow(),
+               vec!["http://www.example.com/index.xml",
+                    "http://www.example.com/nested.xml",
+                    "http://www.example.com/a.xml"]);
+}
+
+#[test]
+fn test_resolve_max_depth() {
+    let fetcher = MemoryFetch::new()
+        .document("http://www.example.com/index.xml",
+                  index(&["http://www.example.com/nested.xml"]))
+        .document("http://www.example.com/nested.xml",
+                  index(&["http://www.example.com/a.xml"]))
+        .document("http://www.example.com/a.xml", urlset(&["http://www.example.com/1"]));
+    let resolver = SiteMapIndexResolver::new(&fetcher);
+    assert_eq!(resolve(&resolver, "http://www.example.com/index.xml"),
+               vec![Err("sitemap http://www.example.com/a.xml over the maximum depth of 1"
+                   .to_string())]);
+    let resolver = SiteMapIndexResolver::new(&fetcher).max_depth(2);
+    assert_eq!(resolve(&resolver, "http://www.example.com/index.xml"),
+               vec![Ok("http://www.example.com/1".to_string())]);
+}
+
+#[test]
+fn test_resolve_max_urls() {
+    let fetcher = MemoryFetch::new()
+        .document("http://www.example.com/index.xml",
+                  index(&["http://www.example.com/a.xml", "http://www.example.com/b.xml"]))
+        .document("http://www.example.com/a.xml",
+                  urlset(&["http://www.example.com/1", "http://www.example.com/2"]))
+        .document("http://www.example.com/b.xml", urlset(&["http://www.example.com/3"]));
+    let resolver = SiteMapIndexResolver::new(fetcher).max_urls(2);
+    let mut urls = resolver.resolve(&Url::parse("http://www.example.com/index.xml").unwrap());
+    assert!(urls.next().unwrap().is_ok());
+    assert!(urls.next().unwrap().is_ok());
+    match urls.next() {
+        Some(Err(Error::LimitExceeded { kind: LimitKind::UrlCount, limit: 2 })) => {}
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert!(urls.next().is_none());
+}
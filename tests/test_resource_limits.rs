@@ -0,0 +1,165 @@
+extern crate sitemap;
+
+use sitemap::{Error, ResourceKind};
+use sitemap::reader::{ReaderOptions, SiteMapEvent, SiteMapReader};
+use std::fs::File;
+
+fn nested(depth: usize) -> Vec<u8> {
+    let mut document = "<urlset><url><loc>http://www.example.com/</loc>".to_string();
+    document.push_str(&"<a>".repeat(depth));
+    document.push_str(&"</a>".repeat(depth));
+    document.push_str("</url></urlset>");
+    document.into_bytes()
+}
+
+fn resource_limit(events: &[Result<SiteMapEvent, Error>]) -> Option<(ResourceKind, u64)> {
+    match events.last() {
+        Some(Err(Error::ResourceLimit { which, limit })) => Some((*which, *limit)),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_depth_limit() {
+    let data = nested(40);
+    let events: Vec<_> = SiteMapReader::new(&data[..]).collect();
+    assert_eq!(events.len(), 1);
+    assert_eq!(resource_limit(&events), Some((ResourceKind::Depth, 32)));
+    assert_eq!(events[0].as_ref().unwrap_err().to_string(),
+               "Elements nested over the limit of 32 levels");
+
+    let options = ReaderOptions { max_depth: 64, ..ReaderOptions::default() };
+    let events: Vec<_> = SiteMapReader::with_options(&data[..], options).collect();
+    assert!(matches!(events[..], [Ok(SiteMapEvent::Url(_))]));
+}
+
+#[test]
+fn test_text_length_limit() {
+    let data = format!("<urlset><url><loc>http://www.example.com/{}</loc></url></urlset>",
+                       "a".repeat(5000));
+    let events: Vec<_> = SiteMapReader::new(data.as_bytes()).collect();
+    assert_eq!(events.len(), 1);
+    assert_eq!(resource_limit(&events), Some((ResourceKind::TextLength, 4096)));
+
+    // extension elements allow longer texts
+    let caption = |length: usize| {
+        format!("<urlset xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\"><url>\
+                 <loc>http://www.example.com/</loc><image:image>\
+                 <image:loc>http://www.example.com/image.png</image:loc>\
+                 <image:caption>{}</image:caption></image:image></url></urlset>",
+                "&amp;".repeat(length))
+    };
+    let data = caption(5000);
+    match SiteMapReader::new(data.as_bytes()).next() {
+        Some(Ok(SiteMapEvent::Url(url_entry))) => {
+            assert_eq!(url_entry.images[0].caption.as_ref().map(String::len), Some(5000))
+        }
+        other => panic!("unexpected event {:?}", other),
+    }
+    let data = caption(70000);
+    let events: Vec<_> = SiteMapReader::new(data.as_bytes()).collect();
+    assert_eq!(resource_limit(&events), Some((ResourceKind::TextLength, 65536)));
+}
+
+#[test]
+fn test_raw_text_limit() {
+    let options = ReaderOptions {
+        max_text_length: 16,
+        max_extension_text_length: 16,
+        ..ReaderOptions::default()
+    };
+    let data = format!("<urlset><url><loc>{}</loc></url></urlset>", " ".repeat(100_000));
+    let events: Vec<_> = SiteMapReader::with_options(data.as_bytes(), options).collect();
+    assert_eq!(events.len(), 1);
+    assert_eq!(resource_limit(&events), Some((ResourceKind::TextLength, 128)));
+}
+
+#[test]
+fn test_doctype_rejected() {
+    let data: &[u8] = b"<?xml version=\"1.0\"?>
+<!DOCTYPE lolz [
+  <!ENTITY lol \"lol\">
+  <!ENTITY lol1 \"&lol;&lol;&lol;&lol;&lol;&lol;&lol;&lol;&lol;&lol;\">
+  <!ENTITY lol2 \"&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;\">
+  <!ENTITY lol3 \"&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;\">
+  <!ENTITY lol4 \"&lol3;&lol3;&lol3;&lol3;&lol3;&lol3;&lol3;&lol3;&lol3;&lol3;\">
+  <!ENTITY lol5 \"&lol4;&lol4;&lol4;&lol4;&lol4;&lol4;&lol4;&lol4;&lol4;&lol4;\">
+  <!ENTITY lol6 \"&lol5;&lol5;&lol5;&lol5;&lol5;&lol5;&lol5;&lol5;&lol5;&lol5;\">
+]>
+<urlset><url><loc>http://www.example.com/&lol6;</loc></url></urlset>";
+    let events: Vec<_> = SiteMapReader::new(data).collect();
+    assert_eq!(events.len(), 1);
+    match events[0] {
+        Err(Error::Parse { line, ref message, .. }) => {
+            assert_eq!(line, 2);
+            assert_eq!(message, "DOCTYPE declarations are not allowed");
+        }
+        ref other => panic!("unexpected event {:?}", other),
+    }
+    // the expansion is bounded when allowed
+    let options = ReaderOptions { allow_doctype: true, ..ReaderOptions::default() };
+    let events: Vec<_> = SiteMapReader::with_options(data, options).collect();
+    assert!(matches!(events[..], [Err(_)]), "{:?}", events);
+}
+
+#[test]
+fn test_bytes_limit() {
+    let file = File::open("tests/documents/sitemap1.xml").unwrap();
+    let options = ReaderOptions { max_bytes: Some(300), ..ReaderOptions::default() };
+    let events: Vec<_> = SiteMapReader::with_options(file, options).collect();
+    assert_eq!(resource_limit(&events), Some((ResourceKind::Bytes, 300)));
+    assert_eq!(events.last().unwrap().as_ref().unwrap_err().to_string(),
+               "Document over the limit of 300 bytes");
+
+    let data = format!("{}<urlset/>", "garbage ".repeat(1000));
+    let options = ReaderOptions {
+        max_bytes: Some(1024),
+        skip_leading_garbage: true,
+        ..ReaderOptions::default()
+    };
+    let events: Vec<_> = SiteMapReader::with_options(data.as_bytes(), options).collect();
+    assert_eq!(resource_limit(&events), Some((ResourceKind::Bytes, 1024)));
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_async_resource_limits() {
+    use sitemap::async_reader::AsyncSiteMapReader;
+
+    async fn read(data: &[u8], options: ReaderOptions) -> Vec<Result<SiteMapEvent, Error>> {
+        let mut reader = AsyncSiteMapReader::with_options(data, options);
+        let mut events = Vec::new();
+        while let Some(event) = reader.next_event().await {
+            events.push(event);
+        }
+        events
+    }
+
+    let events = read(&nested(40), ReaderOptions::default()).await;
+    assert_eq!(resource_limit(&events), Some((ResourceKind::Depth, 32)));
+
+    let data = format!("<urlset><url><loc>http://www.example.com/{}</loc></url></urlset>",
+                       "a&amp;".repeat(3000));
+    let events = read(data.as_bytes(), ReaderOptions::default()).await;
+    assert_eq!(resource_limit(&events), Some((ResourceKind::TextLength, 4096)));
+
+    let options = ReaderOptions {
+        max_text_length: 16,
+        max_extension_text_length: 16,
+        ..ReaderOptions::default()
+    };
+    let data = format!("<urlset><url><loc>{}</loc></url></urlset>", " ".repeat(100_000));
+    let events = read(data.as_bytes(), options).await;
+    assert_eq!(resource_limit(&events), Some((ResourceKind::TextLength, 128)));
+
+    let data: &[u8] = b"<!DOCTYPE urlset><urlset><url><loc>http://www.example.com/</loc></url></urlset>";
+    let events = read(data, ReaderOptions::default()).await;
+    assert!(matches!(events[..], [Err(Error::Parse { .. })]));
+    let options = ReaderOptions { allow_doctype: true, ..ReaderOptions::default() };
+    assert!(matches!(read(data, options).await[..], [Ok(SiteMapEvent::Url(_))]));
+
+    let data = std::fs::read("tests/documents/sitemap1.xml").unwrap();
+    let options = ReaderOptions { max_bytes: Some(300), ..ReaderOptions::default() };
+    let events = read(&data, options).await;
+    assert_eq!(resource_limit(&events), Some((ResourceKind::Bytes, 300)));
+}
@@ -0,0 +1,50 @@
+extern crate sitemap;
+
+use sitemap::Error;
+use sitemap::robots::extract_sitemaps;
+use std::fs;
+
+fn extract(robots_txt: &str) -> Vec<Result<String, String>> {
+    extract_sitemaps(robots_txt)
+        .into_iter()
+        .map(|result| {
+            result.map(|url| url.to_string()).map_err(|error| match error {
+                Error::InvalidValue { value, .. } => value,
+                error => panic!("unexpected error {:?}", error),
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn test_extract_sitemaps() {
+    let robots_txt = fs::read_to_string("tests/documents/robots.txt").unwrap();
+    assert_eq!(extract(&robots_txt),
+               vec![Ok("http://www.example.com/sitemap.xml".to_string()),
+                    Ok("http://www.example.com/news_sitemap.xml".to_string()),
+                    Ok("http://www.example.com/sitemap_index.xml.gz".to_string()),
+                    Err("/relative_sitemap.xml".to_string()),
+                    Ok("http://www.example.com/sitemap.xml?page=2".to_string())]);
+}
+
+#[test]
+fn test_extract_sitemaps_bom_crlf() {
+    let robots_txt = fs::read_to_string("tests/documents/robots_crlf.txt").unwrap();
+    assert!(robots_txt.starts_with('\u{feff}'));
+    assert_eq!(extract(&robots_txt),
+               vec![Ok("http://www.example.com/first.xml".to_string()),
+                    Ok("http://www.example.com/second.xml".to_string())]);
+}
+
+#[test]
+fn test_extract_sitemaps_relative() {
+    match extract_sitemaps("Sitemap: sitemap.xml").pop() {
+        Some(Err(error)) => {
+            assert_eq!(error.to_string(),
+                       "Invalid <Sitemap> value 'sitemap.xml': relative URL without a base")
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert!(extract_sitemaps("").is_empty());
+    assert!(extract_sitemaps("User-agent: *\nDisallow: /").is_empty());
+}
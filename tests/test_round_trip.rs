@@ -0,0 +1,187 @@
+extern crate sitemap;
+extern crate url;
+extern crate chrono;
+
+use sitemap::reader::{SiteMapReader, SiteMapEvent};
+use sitemap::writer::{Declaration, SiteMapWriter, WriterOptions};
+use chrono::{DateTime, NaiveDate};
+use sitemap::structs::{ImageEntry, LastMod, SiteMapEntry, UrlEntry, Priority};
+use std::fs::File;
+use std::io::Read;
+use url::Url;
+
+fn read_sitemap<T: Read>(source: T) -> (Vec<UrlEntry>, Vec<SiteMapEntry>) {
+    let mut urls = Vec::new();
+    let mut sitemaps = Vec::new();
+    for entity in SiteMapReader::new(source) {
+        match entity {
+            Ok(SiteMapEvent::Url(url_entry)) => {
+                urls.push(url_entry);
+            }
+            Ok(SiteMapEvent::SiteMap(sitemap_entry)) => {
+                sitemaps.push(sitemap_entry);
+            }
+            Ok(_) => {}
+            Err(error) => {
+                panic!("unexpected error {:?}", error);
+            }
+        }
+    }
+    (urls, sitemaps)
+}
+
+fn write_sitemap(urls: &[UrlEntry], sitemaps: &[SiteMapEntry]) -> Vec<u8> {
+    let sitemap_writer = SiteMapWriter::new(Vec::new());
+    let mut url_writer = sitemap_writer.start_urlset().expect("start urlset");
+    for url in urls {
+        url_writer.url(url.clone()).expect("write url");
+    }
+    let mut output = url_writer.end().expect("end urlset");
+    // the index follows the urlset in the same document
+    let options = WriterOptions { xml_declaration: Declaration::None, ..WriterOptions::default() };
+    let sitemap_writer = SiteMapWriter::with_options(Vec::new(), options);
+    let mut index_writer = sitemap_writer.start_sitemapindex().expect("start index");
+    for sitemap in sitemaps {
+        index_writer.sitemap(sitemap.clone()).expect("write sitemap");
+    }
+    output.extend(index_writer.end().expect("end index"));
+    output
+}
+
+#[test]
+fn test_round_trip_equality() {
+    let file = File::open("tests/documents/sitemap1.xml").unwrap();
+    let (urls, sitemaps) = read_sitemap(file);
+    let output = write_sitemap(&urls, &sitemaps);
+    let (read_urls, read_sitemaps) = read_sitemap(&output[..]);
+    assert_eq!(read_urls, urls);
+    assert_eq!(read_sitemaps, sitemaps);
+
+    // a second pass must produce the very same document
+    assert_eq!(write_sitemap(&read_urls, &read_sitemaps), output);
+}
+
+#[test]
+fn test_entry_equality() {
+    let first = UrlEntry::builder()
+        .loc("http://www.example.com/")
+        .priority(0.3)
+        .build()
+        .expect("valid");
+    let mut second = first.clone();
+    assert_eq!(first, second);
+    second.priority = Priority::Value(0.31);
+    assert_ne!(first, second);
+    second.priority = Priority::from("0.3".to_string());
+    assert_eq!(first, second);
+    assert_ne!(first, UrlEntry::new());
+    assert_eq!(Priority::from("NaN".to_string()), Priority::from("NaN".to_string()));
+    assert_eq!(Priority::from("x".to_string()), Priority::from("y".to_string()));
+    assert_ne!(Priority::from("x".to_string()), Priority::from("".to_string()));
+}
+
+#[test]
+fn test_round_trip_escaping() {
+    let mut image = ImageEntry::with_loc(Url::parse("https://example.com/a.png?s=1&t=2").unwrap());
+    image.title = Some("Tom's \"best\" <pics> & more".to_string());
+    let urls = vec![UrlEntry::builder()
+                        .loc("https://example.com/search?q=a&b=c<d")
+                        .image(image)
+                        .build()
+                        .expect("valid"),
+                    UrlEntry::builder()
+                        .loc("https://example.com/it's?q=\"quoted\"&x=>")
+                        .build()
+                        .expect("valid")];
+    let sitemaps = vec![SiteMapEntry::builder()
+                            .loc("https://example.com/sitemap.xml?page=1&size=10")
+                            .build()
+                            .expect("valid")];
+    let output = write_sitemap(&urls, &sitemaps);
+    let text = String::from_utf8(output.clone()).unwrap();
+    assert!(text.contains("<loc>https://example.com/search?q=a&amp;b=c%3Cd</loc>"), "{}", text);
+    assert!(text.contains("<image:title>Tom&apos;s &quot;best&quot; &lt;pics&gt; &amp; more</image:title>"),
+            "{}",
+            text);
+    assert!(text.contains("page=1&amp;size=10"), "{}", text);
+    let (read_urls, read_sitemaps) = read_sitemap(&output[..]);
+    assert_eq!(read_urls, urls);
+    assert_eq!(read_sitemaps, sitemaps);
+    assert_eq!(read_urls[0].images[0].title, Some("Tom's \"best\" <pics> & more".to_string()));
+}
+
+#[test]
+fn test_read_character_references() {
+    let data: &[u8] = b"<urlset>\
+        <url><loc>https://example.com/?a=1&#38;b=2&#x26;c=3&amp;d=&lt;4&gt;</loc></url>\
+        <url><loc>https://example.com/?a=1&amp;amp;b=2</loc></url>\
+        </urlset>";
+    let (urls, _) = read_sitemap(data);
+    assert_eq!(urls[0].loc.get_url().unwrap().as_str(), "https://example.com/?a=1&b=2&c=3&d=%3C4%3E");
+    // a double escaped ampersand is an `&amp;` in the url itself
+    assert_eq!(urls[1].loc.get_url().unwrap().as_str(), "https://example.com/?a=1&amp;b=2");
+}
+
+#[test]
+fn test_round_trip_date_only() {
+    let date = NaiveDate::from_ymd_opt(2024, 1, 5).unwrap();
+    let time = DateTime::parse_from_rfc3339("2024-01-05T10:20:30+02:00").unwrap();
+    let urls = vec![UrlEntry::builder()
+                        .loc("http://www.example.com/")
+                        .lastmod_date(date)
+                        .build()
+                        .expect("valid"),
+                    UrlEntry::builder()
+                        .loc("http://www.example.com/other.html")
+                        .lastmod(time)
+                        .build()
+                        .expect("valid")];
+    let sitemaps = vec![SiteMapEntry::builder()
+                            .loc("http://www.example.com/sitemap.xml")
+                            .lastmod_date(date)
+                            .build()
+                            .expect("valid")];
+    let output = write_sitemap(&urls, &sitemaps);
+    let text = String::from_utf8(output.clone()).unwrap();
+    assert_eq!(text.matches("<lastmod>2024-01-05</lastmod>").count(), 2, "{}", text);
+    assert!(text.contains("<lastmod>2024-01-05T10:20:30+02:00</lastmod>"), "{}", text);
+    let (read_urls, read_sitemaps) = read_sitemap(&output[..]);
+    assert_eq!(read_urls, urls);
+    assert_eq!(read_sitemaps, sitemaps);
+    assert_eq!(read_urls[0].lastmod, LastMod::Date(date));
+    assert_eq!(read_urls[1].lastmod, LastMod::DateTime(time));
+
+    // compatible accessors of a date
+    assert_eq!(read_urls[0].lastmod.get_time(),
+               Some(DateTime::parse_from_rfc3339("2024-01-05T00:00:00+00:00").unwrap()));
+    assert_eq!(read_urls[0].lastmod.get_date(), Some(date));
+    assert_eq!(read_urls[1].lastmod.get_date(), Some(date));
+    assert_eq!(read_urls[0].lastmod.raw(), Some("2024-01-05".to_string()));
+}
+
+#[test]
+fn test_round_trip_w3c_precision() {
+    let texts = ["2024-05-01T10:00Z", "2024-05-01T10:00+02:00", "2024-05-01T10:00:00Z", "2024-05-01T10:00:00.5-05:00"];
+    let mut document = String::from("<urlset>");
+    for text in &texts {
+        document.push_str(&format!("<url><loc>http://www.example.com/</loc><lastmod>{}</lastmod></url>", text));
+    }
+    document.push_str("</urlset>");
+    let (urls, _) = read_sitemap(document.as_bytes());
+    let time = DateTime::parse_from_rfc3339("2024-05-01T10:00:00+00:00").unwrap();
+    assert!(matches!(urls[0].lastmod, LastMod::W3c(_)));
+    assert_eq!(urls[0].lastmod.get_time(), Some(time));
+    assert_eq!(urls[0].lastmod.raw(), Some("2024-05-01T10:00Z".to_string()));
+    let output = write_sitemap(&urls, &[]);
+    let text = String::from_utf8(output.clone()).unwrap();
+    for lastmod in &texts {
+        assert!(text.contains(&format!("<lastmod>{}</lastmod>", lastmod)), "{}", text);
+    }
+    let (read_urls, _) = read_sitemap(&output[..]);
+    assert_eq!(read_urls, urls);
+
+    // the text a `DateTime` is written with stays a `DateTime`
+    let (urls, _) = read_sitemap(&b"<urlset><url><loc>http://www.example.com/</loc>\
+                                    <lastmod>2024-05-01T10:00:00+00:00</lastmod></url></urlset>"[..]);
+    assert_eq!(urls[0].lastmod, LastMod::DateTime(time));
+}
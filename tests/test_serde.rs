@@ -0,0 +1,97 @@
+#![cfg(feature = "serde")]
+extern crate sitemap;
+extern crate serde_json;
+
+use sitemap::reader::{SiteMapReader, SiteMapEvent};
+use sitemap::structs::{ChangeFreq, LastMod, Location, Priority, SiteMapEntry, UrlEntry};
+use sitemap::diff::{self, DiffOptions};
+use sitemap::stats::Stats;
+use sitemap::validate::{self, ValidationOptions};
+use std::fs::File;
+
+#[test]
+fn test_serde_round_trip() {
+    let file = File::open("tests/documents/sitemap1.xml").unwrap();
+    for entity in SiteMapReader::new(file) {
+        match entity {
+            Ok(SiteMapEvent::Url(url_entry)) => {
+                let json = serde_json::to_string(&url_entry).unwrap();
+                let read: UrlEntry = serde_json::from_str(&json).unwrap();
+                assert_eq!(read, url_entry);
+            }
+            Ok(SiteMapEvent::SiteMap(sitemap_entry)) => {
+                let json = serde_json::to_string(&sitemap_entry).unwrap();
+                let read: SiteMapEntry = serde_json::from_str(&json).unwrap();
+                assert_eq!(read, sitemap_entry);
+            }
+            Ok(_) => {}
+            Err(error) => panic!("unexpected error {:?}", error),
+        }
+    }
+}
+
+#[test]
+fn test_serde_format() {
+    let mut entry = UrlEntry::new();
+    entry.loc = Location::from("http://www.example.com/".to_string());
+    entry.lastmod = LastMod::from("2005-01-01".to_string());
+    entry.changefreq = ChangeFreq::Monthly;
+    entry.priority = Priority::Value(0.5);
+    assert_eq!(serde_json::to_string(&entry).unwrap(),
+               "{\"loc\":\"http://www.example.com/\",\"lastmod\":\"2005-01-01\",\
+                \"changefreq\":\"monthly\",\"priority\":0.5}");
+    assert_eq!(serde_json::to_string(&UrlEntry::new()).unwrap(),
+               "{\"loc\":null,\"lastmod\":null,\"changefreq\":null,\"priority\":null}");
+    let read: UrlEntry = serde_json::from_str("{\"loc\":\"http://www.example.com/\"}").unwrap();
+    assert_eq!(read.loc, entry.loc);
+    assert_eq!(read.lastmod, LastMod::None);
+}
+
+#[test]
+fn test_serde_invalid_values() {
+    assert!(serde_json::from_str::<UrlEntry>("{\"loc\":\"not a url\"}").is_err());
+    assert!(serde_json::from_str::<UrlEntry>("{\"lastmod\":\"yesterday\"}").is_err());
+    assert!(serde_json::from_str::<UrlEntry>("{\"changefreq\":\"2 days\"}").is_err());
+    assert!(serde_json::from_str::<UrlEntry>("{\"priority\":1.5}").is_err());
+    assert!(serde_json::from_str::<UrlEntry>("{\"priority\":\"high\"}").is_err());
+
+    let mut entry = UrlEntry::new();
+    entry.priority = Priority::from("1.5".to_string());
+    assert!(serde_json::to_string(&entry).is_err());
+}
+
+#[test]
+fn test_serde_validation_report() {
+    let document = "<urlset><url><loc>http://www.example.com/</loc><priority>2</priority></url></urlset>";
+    let report = validate::validate(document.as_bytes(), &ValidationOptions::default());
+    assert_eq!(serde_json::to_string(&report).unwrap(),
+               "{\"entries\":1,\"issues\":[\
+                {\"kind\":\"InvalidPriority\",\"count\":1,\"samples\":\
+                [{\"position\":{\"line\":1,\"column\":48},\"value\":\"2\"}]},\
+                {\"kind\":\"MissingNamespace\",\"count\":1,\"samples\":\
+                [{\"position\":null,\"value\":\"\"}]}],\
+                \"fatal\":null}");
+}
+
+#[test]
+fn test_serde_diff() {
+    let old = vec![UrlEntry::builder().loc("http://www.example.com/").priority(0.5).build().unwrap()];
+    let new = vec![UrlEntry::builder().loc("http://www.example.com/").priority(0.8).build().unwrap()];
+    let diff = diff::diff(&old, &new, &DiffOptions::default());
+    assert_eq!(serde_json::to_string(&diff).unwrap(),
+               "{\"added\":[],\"removed\":[],\"changed\":[{\"loc\":\"http://www.example.com/\",\
+                \"fields\":[{\"Priority\":{\"old\":0.5,\"new\":0.8}}]}]}");
+}
+
+#[test]
+fn test_serde_stats() {
+    let mut stats = Stats::new();
+    stats.add(&UrlEntry::builder().loc("http://www.example.com/").priority(0.5).build().unwrap());
+    assert_eq!(serde_json::to_string(&stats).unwrap(),
+               "{\"urls\":1,\"changefreq\":{\"always\":0,\"hourly\":0,\"daily\":0,\"weekly\":0,\
+                \"monthly\":0,\"yearly\":0,\"never\":0},\"min_lastmod\":null,\"median_lastmod\":null,\
+                \"max_lastmod\":null,\"priorities\":[0,0,0,0,0,1,0,0,0,0,0],\
+                \"missing\":{\"lastmod\":1,\"changefreq\":1,\"priority\":0},\
+                \"invalid\":{\"lastmod\":0,\"changefreq\":0,\"priority\":0},\
+                \"hosts\":[\"www.example.com\"],\"hosts_truncated\":false,\"bytes\":69}");
+}
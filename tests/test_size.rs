@@ -0,0 +1,16 @@
+#![cfg(target_pointer_width = "64")]
+extern crate sitemap;
+
+use sitemap::structs::{ChangeFreq, LastMod, News, Priority, SiteMapEntry, UrlEntry};
+use std::mem::size_of;
+
+#[test]
+fn test_entry_size() {
+    // only the valid values are kept inline, errors and news are boxed
+    assert!(size_of::<LastMod>() <= 24, "LastMod is {} bytes", size_of::<LastMod>());
+    assert!(size_of::<Priority>() <= 16, "Priority is {} bytes", size_of::<Priority>());
+    assert!(size_of::<ChangeFreq>() <= 16, "ChangeFreq is {} bytes", size_of::<ChangeFreq>());
+    assert!(size_of::<News>() <= 16, "News is {} bytes", size_of::<News>());
+    assert!(size_of::<UrlEntry>() <= 280, "UrlEntry is {} bytes", size_of::<UrlEntry>());
+    assert!(size_of::<SiteMapEntry>() <= 160, "SiteMapEntry is {} bytes", size_of::<SiteMapEntry>());
+}
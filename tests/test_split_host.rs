@@ -0,0 +1,94 @@
+extern crate sitemap;
+
+use sitemap::reader::{SiteMapEvent, SiteMapReader};
+use sitemap::split::{self, HostSummary};
+use sitemap::structs::{Location, UrlEntry, MAX_URLS_PER_SITEMAP};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+type Outputs = Rc<RefCell<BTreeMap<(String, usize), Vec<u8>>>>;
+
+/// Output appending to the map of the outputs.
+struct Output {
+    outputs: Outputs,
+    key: (String, usize),
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.outputs.borrow_mut().entry(self.key.clone()).or_default().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn urls(document: &[u8]) -> Vec<String> {
+    SiteMapReader::new(document)
+        .filter_map(|event| match event {
+            Ok(SiteMapEvent::Url(entry)) => entry.loc.raw(),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_split_by_host() {
+    let mut entries: Vec<UrlEntry> = ["http://www.example.com/",
+                                      "http://blog.example.com/a",
+                                      "https://www.example.com/secure",
+                                      "http://blog.example.com/b"]
+        .iter()
+        .map(|loc| UrlEntry::builder().loc(*loc).build().unwrap())
+        .collect();
+    entries.push(UrlEntry::new());
+    let mut invalid = UrlEntry::new();
+    invalid.loc = Location::from("not a url".to_string());
+    entries.push(invalid);
+    entries.push(UrlEntry::builder().loc("mailto:someone@example.com").build().unwrap());
+
+    let outputs = Outputs::default();
+    let split = split::by_host(entries, |host: &str, number: usize| {
+            Ok(Output {
+                outputs: Rc::clone(&outputs),
+                key: (host.to_string(), number),
+            })
+        })
+        .unwrap();
+    assert_eq!(split.hosts.into_iter().collect::<Vec<_>>(),
+               [("blog.example.com".to_string(), HostSummary { files: 1, urls: 2 }),
+                ("www.example.com".to_string(), HostSummary { files: 1, urls: 2 })]);
+    assert_eq!(split.errors.len(), 3);
+    assert_eq!(split.errors[1].loc.raw(), Some("not a url".to_string()));
+
+    let outputs = outputs.borrow();
+    assert_eq!(outputs.len(), 2);
+    assert_eq!(urls(&outputs[&("blog.example.com".to_string(), 1)]),
+               ["http://blog.example.com/a", "http://blog.example.com/b"]);
+    assert_eq!(urls(&outputs[&("www.example.com".to_string(), 1)]),
+               ["http://www.example.com/", "https://www.example.com/secure"]);
+}
+
+#[test]
+fn test_split_by_host_limit() {
+    let entries = (0..MAX_URLS_PER_SITEMAP + 1).map(|i| {
+        UrlEntry::builder().loc(format!("http://www.example.com/{}", i)).build().unwrap()
+    });
+    let outputs = Outputs::default();
+    let split = split::by_host(entries, |host: &str, number: usize| {
+            Ok(Output {
+                outputs: Rc::clone(&outputs),
+                key: (host.to_string(), number),
+            })
+        })
+        .unwrap();
+    assert_eq!(split.hosts["www.example.com"],
+               HostSummary { files: 2, urls: MAX_URLS_PER_SITEMAP + 1 });
+    let outputs = outputs.borrow();
+    assert_eq!(urls(&outputs[&("www.example.com".to_string(), 2)]),
+               [format!("http://www.example.com/{}", MAX_URLS_PER_SITEMAP)]);
+}
@@ -0,0 +1,143 @@
+extern crate sitemap;
+
+use sitemap::Error;
+use sitemap::reader::{SiteMapReader, SiteMapEvent};
+use sitemap::writer::{SiteMapWriter, SplitSiteMapWriter, WriterOptions};
+use sitemap::structs::{UrlEntry, Location, LastMod};
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+#[derive(Clone,Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn urls(count: usize) -> Vec<UrlEntry> {
+    (0..count)
+        .map(|i| UrlEntry::builder().loc(format!("http://www.example.com/page{}.html", i)).build())
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+fn read_urls(document: &[u8]) -> Vec<UrlEntry> {
+    let mut urls = Vec::new();
+    for entity in SiteMapReader::new(document) {
+        match entity {
+            Ok(SiteMapEvent::Url(url_entry)) => urls.push(url_entry),
+            other => panic!("unexpected entity {:?}", other),
+        }
+    }
+    urls
+}
+
+fn split(entries: &[UrlEntry], max_urls: usize, max_bytes: u64) -> (Vec<Vec<u8>>, Vec<u8>) {
+    let files: Rc<RefCell<Vec<SharedBuffer>>> = Rc::default();
+    let index = {
+        let files = files.clone();
+        let mut writer = SplitSiteMapWriter::new(move |number| {
+                                                     let mut files = files.borrow_mut();
+                                                     assert_eq!(files.len() + 1, number);
+                                                     files.push(SharedBuffer::default());
+                                                     Ok(files[number - 1].clone())
+                                                 },
+                                                 "http://www.example.com/sitemap-{}.xml")
+            .unwrap()
+            .max_urls(max_urls)
+            .max_bytes(max_bytes);
+        for entry in entries {
+            writer.url(entry.clone()).unwrap();
+        }
+        writer.end(Vec::new()).unwrap()
+    };
+    let files = files.borrow().iter().map(|file| file.0.borrow().clone()).collect();
+    (files, index)
+}
+
+#[test]
+fn test_split_by_url_count() {
+    let entries = urls(5);
+    let (files, index) = split(&entries, 2, u64::MAX);
+    assert_eq!(files.len(), 3);
+    let counts: Vec<usize> = files.iter().map(|file| read_urls(file).len()).collect();
+    assert_eq!(counts, vec![2, 2, 1]);
+    let read: Vec<UrlEntry> = files.iter().flat_map(|file| read_urls(file)).collect();
+    assert_eq!(read, entries);
+
+    let mut locs = Vec::new();
+    for entity in SiteMapReader::new(&index[..]) {
+        match entity {
+            Ok(SiteMapEvent::SiteMap(sitemap_entry)) => {
+                assert!(matches!(sitemap_entry.lastmod, LastMod::DateTime(_)));
+                locs.push(sitemap_entry.loc.get_url().unwrap().to_string());
+            }
+            Ok(SiteMapEvent::IndexStart { .. }) => {}
+            other => panic!("unexpected entity {:?}", other),
+        }
+    }
+    assert_eq!(locs,
+               vec!["http://www.example.com/sitemap-1.xml",
+                    "http://www.example.com/sitemap-2.xml",
+                    "http://www.example.com/sitemap-3.xml"]);
+}
+
+#[test]
+fn test_split_by_size() {
+    let entries = urls(7);
+    let mut two_urls = Vec::new();
+    {
+        let mut writer = SiteMapWriter::new(&mut two_urls).start_urlset().unwrap();
+        writer.url(entries[0].clone()).unwrap();
+        writer.url(entries[1].clone()).unwrap();
+        writer.end().unwrap();
+    }
+    // a document with two urls fits exactly, including the closing tag
+    let max_bytes = two_urls.len() as u64;
+    let (files, _) = split(&entries, 1000, max_bytes);
+    assert_eq!(files[0], two_urls);
+    assert_eq!(files.len(), 4);
+    for file in &files {
+        assert!(file.len() as u64 <= max_bytes);
+    }
+    let read: Vec<UrlEntry> = files.iter().flat_map(|file| read_urls(file)).collect();
+    assert_eq!(read, entries);
+}
+
+#[test]
+fn test_split_errors() {
+    let result = SplitSiteMapWriter::new(|_| Ok(Vec::new()), "http://www.example.com/sitemap.xml");
+    assert!(matches!(result, Err(Error::Invalid(_))));
+    let result = SplitSiteMapWriter::new(|_| Ok(Vec::new()), "sitemap-{}.xml");
+    assert!(matches!(result, Err(Error::Url(_))));
+
+    let mut writer = SplitSiteMapWriter::new(|_| Ok(Vec::new()), "http://www.example.com/{}.xml")
+        .unwrap()
+        .max_bytes(100);
+    let mut entry = UrlEntry::new();
+    entry.loc = Location::from(format!("http://www.example.com/{}", "a".repeat(100)));
+    assert!(matches!(writer.url(entry), Err(Error::Invalid(_))));
+}
+
+#[test]
+fn test_split_missing_loc() {
+    let mut writer = SplitSiteMapWriter::new(|_| Ok(Vec::new()), "http://www.example.com/sitemap-{}.xml").unwrap();
+    writer.url(urls(1).remove(0)).unwrap();
+    assert!(matches!(writer.url(UrlEntry::new()), Err(Error::MissingLoc { index: 1 })));
+
+    let options = WriterOptions { skip_invalid: true, ..WriterOptions::default() };
+    let mut writer = SplitSiteMapWriter::new(|_| Ok(Vec::new()), "http://www.example.com/sitemap-{}.xml")
+        .unwrap()
+        .options(options);
+    for entry in urls(2) {
+        writer.url(UrlEntry::new()).unwrap();
+        writer.url(entry).unwrap();
+    }
+    assert_eq!(writer.skipped(), 2);
+}
@@ -0,0 +1,79 @@
+extern crate sitemap;
+extern crate chrono;
+
+use chrono::NaiveDate;
+use sitemap::reader::SiteMapReader;
+use sitemap::stats::{Stats, StatsOptions};
+use sitemap::structs::{ChangeFreq, LastMod, UrlEntry};
+
+#[test]
+fn test_stats() {
+    let document = "<urlset>\
+                    <url><loc>http://www.example.com/</loc><lastmod>2020-01-03</lastmod>\
+                    <changefreq>daily</changefreq><priority>1.0</priority></url>\
+                    <url><loc>http://www.example.com/a?b=1&amp;c=2</loc><lastmod>2020-01-01T10:00:00Z</lastmod>\
+                    <changefreq>sometimes</changefreq><priority>0.26</priority></url>\
+                    <url><loc>http://other.example.com/</loc><lastmod>2020-01-02</lastmod></url>\
+                    <url><loc>http://other.example.com/b</loc><lastmod>yesterday</lastmod>\
+                    <changefreq>daily</changefreq><priority>3</priority></url>\
+                    </urlset>";
+    let stats = Stats::from_reader(SiteMapReader::new(document.as_bytes())).unwrap();
+    assert_eq!(stats.urls(), 4);
+    assert_eq!(stats.changefreq().daily, 2);
+    assert_eq!(stats.changefreq().weekly, 0);
+    assert_eq!(stats.min_lastmod().raw(), Some("2020-01-01T10:00:00Z".to_string()));
+    assert_eq!(stats.median_lastmod().raw(), Some("2020-01-02".to_string()));
+    assert_eq!(stats.max_lastmod().raw(), Some("2020-01-03".to_string()));
+    assert_eq!(stats.priorities(), &[0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 1]);
+    assert_eq!((stats.missing().lastmod, stats.missing().changefreq, stats.missing().priority), (0, 1, 1));
+    assert_eq!((stats.invalid().lastmod, stats.invalid().changefreq, stats.invalid().priority), (1, 1, 1));
+    assert_eq!(stats.hosts().iter().collect::<Vec<_>>(), ["other.example.com", "www.example.com"]);
+    assert!(!stats.hosts_truncated());
+    let first = "<url><loc>http://www.example.com/</loc><lastmod>2020-01-03</lastmod>\
+                 <changefreq>daily</changefreq><priority>1.0</priority></url>";
+    let second = "<url><loc>http://www.example.com/a?b=1&amp;c=2</loc>\
+                  <lastmod>2020-01-01T10:00:00Z</lastmod><priority>0.3</priority></url>";
+    let third = "<url><loc>http://other.example.com/</loc><lastmod>2020-01-02</lastmod></url>";
+    let fourth = "<url><loc>http://other.example.com/b</loc><changefreq>daily</changefreq></url>";
+    assert_eq!(stats.bytes(), (first.len() + second.len() + third.len() + fourth.len()) as u64);
+
+    let text = stats.to_string();
+    let lines: Vec<_> = text.lines().collect();
+    assert_eq!(lines[0], "urls        4");
+    assert_eq!(lines[2], "lastmod     min 2020-01-01T10:00:00Z, median 2020-01-02, max 2020-01-03");
+    assert_eq!(lines[4], "missing     lastmod 0, changefreq 1, priority 1");
+    assert_eq!(lines[6], "hosts       2");
+}
+
+#[test]
+fn test_stats_options() {
+    let mut stats = Stats::with_options(StatsOptions { max_hosts: 1 });
+    for host in &["a", "a", "b"] {
+        stats.add(&UrlEntry::builder().loc(format!("http://{}.example.com/", host)).build().unwrap());
+    }
+    assert_eq!(stats.hosts().len(), 1);
+    assert!(stats.hosts_truncated());
+    assert_eq!(stats.median_lastmod(), LastMod::None);
+    assert!(stats.to_string().contains("hosts       1+"));
+}
+
+#[test]
+fn test_stats_median_sample() {
+    let mut stats = Stats::new();
+    let first = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+    for day in 0..5000 {
+        let date = first + chrono::Duration::days((day * 7919) % 5000);
+        stats.add(&UrlEntry::builder()
+            .loc("http://www.example.com/")
+            .lastmod_date(date)
+            .changefreq(ChangeFreq::Never)
+            .build()
+            .unwrap());
+    }
+    assert_eq!(stats.changefreq().never, 5000);
+    assert_eq!(stats.min_lastmod().get_date(), Some(first));
+    assert_eq!(stats.max_lastmod().get_date(), Some(first + chrono::Duration::days(4999)));
+    let median = stats.median_lastmod().get_date().unwrap();
+    let offset = (median - first).num_days();
+    assert!((2250..2750).contains(&offset), "median {} days from the first date", offset);
+}
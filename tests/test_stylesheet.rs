@@ -0,0 +1,93 @@
+extern crate sitemap;
+
+use sitemap::reader::{SiteMapEvent, SiteMapReader};
+use sitemap::structs::{SiteMapEntry, Stylesheet, UrlEntry};
+use sitemap::writer::{Declaration, Indent, SiteMapWriter, WriterOptions};
+
+fn urls() -> Vec<UrlEntry> {
+    vec![UrlEntry::builder().loc("http://www.example.com/").build().expect("valid"),
+         UrlEntry::builder().loc("http://www.example.com/about").build().expect("valid")]
+}
+
+fn write_styled(options: WriterOptions) -> String {
+    let mut writer = SiteMapWriter::with_options(Vec::new(), options)
+        .with_stylesheet("/sitemap.xsl?style=\"table\"&v=<2>")
+        .start_urlset()
+        .expect("start urlset");
+    for url in urls() {
+        writer.url(url).expect("write url");
+    }
+    String::from_utf8(writer.end().expect("end urlset")).unwrap()
+}
+
+#[test]
+fn test_write_stylesheet() {
+    let output = write_styled(WriterOptions::default());
+    assert!(output.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                                <?xml-stylesheet type=\"text/xsl\" \
+                                href=\"/sitemap.xsl?style=&quot;table&quot;&amp;v=&lt;2&gt;\"?>\n\
+                                <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">"));
+    let compact = WriterOptions { indent: Indent::None, xml_declaration: Declaration::None, ..WriterOptions::default() };
+    assert!(write_styled(compact).starts_with("<?xml-stylesheet type=\"text/xsl\""));
+
+    let mut index_writer = SiteMapWriter::new(Vec::new())
+        .with_stylesheet("/index.xsl")
+        .stylesheet(Stylesheet {
+            href: "/index.css".to_string(),
+            media_type: "text/css".to_string(),
+        })
+        .start_sitemapindex()
+        .expect("start index");
+    let sitemap = SiteMapEntry::builder().loc("http://www.example.com/sitemap.xml").build().expect("valid");
+    index_writer.sitemap(sitemap).expect("write sitemap");
+    let output = String::from_utf8(index_writer.end().expect("end index")).unwrap();
+    assert!(output.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                                <?xml-stylesheet type=\"text/xsl\" href=\"/index.xsl\"?>\n\
+                                <?xml-stylesheet type=\"text/css\" href=\"/index.css\"?>\n\
+                                <sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">"));
+}
+
+#[test]
+fn test_read_stylesheet() {
+    let output = write_styled(WriterOptions::default());
+    let mut reader = SiteMapReader::new(output.as_bytes());
+    let mut read = Vec::new();
+    for event in &mut reader {
+        match event.expect("valid document") {
+            SiteMapEvent::Url(url) => read.push(url),
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+    assert_eq!(read, urls());
+    assert_eq!(reader.stylesheets(),
+               [Stylesheet {
+                    href: "/sitemap.xsl?style=\"table\"&v=<2>".to_string(),
+                    media_type: "text/xsl".to_string(),
+                }]);
+
+    let document = "<?xml-stylesheet href='a.xsl' type='text/xsl'?>\
+                    <?xml-stylesheet href=\"missing-type.xsl\"?>\
+                    <?other data?>\
+                    <urlset><url><loc>http://www.example.com/</loc></url></urlset>\
+                    <?xml-stylesheet type=\"text/xsl\" href=\"after.xsl\"?>";
+    let mut reader = SiteMapReader::new(document.as_bytes());
+    assert_eq!(reader.by_ref().filter(|event| event.is_ok()).count(), 1);
+    assert_eq!(reader.stylesheets(), [Stylesheet::new("a.xsl")]);
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_async_read_stylesheet() {
+    use sitemap::async_reader::AsyncSiteMapReader;
+
+    let output = write_styled(WriterOptions::default());
+    let mut reader = AsyncSiteMapReader::new(output.as_bytes());
+    let mut read = Vec::new();
+    while let Some(event) = reader.next_event().await {
+        if let SiteMapEvent::Url(url) = event.expect("valid document") {
+            read.push(url);
+        }
+    }
+    assert_eq!(read, urls());
+    assert_eq!(reader.stylesheets(), [Stylesheet::new("/sitemap.xsl?style=\"table\"&v=<2>")]);
+}
@@ -0,0 +1,48 @@
+extern crate sitemap;
+extern crate chrono;
+
+use chrono::DateTime;
+#[cfg(feature = "fs")]
+use sitemap::Error;
+use sitemap::structs::LastMod;
+#[cfg(feature = "fs")]
+use sitemap::structs::UrlEntry;
+#[cfg(feature = "fs")]
+use std::fs::{self, File};
+use std::time::{Duration, UNIX_EPOCH};
+
+#[test]
+fn test_lastmod_from_system_time() {
+    let time = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123) + Duration::from_nanos(456);
+    assert_eq!(LastMod::from(time),
+               LastMod::DateTime(DateTime::parse_from_rfc3339("2023-11-14T22:13:20+00:00").unwrap()));
+    assert_eq!(LastMod::from_system_time_millis(time),
+               LastMod::DateTime(DateTime::parse_from_rfc3339("2023-11-14T22:13:20.123+00:00").unwrap()));
+    let before_epoch = UNIX_EPOCH - Duration::from_millis(1500);
+    assert_eq!(LastMod::from(before_epoch),
+               LastMod::DateTime(DateTime::parse_from_rfc3339("1969-12-31T23:59:58+00:00").unwrap()));
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_lastmod_from_file() {
+    let path = std::env::temp_dir().join(format!("sitemap-mtime-{}.xml", std::process::id()));
+    let file = File::create(&path).unwrap();
+    let modified = UNIX_EPOCH + Duration::from_millis(1_600_000_000_999);
+    file.set_modified(modified).unwrap();
+    let url_entry = UrlEntry::builder()
+        .loc("http://www.example.com/")
+        .lastmod_from_file(&path)
+        .unwrap()
+        .build()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(url_entry.lastmod.get_time(),
+               Some(DateTime::parse_from_rfc3339("2020-09-13T12:26:40+00:00").unwrap()));
+
+    match UrlEntry::builder().lastmod_from_file(&path) {
+        Err(Error::Io(_)) => {}
+        Err(error) => panic!("unexpected error {:?}", error),
+        Ok(_) => panic!("missing file read"),
+    }
+}
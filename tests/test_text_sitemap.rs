@@ -0,0 +1,110 @@
+extern crate sitemap;
+
+use sitemap::{Error, LimitKind};
+use sitemap::reader::{ReaderOptions, SiteMapReader, TextSiteMapReader};
+use sitemap::structs::{Location, Position, UrlEntry};
+use sitemap::writer::{LimitPolicy, TextSiteMapWriter};
+use std::fs::File;
+use std::io::BufReader;
+
+#[test]
+fn test_read_text_sitemap() {
+    let file = File::open("tests/documents/sitemap.txt").unwrap();
+    let results: Vec<_> = TextSiteMapReader::new(BufReader::new(file)).collect();
+    assert_eq!(results.len(), 6);
+    let locs: Vec<String> = results.iter()
+        .filter_map(|result| result.as_ref().ok())
+        .map(|url_entry| url_entry.loc.get_url().unwrap().to_string())
+        .collect();
+    assert_eq!(locs,
+               vec!["http://www.example.com/",
+                    "http://www.example.com/catalog?item=12&desc=vacation_hawaii",
+                    "http://www.example.com/indented.html",
+                    "http://www.example.com/last.html"]);
+    let url_entry = results[2].as_ref().unwrap();
+    assert_eq!(url_entry.position(), Some(Position { line: 5, column: 3 }));
+    assert_eq!(url_entry.priority, sitemap::structs::Priority::None);
+    match results[3] {
+        Err(Error::Parse { line: 6, column: 1, ref message }) => {
+            assert_eq!(message, "invalid url 'catalog.html'")
+        }
+        ref other => panic!("unexpected result {:?}", other),
+    }
+    match results[4] {
+        Err(Error::Parse { line: 7, column: 2049, ref message }) => {
+            assert_eq!(message, "line longer than 2048 characters")
+        }
+        ref other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_read_text_sitemap_encoding() {
+    let data: &[u8] = b"\xef\xbb\xbfhttp://www.example.com/\nhttp://www.example.com/\xff\n";
+    let results: Vec<_> = TextSiteMapReader::new(data).collect();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    assert!(matches!(results[1], Err(Error::Parse { line: 2, column: 24, .. })));
+}
+
+#[test]
+fn test_write_text_sitemap() {
+    let mut writer = TextSiteMapWriter::new(Vec::new());
+    for loc in &["http://www.example.com/", "http://www.example.com/other.html"] {
+        writer.url(UrlEntry::builder().loc(loc).priority(0.5).build().unwrap()).unwrap();
+    }
+    let mut url_entry = UrlEntry::new();
+    url_entry.loc = Location::from("other.html".to_string());
+    assert!(matches!(writer.url(url_entry), Err(Error::Invalid(_))));
+    assert!(matches!(writer.url(UrlEntry::new()), Err(Error::Invalid(_))));
+    let output = writer.into_inner();
+    assert_eq!(String::from_utf8(output.clone()).unwrap(),
+               "http://www.example.com/\nhttp://www.example.com/other.html\n");
+
+    let urls = TextSiteMapReader::new(&output[..]).collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(urls.len(), 2);
+}
+
+#[test]
+fn test_write_text_sitemap_raw_locs() {
+    let mut writer = TextSiteMapWriter::new(Vec::new());
+    for raw in &["not a url\nhttp://evil.example/", "not a url"] {
+        let mut url_entry = UrlEntry::new();
+        url_entry.loc = Location::Raw(raw.to_string());
+        assert!(matches!(writer.url(url_entry), Err(Error::Invalid(_))), "{}", raw);
+    }
+    let document = "<urlset><url><loc>not a url</loc></url><url><loc>HTTP://www.Example.com/a</loc></url></urlset>";
+    let options = ReaderOptions { parse_urls: false, ..ReaderOptions::default() };
+    let urls = SiteMapReader::with_options(document.as_bytes(), options)
+        .into_url_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert!(matches!(urls[0].loc, Location::Raw(_)));
+    assert!(matches!(writer.url(urls[0].clone()), Err(Error::Invalid(_))));
+    writer.url(urls[1].clone()).unwrap();
+    assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), "http://www.example.com/a\n");
+}
+
+#[test]
+fn test_write_text_sitemap_limits() {
+    let url = UrlEntry::builder().loc("http://www.example.com/").build().unwrap();
+    let mut writer = TextSiteMapWriter::new(Vec::new())
+        .limit_policy(LimitPolicy::Error)
+        .max_urls(2);
+    writer.url(url.clone()).unwrap();
+    writer.url(url.clone()).unwrap();
+    match writer.url(url.clone()) {
+        Err(Error::LimitExceeded { kind: LimitKind::UrlCount, limit: 2 }) => {}
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    let mut writer = TextSiteMapWriter::new(Vec::new())
+        .limit_policy(LimitPolicy::Warn)
+        .max_bytes(30);
+    for _ in 0..3 {
+        writer.url(url.clone()).unwrap();
+    }
+    assert_eq!(writer.warnings().len(), 1);
+    assert!(matches!(writer.warnings()[0],
+                     Error::LimitExceeded { kind: LimitKind::Size, limit: 30 }));
+}
@@ -0,0 +1,106 @@
+extern crate sitemap;
+
+use sitemap::reader::{ReaderOptions, SiteMapEvent, SiteMapReader, TruncateReason};
+use sitemap::{Error, LimitKind, ResourceKind};
+use std::cell::Cell;
+use std::io::{self, Read};
+use std::rc::Rc;
+
+/// Endless `<urlset>`, like a hostile server, counting the bytes read.
+struct Endless {
+    entry: u64,
+    pending: Vec<u8>,
+    read: Rc<Cell<u64>>,
+}
+
+impl Endless {
+    fn new() -> (Endless, Rc<Cell<u64>>) {
+        let read = Rc::new(Cell::new(0));
+        let endless = Endless {
+            entry: 0,
+            pending: b"<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n".to_vec(),
+            read: read.clone(),
+        };
+        (endless, read)
+    }
+}
+
+impl Read for Endless {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pending.len() < buf.len() {
+            let url = format!("<url><loc>http://www.example.com/{}</loc></url>\n", self.entry);
+            self.pending.extend_from_slice(url.as_bytes());
+            self.entry += 1;
+        }
+        buf.copy_from_slice(&self.pending[..buf.len()]);
+        self.pending.drain(..buf.len());
+        self.read.set(self.read.get() + buf.len() as u64);
+        Ok(buf.len())
+    }
+}
+
+fn urls(events: &[Result<SiteMapEvent, Error>]) -> usize {
+    events.iter().filter(|event| matches!(event, Ok(SiteMapEvent::Url(_)))).count()
+}
+
+#[test]
+fn test_take_bounded() {
+    let (endless, read) = Endless::new();
+    let urls: Vec<_> = SiteMapReader::new(endless).take(100).collect();
+    assert_eq!(urls.len(), 100);
+    assert!(urls.iter().all(Result::is_ok));
+    // about 5 KB of urls, and the read ahead of the parser
+    assert!(read.get() < 64 * 1024, "{} bytes read", read.get());
+}
+
+#[test]
+fn test_max_entries() {
+    let (endless, read) = Endless::new();
+    let options = ReaderOptions { max_entries: Some(100), ..ReaderOptions::default() };
+    let events: Vec<_> = SiteMapReader::with_options(endless, options).collect();
+    assert_eq!(urls(&events), 100);
+    assert_eq!(events.len(), 101);
+    assert!(matches!(events[100], Err(Error::LimitExceeded { kind: LimitKind::EntryCount, limit: 100 })));
+    assert!(read.get() < 64 * 1024, "{} bytes read", read.get());
+
+    let (endless, _) = Endless::new();
+    let options = ReaderOptions { max_entries: Some(100), truncate: true, ..ReaderOptions::default() };
+    let events: Vec<_> = SiteMapReader::with_options(endless, options).collect();
+    assert_eq!(urls(&events), 100);
+    assert!(matches!(events[100], Ok(SiteMapEvent::Truncated { reason: TruncateReason::Entries(100) })));
+    assert_eq!(events.len(), 101);
+
+    let document = "<sitemapindex><sitemap><loc>http://www.example.com/a.xml</loc></sitemap>\
+                    <sitemap><loc>http://www.example.com/b.xml</loc></sitemap></sitemapindex>";
+    let options = ReaderOptions { max_entries: Some(1), truncate: true, ..ReaderOptions::default() };
+    let events: Vec<_> = SiteMapReader::with_options(document.as_bytes(), options).collect();
+    assert_eq!(events.len(), 2);
+    assert!(matches!(events[1], Ok(SiteMapEvent::Truncated { reason: TruncateReason::Entries(1) })));
+    // the whole document within the limit is not truncated
+    let options = ReaderOptions { max_entries: Some(2), truncate: true, ..ReaderOptions::default() };
+    assert_eq!(SiteMapReader::with_options(document.as_bytes(), options).count(), 2);
+}
+
+#[test]
+fn test_max_bytes_truncate() {
+    let (endless, read) = Endless::new();
+    let options = ReaderOptions { max_bytes: Some(100_000), ..ReaderOptions::default() };
+    let events: Vec<_> = SiteMapReader::with_options(endless, options).collect();
+    assert!(urls(&events) > 1000);
+    match events.last() {
+        Some(Err(Error::ResourceLimit { which: ResourceKind::Bytes, limit: 100_000 })) => {}
+        other => panic!("unexpected event {:?}", other),
+    }
+    assert!(read.get() < 200_000, "{} bytes read", read.get());
+
+    let (endless, _) = Endless::new();
+    let options = ReaderOptions { max_bytes: Some(100_000), truncate: true, ..ReaderOptions::default() };
+    let events: Vec<_> = SiteMapReader::with_options(endless, options).collect();
+    assert!(events[..events.len() - 1].iter().all(|event| matches!(event, Ok(SiteMapEvent::Url(_)))));
+    assert!(matches!(events.last(), Some(Ok(SiteMapEvent::Truncated { reason: TruncateReason::Bytes(100_000) }))));
+
+    let options = ReaderOptions { max_urls: Some(10), truncate: true, ..ReaderOptions::default() };
+    let (endless, _) = Endless::new();
+    let events: Vec<_> = SiteMapReader::with_options(endless, options).collect();
+    assert!(matches!(events.last(), Some(Ok(SiteMapEvent::Truncated { reason: TruncateReason::Urls(10) }))));
+}
@@ -0,0 +1,92 @@
+extern crate sitemap;
+
+use sitemap::reader::{ReaderOptions, SiteMapEvent, SiteMapReader};
+use sitemap::structs::Position;
+
+static DOCUMENT: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                         <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" \
+                         xmlns:geo=\"http://www.google.com/geo/schemas/sitemap/1.0\">\n\
+                         <url><loc>http://www.example.com/</loc><lastmode>2024-01-01</lastmode></url>\n\
+                         <url><loc>http://www.example.com/b</loc>\
+                         <geo:geo a=\"1\"><geo:format>kml</geo:format></geo:geo></url>\n\
+                         <urls/>\n\
+                         </urlset>";
+
+fn emit() -> ReaderOptions {
+    ReaderOptions {
+        emit_unknown: true,
+        ..ReaderOptions::default()
+    }
+}
+
+fn unknown(document: &str, options: ReaderOptions) -> Vec<(String, usize, String, Position)> {
+    SiteMapReader::with_options(document.as_bytes(), options)
+        .filter_map(|event| match event {
+            Ok(SiteMapEvent::Unknown { name, depth, raw, position }) => Some((name.to_string(), depth, raw, position)),
+            Ok(_) => None,
+            Err(error) => panic!("unexpected error {:?}", error),
+        })
+        .collect()
+}
+
+#[test]
+fn test_unknown_disabled() {
+    assert!(unknown(DOCUMENT, ReaderOptions::default()).is_empty());
+}
+
+#[test]
+fn test_unknown_events() {
+    let events = unknown(DOCUMENT, emit());
+    assert_eq!(events, vec![("{http://www.sitemaps.org/schemas/sitemap/0.9}lastmode".to_string(),
+                             3,
+                             "<lastmode>2024-01-01</lastmode>".to_string(),
+                             Position { line: 3, column: 40 }),
+                            ("{http://www.google.com/geo/schemas/sitemap/1.0}geo:geo".to_string(),
+                             3,
+                             "<geo:geo a=\"1\"><geo:format>kml</geo:format></geo:geo>".to_string(),
+                             Position { line: 4, column: 41 }),
+                            ("{http://www.sitemaps.org/schemas/sitemap/0.9}urls".to_string(),
+                             2,
+                             "<urls></urls>".to_string(),
+                             Position { line: 5, column: 1 })]);
+}
+
+#[test]
+fn test_unknown_after_url() {
+    let events: Vec<_> = SiteMapReader::with_options(DOCUMENT.as_bytes(), emit())
+        .map(|event| match event.unwrap() {
+            SiteMapEvent::Url(url) => url.loc.get_url().unwrap().to_string(),
+            SiteMapEvent::Unknown { name, .. } => name.local_name,
+            _ => String::new(),
+        })
+        .collect();
+    assert_eq!(events, vec!["lastmode", "http://www.example.com/", "geo", "http://www.example.com/b", "urls"]);
+}
+
+#[test]
+fn test_unknown_index() {
+    let document = "<sitemapindex><sitemap><loc>http://www.example.com/a.xml</loc>\
+                    <lastmodified>2024-01-01</lastmodified></sitemap><site/></sitemapindex>";
+    let events = unknown(document, emit());
+    let names: Vec<_> = events.iter().map(|(name, depth, _, _)| (name.as_str(), *depth)).collect();
+    assert_eq!(names, vec![("lastmodified", 3), ("site", 2)]);
+}
+
+#[test]
+fn test_unknown_and_capture() {
+    let options = ReaderOptions {
+        capture_unknown: true,
+        ..emit()
+    };
+    let mut extensions = Vec::new();
+    let mut events = 0;
+    for event in SiteMapReader::with_options(DOCUMENT.as_bytes(), options) {
+        match event.unwrap() {
+            SiteMapEvent::Url(url) => extensions.extend(url.extensions.into_iter().map(|element| element.name)),
+            SiteMapEvent::Unknown { .. } => events += 1,
+            _ => {}
+        }
+    }
+    assert_eq!(events, 3);
+    assert_eq!(extensions, vec!["lastmode", "geo:geo"]);
+}
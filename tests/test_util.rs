@@ -0,0 +1,116 @@
+extern crate sitemap;
+extern crate chrono;
+
+use chrono::NaiveDate;
+use sitemap::structs::{CanonicalizeOptions, Location, Priority, UrlEntry};
+use sitemap::util::{self, LocOptions};
+
+fn entry(loc: &str, day: Option<u32>, priority: Option<f32>) -> UrlEntry {
+    let mut builder = UrlEntry::builder().loc(loc);
+    if let Some(day) = day {
+        builder = builder.lastmod_date(NaiveDate::from_ymd_opt(2020, 1, day).unwrap());
+    }
+    if let Some(priority) = priority {
+        builder = builder.priority(priority);
+    }
+    builder.build().expect("valid entry")
+}
+
+fn locs(entries: &[UrlEntry]) -> Vec<String> {
+    entries.iter().map(|entry| entry.loc.raw().unwrap()).collect()
+}
+
+#[test]
+fn test_dedup_by_loc() {
+    let mut entries = vec![entry("http://www.example.com", Some(1), None),
+                           entry("http://www.example.com/a", None, Some(0.2)),
+                           entry("http://www.example.com/", Some(3), None),
+                           entry("http://www.example.com/a", None, Some(0.9)),
+                           entry("http://www.example.com/a", None, None),
+                           entry("http://www.example.com/", Some(2), Some(1.0)),
+                           entry("http://www.example.com/b", None, None)];
+    util::dedup_by_loc(&mut entries);
+    assert_eq!(locs(&entries),
+               ["http://www.example.com/", "http://www.example.com/a", "http://www.example.com/b"]);
+    assert_eq!(entries[0].lastmod.raw(), Some("2020-01-03".to_string()));
+    assert_eq!(entries[1].priority, Priority::Value(0.9));
+}
+
+#[test]
+fn test_dedup_by_loc_options() {
+    let entries = vec![entry("http://www.example.com/a", None, None),
+                       entry("http://www.example.com/a/", None, None),
+                       entry("http://www.example.com/a#top", None, None),
+                       entry("http://www.example.com/a/#top", None, None)];
+    let mut deduped = entries.clone();
+    util::dedup_by_loc(&mut deduped);
+    assert_eq!(deduped.len(), 4);
+
+    let mut deduped = entries.clone();
+    let options = LocOptions { ignore_fragment: true, ..LocOptions::default() };
+    util::dedup_by_loc_with_options(&mut deduped, &options);
+    assert_eq!(locs(&deduped), ["http://www.example.com/a", "http://www.example.com/a/"]);
+
+    let mut deduped = entries.clone();
+    let options = LocOptions { ignore_trailing_slash: true, ..LocOptions::default() };
+    util::dedup_by_loc_with_options(&mut deduped, &options);
+    assert_eq!(locs(&deduped), ["http://www.example.com/a", "http://www.example.com/a#top"]);
+
+    let mut deduped = entries;
+    let options = LocOptions { ignore_fragment: true, ignore_trailing_slash: true };
+    util::dedup_by_loc_with_options(&mut deduped, &options);
+    assert_eq!(locs(&deduped), ["http://www.example.com/a"]);
+}
+
+#[test]
+fn test_sort() {
+    let mut entries = vec![entry("http://www.example.com/c", Some(2), None),
+                           entry("http://www.example.com/a", None, Some(0.9)),
+                           entry("http://www.example.com/d", Some(5), Some(0.1)),
+                           entry("http://www.example.com/b", Some(1), Some(0.6))];
+    util::sort_by_loc(&mut entries);
+    assert_eq!(locs(&entries),
+               ["http://www.example.com/a",
+                "http://www.example.com/b",
+                "http://www.example.com/c",
+                "http://www.example.com/d"]);
+    util::sort_by_priority_desc(&mut entries);
+    assert_eq!(locs(&entries),
+               ["http://www.example.com/a",
+                "http://www.example.com/b",
+                "http://www.example.com/c",
+                "http://www.example.com/d"]);
+    util::sort_by_lastmod_desc(&mut entries);
+    assert_eq!(locs(&entries),
+               ["http://www.example.com/d",
+                "http://www.example.com/c",
+                "http://www.example.com/b",
+                "http://www.example.com/a"]);
+}
+
+#[test]
+fn test_canonicalize() {
+    let options = CanonicalizeOptions::default();
+    let canonical = |loc: &str, options: &CanonicalizeOptions| {
+        loc.parse::<Location>().unwrap().canonicalize(options).raw().unwrap()
+    };
+    assert_eq!(canonical("HTTP://WWW.Example.COM:80/a/./b/../c", &options),
+               "http://www.example.com/a/c");
+    assert_eq!(canonical("https://www.example.com:443/%7euser/%2fdir%3a", &options),
+               "https://www.example.com/~user/%2Fdir%3A");
+    assert_eq!(canonical("http://www.example.com/?b=%41&a=1&b=0#top%2d", &options),
+               "http://www.example.com/?b=A&a=1&b=0#top-");
+    assert_eq!(canonical("http://B\u{fc}cher.example/", &options), "http://xn--bcher-kva.example/");
+
+    let options = CanonicalizeOptions { strip_fragment: true, sort_query: true };
+    assert_eq!(canonical("http://www.example.com/?b=2&a=1&b=0&c#top", &options),
+               "http://www.example.com/?a=1&b=2&b=0&c");
+    let invalid = Location::from("not a url".to_string());
+    assert_eq!(invalid.canonicalize(&options), invalid);
+
+    let mut entries = vec![entry("http://www.example.com:80/%7ea#x", None, None),
+                           entry("http://www.example.com/~a", None, None)];
+    util::canonicalize(&mut entries, &options);
+    util::dedup_by_loc(&mut entries);
+    assert_eq!(locs(&entries), ["http://www.example.com/~a"]);
+}
@@ -0,0 +1,137 @@
+extern crate sitemap;
+extern crate url;
+
+use sitemap::structs::{Position, UrlEntry};
+use sitemap::validate::{self, IssueKind, ScopeOptions, ScopeViolationKind, ValidationOptions};
+use url::Url;
+
+fn entries(locs: &[&str]) -> Vec<UrlEntry> {
+    locs.iter().map(|loc| UrlEntry::builder().loc(*loc).build().expect("valid entry")).collect()
+}
+
+#[test]
+fn test_check_scope() {
+    let sitemap_url = Url::parse("https://www.example.com/blog/sitemap.xml").unwrap();
+    let entries = entries(&["https://www.example.com/blog/",
+                            "https://www.example.com/blog/post?id=1",
+                            "http://www.example.com/blog/post",
+                            "https://example.com/blog/post",
+                            "https://www.example.com:8443/blog/post",
+                            "https://WWW.EXAMPLE.COM:443/blog/post",
+                            "https://www.example.com/about",
+                            "https://www.example.com/blogger"]);
+    let violations = validate::check_scope(&sitemap_url, entries.iter());
+    let kinds: Vec<_> = violations.iter().map(|violation| (violation.index, violation.kind)).collect();
+    assert_eq!(kinds,
+               [(2, ScopeViolationKind::Scheme),
+                (3, ScopeViolationKind::Host),
+                (4, ScopeViolationKind::Host),
+                (6, ScopeViolationKind::Path),
+                (7, ScopeViolationKind::Path)]);
+    assert_eq!(violations[0].url.as_str(), "http://www.example.com/blog/post");
+    assert_eq!(violations[3].to_string(),
+               "Url 6 https://www.example.com/about: path outside of the directory of the sitemap");
+
+    let options = ScopeOptions { check_path: false };
+    let violations = validate::check_scope_with_options(&sitemap_url, entries.iter(), &options);
+    assert_eq!(violations.len(), 3);
+    assert!(violations.iter().all(|violation| violation.kind != ScopeViolationKind::Path));
+}
+
+#[test]
+fn test_check_scope_root() {
+    let sitemap_url = Url::parse("http://www.example.com/sitemap.xml").unwrap();
+    let mut entries = entries(&["http://www.example.com/", "http://www.example.com/a/b/c"]);
+    entries.push(UrlEntry::default());
+    assert!(validate::check_scope(&sitemap_url, entries.iter()).is_empty());
+}
+
+#[test]
+fn test_validate() {
+    let long_loc = format!("http://www.example.com/{}", "a".repeat(2048));
+    let document = format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<urlset>
+<url><loc>http://www.example.com/</loc><lastmod>2020-01-01</lastmod></url>
+<url><loc>not a url</loc><priority>1.5</priority></url>
+<url><loc>http://www.example.com/</loc><changefreq>sometimes</changefreq></url>
+<url><loc>http://www.example.com/a?b=1&amp;c=2</loc><lastmod>yesterday</lastmod><priority>high</priority></url>
+<url><loc>{}</loc><lastmod>Wed, 01 Jan 2020 10:00:00 +0000</lastmod></url>
+</urlset>", long_loc);
+    let report = validate::validate(document.as_bytes(), &ValidationOptions::default());
+    assert_eq!(report.entries, 5);
+    assert_eq!(report.fatal, None);
+    assert!(!report.is_valid());
+    let counts: Vec<_> = report.issues.iter().map(|issues| (issues.kind, issues.count)).collect();
+    assert_eq!(counts,
+               [(IssueKind::InvalidPriority, 2),
+                (IssueKind::InvalidLoc, 1),
+                (IssueKind::InvalidChangeFreq, 1),
+                (IssueKind::DuplicateLoc, 1),
+                (IssueKind::InvalidLastMod, 2),
+                (IssueKind::LocTooLong, 1),
+                (IssueKind::MissingNamespace, 1)]);
+    let samples = &report.issues(IssueKind::InvalidLastMod).unwrap().samples;
+    assert_eq!(samples[0].position, Some(Position { line: 6, column: 53 }));
+    assert_eq!(samples[0].value, "yesterday");
+    assert_eq!(samples[1].value, "Wed, 01 Jan 2020 10:00:00 +0000");
+    assert_eq!(report.issues(IssueKind::InvalidChangeFreq).unwrap().samples[0].value, "sometimes");
+    assert_eq!(report.issues(IssueKind::DuplicateLoc).unwrap().samples[0].position,
+               Some(Position { line: 5, column: 6 }));
+    assert_eq!(report.count(IssueKind::UnescapedAmpersand), 0);
+
+    let options = ValidationOptions { max_samples: 1, ..ValidationOptions::default() };
+    let report = validate::validate(document.as_bytes(), &options);
+    let priorities = report.issues(IssueKind::InvalidPriority).unwrap();
+    assert_eq!((priorities.count, priorities.samples.len()), (2, 1));
+    assert_eq!(report.to_string().lines().take(3).collect::<Vec<_>>(),
+               ["5 entries, 9 issues", "priority out of range: 2", "  4:26 1.5"]);
+}
+
+#[test]
+fn test_validate_changefreq_suggestion() {
+    let document = "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n\
+                    <url><loc>http://www.example.com/a</loc><changefreq>Dayly</changefreq></url>\n\
+                    <url><loc>http://www.example.com/b</loc><changefreq>sometimes</changefreq></url>\n\
+                    </urlset>";
+    let report = validate::validate(document.as_bytes(), &ValidationOptions::default());
+    let samples = &report.issues(IssueKind::InvalidChangeFreq).unwrap().samples;
+    assert_eq!(samples[0].value, "Dayly");
+    assert_eq!(samples[0].suggestion.as_deref(), Some("daily"));
+    assert_eq!(samples[1].suggestion, None);
+    assert!(report.to_string().contains(" Dayly (did you mean 'daily'?)\n"), "{}", report);
+}
+
+#[test]
+fn test_validate_ampersand() {
+    let document = "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">
+<url><loc>http://www.example.com/?a=1&#38;b=2&amp;c=&#x26;</loc></url>
+<!-- a & b --><url><loc><![CDATA[http://www.example.com/?a&b]]></loc></url>
+<url><loc>http://www.example.com/?a=1&b=2</loc></url>
+</urlset>";
+    let report = validate::validate(document.as_bytes(), &ValidationOptions::default());
+    assert_eq!(report.entries, 2);
+    assert!(report.fatal.is_some());
+    let ampersands = report.issues(IssueKind::UnescapedAmpersand).unwrap();
+    assert_eq!(ampersands.count, 1);
+    assert_eq!(ampersands.samples[0].position, Some(Position { line: 4, column: 38 }));
+    assert_eq!(ampersands.samples[0].value, "&b=2");
+    assert_eq!(report.count(IssueKind::MissingNamespace), 0);
+
+    let document = "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\
+                    <url><loc>http://www.example.com/</loc></url></urlset>";
+    let report = validate::validate(document.as_bytes(), &ValidationOptions::default());
+    assert!(report.is_valid());
+    assert_eq!(report.to_string(), "1 entries, 0 issues");
+}
+
+#[test]
+fn test_validate_nan_priority() {
+    let document = "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n\
+                    <url><loc>http://www.example.com/a</loc><priority>NaN</priority></url>\n\
+                    </urlset>";
+    let report = validate::validate(document.as_bytes(), &ValidationOptions::default());
+    let priorities = report.issues(IssueKind::InvalidPriority).unwrap();
+    assert_eq!(priorities.count, 1);
+    assert_eq!(priorities.samples[0].value, "NaN");
+    assert!(!report.is_valid());
+}
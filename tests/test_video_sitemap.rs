@@ -0,0 +1,112 @@
+extern crate sitemap;
+extern crate chrono;
+extern crate url;
+
+use sitemap::reader::{SiteMapReader, SiteMapEvent};
+use sitemap::writer::SiteMapWriter;
+use sitemap::structs::{LastMod, Location, UrlEntry, VideoDuration, VideoEntry};
+use chrono::DateTime;
+use std::fs::File;
+use std::io::Read;
+use std::time::Duration;
+use url::Url;
+
+fn read_urls<T: Read>(source: T) -> Vec<UrlEntry> {
+    let mut urls = Vec::new();
+    for entity in SiteMapReader::new(source) {
+        match entity {
+            Ok(SiteMapEvent::Url(url_entry)) => urls.push(url_entry),
+            Ok(_) => {}
+            Err(error) => panic!("unexpected error {:?}", error),
+        }
+    }
+    urls
+}
+
+fn write_urls(urls: &[UrlEntry]) -> String {
+    let mut output = Vec::<u8>::new();
+    {
+        let mut url_writer = SiteMapWriter::new(&mut output).start_urlset().expect("start urlset");
+        for url in urls {
+            url_writer.url(url.clone()).expect("write url");
+        }
+        url_writer.end().expect("end urlset");
+    }
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn test_read_videos() {
+    let urls = read_urls(File::open("tests/documents/sitemap_videos.xml").unwrap());
+    assert_eq!(urls.len(), 2);
+    let video = &urls[0].videos[0];
+    assert_eq!(video.thumbnail_loc.get_url().unwrap(),
+               Url::parse("http://www.example.com/thumbs/123.jpg").unwrap());
+    assert_eq!(video.title, Some("Grilling steaks for summer".to_string()));
+    assert_eq!(video.description,
+               Some("Alkis shows you how to get perfectly done steaks every time".to_string()));
+    assert_eq!(video.content_loc.get_url().unwrap(),
+               Url::parse("http://streamserver.example.com/video123.mp4").unwrap());
+    assert_eq!(video.player_loc.get_url().unwrap(),
+               Url::parse("http://www.example.com/videoplayer.php?video=123").unwrap());
+    assert_eq!(video.duration.get_duration(), Some(Duration::from_secs(600)));
+    assert_eq!(video.expiration_date.get_time().unwrap(),
+               DateTime::parse_from_rfc3339("2021-11-05T19:20:30+08:00").unwrap());
+    assert_eq!(video.publication_date.get_time().unwrap(),
+               DateTime::parse_from_rfc3339("2007-11-05T19:20:30+08:00").unwrap());
+    assert_eq!(video.tags, vec!["steak".to_string(), "meat".to_string()]);
+    assert_eq!(video.live, Some(false));
+
+    let videos = &urls[1].videos;
+    assert_eq!(videos.len(), 2);
+    assert_eq!(videos[0].duration, VideoDuration::ErrValueGreaterMax(28801));
+    assert_eq!(videos[0].live, Some(true));
+    assert_eq!(videos[1].duration, VideoDuration::ErrValueLesserOne(0));
+    assert_eq!(videos[1].content_loc, Location::None);
+    assert_eq!(videos[1].expiration_date, LastMod::None);
+    assert!(videos[1].tags.is_empty());
+}
+
+#[test]
+fn test_video_duration() {
+    assert_eq!(VideoDuration::from("1".to_string()).get_duration(),
+               Some(Duration::from_secs(1)));
+    assert_eq!(VideoDuration::from("28800".to_string()).get_duration(),
+               Some(Duration::from_secs(28800)));
+    assert!(matches!(VideoDuration::from("ten".to_string()), VideoDuration::Err(_)));
+    assert!(VideoDuration::from("-5".to_string()).get_duration().is_none());
+}
+
+#[test]
+fn test_write_videos() {
+    let urls = read_urls(File::open("tests/documents/sitemap_videos.xml").unwrap());
+    let output = write_urls(&urls[..1]);
+    assert!(output.contains("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" xmlns:video=\"http://www.google.com/schemas/sitemap-video/1.1\">"));
+    assert!(output.contains("<video:duration>600</video:duration>"));
+    assert!(output.contains("<video:live>no</video:live>"));
+    assert_eq!(read_urls(output.as_bytes()), &urls[..1]);
+
+    // invalid durations are not written
+    let output = write_urls(&urls[1..]);
+    assert!(!output.contains("video:duration"));
+}
+
+#[test]
+fn test_build_video() {
+    let mut video = VideoEntry::new();
+    video.thumbnail_loc = Location::from("http://www.example.com/thumb.jpg".to_string());
+    video.title = Some("Title".to_string());
+    video.description = Some("Description".to_string());
+    video.duration = VideoDuration::from(120);
+    let entry = UrlEntry::builder()
+        .loc("http://www.example.com/")
+        .video(video.clone())
+        .build()
+        .unwrap();
+    let output = write_urls(std::slice::from_ref(&entry));
+    assert_eq!(read_urls(output.as_bytes()), vec![entry]);
+
+    video.duration = VideoDuration::from(30000);
+    let error = UrlEntry::builder().loc("http://www.example.com/").video(video).build().unwrap_err();
+    assert_eq!(error.errors()[0].field(), "video:duration");
+}
@@ -0,0 +1,83 @@
+extern crate sitemap;
+
+use sitemap::Error;
+use sitemap::reader::{DuplicateChildren, ReaderOptions, SiteMapReader, Warning, WarningKind};
+use sitemap::structs::{Position, Priority};
+
+static DOCUMENT: &str = "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n\
+                         <url><loc>http://www.example.com/a</loc><priority>high</priority></url>\n\
+                         <url><loc>not a url</loc><lastmod>yesterday</lastmod><changefreq>Dayly</changefreq></url>\n\
+                         <url><loc>http://www.example.com/c</loc><priority>1.5</priority>\
+                         <lastmod>Thu, 15 Jun 2023 10:00:00 GMT</lastmod></url>\n\
+                         <url><loc>http://www.example.com/d</loc><loc>http://www.example.com/e</loc></url>\n\
+                         </urlset>";
+
+fn read_warnings(document: &str, options: ReaderOptions) -> Vec<Warning> {
+    let mut reader = SiteMapReader::with_options(document.as_bytes(), options);
+    assert!(reader.by_ref().all(|event| event.is_ok()));
+    reader.warnings().to_vec()
+}
+
+#[test]
+fn test_warning_kinds() {
+    let options = ReaderOptions {
+        duplicate_children: DuplicateChildren::Warn,
+        ..ReaderOptions::default()
+    };
+    let warnings = read_warnings(DOCUMENT, options);
+    let kinds: Vec<_> = warnings.iter().map(|warning| (warning.entry_index, warning.kind)).collect();
+    assert_eq!(kinds,
+               [(0, WarningKind::InvalidPriority),
+                (1, WarningKind::InvalidLoc),
+                (1, WarningKind::InvalidLastMod),
+                (1, WarningKind::InvalidChangeFreq),
+                (2, WarningKind::NonStandardLastMod),
+                (2, WarningKind::PriorityOutOfRange),
+                (3, WarningKind::DuplicateChild)]);
+    assert_eq!(warnings[1].position, Some(Position { line: 3, column: 6 }));
+    assert_eq!(warnings[3].message,
+               "Invalid <changefreq> value: Not recognezed string 'Dayly', did you mean 'daily'?");
+    assert_eq!(warnings[6].to_string(), "5:41 duplicate loc in url, first at 5:6");
+    match Error::from(warnings[6].clone()) {
+        Error::Parse { line: 5, column: 41, message } => assert_eq!(message, warnings[6].message),
+        other => panic!("unexpected error {:?}", other),
+    }
+
+    let index = "<sitemapindex>\n\
+                 <sitemap><loc>http://www.example.com/a.xml</loc></sitemap>\n\
+                 <sitemap><loc>http://www.example.com/b.xml</loc><priority>0.5</priority>\
+                 <lastmod>2024-13-01</lastmod></sitemap>\n\
+                 </sitemapindex>";
+    let kinds: Vec<_> = read_warnings(index, ReaderOptions::default())
+        .iter()
+        .map(|warning| (warning.entry_index, warning.kind))
+        .collect();
+    assert_eq!(kinds, [(1, WarningKind::SiteMapUrlField), (1, WarningKind::InvalidLastMod)]);
+
+    let strict = ReaderOptions {
+        strict: true,
+        ..ReaderOptions::default()
+    };
+    let document = "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\
+                    <url><loc>http://www.example.com/</loc><changefreq>Weekly</changefreq></url></urlset>";
+    let warnings = read_warnings(document, strict);
+    assert!(matches!(warnings[..], [Warning { entry_index: 0, kind: WarningKind::ChangeFreqCase, .. }]));
+}
+
+#[test]
+fn test_warning_nan_priority() {
+    let document = "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n\
+                    <url><loc>http://www.example.com/a</loc><priority>NaN</priority></url>\n\
+                    </urlset>";
+    let urls = SiteMapReader::new(document.as_bytes())
+        .into_url_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(urls[0].priority, Priority::ErrNotANumber(Box::new("NaN".to_string())));
+    assert_eq!(urls[0].priority.raw().as_deref(), Some("NaN"));
+    let warnings = read_warnings(document, ReaderOptions::default());
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, WarningKind::InvalidPriority);
+    assert_eq!(warnings[0].message, "Invalid <priority> value: priority is not a number");
+    assert_eq!(warnings[0].position, Some(Position { line: 2, column: 41 }));
+}
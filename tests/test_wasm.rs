@@ -0,0 +1,59 @@
+//! Compile test of the minimal feature set for `wasm32-unknown-unknown`.
+//!
+//! The library and the `wasm_count_urls` example are checked for the
+//! target when its standard library is installed, with
+//! `rustup target add wasm32-unknown-unknown`, and for the host
+//! otherwise, the crate being built without a file system in both cases.
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+const TARGET: &str = "wasm32-unknown-unknown";
+
+/// Features supported on `wasm32-unknown-unknown`, `fs`, `http` and
+/// `parallel` excluded.
+const FEATURES: &str = "gzip,fast-xml,serde,encodings,feeds,borrowed,glob";
+
+/// Whether the standard library of `target` is installed.
+fn installed(target: &str) -> bool {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    Command::new(rustc)
+        .args(["--print", "sysroot"])
+        .output()
+        .ok()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .is_some_and(|sysroot| Path::new(&sysroot).join("lib/rustlib").join(target).exists())
+}
+
+/// Runs `cargo check` of the library and the example without the default
+/// features, with `features`.
+fn check(target: Option<&str>, features: &str) {
+    let manifest = env!("CARGO_MANIFEST_DIR");
+    let mut command = Command::new(env!("CARGO"));
+    command.current_dir(manifest)
+        .args(["check", "--quiet", "--lib", "--example", "wasm_count_urls", "--no-default-features"])
+        .args(["--target-dir", &Path::new(manifest).join("target").join("wasm-check").to_string_lossy()]);
+    if !features.is_empty() {
+        command.args(["--features", features]);
+    }
+    if let Some(target) = target {
+        command.args(["--target", target]);
+    }
+    let output = command.output().unwrap();
+    assert!(output.status.success(),
+            "cargo check of '{}' failed:\n{}",
+            features,
+            String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn test_wasm_check() {
+    let target = if installed(TARGET) {
+        Some(TARGET)
+    } else {
+        eprintln!("{} not installed, checking the host", TARGET);
+        None
+    };
+    check(target, "");
+    check(target, FEATURES);
+}
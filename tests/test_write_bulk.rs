@@ -0,0 +1,97 @@
+extern crate sitemap;
+
+use sitemap::Error;
+use sitemap::structs::{ChangeFreq, SiteMapEntry, UrlEntry};
+use sitemap::writer::{self, Declaration, DedupPolicy, ErrorPolicy, Indent, SiteMapWriter, UrlSetWriter, WriterOptions};
+
+fn compact_urlset() -> UrlSetWriter<Vec<u8>> {
+    let options = WriterOptions {
+        indent: Indent::None,
+        xml_declaration: Declaration::None,
+        ..WriterOptions::default()
+    };
+    SiteMapWriter::with_options(Vec::new(), options).start_urlset().unwrap()
+}
+
+fn url(loc: &str) -> UrlEntry {
+    let mut url = UrlEntry::new();
+    url.loc = loc.to_string().into();
+    url
+}
+
+#[test]
+fn test_url_with() {
+    let mut urlset = compact_urlset();
+    urlset.url_with(|url| url.loc("http://www.example.com/a").changefreq(ChangeFreq::Daily).priority(0.5)).unwrap();
+    match urlset.url_with(|url| url.loc("/relative").priority(1.5)) {
+        Err(Error::InvalidEntry { index: 1, field: "loc", ref reason }) => {
+            assert!(reason.starts_with("loc '/relative': "), "{}", reason);
+            assert!(reason.contains("; priority '1.5': "), "{}", reason);
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    // the index counts the urls refused
+    assert!(matches!(urlset.url(UrlEntry::new()), Err(Error::MissingLoc { index: 2 })));
+    match urlset.url_with(|url| url.priority(-1.0)) {
+        Err(error @ Error::InvalidEntry { index: 3, .. }) => {
+            assert!(error.to_string().starts_with("Entry 3 has an invalid "), "{}", error)
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    urlset.url_with(|url| url.loc("http://www.example.com/b")).unwrap();
+    let written = String::from_utf8(urlset.end().unwrap()).unwrap();
+    assert_eq!(written,
+               "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\
+                <url><loc>http://www.example.com/a</loc><changefreq>daily</changefreq><priority>0.5</priority></url>\
+                <url><loc>http://www.example.com/b</loc></url></urlset>");
+}
+
+#[test]
+fn test_urls() {
+    let urls = || vec![url("http://www.example.com/a"), url("not a url"), UrlEntry::new(), url("http://www.example.com/d")];
+
+    let mut urlset = compact_urlset();
+    let errors = urlset.urls(urls(), ErrorPolicy::Collect).unwrap();
+    let indexes: Vec<_> = errors.iter()
+        .map(|error| match *error {
+            Error::MissingLoc { index } => index,
+            ref other => panic!("unexpected error {:?}", other),
+        })
+        .collect();
+    assert_eq!(indexes, [1, 2]);
+    let written = String::from_utf8(urlset.end().unwrap()).unwrap();
+    assert!(written.contains("/a</loc></url><url><loc>http://www.example.com/d"), "{}", written);
+
+    let mut urlset = compact_urlset();
+    assert!(matches!(urlset.urls(urls(), ErrorPolicy::FailFast), Err(Error::MissingLoc { index: 1 })));
+    assert!(urlset.urls(Vec::new(), ErrorPolicy::default()).unwrap().is_empty());
+    let written = String::from_utf8(urlset.end().unwrap()).unwrap();
+    assert!(!written.contains("/d</loc>"), "{}", written);
+}
+
+#[test]
+fn test_write_urlset() {
+    let options = WriterOptions {
+        skip_invalid: true,
+        dedup: DedupPolicy::SkipSilently,
+        ..WriterOptions::default()
+    };
+    let urls = vec![url("http://www.example.com/a"), url("not a url"), url("http://www.example.com/a"), url("http://www.example.com/b")];
+    let mut out = Vec::new();
+    let summary = writer::write_urlset(&mut out, urls, &options).unwrap();
+    assert_eq!((summary.written, summary.skipped, summary.bytes), (2, 2, out.len() as u64));
+    let written = String::from_utf8(out).unwrap();
+    assert!(written.starts_with("<?xml") && written.ends_with("</urlset>"), "{}", written);
+    assert_eq!(written.matches("<url>").count(), 2);
+
+    let urls = vec![url("http://www.example.com/a"), UrlEntry::new()];
+    assert!(matches!(writer::write_urlset(Vec::new(), urls, &WriterOptions::default()), Err(Error::MissingLoc { index: 1 })));
+
+    let mut sitemap = SiteMapEntry::new();
+    sitemap.loc = "http://www.example.com/a.xml".to_string().into();
+    let entries = vec![sitemap, SiteMapEntry::new()];
+    let mut out = Vec::new();
+    let summary = writer::write_index(&mut out, entries, &options).unwrap();
+    assert_eq!((summary.written, summary.skipped, summary.bytes), (1, 1, out.len() as u64));
+    assert!(String::from_utf8(out).unwrap().ends_with("</sitemapindex>"));
+}
@@ -0,0 +1,89 @@
+extern crate sitemap;
+extern crate chrono;
+extern crate url;
+
+use chrono::{DateTime, NaiveDate};
+use sitemap::structs::{Alternate, ChangeFreq, ImageEntry, LastMod, SiteMapEntry, UrlEntry};
+use sitemap::writer::{LastModFormat, Newline, SiteMapWriter, WriterOptions};
+use std::fs;
+use url::Url;
+
+fn urls() -> Vec<UrlEntry> {
+    let mut image = ImageEntry::with_loc(Url::parse("http://www.example.com/fish.jpg").unwrap());
+    image.caption = Some("Fish & <chips>".to_string());
+    let alternate = Alternate::new("de", Url::parse("http://www.example.com/de/").unwrap());
+    let mut rfc2822 = UrlEntry::builder().loc("http://www.example.com/c").build().expect("valid");
+    rfc2822.lastmod = LastMod::from("Mon, 01 Jan 2024 10:00:00 +0200".to_string());
+    vec![UrlEntry::builder()
+             .loc("http://www.example.com/")
+             .lastmod(DateTime::parse_from_rfc3339("2024-01-05T10:20:30+02:00").unwrap())
+             .changefreq(ChangeFreq::Daily)
+             .priority(0.85)
+             .alternate(alternate)
+             .image(image)
+             .build()
+             .expect("valid"),
+         UrlEntry::builder()
+             .loc("http://www.example.com/a?b=1&c=2")
+             .lastmod(DateTime::parse_from_rfc3339("2024-01-05T23:59:59.250-05:00").unwrap())
+             .priority(1.0 / 3.0)
+             .build()
+             .expect("valid"),
+         UrlEntry::builder()
+             .loc("http://www.example.com/b")
+             .lastmod_date(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap())
+             .build()
+             .expect("valid"),
+         rfc2822]
+}
+
+/// Urlset and index written with `options`, the urlset attributes added in
+/// the order of `attributes`.
+fn write(options: WriterOptions, attributes: &[(&str, &str)]) -> (String, String) {
+    let mut url_writer = SiteMapWriter::with_options(Vec::new(), options)
+        .start_urlset()
+        .and_then(|writer| writer.namespace("xsi", "http://www.w3.org/2001/XMLSchema-instance"))
+        .expect("start urlset");
+    for &(name, value) in attributes {
+        url_writer = url_writer.attribute(name, value).expect("valid attribute");
+    }
+    for url in urls() {
+        url_writer.url(url).expect("write url");
+    }
+    let (urlset, sitemap_writer) = url_writer.end_urlset().expect("end urlset").rotate(Vec::new());
+    let mut index_writer = sitemap_writer.start_sitemapindex().expect("start index");
+    let sitemap = SiteMapEntry::builder()
+        .loc("http://www.example.com/sitemap.xml")
+        .lastmod(DateTime::parse_from_rfc3339("2024-01-05T10:20:30+02:00").unwrap())
+        .build()
+        .expect("valid");
+    index_writer.sitemap(sitemap).expect("write sitemap");
+    let index = index_writer.end().expect("end index");
+    (String::from_utf8(urlset).unwrap(), String::from_utf8(index).unwrap())
+}
+
+#[test]
+fn test_write_deterministic() {
+    let attributes = [("xsi:schemaLocation", "http://www.sitemaps.org/schemas/sitemap/0.9"), ("id", "main")];
+    let options = WriterOptions { deterministic: true, ..WriterOptions::default() };
+    let output = write(options, &attributes);
+    assert_eq!(output.0, fs::read_to_string("tests/documents/written_deterministic.xml").unwrap());
+    assert_eq!(output.1, fs::read_to_string("tests/documents/written_deterministic_index.xml").unwrap());
+
+    let reversed = [attributes[1], attributes[0]];
+    assert_eq!(write(options, &reversed), output);
+    let crlf = WriterOptions { newline: Newline::CrLf, ..options };
+    assert_eq!(write(crlf, &attributes), output);
+
+    // only the as-is lastmod format is normalized
+    let date_only = WriterOptions { lastmod_format: LastModFormat::DateOnly, ..options };
+    let (output, _) = write(date_only, &attributes);
+    assert!(output.contains("<lastmod>2024-01-05</lastmod>"), "{}", output);
+    assert!(output.contains("<lastmod>2024-01-06</lastmod>"), "{}", output);
+    assert!(output.contains("<lastmod>2024-01-01</lastmod>"), "{}", output);
+
+    let (output, _) = write(WriterOptions::default(), &attributes);
+    assert!(output.contains("<lastmod>2024-01-05T10:20:30+02:00</lastmod>"), "{}", output);
+    assert!(output.contains("<lastmod>2024-01-01T10:00:00+02:00</lastmod>"), "{}", output);
+    assert!(output.contains("sitemap/0.9\" id=\"main\">"), "{}", output);
+}
@@ -0,0 +1,85 @@
+extern crate sitemap;
+extern crate chrono;
+extern crate url;
+
+use sitemap::structs::{LastMod, Location, NewsEntry, NewsPublication, Priority, UrlEntry, VideoDuration, VideoEntry};
+use sitemap::writer::{self, Indent, LastModFormat, WriterOptions};
+use chrono::{DateTime, NaiveDate};
+use std::fs;
+use std::time::Duration;
+use url::Url;
+
+fn urls() -> Vec<UrlEntry> {
+    let times = ["2024-01-01T10:20:30+02:00",
+                 "2024-02-29T23:59:59.5-05:30",
+                 "2024-03-01T00:00:00.123456Z",
+                 "1999-12-31T23:59:60+00:00"];
+    let priorities = [0.0, 0.05, 0.5, 0.95, 0.123456, 0.996, 1.0, 1.5, -1.0];
+    let mut urls = Vec::new();
+    for (i, priority) in priorities.iter().enumerate() {
+        let mut url = UrlEntry::builder()
+            .loc(format!("http://www.example.com/{}", i))
+            .build()
+            .expect("valid");
+        url.priority = Priority::Value(*priority);
+        url.lastmod = match times.get(i) {
+            Some(time) => LastMod::DateTime(DateTime::parse_from_rfc3339(time).unwrap()),
+            None => LastMod::Date(NaiveDate::from_ymd_opt(2024, 1, i as u32).unwrap()),
+        };
+        urls.push(url);
+    }
+    let mut video = VideoEntry::new();
+    video.thumbnail_loc = Location::Url(Url::parse("http://www.example.com/thumb.jpg").unwrap());
+    video.title = Some("Video".to_string());
+    video.description = Some("A video".to_string());
+    video.content_loc = Location::Url(Url::parse("http://www.example.com/video.mp4").unwrap());
+    video.duration = VideoDuration::Value(Duration::from_secs(600));
+    video.publication_date = LastMod::DateTime(DateTime::parse_from_rfc3339(times[1]).unwrap());
+    video.expiration_date = LastMod::Date(NaiveDate::from_ymd_opt(2030, 1, 1).unwrap());
+    let publication = NewsPublication {
+        name: "The Example Times".to_string(),
+        language: "en".to_string(),
+    };
+    let news = NewsEntry::new(publication, DateTime::parse_from_rfc3339(times[2]).unwrap(), "News".to_string());
+    urls.push(UrlEntry::builder()
+        .loc("http://www.example.com/media")
+        .video(video)
+        .news(news)
+        .build()
+        .expect("valid"));
+    urls
+}
+
+/// Documents written with the lastmod formats and priority decimals, one
+/// after the other.
+fn documents() -> String {
+    let options = [WriterOptions::default(),
+                   WriterOptions {
+                       indent: Indent::None,
+                       lastmod_format: LastModFormat::DateOnly,
+                       priority_decimals: 2,
+                       ..WriterOptions::default()
+                   },
+                   WriterOptions {
+                       lastmod_format: LastModFormat::SecondsUtc,
+                       priority_decimals: 0,
+                       omit_default_priority: true,
+                       ..WriterOptions::default()
+                   },
+                   WriterOptions {
+                       deterministic: true,
+                       priority_decimals: 3,
+                       ..WriterOptions::default()
+                   }];
+    let mut documents = String::new();
+    for options in &options {
+        documents.push_str(&writer::urlset_to_string(urls(), *options).expect("write urlset"));
+        documents.push('\n');
+    }
+    documents
+}
+
+#[test]
+fn test_write_formats() {
+    assert_eq!(documents(), fs::read_to_string("tests/documents/written_formats.txt").unwrap());
+}
@@ -0,0 +1,80 @@
+extern crate sitemap;
+extern crate url;
+
+use sitemap::reader::{SiteMapEvent, SiteMapReader};
+use sitemap::structs::{ImageEntry, SiteMapEntry, UrlEntry};
+use sitemap::writer::{self, Indent, LimitPolicy, Newline, SiteMapWriter, WriterOptions};
+use std::fs;
+use std::io;
+use url::Url;
+
+fn urls() -> Vec<UrlEntry> {
+    let mut image = ImageEntry::with_loc(Url::parse("http://www.example.com/fish.jpg").unwrap());
+    image.caption = Some("Fish & <chips>".to_string());
+    vec![UrlEntry::builder()
+             .loc("http://www.example.com/index.html?a=1&b=2")
+             .priority(0.8)
+             .image(image)
+             .build()
+             .expect("valid"),
+         UrlEntry::builder().loc("http://www.example.com/other.html").build().expect("valid")]
+}
+
+fn options(indent: Indent, newline: Newline) -> WriterOptions {
+    WriterOptions { indent, newline, ..WriterOptions::default() }
+}
+
+/// Checks the urlset written with `indent` and `newline` against the
+/// document `name`.
+fn check(indent: Indent, newline: Newline, name: &str) {
+    let output = writer::urlset_to_vec(urls(), options(indent, newline)).expect("write urlset");
+    let expected = fs::read(format!("tests/documents/{}", name)).unwrap();
+    assert_eq!(String::from_utf8(output.clone()).unwrap(), String::from_utf8(expected).unwrap());
+    let read: Vec<UrlEntry> = SiteMapReader::new(&output[..])
+        .filter_map(|event| match event {
+            Ok(SiteMapEvent::Url(url)) => Some(url),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(read, urls());
+}
+
+#[test]
+fn test_write_indent() {
+    check(Indent::Spaces(2), Newline::Lf, "written_spaces2_lf.xml");
+    check(Indent::Spaces(4), Newline::Lf, "written_spaces4_lf.xml");
+    check(Indent::Tabs, Newline::Lf, "written_tabs_lf.xml");
+    check(Indent::Spaces(2), Newline::CrLf, "written_spaces2_crlf.xml");
+    check(Indent::Spaces(4), Newline::CrLf, "written_spaces4_crlf.xml");
+    check(Indent::Tabs, Newline::CrLf, "written_tabs_crlf.xml");
+    check(Indent::None, Newline::Lf, "written_compact.xml");
+    check(Indent::None, Newline::CrLf, "written_compact.xml");
+    assert_eq!(WriterOptions::default().indent, Indent::Spaces(2));
+    assert_eq!(WriterOptions::default().newline, Newline::Lf);
+}
+
+#[test]
+fn test_write_compact_index() {
+    let sitemap = SiteMapEntry::builder().loc("http://www.example.com/sitemap.xml?a=1&b=2").build().expect("valid");
+    let output = writer::sitemapindex_to_string(vec![sitemap], options(Indent::None, Newline::Lf))
+        .expect("write index");
+    assert_eq!(output,
+               "<?xml version=\"1.0\" encoding=\"UTF-8\"?><sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\"><sitemap>\
+                <loc>http://www.example.com/sitemap.xml?a=1&amp;b=2</loc></sitemap></sitemapindex>");
+}
+
+#[test]
+fn test_size_limit_with_indent() {
+    for &(indent, newline) in &[(Indent::None, Newline::Lf), (Indent::Tabs, Newline::CrLf)] {
+        let size = writer::urlset_to_vec(urls(), options(indent, newline)).expect("write urlset").len() as u64;
+        for &(max_bytes, fits) in &[(size, true), (size - 1, false)] {
+            let mut url_writer = SiteMapWriter::with_options(io::sink(), options(indent, newline))
+                .start_urlset()
+                .expect("start urlset")
+                .limit_policy(LimitPolicy::Error)
+                .max_bytes(max_bytes);
+            let results: Vec<bool> = urls().into_iter().map(|url| url_writer.url(url).is_ok()).collect();
+            assert_eq!(results, [true, fits]);
+        }
+    }
+}
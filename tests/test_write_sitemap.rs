@@ -1,13 +1,21 @@
 extern crate sitemap;
 extern crate chrono;
+extern crate url;
 
-use sitemap::writer::SiteMapWriter;
-use sitemap::structs::{UrlEntry, ChangeFreq, SiteMapEntry};
-use chrono::{DateTime, NaiveDate, FixedOffset};
+use sitemap::Error;
+use sitemap::util::LocOptions;
+use sitemap::reader::{ReaderOptions, SiteMapEvent, SiteMapReader};
+use sitemap::writer::{self, Declaration, DedupPolicy, LastModFormat, SiteMapIndexWriter, SiteMapWriter, WriterOptions};
+use sitemap::structs::{UrlEntry, ChangeFreq, LastMod, SiteMapEntry, Location, Priority};
+use sitemap::structs::{ImageEntry, News, NewsEntry, NewsPublication};
+use chrono::{DateTime, NaiveDate};
+use std::path::PathBuf;
+use std::time::{Duration, UNIX_EPOCH};
+use url::Url;
 
 static CONTENT: &str =
-    "<?xml version=\"1.0\" encoding=\"utf-8\"?>
-<urlset>
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">
   <url>
     \
      <loc>http://www.example.com/index.html</loc>
@@ -15,7 +23,7 @@ static CONTENT: &str =
      <lastmod>2016-07-08T09:10:11+00:00</lastmod>
     <changefreq>daily</changefreq>
     \
-     <lastmod>0.2</lastmod>
+     <priority>0.2</priority>
   </url>
   <url>
     <loc>http://www.example.com/other.html</loc>
@@ -23,10 +31,13 @@ static CONTENT: &str =
      <lastmod>2016-07-18T09:10:11+00:00</lastmod>
     <changefreq>monthly</changefreq>
     \
-     <lastmod>0.1</lastmod>
+     <priority>0.1</priority>
   </url>
-</urlset>
-<sitemapindex>
+</urlset>";
+
+static INDEX_CONTENT: &str =
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">
   <sitemap>
     \
      <loc>http://www.example.com/other_sitemap.xml</loc>
@@ -39,59 +50,669 @@ static CONTENT: &str =
 #[test]
 fn test_write_sitemap() {
     let mut output = Vec::<u8>::new();
+    let mut index_output = Vec::<u8>::new();
     {
         let sitemap_writer = SiteMapWriter::new(&mut output);
         let mut urlwriter = sitemap_writer.start_urlset().expect("can write the file");
-        let date = DateTime::from_utc(NaiveDate::from_ymd(2016, 7, 8).and_hms(9, 10, 11),
-                                      FixedOffset::east(0));
+        let date = DateTime::parse_from_rfc3339("2016-07-08T09:10:11+00:00").unwrap();
         let url_entry = UrlEntry::builder()
-            .loc("http://www.example.com/index.html".into())
-            .expect("is valid")
+            .loc("http://www.example.com/index.html")
             .changefreq(ChangeFreq::Daily)
-            .expect("is valid")
             .priority(0.2)
-            .expect("is valid")
             .lastmod(date)
-            .expect("is valid")
             .build()
             .expect("valid");
         urlwriter.url(url_entry).expect("can write the file");
-        let date1 = DateTime::from_utc(NaiveDate::from_ymd(2016, 7, 18).and_hms(9, 10, 11),
-                                       FixedOffset::east(0));
+        let date1 = DateTime::parse_from_rfc3339("2016-07-18T09:10:11+00:00").unwrap();
         let url_entry = UrlEntry::builder()
-            .loc("http://www.example.com/other.html".into())
-            .expect("is valid")
+            .loc("http://www.example.com/other.html")
             .changefreq(ChangeFreq::Monthly)
-            .expect("is valid")
             .priority(0.1)
-            .expect("is valid")
             .lastmod(date1)
-            .expect("is valid")
             .build()
             .expect("valid");
         urlwriter.url(url_entry).expect("can write the file");
-        let sitemap_writer = urlwriter.end().expect("close the urlset block");
+        let sitemap_writer = urlwriter.end_urlset().expect("close the urlset block");
+        let (_, sitemap_writer) = sitemap_writer.rotate(&mut index_output);
 
         let mut sitemap_index_writer = sitemap_writer.start_sitemapindex()
             .expect("start sitemap index tag");
         let sitemap_entry = SiteMapEntry::builder()
-            .loc("http://www.example.com/other_sitemap.xml".into())
-            .expect("is valid")
+            .loc("http://www.example.com/other_sitemap.xml")
             .lastmod(date1)
-            .expect("is valid")
             .build()
             .expect("valid");
         sitemap_index_writer.sitemap(sitemap_entry).expect("can write the file");
         sitemap_index_writer.end().expect("close sitemap block");
     }
     assert_eq!(output, CONTENT.as_bytes());
+    assert_eq!(index_output, INDEX_CONTENT.as_bytes());
 }
 
+#[test]
+fn test_write_second_root() {
+    let sitemap_writer = SiteMapWriter::new(Vec::new())
+        .start_urlset()
+        .and_then(|url_writer| url_writer.end_urlset())
+        .expect("write urlset");
+    match sitemap_writer.start_sitemapindex() {
+        Err(Error::DocumentEnded) => {}
+        other => panic!("unexpected result {:?}", other.map(|_| ())),
+    }
+
+    let options = WriterOptions { priority_decimals: 2, ..WriterOptions::default() };
+    let mut sitemap_writer = SiteMapWriter::with_options(Vec::new(), options).with_stylesheet("/sitemap.xsl");
+    let mut files = Vec::new();
+    for number in 0..2 {
+        let mut url_writer = sitemap_writer.start_urlset().expect("start urlset");
+        let url_entry = UrlEntry::builder()
+            .loc(format!("http://www.example.com/{}", number).as_str())
+            .priority(0.25)
+            .build()
+            .expect("valid");
+        url_writer.url(url_entry).expect("write url");
+        let (file, next) = url_writer.end_urlset().expect("end urlset").rotate(Vec::new());
+        files.push(String::from_utf8(file).unwrap());
+        sitemap_writer = next;
+    }
+    assert_eq!(sitemap_writer.options().priority_decimals, 2);
+    let index = sitemap_writer.start_sitemapindex().and_then(|writer| writer.end()).expect("write index");
+    for file in &files {
+        assert!(file.contains("<?xml-stylesheet type=\"text/xsl\" href=\"/sitemap.xsl\"?>"));
+        assert!(file.contains("<priority>0.25</priority>"));
+    }
+    assert!(files[1].contains("http://www.example.com/1"));
+    assert!(String::from_utf8(index).unwrap().contains("href=\"/sitemap.xsl\"?>\n<sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" />"));
+}
+
+
+/// Lastmod elements written with `format` for a time, a date and an index entry.
+fn lastmods(format: LastModFormat) -> Vec<String> {
+    let options = WriterOptions { lastmod_format: format, ..WriterOptions::default() };
+    let time = DateTime::parse_from_rfc3339("2024-01-01T01:00:00.250+02:00").unwrap();
+    let date = NaiveDate::from_ymd_opt(2024, 1, 5).unwrap();
+    let mut url_writer = SiteMapWriter::with_options(Vec::new(), options)
+        .start_urlset()
+        .expect("start urlset");
+    for url_entry in [UrlEntry::builder().loc("http://www.example.com/").lastmod(time),
+                      UrlEntry::builder().loc("http://www.example.com/a").lastmod_date(date),
+                      UrlEntry::builder().loc("http://www.example.com/b")] {
+        url_writer.url(url_entry.build().expect("valid")).expect("write url");
+    }
+    let (mut output, index_writer) = url_writer.end_urlset().expect("end urlset").rotate(Vec::new());
+    let mut index_writer = index_writer.start_sitemapindex().expect("start index");
+    let sitemap_entry = SiteMapEntry::builder()
+        .loc("http://www.example.com/sitemap.xml")
+        .lastmod(time)
+        .build()
+        .expect("valid");
+    index_writer.sitemap(sitemap_entry).expect("write sitemap");
+    output.extend(index_writer.end().expect("end index"));
+    String::from_utf8(output)
+        .unwrap()
+        .split("<lastmod>")
+        .skip(1)
+        .map(|part| part[..part.find("</lastmod>").unwrap()].to_string())
+        .collect()
+}
+
+fn year(lastmod: &LastMod) -> String {
+    lastmod.get_date().unwrap().format("%Y").to_string()
+}
+
+#[test]
+fn test_write_lastmod_format() {
+    assert_eq!(lastmods(LastModFormat::AsIs),
+               ["2024-01-01T01:00:00.250+02:00", "2024-01-05", "2024-01-01T01:00:00.250+02:00"]);
+    assert_eq!(lastmods(LastModFormat::DateOnly), ["2023-12-31", "2024-01-05", "2023-12-31"]);
+    assert_eq!(lastmods(LastModFormat::SecondsUtc),
+               ["2023-12-31T23:00:00+00:00", "2024-01-05T00:00:00+00:00", "2023-12-31T23:00:00+00:00"]);
+    assert_eq!(lastmods(LastModFormat::Custom(year)), ["2024", "2024", "2024"]);
+    assert_eq!(lastmods(WriterOptions::default().lastmod_format), lastmods(LastModFormat::AsIs));
+}
+
+/// Priority elements written with `options`, one url for each priority.
+fn priorities(options: WriterOptions, priorities: &[f32]) -> Vec<String> {
+    let mut output = Vec::<u8>::new();
+    {
+        let mut url_writer = SiteMapWriter::with_options(&mut output, options)
+            .start_urlset()
+            .expect("start urlset");
+        for &priority in priorities {
+            let mut url_entry = UrlEntry::builder().loc("http://www.example.com/").build().expect("valid");
+            url_entry.priority = Priority::Value(priority);
+            url_writer.url(url_entry).expect("write url");
+        }
+        url_writer.end().expect("end urlset");
+    }
+    String::from_utf8(output)
+        .unwrap()
+        .split("<priority>")
+        .skip(1)
+        .map(|part| part[..part.find("</priority>").unwrap()].to_string())
+        .collect()
+}
+
+#[test]
+fn test_write_priority_format() {
+    let options = WriterOptions::default();
+    assert_eq!(priorities(options, &[0.1 + 0.2, 1.0, 0.0, 0.25, 0.35, 0.05, 0.95, 1.5, -0.1]),
+               ["0.3", "1.0", "0.0", "0.3", "0.4", "0.1", "1.0", "1.0", "0.0"]);
+    let options = WriterOptions { priority_decimals: 2, ..WriterOptions::default() };
+    assert_eq!(priorities(options, &[0.2, 0.125, 0.999, 0.0000001]), ["0.20", "0.13", "1.00", "0.00"]);
+    let options = WriterOptions { priority_decimals: 0, ..WriterOptions::default() };
+    assert_eq!(priorities(options, &[0.5, 0.49, 1.0]), ["1", "0", "1"]);
+
+    let options = WriterOptions { omit_default_priority: true, ..WriterOptions::default() };
+    assert_eq!(priorities(options, &[0.5, 0.54, 0.55, 0.8]), ["0.6", "0.8"]);
+    assert_eq!(priorities(WriterOptions::default(), &[0.5, f32::NAN]), ["0.5"]);
+}
+
+#[test]
+fn test_write_returns_stream() {
+    let mut url_writer = SiteMapWriter::new(Vec::new()).start_urlset().expect("start urlset");
+    assert_eq!(url_writer.bytes_written(), 0);
+    let url_entry = UrlEntry::builder().loc("http://www.example.com/").build().expect("valid");
+    url_writer.url(url_entry).expect("write url");
+    let written = url_writer.bytes_written();
+    assert!(written > 0);
+    let output = url_writer.end().expect("end urlset");
+    assert!(output.len() as u64 > written);
+    assert!(String::from_utf8(output).unwrap().ends_with("</urlset>"));
+
+    let mut index_writer = SiteMapWriter::new(Vec::new()).start_sitemapindex().expect("start index");
+    let sitemap_entry = SiteMapEntry::builder()
+        .loc("http://www.example.com/sitemap.xml")
+        .build()
+        .expect("valid");
+    index_writer.sitemap(sitemap_entry).expect("write sitemap");
+    let written = index_writer.bytes_written();
+    let output = index_writer.end().expect("end index");
+    assert_eq!(output.len() as u64, written + "\n</sitemapindex>".len() as u64);
+
+    let mut output = Vec::new();
+    let sitemap_writer = SiteMapWriter::new(&mut output)
+        .start_urlset()
+        .expect("start urlset")
+        .end_urlset()
+        .expect("end urlset");
+    let written = sitemap_writer.bytes_written();
+    sitemap_writer.into_inner();
+    assert_eq!(output.len() as u64, written);
+}
+
+#[test]
+fn test_write_to_string() {
+    let options = WriterOptions {
+        lastmod_format: LastModFormat::DateOnly,
+        priority_decimals: 2,
+        ..WriterOptions::default()
+    };
+    let date = DateTime::parse_from_rfc3339("2016-07-08T09:10:11+00:00").unwrap();
+    let urls = vec![UrlEntry::builder()
+                        .loc("http://www.example.com/index.html?a=1&b=2")
+                        .lastmod(date)
+                        .priority(0.2)
+                        .build()
+                        .expect("valid"),
+                    UrlEntry::builder().loc("http://www.example.com/other.html").build().expect("valid")];
+    let mut url_writer = SiteMapWriter::with_options(Vec::new(), options).start_urlset().expect("start urlset");
+    for url in urls.clone() {
+        url_writer.url(url).expect("write url");
+    }
+    let expected = url_writer.end().expect("end urlset");
+    assert_eq!(writer::urlset_to_vec(urls.clone(), options).expect("write urlset"), expected);
+    let output = writer::urlset_to_string(urls, options).expect("write urlset");
+    assert_eq!(output.as_bytes(), &expected[..]);
+    assert!(output.contains("<lastmod>2016-07-08</lastmod>"));
+    assert!(output.contains("<priority>0.20</priority>"));
+
+    let sitemaps = vec![SiteMapEntry::builder()
+                            .loc("http://www.example.com/sitemap.xml")
+                            .lastmod(date)
+                            .build()
+                            .expect("valid")];
+    let mut index_writer = SiteMapWriter::with_options(Vec::new(), options)
+        .start_sitemapindex()
+        .expect("start index");
+    for sitemap in sitemaps.clone() {
+        index_writer.sitemap(sitemap).expect("write sitemap");
+    }
+    let expected = index_writer.end().expect("end index");
+    assert_eq!(writer::sitemapindex_to_vec(sitemaps.clone(), options).expect("write index"), expected);
+    let output = writer::sitemapindex_to_string(sitemaps, options).expect("write index");
+    assert_eq!(output.as_bytes(), &expected[..]);
+
+    let empty = writer::urlset_to_string(Vec::new(), WriterOptions::default()).expect("write urlset");
+    assert_eq!(empty, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" />");
+}
+
+#[test]
+fn test_write_strict_read_back() {
+    let options = ReaderOptions { strict: true, ..ReaderOptions::default() };
+    let url = UrlEntry::builder().loc("http://www.example.com/").build().expect("valid");
+    let urlset = writer::urlset_to_string(vec![url], WriterOptions::default()).expect("write urlset");
+    let events: Vec<_> = SiteMapReader::with_options(urlset.as_bytes(), options).collect();
+    assert!(matches!(events[..], [Ok(SiteMapEvent::Url(_))]), "{:?}", events);
+    let sitemap = SiteMapEntry::builder().loc("http://www.example.com/sitemap.xml").build().expect("valid");
+    let index = writer::sitemapindex_to_string(vec![sitemap], WriterOptions::default()).expect("write index");
+    assert!(index.contains("<sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">"), "{}", index);
+    let events: Vec<_> = SiteMapReader::with_options(index.as_bytes(), options).collect();
+    assert!(matches!(events[..], [Ok(SiteMapEvent::SiteMap(_))]), "{:?}", events);
+}
+
+/// Urlset of a url with a non ASCII title written with `declaration`.
+fn declared(declaration: Declaration) -> Result<String, Error> {
+    let options = WriterOptions { xml_declaration: declaration, ..WriterOptions::default() };
+    let mut url = UrlEntry::builder().loc("http://www.example.com/caf%C3%A9").build().expect("valid");
+    url.news = News::Entry(Box::new(NewsEntry {
+        publication: NewsPublication {
+            name: "Café".to_string(),
+            language: "fr".to_string(),
+        },
+        publication_date: LastMod::None,
+        title: "Crème & brûlée".to_string(),
+    }));
+    writer::urlset_to_string(vec![url], options)
+}
+
+#[test]
+fn test_write_declaration() {
+    let default = declared(Declaration::Default).expect("write urlset");
+    assert!(default.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset"));
+    assert!(default.contains("<news:title>Crème &amp; brûlée</news:title>"));
+
+    let none = declared(Declaration::None).expect("write urlset");
+    assert!(none.starts_with("<urlset"));
+    assert_eq!(none, default.split_once('\n').unwrap().1);
+
+    let custom = Declaration::Custom {
+        encoding: "ISO-8859-1",
+        standalone: Some(true),
+    };
+    let latin1 = declared(custom).expect("write urlset");
+    assert!(latin1.starts_with("<?xml version=\"1.0\" encoding=\"ISO-8859-1\" standalone=\"yes\"?>\n<urlset"));
+    assert!(latin1.is_ascii());
+    assert!(latin1.contains("<news:name>Caf&#xE9;</news:name>"));
+    assert!(latin1.contains("<news:title>Cr&#xE8;me &amp; br&#xFB;l&#xE9;e</news:title>"));
+
+    let utf8 = Declaration::Custom {
+        encoding: "utf-8",
+        standalone: None,
+    };
+    let utf8 = declared(utf8).expect("write urlset");
+    assert!(utf8.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<urlset"));
+    assert!(utf8.contains("Crème"));
+
+    for &(encoding, message) in &[("8859-1", "invalid encoding name"),
+                                  ("ISO 8859", "invalid encoding name"),
+                                  ("", "invalid encoding name"),
+                                  ("UTF-16LE", "encoding not compatible with ASCII")] {
+        let declaration = Declaration::Custom {
+            encoding,
+            standalone: None,
+        };
+        match declared(declaration) {
+            Err(Error::InvalidValue { ref tag, message: ref error, .. }) => {
+                assert_eq!(tag, "encoding");
+                assert_eq!(error, message);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        let options = WriterOptions { xml_declaration: declaration, ..WriterOptions::default() };
+        assert!(writer::sitemapindex_to_vec(Vec::new(), options).is_err());
+    }
+
+    let index = writer::sitemapindex_to_string(Vec::new(), WriterOptions { xml_declaration: custom, ..WriterOptions::default() })
+        .expect("write index");
+    assert_eq!(index, "<?xml version=\"1.0\" encoding=\"ISO-8859-1\" standalone=\"yes\"?>\n<sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" />");
+}
+
+#[test]
+fn test_write_urlset_namespaces() {
+    let image = ImageEntry::with_loc(Url::parse("http://www.example.com/image.jpg").unwrap());
+    let url = UrlEntry::builder().loc("http://www.example.com/").image(image).build().expect("valid");
+    let mut url_writer = SiteMapWriter::new(Vec::new())
+        .start_urlset()
+        .and_then(|writer| writer.namespace("xsi", "http://www.w3.org/2001/XMLSchema-instance"))
+        .and_then(|writer| writer.namespace("vendor", "http://www.example.com/vendor"))
+        .and_then(|writer| {
+            writer.attribute("xsi:schemaLocation",
+                             "http://www.sitemaps.org/schemas/sitemap/0.9 \
+                              http://www.sitemaps.org/schemas/sitemap/0.9/sitemap.xsd")
+        })
+        .and_then(|writer| writer.attribute("vendor:id", "a&b"))
+        .expect("valid declarations");
+    url_writer.url(url.clone()).expect("write url");
+    let output = String::from_utf8(url_writer.end().expect("end urlset")).unwrap();
+    assert!(output.contains("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" \
+                             xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\" \
+                             xmlns:vendor=\"http://www.example.com/vendor\" \
+                             xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+                             xsi:schemaLocation=\"http://www.sitemaps.org/schemas/sitemap/0.9 \
+                             http://www.sitemaps.org/schemas/sitemap/0.9/sitemap.xsd\" \
+                             vendor:id=\"a&amp;b\">"));
+    assert!(output.contains("<image:image>"));
+
+    // a prefix declared by the caller wins over the extension one
+    let mut url_writer = SiteMapWriter::new(Vec::new())
+        .start_urlset()
+        .and_then(|writer| writer.namespace("image", "http://www.example.com/image"))
+        .expect("valid declarations");
+    url_writer.url(url).expect("write url");
+    let output = String::from_utf8(url_writer.end().expect("end urlset")).unwrap();
+    assert!(output.contains("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" \
+                             xmlns:image=\"http://www.example.com/image\">"));
+    assert!(output.contains("<image:image xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\">"));
+
+    let url_writer = || SiteMapWriter::new(Vec::new()).start_urlset().expect("start urlset");
+    for prefix in ["", "xmlns", "xml", "1a", "a:b"] {
+        assert!(url_writer().namespace(prefix, "http://www.example.com/").is_err(), "{}", prefix);
+    }
+    for name in ["xmlns", "xsi:schemaLocation", "1a", "a:", ""] {
+        assert!(url_writer().attribute(name, "value").is_err(), "{}", name);
+    }
+    let mut started = url_writer();
+    started.url(UrlEntry::builder().loc("http://www.example.com/").build().expect("valid")).expect("write url");
+    assert!(started.namespace("vendor", "http://www.example.com/vendor").is_err());
+}
+
+/// A valid url, a url without location and a url with an invalid one.
+fn urls_with_invalid_loc() -> Vec<UrlEntry> {
+    let mut invalid = UrlEntry::new();
+    invalid.loc = Location::from("not a url".to_string());
+    assert!(matches!(invalid.loc, Location::Err { .. }));
+    vec![UrlEntry::builder().loc("http://www.example.com/").build().expect("valid"), UrlEntry::new(), invalid]
+}
+
+#[test]
+fn test_write_invalid_entry() {
+    let image = ImageEntry::with_loc(Url::parse("http://www.example.com/image.jpg").unwrap());
+    let mut url_writer = SiteMapWriter::new(Vec::new()).start_urlset().expect("start urlset");
+    url_writer.url(UrlEntry::builder().loc("http://www.example.com/").build().expect("valid")).expect("write url");
+    let mut url_entry = UrlEntry::builder().loc("http://www.example.com/images").build().expect("valid");
+    url_entry.images = vec![image; 1001];
+    match url_writer.url(url_entry) {
+        Err(error @ Error::InvalidEntry { index: 1, field: "image", .. }) => {
+            assert_eq!(error.to_string(), "Entry 1 has an invalid image: 1001 images, a url can contain at most 1000");
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    match url_writer.namespace("vendor", "http://www.example.com/vendor") {
+        Err(error @ Error::State(_)) => {
+            assert_eq!(error.to_string(), "Invalid writer state: namespace declared after the first url");
+        }
+        other => panic!("unexpected result {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_write_missing_loc() {
+    for (index, url) in urls_with_invalid_loc().into_iter().enumerate().skip(1) {
+        let mut url_writer = SiteMapWriter::new(Vec::new()).start_urlset().expect("start urlset");
+        url_writer.url(urls_with_invalid_loc()[0].clone()).expect("write url");
+        match url_writer.url(url.clone()) {
+            Err(Error::MissingLoc { index: 1 }) => {}
+            other => panic!("unexpected result {:?} for entry {}", other, index),
+        }
+        assert_eq!(url_writer.skipped(), 0);
+    }
+    assert_eq!(writer::urlset_to_vec(urls_with_invalid_loc(), WriterOptions::default())
+                   .unwrap_err()
+                   .to_string(),
+               "Entry 1 without a valid loc");
+
+    let options = WriterOptions { skip_invalid: true, ..WriterOptions::default() };
+    let mut url_writer = SiteMapWriter::with_options(Vec::new(), options).start_urlset().expect("start urlset");
+    for url in urls_with_invalid_loc() {
+        url_writer.url(url).expect("write url");
+    }
+    assert_eq!(url_writer.skipped(), 2);
+    let output = url_writer.end().expect("end urlset");
+    assert_eq!(output,
+               writer::urlset_to_vec(urls_with_invalid_loc().into_iter().take(1), WriterOptions::default())
+                   .expect("write urlset"));
+
+    let sitemaps = || {
+        vec![SiteMapEntry::new(),
+             SiteMapEntry::builder().loc("http://www.example.com/sitemap.xml").build().expect("valid"),
+             SiteMapEntry::new()]
+    };
+    let mut index_writer = SiteMapWriter::new(Vec::new()).start_sitemapindex().expect("start index");
+    assert!(matches!(index_writer.sitemap(sitemaps()[0].clone()), Err(Error::MissingLoc { index: 0 })));
+    index_writer.sitemap(sitemaps()[1].clone()).expect("write sitemap");
+    assert!(matches!(index_writer.sitemap(sitemaps()[2].clone()), Err(Error::MissingLoc { index: 1 })));
+    let mut index_writer = SiteMapWriter::with_options(Vec::new(), options)
+        .start_sitemapindex()
+        .expect("start index");
+    for sitemap in sitemaps() {
+        index_writer.sitemap(sitemap).expect("write sitemap");
+    }
+    assert_eq!(index_writer.skipped(), 2);
+    let output = String::from_utf8(index_writer.end().expect("end index")).unwrap();
+    assert_eq!(output.matches("<sitemap>").count(), 1);
+}
+
+/// Locations of the urls written with `options`, the number of duplicates
+/// skipped and the first error.
+fn dedup(options: WriterOptions, locs: &[&str]) -> (Vec<String>, usize, Option<Error>) {
+    let mut url_writer = SiteMapWriter::with_options(Vec::new(), options).start_urlset().expect("start urlset");
+    let mut error = None;
+    for loc in locs {
+        if let Err(url_error) = url_writer.url(UrlEntry::builder().loc(*loc).build().expect("valid")) {
+            error.get_or_insert(url_error);
+        }
+    }
+    let skipped = url_writer.duplicates_skipped();
+    let sitemap_writer = url_writer.end_urlset().expect("end urlset");
+    assert_eq!(sitemap_writer.duplicates_skipped(), skipped);
+    let output = String::from_utf8(sitemap_writer.into_inner()).unwrap();
+    let written = output.split("<loc>")
+        .skip(1)
+        .map(|part| part[..part.find("</loc>").unwrap()].to_string())
+        .collect();
+    (written, skipped, error)
+}
+
+#[test]
+fn test_write_dedup() {
+    let locs = ["http://www.example.com/a",
+                "http://www.example.com/b",
+                "http://WWW.example.com/a",
+                "http://www.example.com/%62",
+                "http://www.example.com/b#top"];
+    let (written, skipped, error) = dedup(WriterOptions::default(), &locs);
+    assert_eq!(written.len(), 5);
+    assert_eq!(skipped, 0);
+    assert!(error.is_none());
+
+    let options = WriterOptions { dedup: DedupPolicy::SkipAndCount, ..WriterOptions::default() };
+    let (written, skipped, error) = dedup(options, &locs);
+    assert_eq!(written, ["http://www.example.com/a", "http://www.example.com/b", "http://www.example.com/b#top"]);
+    assert_eq!(skipped, 2);
+    assert!(error.is_none());
+
+    let options = WriterOptions {
+        dedup: DedupPolicy::SkipSilently,
+        dedup_loc: LocOptions { ignore_fragment: true, ..LocOptions::default() },
+        ..WriterOptions::default()
+    };
+    let (written, skipped, error) = dedup(options, &locs);
+    assert_eq!(written, ["http://www.example.com/a", "http://www.example.com/b"]);
+    assert_eq!(skipped, 0);
+    assert!(error.is_none());
+
+    let options = WriterOptions { dedup: DedupPolicy::Error, ..WriterOptions::default() };
+    let (written, _, error) = dedup(options, &locs);
+    assert_eq!(written, ["http://www.example.com/a", "http://www.example.com/b", "http://www.example.com/b#top"]);
+    match error {
+        Some(Error::DuplicateLoc { index: 2, ref loc }) => assert_eq!(loc, "http://www.example.com/a"),
+        other => panic!("unexpected error {:?}", other),
+    }
+    assert_eq!(error.unwrap().to_string(), "Entry 2 with the duplicate loc http://www.example.com/a");
+}
+
+/// Locations and lastmods of the index of `files` on `base`.
+fn index_of_files(base: &str, files: &[&str]) -> Vec<(String, LastMod)> {
+    let files = files.iter().enumerate().map(|(i, file)| {
+        let modified = if i == 0 { None } else { Some(UNIX_EPOCH + Duration::from_secs(86400)) };
+        (PathBuf::from(file), modified)
+    });
+    let index_writer = SiteMapIndexWriter::from_files(SiteMapWriter::new(Vec::new()), &Url::parse(base).unwrap(), files)
+        .expect("write index");
+    let output = index_writer.end().expect("end index");
+    SiteMapReader::new(&output[..])
+        .map(|event| match event.expect("valid index") {
+            SiteMapEvent::SiteMap(sitemap) => (sitemap.loc.get_url().unwrap().to_string(), sitemap.lastmod),
+            other => panic!("unexpected event {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn test_index_from_files() {
+    let files = ["out/sitemap-1.xml.gz", "sitemap 2.xml", "plan-été.xml", "a#b?.xml"];
+    let expected = ["sitemap-1.xml.gz", "sitemap%202.xml", "plan-%C3%A9t%C3%A9.xml", "a%23b%3F.xml"];
+    let day = LastMod::from(UNIX_EPOCH + Duration::from_secs(86400));
+    for base in ["http://www.example.com/sitemaps", "http://www.example.com/sitemaps/", "http://www.example.com/sitemaps/?a=1#b"] {
+        let index = index_of_files(base, &files);
+        let locs: Vec<&str> = index.iter().map(|(loc, _)| loc.as_str()).collect();
+        let lastmods: Vec<&LastMod> = index.iter().map(|(_, lastmod)| lastmod).collect();
+        let expected: Vec<String> = expected.iter().map(|name| format!("http://www.example.com/sitemaps/{}", name)).collect();
+        assert_eq!(locs, expected);
+        assert_eq!(lastmods, [&LastMod::None, &day, &day, &day]);
+    }
+    for base in ["http://www.example.com", "http://www.example.com/"] {
+        assert_eq!(index_of_files(base, &["sitemap.xml"])[0].0, "http://www.example.com/sitemap.xml");
+    }
+
+    let base = Url::parse("http://www.example.com/").unwrap();
+    let files = vec![(PathBuf::from("/"), None)];
+    assert!(SiteMapIndexWriter::from_files(SiteMapWriter::new(Vec::new()), &base, files).is_err());
+    let base = Url::parse("mailto:someone@example.com").unwrap();
+    assert!(SiteMapIndexWriter::from_files(SiteMapWriter::new(Vec::new()), &base, Vec::new()).is_err());
+}
 
 #[test]
 fn test_validation() {
     assert!(UrlEntry::builder().build().is_err());
-    assert!(UrlEntry::builder().priority(2.0).is_err());
-    assert!(UrlEntry::builder().priority(-1.0).is_err());
+    assert!(UrlEntry::builder().loc("http://www.example.com/").priority(2.0).build().is_err());
+    assert!(UrlEntry::builder().loc("http://www.example.com/").priority(-1.0).build().is_err());
     assert!(SiteMapEntry::builder().build().is_err());
 }
+
+#[test]
+fn test_builder_loc_inputs() {
+    let expected = Url::parse("http://www.example.com/index.html").unwrap();
+    let from_str = UrlEntry::builder()
+        .loc("http://www.example.com/index.html")
+        .build()
+        .expect("valid");
+    let from_string = UrlEntry::builder()
+        .loc(String::from("http://www.example.com/index.html"))
+        .build()
+        .expect("valid");
+    let from_url = UrlEntry::builder()
+        .loc_url(expected.clone())
+        .build()
+        .expect("valid");
+    assert_eq!(from_str.loc, Location::Url(expected.clone()));
+    assert_eq!(from_string, from_str);
+    assert_eq!(from_url, from_str);
+
+    let sitemap = SiteMapEntry::builder()
+        .loc_url(expected.clone())
+        .build()
+        .expect("valid");
+    assert_eq!(sitemap.loc.get_url(), Some(expected));
+    let sitemap_from_str = SiteMapEntry::builder()
+        .loc("http://www.example.com/index.html")
+        .build()
+        .expect("valid");
+    assert_eq!(sitemap_from_str, sitemap);
+}
+
+#[test]
+fn test_builder_invalid_loc() {
+    let error = UrlEntry::builder().loc("/relative/path").build().unwrap_err();
+    assert_eq!(error.errors().len(), 1);
+    assert_eq!(error.errors()[0].field(), "loc");
+    assert_eq!(error.errors()[0].value(), Some("/relative/path"));
+    assert!(SiteMapEntry::builder().loc(String::from("not a url")).build().is_err());
+}
+
+#[test]
+fn test_builder_collects_all_errors() {
+    let error = UrlEntry::builder()
+        .priority(1.5)
+        .changefreq(ChangeFreq::Daily)
+        .build()
+        .unwrap_err();
+    let fields: Vec<&str> = error.errors().iter().map(|error| error.field()).collect();
+    assert_eq!(fields, vec!["priority", "loc"]);
+    assert_eq!(error.errors()[1].value(), None);
+    assert_eq!(error.to_string(),
+               "Invalid entry: priority '1.5': priority should be between 0 and 1; \
+                loc: location is required");
+
+    let error: Error = SiteMapEntry::builder().loc("bad url").build().unwrap_err().into();
+    match error {
+        Error::Build(ref build_error) => assert_eq!(build_error.errors().len(), 1),
+        ref other => panic!("unexpected error {:?}", other),
+    }
+}
+
+#[test]
+fn test_builder_from_strings() {
+    let url = UrlEntry::builder()
+        .loc("http://www.example.com/")
+        .lastmod_str("2016-07-08T09:10:11+02:00")
+        .changefreq_str("Weekly")
+        .priority_str(" 0.8 ")
+        .build()
+        .unwrap();
+    assert_eq!(url.lastmod,
+               LastMod::DateTime(DateTime::parse_from_rfc3339("2016-07-08T09:10:11+02:00").unwrap()));
+    assert_eq!(url.changefreq, ChangeFreq::Weekly);
+    assert_eq!(url.priority, Priority::Value(0.8));
+    let date = UrlEntry::builder().loc("http://www.example.com/").lastmod_str("2016-07-08").build().unwrap();
+    assert_eq!(date.lastmod, LastMod::Date(NaiveDate::from_ymd_opt(2016, 7, 8).unwrap()));
+    let sitemap = SiteMapEntry::builder()
+        .loc("http://www.example.com/sitemap.xml")
+        .lastmod_str("2016-07-08")
+        .build()
+        .unwrap();
+    assert_eq!(sitemap.lastmod, date.lastmod);
+}
+
+#[test]
+fn test_builder_from_invalid_strings() {
+    let error = UrlEntry::builder()
+        .loc("http://www.example.com/")
+        .lastmod_str("08/07/2016")
+        .changefreq_str("fortnightly")
+        .priority_str("0,8")
+        .build()
+        .unwrap_err();
+    let fields: Vec<(&str, Option<&str>)> = error.errors()
+        .iter()
+        .map(|error| (error.field(), error.value()))
+        .collect();
+    assert_eq!(fields,
+               vec![("lastmod", Some("08/07/2016")), ("changefreq", Some("fortnightly")), ("priority", Some("0,8"))]);
+    assert_eq!(error.errors()[1].to_string(), "changefreq 'fortnightly': unknown change frequency");
+    assert_eq!(error.errors()[2].to_string(), "priority '0,8': decimal separator should be '.'");
+
+    let error = UrlEntry::builder()
+        .lastmod_str("Fri, 08 Jul 2016 09:10:11 GMT")
+        .priority_str("1.5")
+        .build()
+        .unwrap_err();
+    assert_eq!(error.to_string(),
+               "Invalid entry: lastmod 'Fri, 08 Jul 2016 09:10:11 GMT': RFC 2822 time instead of W3C datetime; \
+                priority '1.5': priority should be between 0 and 1; loc: location is required");
+    let error = UrlEntry::builder().loc("http://www.example.com/").priority_str("high").build().unwrap_err();
+    assert_eq!(error.errors()[0].to_string(), "priority 'high': invalid float literal");
+    let error = SiteMapEntry::builder().loc("http://www.example.com/").lastmod_str("").build().unwrap_err();
+    assert_eq!(error.errors()[0].field(), "lastmod");
+}
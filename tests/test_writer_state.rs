@@ -0,0 +1,120 @@
+extern crate sitemap;
+
+use sitemap::structs::{SiteMapEntry, UrlEntry};
+use sitemap::writer::SiteMapWriter;
+use sitemap::Error;
+use std::io::{self, Write};
+
+/// Stream failing once `capacity` bytes have been written.
+struct FullWriter {
+    capacity: usize,
+}
+
+impl Write for FullWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.capacity == 0 {
+            return Err(io::Error::other("stream full"));
+        }
+        let written = buf.len().min(self.capacity);
+        self.capacity -= written;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn url(number: usize) -> UrlEntry {
+    UrlEntry::builder().loc(format!("http://www.example.com/{}", number)).build().unwrap()
+}
+
+fn sitemap(number: usize) -> SiteMapEntry {
+    SiteMapEntry::builder().loc(format!("http://www.example.com/sitemap{}.xml", number)).build().unwrap()
+}
+
+fn assert_state<T>(result: Result<T, Error>, message: &str) {
+    match result {
+        Err(Error::State(state)) => assert_eq!(state, message),
+        Err(error) => panic!("unexpected error {:?}", error),
+        Ok(_) => panic!("unexpected success"),
+    }
+}
+
+#[test]
+fn test_second_root_element() {
+    let ended = || SiteMapWriter::new(Vec::new()).start_urlset().unwrap().end_urlset().unwrap();
+    assert!(matches!(ended().start_urlset().map(|_| ()), Err(Error::DocumentEnded)));
+    assert!(matches!(ended().start_sitemapindex().map(|_| ()), Err(Error::DocumentEnded)));
+    let ended = || SiteMapWriter::new(Vec::new()).start_sitemapindex().unwrap().end_sitemapindex().unwrap();
+    assert!(matches!(ended().start_urlset().map(|_| ()), Err(Error::DocumentEnded)));
+    assert!(matches!(ended().start_sitemapindex().map(|_| ()), Err(Error::DocumentEnded)));
+
+    // the next document is written to another stream
+    let (first, next) = ended().rotate(Vec::new());
+    assert!(String::from_utf8(first).unwrap().contains("<sitemapindex"));
+    assert!(next.start_urlset().is_ok());
+}
+
+#[test]
+fn test_element_outside_root() {
+    let mut writer = SiteMapWriter::new(Vec::new());
+    assert_state(writer.write_content_element("loc", "http://www.example.com/"),
+                 "write_content_element() called outside a root element");
+    let mut urlset = writer.start_urlset().unwrap();
+    urlset.url(url(0)).unwrap();
+    let mut writer = urlset.end_urlset().unwrap();
+    assert_state(writer.write_content_element("loc", "http://www.example.com/"),
+                 "write_content_element() called outside a root element");
+    assert!(String::from_utf8(writer.into_inner()).unwrap().ends_with("</urlset>"));
+}
+
+#[test]
+fn test_root_changes_after_first_url() {
+    let mut urlset = SiteMapWriter::new(Vec::new()).start_urlset().unwrap();
+    urlset.url(url(0)).unwrap();
+    assert_state(urlset.namespace("vendor", "http://www.example.com/vendor").map(|_| ()),
+                 "namespace declared after the first url");
+    let mut urlset = SiteMapWriter::new(Vec::new()).start_urlset().unwrap();
+    urlset.url(url(0)).unwrap();
+    assert_state(urlset.attribute("version", "1").map(|_| ()), "attribute added after the first url");
+}
+
+#[test]
+fn test_urlset_after_failed_write() {
+    let mut urlset = SiteMapWriter::new(FullWriter { capacity: 1000 }).start_urlset().unwrap();
+    let mut number = 0;
+    let error = loop {
+        if let Err(error) = urlset.url(url(number)) {
+            break error;
+        }
+        number += 1;
+    };
+    assert!(matches!(error, Error::Io(_)), "{:?}", error);
+    assert_state(urlset.url(url(number + 1)), "url() called after a failed write");
+    // an invalid url is not written, the state is checked first
+    assert_state(urlset.url(UrlEntry::new()), "url() called after a failed write");
+    assert_state(urlset.end(), "end() called after a failed write");
+
+    // failing on the root element
+    let mut urlset = SiteMapWriter::new(FullWriter { capacity: 0 }).start_urlset().unwrap();
+    assert!(urlset.url(url(0)).is_err());
+    assert_state(urlset.end_urlset(), "end() called after a failed write");
+}
+
+#[test]
+fn test_sitemapindex_after_failed_write() {
+    let mut index = SiteMapWriter::new(FullWriter { capacity: 1000 }).start_sitemapindex().unwrap();
+    let mut number = 0;
+    while index.sitemap(sitemap(number)).is_ok() {
+        number += 1;
+    }
+    assert_state(index.sitemap(sitemap(number + 1)), "sitemap() called after a failed write");
+    assert_state(index.end_sitemapindex(), "end() called after a failed write");
+
+    // errors before writing keep the writer usable
+    let mut index = SiteMapWriter::new(Vec::new()).start_sitemapindex().unwrap();
+    assert!(matches!(index.sitemap(SiteMapEntry::new()), Err(Error::MissingLoc { index: 0 })));
+    index.sitemap(sitemap(0)).unwrap();
+    assert!(index.end().is_ok());
+}